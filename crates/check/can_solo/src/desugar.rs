@@ -441,11 +441,14 @@ fn desugar_value_def<'a>(
         }
         Expect {
             condition,
+            message,
             preceding_comment,
         } => {
             let desugared_condition = &*env.arena.alloc(desugar_expr(env, scope, condition));
+            let desugared_message = message.map(|message| desugar_expr(env, scope, message));
             Expect {
                 condition: desugared_condition,
+                message: desugared_message,
                 preceding_comment: *preceding_comment,
             }
         }
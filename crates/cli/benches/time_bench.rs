@@ -1,7 +1,8 @@
 use std::time::Duration;
 
 use cli_test_utils::bench_utils::{
-    bench_cfold, bench_deriv, bench_nqueens, bench_quicksort, bench_rbtree_ck,
+    bench_cfold, bench_deriv, bench_drop_lists, bench_nqueens, bench_quicksort, bench_rbtree_ck,
+    bench_sort_records,
 };
 use criterion::{measurement::WallTime, BenchmarkGroup, Criterion, SamplingMode};
 
@@ -30,7 +31,9 @@ fn bench_group_wall_time(c: &mut Criterion) {
         bench_deriv,     // nest deriv 8 f
         bench_rbtree_ck, // ms = makeMap 5 80000
         // bench_rbtree_delete, // m = makeMap 100000
-        bench_quicksort, // list size 10000
+        bench_quicksort,    // list size 10000
+        bench_sort_records, // 100000 records
+        bench_drop_lists,   // 100000 rounds
     ];
 
     for bench_func in bench_funcs.iter() {
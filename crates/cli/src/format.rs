@@ -305,14 +305,22 @@ pub struct TypeProblem {
     pub position: LineColumn,
 }
 
-pub fn annotate_file(arena: &Bump, file: PathBuf) -> Result<(), AnnotationProblem> {
+pub fn annotate_file(
+    arena: &Bump,
+    file: PathBuf,
+    render: RenderTarget,
+) -> Result<(), AnnotationProblem> {
     let load_config = LoadConfig {
         target: Target::default(),
         function_kind: FunctionKind::from_env(),
-        render: RenderTarget::ColorTerminal,
+        render,
         palette: DEFAULT_PALETTE,
         threading: Threading::AllAvailable,
         exec_mode: ExecutionMode::Check,
+        expect_filter: None,
+        finalizers: Vec::new(),
+        types_cache_dir: None,
+        fold_constants: true,
     };
 
     let mut loaded = roc_load::load_and_typecheck(
@@ -463,6 +471,7 @@ pub fn annotation_edit(
 mod tests {
     use super::*;
     use indoc::indoc;
+    use roc_reporting::report::ColorChoice;
     use std::fs::{read_to_string, File};
     use std::io::Write;
     use tempfile::{tempdir, TempDir};
@@ -569,7 +578,11 @@ main =
         let file_path = setup_test_file(dir.path(), "before.roc", &before);
 
         let arena = Bump::new();
-        let result = annotate_file(&arena, file_path.clone());
+        let result = annotate_file(
+            &arena,
+            file_path.clone(),
+            RenderTarget::ColorTerminal(ColorChoice::Always),
+        );
         result.unwrap();
 
         let annotated = read_to_string(file_path).unwrap();
@@ -9,6 +9,7 @@ use clap::{
     Command,
 };
 use roc_build::link::{LinkType, LinkingStrategy};
+use roc_build::object_symbols::ObjectSymbols;
 use roc_build::program::{
     handle_error_module, handle_loading_problem, standard_load_config, BuildFileError,
     BuildOrdering, BuiltFile, CodeGenBackend, CodeGenOptions, DEFAULT_ROC_FILENAME,
@@ -18,6 +19,7 @@ use roc_collections::MutMap;
 use roc_error_macros::{internal_error, user_error};
 use roc_gen_dev::AssemblyBackendMode;
 use roc_gen_llvm::llvm::build::LlvmBackendMode;
+use roc_gen_llvm::llvm::pgo::PgoMode;
 use roc_load::{ExpectMetadata, Threading};
 #[cfg(not(windows))]
 use roc_module::symbol::ModuleId;
@@ -26,7 +28,8 @@ use roc_packaging::cache::RocCacheDir;
 use roc_packaging::tarball::Compression;
 #[cfg(not(windows))]
 use roc_reporting::report::ANSI_STYLE_CODES;
-use roc_target::{Architecture, Target};
+use roc_reporting::report::{ColorChoice, RenderTarget};
+use roc_target::{Architecture, OperatingSystem, Target};
 use std::env;
 use std::ffi::{CString, OsStr, OsString};
 use std::io;
@@ -42,10 +45,12 @@ use strum::IntoEnumIterator;
 use tempfile::TempDir;
 
 mod format;
+mod watch;
 pub use format::{
     annotate_file, annotation_edit, annotation_edits, format_files, format_src, AnnotationProblem,
     FormatMode,
 };
+pub use watch::watch;
 
 pub const CMD_BUILD: &str = "build";
 pub const CMD_RUN: &str = "run";
@@ -58,11 +63,14 @@ pub const CMD_FORMAT: &str = "format";
 pub const CMD_FORMAT_ANNOTATE: &str = "annotate";
 pub const CMD_TEST: &str = "test";
 pub const CMD_GLUE: &str = "glue";
+pub const CMD_GEN_HEADER: &str = "gen-header";
 pub const CMD_PREPROCESS_HOST: &str = "preprocess-host";
 pub const CMD_LICENSES: &str = "licenses";
 
 pub const FLAG_EMIT_LLVM_IR: &str = "emit-llvm-ir";
+pub const FLAG_EMIT: &str = "emit";
 pub const FLAG_PROFILING: &str = "profiling";
+pub const FLAG_DEBUG: &str = "debug";
 pub const FLAG_BUNDLE: &str = "bundle";
 pub const FLAG_DEV: &str = "dev";
 pub const FLAG_OPTIMIZE: &str = "optimize";
@@ -70,21 +78,49 @@ pub const FLAG_MAX_THREADS: &str = "max-threads";
 pub const FLAG_OPT_SIZE: &str = "opt-size";
 pub const FLAG_LIB: &str = "lib";
 pub const FLAG_NO_LINK: &str = "no-link";
+pub const FLAG_EMIT_OBJECT: &str = "emit-object";
 pub const FLAG_TARGET: &str = "target";
 pub const FLAG_TIME: &str = "time";
 pub const FLAG_VERBOSE: &str = "verbose";
 pub const FLAG_NO_COLOR: &str = "no-color";
+pub const FLAG_COLOR: &str = "color";
 pub const FLAG_NO_HEADER: &str = "no-header";
 pub const FLAG_LINKER: &str = "linker";
 pub const FLAG_BUILD_HOST: &str = "build-host";
+pub const FLAG_NO_CACHE: &str = "no-cache";
 pub const FLAG_SUPPRESS_BUILD_HOST_WARNING: &str = "suppress-build-host-warning";
 pub const FLAG_CHECK: &str = "check";
 pub const FLAG_STDIN: &str = "stdin";
 pub const FLAG_STDOUT: &str = "stdout";
 pub const FLAG_WASM_STACK_SIZE_KB: &str = "wasm-stack-size-kb";
+pub const FLAG_WASM_BULK_MEMORY: &str = "wasm-bulk-memory";
+pub const FLAG_WASM_EXCEPTIONS: &str = "wasm-exceptions";
+pub const FLAG_MAX_STACK_FRAME: &str = "max-stack-frame";
+pub const FLAG_SBF_COMPUTE_BUDGET: &str = "sbf-compute-budget";
+pub const FLAG_SBF_STACK_LIMIT: &str = "sbf-stack-limit";
+pub const FLAG_DATA_SECTION: &str = "data-section";
+pub const FLAG_FINALIZER: &str = "finalizer";
+pub const FLAG_HOST_PANIC: &str = "host-panic";
+pub const FLAG_SANITIZE: &str = "sanitize";
+pub const FLAG_CHECK_ALIGNMENT: &str = "check-alignment";
+pub const FLAG_CFI: &str = "cfi";
+pub const FLAG_INLINE_THRESHOLD: &str = "inline-threshold";
+pub const FLAG_OPTIMIZE_STACK_ALLOC: &str = "optimize-stack-alloc";
+pub const FLAG_POOL_ALLOC: &str = "pool-alloc";
+pub const FLAG_VALGRIND: &str = "valgrind";
+pub const FLAG_OVERFLOW: &str = "overflow";
+pub const FLAG_STACK_SIZE: &str = "stack-size";
+pub const FLAG_PGO_INSTRUMENT: &str = "pgo-instrument";
+pub const FLAG_PGO_USE: &str = "pgo-use";
+pub const FLAG_LTO: &str = "lto";
+pub const FLAG_PIE: &str = "pie";
+pub const FLAG_NO_PIE: &str = "no-pie";
 pub const FLAG_OUTPUT: &str = "output";
 pub const FLAG_FUZZ: &str = "fuzz";
+pub const FLAG_JSON_ERRORS: &str = "json-errors";
 pub const FLAG_MAIN: &str = "main";
+pub const FLAG_FILTER: &str = "filter";
+pub const FLAG_WATCH: &str = "watch";
 pub const ROC_FILE: &str = "ROC_FILE";
 pub const GLUE_DIR: &str = "GLUE_DIR";
 pub const GLUE_SPEC: &str = "GLUE_SPEC";
@@ -98,6 +134,7 @@ pub const FLAG_DOCS_ROOT: &str = "root-dir";
 
 pub const VERSION: &str = env!("ROC_VERSION");
 const DEFAULT_GENERATED_DOCS_DIR: &str = "generated-docs";
+const DEFAULT_GENERATED_HEADER: &str = "roc_app.h";
 
 pub fn build_app() -> Command {
     let flag_optimize = Arg::new(FLAG_OPTIMIZE)
@@ -108,7 +145,8 @@ pub fn build_app() -> Command {
 
     let flag_max_threads = Arg::new(FLAG_MAX_THREADS)
         .long(FLAG_MAX_THREADS)
-        .help("Limit the number of threads (and hence cores) used during compilation")
+        .visible_alias("jobs")
+        .help("Limit the number of threads (and hence cores) used during compilation\n(Defaults to the number of cores available.)")
         .value_parser(value_parser!(usize))
         .required(false);
 
@@ -130,12 +168,27 @@ pub fn build_app() -> Command {
         .action(ArgAction::SetTrue)
         .required(false);
 
+    let flag_emit = Arg::new(FLAG_EMIT)
+        .long(FLAG_EMIT)
+        .help("Write intermediate representations of the program next to the .roc file, e.g. --emit llvm-ir,mono\n(llvm-ir writes a .ll file and is the same as --emit-llvm-ir, llvm-bc a .bc file, mono a .mono file with the specialized procedures, and asm a .s file with the assembly for the target. Everything but mono is written after optimization, and needs the LLVM backend.)")
+        .value_parser(["llvm-ir", "llvm-bc", "mono", "asm"])
+        .value_delimiter(',')
+        .action(ArgAction::Append)
+        .required(false);
+
     let flag_profiling = Arg::new(FLAG_PROFILING)
         .long(FLAG_PROFILING)
         .help("Keep debug info in the final generated program even in optimized builds")
         .action(ArgAction::SetTrue)
         .required(false);
 
+    let flag_debug = Arg::new(FLAG_DEBUG)
+        .long(FLAG_DEBUG)
+        .visible_alias("debug-info")
+        .help("Emit debug info that maps the generated code back to the .roc source, for use with gdb or lldb\n(Uses the legacy linker unless --linker is given, because the surgical linker drops the debug info of the app.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
     let flag_time = Arg::new(FLAG_TIME)
         .long(FLAG_TIME)
         .help("Print detailed compilation time information")
@@ -154,6 +207,12 @@ pub fn build_app() -> Command {
         .action(ArgAction::SetTrue)
         .required(false);
 
+    let flag_no_cache = Arg::new(FLAG_NO_CACHE)
+        .long(FLAG_NO_CACHE)
+        .help("Build without the cache in the roc cache dir\n(The cache keeps preprocessed hosts by the contents of the host, the object code of an app that was built with the LLVM backend by its monomorphized code, sources and options, and the solved types of a module by its source and the modules it imports.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
     let flag_suppress_build_host_warning = Arg::new(FLAG_SUPPRESS_BUILD_HOST_WARNING)
         .long(FLAG_SUPPRESS_BUILD_HOST_WARNING)
         .help("WARNING: platforms are responsible for building hosts, this flag will be removed when internal test platforms have a build script")
@@ -166,12 +225,166 @@ pub fn build_app() -> Command {
         .value_parser(value_parser!(u32))
         .required(false);
 
+    let flag_wasm_bulk_memory = Arg::new(FLAG_WASM_BULK_MEMORY)
+        .long(FLAG_WASM_BULK_MEMORY)
+        .help("Copy and fill memory with the bulk memory instructions memory.copy and memory.fill for wasm32 target\n(This only applies when --dev also provided. Most runtimes support them since 2021.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_wasm_exceptions = Arg::new(FLAG_WASM_EXCEPTIONS)
+        .long(FLAG_WASM_EXCEPTIONS)
+        .help("Crash by throwing a WebAssembly exception for wasm32 target, which the functions the host calls catch and pass on to roc_panic\n(This only applies when --dev also provided. The runtime needs to support the exception handling proposal.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_max_stack_frame = Arg::new(FLAG_MAX_STACK_FRAME)
+        .long(FLAG_MAX_STACK_FRAME)
+        .help("Warn about functions whose stack frame is estimated to be larger than this many bytes\n(Defaults to 4096 for the sbf target, and no limit otherwise.)")
+        .value_parser(value_parser!(u32))
+        .required(false);
+
+    let flag_sbf_compute_budget = Arg::new(FLAG_SBF_COMPUTE_BUDGET)
+        .long(FLAG_SBF_COMPUTE_BUDGET)
+        .help("Warn when the program is estimated to use more than this many compute units, and list the functions that contribute the most\n(Only for the sbf target. The estimate counts every instruction once, so a loop costs as much as a single iteration.)")
+        .value_parser(value_parser!(u64))
+        .required(false);
+
+    let flag_sbf_stack_limit = Arg::new(FLAG_SBF_STACK_LIMIT)
+        .long(FLAG_SBF_STACK_LIMIT)
+        .help("Warn when the stack frames along the deepest chain of calls are estimated to take more than this many bytes together\n(Only for the sbf target. Defaults to 4096.)")
+        .value_parser(value_parser!(u32))
+        .required(false);
+
+    let flag_data_section = Arg::new(FLAG_DATA_SECTION)
+        .long(FLAG_DATA_SECTION)
+        .help("Place the constant data of a top-level definition in a section of the object file, given as <definition>=<section>, e.g. table=.rodata.roc_tables\n(Can be given more than once. Only string literals that are too long to be small strings, and lists of constant numbers, are stored as data.)")
+        .action(ArgAction::Append)
+        .required(false);
+
+    let flag_host_panic = Arg::new(FLAG_HOST_PANIC)
+        .long(FLAG_HOST_PANIC)
+        .help("Report panics through the host's roc_panic\n(By default, programs for the sbf target abort with the sol_panic_ syscall instead.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_sanitize = Arg::new(FLAG_SANITIZE)
+        .long(FLAG_SANITIZE)
+        .help("Instrument the generated code with a sanitizer\n(The host must be linked against the sanitizer runtime, e.g. by building it with -fsanitize=address.)")
+        .value_parser(["address"])
+        .required(false);
+
+    let flag_check_alignment = Arg::new(FLAG_CHECK_ALIGNMENT)
+        .long(FLAG_CHECK_ALIGNMENT)
+        .help("Check the alignment of every pointer the generated code loads from or stores to, and trap when it is misaligned\n(Outside of the sbf target, the host must link libc.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_cfi = Arg::new(FLAG_CFI)
+        .long(FLAG_CFI)
+        .help("Check that every call through a function pointer calls a function of the right type, and trap when it does not\n(Closures are called through a function pointer only when they are erased.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_inline_threshold = Arg::new(FLAG_INLINE_THRESHOLD)
+        .long(FLAG_INLINE_THRESHOLD)
+        .help("Set LLVM's inline threshold for calls to Roc functions\n(Higher values inline more. Builtins that are always or never inlined are not affected.)")
+        .value_parser(value_parser!(u32))
+        .required(false);
+
+    let flag_optimize_stack_alloc = Arg::new(FLAG_OPTIMIZE_STACK_ALLOC)
+        .long(FLAG_OPTIMIZE_STACK_ALLOC)
+        .help("Put list literals that never leave the function that creates them on the stack, instead of allocating them on the heap")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_pool_alloc = Arg::new(FLAG_POOL_ALLOC)
+        .long(FLAG_POOL_ALLOC)
+        .help("Allocate the tags of recursive unions, like the nodes of a tree, from pools of a few fixed sizes instead of with roc_alloc\n(The pools keep their memory until the program exits, and are not thread safe. The host must not free these tags itself.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_valgrind = Arg::new(FLAG_VALGRIND)
+        .long(FLAG_VALGRIND)
+        .help("Tell Valgrind about the values that are allocated from pools, or from the arenas of with_arena!, so that memcheck finds the ones that leak\n(Allocations with roc_alloc are tracked anyway. Outside of Valgrind, this only costs a few instructions per allocation.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_finalizer = Arg::new(FLAG_FINALIZER)
+        .long(FLAG_FINALIZER)
+        .help("Call a host function right before a value of an opaque type that wraps a Box is freed, given as <type>=<function>, e.g. File.File=closeFile\n(Can be given more than once. A type without a module name is looked up in the app. The function receives a pointer to the boxed value, with the C signature void function(void *data).)")
+        .action(ArgAction::Append)
+        .required(false);
+
+    let flag_overflow = Arg::new(FLAG_OVERFLOW)
+        .long(FLAG_OVERFLOW)
+        .help("Choose how integer overflow is reported\n(With `trap`, the program calls `__roc_overflow_trap` with a message that includes the source location of the overflow. Unless the platform defines it, that message goes to `roc_panic`.)")
+        .value_parser(["trap"])
+        .required(false);
+
+    let flag_stack_size = Arg::new(FLAG_STACK_SIZE)
+        .long(FLAG_STACK_SIZE)
+        .help("Link the executable with a stack of this many bytes, and check at the start of every function that the stack has not overflowed\n(Not for the sbf target. A function that starts below `__roc_stack_limit` calls `__roc_stack_overflow`, which goes to `roc_panic` unless the platform defines it. The limit is thread-local, and nothing is checked on a thread until the host sets it.)")
+        .value_parser(value_parser!(u32))
+        .required(false);
+
+    let flag_pgo_instrument = Arg::new(FLAG_PGO_INSTRUMENT)
+        .long(FLAG_PGO_INSTRUMENT)
+        .help("Count how often each branch is taken, for profile-guided optimization with --pgo-use\n(The host must be linked against LLVM's profile runtime, e.g. with clang -fprofile-instr-generate. The counts go to the file named by LLVM_PROFILE_FILE, and must be merged with llvm-profdata.)")
+        .action(ArgAction::SetTrue)
+        .conflicts_with(FLAG_PGO_USE)
+        .required(false);
+
+    let flag_pgo_use = Arg::new(FLAG_PGO_USE)
+        .long(FLAG_PGO_USE)
+        .help("Optimize with a profile collected by a program built with --pgo-instrument\n(The profile only applies to the same program, built with the same options.)")
+        .value_parser(value_parser!(PathBuf))
+        .required(false);
+
+    let flag_lto = Arg::new(FLAG_LTO)
+        .long(FLAG_LTO)
+        .help("Emit LLVM bitcode instead of an object file, so the linker can optimize the app together with the host\n(Only supported on Linux. Linking uses the legacy linker with ld.lld. With --no-link, the bitcode can be linked with e.g. clang -flto -fuse-ld=lld.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_watch = Arg::new(FLAG_WATCH)
+        .long(FLAG_WATCH)
+        .help("Rebuild and rerun whenever the app, a module it imports, or the platform changes\n(The program is run without standard input. Stop watching with Ctrl-C.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
+    let flag_pie = Arg::new(FLAG_PIE)
+        .long(FLAG_PIE)
+        .help("Generate position-independent code, and link a position-independent executable\n(This is the default. It undoes an earlier --no-pie.)")
+        .action(ArgAction::SetTrue)
+        .overrides_with(FLAG_NO_PIE)
+        .required(false);
+
+    let flag_no_pie = Arg::new(FLAG_NO_PIE)
+        .long(FLAG_NO_PIE)
+        .help("Generate code for a fixed address, and link an executable that is always loaded there\n(Only supported on Linux. Linking uses the legacy linker.)")
+        .action(ArgAction::SetTrue)
+        .overrides_with(FLAG_PIE)
+        .required(false);
+
     let flag_fuzz = Arg::new(FLAG_FUZZ)
         .long(FLAG_FUZZ)
         .help("Instrument the roc binary for fuzzing with roc-fuzz")
         .action(ArgAction::SetTrue)
         .required(false);
 
+    let flag_color = Arg::new(FLAG_COLOR)
+        .long(FLAG_COLOR)
+        .help("When to color the output with ANSI escape codes\n(auto colors it when printing to a terminal, unless the NO_COLOR environment variable is set. Setting CLICOLOR_FORCE colors it anyway.)")
+        .value_parser(["always", "auto", "never"])
+        .default_value("auto")
+        .global(true);
+
+    let flag_json_errors = Arg::new(FLAG_JSON_ERRORS)
+        .long(FLAG_JSON_ERRORS)
+        .help("Print errors and warnings as JSON, one object per line, for editors and other tools\n(The summary of how many were found is left out. The exit code is the same as without this flag.)")
+        .action(ArgAction::SetTrue)
+        .required(false);
+
     let flag_main = Arg::new(FLAG_MAIN)
         .long(FLAG_MAIN)
         .help("The .roc file of the main app/package module to resolve dependencies from")
@@ -185,7 +398,7 @@ pub fn build_app() -> Command {
         .default_value(DEFAULT_ROC_FILENAME);
 
     let args_for_app = Arg::new(ARGS_FOR_APP)
-        .help("Arguments to pass into the app being run\ne.g. `roc run -- arg1 arg2`")
+        .help("Arguments to pass into the app being run, everything after `--` is passed as is\ne.g. `roc run -- arg1 --arg2`")
         .value_parser(value_parser!(OsString))
         .num_args(0..)
         .allow_hyphen_values(true);
@@ -203,6 +416,7 @@ pub fn build_app() -> Command {
         .version(VERSION)
         .about("Run the given .roc file, if there are no compilation errors.\nYou can use one of the SUBCOMMANDS below to do something else!")
         .args_conflicts_with_subcommands(true)
+        .arg(flag_color)
         .subcommand(Command::new(CMD_BUILD)
             .about("Build a binary from the given .roc file, but don't run it")
             .arg(Arg::new(FLAG_OUTPUT)
@@ -216,13 +430,39 @@ pub fn build_app() -> Command {
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_emit_llvm_ir.clone())
+            .arg(flag_emit.clone())
             .arg(flag_profiling.clone())
+            .arg(flag_debug.clone())
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_build_host.clone())
+            .arg(flag_no_cache.clone())
             .arg(flag_suppress_build_host_warning.clone())
             .arg(flag_fuzz.clone())
+            .arg(flag_sanitize.clone())
+            .arg(flag_check_alignment.clone())
+            .arg(flag_cfi.clone())
+            .arg(flag_inline_threshold.clone())
+            .arg(flag_optimize_stack_alloc.clone())
+            .arg(flag_pool_alloc.clone())
+            .arg(flag_valgrind.clone())
+            .arg(flag_finalizer.clone())
+            .arg(flag_overflow.clone())
+            .arg(flag_stack_size.clone())
+            .arg(flag_pgo_instrument.clone())
+            .arg(flag_pgo_use.clone())
+            .arg(flag_lto.clone())
             .arg(flag_wasm_stack_size_kb)
+            .arg(flag_wasm_bulk_memory)
+            .arg(flag_wasm_exceptions)
+            .arg(flag_max_stack_frame)
+            .arg(flag_sbf_compute_budget)
+            .arg(flag_sbf_stack_limit)
+            .arg(flag_data_section)
+            .arg(flag_pie)
+            .arg(flag_no_pie)
+            .arg(flag_host_panic)
+            .arg(flag_json_errors.clone())
             .arg(
                 Arg::new(FLAG_TARGET)
                     .long(FLAG_TARGET)
@@ -234,7 +474,7 @@ pub fn build_app() -> Command {
             .arg(
                 Arg::new(FLAG_LIB)
                     .long(FLAG_LIB)
-                    .help("Build a C library instead of an executable")
+                    .help("Build a C library instead of an executable\n(only the functions the platform provides to its host are exported from it)")
                     .action(ArgAction::SetTrue)
                     .required(false),
             )
@@ -253,6 +493,14 @@ pub fn build_app() -> Command {
                     .action(ArgAction::SetTrue)
                     .required(false),
             )
+            .arg(
+                Arg::new(FLAG_EMIT_OBJECT)
+                    .long(FLAG_EMIT_OBJECT)
+                    .help("Output the app's relocatable object file for --target instead of linking it with the host, and list the symbols the host has to define and the entrypoints the app defines\n(Use this to link the app yourself, for example with solana-lld and --target=sbf.)")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with_all([FLAG_LIB, FLAG_NO_LINK])
+                    .required(false),
+            )
             .arg(
                 Arg::new(FLAG_VERBOSE)
                     .long(FLAG_VERBOSE)
@@ -276,12 +524,29 @@ pub fn build_app() -> Command {
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_emit_llvm_ir.clone())
+            .arg(flag_emit.clone())
             .arg(flag_profiling.clone())
+            .arg(flag_debug.clone())
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_build_host.clone())
+            .arg(flag_no_cache.clone())
             .arg(flag_suppress_build_host_warning.clone())
             .arg(flag_fuzz.clone())
+            .arg(flag_sanitize.clone())
+            .arg(flag_check_alignment.clone())
+            .arg(flag_cfi.clone())
+            .arg(flag_inline_threshold.clone())
+            .arg(flag_optimize_stack_alloc.clone())
+            .arg(flag_pool_alloc.clone())
+            .arg(flag_valgrind.clone())
+            .arg(flag_finalizer.clone())
+            .arg(flag_overflow.clone())
+            .arg(flag_stack_size.clone())
+            .arg(flag_pgo_instrument.clone())
+            .arg(flag_pgo_use.clone())
+            .arg(flag_lto.clone())
+            .arg(flag_watch.clone())
             .arg(
                 Arg::new(FLAG_VERBOSE)
                     .long(FLAG_VERBOSE)
@@ -289,6 +554,13 @@ pub fn build_app() -> Command {
                     .action(ArgAction::SetTrue)
                     .required(false)
             )
+            .arg(
+                Arg::new(FLAG_FILTER)
+                    .long(FLAG_FILTER)
+                    .help("Only run the top-level `expect`s whose enclosing definition or module name contains this\n(The enclosing definition of an `expect` is the last top-level definition before it.)")
+                    .value_parser(value_parser!(String))
+                    .required(false)
+            )
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file to test")
@@ -323,12 +595,28 @@ pub fn build_app() -> Command {
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_emit_llvm_ir.clone())
+            .arg(flag_emit.clone())
             .arg(flag_profiling.clone())
+            .arg(flag_debug.clone())
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_build_host.clone())
+            .arg(flag_no_cache.clone())
             .arg(flag_suppress_build_host_warning.clone())
             .arg(flag_fuzz.clone())
+            .arg(flag_sanitize.clone())
+            .arg(flag_check_alignment.clone())
+            .arg(flag_cfi.clone())
+            .arg(flag_inline_threshold.clone())
+            .arg(flag_optimize_stack_alloc.clone())
+            .arg(flag_pool_alloc.clone())
+            .arg(flag_valgrind.clone())
+            .arg(flag_finalizer.clone())
+            .arg(flag_overflow.clone())
+            .arg(flag_stack_size.clone())
+            .arg(flag_pgo_instrument.clone())
+            .arg(flag_pgo_use.clone())
+            .arg(flag_lto.clone())
             .arg(roc_file_to_run.clone())
             .arg(args_for_app.clone().last(true))
         )
@@ -339,12 +627,29 @@ pub fn build_app() -> Command {
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_emit_llvm_ir.clone())
+            .arg(flag_emit.clone())
             .arg(flag_profiling.clone())
+            .arg(flag_debug.clone())
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_build_host.clone())
+            .arg(flag_no_cache.clone())
             .arg(flag_suppress_build_host_warning.clone())
             .arg(flag_fuzz.clone())
+            .arg(flag_sanitize.clone())
+            .arg(flag_check_alignment.clone())
+            .arg(flag_cfi.clone())
+            .arg(flag_inline_threshold.clone())
+            .arg(flag_optimize_stack_alloc.clone())
+            .arg(flag_pool_alloc.clone())
+            .arg(flag_valgrind.clone())
+            .arg(flag_finalizer.clone())
+            .arg(flag_overflow.clone())
+            .arg(flag_stack_size.clone())
+            .arg(flag_pgo_instrument.clone())
+            .arg(flag_pgo_use.clone())
+            .arg(flag_lto.clone())
+            .arg(flag_watch)
             .arg(roc_file_to_run.clone())
             .arg(args_for_app.clone().last(true))
         )
@@ -405,6 +710,8 @@ pub fn build_app() -> Command {
             .arg(flag_main.clone())
             .arg(flag_time.clone())
             .arg(flag_max_threads.clone())
+            .arg(flag_json_errors)
+            .arg(flag_no_cache.clone())
             .arg(
                 Arg::new(ROC_FILE)
                     .help("The .roc file to check")
@@ -455,6 +762,23 @@ pub fn build_app() -> Command {
             )
             .arg(flag_linker.clone())
         )
+        .subcommand(Command::new(CMD_GEN_HEADER)
+            .about("Generate a C header with the types and functions a platform exposes to its host")
+            .arg(Arg::new(FLAG_OUTPUT)
+                .long(FLAG_OUTPUT)
+                .help("The path of the header to write")
+                .value_parser(value_parser!(PathBuf))
+                .required(false)
+                .default_value(DEFAULT_GENERATED_HEADER),
+            )
+            .arg(
+                Arg::new(ROC_FILE)
+                    .help("The .roc file of the platform whose exposed types should be declared")
+                    .value_parser(value_parser!(PathBuf))
+                    .required(false)
+                    .default_value(DEFAULT_ROC_FILENAME)
+            )
+        )
         .subcommand(Command::new(CMD_PREPROCESS_HOST)
             .about("Runs the surgical linker preprocessor to generate `.rh` and `.rm` files.")
             .arg(
@@ -496,12 +820,28 @@ pub fn build_app() -> Command {
         .arg(flag_opt_size)
         .arg(flag_dev)
         .arg(flag_emit_llvm_ir)
+        .arg(flag_emit)
         .arg(flag_profiling)
+        .arg(flag_debug)
         .arg(flag_time)
         .arg(flag_linker)
         .arg(flag_build_host)
+        .arg(flag_no_cache)
         .arg(flag_suppress_build_host_warning)
         .arg(flag_fuzz)
+        .arg(flag_sanitize)
+        .arg(flag_check_alignment)
+        .arg(flag_cfi)
+        .arg(flag_inline_threshold)
+        .arg(flag_optimize_stack_alloc)
+        .arg(flag_pool_alloc)
+        .arg(flag_valgrind)
+        .arg(flag_finalizer)
+        .arg(flag_overflow)
+        .arg(flag_stack_size)
+        .arg(flag_pgo_instrument)
+        .arg(flag_pgo_use)
+        .arg(flag_lto)
         .arg(roc_file_to_run)
         .arg(args_for_app.trailing_var_arg(true))
 }
@@ -513,6 +853,41 @@ pub enum BuildConfig {
     BuildAndRunIfNoErrors,
 }
 
+pub fn color_choice_from_flags(matches: &ArgMatches) -> ColorChoice {
+    match matches.get_one::<String>(FLAG_COLOR).map(String::as_str) {
+        Some("always") => ColorChoice::Always,
+        Some("never") => ColorChoice::Never,
+        _ => ColorChoice::Auto,
+    }
+}
+
+/// The rule between the compiler's output and the output of the program it runs
+fn horizontal_rule(has_color: bool) -> String {
+    if has_color {
+        format!("\x1B[36m{}\x1B[39m", "─".repeat(80))
+    } else {
+        "─".repeat(80)
+    }
+}
+
+fn finalizers_from_flags(matches: &ArgMatches) -> Vec<(String, String)> {
+    matches
+        .try_get_many::<String>(FLAG_FINALIZER)
+        .ok()
+        .flatten()
+        .into_iter()
+        .flatten()
+        .map(|arg| match arg.split_once('=') {
+            Some((type_name, finalizer)) if !type_name.is_empty() && !finalizer.is_empty() => {
+                (type_name.to_string(), finalizer.to_string())
+            }
+            _ => user_error!(
+                "--{FLAG_FINALIZER} takes an opaque type and a function, like File.File=closeFile, not {arg}."
+            ),
+        })
+        .collect()
+}
+
 fn opt_level_from_flags(matches: &ArgMatches) -> OptLevel {
     match (
         matches.get_flag(FLAG_OPTIMIZE),
@@ -549,6 +924,7 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
     let start_time = Instant::now();
     let arena = Bump::new();
     let opt_level = opt_level_from_flags(matches);
+    let render = RenderTarget::ColorTerminal(color_choice_from_flags(matches));
 
     let threading = match matches.get_one::<usize>(FLAG_MAX_THREADS) {
         None => Threading::AllAvailable,
@@ -557,6 +933,9 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
         Some(n) => Threading::AtMost(*n),
     };
 
+    let expect_filter = matches.get_one::<String>(FLAG_FILTER).cloned();
+    let finalizers = finalizers_from_flags(matches);
+
     let paths: Vec<_> = matches.get_many::<PathBuf>(ROC_FILE).unwrap().collect();
 
     let paths: Vec<_> = {
@@ -591,6 +970,7 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
 
     let mut all_files_total_failed_count = 0;
     let mut all_files_total_passed_count = 0;
+    let mut all_files_total_filtered_out_count = 0;
 
     for path in paths.iter() {
         let arena = &arena;
@@ -602,11 +982,14 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
         let load_config = LoadConfig {
             target,
             function_kind,
-            // TODO: expose this from CLI?
-            render: roc_reporting::report::RenderTarget::ColorTerminal,
+            render,
             palette: roc_reporting::report::DEFAULT_PALETTE,
             threading,
             exec_mode: ExecutionMode::Test,
+            expect_filter: expect_filter.clone(),
+            finalizers: finalizers.clone(),
+            types_cache_dir: None,
+            fold_constants: true,
         };
         let load_result = roc_load::load_and_monomorphize(
             arena,
@@ -622,12 +1005,17 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
                 return handle_loading_problem(problem);
             }
             Err(LoadMonomorphizedError::ErrorModule(module)) => {
-                return handle_error_module(module, start_time.elapsed());
+                watch::report_sources(watch::module_paths(&module.sources), None);
+
+                return handle_error_module(module, start_time.elapsed(), render);
             }
         };
-        let problems = report_problems_monomorphized(&mut loaded);
+        watch::report_sources(watch::module_paths(&loaded.sources), None);
+
+        let problems = report_problems_monomorphized(&mut loaded, render);
 
         let mut expectations = std::mem::take(&mut loaded.expectations);
+        let filtered_out_count = loaded.filtered_out_expects;
 
         let interns = loaded.interns.clone();
         let sources = loaded.sources.clone();
@@ -649,8 +1037,11 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
                 "if there were errors, we would have already exited."
             );
             if problems.warnings > 0 {
-                problems.print_error_warning_count(start_time.elapsed());
-                println!(".\n\nRunning tests…\n\n\x1B[36m{}\x1B[39m", "─".repeat(80));
+                problems.print_error_warning_count(start_time.elapsed(), render.has_color());
+                println!(
+                    ".\n\nRunning tests…\n\n{}",
+                    horizontal_rule(render.has_color())
+                );
             }
         }
 
@@ -673,7 +1064,7 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
 
             let (failed_count, passed_count) = roc_repl_expect::run::run_toplevel_expects(
                 &mut writer,
-                roc_reporting::report::RenderTarget::ColorTerminal,
+                render,
                 arena,
                 interns,
                 &global_layout_interner,
@@ -699,23 +1090,40 @@ pub fn test(matches: &ArgMatches, target: Target) -> io::Result<i32> {
         let total_duration = start_time.elapsed();
         all_files_total_failed_count += total_failed_count;
         all_files_total_passed_count += total_passed_count;
+        all_files_total_filtered_out_count += filtered_out_count;
         if total_failed_count == 0 && total_passed_count == 0 {
             // Only report no expectations found once.
             continue;
         } else if matches.get_flag(FLAG_VERBOSE) {
             println!("Compiled in {} ms.", compilation_duration.as_millis());
             for module_test_results in results_by_module {
-                print_test_results(module_test_results, &sources);
+                print_test_results(module_test_results, &sources, render.has_color());
             }
         } else {
-            let test_summary_str =
-                test_summary(total_failed_count, total_passed_count, total_duration);
+            let test_summary_str = test_summary(
+                total_failed_count,
+                total_passed_count,
+                total_duration,
+                render.has_color(),
+            );
             println!("{test_summary_str}");
         }
+
+        if expect_filter.is_some() {
+            let ran_count = total_failed_count + total_passed_count;
+            let total_count = ran_count + filtered_out_count;
+
+            println!("ran {ran_count} of {total_count} expects (filtered)");
+        }
     }
     if all_files_total_failed_count == 0 && all_files_total_passed_count == 0 {
         // TODO print this in a more nicely formatted way!
-        println!("No expectations were found.");
+        match &expect_filter {
+            Some(filter) if all_files_total_filtered_out_count > 0 => {
+                println!("No expectations matched the filter \"{filter}\".")
+            }
+            _ => println!("No expectations were found."),
+        }
 
         // If no tests ran, treat that as an error. This is perhaps
         // briefly annoying at the very beginning of a project when
@@ -757,6 +1165,7 @@ fn find_all_roc_files(path: &PathBuf, flatten_paths: &mut Vec<PathBuf>) {
 fn print_test_results(
     module_test_results: ModuleTestResults,
     sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    has_color: bool,
 ) {
     let ModuleTestResults {
         module_id,
@@ -765,7 +1174,7 @@ fn print_test_results(
         tests_duration,
     } = module_test_results;
 
-    let test_summary_str = test_summary(failed_count, passed_count, tests_duration);
+    let test_summary_str = test_summary(failed_count, passed_count, tests_duration, has_color);
 
     let (module_path, _) = sources.get(&module_id).unwrap();
     let module_name = module_path.file_name().unwrap().to_str().unwrap();
@@ -774,14 +1183,25 @@ fn print_test_results(
 }
 
 #[cfg(not(windows))]
-fn test_summary(failed_count: usize, passed_count: usize, tests_duration: Duration) -> String {
-    let failed_color = if failed_count == 0 {
-        ANSI_STYLE_CODES.green
-    } else {
-        ANSI_STYLE_CODES.red
+fn test_summary(
+    failed_count: usize,
+    passed_count: usize,
+    tests_duration: Duration,
+    has_color: bool,
+) -> String {
+    let (failed_color, passed_color, reset) = match has_color {
+        true if failed_count == 0 => (
+            ANSI_STYLE_CODES.green,
+            ANSI_STYLE_CODES.green,
+            ANSI_STYLE_CODES.reset,
+        ),
+        true => (
+            ANSI_STYLE_CODES.red,
+            ANSI_STYLE_CODES.green,
+            ANSI_STYLE_CODES.reset,
+        ),
+        false => ("", "", ""),
     };
-    let passed_color = ANSI_STYLE_CODES.green;
-    let reset = ANSI_STYLE_CODES.reset;
 
     format!(
         "{failed_color}{failed_count}{reset} failed and {passed_color}{passed_count}{reset} passed in {} ms.",
@@ -954,12 +1374,50 @@ pub fn build(
         CodeGenBackend::Llvm(backend_mode)
     };
 
-    let emit_llvm_ir = matches.get_flag(FLAG_EMIT_LLVM_IR);
+    let emit: Vec<&str> = matches
+        .try_get_many::<String>(FLAG_EMIT)
+        .ok()
+        .flatten()
+        .into_iter()
+        .flatten()
+        .map(String::as_str)
+        .collect();
+
+    let emit_llvm_ir = matches.get_flag(FLAG_EMIT_LLVM_IR) || emit.contains(&"llvm-ir");
     if emit_llvm_ir && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
         user_error!("Cannot emit llvm ir while using a dev backend.");
     }
 
+    let emit_llvm_bc = emit.contains(&"llvm-bc");
+    if emit_llvm_bc && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot emit llvm bitcode while using a dev backend.");
+    }
+
+    let emit_asm = emit.contains(&"asm");
+    if emit_asm && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot emit assembly while using a dev backend.");
+    }
+
+    let emit_mono = emit.contains(&"mono");
+
+    let emit_object = matches
+        .try_get_one::<bool>(FLAG_EMIT_OBJECT)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+    if emit_object && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!(
+            "Cannot emit a relocatable object while using a dev backend, use --no-link instead."
+        );
+    }
+    if emit_object && target == Target::Wasm32 {
+        user_error!("The wasm32 app is LLVM bitcode rather than a relocatable object, use --no-link instead.");
+    }
+
+    let emit_source_debug_info = matches.get_flag(FLAG_DEBUG);
     let emit_debug_info = matches.get_flag(FLAG_PROFILING)
+        || emit_source_debug_info
         || matches!(opt_level, OptLevel::Development | OptLevel::Normal);
     let emit_timings = matches.get_flag(FLAG_TIME);
 
@@ -972,21 +1430,209 @@ pub fn build(
 
     let wasm_dev_backend = matches!(code_gen_backend, CodeGenBackend::Wasm);
 
+    let lto = matches
+        .try_get_one::<bool>(FLAG_LTO)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+    if lto && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot emit bitcode for link-time optimization while using a dev backend.");
+    }
+    if lto && link_type != LinkType::None && target.operating_system() != OperatingSystem::Linux {
+        user_error!("Link-time optimization is only supported on Linux, use --no-link to link the bitcode yourself.");
+    }
+    if lto && link_type != LinkType::None && !target.is_native() {
+        user_error!("Link-time optimization needs the linker of the system, which cannot link for another target. Use --no-link to link the bitcode yourself.");
+    }
+    if lto && emit_object {
+        user_error!("With --lto the app is LLVM bitcode rather than a relocatable object, use --no-link instead.");
+    }
+
+    let pie = !matches
+        .try_get_one::<bool>(FLAG_NO_PIE)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+    if !pie && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot generate position-dependent code while using a dev backend.");
+    }
+    if !pie && (target.operating_system() != OperatingSystem::Linux || target == Target::Sbf) {
+        user_error!("Position-dependent executables are only supported on Linux.");
+    }
+    if !pie && link_type == LinkType::Dylib {
+        user_error!(
+            "A library must be position-independent, so --no-pie cannot be used with --lib."
+        );
+    }
+
+    let stack_size: Option<u32> = matches
+        .try_get_one::<u32>(FLAG_STACK_SIZE)
+        .ok()
+        .flatten()
+        .copied();
+    if stack_size.is_some() && target == Target::Sbf {
+        user_error!("The stack of the sbf target has a fixed size, use --sbf-stack-limit to check how much of it the program uses.");
+    }
+    if stack_size.is_some() && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot check for stack overflow while using a dev backend.");
+    }
+    // the surgical linker keeps the stack size of the host
+    let link_stack_size = stack_size.is_some() && link_type == LinkType::Executable;
+
     let linking_strategy = if wasm_dev_backend {
         LinkingStrategy::Additive
+    } else if lto {
+        // the surgical linker copies machine code into the host, so it cannot take bitcode
+        if matches.get_one::<String>(FLAG_LINKER).map(String::as_str) == Some("surgical") {
+            user_error!(
+                "The surgical linker cannot link bitcode, use `--linker=legacy` with --lto."
+            );
+        }
+
+        LinkingStrategy::Legacy
+    } else if !pie {
+        // the surgical linker copies the app into a host that is itself position-independent
+        if matches.get_one::<String>(FLAG_LINKER).map(String::as_str) == Some("surgical") {
+            user_error!("The surgical linker only links position-independent executables, use `--linker=legacy` with --no-pie.");
+        }
+
+        LinkingStrategy::Legacy
+    } else if link_stack_size {
+        if matches.get_one::<String>(FLAG_LINKER).map(String::as_str) == Some("surgical") {
+            user_error!("The surgical linker cannot change the stack size of the host, use `--linker=legacy` with --stack-size.");
+        }
+
+        LinkingStrategy::Legacy
+    } else if emit_source_debug_info && matches.get_one::<String>(FLAG_LINKER).is_none() {
+        // the surgical linker only copies the code and data of the app into the host, so the
+        // debug info would be lost
+        LinkingStrategy::Legacy
     } else {
         default_linking_strategy(matches, link_type, target)
     };
 
+    if emit_source_debug_info && linking_strategy == LinkingStrategy::Surgical {
+        eprintln!(
+            "Warning: the surgical linker does not keep the debug info of the app, use `--linker=legacy` to debug it."
+        );
+    }
+
     // All hosts should be prebuilt, this flag keeps the rebuilding behvaiour
     // as required for internal tests
     let build_host = matches.get_flag(FLAG_BUILD_HOST);
     let suppress_build_host_warning = matches.get_flag(FLAG_SUPPRESS_BUILD_HOST_WARNING);
+    let use_cache = !matches.get_flag(FLAG_NO_CACHE);
 
     let fuzz = matches.get_flag(FLAG_FUZZ);
     if fuzz && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
         user_error!("Cannot instrument binary for fuzzing while using a dev backend.");
     }
+    if fuzz && lto {
+        user_error!("Cannot instrument binary for fuzzing while emitting bitcode for link-time optimization.");
+    }
+
+    let sanitize_address =
+        matches.get_one::<String>(FLAG_SANITIZE).map(String::as_str) == Some("address");
+    if sanitize_address && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot instrument binary with a sanitizer while using a dev backend.");
+    }
+
+    let check_alignment = matches
+        .try_get_one::<bool>(FLAG_CHECK_ALIGNMENT)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+    if check_alignment && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot check alignment while using a dev backend.");
+    }
+
+    let cfi = matches
+        .try_get_one::<bool>(FLAG_CFI)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+    if cfi && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot check indirect calls while using a dev backend.");
+    }
+    if cfi && matches!(target, Target::Sbf | Target::Wasm32) {
+        user_error!("Control-flow integrity checks need jump tables, which the {target} target does not support.");
+    }
+
+    let pool_alloc = matches
+        .try_get_one::<bool>(FLAG_POOL_ALLOC)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+    if pool_alloc && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot allocate from pools while using a dev backend.");
+    }
+    if pool_alloc && matches!(target, Target::Sbf) {
+        user_error!("The sbf target cannot allocate from pools, because a program cannot keep them between instructions.");
+    }
+
+    let valgrind = matches
+        .try_get_one::<bool>(FLAG_VALGRIND)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+    if valgrind && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot make Valgrind client requests while using a dev backend.");
+    }
+
+    let inline_threshold: Option<u32> = matches
+        .try_get_one::<u32>(FLAG_INLINE_THRESHOLD)
+        .ok()
+        .flatten()
+        .copied();
+    if inline_threshold.is_some() && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot set the inline threshold while using a dev backend.");
+    }
+
+    let optimize_stack_alloc = matches
+        .try_get_one::<bool>(FLAG_OPTIMIZE_STACK_ALLOC)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+    if optimize_stack_alloc && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot optimize stack allocations while using a dev backend.");
+    }
+
+    let overflow_trap =
+        matches.get_one::<String>(FLAG_OVERFLOW).map(String::as_str) == Some("trap");
+    if overflow_trap && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot trap on overflow while using a dev backend.");
+    }
+
+    let pgo_instrument = matches
+        .try_get_one::<bool>(FLAG_PGO_INSTRUMENT)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+    let pgo = if pgo_instrument {
+        Some(PgoMode::Instrument)
+    } else {
+        matches
+            .try_get_one::<PathBuf>(FLAG_PGO_USE)
+            .ok()
+            .flatten()
+            .map(|profile| PgoMode::Use(profile.to_owned()))
+    };
+    if pgo.is_some() && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot use profile-guided optimization while using a dev backend.");
+    }
+    if let Some(PgoMode::Use(profile)) = &pgo {
+        if !profile.is_file() {
+            user_error!("The profile {} does not exist.", profile.display());
+        }
+    }
 
     let wasm_dev_stack_bytes: Option<u32> = matches
         .try_get_one::<u32>(FLAG_WASM_STACK_SIZE_KB)
@@ -994,6 +1640,89 @@ pub fn build(
         .flatten()
         .map(|x| x * 1024);
 
+    let bulk_memory = matches
+        .try_get_one::<bool>(FLAG_WASM_BULK_MEMORY)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+    if bulk_memory && !matches!(code_gen_backend, CodeGenBackend::Wasm) {
+        user_error!(
+            "The bulk memory instructions are only used by the dev backend for the wasm32 target."
+        );
+    }
+
+    let wasm_exceptions = matches
+        .try_get_one::<bool>(FLAG_WASM_EXCEPTIONS)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+    if wasm_exceptions && !matches!(code_gen_backend, CodeGenBackend::Wasm) {
+        user_error!("Wasm exceptions are only used by the dev backend for the wasm32 target.");
+    }
+
+    let max_stack_frame: Option<u32> = matches
+        .try_get_one::<u32>(FLAG_MAX_STACK_FRAME)
+        .ok()
+        .flatten()
+        .copied();
+
+    let sbf_compute_budget: Option<u64> = matches
+        .try_get_one::<u64>(FLAG_SBF_COMPUTE_BUDGET)
+        .ok()
+        .flatten()
+        .copied();
+    if sbf_compute_budget.is_some() && target != Target::Sbf {
+        user_error!("The compute budget is only checked for the sbf target.");
+    }
+    if sbf_compute_budget.is_some() && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot check the compute budget while using a dev backend.");
+    }
+
+    let sbf_stack_limit: Option<u32> = matches
+        .try_get_one::<u32>(FLAG_SBF_STACK_LIMIT)
+        .ok()
+        .flatten()
+        .copied();
+    if sbf_stack_limit.is_some() && target != Target::Sbf {
+        user_error!("The stack limit is only checked for the sbf target.");
+    }
+    if sbf_stack_limit.is_some() && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot check the stack limit while using a dev backend.");
+    }
+
+    let data_sections: Vec<(String, String)> = matches
+        .try_get_many::<String>(FLAG_DATA_SECTION)
+        .ok()
+        .flatten()
+        .into_iter()
+        .flatten()
+        .map(|arg| match arg.split_once('=') {
+            Some((name, section)) if !name.is_empty() && !section.is_empty() => {
+                (name.to_string(), section.to_string())
+            }
+            _ => user_error!(
+                "--{FLAG_DATA_SECTION} takes a definition and a section, like table=.rodata.roc_tables, not {arg}."
+            ),
+        })
+        .collect();
+    if !data_sections.is_empty() && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot place data in sections while using a dev backend.");
+    }
+
+    let finalizers = finalizers_from_flags(matches);
+    if !finalizers.is_empty() && !matches!(code_gen_backend, CodeGenBackend::Llvm(_)) {
+        user_error!("Cannot call finalizers while using a dev backend.");
+    }
+
+    let host_panic = matches
+        .try_get_one::<bool>(FLAG_HOST_PANIC)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+
     let build_ordering = match config {
         BuildAndRunIfNoErrors | BuildOnly => BuildOrdering::BuildIfChecks,
         _ => BuildOrdering::AlwaysBuild,
@@ -1003,11 +1732,51 @@ pub fn build(
         backend: code_gen_backend,
         opt_level,
         emit_debug_info,
+        emit_source_debug_info,
         emit_llvm_ir,
+        emit_llvm_bc,
+        emit_mono,
+        emit_asm,
         fuzz,
+        max_stack_frame,
+        host_panic,
+        sanitize_address,
+        check_alignment,
+        inline_threshold,
+        optimize_stack_alloc,
+        overflow_trap,
+        pgo,
+        lto,
+        sbf_compute_budget,
+        sbf_stack_limit,
+        stack_size,
+        data_sections,
+        shared_library: link_type == LinkType::Dylib,
+        pie,
+        cfi,
+        pool_alloc,
+        valgrind,
+        bulk_memory,
+        wasm_exceptions,
+    };
+
+    let mut load_config = standard_load_config(target, build_ordering, threading);
+    load_config.finalizers = finalizers;
+
+    let json_errors = matches
+        .try_get_one::<bool>(FLAG_JSON_ERRORS)
+        .ok()
+        .flatten()
+        .copied()
+        .unwrap_or(false);
+
+    load_config.render = if json_errors {
+        RenderTarget::Json
+    } else {
+        RenderTarget::ColorTerminal(color_choice_from_flags(matches))
     };
 
-    let load_config = standard_load_config(target, build_ordering, threading);
+    let render = load_config.render;
 
     let res_binary_path = roc_build::program::build_file(
         &arena,
@@ -1019,6 +1788,7 @@ pub fn build(
         linking_strategy,
         build_host,
         suppress_build_host_warning,
+        use_cache,
         wasm_dev_stack_bytes,
         roc_cache_dir,
         load_config,
@@ -1032,7 +1802,14 @@ pub fn build(
             problems,
             total_time,
             expect_metadata,
+            module_paths,
+            platform_main_roc_path,
         }) => {
+            watch::report_sources(
+                module_paths.iter().map(PathBuf::as_path),
+                Some(&platform_main_roc_path),
+            );
+
             match config {
                 BuildOnly => {
                     // If possible, report the generated executable name relative to the current dir.
@@ -1046,28 +1823,34 @@ pub fn build(
                     // since the process is about to exit anyway.
                     // std::mem::forget(arena);
 
-                    problems.print_error_warning_count(total_time);
-                    println!(" while successfully building:\n\n    {generated_filename}");
+                    if !json_errors {
+                        problems.print_error_warning_count(total_time, render.has_color());
+                        println!(" while successfully building:\n\n    {generated_filename}");
+
+                        if emit_object {
+                            print_object_symbols(&binary_path)?;
+                        }
+                    }
 
                     // Return a nonzero exit code if there were problems
                     Ok(problems.exit_code())
                 }
                 BuildAndRun => {
                     if problems.fatally_errored {
-                        problems.print_error_warning_count(total_time);
+                        problems.print_error_warning_count(total_time, render.has_color());
                         println!(
-                            ".\n\nCannot run program due to fatal error…\n\n\x1B[36m{}\x1B[39m",
-                            "─".repeat(80)
+                            ".\n\nCannot run program due to fatal error…\n\n{}",
+                            horizontal_rule(render.has_color())
                         );
 
                         // Return a nonzero exit code due to fatal problem
                         return Ok(problems.exit_code());
                     }
                     if problems.errors > 0 || problems.warnings > 0 {
-                        problems.print_error_warning_count(total_time);
+                        problems.print_error_warning_count(total_time, render.has_color());
                         println!(
-                            ".\n\nRunning program anyway…\n\n\x1B[36m{}\x1B[39m",
-                            "─".repeat(80)
+                            ".\n\nRunning program anyway…\n\n{}",
+                            horizontal_rule(render.has_color())
                         );
                     }
 
@@ -1092,10 +1875,10 @@ pub fn build(
                 }
                 BuildAndRunIfNoErrors => {
                     if problems.fatally_errored {
-                        problems.print_error_warning_count(total_time);
+                        problems.print_error_warning_count(total_time, render.has_color());
                         println!(
-                            ".\n\nCannot run program due to fatal error…\n\n\x1B[36m{}\x1B[39m",
-                            "─".repeat(80)
+                            ".\n\nCannot run program due to fatal error…\n\n{}",
+                            horizontal_rule(render.has_color())
                         );
 
                         // Return a nonzero exit code due to fatal problem
@@ -1107,10 +1890,10 @@ pub fn build(
                     );
 
                     if problems.warnings > 0 {
-                        problems.print_error_warning_count(total_time);
+                        problems.print_error_warning_count(total_time, render.has_color());
                         println!(
-                            ".\n\nRunning program…\n\n\x1B[36m{}\x1B[39m",
-                            "─".repeat(80)
+                            ".\n\nRunning program…\n\n{}",
+                            horizontal_rule(render.has_color())
                         );
                     }
 
@@ -1136,12 +1919,34 @@ pub fn build(
             }
         }
         Err(BuildFileError::ErrorModule { module, total_time }) => {
-            handle_error_module(module, total_time)
+            watch::report_sources(watch::module_paths(&module.sources), None);
+
+            handle_error_module(module, total_time, render)
         }
         Err(BuildFileError::LoadingProblem(problem)) => handle_loading_problem(problem),
     }
 }
 
+/// List the symbols that the host and the app object at `path` have to define for each other
+fn print_object_symbols(path: &Path) -> io::Result<()> {
+    let bytes = std::fs::read(path)?;
+    let symbols = ObjectSymbols::from_object(&bytes).unwrap_or_else(|error| {
+        internal_error!("could not read the symbols of {}: {error}", path.display())
+    });
+
+    println!("\nThe host has to define these symbols, which the app uses:\n");
+    for name in &symbols.undefined {
+        println!("    {name}");
+    }
+
+    println!("\nThe app defines these symbols for the host:\n");
+    for name in &symbols.exported {
+        println!("    {name}");
+    }
+
+    Ok(())
+}
+
 fn roc_run<'a, I: IntoIterator<Item = &'a OsStr>>(
     arena: &Bump,
     script_path: &Path,
@@ -1160,29 +1965,7 @@ fn roc_run<'a, I: IntoIterator<Item = &'a OsStr>>(
                 .strip_prefix(env::current_dir().unwrap())
                 .unwrap_or(path);
 
-            #[cfg(target_family = "unix")]
-            {
-                use std::os::unix::ffi::OsStrExt;
-
-                run_wasm(
-                    generated_filename,
-                    args.into_iter().map(|os_str| os_str.as_bytes()),
-                );
-            }
-
-            #[cfg(not(target_family = "unix"))]
-            {
-                run_wasm(
-                    generated_filename,
-                    args.into_iter().map(|os_str| {
-                        os_str.to_str().expect(
-                            "Roc does not currently support passing non-UTF8 arguments to Wasm.",
-                        )
-                    }),
-                );
-            }
-
-            Ok(0)
+            run_wasm(generated_filename, args)
         }
         _ => roc_run_native(
             arena,
@@ -1556,33 +2339,72 @@ fn roc_run_native<I: IntoIterator<Item = S>, S: AsRef<OsStr>>(
     Ok(1)
 }
 
+/// Run a WASI module with the interpreter of roc_wasm_interp, and return its exit code. A module
+/// that exits through `proc_exit` exits the whole process.
 #[cfg(feature = "run-wasm32")]
-fn run_wasm<I: Iterator<Item = S>, S: AsRef<[u8]>>(wasm_path: &std::path::Path, args: I) {
+fn run_wasm<'a, I: IntoIterator<Item = &'a OsStr>>(
+    wasm_path: &std::path::Path,
+    args: I,
+) -> io::Result<i32> {
     use bumpalo::collections::Vec;
-    use roc_wasm_interp::{DefaultImportDispatcher, Instance};
+    use roc_wasm_interp::{DefaultImportDispatcher, Instance, Value};
 
-    let bytes = std::fs::read(wasm_path).unwrap();
+    let bytes = std::fs::read(wasm_path)?;
     let arena = Bump::new();
 
     let mut argv = Vec::<&[u8]>::new_in(&arena);
     for arg in args {
-        let mut arg_copy = Vec::<u8>::new_in(&arena);
-        arg_copy.extend_from_slice(arg.as_ref());
-        argv.push(arg_copy.into_bump_slice());
+        #[cfg(target_family = "unix")]
+        let arg = {
+            use std::os::unix::ffi::OsStrExt;
+            arg.as_bytes()
+        };
+
+        #[cfg(not(target_family = "unix"))]
+        let arg = arg
+            .to_str()
+            .expect("Roc does not currently support passing non-UTF8 arguments to Wasm.")
+            .as_bytes();
+
+        argv.push(arena.alloc_slice_copy(arg));
     }
     let import_dispatcher = DefaultImportDispatcher::new(&argv);
 
-    let mut instance = Instance::from_bytes(&arena, &bytes, import_dispatcher, false).unwrap();
-
-    instance
-        .call_export("_start", [])
-        .unwrap()
-        .unwrap()
-        .expect_i32()
-        .unwrap();
+    let mut instance = Instance::from_bytes(&arena, &bytes, import_dispatcher, false)
+        .unwrap_or_else(|e| user_error!("I could not load {}:\n{e}", wasm_path.display()));
+
+    // the `_start` that a WASI libc defines returns nothing, and one that we generate to call the
+    // host's `main` drops its result, but a host may define its own
+    match instance.call_export("_start", []) {
+        Ok(None) => Ok(0),
+        Ok(Some(Value::I32(exit_code))) => Ok(exit_code),
+        Ok(Some(value)) => user_error!("`_start` returned {value:?} instead of an exit code."),
+        Err(e) => {
+            eprintln!("{e}");
+            Ok(1)
+        }
+    }
 }
 
+/// Run a WASI module with `wasmtime`, when it is on the PATH, and return its exit code
 #[cfg(not(feature = "run-wasm32"))]
-fn run_wasm<I: Iterator<Item = S>, S: AsRef<[u8]>>(_wasm_path: &std::path::Path, _args: I) {
-    println!("Running wasm files is not supported on this target.");
+fn run_wasm<'a, I: IntoIterator<Item = &'a OsStr>>(
+    wasm_path: &std::path::Path,
+    args: I,
+) -> io::Result<i32> {
+    let status = match std::process::Command::new("wasmtime")
+        .arg("run")
+        .arg(wasm_path)
+        .args(args)
+        .status()
+    {
+        Ok(status) => status,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            eprintln!("Running wasm files needs `wasmtime` on the PATH, or a roc that was built with the `run-wasm32` feature.");
+            return Ok(1);
+        }
+        Err(e) => return Err(e),
+    };
+
+    Ok(status.code().unwrap_or(1))
 }
@@ -3,13 +3,14 @@ use bumpalo::Bump;
 use roc_build::link::LinkType;
 use roc_build::program::{check_file, CodeGenBackend};
 use roc_cli::{
-    annotate_file, build_app, default_linking_strategy, format_files, format_src, test,
-    AnnotationProblem, BuildConfig, FormatMode, CMD_BUILD, CMD_CHECK, CMD_DEV, CMD_DOCS,
-    CMD_FORMAT, CMD_FORMAT_ANNOTATE, CMD_GLUE, CMD_LICENSES, CMD_PREPROCESS_HOST, CMD_REPL,
-    CMD_RUN, CMD_TEST, CMD_VERSION, DIRECTORY_OR_FILES, FLAG_CHECK, FLAG_DEV, FLAG_DOCS_ROOT,
-    FLAG_LIB, FLAG_MAIN, FLAG_MIGRATE, FLAG_NO_COLOR, FLAG_NO_HEADER, FLAG_NO_LINK, FLAG_OUTPUT,
+    annotate_file, build_app, color_choice_from_flags, default_linking_strategy, format_files,
+    format_src, test, watch, AnnotationProblem, BuildConfig, FormatMode, CMD_BUILD, CMD_CHECK,
+    CMD_DEV, CMD_DOCS, CMD_FORMAT, CMD_FORMAT_ANNOTATE, CMD_GEN_HEADER, CMD_GLUE, CMD_LICENSES,
+    CMD_PREPROCESS_HOST, CMD_REPL, CMD_RUN, CMD_TEST, CMD_VERSION, DIRECTORY_OR_FILES, FLAG_CHECK,
+    FLAG_DEV, FLAG_DOCS_ROOT, FLAG_EMIT_OBJECT, FLAG_JSON_ERRORS, FLAG_LIB, FLAG_MAIN,
+    FLAG_MIGRATE, FLAG_NO_CACHE, FLAG_NO_COLOR, FLAG_NO_HEADER, FLAG_NO_LINK, FLAG_OUTPUT,
     FLAG_PP_DYLIB, FLAG_PP_HOST, FLAG_PP_PLATFORM, FLAG_STDIN, FLAG_STDOUT, FLAG_TARGET, FLAG_TIME,
-    FLAG_VERBOSE, GLUE_DIR, GLUE_SPEC, ROC_FILE, VERSION,
+    FLAG_VERBOSE, FLAG_WATCH, GLUE_DIR, GLUE_SPEC, ROC_FILE, VERSION,
 };
 use roc_docs::generate_docs_html;
 use roc_error_macros::{internal_error, user_error};
@@ -17,6 +18,7 @@ use roc_gen_dev::AssemblyBackendMode;
 use roc_gen_llvm::llvm::build::LlvmBackendMode;
 use roc_load::{LoadingProblem, Threading};
 use roc_packaging::cache::{self, RocCacheDir};
+use roc_reporting::report::RenderTarget;
 use roc_target::Target;
 use std::fs::{self, FileType};
 use std::io::BufRead;
@@ -81,7 +83,15 @@ fn main() -> io::Result<()> {
             }
         }
         Some((CMD_TEST, matches)) => {
-            if matches.contains_id(ROC_FILE) {
+            if matches.contains_id(ROC_FILE) && matches.get_flag(FLAG_WATCH) {
+                let roots: Vec<PathBuf> = matches
+                    .get_many::<PathBuf>(ROC_FILE)
+                    .unwrap()
+                    .cloned()
+                    .collect();
+
+                watch(&roots)
+            } else if matches.contains_id(ROC_FILE) {
                 test(matches, Triple::host().into())
             } else {
                 eprintln!("What .roc file do you want to test? Specify it at the end of the `roc test` command.");
@@ -90,7 +100,11 @@ fn main() -> io::Result<()> {
             }
         }
         Some((CMD_DEV, matches)) => {
-            if matches.contains_id(ROC_FILE) {
+            if matches.contains_id(ROC_FILE) && matches.get_flag(FLAG_WATCH) {
+                let root = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
+
+                watch(std::slice::from_ref(root))
+            } else if matches.contains_id(ROC_FILE) {
                 build(
                     matches,
                     &subcommands,
@@ -137,6 +151,12 @@ fn main() -> io::Result<()> {
                 Ok(1)
             }
         }
+        Some((CMD_GEN_HEADER, matches)) => {
+            let input_path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
+            let output_path = matches.get_one::<PathBuf>(FLAG_OUTPUT).unwrap();
+
+            roc_glue::c_header::generate(input_path, output_path)
+        }
         Some((CMD_PREPROCESS_HOST, matches)) => {
             let preprocess_host_err =
                 { |msg: String| user_error!("\n\n ERROR PRE-PROCESSING HOST: {}\n\n", msg) };
@@ -191,7 +211,8 @@ fn main() -> io::Result<()> {
                 .get_one::<String>(FLAG_TARGET)
                 .and_then(|s| Target::from_str(s).ok())
                 .unwrap_or_default();
-            let link_type = match (matches.get_flag(FLAG_LIB), matches.get_flag(FLAG_NO_LINK)) {
+            let no_link = matches.get_flag(FLAG_NO_LINK) || matches.get_flag(FLAG_EMIT_OBJECT);
+            let link_type = match (matches.get_flag(FLAG_LIB), no_link) {
                 (true, false) => LinkType::Dylib,
                 (true, true) => user_error!("build can only be one of `--lib` or `--no-link`"),
                 (false, true) => LinkType::None,
@@ -217,6 +238,7 @@ fn main() -> io::Result<()> {
             let arena = Bump::new();
 
             let emit_timings = matches.get_flag(FLAG_TIME);
+            let use_cache = !matches.get_flag(FLAG_NO_CACHE);
             let roc_file_path = matches.get_one::<PathBuf>(ROC_FILE).unwrap();
             let threading = match matches.get_one::<usize>(roc_cli::FLAG_MAX_THREADS) {
                 None => Threading::AllAvailable,
@@ -227,6 +249,13 @@ fn main() -> io::Result<()> {
 
             let opt_main_path = matches.get_one::<PathBuf>(FLAG_MAIN);
 
+            let json_errors = matches.get_flag(FLAG_JSON_ERRORS);
+            let render = if json_errors {
+                RenderTarget::Json
+            } else {
+                RenderTarget::ColorTerminal(color_choice_from_flags(matches))
+            };
+
             match roc_file_path.extension().and_then(OsStr::to_str) {
                 Some("md") => {
                     // Extract the blocks of roc code
@@ -263,11 +292,16 @@ fn main() -> io::Result<()> {
                             opt_main_path.cloned(),
                             emit_timings,
                             RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+                            use_cache,
                             threading,
+                            render,
                         ) {
                             Ok((problems, total_time)) => {
-                                problems.print_error_warning_count(total_time);
-                                println!(".\n");
+                                if !json_errors {
+                                    problems
+                                        .print_error_warning_count(total_time, render.has_color());
+                                    println!(".\n");
+                                }
 
                                 exit_code = problems.exit_code();
                             }
@@ -296,11 +330,15 @@ fn main() -> io::Result<()> {
                         opt_main_path.cloned(),
                         emit_timings,
                         RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
+                        use_cache,
                         threading,
+                        render,
                     ) {
                         Ok((problems, total_time)) => {
-                            problems.print_error_warning_count(total_time);
-                            println!(".\n");
+                            if !json_errors {
+                                problems.print_error_warning_count(total_time, render.has_color());
+                                println!(".\n");
+                            }
                             Ok(problems.exit_code())
                         }
 
@@ -358,7 +396,11 @@ fn main() -> io::Result<()> {
                 .get_one::<PathBuf>(ROC_FILE)
                 .unwrap_or_else(|| internal_error!("No default for ROC_FILE"));
 
-            let annotate_exit_code = match annotate_file(&arena, roc_file_path.to_owned()) {
+            let annotate_exit_code = match annotate_file(
+                &arena,
+                roc_file_path.to_owned(),
+                RenderTarget::ColorTerminal(color_choice_from_flags(matches)),
+            ) {
                 Ok(()) => 0,
                 Err(AnnotationProblem::Loading(LoadingProblem::FormattedReport(report, ..))) => {
                     eprintln!("{report}");
@@ -0,0 +1,342 @@
+//! `roc dev --watch` and `roc test --watch`: build and run again whenever a source changes.
+//!
+//! Every build runs in a child `roc` that is given the same arguments without `--watch`, so that
+//! build errors, warnings and failed expects are reported exactly like they are without it. The
+//! child gets a process group of its own, which is killed as a whole before the next build
+//! starts: `roc dev` runs the program in a process of its own when it checks expects.
+//!
+//! The child writes the files it was built from to the file that [WATCH_SOURCES_VAR] names: the
+//! modules roc_load loaded, and the platform's main.roc. Those modules, and the host sources next
+//! to the platform's main.roc, are what is watched until the next build, so a module that gets
+//! imported is watched from the build that first imports it. When a build fails before any
+//! module is loaded, the files of the build before it are still watched.
+//!
+//! Files are watched by checking their modification time and size every [POLL_INTERVAL]. Editors
+//! often write a file more than once when it is saved, so a build only starts once nothing has
+//! changed for [DEBOUNCE].
+
+use roc_collections::MutMap;
+use roc_module::symbol::ModuleId;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// The environment variable that a watched build writes its sources to
+pub const WATCH_SOURCES_VAR: &str = "ROC_WATCH_SOURCES";
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Files in the platform's directory (or below it) with these extensions are host sources
+const HOST_SOURCE_EXTENSIONS: &[&str] = &["c", "h", "zig", "rs", "toml"];
+
+/// Directories with build outputs in them, which change whenever the host is built
+const SKIPPED_DIRECTORIES: &[&str] = &["target", "zig-cache", "zig-out"];
+
+/// Build and run `roots` with `roc`'s own arguments over and over, until `roc` is interrupted
+pub fn watch(roots: &[PathBuf]) -> io::Result<i32> {
+    let interrupted = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&interrupted))?;
+    signal_hook::flag::register(signal_hook::consts::SIGTERM, Arc::clone(&interrupted))?;
+
+    let args = child_args(env::args_os().skip(1));
+    let sources_dir = tempfile::tempdir()?;
+
+    let mut watched = WatchedFiles::new(roots);
+    let mut build_count = 0;
+
+    loop {
+        build_count += 1;
+        let sources_path = sources_dir.path().join(format!("sources-{build_count}"));
+
+        let mut child = spawn_build(&args, &sources_path)?;
+        let mut sources_stamp = None;
+
+        let changed = loop {
+            thread::sleep(POLL_INTERVAL);
+
+            if interrupted.load(Ordering::Relaxed) {
+                kill_build(&mut child);
+
+                return Ok(130);
+            }
+
+            let new_sources_stamp = stamp(&sources_path);
+            if new_sources_stamp != sources_stamp {
+                sources_stamp = new_sources_stamp;
+
+                if let Ok(sources) = fs::read_to_string(&sources_path) {
+                    watched.update(roots, &sources);
+                }
+            }
+
+            if let Some(changed) = watched.changed() {
+                break changed;
+            }
+        };
+
+        // wait for the other writes of a save before building
+        loop {
+            thread::sleep(DEBOUNCE);
+
+            if watched.changed().is_none() {
+                break;
+            }
+        }
+
+        kill_build(&mut child);
+
+        let current_dir = env::current_dir().unwrap_or_default();
+        let changed = changed.strip_prefix(&current_dir).unwrap_or(&changed);
+
+        println!("\nrebuilding because {} changed\n", changed.display());
+    }
+}
+
+/// Tell the `roc --watch` that started this build which files it was built from. Builds that
+/// were not started by `--watch` don't do anything here.
+pub fn report_sources<'p>(
+    modules: impl IntoIterator<Item = &'p Path>,
+    platform_main_roc_path: Option<&Path>,
+) {
+    let Some(sources_path) = env::var_os(WATCH_SOURCES_VAR) else {
+        return;
+    };
+
+    let mut report = String::new();
+
+    if let Some(platform_main_roc_path) = platform_main_roc_path {
+        report.push_str("platform ");
+        report.push_str(&platform_main_roc_path.to_string_lossy());
+        report.push('\n');
+    }
+
+    for module in modules {
+        report.push_str("module ");
+        report.push_str(&module.to_string_lossy());
+        report.push('\n');
+    }
+
+    // `roc test` reports once for every file it tests
+    let written = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&sources_path)
+        .and_then(|mut file| file.write_all(report.as_bytes()));
+
+    if let Err(error) = written {
+        eprintln!("Could not tell `--watch` which files to watch: {error}");
+    }
+}
+
+/// The paths of the modules in `sources` that come from files, which leaves out the builtins
+pub fn module_paths(sources: &MutMap<ModuleId, (PathBuf, Box<str>)>) -> Vec<&Path> {
+    sources
+        .iter()
+        .filter(|(module_id, _)| !module_id.is_builtin())
+        .map(|(_, (path, _))| path.as_path())
+        .collect()
+}
+
+/// `roc`'s own arguments without the first `--watch`, which is the one that started watching.
+/// A `--watch` after `--` is for the program.
+fn child_args(args: impl Iterator<Item = OsString>) -> Vec<OsString> {
+    const WATCH_FLAG: &str = concatcp!("--", crate::FLAG_WATCH);
+
+    let mut removed = false;
+    let mut for_program = false;
+
+    args.filter(|arg| {
+        if arg == "--" {
+            for_program = true;
+        }
+
+        if !removed && !for_program && arg == WATCH_FLAG {
+            removed = true;
+            false
+        } else {
+            true
+        }
+    })
+    .collect()
+}
+
+fn spawn_build(args: &[OsString], sources_path: &Path) -> io::Result<Child> {
+    let mut command = Command::new(env::current_exe()?);
+
+    command
+        .args(args)
+        .env(WATCH_SOURCES_VAR, sources_path)
+        // the program runs in the background, where reading from the terminal would stop it
+        .stdin(Stdio::null());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+
+        command.process_group(0);
+    }
+
+    command.spawn()
+}
+
+fn kill_build(child: &mut Child) {
+    #[cfg(unix)]
+    unsafe {
+        // the child leads its process group, so this also kills the program it runs
+        libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL);
+    }
+
+    #[cfg(not(unix))]
+    let _ = child.kill();
+
+    let _ = child.wait();
+}
+
+type Stamp = (SystemTime, u64);
+
+fn stamp(path: &Path) -> Option<Stamp> {
+    let metadata = fs::metadata(path).ok()?;
+
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+struct WatchedFiles {
+    stamps: MutMap<PathBuf, Option<Stamp>>,
+    platform_main_roc_path: Option<PathBuf>,
+}
+
+impl WatchedFiles {
+    fn new(roots: &[PathBuf]) -> Self {
+        let mut watched = WatchedFiles {
+            stamps: MutMap::default(),
+            platform_main_roc_path: None,
+        };
+
+        watched.update(roots, "");
+
+        watched
+    }
+
+    /// Watch `roots` and the files in a report of [report_sources], instead of what was watched
+    /// before. A report without a platform keeps the platform that was watched before. Files
+    /// that were already watched keep their stamp, so that a change that was made during the
+    /// build is not missed.
+    fn update(&mut self, roots: &[PathBuf], sources: &str) {
+        let mut paths: Vec<PathBuf> = roots.to_vec();
+
+        for line in sources.lines() {
+            if let Some(module) = line.strip_prefix("module ") {
+                paths.push(PathBuf::from(module));
+            } else if let Some(platform_main_roc_path) = line.strip_prefix("platform ") {
+                self.platform_main_roc_path = Some(PathBuf::from(platform_main_roc_path));
+            }
+        }
+
+        if let Some(platform_main_roc_path) = &self.platform_main_roc_path {
+            paths.push(platform_main_roc_path.clone());
+
+            if let Some(platform_dir) = platform_main_roc_path.parent() {
+                host_sources(platform_dir, &mut paths);
+            }
+        }
+
+        let mut stamps = MutMap::default();
+
+        for path in paths {
+            let old_stamp = match self.stamps.remove(&path) {
+                Some(old_stamp) => old_stamp,
+                None => stamp(&path),
+            };
+
+            stamps.insert(path, old_stamp);
+        }
+
+        self.stamps = stamps;
+    }
+
+    /// A file that changed since the last time this was called, if any did
+    fn changed(&mut self) -> Option<PathBuf> {
+        let mut changed = None;
+
+        for (path, old_stamp) in self.stamps.iter_mut() {
+            let new_stamp = stamp(path);
+
+            if new_stamp != *old_stamp {
+                *old_stamp = new_stamp;
+                changed.get_or_insert_with(|| path.clone());
+            }
+        }
+
+        changed
+    }
+}
+
+fn host_sources(dir: &Path, paths: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(OsStr::to_str) else {
+            continue;
+        };
+
+        if path.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRECTORIES.contains(&name) {
+                host_sources(&path, paths);
+            }
+        } else if path
+            .extension()
+            .and_then(OsStr::to_str)
+            .is_some_and(|extension| HOST_SOURCE_EXTENSIONS.contains(&extension))
+        {
+            paths.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn os_strings(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn child_args_leave_out_watch() {
+        let args = os_strings(&["dev", "--watch", "main.roc", "--", "--watch"]);
+
+        assert_eq!(
+            child_args(args.into_iter()),
+            os_strings(&["dev", "main.roc", "--", "--watch"])
+        );
+    }
+
+    #[test]
+    fn changes_and_reported_sources_are_watched() {
+        let dir = tempfile::tempdir().unwrap();
+        let app = dir.path().join("main.roc");
+        let module = dir.path().join("Foo.roc");
+        fs::write(&app, "app").unwrap();
+        fs::write(&module, "module").unwrap();
+
+        let mut watched = WatchedFiles::new(&[app.clone()]);
+        assert_eq!(watched.changed(), None);
+
+        watched.update(&[app.clone()], &format!("module {}\n", module.display()));
+        fs::write(&module, "module []").unwrap();
+
+        assert_eq!(watched.changed(), Some(module));
+        assert_eq!(watched.changed(), None);
+    }
+}
@@ -0,0 +1,42 @@
+app [main!] { pf: platform "platform/main.roc" }
+
+import pf.Host
+
+main! : {} => {}
+main! = \{} ->
+    { value, is_error } = Host.get_int!({})
+    input_result =
+        if is_error then
+            Err(GetIntError)
+        else
+            Ok(value)
+
+    when input_result is
+        Ok(n) ->
+            drop_lists(Num.to_u64(n), 0)
+            |> Num.to_str
+            |> Host.put_line!
+
+        Err(GetIntError) ->
+            Host.put_line!("Error: Failed to get Integer from stdin.")
+
+# every round allocates a few lists and strings, and drops them one after the other
+drop_lists : U64, U64 -> U64
+drop_lists = \n, total ->
+    if n == 0 then
+        total
+    else
+        words = List.repeat(n, 8)
+        more_words = List.repeat(n, 16)
+        pairs = List.repeat((n, n), 4)
+        name = Str.concat("a string that is on the heap, round ", Num.to_str(n))
+        title = Str.repeat("-", 40)
+
+        round =
+            List.sum(words)
+            + List.len(more_words)
+            + List.len(pairs)
+            + Str.count_utf8_bytes(name)
+            + Str.count_utf8_bytes(title)
+
+        drop_lists(n - 1, Num.add_wrap(total, round))
@@ -0,0 +1,47 @@
+app [main!] { pf: platform "platform/main.roc" }
+
+import pf.Host
+
+main! : {} => {}
+main! = \{} ->
+    { value, is_error } = Host.get_int!({})
+    input_result =
+        if is_error then
+            Err(GetIntError)
+        else
+            Ok(value)
+
+    when input_result is
+        Ok(n) ->
+            records(Num.to_u64(n))
+            |> List.sort_with(\a, b -> Num.compare(a.key, b.key))
+            |> checksum
+            |> Num.to_str
+            |> Host.put_line!
+
+        Err(GetIntError) ->
+            Host.put_line!("Error: Failed to get Integer from stdin.")
+
+# wider than a word, so the sort moves more than a pointer per element
+Record : { key : U64, id : U64, seed : U64, weight : U64 }
+
+# `n` records with pseudo-random keys out of 1000, so most keys repeat
+records : U64 -> List Record
+records = \n ->
+    records_help(List.with_capacity(n), 42, 0, n)
+
+records_help : List Record, U64, U64, U64 -> List Record
+records_help = \list, seed, id, n ->
+    if id == n then
+        list
+    else
+        next = Num.add_wrap(Num.mul_wrap(seed, 6364136223846793005), 1442695040888963407)
+        key = Num.shift_right_zf_by(next, 33) % 1000
+        record = { key, id, seed: next, weight: id * 2 }
+
+        records_help(List.append(list, record), next, id + 1, n)
+
+# depends on the order of the ids, so it changes when equal keys are reordered
+checksum : List Record -> U64
+checksum = \list ->
+    List.walk(list, 0, \acc, record -> Num.add_wrap(Num.mul_wrap(acc, 31), record.id))
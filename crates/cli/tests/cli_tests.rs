@@ -37,7 +37,12 @@ mod cli_tests {
     const BUILD_HOST_FLAG: &str = concatcp!("--", roc_cli::FLAG_BUILD_HOST);
     const SUPPRESS_BUILD_HOST_WARNING_FLAG: &str =
         concatcp!("--", roc_cli::FLAG_SUPPRESS_BUILD_HOST_WARNING);
+    #[allow(dead_code)]
+    const NO_CACHE_FLAG: &str = concatcp!("--", roc_cli::FLAG_NO_CACHE);
     const CHECK_FLAG: &str = concatcp!("--", roc_cli::FLAG_CHECK);
+    const JSON_ERRORS_FLAG: &str = concatcp!("--", roc_cli::FLAG_JSON_ERRORS);
+    const COLOR_FLAG: &str = concatcp!("--", roc_cli::FLAG_COLOR);
+    const FILTER_FLAG: &str = concatcp!("--", roc_cli::FLAG_FILTER);
     #[allow(dead_code)]
     const TARGET_FLAG: &str = concatcp!("--", roc_cli::FLAG_TARGET);
 
@@ -114,6 +119,1069 @@ mod cli_tests {
         cli_check_out.assert_clean_success();
     }
 
+    #[test]
+    #[cfg(feature = "wasm32-cli-run")]
+    fn wasi_hello_world() {
+        copy_zig_glue::initialize_zig_test_platforms();
+
+        let roc_file_path =
+            file_from_root("crates/cli/tests/test-projects/wasi-hello", "hello.roc");
+
+        // with the LLVM backend, and with the dev backend, which links the host itself
+        for flags in [vec![], vec![concatcp!("--", roc_cli::FLAG_DEV)]] {
+            let cli_build = ExecCli::new(CMD_BUILD, roc_file_path.clone())
+                .add_args(flags)
+                .arg(concatcp!(TARGET_FLAG, "=wasm32"))
+                .arg(BUILD_HOST_FLAG)
+                .arg(SUPPRESS_BUILD_HOST_WARNING_FLAG);
+
+            cli_build.run().assert_clean_success();
+
+            let wasm_run_out =
+                crate::run_wasm_for_cli_test(&roc_file_path.with_extension("wasm"), vec![]);
+
+            assert_eq!(wasm_run_out, "Hello, World!\n");
+        }
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn debug_info_points_at_roc_source() {
+        let object_path =
+            file_from_root("crates/cli/tests/platform-switching", "roc_loves_c_debug.o");
+
+        let cli_build = ExecCli::new(
+            CMD_BUILD,
+            file_from_root("crates/cli/tests/platform-switching", "roc_loves_c.roc"),
+        )
+        .arg(concatcp!("--", roc_cli::FLAG_DEBUG))
+        .arg(concatcp!("--", roc_cli::FLAG_NO_LINK))
+        .arg(format!(
+            "--{}={}",
+            roc_cli::FLAG_OUTPUT,
+            object_path.display()
+        ));
+
+        cli_build.run().assert_clean_success();
+
+        // distros tend to only ship the versioned binary
+        let dwarfdump = ["llvm-dwarfdump-18", "llvm-dwarfdump"]
+            .into_iter()
+            .find_map(|name| {
+                std::process::Command::new(name)
+                    .arg("--debug-info")
+                    .arg(&object_path)
+                    .output()
+                    .ok()
+            })
+            .expect("llvm-dwarfdump should be installed");
+
+        assert!(dwarfdump.status.success());
+
+        let dwarf = String::from_utf8_lossy(&dwarfdump.stdout);
+        let subprogram = dwarf
+            .split("\n\n")
+            .find(|entry| entry.contains("DW_TAG_subprogram") && entry.contains("(\"main\")"))
+            .unwrap_or_else(|| panic!("no subprogram for main in:\n{dwarf}"));
+
+        assert!(
+            subprogram.contains("roc_loves_c.roc"),
+            "main is not attributed to roc_loves_c.roc:\n{subprogram}"
+        );
+
+        std::fs::remove_file(&object_path).unwrap();
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn debug_info_describes_parameters() {
+        let object_path =
+            file_from_root("crates/cli/tests/test-projects/algorithms", "fib_debug.o");
+
+        let cli_build = ExecCli::new(
+            CMD_BUILD,
+            file_from_root("crates/cli/tests/test-projects/algorithms", "fibonacci.roc"),
+        )
+        .arg(concatcp!("--", roc_cli::FLAG_DEBUG))
+        .arg(concatcp!("--", roc_cli::FLAG_NO_LINK))
+        .arg(format!(
+            "--{}={}",
+            roc_cli::FLAG_OUTPUT,
+            object_path.display()
+        ));
+
+        cli_build.run().assert_clean_success();
+
+        let dwarfdump = ["llvm-dwarfdump-18", "llvm-dwarfdump"]
+            .into_iter()
+            .find_map(|name| {
+                std::process::Command::new(name)
+                    .arg("--debug-info")
+                    .arg(&object_path)
+                    .output()
+                    .ok()
+            })
+            .expect("llvm-dwarfdump should be installed");
+
+        assert!(dwarfdump.status.success());
+
+        let dwarf = String::from_utf8_lossy(&dwarfdump.stdout);
+
+        // fib = \n, a, b -> ...
+        for name in ["n", "a", "b"] {
+            let parameter = format!("(\"{name}\")");
+
+            assert!(
+                dwarf.split("\n\n").any(|entry| {
+                    entry.contains("DW_TAG_formal_parameter") && entry.contains(&parameter)
+                }),
+                "no formal parameter {name} in:\n{dwarf}"
+            );
+        }
+
+        std::fs::remove_file(&object_path).unwrap();
+    }
+
+    #[test]
+    fn emit_writes_intermediate_representations() {
+        let project_dir = dir_from_root("crates/cli/tests/platform-switching");
+        let object_path = project_dir.join("roc_loves_c_emit.o");
+
+        let cli_build = ExecCli::new(CMD_BUILD, project_dir.join("roc_loves_c.roc"))
+            .arg(concatcp!(
+                "--",
+                roc_cli::FLAG_EMIT,
+                "=llvm-ir,llvm-bc,mono,asm"
+            ))
+            .arg(concatcp!("--", roc_cli::FLAG_NO_LINK))
+            .arg(format!(
+                "--{}={}",
+                roc_cli::FLAG_OUTPUT,
+                object_path.display()
+            ));
+
+        cli_build.run().assert_clean_success();
+
+        let read = |extension: &str| {
+            let path = project_dir.join("roc_loves_c").with_extension(extension);
+            let bytes = std::fs::read(&path)
+                .unwrap_or_else(|err| panic!("{} was not written: {err}", path.display()));
+            std::fs::remove_file(&path).unwrap();
+
+            bytes
+        };
+
+        let llvm_ir = String::from_utf8(read("ll")).unwrap();
+        let llvm_bc = read("bc");
+        let mono = String::from_utf8(read("mono")).unwrap();
+        let asm = String::from_utf8(read("s")).unwrap();
+
+        std::fs::remove_file(&object_path).unwrap();
+
+        assert!(
+            llvm_ir.contains("define") && llvm_ir.contains("roc__main_for_host_1_exposed"),
+            "the .ll file has no definition of the entry point:\n{llvm_ir}"
+        );
+        assert!(
+            llvm_bc.starts_with(b"BC\xC0\xDE"),
+            "the .bc file is not LLVM bitcode"
+        );
+        assert!(
+            mono.contains("procedure"),
+            "the .mono file has no procedures:\n{mono}"
+        );
+        assert!(
+            asm.contains("roc__main_for_host_1_exposed"),
+            "the .s file has no assembly for the entry point:\n{asm}"
+        );
+    }
+
+    #[test]
+    fn emit_object_lists_host_symbols() {
+        let project_dir = dir_from_root("crates/cli/tests/platform-switching");
+        let object_path = project_dir.join("roc_loves_c_emit_object.o");
+
+        let cli_build = ExecCli::new(CMD_BUILD, project_dir.join("roc_loves_c.roc"))
+            .arg(concatcp!("--", roc_cli::FLAG_EMIT_OBJECT))
+            .arg(format!(
+                "--{}={}",
+                roc_cli::FLAG_OUTPUT,
+                object_path.display()
+            ));
+
+        let cli_build_out = cli_build.run();
+        cli_build_out.assert_clean_success();
+
+        let object = std::fs::read(&object_path).unwrap();
+        std::fs::remove_file(&object_path).unwrap();
+
+        assert!(
+            object::File::parse(object.as_slice()).is_ok(),
+            "--emit-object did not write a relocatable object"
+        );
+
+        let (undefined, exported) = cli_build_out
+            .stdout
+            .split_once("The app defines these symbols for the host:")
+            .unwrap_or_else(|| panic!("no symbols were listed:\n{}", cli_build_out.stdout));
+
+        assert!(
+            undefined.contains("roc_alloc") && undefined.contains("roc_panic"),
+            "the host functions are not listed:\n{undefined}"
+        );
+        assert!(
+            exported.contains("roc__main_for_host_1_exposed"),
+            "the entry point is not listed:\n{exported}"
+        );
+    }
+
+    #[test]
+    #[ignore = "this needs the Solana platform tools"]
+    fn sbf_program_exports_entrypoint() {
+        use object::read::elf::{FileHeader, ProgramHeader};
+        use object::{Object, ObjectSymbol};
+
+        let project_dir = dir_from_root("crates/cli/tests/test-projects/sbf");
+        let host_path = project_dir.join("platform").join("sbf.o");
+        let program_path = project_dir.join("app.so");
+
+        let clang_status = roc_command_utils::sbf_clang()
+            .args(["--target=sbf", "-O2", "-fPIC", "-c"])
+            .arg(project_dir.join("platform").join("host.c"))
+            .arg("-o")
+            .arg(&host_path)
+            .status()
+            .unwrap();
+
+        assert!(clang_status.success());
+
+        let cli_build = ExecCli::new(CMD_BUILD, project_dir.join("app.roc"))
+            .arg(concatcp!("--", roc_cli::FLAG_TARGET, "=sbf"))
+            .arg(format!(
+                "--{}={}",
+                roc_cli::FLAG_OUTPUT,
+                program_path.display()
+            ));
+
+        cli_build.run().assert_clean_success();
+
+        let program = std::fs::read(&program_path).unwrap();
+
+        std::fs::remove_file(&host_path).unwrap();
+        std::fs::remove_file(&program_path).unwrap();
+
+        let endian = object::LittleEndian;
+        let header =
+            object::elf::FileHeader64::<object::LittleEndian>::parse(program.as_slice()).unwrap();
+
+        assert_eq!(header.e_type(endian), object::elf::ET_DYN);
+        assert!(
+            matches!(
+                header.e_machine(endian),
+                object::elf::EM_BPF | object::elf::EM_SBF
+            ),
+            "the program is not an SBF program"
+        );
+        // the first version of SBF, which every cluster runs
+        assert_eq!(header.e_flags(endian), 0);
+
+        let segments: Vec<_> = header
+            .program_headers(endian, program.as_slice())
+            .unwrap()
+            .iter()
+            .map(|segment| (segment.p_type(endian), segment.p_flags(endian)))
+            .collect();
+
+        assert!(
+            segments.contains(&(object::elf::PT_LOAD, object::elf::PF_R | object::elf::PF_X)),
+            "there is no segment for the code: {segments:?}"
+        );
+        assert!(
+            segments
+                .iter()
+                .any(|(p_type, _)| *p_type == object::elf::PT_DYNAMIC),
+            "there is no dynamic segment for the loader: {segments:?}"
+        );
+        assert!(
+            !segments
+                .iter()
+                .any(|(p_type, p_flags)| *p_type == object::elf::PT_LOAD
+                    && p_flags & object::elf::PF_W != 0
+                    && p_flags & object::elf::PF_X != 0),
+            "a segment is both writable and executable: {segments:?}"
+        );
+
+        let file = object::File::parse(program.as_slice()).unwrap();
+        let entrypoint = file
+            .dynamic_symbols()
+            .find(|symbol| symbol.name().ok() == Some("entrypoint"))
+            .expect("entrypoint is not in the dynamic symbol table");
+
+        assert!(!entrypoint.is_undefined());
+        assert_eq!(file.entry(), entrypoint.address());
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(target_os = "linux"),
+        ignore = "this relies on clang shipping the AddressSanitizer runtime"
+    )]
+    fn address_sanitizer_reports_heap_buffer_overflow() {
+        let project_dir = dir_from_root("crates/cli/tests/test-projects/asan");
+        let object_path = project_dir.join("app.o");
+        let executable_path = project_dir.join("app");
+
+        let cli_build = ExecCli::new(CMD_BUILD, project_dir.join("app.roc"))
+            .arg(concatcp!("--", roc_cli::FLAG_SANITIZE, "=address"))
+            .arg(concatcp!("--", roc_cli::FLAG_NO_LINK))
+            .arg(format!(
+                "--{}={}",
+                roc_cli::FLAG_OUTPUT,
+                object_path.display()
+            ));
+
+        cli_build.run().assert_clean_success();
+
+        let clang_status = std::process::Command::new("clang")
+            .arg("-fsanitize=address")
+            .arg(project_dir.join("platform").join("host.c"))
+            .arg(&object_path)
+            .arg("-o")
+            .arg(&executable_path)
+            .status()
+            .unwrap();
+
+        assert!(clang_status.success());
+
+        let output = std::process::Command::new(&executable_path)
+            .output()
+            .unwrap();
+
+        std::fs::remove_file(&object_path).unwrap();
+        std::fs::remove_file(&executable_path).unwrap();
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        assert!(!output.status.success());
+        assert!(
+            stderr.contains("ERROR: AddressSanitizer: heap-buffer-overflow"),
+            "AddressSanitizer did not catch the overflow:\n{stderr}"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore = "this checks cross-compilation to Windows")]
+    fn cross_compile_windows_x64_dll() {
+        use object::Object;
+
+        let project_dir = dir_from_root("crates/cli/tests/test-projects/windows-dll");
+        let dll_path = project_dir.join("app.dll");
+
+        let cli_build = ExecCli::new(CMD_BUILD, project_dir.join("app.roc"))
+            .arg(concatcp!(TARGET_FLAG, "=windows-x64"))
+            .arg(concatcp!("--", roc_cli::FLAG_LIB))
+            .arg(format!("--{}={}", roc_cli::FLAG_OUTPUT, dll_path.display()));
+
+        cli_build.run().assert_clean_success();
+
+        let bytes = std::fs::read(&dll_path).unwrap();
+        std::fs::remove_file(&dll_path).unwrap();
+
+        let dll = object::File::parse(bytes.as_slice()).unwrap();
+
+        assert_eq!(dll.format(), object::BinaryFormat::Pe);
+        assert_eq!(dll.architecture(), object::Architecture::X86_64);
+
+        let exports: Vec<_> = dll
+            .exports()
+            .unwrap()
+            .iter()
+            .map(|export| String::from_utf8_lossy(export.name()).into_owned())
+            .collect();
+
+        assert!(
+            exports
+                .iter()
+                .any(|name| name == "roc__main_for_host_1_exposed"),
+            "the DLL does not export the function for the host: {exports:?}"
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    fn cross_compile_windows_x64_executable() {
+        use object::Object;
+
+        copy_zig_glue::initialize_zig_test_platforms();
+
+        let project_dir = dir_from_root("crates/cli/tests/test-projects/algorithms");
+        let exe_path = project_dir.join("fibonacci-windows-x64.exe");
+
+        let cli_build = ExecCli::new(CMD_BUILD, project_dir.join("fibonacci.roc"))
+            .arg(concatcp!(TARGET_FLAG, "=windows-x64"))
+            .arg(LEGACY_LINKER_FLAG)
+            .arg(BUILD_HOST_FLAG)
+            .arg(SUPPRESS_BUILD_HOST_WARNING_FLAG)
+            .arg(format!("--{}={}", roc_cli::FLAG_OUTPUT, exe_path.display()));
+
+        cli_build.run().assert_success_with_no_unexpected_errors();
+
+        let bytes = std::fs::read(&exe_path).unwrap();
+        std::fs::remove_file(&exe_path).unwrap();
+
+        let exe = object::File::parse(bytes.as_slice()).unwrap();
+
+        assert_eq!(exe.format(), object::BinaryFormat::Pe);
+        assert_eq!(exe.architecture(), object::Architecture::X86_64);
+        assert_eq!(exe.kind(), object::ObjectKind::Executable);
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn cross_compile_linux_x64_executable() {
+        use object::Object;
+
+        copy_zig_glue::initialize_zig_test_platforms();
+
+        let project_dir = dir_from_root("crates/cli/tests/test-projects/algorithms");
+        let exe_path = project_dir.join("fibonacci-linux-x64");
+
+        let cli_build = ExecCli::new(CMD_BUILD, project_dir.join("fibonacci.roc"))
+            .arg(concatcp!(TARGET_FLAG, "=linux-x64"))
+            .arg(LEGACY_LINKER_FLAG)
+            .arg(BUILD_HOST_FLAG)
+            .arg(SUPPRESS_BUILD_HOST_WARNING_FLAG)
+            .arg(format!("--{}={}", roc_cli::FLAG_OUTPUT, exe_path.display()));
+
+        cli_build.run().assert_success_with_no_unexpected_errors();
+
+        let bytes = std::fs::read(&exe_path).unwrap();
+        std::fs::remove_file(&exe_path).unwrap();
+
+        let exe = object::File::parse(bytes.as_slice()).unwrap();
+
+        assert_eq!(exe.format(), object::BinaryFormat::Elf);
+        assert_eq!(exe.architecture(), object::Architecture::X86_64);
+    }
+
+    #[test]
+    #[cfg_attr(
+        windows,
+        ignore = "alignment failures are reported with dprintf, which Windows lacks"
+    )]
+    fn check_alignment_traps_on_misaligned_list() {
+        let project_dir = dir_from_root("crates/cli/tests/test-projects/misaligned");
+        let object_path = project_dir.join("app.o");
+        let executable_path = project_dir.join("app");
+
+        let cli_build = ExecCli::new(CMD_BUILD, project_dir.join("app.roc"))
+            .arg(concatcp!("--", roc_cli::FLAG_CHECK_ALIGNMENT))
+            .arg(concatcp!("--", roc_cli::FLAG_NO_LINK))
+            .arg(format!(
+                "--{}={}",
+                roc_cli::FLAG_OUTPUT,
+                object_path.display()
+            ));
+
+        cli_build.run().assert_clean_success();
+
+        let clang_status = std::process::Command::new("clang")
+            .arg(project_dir.join("platform").join("host.c"))
+            .arg(&object_path)
+            .arg("-o")
+            .arg(&executable_path)
+            .status()
+            .unwrap();
+
+        assert!(clang_status.success());
+
+        let output = std::process::Command::new(&executable_path)
+            .output()
+            .unwrap();
+
+        std::fs::remove_file(&object_path).unwrap();
+        std::fs::remove_file(&executable_path).unwrap();
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        assert!(!output.status.success());
+        assert!(
+            stderr.contains("misaligned access to") && stderr.contains("8-byte aligned"),
+            "the misaligned access was not reported:\n{stderr}"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore = "the test host is built with clang")]
+    fn host_rejects_app_built_for_other_abi() {
+        let project_dir = dir_from_root("crates/cli/tests/test-projects/abi-version");
+        let header_path = project_dir.join("roc_app.h");
+        let object_path = project_dir.join("app.o");
+        let executable_path = project_dir.join("app");
+
+        // the host is built with the header of the platform before its main changed
+        let gen_header = ExecCli::new(
+            roc_cli::CMD_GEN_HEADER,
+            project_dir.join("old-platform").join("main.roc"),
+        )
+        .arg(format!(
+            "--{}={}",
+            roc_cli::FLAG_OUTPUT,
+            header_path.display()
+        ));
+
+        gen_header.run().assert_clean_success();
+
+        let cli_build = ExecCli::new(CMD_BUILD, project_dir.join("app.roc"))
+            .arg(concatcp!("--", roc_cli::FLAG_NO_LINK))
+            .arg(format!(
+                "--{}={}",
+                roc_cli::FLAG_OUTPUT,
+                object_path.display()
+            ));
+
+        cli_build.run().assert_clean_success();
+
+        let clang_status = std::process::Command::new("clang")
+            .arg("-I")
+            .arg(&project_dir)
+            .arg(project_dir.join("platform").join("host.c"))
+            .arg(&object_path)
+            .arg("-o")
+            .arg(&executable_path)
+            .status()
+            .unwrap();
+
+        assert!(clang_status.success());
+
+        let output = std::process::Command::new(&executable_path)
+            .output()
+            .unwrap();
+
+        std::fs::remove_file(&header_path).unwrap();
+        std::fs::remove_file(&object_path).unwrap();
+        std::fs::remove_file(&executable_path).unwrap();
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        assert!(!output.status.success());
+        assert!(
+            stderr.contains("built for a different version of its platform"),
+            "the ABI mismatch was not reported:\n{stderr}"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore = "the test host is built with clang")]
+    fn overflow_trap_reports_source_location() {
+        let project_dir = dir_from_root("crates/cli/tests/test-projects/overflow");
+        let object_path = project_dir.join("app.o");
+        let executable_path = project_dir.join("app");
+
+        let cli_build = ExecCli::new(CMD_BUILD, project_dir.join("app.roc"))
+            .arg(format!("--{}=trap", roc_cli::FLAG_OVERFLOW))
+            .arg(concatcp!("--", roc_cli::FLAG_NO_LINK))
+            .arg(format!(
+                "--{}={}",
+                roc_cli::FLAG_OUTPUT,
+                object_path.display()
+            ));
+
+        cli_build.run().assert_clean_success();
+
+        let clang_status = std::process::Command::new("clang")
+            .arg(project_dir.join("platform").join("host.c"))
+            .arg(&object_path)
+            .arg("-o")
+            .arg(&executable_path)
+            .status()
+            .unwrap();
+
+        assert!(clang_status.success());
+
+        let output = std::process::Command::new(&executable_path)
+            .output()
+            .unwrap();
+
+        std::fs::remove_file(&object_path).unwrap();
+        std::fs::remove_file(&executable_path).unwrap();
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        assert!(!output.status.success());
+        assert!(
+            stderr.contains("Integer addition overflowed at") && stderr.contains("app.roc:4:"),
+            "the overflow was not reported with its location:\n{stderr}"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore = "the test host is built with clang")]
+    fn with_arena_frees_a_request_in_one_dealloc() {
+        let project_dir = dir_from_root("crates/cli/tests/test-projects/arena");
+        let object_path = project_dir.join("app.o");
+        let executable_path = project_dir.join("app");
+
+        let cli_build = ExecCli::new(CMD_BUILD, project_dir.join("app.roc"))
+            .arg(concatcp!("--", roc_cli::FLAG_NO_LINK))
+            .arg(format!(
+                "--{}={}",
+                roc_cli::FLAG_OUTPUT,
+                object_path.display()
+            ));
+
+        cli_build.run().assert_clean_success();
+
+        let clang_status = std::process::Command::new("clang")
+            .arg(project_dir.join("platform").join("host.c"))
+            .arg(&object_path)
+            .arg("-o")
+            .arg(&executable_path)
+            .status()
+            .unwrap();
+
+        assert!(clang_status.success());
+
+        let output = std::process::Command::new(&executable_path)
+            .output()
+            .unwrap();
+
+        std::fs::remove_file(&object_path).unwrap();
+        std::fs::remove_file(&executable_path).unwrap();
+
+        assert!(output.status.success());
+
+        // every one of the 10 requests allocates its arena, and frees it, nothing else
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "bytes: 2890, allocs: 10, deallocs: 10\n"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(target_os = "linux"),
+        ignore = "this relies on clang shipping the profile runtime, and on llvm-profdata"
+    )]
+    fn pgo_inlines_the_hot_call_only() {
+        let project_dir = dir_from_root("crates/cli/tests/test-projects/pgo");
+        let object_path = project_dir.join("app.o");
+        let executable_path = project_dir.join("app");
+        let raw_profile_path = project_dir.join("app.profraw");
+        let profile_path = project_dir.join("app.profdata");
+        let ir_path = project_dir.join("app.ll");
+
+        let build = |pgo_flag: String| {
+            ExecCli::new(CMD_BUILD, project_dir.join("app.roc"))
+                .arg(OPTIMIZE_FLAG)
+                .arg(pgo_flag)
+                .arg(concatcp!("--", roc_cli::FLAG_EMIT_LLVM_IR))
+                .arg(concatcp!("--", roc_cli::FLAG_NO_LINK))
+                .arg(format!(
+                    "--{}={}",
+                    roc_cli::FLAG_OUTPUT,
+                    object_path.display()
+                ))
+                .run()
+                .assert_clean_success();
+        };
+
+        build(concatcp!("--", roc_cli::FLAG_PGO_INSTRUMENT).to_string());
+
+        let clang_status = std::process::Command::new("clang")
+            .arg("-fprofile-instr-generate")
+            .arg(project_dir.join("platform").join("host.c"))
+            .arg(&object_path)
+            .arg("-o")
+            .arg(&executable_path)
+            .status()
+            .unwrap();
+
+        assert!(clang_status.success());
+
+        let run_status = std::process::Command::new(&executable_path)
+            .env("LLVM_PROFILE_FILE", &raw_profile_path)
+            .status()
+            .unwrap();
+
+        assert!(run_status.success());
+
+        let merge_status = ["llvm-profdata-18", "llvm-profdata"]
+            .into_iter()
+            .find_map(|name| {
+                std::process::Command::new(name)
+                    .arg("merge")
+                    .arg(&raw_profile_path)
+                    .arg("-o")
+                    .arg(&profile_path)
+                    .status()
+                    .ok()
+            })
+            .expect("llvm-profdata should be installed");
+
+        assert!(merge_status.success());
+
+        build(format!(
+            "--{}={}",
+            roc_cli::FLAG_PGO_USE,
+            profile_path.display()
+        ));
+
+        let ir = std::fs::read_to_string(&ir_path).unwrap();
+
+        for path in [
+            &object_path,
+            &executable_path,
+            &raw_profile_path,
+            &profile_path,
+            &ir_path,
+        ] {
+            std::fs::remove_file(path).unwrap();
+        }
+
+        // `mix` is too large to be inlined at both call sites, but the profile makes the call in
+        // the `else` branch hot enough
+        let calls = ir
+            .lines()
+            .filter(|line| line.contains("call") && line.contains("_mix_"))
+            .collect::<Vec<_>>();
+
+        assert!(ir.contains("!prof"), "the profile was not applied:\n{ir}");
+        assert_eq!(
+            calls.len(),
+            1,
+            "expected only the cold call to `mix` to remain:\n{ir}"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(target_os = "linux"),
+        ignore = "link-time optimization is only supported on Linux"
+    )]
+    fn lto_inlines_the_app_into_the_host() {
+        let project_dir = dir_from_root("crates/cli/tests/test-projects/lto");
+        let bitcode_path = project_dir.join("app.bc");
+        let executable_path = project_dir.join("app");
+
+        let cli_build = ExecCli::new(CMD_BUILD, project_dir.join("app.roc"))
+            .arg(OPTIMIZE_FLAG)
+            .arg(concatcp!("--", roc_cli::FLAG_LTO))
+            .arg(concatcp!("--", roc_cli::FLAG_NO_LINK))
+            .arg(format!(
+                "--{}={}",
+                roc_cli::FLAG_OUTPUT,
+                bitcode_path.display()
+            ));
+
+        cli_build.run().assert_clean_success();
+
+        let clang_status = std::process::Command::new("clang")
+            .args(["-O3", "-flto", "-fuse-ld=lld"])
+            .arg(project_dir.join("platform").join("host.c"))
+            .arg(&bitcode_path)
+            .arg("-o")
+            .arg(&executable_path)
+            .status()
+            .unwrap();
+
+        assert!(clang_status.success());
+
+        let output = std::process::Command::new(&executable_path)
+            .output()
+            .unwrap();
+
+        let nm_output = std::process::Command::new("nm")
+            .arg(&executable_path)
+            .output()
+            .unwrap();
+
+        std::fs::remove_file(&bitcode_path).unwrap();
+        std::fs::remove_file(&executable_path).unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            "11166244414315200793\n"
+        );
+
+        // the host calls the app, which calls `Checksum.step` from the other Roc module. With
+        // the whole program in view, the linker inlines all of it into the host's `main`.
+        let symbols = String::from_utf8_lossy(&nm_output.stdout);
+
+        assert!(
+            !symbols.contains("roc__main_for_host_1_exposed") && !symbols.contains("_step_"),
+            "the app was not inlined into the host:\n{symbols}"
+        );
+    }
+
+    #[test]
+    fn opt_size_builds_the_smallest_object() {
+        let project_dir = dir_from_root("crates/cli/tests/test-projects/opt-levels");
+
+        let object_size = |name: &str, opt_flag: Option<&str>| {
+            let object_path = project_dir.join(format!("{name}.o"));
+
+            let mut cli_build = ExecCli::new(CMD_BUILD, project_dir.join("app.roc"))
+                .arg(concatcp!("--", roc_cli::FLAG_NO_LINK))
+                .arg(format!(
+                    "--{}={}",
+                    roc_cli::FLAG_OUTPUT,
+                    object_path.display()
+                ));
+
+            if let Some(opt_flag) = opt_flag {
+                cli_build = cli_build.arg(opt_flag);
+            }
+
+            cli_build.run().assert_clean_success();
+
+            let size = std::fs::metadata(&object_path).unwrap().len();
+            std::fs::remove_file(&object_path).unwrap();
+
+            size
+        };
+
+        let unoptimized = object_size("unoptimized", None);
+        let optimized = object_size("optimized", Some(OPTIMIZE_FLAG));
+        let opt_size = object_size("opt-size", Some(concatcp!("--", roc_cli::FLAG_OPT_SIZE)));
+
+        // --optimize vectorizes and unrolls the loop, which --opt-size does not
+        assert!(
+            opt_size < unoptimized && opt_size < optimized,
+            "unoptimized: {unoptimized} bytes, --optimize: {optimized} bytes, --opt-size: {opt_size} bytes"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(target_os = "linux"),
+        ignore = "the driver loads the library with dlopen and links with -rdynamic"
+    )]
+    fn lib_exports_only_host_functions() {
+        let project_dir = dir_from_root("crates/cli/tests/test-projects/shared-library");
+        let library_path = project_dir.join("libapp.so");
+        let driver_path = project_dir.join("driver");
+
+        let cli_build = ExecCli::new(CMD_BUILD, project_dir.join("app.roc"))
+            .arg(concatcp!("--", roc_cli::FLAG_LIB))
+            .arg(format!(
+                "--{}={}",
+                roc_cli::FLAG_OUTPUT,
+                library_path.display()
+            ));
+
+        cli_build.run().assert_clean_success();
+
+        let clang_status = std::process::Command::new("clang")
+            .arg("-rdynamic")
+            .arg(project_dir.join("driver.c"))
+            .args(["-ldl", "-o"])
+            .arg(&driver_path)
+            .status()
+            .unwrap();
+
+        assert!(clang_status.success());
+
+        let output = std::process::Command::new(&driver_path)
+            .arg(&library_path)
+            .output()
+            .unwrap();
+
+        let nm_output = std::process::Command::new("nm")
+            .args(["--dynamic", "--defined-only"])
+            .arg(&library_path)
+            .output()
+            .unwrap();
+
+        std::fs::remove_file(&library_path).unwrap();
+        std::fs::remove_file(&driver_path).unwrap();
+
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "210\n");
+
+        // the builtins and the procedures of the app are hidden, only the functions the host calls
+        // are left in the dynamic symbol table, next to the symbols the linker defines itself
+        let symbols = String::from_utf8_lossy(&nm_output.stdout);
+        let linker_symbols = ["__bss_start", "_edata", "_end", "_init", "_fini"];
+
+        let exported: Vec<&str> = symbols
+            .lines()
+            .filter_map(|line| line.split_whitespace().nth(2))
+            .filter(|name| !name.starts_with("roc__") && !linker_symbols.contains(name))
+            .collect();
+
+        assert!(
+            exported.is_empty(),
+            "the library exports more than the host functions: {exported:?}"
+        );
+    }
+
+    /// Build the app in test-projects/pie with `roc_flag`, link it into the lto host with clang and
+    /// `clang_flag`, and return the output of the executable, and what `readelf` reads from it with
+    /// each of `readelf_args`.
+    fn build_and_link_pie_project(
+        roc_flag: &str,
+        clang_flag: &str,
+        readelf_args: &[&str],
+    ) -> (String, Vec<String>) {
+        let project_dir = dir_from_root("crates/cli/tests/test-projects/pie");
+        let object_path = project_dir.join(format!("app{clang_flag}.o"));
+        let executable_path = project_dir.join(format!("app{clang_flag}"));
+
+        let cli_build = ExecCli::new(CMD_BUILD, project_dir.join("app.roc"))
+            .arg(roc_flag)
+            .arg(concatcp!("--", roc_cli::FLAG_NO_LINK))
+            .arg(format!(
+                "--{}={}",
+                roc_cli::FLAG_OUTPUT,
+                object_path.display()
+            ));
+
+        cli_build.run().assert_clean_success();
+
+        let clang_status = std::process::Command::new("clang")
+            .arg(clang_flag)
+            .arg(dir_from_root(
+                "crates/cli/tests/test-projects/lto/platform/host.c",
+            ))
+            .arg(&object_path)
+            .arg("-o")
+            .arg(&executable_path)
+            .status()
+            .unwrap();
+
+        std::fs::remove_file(&object_path).unwrap();
+
+        assert!(clang_status.success());
+
+        let output = std::process::Command::new(&executable_path)
+            .output()
+            .unwrap();
+
+        let readelf_outputs = readelf_args
+            .iter()
+            .map(|arg| {
+                let readelf = std::process::Command::new("readelf")
+                    .args([*arg, "--wide"])
+                    .arg(&executable_path)
+                    .output()
+                    .unwrap();
+
+                assert!(readelf.status.success());
+
+                String::from_utf8_lossy(&readelf.stdout).into_owned()
+            })
+            .collect();
+
+        std::fs::remove_file(&executable_path).unwrap();
+
+        assert!(output.status.success());
+
+        (
+            String::from_utf8_lossy(&output.stdout).into_owned(),
+            readelf_outputs,
+        )
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(all(target_os = "linux", target_arch = "x86_64")),
+        ignore = "checks the relocations of x86_64 ELF executables"
+    )]
+    fn pie_links_a_position_independent_executable() {
+        let (output, readelf) = build_and_link_pie_project(
+            concatcp!("--", roc_cli::FLAG_PIE),
+            "-pie",
+            &["--dynamic", "--relocs"],
+        );
+
+        assert_eq!(output, "127\n");
+
+        let (dynamic, relocs) = (&readelf[0], &readelf[1]);
+
+        assert!(
+            dynamic.contains("(FLAGS_1)") && dynamic.contains("PIE"),
+            "the executable is not position-independent:\n{dynamic}"
+        );
+
+        // the pointers in the constant data of the app are relative to where it is loaded, there
+        // are no absolute addresses that the loader has to look up by name
+        assert!(
+            !relocs.contains("R_X86_64_64 ") && !relocs.contains("R_X86_64_32"),
+            "the executable has absolute relocations:\n{relocs}"
+        );
+    }
+
+    #[test]
+    #[cfg_attr(
+        not(target_os = "linux"),
+        ignore = "only Linux supports position-dependent executables"
+    )]
+    fn no_pie_links_a_position_dependent_executable() {
+        let (output, readelf) = build_and_link_pie_project(
+            concatcp!("--", roc_cli::FLAG_NO_PIE),
+            "-no-pie",
+            &["--file-header"],
+        );
+
+        assert_eq!(output, "127\n");
+
+        assert!(
+            readelf[0].contains("EXEC (Executable file)"),
+            "the executable is position-independent:\n{}",
+            readelf[0]
+        );
+    }
+
+    #[test]
+    #[cfg_attr(not(target_os = "linux"), ignore = "readelf only reads ELF objects")]
+    fn data_section_places_constants_in_named_sections() {
+        let project_dir = dir_from_root("crates/cli/tests/test-projects/data-sections");
+        let object_path = project_dir.join("app.o");
+
+        let cli_build = ExecCli::new(CMD_BUILD, project_dir.join("app.roc"))
+            .arg(format!(
+                "--{}=greeting=.rodata.roc_strings",
+                roc_cli::FLAG_DATA_SECTION
+            ))
+            .arg(format!(
+                "--{}=table=.rodata.roc_tables",
+                roc_cli::FLAG_DATA_SECTION
+            ))
+            .arg(concatcp!("--", roc_cli::FLAG_NO_LINK))
+            .arg(format!(
+                "--{}={}",
+                roc_cli::FLAG_OUTPUT,
+                object_path.display()
+            ));
+
+        cli_build.run().assert_clean_success();
+
+        let readelf = std::process::Command::new("readelf")
+            .args(["--section-headers", "--wide"])
+            .arg(&object_path)
+            .output()
+            .unwrap();
+
+        std::fs::remove_file(&object_path).unwrap();
+
+        assert!(readelf.status.success());
+
+        let sections = String::from_utf8_lossy(&readelf.stdout);
+
+        for section in [".rodata.roc_strings", ".rodata.roc_tables"] {
+            assert!(
+                sections.contains(section),
+                "there is no {section} section:\n{sections}"
+            );
+        }
+    }
+
     #[test]
     #[cfg_attr(
         windows,
@@ -140,6 +1208,40 @@ mod cli_tests {
         );
     }
 
+    #[test]
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    fn preprocessed_host_is_cached() {
+        copy_zig_glue::initialize_zig_test_platforms();
+
+        let cli_build = || {
+            ExecCli::new(
+                CMD_BUILD,
+                file_from_root("crates/cli/tests/test-projects/algorithms", "fibonacci.roc"),
+            )
+            .arg(BUILD_HOST_FLAG)
+            .arg(SUPPRESS_BUILD_HOST_WARNING_FLAG)
+        };
+
+        // the first build fills the cache, unless an earlier one did already
+        cli_build().run().assert_success_with_no_unexpected_errors();
+
+        let cached_build = cli_build().run();
+        cached_build.assert_success_with_no_unexpected_errors();
+        assert!(
+            cached_build
+                .stderr
+                .contains(roc_build::program::PREPROCESSED_HOST_CACHED),
+            "the unchanged host was preprocessed again:\n{}",
+            cached_build.stderr
+        );
+
+        let uncached_build = cli_build().arg(NO_CACHE_FLAG).run();
+        uncached_build.assert_success_with_no_unexpected_errors();
+        assert!(!uncached_build
+            .stderr
+            .contains(roc_build::program::PREPROCESSED_HOST_CACHED));
+    }
+
     #[test]
     #[cfg_attr(windows, ignore)]
     fn quicksort() {
@@ -235,6 +1337,64 @@ mod cli_tests {
         );
     }
 
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn filtered_expects() {
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root(
+                "crates/cli/tests/test-projects/expects_transitive",
+                "main.roc",
+            ),
+        )
+        .add_args([FILTER_FLAG, "add_and_stringify"]);
+
+        let cli_test_out = cli_test.run();
+        cli_test_out.assert_clean_success();
+        cli_test_out.assert_stdout_and_stderr_ends_with(
+            "0 failed and 2 passed in <ignored for test> ms.\nran 2 of 3 expects (filtered)\n",
+        );
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn module_expects_only() {
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root(
+                "crates/cli/tests/test-projects/expects_transitive",
+                "Direct.roc",
+            ),
+        );
+
+        // the expect in Transitive.roc, which Direct.roc imports, is not run
+        let cli_test_out = cli_test.run();
+        cli_test_out.assert_clean_success();
+        cli_test_out.assert_stdout_and_stderr_ends_with(
+            "0 failed and 2 passed in <ignored for test> ms.\n",
+        );
+    }
+
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn expect_message_names_failing_case() {
+        let cli_test = ExecCli::new(
+            CMD_TEST,
+            file_from_root(
+                "crates/cli/tests/test-projects/expects_message",
+                "Clamp.roc",
+            ),
+        );
+
+        let cli_test_out = cli_test.run();
+        cli_test_out.assert_nonzero_exit();
+
+        // only the case that fails is reported, under its message
+        let output = cli_test_out.normalize_stdout_and_stderr();
+        assert!(output.contains("clamp 15"), "{output}");
+        assert!(!output.contains("clamp 5"), "{output}");
+    }
+
     #[test]
     #[cfg_attr(windows, ignore)]
     fn transitive_expects_verbose() {
@@ -392,6 +1552,50 @@ mod cli_tests {
                 .assert_stdout_and_stderr_ends_with(expected_out);
         }
 
+        #[test]
+        #[cfg_attr(windows, ignore)]
+        fn problems_are_reported_in_the_same_order_with_any_number_of_threads() {
+            const MODULE_COUNT: usize = 50;
+
+            let dir = tempfile::tempdir().unwrap();
+            let mut main = String::from("module [main]\n\n");
+
+            // every module has a type error and a warning
+            for index in 0..MODULE_COUNT {
+                std::fs::write(
+                    dir.path().join(format!("Mod{index}.roc")),
+                    format!(
+                        "module [run]\n\nrun : U64 -> Str\nrun = \\n ->\n    unused = n + {index}\n    n\n"
+                    ),
+                )
+                .unwrap();
+
+                main.push_str(&format!("import Mod{index}\n"));
+            }
+
+            main.push_str("\nmain = Mod0.run\n");
+            std::fs::write(dir.path().join("Main.roc"), main).unwrap();
+
+            let check = |threads: usize| {
+                ExecCli::new(CMD_CHECK, dir.path().join("Main.roc"))
+                    .arg(NO_CACHE_FLAG)
+                    .arg(format!("--{}={threads}", roc_cli::FLAG_MAX_THREADS))
+                    .run()
+                    .normalize_stdout_and_stderr()
+            };
+
+            let single_threaded = check(1);
+
+            assert!(
+                single_threaded.contains(&format!("{MODULE_COUNT} errors")),
+                "{single_threaded}"
+            );
+
+            for _ in 0..3 {
+                assert_eq!(check(16), single_threaded);
+            }
+        }
+
         #[test]
         #[cfg_attr(windows, ignore)]
         // https://github.com/roc-lang/roc/issues/5514
@@ -842,6 +2046,25 @@ mod cli_tests {
 
             insta::assert_snapshot!(cli_test_out.normalize_stdout_and_stderr());
         }
+
+        #[test]
+        #[cfg_attr(windows, ignore)]
+        fn dbg_custom_inspector() {
+            build_platform_host();
+
+            let cli_dev = ExecCli::new(
+                CMD_DEV,
+                file_from_root(
+                    "crates/cli/tests/test-projects/inspect",
+                    "custom_inspector.roc",
+                ),
+            );
+
+            let cli_dev_out = cli_dev.run();
+            cli_dev_out.assert_clean_success();
+
+            insta::assert_snapshot!(cli_dev_out.normalize_stdout_and_stderr());
+        }
     }
 
     mod test_platform_effects_zig {
@@ -993,6 +2216,24 @@ mod cli_tests {
             cli_dev.run().assert_clean_stdout(expected_out);
         }
 
+        #[test]
+        #[cfg_attr(windows, ignore)]
+        fn effectful_args_after_separator() {
+            build_platform_host();
+
+            let expected_out = "--input\ndata.csv\n-v\n";
+
+            for sub_command in [roc_cli::CMD_DEV, roc_cli::CMD_RUN] {
+                let cli_cmd = ExecCli::new(
+                    sub_command,
+                    file_from_root("crates/cli/tests/test-projects/effectful/", "args.roc"),
+                )
+                .add_args(["--", "--input", "data.csv", "-v"]);
+
+                cli_cmd.run().assert_clean_stdout(expected_out);
+            }
+        }
+
         #[test]
         #[cfg_attr(windows, ignore)]
         fn effectful_ignore_result() {
@@ -1402,6 +2643,30 @@ mod cli_tests {
                 UseValgrind::Yes,
             );
         }
+
+        #[test]
+        #[cfg_attr(windows, ignore = "Command failed Exit Code: exit code: 0xc0000005")]
+        fn sort_records() {
+            let expected_output = "Please enter an integer\n818783102771087332\n";
+            test_benchmark(
+                "sort_records.roc",
+                expected_output,
+                Some("1000"),
+                UseValgrind::Yes,
+            );
+        }
+
+        #[test]
+        #[cfg_attr(windows, ignore = "Command failed Exit Code: exit code: 0xc0000005")]
+        fn drop_lists() {
+            let expected_output = "Please enter an integer\n4102893\n";
+            test_benchmark(
+                "drop_lists.roc",
+                expected_output,
+                Some("1000"),
+                UseValgrind::Yes,
+            );
+        }
     }
 
     #[test]
@@ -1511,6 +2776,69 @@ mod cli_tests {
         insta::assert_snapshot!(cli_check_out.normalize_stdout_and_stderr());
     }
 
+    #[test]
+    fn unused_import_as_json() {
+        let cli_check = ExecCli::new(
+            CMD_CHECK,
+            file_from_root(
+                "crates/cli/tests/test-projects/known_bad",
+                "UnusedImport.roc",
+            ),
+        )
+        .arg(JSON_ERRORS_FLAG);
+
+        let cli_check_out = cli_check.run();
+
+        // the same exit code as without JSON: only warnings were found
+        assert_eq!(cli_check_out.status.code(), Some(2), "{cli_check_out}");
+
+        let lines: Vec<&str> = cli_check_out.stdout.lines().collect();
+
+        assert_eq!(lines.len(), 1, "{cli_check_out}");
+        assert!(lines[0].starts_with(r#"{"file":""#), "{}", lines[0]);
+        assert!(
+            lines[0].contains(r#""severity":"warning","code":"unused-import","#),
+            "{}",
+            lines[0]
+        );
+        assert!(
+            lines[0].contains(r#""span":{"start":{"offset":"#),
+            "{}",
+            lines[0]
+        );
+    }
+
+    #[test]
+    fn unused_import_color() {
+        let cli_check = |color: &str| {
+            ExecCli::new(
+                CMD_CHECK,
+                file_from_root(
+                    "crates/cli/tests/test-projects/known_bad",
+                    "UnusedImport.roc",
+                ),
+            )
+            .arg(COLOR_FLAG)
+            .arg(color)
+            .run()
+        };
+
+        let always = cli_check("always");
+        assert!(always.stdout.contains('\u{1b}'), "{always}");
+
+        // the output is not a terminal, so auto leaves colors out too
+        for color in ["never", "auto"] {
+            let cli_check_out = cli_check(color);
+
+            assert_eq!(cli_check_out.status.code(), Some(2), "{cli_check_out}");
+            assert!(
+                cli_check_out.stdout.contains("UNUSED IMPORT"),
+                "{cli_check_out}"
+            );
+            assert!(!cli_check_out.stdout.contains('\u{1b}'), "{cli_check_out}");
+        }
+    }
+
     #[test]
     fn format_check_good() {
         ExecCli::new(
@@ -0,0 +1,5 @@
+app [main] { pf: platform "platform/main.roc" }
+
+# The host was built against old-platform, whose main returns a U32,
+# so it has to refuse to run this app.
+main = \n -> n + 1
@@ -0,0 +1,9 @@
+platform "abi-version"
+    requires {} { main : U64 -> U32 }
+    exposes []
+    packages {}
+    imports []
+    provides [main_for_host]
+
+main_for_host : U64 -> U32
+main_for_host = \n -> main(n)
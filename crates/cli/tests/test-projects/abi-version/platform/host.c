@@ -0,0 +1,40 @@
+// A host built against an older version of the platform, to check that the ABI version
+// in the header it was built with stops it from running an app built for the new one.
+//
+// build with: roc gen-header --output roc_app.h old-platform/main.roc
+//             clang -I . platform/host.c app.o
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+#include "roc_app.h"
+
+void* roc_alloc(size_t size, uint32_t alignment) { return malloc(size); }
+
+void* roc_realloc(void* ptr, size_t new_size, size_t old_size, uint32_t alignment) {
+  return realloc(ptr, new_size);
+}
+
+void roc_dealloc(void* ptr, uint32_t alignment) { free(ptr); }
+
+// the app never runs, so these do not need to decode the strings
+void roc_panic(RocStr* msg, uint32_t tag_id) {
+  fprintf(stderr, "Application crashed\n");
+  exit(1);
+}
+
+void roc_dbg(RocStr* loc, RocStr* msg, RocStr* src) {}
+
+void* roc_memset(void* str, int c, size_t n) { return memset(str, c, n); }
+
+int roc_getppid() { return getppid(); }
+
+int main() {
+  uint32_t result;
+
+  roc__main_for_host_1_exposed_generic(&result, 41);
+
+  return result == 42 ? 0 : 1;
+}
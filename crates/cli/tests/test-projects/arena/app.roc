@@ -0,0 +1,10 @@
+app [handle] { pf: platform "platform/main.roc" }
+
+# Builds a list, a list of strings and a string for every request, which the platform all
+# allocates in the arena of the request.
+handle : U64 -> U64
+handle = \n ->
+    List.range({ start: At(0), end: Before(n) })
+    |> List.map(Num.to_str)
+    |> Str.join_with(",")
+    |> Str.count_utf8_bytes
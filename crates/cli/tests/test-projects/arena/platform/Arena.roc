@@ -0,0 +1,5 @@
+hosted [with_arena!]
+
+## Runs the function with everything it allocates in an arena, which is freed at once when it
+## returns. The compiler implements this, so the host does not define `roc_fx_with_arena`.
+with_arena! : ({} => a) => a
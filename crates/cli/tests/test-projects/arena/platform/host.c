@@ -0,0 +1,55 @@
+// A host that handles a few requests, each in an arena, and counts the calls to the allocator.
+//
+// build with: clang platform/host.c app.o
+
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+static unsigned long allocs = 0;
+static unsigned long deallocs = 0;
+
+void* roc_alloc(size_t size, unsigned int alignment) {
+  allocs++;
+  return malloc(size);
+}
+
+void* roc_realloc(void* ptr, size_t new_size, size_t old_size, unsigned int alignment) {
+  allocs++;
+  return realloc(ptr, new_size);
+}
+
+void roc_dealloc(void* ptr, unsigned int alignment) {
+  deallocs++;
+  free(ptr);
+}
+
+void roc_panic(void* msg, unsigned int tag_id) {
+  fprintf(stderr, "Application crashed\n");
+  exit(1);
+}
+
+void roc_dbg(char* loc, char* msg, char* src) {
+  fprintf(stderr, "[%s] %s = %s\n", loc, src, msg);
+}
+
+void* roc_memset(void* str, int c, size_t n) { return memset(str, c, n); }
+
+int roc_getppid() { return getppid(); }
+
+extern uint64_t roc__handle_for_host_1_exposed(uint64_t n);
+
+int main() {
+  uint64_t bytes = 0;
+
+  for (int request = 0; request < 10; request++) {
+    bytes += roc__handle_for_host_1_exposed(100);
+  }
+
+  printf("bytes: %llu, allocs: %lu, deallocs: %lu\n", (unsigned long long)bytes, allocs,
+         deallocs);
+
+  return 0;
+}
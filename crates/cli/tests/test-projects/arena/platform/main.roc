@@ -0,0 +1,11 @@
+platform "arena"
+    requires {} { handle : U64 -> U64 }
+    exposes []
+    packages {}
+    imports []
+    provides [handle_for_host!]
+
+import Arena
+
+handle_for_host! : U64 => U64
+handle_for_host! = \n -> Arena.with_arena!(\{} -> handle(n))
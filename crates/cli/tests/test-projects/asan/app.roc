@@ -0,0 +1,5 @@
+app [main] { pf: platform "platform/main.roc" }
+
+# The host hands us a list that claims to have one more element than it allocated,
+# so this writes one byte past the end of the allocation.
+main = \list -> List.set(list, 3, 42)
@@ -0,0 +1,56 @@
+// A host with a deliberate bug in its glue code, to check that the AddressSanitizer
+// instrumentation of the Roc code catches it.
+//
+// build with: clang -fsanitize=address platform/host.c app.o
+
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+void* roc_alloc(size_t size, unsigned int alignment) { return malloc(size); }
+
+void* roc_realloc(void* ptr, size_t new_size, size_t old_size, unsigned int alignment) {
+  return realloc(ptr, new_size);
+}
+
+void roc_dealloc(void* ptr, unsigned int alignment) { free(ptr); }
+
+void roc_panic(void* ptr, unsigned int alignment) {
+  char* msg = (char*)ptr;
+  fprintf(stderr,
+          "Application crashed with message\n\n    %s\n\nShutting down\n", msg);
+  exit(1);
+}
+
+void roc_dbg(char* loc, char* msg, char* src) {
+  fprintf(stderr, "[%s] %s = %s\n", loc, src, msg);
+}
+
+void* roc_memset(void* str, int c, size_t n) { return memset(str, c, n); }
+
+int roc_getppid() { return getppid(); }
+
+struct RocList {
+  uint8_t* bytes;
+  size_t len;
+  size_t capacity;
+};
+
+extern struct RocList roc__main_for_host_1_exposed(struct RocList list);
+
+int main() {
+  // the refcount is stored right before the elements
+  uint8_t* allocation = malloc(sizeof(intptr_t) + 3);
+
+  // a refcount of 1 means the list is unique, so Roc updates it in place
+  *(intptr_t*)allocation = 1;
+
+  // BUG: the list claims to hold 4 elements, but there is only room for 3
+  struct RocList list = {allocation + sizeof(intptr_t), 4, 4};
+
+  struct RocList result = roc__main_for_host_1_exposed(list);
+
+  return result.len == 4 ? 0 : 1;
+}
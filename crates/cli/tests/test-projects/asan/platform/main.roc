@@ -0,0 +1,9 @@
+platform "asan"
+    requires {} { main : List U8 -> List U8 }
+    exposes []
+    packages {}
+    imports []
+    provides [main_for_host]
+
+main_for_host : List U8 -> List U8
+main_for_host = \list -> main(list)
@@ -0,0 +1,13 @@
+app [main] { pf: platform "../lto/platform/main.roc" }
+
+# too long to be a small string, so its bytes are a constant global
+greeting = "This string is much too long to be stored as a small string."
+
+# a list of constant numbers is stored as a constant global as well
+table = [3, 1, 4, 1, 5, 9, 2, 6]
+
+main = \n ->
+    byte = Str.to_utf8 greeting |> List.get (n % 60) |> Result.with_default 0
+    entry = List.get table (n % 8) |> Result.with_default 0
+
+    Num.to_u64 byte + entry
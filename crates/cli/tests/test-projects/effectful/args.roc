@@ -0,0 +1,8 @@
+app [main!] { pf: platform "../test-platform-effects-zig/main.roc" }
+
+import pf.Effect
+
+main! : {} => {}
+main! = \{} ->
+    Effect.args!({})
+    |> List.for_each!(Effect.put_line!)
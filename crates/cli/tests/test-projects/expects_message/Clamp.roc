@@ -0,0 +1,19 @@
+module [clamp]
+
+clamp = \n, low, high ->
+    if n < low then
+        low
+    else if n > high then
+        low
+    else
+        n
+
+check = \n, expected ->
+    actual = clamp(n, 0, 10)
+
+    expect actual == expected, "clamp ${Num.to_str(n)}"
+
+    actual == expected
+
+expect
+    List.all([(-5, 0), (5, 5), (15, 10)], \(n, expected) -> check(n, expected))
@@ -0,0 +1,18 @@
+app [main] { pf: platform "../test-platform-simple-zig/main.roc" }
+
+Meters := U64 implements [Inspect { to_inspector: meters_to_inspector }]
+
+meters_to_inspector : Meters -> Inspector f where f implements InspectFormatter
+meters_to_inspector = |@Meters(n)| Inspect.str("${Num.to_str(n)}m")
+
+# does not implement Inspect, so it is shown as <opaque>
+Secret := Str
+
+main =
+    distance = @Meters(42)
+    dbg distance
+
+    route = { from: "home", legs: [@Meters(3), @Meters(5)], last: Leg(@Meters(5)), key: @Secret("hunter2") }
+    dbg route
+
+    "Program finished!\n"
@@ -0,0 +1,4 @@
+module [step]
+
+step : U64 -> U64
+step = \n -> Num.add_wrap (Num.mul_wrap n 6364136223846793005) 1442695040888963407
@@ -0,0 +1,5 @@
+app [main] { pf: platform "platform/main.roc" }
+
+import Checksum
+
+main = \n -> Checksum.step n
@@ -0,0 +1,45 @@
+// A host that calls into the app in a loop, to check that `--lto` lets the linker inline the app
+// into the host.
+//
+// build with: clang -O3 -flto -fuse-ld=lld platform/host.c app.bc
+
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+void* roc_alloc(size_t size, unsigned int alignment) { return malloc(size); }
+
+void* roc_realloc(void* ptr, size_t new_size, size_t old_size, unsigned int alignment) {
+  return realloc(ptr, new_size);
+}
+
+void roc_dealloc(void* ptr, unsigned int alignment) { free(ptr); }
+
+void roc_panic(void* msg, unsigned int tag_id) {
+  fprintf(stderr, "Application crashed\n");
+  exit(1);
+}
+
+void roc_dbg(char* loc, char* msg, char* src) {
+  fprintf(stderr, "[%s] %s = %s\n", loc, src, msg);
+}
+
+void* roc_memset(void* str, int c, size_t n) { return memset(str, c, n); }
+
+int roc_getppid() { return getppid(); }
+
+extern uint64_t roc__main_for_host_1_exposed(uint64_t n);
+
+int main() {
+  uint64_t state = 0;
+
+  for (int i = 0; i < 3; i++) {
+    state = roc__main_for_host_1_exposed(state);
+  }
+
+  printf("%llu\n", (unsigned long long)state);
+
+  return 0;
+}
@@ -0,0 +1,5 @@
+app [main] { pf: platform "platform/main.roc" }
+
+# The host hands us a list whose elements are not 8-byte aligned,
+# so reading them is a misaligned access.
+main = \list -> List.sum(list)
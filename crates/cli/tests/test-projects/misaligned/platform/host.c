@@ -0,0 +1,57 @@
+// A host with a deliberate bug in its glue code, to check that the alignment checks
+// in the Roc code catch it.
+//
+// build with: clang platform/host.c app.o
+
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+void* roc_alloc(size_t size, unsigned int alignment) { return malloc(size); }
+
+void* roc_realloc(void* ptr, size_t new_size, size_t old_size, unsigned int alignment) {
+  return realloc(ptr, new_size);
+}
+
+void roc_dealloc(void* ptr, unsigned int alignment) { free(ptr); }
+
+void roc_panic(void* ptr, unsigned int alignment) {
+  char* msg = (char*)ptr;
+  fprintf(stderr,
+          "Application crashed with message\n\n    %s\n\nShutting down\n", msg);
+  exit(1);
+}
+
+void roc_dbg(char* loc, char* msg, char* src) {
+  fprintf(stderr, "[%s] %s = %s\n", loc, src, msg);
+}
+
+void* roc_memset(void* str, int c, size_t n) { return memset(str, c, n); }
+
+int roc_getppid() { return getppid(); }
+
+struct RocList {
+  uint64_t* elements;
+  size_t len;
+  size_t capacity;
+};
+
+extern uint64_t roc__main_for_host_1_exposed(struct RocList list);
+
+int main() {
+  // the refcount is stored right before the elements
+  uint8_t* allocation = malloc(sizeof(intptr_t) + 1 + 3 * sizeof(uint64_t));
+
+  // BUG: the elements start one byte too late, so they are not 8-byte aligned
+  uint8_t* elements = allocation + sizeof(intptr_t) + 1;
+
+  // a refcount of 1 means the list is unique
+  memcpy(elements - sizeof(intptr_t), &(intptr_t){1}, sizeof(intptr_t));
+  memset(elements, 0, 3 * sizeof(uint64_t));
+
+  struct RocList list = {(uint64_t*)elements, 3, 3};
+
+  return roc__main_for_host_1_exposed(list) == 0 ? 0 : 1;
+}
@@ -0,0 +1,9 @@
+platform "misaligned"
+    requires {} { main : List U64 -> U64 }
+    exposes []
+    packages {}
+    imports []
+    provides [main_for_host]
+
+main_for_host : List U64 -> U64
+main_for_host = \list -> main(list)
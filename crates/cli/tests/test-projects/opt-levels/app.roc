@@ -0,0 +1,7 @@
+app [main] { pf: platform "platform/main.roc" }
+
+main : U64 -> U64
+main = \n ->
+    List.range({ start: At 0, end: Before n })
+    |> List.map(\x -> Num.mul_wrap(x, 31) |> Num.bitwise_xor(x))
+    |> List.walk(0, Num.add_wrap)
@@ -0,0 +1,4 @@
+app [main] { pf: platform "platform/main.roc" }
+
+# The host passes in 255, so adding one overflows.
+main = \n -> n + 1
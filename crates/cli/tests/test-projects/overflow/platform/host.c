@@ -0,0 +1,47 @@
+// A host that makes the Roc code overflow, to check that `--overflow=trap` reports where.
+// It does not define `__roc_overflow_trap`, so the message goes to `roc_panic`.
+//
+// build with: clang platform/host.c app.o
+
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+void* roc_alloc(size_t size, unsigned int alignment) { return malloc(size); }
+
+void* roc_realloc(void* ptr, size_t new_size, size_t old_size, unsigned int alignment) {
+  return realloc(ptr, new_size);
+}
+
+void roc_dealloc(void* ptr, unsigned int alignment) { free(ptr); }
+
+struct RocStr {
+  char* bytes;
+  size_t len;
+  size_t capacity;
+};
+
+void roc_panic(struct RocStr* msg, unsigned int tag_id) {
+  // overflow messages are too long to be small strings
+  fprintf(stderr, "Application crashed with message\n\n    %.*s\n\nShutting down\n",
+          (int)msg->len, msg->bytes);
+  exit(1);
+}
+
+void roc_dbg(char* loc, char* msg, char* src) {
+  fprintf(stderr, "[%s] %s = %s\n", loc, src, msg);
+}
+
+void* roc_memset(void* str, int c, size_t n) { return memset(str, c, n); }
+
+int roc_getppid() { return getppid(); }
+
+extern uint8_t roc__main_for_host_1_exposed(uint8_t n);
+
+int main() {
+  printf("%d\n", roc__main_for_host_1_exposed(255));
+
+  return 0;
+}
@@ -0,0 +1,9 @@
+platform "overflow"
+    requires {} { main : U8 -> U8 }
+    exposes []
+    packages {}
+    imports []
+    provides [main_for_host]
+
+main_for_host : U8 -> U8
+main_for_host = \n -> main(n)
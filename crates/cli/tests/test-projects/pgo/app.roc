@@ -0,0 +1,34 @@
+app [main] { pf: platform "platform/main.roc" }
+
+# The host never passes in 0, so the `then` branch is cold and the `else` branch is hot.
+main = \n ->
+    if n == 0 then
+        Num.bitwise_xor (mix (Num.add_wrap n 1)) 1
+    else
+        mix n
+
+# Too large to be inlined at both call sites without a profile
+mix : U64 -> U64
+mix = \x0 ->
+    x1 = Num.mul_wrap (Num.bitwise_xor x0 (Num.shift_right_zf_by x0 29)) 0xc4ceb9fe1a85ec53
+    x2 = Num.mul_wrap (Num.bitwise_xor x1 (Num.shift_right_zf_by x1 31)) 0x9e3779b97f4a7c15
+    x3 = Num.mul_wrap (Num.bitwise_xor x2 (Num.shift_right_zf_by x2 27)) 0xbf58476d1ce4e5b9
+    x4 = Num.mul_wrap (Num.bitwise_xor x3 (Num.shift_right_zf_by x3 30)) 0x94d049bb133111eb
+    x5 = Num.mul_wrap (Num.bitwise_xor x4 (Num.shift_right_zf_by x4 33)) 0xff51afd7ed558ccd
+    x6 = Num.mul_wrap (Num.bitwise_xor x5 (Num.shift_right_zf_by x5 29)) 0xc4ceb9fe1a85ec53
+    x7 = Num.mul_wrap (Num.bitwise_xor x6 (Num.shift_right_zf_by x6 31)) 0x9e3779b97f4a7c15
+    x8 = Num.mul_wrap (Num.bitwise_xor x7 (Num.shift_right_zf_by x7 27)) 0xbf58476d1ce4e5b9
+    x9 = Num.mul_wrap (Num.bitwise_xor x8 (Num.shift_right_zf_by x8 30)) 0x94d049bb133111eb
+    x10 = Num.mul_wrap (Num.bitwise_xor x9 (Num.shift_right_zf_by x9 33)) 0xff51afd7ed558ccd
+    x11 = Num.mul_wrap (Num.bitwise_xor x10 (Num.shift_right_zf_by x10 29)) 0xc4ceb9fe1a85ec53
+    x12 = Num.mul_wrap (Num.bitwise_xor x11 (Num.shift_right_zf_by x11 31)) 0x9e3779b97f4a7c15
+    x13 = Num.mul_wrap (Num.bitwise_xor x12 (Num.shift_right_zf_by x12 27)) 0xbf58476d1ce4e5b9
+    x14 = Num.mul_wrap (Num.bitwise_xor x13 (Num.shift_right_zf_by x13 30)) 0x94d049bb133111eb
+    x15 = Num.mul_wrap (Num.bitwise_xor x14 (Num.shift_right_zf_by x14 33)) 0xff51afd7ed558ccd
+    x16 = Num.mul_wrap (Num.bitwise_xor x15 (Num.shift_right_zf_by x15 29)) 0xc4ceb9fe1a85ec53
+    x17 = Num.mul_wrap (Num.bitwise_xor x16 (Num.shift_right_zf_by x16 31)) 0x9e3779b97f4a7c15
+    x18 = Num.mul_wrap (Num.bitwise_xor x17 (Num.shift_right_zf_by x17 27)) 0xbf58476d1ce4e5b9
+    x19 = Num.mul_wrap (Num.bitwise_xor x18 (Num.shift_right_zf_by x18 30)) 0x94d049bb133111eb
+    x20 = Num.mul_wrap (Num.bitwise_xor x19 (Num.shift_right_zf_by x19 33)) 0xff51afd7ed558ccd
+
+    x20
@@ -0,0 +1,44 @@
+// A host that only ever takes the hot path of the app, to collect a profile for `--pgo-use`.
+//
+// build with: clang -fprofile-instr-generate platform/host.c app.o
+
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+void* roc_alloc(size_t size, unsigned int alignment) { return malloc(size); }
+
+void* roc_realloc(void* ptr, size_t new_size, size_t old_size, unsigned int alignment) {
+  return realloc(ptr, new_size);
+}
+
+void roc_dealloc(void* ptr, unsigned int alignment) { free(ptr); }
+
+void roc_panic(void* msg, unsigned int tag_id) {
+  fprintf(stderr, "Application crashed\n");
+  exit(1);
+}
+
+void roc_dbg(char* loc, char* msg, char* src) {
+  fprintf(stderr, "[%s] %s = %s\n", loc, src, msg);
+}
+
+void* roc_memset(void* str, int c, size_t n) { return memset(str, c, n); }
+
+int roc_getppid() { return getppid(); }
+
+extern uint64_t roc__main_for_host_1_exposed(uint64_t n);
+
+int main() {
+  uint64_t hash = 0;
+
+  for (uint64_t n = 1; n <= 10000; n++) {
+    hash ^= roc__main_for_host_1_exposed(n);
+  }
+
+  printf("%llu\n", (unsigned long long)hash);
+
+  return 0;
+}
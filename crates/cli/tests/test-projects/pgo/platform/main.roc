@@ -0,0 +1,9 @@
+platform "pgo"
+    requires {} { main : U64 -> U64 }
+    exposes []
+    packages {}
+    imports []
+    provides [main_for_host]
+
+main_for_host : U64 -> U64
+main_for_host = \n -> main(n)
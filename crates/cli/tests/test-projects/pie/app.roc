@@ -0,0 +1,10 @@
+app [main] { pf: platform "../lto/platform/main.roc" }
+
+# the list holds pointers to the bytes of its strings, which the loader has to relocate when the
+# executable is position-independent
+names = ["a string that is too long to be a small string", "another string that is too long as well"]
+
+main = |n|
+    name = List.get(names, n % 2) |> Result.with_default("")
+
+    n + 1 + Str.count_utf8_bytes(name)
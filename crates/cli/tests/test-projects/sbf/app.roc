@@ -0,0 +1,3 @@
+app [main] { pf: platform "platform/main.roc" }
+
+main = \n -> n * 2
@@ -0,0 +1,58 @@
+// A Solana program host. The runtime calls `entrypoint`, which calls the app.
+// It has no writable globals, since the loader does not map them.
+//
+// build with: clang --target=sbf -O2 -fPIC -c platform/host.c -o platform/sbf.o
+
+#include <stddef.h>
+#include <stdint.h>
+
+// the runtime maps the heap of every program at the same address
+#define HEAP_START 0x300000000
+#define HEAP_LENGTH (32 * 1024)
+
+extern void sol_panic_(const char* file, uint64_t len, uint64_t line, uint64_t column);
+
+// A bump allocator that keeps its position in the first word of the heap, and allocates
+// downwards from the end of it.
+void* roc_alloc(size_t size, unsigned int alignment) {
+  uint64_t* position = (uint64_t*)HEAP_START;
+
+  if (*position == 0) {
+    *position = HEAP_START + HEAP_LENGTH;
+  }
+
+  if (size > *position - HEAP_START - sizeof(uint64_t)) {
+    sol_panic_("host.c", 6, __LINE__, 0);
+  }
+
+  uint64_t start = (*position - size) & ~((uint64_t)alignment - 1);
+
+  if (start < HEAP_START + sizeof(uint64_t)) {
+    sol_panic_("host.c", 6, __LINE__, 0);
+  }
+
+  *position = start;
+
+  return (void*)start;
+}
+
+void* roc_realloc(void* ptr, size_t new_size, size_t old_size, unsigned int alignment) {
+  uint8_t* new_ptr = roc_alloc(new_size, alignment);
+  size_t len = old_size < new_size ? old_size : new_size;
+
+  for (size_t i = 0; i < len; i++) {
+    new_ptr[i] = ((uint8_t*)ptr)[i];
+  }
+
+  return new_ptr;
+}
+
+// the heap only lives as long as the instruction, so it is never freed
+void roc_dealloc(void* ptr, unsigned int alignment) {}
+
+extern uint64_t roc__main_for_host_1_exposed(uint64_t n);
+
+uint64_t entrypoint(uint8_t* input) {
+  // zero means success
+  return roc__main_for_host_1_exposed(21) == 42 ? 0 : 1;
+}
@@ -0,0 +1,3 @@
+app [main] { pf: platform "../lto/platform/main.roc" }
+
+main = |n| List.sum(List.range({ start: At(1), end: At(n) }))
@@ -0,0 +1,62 @@
+// A program that loads an app built with `roc build --lib` at runtime, to check that the shared
+// library exports the functions the host calls.
+//
+// build with: clang -rdynamic driver.c -ldl
+// run with: ./a.out libapp.so
+
+#include <dlfcn.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+
+// the app calls these, so they are exported from the driver with -rdynamic
+
+void* roc_alloc(size_t size, unsigned int alignment) { return malloc(size); }
+
+void* roc_realloc(void* ptr, size_t new_size, size_t old_size, unsigned int alignment) {
+  return realloc(ptr, new_size);
+}
+
+void roc_dealloc(void* ptr, unsigned int alignment) { free(ptr); }
+
+void roc_panic(void* msg, unsigned int tag_id) {
+  fprintf(stderr, "Application crashed\n");
+  exit(1);
+}
+
+void roc_dbg(char* loc, char* msg, char* src) {
+  fprintf(stderr, "[%s] %s = %s\n", loc, src, msg);
+}
+
+void* roc_memset(void* str, int c, size_t n) { return memset(str, c, n); }
+
+int roc_getppid() { return getppid(); }
+
+int main(int argc, char** argv) {
+  if (argc != 2) {
+    fprintf(stderr, "usage: %s <library>\n", argv[0]);
+    return 1;
+  }
+
+  void* app = dlopen(argv[1], RTLD_NOW | RTLD_LOCAL);
+
+  if (app == NULL) {
+    fprintf(stderr, "%s\n", dlerror());
+    return 1;
+  }
+
+  uint64_t (*main_for_host)(uint64_t) = dlsym(app, "roc__main_for_host_1_exposed");
+
+  if (main_for_host == NULL) {
+    fprintf(stderr, "%s\n", dlerror());
+    return 1;
+  }
+
+  printf("%llu\n", (unsigned long long)main_for_host(20));
+
+  dlclose(app);
+
+  return 0;
+}
@@ -1,7 +1,9 @@
-hosted [put_line!, get_line!, id_effectful!]
+hosted [put_line!, get_line!, id_effectful!, args!]
 
 put_line! : Str => {}
 
 get_line! : {} => Str
 
 id_effectful! : U64 => U64
+
+args! : {} => List Str
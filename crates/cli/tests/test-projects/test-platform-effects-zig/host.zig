@@ -2,6 +2,8 @@ const std = @import("std");
 const builtin = @import("builtin");
 const str = @import("glue/str.zig");
 const RocStr = str.RocStr;
+const list = @import("glue/list.zig");
+const RocList = list.RocList;
 const testing = std.testing;
 const expectEqual = testing.expectEqual;
 const expect = testing.expect;
@@ -107,7 +109,11 @@ comptime {
 
 const Unit = extern struct {};
 
-pub export fn main() u8 {
+var program_args: []const [*:0]u8 = &.{};
+
+pub export fn main(argc: c_int, argv: [*][*:0]u8) u8 {
+    program_args = argv[0..@as(usize, @intCast(argc))];
+
     const allocator = std.heap.page_allocator;
 
     // NOTE the return size can be zero, which will segfault. Always allocate at least 8 bytes
@@ -137,6 +143,24 @@ fn roc_fx_get_line_help() !RocStr {
     return str.RocStr.init(@as([*]const u8, @ptrCast(line)), line.len);
 }
 
+/// The arguments the program was started with, without the program itself
+pub export fn roc_fx_args() RocList {
+    const allocator = std.heap.page_allocator;
+
+    if (program_args.len <= 1) {
+        return RocList.empty();
+    }
+
+    const args = allocator.alloc(RocStr, program_args.len - 1) catch unreachable;
+    defer allocator.free(args);
+
+    for (program_args[1..], args) |arg, *roc_arg| {
+        roc_arg.* = RocStr.fromSlice(mem.span(arg));
+    }
+
+    return RocList.fromSlice(RocStr, args, true);
+}
+
 pub export fn roc_fx_put_line(rocPath: *str.RocStr) i64 {
     const stdout = std.io.getStdOut().writer();
 
@@ -0,0 +1,3 @@
+app [main] { pf: platform "platform/main.roc" }
+
+main = \a, b -> a * 2 + b
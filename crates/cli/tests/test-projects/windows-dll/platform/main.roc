@@ -0,0 +1,9 @@
+platform "windows-dll"
+    requires {} { main : I64, I64 -> I64 }
+    exposes []
+    packages {}
+    imports []
+    provides [main_for_host]
+
+main_for_host : I64, I64 -> I64
+main_for_host = \a, b -> main(a, b)
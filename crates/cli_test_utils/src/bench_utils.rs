@@ -131,3 +131,21 @@ pub fn bench_quicksort<T: Measurement>(bench_group_opt: Option<&mut BenchmarkGro
         bench_group_opt,
     );
 }
+
+pub fn bench_sort_records<T: Measurement>(bench_group_opt: Option<&mut BenchmarkGroup<T>>) {
+    exec_bench_w_input(
+        &file_from_root("crates/cli/tests/benchmarks", "sort_records.roc"),
+        "100000",
+        "Please enter an integer\n8914438952622190350\n",
+        bench_group_opt,
+    );
+}
+
+pub fn bench_drop_lists<T: Measurement>(bench_group_opt: Option<&mut BenchmarkGroup<T>>) {
+    exec_bench_w_input(
+        &file_from_root("crates/cli/tests/benchmarks", "drop_lists.roc"),
+        "100000",
+        "Please enter an integer\n40010488895\n",
+        bench_group_opt,
+    );
+}
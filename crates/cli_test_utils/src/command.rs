@@ -125,6 +125,7 @@ pub fn assert_no_unexpected_error(stderr: &str) {
 
     let expected_errors = [
         "🔨 Building host ...\n",
+        "🔨 Reusing the preprocessed host from the cache ...\n",
         "ld: warning: -undefined dynamic_lookup may not work with chained fixups",
         "warning: ignoring debug info with an invalid version (0) in app\r\n",
     ];
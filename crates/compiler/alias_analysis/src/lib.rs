@@ -1165,7 +1165,7 @@ fn recursive_variant_types<'a>(
         NonRecursive(_) => {
             unreachable!()
         }
-        Recursive(tags) => {
+        Recursive(tags) | TaggedPointer { tags, .. } => {
             result = Vec::with_capacity(tags.len());
 
             for tag in tags.iter() {
@@ -1254,7 +1254,7 @@ fn expr_spec<'a>(
 
                     return builder.add_make_named(block, MOD_APP, type_name, value_id);
                 }
-                UnionLayout::Recursive(_) => data_id,
+                UnionLayout::Recursive(_) | UnionLayout::TaggedPointer { .. } => data_id,
                 UnionLayout::NullableWrapped { .. } => data_id,
                 UnionLayout::NullableUnwrapped { .. } => data_id,
             };
@@ -1290,7 +1290,8 @@ fn expr_spec<'a>(
             }
             UnionLayout::Recursive(_)
             | UnionLayout::NullableUnwrapped { .. }
-            | UnionLayout::NullableWrapped { .. } => {
+            | UnionLayout::NullableWrapped { .. }
+            | UnionLayout::TaggedPointer { .. } => {
                 let index = (*index) as u32;
                 let tag_value_id = env.symbols[structure];
 
@@ -1545,7 +1546,8 @@ fn layout_spec_help<'a>(
                 UnionLayout::Recursive(_)
                 | UnionLayout::NullableUnwrapped { .. }
                 | UnionLayout::NullableWrapped { .. }
-                | UnionLayout::NonNullableUnwrapped(_) => {
+                | UnionLayout::NonNullableUnwrapped(_)
+                | UnionLayout::TaggedPointer { .. } => {
                     let type_name_bytes = recursive_tag_union_name_bytes(&union_layout).as_bytes();
                     let type_name = TypeName(&type_name_bytes);
 
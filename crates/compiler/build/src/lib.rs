@@ -4,5 +4,6 @@
 #![allow(clippy::large_enum_variant)]
 pub mod link;
 pub mod llvm_passes;
+pub mod object_symbols;
 pub mod program;
 pub mod target;
@@ -1,8 +1,8 @@
 use crate::target::arch_str;
 use libloading::{Error, Library};
-use roc_command_utils::{cargo, clang, rustup, zig};
+use roc_command_utils::{cargo, clang, rustup, sbf_lld, zig};
 use roc_debug_flags;
-use roc_error_macros::internal_error;
+use roc_error_macros::{internal_error, user_error};
 use roc_mono::ir::OptLevel;
 use roc_target::{Architecture, OperatingSystem, Target};
 use std::collections::HashMap;
@@ -27,17 +27,51 @@ pub enum LinkingStrategy {
 }
 
 /// input_paths can include the host as well as the app. e.g. &["host.o", "roc_app.o"]
+///
+/// With `lto`, some of the inputs are LLVM bitcode, which the linker optimizes together with the
+/// rest of the program. This is only supported on Linux, where it needs `ld.lld`.
+///
+/// An executable gets a stack of `stack_size` bytes, if given, instead of the default of the
+/// target.
+///
+/// The system linker only links for the system. Other targets are linked with zig, which is how
+/// Windows is always linked.
 pub fn link(
     target: Target,
     output_path: PathBuf,
     input_paths: &[&str],
     link_type: LinkType,
+    lto: bool,
+    pie: bool,
+    stack_size: Option<u32>,
 ) -> io::Result<(Child, PathBuf)> {
+    if lto && target.operating_system() != OperatingSystem::Linux {
+        internal_error!("link-time optimization is only supported on Linux");
+    }
+
+    // the stack of a library is the one of the executable that loads it
+    let stack_size = stack_size.filter(|_| link_type == LinkType::Executable);
+
     match target.arch_os() {
-        (Architecture::Wasm32, _) => link_wasm32(target, output_path, input_paths, link_type),
-        (_, OperatingSystem::Linux) => link_linux(target, output_path, input_paths, link_type),
-        (_, OperatingSystem::Mac) => link_macos(target, output_path, input_paths, link_type),
-        (_, OperatingSystem::Windows) => link_windows(output_path, input_paths, link_type),
+        (Architecture::Wasm32, _) => {
+            link_wasm32(target, output_path, input_paths, link_type, stack_size)
+        }
+        (Architecture::Sbf, _) => link_sbf(output_path, input_paths, link_type),
+        (_, OperatingSystem::Linux) => link_linux(
+            target,
+            output_path,
+            input_paths,
+            link_type,
+            lto,
+            pie,
+            stack_size,
+        ),
+        (_, OperatingSystem::Mac) => {
+            link_macos(target, output_path, input_paths, link_type, stack_size)
+        }
+        (_, OperatingSystem::Windows) => {
+            link_windows(target, output_path, input_paths, link_type, stack_size)
+        }
         _ => internal_error!("TODO gracefully handle unsupported target: {:?}", target),
     }
 }
@@ -451,7 +485,11 @@ pub fn rebuild_host(
                 // This used to be "native" but that caused segfaults that were hard to
                 // reproduce and investigate.
                 // For context: github.com/roc-lang/roc/pull/6591#issuecomment-2039808944
-                "x86_64-native",
+                if target.is_native() {
+                    "x86_64-native"
+                } else {
+                    zig_target(target)
+                },
                 opt_level,
                 shared_lib_path,
                 builtins_host_tempfile.path(),
@@ -471,7 +509,11 @@ pub fn rebuild_host(
                 &env_home,
                 host_dest.to_str().unwrap(),
                 zig_host_src.to_str().unwrap(),
-                "native",
+                if target.is_native() {
+                    "native"
+                } else {
+                    zig_target(target)
+                },
                 opt_level,
                 shared_lib_path,
                 builtins_host_tempfile.path(),
@@ -804,6 +846,9 @@ fn link_linux(
     output_path: PathBuf,
     input_paths: &[&str],
     link_type: LinkType,
+    lto: bool,
+    pie: bool,
+    stack_size: Option<u32>,
 ) -> io::Result<(Child, PathBuf)> {
     let architecture = format!("{}-linux-gnu", target.architecture());
 
@@ -818,6 +863,10 @@ fn link_linux(
     //        .unwrap();
 
     if let Architecture::X86_32 = target.architecture() {
+        if lto {
+            internal_error!("link-time optimization is not supported on 32-bit Linux");
+        }
+
         return Ok((
             zig()
                 .args(["build-exe"])
@@ -828,11 +877,20 @@ fn link_linux(
                     "-lc",
                     &format!("-femit-bin={}", output_path.to_str().unwrap()),
                 ])
+                .args(zig_stack_args(stack_size))
                 .spawn()?,
             output_path,
         ));
     }
 
+    if !target.is_native() {
+        if lto {
+            internal_error!("link-time optimization is not supported when cross-compiling");
+        }
+
+        return link_cross(target, output_path, input_paths, link_type, pie, stack_size);
+    }
+
     let nix_paths_vec_string = nix_paths();
     let nix_paths_vec: Vec<PathBuf> = nix_paths_vec_string.iter().map(PathBuf::from).collect();
     let usr_lib_arch_path = strs_to_path(&["/usr", "lib", &architecture]);
@@ -862,21 +920,22 @@ fn link_linux(
     let crtn_name = "crtn.o";
     let crtn_path = look_for_library(&lib_dirs, crtn_name);
 
-    let scrt1_name = "Scrt1.o";
-    let scrt1_path = look_for_library(&lib_dirs, scrt1_name);
+    // the startup code of a position-independent executable is in Scrt1.o
+    let crt1_name = if pie { "Scrt1.o" } else { "crt1.o" };
+    let crt1_path = look_for_library(&lib_dirs, crt1_name);
 
     // Unwrap all the paths at once so we can inform the user of any missing libs
-    let (libgcc_path, crti_path, crtn_path, scrt1_path) =
-        match (libgcc_path, crti_path, crtn_path, scrt1_path) {
-            (Some(libgcc), Some(crti), Some(crtn), Some(scrt1)) => (libgcc, crti, crtn, scrt1),
-            (maybe_gcc, maybe_crti, maybe_crtn, maybe_scrt1) => {
+    let (libgcc_path, crti_path, crtn_path, crt1_path) =
+        match (libgcc_path, crti_path, crtn_path, crt1_path) {
+            (Some(libgcc), Some(crti), Some(crtn), Some(crt1)) => (libgcc, crti, crtn, crt1),
+            (maybe_gcc, maybe_crti, maybe_crtn, maybe_crt1) => {
                 if maybe_gcc.is_none() {
                     eprintln!("Couldn't find libgcc_s.so.1!");
                     eprintln!("You may need to install libgcc\n");
                 }
-                if maybe_crti.is_none() | maybe_crtn.is_none() | maybe_scrt1.is_none() {
+                if maybe_crti.is_none() | maybe_crtn.is_none() | maybe_crt1.is_none() {
                     eprintln!("Couldn't find the libc development files!");
-                    eprintln!("We need the files crti.o, crtn.o, and Scrt1.o");
+                    eprintln!("We need the files crti.o, crtn.o, and {crt1_name}");
                     eprintln!();
                     eprintln!("On Ubuntu/Debian execute:");
                     eprintln!("\tsudo apt install libc-dev\n");
@@ -902,11 +961,11 @@ fn link_linux(
             }
         };
 
-    let (libgcc_path_str, crti_path_str, crtn_path_str, scrt1_path_str) = (
+    let (libgcc_path_str, crti_path_str, crtn_path_str, crt1_path_str) = (
         libgcc_path.to_string_lossy(),
         crti_path.to_string_lossy(),
         crtn_path.to_string_lossy(),
-        scrt1_path.to_string_lossy(),
+        crt1_path.to_string_lossy(),
     );
 
     fn get_ld_linux_path(
@@ -936,6 +995,9 @@ fn link_linux(
         Architecture::Aarch64 => {
             get_ld_linux_path(nix_glibc_path_opt(), "/lib", "ld-linux-aarch64.so.1")
         }
+        Architecture::Riscv64 => {
+            get_ld_linux_path(nix_glibc_path_opt(), "/lib", "ld-linux-riscv64-lp64d.so.1")
+        }
         _ => internal_error!(
             "TODO gracefully handle unsupported linux architecture: {:?}",
             target.architecture()
@@ -948,7 +1010,7 @@ fn link_linux(
         LinkType::Executable => (
             // Presumably this S stands for Static, since if we include Scrt1.o
             // in the linking for dynamic builds, linking fails.
-            [scrt1_path_str.as_ref()],
+            [crt1_path_str.as_ref()],
             output_path,
         ),
         LinkType::Dylib => {
@@ -965,7 +1027,16 @@ fn link_linux(
     // NOTE: order of arguments to `ld` matters here!
     // The `-l` flags should go after the `.o` arguments
 
-    let mut ld_command = Command::new("ld");
+    // GNU ld can only read bitcode through the LLVMgold plugin, lld reads it by itself
+    let mut ld_command = Command::new(if lto { "ld.lld" } else { "ld" });
+
+    if lto {
+        // lld does not search the system library directories on its own, and has no -A
+        ld_command.args(lib_dirs.iter().map(|dir| format!("-L{}", dir.display())));
+        ld_command.arg("--plugin-opt=O3");
+    } else {
+        ld_command.args(["-A", arch_str(target)]);
+    }
 
     ld_command
         // Don't allow LD_ env vars to affect this
@@ -977,15 +1048,12 @@ fn link_linux(
                 .filter(|(k, _)| k.starts_with("NIX_"))
                 .collect::<HashMap<String, String>>(),
         )
-        .args([
-            "--gc-sections",
-            "--eh-frame-hdr",
-            "-A",
-            arch_str(target),
-            "-pie",
-            &crti_path_str,
-            &crtn_path_str,
-        ])
+        .args(["--gc-sections", "--eh-frame-hdr"])
+        // ld does not link a position-independent executable unless asked to
+        .args(pie.then_some("-pie"))
+        // the size of the PT_GNU_STACK segment, which musl, for one, gives the threads it starts
+        .args(stack_size.map(|size| format!("-zstack-size={size}")))
+        .args([&*crti_path_str, &*crtn_path_str])
         .args(base_args)
         .args(["-dynamic-linker", ld_linux_path_str])
         .args(input_paths)
@@ -1019,7 +1087,15 @@ fn link_macos(
     output_path: PathBuf,
     input_paths: &[&str],
     link_type: LinkType,
+    stack_size: Option<u32>,
 ) -> io::Result<(Child, PathBuf)> {
+    if !target.is_native() {
+        // every executable on macOS is position-independent
+        let pie = true;
+
+        return link_cross(target, output_path, input_paths, link_type, pie, stack_size);
+    }
+
     let (link_type_args, output_path) = match link_type {
         LinkType::Executable => (vec!["-execute"], output_path),
         LinkType::Dylib => {
@@ -1067,6 +1143,12 @@ fn link_macos(
         .args(input_paths)
         .args(extra_link_flags());
 
+    if let Some(size) = stack_size {
+        // ld64 wants a multiple of the page size, which is 16KB on arm64
+        let size = u64::from(size).next_multiple_of(MACOS_PAGE_SIZE);
+        ld_command.args(["-stack_size", &format!("{size:#x}")]);
+    }
+
     if get_xcode_version() >= 15.0 {
         ld_command.arg("-ld_classic");
     }
@@ -1132,6 +1214,70 @@ fn link_macos(
     }
 }
 
+const MACOS_PAGE_SIZE: u64 = 16 * 1024;
+
+/// The arguments that make `zig build-exe` link an executable with a stack of `stack_size` bytes
+/// The `-target` zig builds and links for, when it is not the system
+fn zig_target(target: Target) -> &'static str {
+    match target {
+        Target::LinuxX32 => "i386-linux-musl",
+        Target::LinuxX64 => "x86_64-linux-gnu",
+        Target::LinuxArm64 => "aarch64-linux-gnu",
+        Target::LinuxRiscv64 => "riscv64-linux-gnu",
+        Target::MacX64 => "x86_64-macos",
+        Target::MacArm64 => "aarch64-macos",
+        Target::WinX32 => "x86-windows-gnu",
+        Target::WinX64 => "x86_64-windows-gnu",
+        Target::WinArm64 => "aarch64-windows-gnu",
+        Target::Wasm32 => "wasm32-wasi-musl",
+        Target::Sbf => internal_error!("zig cannot build for the sbf target"),
+    }
+}
+
+/// Links for a target other than the system. The system linker and libc cannot do that, but zig
+/// brings the libc of every target it supports.
+fn link_cross(
+    target: Target,
+    output_path: PathBuf,
+    input_paths: &[&str],
+    link_type: LinkType,
+    pie: bool,
+    stack_size: Option<u32>,
+) -> io::Result<(Child, PathBuf)> {
+    let (zig_args, output_path) = match link_type {
+        LinkType::Executable => (vec!["build-exe"], output_path),
+        LinkType::Dylib => {
+            let mut output_path = output_path;
+            output_path.set_extension(target.dynamic_library_file_ext());
+
+            (vec!["build-lib", "-dynamic"], output_path)
+        }
+        LinkType::None => internal_error!("link_cross should not be called with link type of none"),
+    };
+
+    let child = zig()
+        .args(zig_args)
+        .args(input_paths)
+        .args([
+            "-target",
+            zig_target(target),
+            "-lc",
+            &format!("-femit-bin={}", output_path.to_str().unwrap()),
+        ])
+        .args((pie && link_type == LinkType::Executable).then_some("-fPIE"))
+        .args(zig_stack_args(stack_size))
+        .spawn()?;
+
+    Ok((child, output_path))
+}
+
+fn zig_stack_args(stack_size: Option<u32>) -> Vec<String> {
+    match stack_size {
+        Some(size) => vec!["--stack".to_string(), size.to_string()],
+        None => Vec::new(),
+    }
+}
+
 fn get_macos_version() -> String {
     let mut cmd = Command::new("sw_vers");
     cmd.arg("-productVersion");
@@ -1176,6 +1322,7 @@ fn link_wasm32(
     output_path: PathBuf,
     input_paths: &[&str],
     _link_type: LinkType,
+    stack_size: Option<u32>,
 ) -> io::Result<(Child, PathBuf)> {
     let child = zig()
         // .env_clear()
@@ -1197,16 +1344,27 @@ fn link_wasm32(
             // useful for debugging
             // "-femit-llvm-ir=/home/folkertdev/roc/roc/crates/cli/tests/benchmarks/platform/host.ll",
         ])
+        // zig passes this on to wasm-ld as `-z stack-size`
+        .args(zig_stack_args(stack_size))
         .spawn()?;
 
     Ok((child, output_path))
 }
 
 fn link_windows(
+    target: Target,
     output_path: PathBuf,
     input_paths: &[&str],
     link_type: LinkType,
+    stack_size: Option<u32>,
 ) -> io::Result<(Child, PathBuf)> {
+    // zig can cross-compile for Windows, with its bundled mingw libc
+    let zig_target = if target.is_native() {
+        "native"
+    } else {
+        zig_target(target)
+    };
+
     match link_type {
         LinkType::Dylib => {
             let child = zig()
@@ -1216,7 +1374,7 @@ fn link_windows(
                     "-lc",
                     &format!("-femit-bin={}", output_path.to_str().unwrap()),
                     "-target",
-                    "native",
+                    zig_target,
                     "-O",
                     "Debug",
                     "-dynamic",
@@ -1231,12 +1389,13 @@ fn link_windows(
                 .args(input_paths)
                 .args([
                     "-target",
-                    "native",
+                    zig_target,
                     "--subsystem",
                     "console",
                     "-lc",
                     &format!("-femit-bin={}", output_path.to_str().unwrap()),
                 ])
+                .args(zig_stack_args(stack_size))
                 .spawn()?;
 
             Ok((child, output_path))
@@ -1245,6 +1404,146 @@ fn link_windows(
     }
 }
 
+/// The layout the Solana loader expects: the code, the read-only data and the tables of the
+/// dynamic linker each in their own segment, and nothing that it would have to ignore. This is
+/// the linker script of the Solana SDK. The stack and the heap are not part of the program, the
+/// loader maps them at fixed addresses.
+const SBF_LINKER_SCRIPT: &str = r#"PHDRS
+{
+  text PT_LOAD ;
+  rodata PT_LOAD ;
+  data PT_LOAD ;
+  dynamic PT_DYNAMIC ;
+}
+
+SECTIONS
+{
+  . = SIZEOF_HEADERS;
+  .text : { *(.text*) } :text
+  .rodata : { *(.rodata*) } :rodata
+  .data.rel.ro : { *(.data.rel.ro*) } :rodata
+  .dynamic : { *(.dynamic) } :dynamic
+  .dynsym : { *(.dynsym) } :data
+  .dynstr : { *(.dynstr) } :data
+  .rel.dyn : { *(.rel.dyn) } :data
+  /DISCARD/ : {
+    *(.eh_frame*)
+    *(.gnu.hash*)
+    *(.hash*)
+  }
+}
+"#;
+
+/// The symbol the Solana runtime calls with the serialized input of an instruction
+const SBF_ENTRYPOINT: &str = "entrypoint";
+
+/// Links a program the Solana runtime can load: a shared object that exports [SBF_ENTRYPOINT].
+/// The platform's host defines the entrypoint, and calls the app from it.
+fn link_sbf(
+    output_path: PathBuf,
+    input_paths: &[&str],
+    link_type: LinkType,
+) -> io::Result<(Child, PathBuf)> {
+    if link_type == LinkType::None {
+        internal_error!("link_sbf should not be called with link type of none");
+    }
+
+    check_sbf_inputs(input_paths);
+
+    // every build writes the same script, so it can be shared between them
+    let script_path = env::temp_dir().join("roc_sbf.ld");
+    let mut script_file = tempfile::NamedTempFile::new_in(env::temp_dir())?;
+    io::Write::write_all(&mut script_file, SBF_LINKER_SCRIPT.as_bytes())?;
+    script_file.persist(&script_path).map_err(|err| err.error)?;
+
+    let mut command = sbf_lld();
+
+    command
+        .args([
+            // the loader applies the relocations of the code itself
+            "-z",
+            "notext",
+            "-shared",
+            "--Bdynamic",
+            "--entry",
+            SBF_ENTRYPOINT,
+            "--script",
+            script_path.to_str().unwrap(),
+        ])
+        .args(input_paths)
+        .arg("-o")
+        .arg(&output_path);
+
+    debug_print_command(&command);
+
+    let child = command.spawn()?;
+
+    Ok((child, output_path))
+}
+
+/// The Solana runtime only runs programs with an entrypoint, and rejects those that mix
+/// versions of SBF, so check for both before the linker gets a chance to be less clear about it.
+fn check_sbf_inputs(input_paths: &[&str]) {
+    let mut defines_entrypoint = false;
+    let mut flags: Option<(&str, u32)> = None;
+
+    for input_path in input_paths.iter().copied() {
+        let bytes = fs::read(input_path).unwrap_or_else(|err| {
+            internal_error!("I could not read {input_path} to link it: {err}")
+        });
+
+        // a host can also be a static library of objects
+        let objects = match object::read::archive::ArchiveFile::parse(&*bytes) {
+            Ok(archive) => archive
+                .members()
+                .filter_map(|member| member.ok()?.data(&*bytes).ok())
+                .collect(),
+            Err(_) => vec![&*bytes],
+        };
+
+        for object_bytes in objects {
+            let (e_flags, defines) = sbf_object_info(object_bytes).unwrap_or_else(|| {
+                user_error!(
+                    "{input_path} is not an ELF object, so it cannot be linked into an sbf program."
+                )
+            });
+
+            match flags {
+                Some((other_path, other_flags)) if other_flags != e_flags => {
+                    user_error!(
+                        "{input_path} and {other_path} were built for different versions of SBF (their ELF flags are {e_flags:#x} and {other_flags:#x}), so they cannot be linked together.\nBuild the host for the same version as the app."
+                    )
+                }
+                Some(_) => {}
+                None => flags = Some((input_path, e_flags)),
+            }
+
+            defines_entrypoint |= defines;
+        }
+    }
+
+    if !defines_entrypoint {
+        user_error!(
+            "The platform's host does not define `{SBF_ENTRYPOINT}`, so the Solana runtime would have no way to call the program.\nDefine `uint64_t {SBF_ENTRYPOINT}(uint8_t *input)` in the host, and call the app from it."
+        );
+    }
+}
+
+/// The ELF flags of an object, and whether it defines [SBF_ENTRYPOINT]
+fn sbf_object_info(object_bytes: &[u8]) -> Option<(u32, bool)> {
+    use object::read::elf::FileHeader;
+    use object::{Object, ObjectSymbol};
+
+    let header = object::elf::FileHeader64::<object::LittleEndian>::parse(object_bytes).ok()?;
+    let file = object::File::parse(object_bytes).ok()?;
+
+    let defines_entrypoint = file.symbols().any(|symbol| {
+        symbol.is_global() && !symbol.is_undefined() && symbol.name().ok() == Some(SBF_ENTRYPOINT)
+    });
+
+    Some((header.e_flags(object::LittleEndian), defines_entrypoint))
+}
+
 pub fn llvm_module_to_dylib(
     module: &inkwell::module::Module,
     target: Target,
@@ -1275,6 +1574,9 @@ pub fn llvm_module_to_dylib(
         app_o_file.clone(),
         &[app_o_file.to_str().unwrap()],
         LinkType::Dylib,
+        false,
+        true,
+        None,
     )
     .unwrap();
 
@@ -1,5 +1,7 @@
 use bumpalo::{collections::String, Bump};
+use inkwell::passes::PassBuilderOptions;
 use roc_error_macros::internal_error;
+use roc_gen_llvm::llvm::pgo::PgoMode;
 use roc_mono::ir::OptLevel;
 use roc_target::Target;
 use std::path::Path;
@@ -8,16 +10,52 @@ use std::path::Path;
 //
 // we also include a "globaldce" pass at the beginning of each pipeline here, which is required
 // to prevent bugs with the surgical linker, and also improves the build time.
-pub fn get_llvm_passes_string(arena: &Bump, opt_level: OptLevel) -> &str {
+//
+// with profile-guided optimization, the instrumentation (or the profile) comes right after that,
+// like clang does it, see roc_gen_llvm::llvm::pgo
+//
+// with link-time optimization, the module only gets the pre-link pipeline, which leaves the
+// optimizations that profit from seeing the whole program to the linker
+pub fn get_llvm_passes_string<'a>(
+    arena: &'a Bump,
+    opt_level: OptLevel,
+    pgo: Option<&PgoMode>,
+    lto: bool,
+) -> &'a str {
     (String::from_str_in("globaldce,", arena)
-        + match opt_level {
-            OptLevel::Development | OptLevel::Normal => "default<O0>",
-            OptLevel::Size => "default<Oz>",
-            OptLevel::Optimize => "default<O3>",
+        + pgo.map_or("", PgoMode::passes)
+        + match (opt_level, lto) {
+            (OptLevel::Development | OptLevel::Normal, _) => "default<O0>",
+            (OptLevel::Size, false) => "default<Oz>",
+            (OptLevel::Optimize, false) => "default<O3>",
+            (OptLevel::Size, true) => "lto-pre-link<Oz>",
+            (OptLevel::Optimize, true) => "lto-pre-link<O3>",
         })
     .into_bump_str()
 }
 
+/// The tuning of the `default<..>` pipeline at each level, which the C API otherwise leaves at
+/// LLVM's own defaults. Like clang, we vectorize straight-line code as well as loops when
+/// optimizing for speed. When optimizing for size, vectorized loops are not worth their code, and
+/// functions that compile to the same code are merged.
+fn pass_builder_options(opt_level: OptLevel) -> PassBuilderOptions {
+    let options = PassBuilderOptions::create();
+
+    match opt_level {
+        OptLevel::Development | OptLevel::Normal => {}
+        OptLevel::Size => {
+            options.set_loop_vectorization(false);
+            options.set_merge_functions(true);
+        }
+        OptLevel::Optimize => {
+            options.set_loop_vectorization(true);
+            options.set_loop_slp_vectorization(true);
+        }
+    }
+
+    options
+}
+
 pub fn optimize_llvm_ir(
     env: &roc_gen_llvm::llvm::build::Env,
     target: Target,
@@ -50,7 +88,12 @@ pub fn optimize_llvm_ir(
     // env.module.print_to_stderr();
 
     let inkwell_opt_level = crate::target::convert_opt_level(opt_level);
-    let inkwell_llvm_passes = get_llvm_passes_string(env.arena, opt_level);
+    if let Some(PgoMode::Use(profile)) = &env.pgo {
+        roc_gen_llvm::llvm::pgo::use_profile(profile);
+    }
+
+    let inkwell_llvm_passes =
+        get_llvm_passes_string(env.arena, opt_level, env.pgo.as_ref(), env.lto);
     let inkwell_target_machine =
         crate::target::target_machine(target, inkwell_opt_level, inkwell::targets::RelocMode::PIC)
             .unwrap_or_else(|| internal_error!("invalid target machine"));
@@ -59,7 +102,7 @@ pub fn optimize_llvm_ir(
         .run_passes(
             inkwell_llvm_passes,
             &inkwell_target_machine,
-            inkwell::passes::PassBuilderOptions::create(),
+            pass_builder_options(opt_level),
         )
         .unwrap_or_else(|e| internal_error!("invalid llvm optimization passes: {:?}", e));
 
@@ -78,3 +121,42 @@ pub fn optimize_llvm_ir(
     // Uncomment this to see the module's optimized LLVM instruction output:
     // env.module.print_to_stderr();
 }
+
+/// Instrument every function marked with `sanitize_address` (see
+/// [roc_gen_llvm::llvm::sanitizers::add_address_sanitizer]). Like clang, we do this after
+/// optimizing, so the checks don't get in the way of the optimizations.
+pub fn run_address_sanitizer(
+    env: &roc_gen_llvm::llvm::build::Env,
+    target: Target,
+    opt_level: OptLevel,
+) {
+    let inkwell_opt_level = crate::target::convert_opt_level(opt_level);
+    let inkwell_target_machine =
+        crate::target::target_machine(target, inkwell_opt_level, inkwell::targets::RelocMode::PIC)
+            .unwrap_or_else(|| internal_error!("invalid target machine"));
+
+    env.module
+        .run_passes(
+            "asan",
+            &inkwell_target_machine,
+            inkwell::passes::PassBuilderOptions::create(),
+        )
+        .unwrap_or_else(|e| internal_error!("invalid llvm sanitizer passes: {:?}", e));
+}
+
+/// Lower the `llvm.type.test` checks of indirect calls (see [roc_gen_llvm::llvm::cfi]) into
+/// jump tables. A call that passes its check jumps through the table to its callee.
+pub fn lower_type_tests(env: &roc_gen_llvm::llvm::build::Env, target: Target, opt_level: OptLevel) {
+    let inkwell_opt_level = crate::target::convert_opt_level(opt_level);
+    let inkwell_target_machine =
+        crate::target::target_machine(target, inkwell_opt_level, inkwell::targets::RelocMode::PIC)
+            .unwrap_or_else(|| internal_error!("invalid target machine"));
+
+    env.module
+        .run_passes(
+            "lowertypetests",
+            &inkwell_target_machine,
+            inkwell::passes::PassBuilderOptions::create(),
+        )
+        .unwrap_or_else(|e| internal_error!("invalid llvm cfi passes: {:?}", e));
+}
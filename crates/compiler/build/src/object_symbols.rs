@@ -0,0 +1,65 @@
+//! The symbols that an application object shares with its host, for `roc build --emit-object`.
+//!
+//! An application that is linked by hand needs a host that defines every symbol the object uses
+//! but does not define itself: `roc_alloc`, `roc_realloc`, `roc_dealloc`, `roc_panic`, `roc_dbg`
+//! and whatever else the platform requires, along with the C functions (like `memcpy`) or
+//! syscalls the builtins call. The host calls the application through the symbols the object
+//! defines. Their names only depend on the names the platform provides to its host:
+//!
+//! * `roc__<name>_1_exposed`, `roc__<name>_1_exposed_generic` and `roc__<name>_1_exposed_size`
+//!   for every value `<name>` the platform provides
+//! * `roc__<name>_<id>_caller`, `roc__<name>_<id>_size` and `roc__<name>_<id>_result_size` for
+//!   the closures that value returns
+//! * `__roc_abi_version` ([roc_mono::abi::ABI_VERSION_SYMBOL]), the ABI version the
+//!   application was built for
+//!
+//! The names are read from the object, so they are exactly what the linker sees (with the
+//! leading `_` that Mach-O adds to every symbol).
+
+use object::{Object, ObjectSymbol, SymbolKind};
+
+#[derive(Debug, Default)]
+pub struct ObjectSymbols {
+    /// Used by the object but not defined in it, so the host (or the linker) has to define them
+    pub undefined: Vec<String>,
+    /// Defined by the object for the host to call or read
+    pub exported: Vec<String>,
+}
+
+impl ObjectSymbols {
+    /// The symbols of a relocatable object file. Fails for LLVM bitcode, which is what wasm32 and
+    /// `--lto` builds output instead.
+    pub fn from_object(object_bytes: &[u8]) -> Result<Self, object::Error> {
+        let object = object::File::parse(object_bytes)?;
+        let mut symbols = ObjectSymbols::default();
+
+        for symbol in object.symbols() {
+            if !symbol.is_global()
+                || matches!(symbol.kind(), SymbolKind::File | SymbolKind::Section)
+            {
+                continue;
+            }
+
+            let Ok(name) = symbol.name() else {
+                continue;
+            };
+
+            if name.is_empty() {
+                continue;
+            }
+
+            if symbol.is_undefined() {
+                symbols.undefined.push(name.to_string());
+            } else {
+                symbols.exported.push(name.to_string());
+            }
+        }
+
+        symbols.undefined.sort_unstable();
+        symbols.undefined.dedup();
+        symbols.exported.sort_unstable();
+        symbols.exported.dedup();
+
+        Ok(symbols)
+    }
+}
@@ -2,21 +2,29 @@ use crate::link::{link, preprocess_host_wasm32, rebuild_host, LinkType, LinkingS
 use bumpalo::collections::CollectIn;
 use bumpalo::Bump;
 use inkwell::memory_buffer::MemoryBuffer;
-use roc_error_macros::internal_error;
+use roc_cache::{Cache, CacheKey, KeyBuilder};
+use roc_collections::MutMap;
+use roc_error_macros::{internal_error, user_error};
 use roc_gen_dev::AssemblyBackendMode;
-use roc_gen_llvm::llvm::build::{module_from_builtins, LlvmBackendMode};
+use roc_gen_llvm::llvm::build::{module_from_builtins, LlvmBackendMode, PanicStrategy};
 use roc_gen_llvm::llvm::externs::add_default_roc_externs;
+use roc_gen_llvm::llvm::pgo::PgoMode;
 use roc_load::{
     EntryPoint, ExecutionMode, ExpectMetadata, FunctionKind, LoadConfig, LoadMonomorphizedError,
     LoadedModule, LoadingProblem, MonomorphizedModule, Threading,
 };
+use roc_module::symbol::{ModuleId, Symbol};
 use roc_mono::ir::{OptLevel, SingleEntryPoint};
+use roc_mono::pool::SizeClasses;
 use roc_packaging::cache::RocCacheDir;
 use roc_reporting::{
-    cli::{report_problems, Problems},
-    report::{RenderTarget, DEFAULT_PALETTE},
+    cli::{
+        report_compute_budget, report_oversized_stack_frames, report_problems, report_stack_depth,
+        report_unsupported_weak_refs, OversizedStackFrame, Problems,
+    },
+    report::{ColorChoice, RenderTarget, DEFAULT_PALETTE},
 };
-use roc_target::{Architecture, Target};
+use roc_target::{Architecture, PtrWidth, Target};
 use std::ops::Deref;
 use std::{
     path::{Path, PathBuf},
@@ -34,23 +42,101 @@ pub struct CodeGenTiming {
     pub generate_final_ir: Duration,
     pub code_gen_object: Duration,
     pub total: Duration,
+    /// Functions that were dropped because another function compiled to the same code
+    pub deduplicated_functions: usize,
+    /// Warnings about the generated code, which can only be checked once it exists
+    pub warnings: usize,
+    /// The object code was generated by an earlier build of the same program
+    pub from_cache: bool,
 }
 
-pub fn report_problems_monomorphized(loaded: &mut MonomorphizedModule) -> Problems {
+pub fn report_problems_monomorphized(
+    loaded: &mut MonomorphizedModule,
+    render: RenderTarget,
+) -> Problems {
     report_problems(
         &loaded.sources,
         &loaded.interns,
         &mut loaded.can_problems,
         &mut loaded.type_problems,
+        render,
+    )
+}
+
+/// Warn about procedures whose estimated stack frame is larger than `max_frame_size` bytes.
+pub fn report_stack_frames_monomorphized(
+    loaded: &MonomorphizedModule,
+    max_frame_size: u32,
+    render: RenderTarget,
+) -> Problems {
+    use roc_mono::layout::LayoutInterner;
+
+    let frames: Vec<_> = roc_mono::stack_frame::oversized_stack_frames(
+        &loaded.layout_interner,
+        &loaded.procedures,
+        max_frame_size,
+    )
+    .into_iter()
+    .map(|(symbol, frame)| OversizedStackFrame {
+        symbol,
+        frame_size: frame.size,
+        largest: frame
+            .largest
+            .map(|(layout, size)| (loaded.layout_interner.dbg(layout), size)),
+    })
+    .collect();
+
+    report_oversized_stack_frames(
+        &loaded.sources,
+        &loaded.interns,
+        &frames,
+        max_frame_size,
+        render,
     )
 }
 
-pub fn report_problems_typechecked(loaded: &mut LoadedModule) -> Problems {
+/// Report the `WeakRef` functions that a program built for a 32-bit target calls. Mono compiles
+/// them to a crash there, so this is an error.
+pub fn report_weak_refs_monomorphized(
+    loaded: &MonomorphizedModule,
+    target: Target,
+    render: RenderTarget,
+) -> Problems {
+    if target.ptr_width() != PtrWidth::Bytes4 {
+        return Problems::default();
+    }
+
+    let mut functions: Vec<Symbol> = loaded
+        .procedures
+        .keys()
+        .map(|(symbol, _)| *symbol)
+        .filter(|symbol| symbol.module_id() == ModuleId::WEAK_REF)
+        .collect();
+
+    functions.sort();
+    functions.dedup();
+
+    if functions.is_empty() {
+        return Problems::default();
+    }
+
+    report_unsupported_weak_refs(
+        &loaded.sources,
+        &loaded.interns,
+        loaded.module_id,
+        &functions,
+        &target.to_string(),
+        render,
+    )
+}
+
+pub fn report_problems_typechecked(loaded: &mut LoadedModule, render: RenderTarget) -> Problems {
     report_problems(
         &loaded.sources,
         &loaded.interns,
         &mut loaded.can_problems,
         &mut loaded.type_problems,
+        render,
     )
 }
 
@@ -77,13 +163,81 @@ pub enum CodeGenBackend {
     Wasm,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct CodeGenOptions {
     pub backend: CodeGenBackend,
     pub opt_level: OptLevel,
     pub emit_debug_info: bool,
+    /// Make the debug info point into the Roc source, instead of only keeping the placeholder
+    /// subprograms LLVM needs. Only used by the LLVM backend.
+    pub emit_source_debug_info: bool,
     pub emit_llvm_ir: bool,
+    /// Write the LLVM bitcode of the program to a `.bc` file. Only used by the LLVM backend.
+    pub emit_llvm_bc: bool,
+    /// Write the specialized procedures of the program to a `.mono` file, before code gen
+    pub emit_mono: bool,
+    /// Write the assembly of the program for the target to a `.s` file. Only used by the LLVM
+    /// backend.
+    pub emit_asm: bool,
     pub fuzz: bool,
+    /// Warn about functions whose stack frame is estimated to be larger than this many bytes.
+    /// Defaults to the limit of the target, if it has one.
+    pub max_stack_frame: Option<u32>,
+    /// Call the host's `roc_panic` even on targets that can abort with a syscall instead (SBF)
+    pub host_panic: bool,
+    /// Instrument the program with AddressSanitizer. Only used by the LLVM backend, and the host
+    /// must be linked against the sanitizer runtime.
+    pub sanitize_address: bool,
+    /// Trap when memory is accessed through a misaligned pointer. Only used by the LLVM backend.
+    pub check_alignment: bool,
+    /// Override LLVM's inline threshold for calls to Roc procedures. Only used by the LLVM backend.
+    pub inline_threshold: Option<u32>,
+    /// Build lists that never leave the function that creates them on the stack. Only used by the
+    /// LLVM backend.
+    pub optimize_stack_alloc: bool,
+    /// Report integer overflow with its source location through `__roc_overflow_trap`. Only used
+    /// by the LLVM backend.
+    pub overflow_trap: bool,
+    /// Instrument the program to collect a profile, or optimize it with one. Only used by the
+    /// LLVM backend.
+    pub pgo: Option<PgoMode>,
+    /// Emit LLVM bitcode instead of an object file, so the linker can optimize the app together
+    /// with the host. Only used by the LLVM backend.
+    pub lto: bool,
+    /// Warn when the compute units a program is estimated to use are over this budget. Only used
+    /// by the LLVM backend, for the sbf target.
+    pub sbf_compute_budget: Option<u64>,
+    /// Warn when the frames along the deepest call path take more than this many bytes of stack
+    /// together. Defaults to the limit of the target, if it has one. Only used by the LLVM
+    /// backend.
+    pub sbf_stack_limit: Option<u32>,
+    /// Link executables with a stack of this many bytes, and check for stack overflow at the start
+    /// of every procedure. Only used by the LLVM backend, for targets other than sbf.
+    pub stack_size: Option<u32>,
+    /// Place the constant data of top-level definitions of the app in named sections, given as
+    /// pairs of the name of a definition and the name of a section. Only used by the LLVM backend.
+    pub data_sections: Vec<(String, String)>,
+    /// The app is linked into a shared library on its own, so hide every symbol it defines except
+    /// the functions the host calls. Only used by the LLVM backend.
+    pub shared_library: bool,
+    /// Generate position-independent code, and link it into a position-independent executable
+    /// with the legacy linker on Linux. Only used by the LLVM backend.
+    pub pie: bool,
+    /// Check that every call through a function pointer calls a function of the right type. Only
+    /// used by the LLVM backend.
+    pub cfi: bool,
+    /// Allocate the tags of recursive unions from pools of a few fixed sizes, instead of with
+    /// `roc_alloc`. Only used by the LLVM backend.
+    pub pool_alloc: bool,
+    /// Tell Valgrind about the allocations the builtins make from their pools and arenas, so that
+    /// Memcheck finds the ones that leak. Only used by the LLVM backend.
+    pub valgrind: bool,
+    /// Copy and fill memory with the bulk memory instructions of WebAssembly. Only used by the
+    /// Wasm backend.
+    pub bulk_memory: bool,
+    /// Crash by throwing a WebAssembly exception, which the functions exposed to the host catch.
+    /// Only used by the Wasm backend.
+    pub wasm_exceptions: bool,
 }
 
 type GenFromMono<'a> = (CodeObject, CodeGenTiming, ExpectMetadata<'a>);
@@ -97,12 +251,76 @@ pub fn gen_from_mono_module<'a>(
     code_gen_options: CodeGenOptions,
     built_host_opt: &BuiltHostOpt,
     wasm_dev_stack_bytes: Option<u32>,
+    cache_dir: Option<&Path>,
 ) -> GenFromMono<'a> {
+    let code_gen_start = Instant::now();
+
+    // only the LLVM backend takes long enough to be worth caching
+    let code_cache = match cache_dir {
+        Some(cache_dir)
+            if matches!(code_gen_options.backend, CodeGenBackend::Llvm(_))
+                && code_is_cacheable(&code_gen_options, target) =>
+        {
+            KeyBuilder::new().ok().map(|key| {
+                let key = code_cache_key(key, &loaded, target, &code_gen_options);
+
+                (Cache::app_objects(cache_dir), key)
+            })
+        }
+        _ => None,
+    };
+
+    if let Some(bytes) = code_cache.as_ref().and_then(|(cache, key)| cache.read(key)) {
+        let total = code_gen_start.elapsed();
+
+        return (
+            CodeObject::Vector(bytes),
+            CodeGenTiming {
+                total,
+                from_cache: true,
+                ..CodeGenTiming::default()
+            },
+            ExpectMetadata {
+                interns: loaded.interns,
+                layout_interner: loaded.layout_interner,
+                expectations: loaded.expectations,
+            },
+        );
+    }
+
     let path = roc_file_path;
     let debug = code_gen_options.emit_debug_info;
+    let source_debug_info = code_gen_options.emit_source_debug_info;
     let emit_llvm_ir = code_gen_options.emit_llvm_ir;
+    let emit_llvm_bc = code_gen_options.emit_llvm_bc;
+    let emit_asm = code_gen_options.emit_asm;
     let fuzz = code_gen_options.fuzz;
     let opt = code_gen_options.opt_level;
+    let host_panic = code_gen_options.host_panic;
+    let sanitize_address = code_gen_options.sanitize_address;
+    let check_alignment = code_gen_options.check_alignment;
+    let inline_threshold = code_gen_options.inline_threshold;
+    let optimize_stack_alloc = code_gen_options.optimize_stack_alloc;
+    let overflow_trap = code_gen_options.overflow_trap;
+    let pgo = code_gen_options.pgo;
+    let lto = code_gen_options.lto;
+    let sbf_compute_budget = code_gen_options.sbf_compute_budget;
+    let sbf_stack_limit = code_gen_options
+        .sbf_stack_limit
+        .or_else(|| target.max_stack_depth());
+    let stack_guard = code_gen_options.stack_size.is_some();
+    let data_sections = code_gen_options.data_sections;
+    let shared_library = code_gen_options.shared_library;
+    let pie = code_gen_options.pie;
+    let cfi = code_gen_options.cfi;
+    let pool_alloc = code_gen_options.pool_alloc;
+    let valgrind = code_gen_options.valgrind;
+    let bulk_memory = code_gen_options.bulk_memory;
+    let wasm_exceptions = code_gen_options.wasm_exceptions;
+
+    if code_gen_options.emit_mono {
+        emit_mono(&loaded, path);
+    }
 
     match code_gen_options.backend {
         CodeGenBackend::Wasm => {
@@ -118,6 +336,8 @@ pub fn gen_from_mono_module<'a>(
                 target,
                 built_host_opt,
                 wasm_dev_stack_bytes,
+                bulk_memory,
+                wasm_exceptions,
                 AssemblyBackendMode::Binary, // dummy value, unused in practice
             )
         }
@@ -127,20 +347,121 @@ pub fn gen_from_mono_module<'a>(
             target,
             built_host_opt,
             wasm_dev_stack_bytes,
+            bulk_memory,
+            wasm_exceptions,
             backend_mode,
         ),
-        CodeGenBackend::Llvm(backend_mode) => gen_from_mono_module_llvm(
-            arena,
-            loaded,
-            path,
-            target,
-            opt,
-            backend_mode,
-            debug,
-            emit_llvm_ir,
-            fuzz,
-        ),
+        CodeGenBackend::Llvm(backend_mode) => {
+            let generated = gen_from_mono_module_llvm(
+                arena,
+                loaded,
+                path,
+                target,
+                opt,
+                backend_mode,
+                debug,
+                source_debug_info,
+                emit_llvm_ir,
+                emit_llvm_bc,
+                emit_asm,
+                fuzz,
+                host_panic,
+                sanitize_address,
+                check_alignment,
+                inline_threshold,
+                optimize_stack_alloc,
+                overflow_trap,
+                pgo,
+                lto,
+                sbf_compute_budget,
+                sbf_stack_limit,
+                stack_guard,
+                &data_sections,
+                shared_library,
+                pie,
+                cfi,
+                pool_alloc,
+                valgrind,
+            );
+
+            if let Some((cache, key)) = &code_cache {
+                // a build that cannot store its code is no slower than one without the cache
+                let _ = cache.write(key, &generated.0);
+            }
+
+            generated
+        }
+    }
+}
+
+/// Whether the object code depends on nothing but the program, the options and the target. A
+/// profile for PGO is read from a file, the files that the `--emit` options ask for would not be
+/// written, and the warnings about the estimated stack and compute budget of sbf programs would
+/// not be reported again.
+fn code_is_cacheable(code_gen_options: &CodeGenOptions, target: Target) -> bool {
+    let CodeGenOptions {
+        emit_llvm_ir,
+        emit_llvm_bc,
+        emit_mono,
+        emit_asm,
+        fuzz,
+        pgo,
+        sbf_compute_budget,
+        sbf_stack_limit,
+        ..
+    } = code_gen_options;
+
+    target != Target::Sbf
+        && sbf_compute_budget.is_none()
+        && sbf_stack_limit.is_none()
+        && pgo.is_none()
+        && !(*emit_llvm_ir || *emit_llvm_bc || *emit_mono || *emit_asm || *fuzz)
+        // the sanitizers of ROC_SANITIZERS are set in the environment
+        && std::env::var_os("ROC_SANITIZERS").is_none()
+}
+
+/// The key of the object code of `loaded`. That is everything code generation reads: the
+/// procedures and their layouts, the names the host calls them by, and the sources, which the
+/// debug info and the locations of crashes point into.
+fn code_cache_key(
+    mut key: KeyBuilder,
+    loaded: &MonomorphizedModule<'_>,
+    target: Target,
+    code_gen_options: &CodeGenOptions,
+) -> CacheKey {
+    key.add(target.to_string())
+        .add(format!("{code_gen_options:?}"));
+
+    let mut sources: Vec<_> = loaded.sources.values().collect();
+    sources.sort();
+
+    for (path, source) in sources {
+        key.add(path.to_string_lossy().as_bytes())
+            .add(source.as_bytes());
+    }
+
+    if let EntryPoint::Executable {
+        exposed_to_host, ..
+    } = &loaded.entry_point
+    {
+        for (fn_name, _, _) in exposed_to_host.iter() {
+            key.add(fn_name);
+        }
+    }
+
+    // sorted like the dump of --emit=mono, because the procedures come out of a hash map
+    let mut procs: Vec<_> = loaded
+        .procedures
+        .values()
+        .map(|proc| proc.to_pretty(&loaded.layout_interner, 200, false))
+        .collect();
+    procs.sort();
+
+    for proc in procs {
+        key.add(proc);
     }
+
+    key.finish()
 }
 
 // TODO how should imported modules factor into this? What if those use builtins too?
@@ -155,8 +476,28 @@ fn gen_from_mono_module_llvm<'a>(
     opt_level: OptLevel,
     backend_mode: LlvmBackendMode,
     emit_debug_info: bool,
+    emit_source_debug_info: bool,
     emit_llvm_ir: bool,
+    emit_llvm_bc: bool,
+    emit_asm: bool,
     fuzz: bool,
+    host_panic: bool,
+    sanitize_address: bool,
+    check_alignment: bool,
+    inline_threshold: Option<u32>,
+    optimize_stack_alloc: bool,
+    overflow_trap: bool,
+    pgo: Option<PgoMode>,
+    lto: bool,
+    sbf_compute_budget: Option<u64>,
+    sbf_stack_limit: Option<u32>,
+    stack_guard: bool,
+    data_sections: &[(String, String)],
+    shared_library: bool,
+    pie: bool,
+    cfi: bool,
+    pool_alloc: bool,
+    valgrind: bool,
 ) -> GenFromMono<'a> {
     use crate::target::{self, convert_opt_level};
     use inkwell::attributes::{Attribute, AttributeLoc};
@@ -203,6 +544,30 @@ fn gen_from_mono_module_llvm<'a>(
 
     let builder = context.create_builder();
     let (dibuilder, compile_unit) = roc_gen_llvm::llvm::build::Env::new_debug_info(module);
+    let panic_strategy = PanicStrategy::for_target(target, host_panic);
+
+    // sol_panic_ reports the source location of a panic, failed expects on SBF log their source,
+    // and overflow traps include their location, all of which the debug info builder tracks
+    let track_source_locations = emit_source_debug_info
+        || overflow_trap
+        || panic_strategy == PanicStrategy::SolPanic
+        || target == Target::Sbf;
+    let debug_info = track_source_locations.then(|| {
+        roc_gen_llvm::llvm::debug_info::DebugInfoBuilder::new(
+            &dibuilder,
+            &loaded.sources,
+            loaded.symbol_regions,
+        )
+    });
+
+    let data_sections = resolve_data_sections(&loaded, data_sections);
+
+    let pool_size_classes = pool_alloc.then(|| {
+        &*arena.alloc(SizeClasses::from_procedures(
+            &loaded.layout_interner,
+            loaded.procedures.values(),
+        ))
+    });
 
     // Compile and add all the Procs before adding main
     let env = roc_gen_llvm::llvm::build::Env {
@@ -210,6 +575,20 @@ fn gen_from_mono_module_llvm<'a>(
         builder: &builder,
         dibuilder: &dibuilder,
         compile_unit: &compile_unit,
+        debug_info: debug_info.as_ref(),
+        panic_strategy,
+        check_alignment,
+        inline_threshold,
+        optimize_stack_alloc,
+        overflow_trap,
+        pgo,
+        lto,
+        sbf_stack_limit,
+        stack_guard,
+        data_sections,
+        cfi,
+        pool_size_classes,
+        valgrind,
         context: &context,
         interns: loaded.interns,
         module,
@@ -258,11 +637,90 @@ fn gen_from_mono_module_llvm<'a>(
         &loaded.glue_layouts,
     );
 
+    let deduplicated_functions = roc_gen_llvm::llvm::dedup::merge_identical_functions(&env);
+
+    if check_alignment {
+        roc_gen_llvm::llvm::sanitizers::add_alignment_checks(&env);
+    }
+
+    if sanitize_address {
+        roc_gen_llvm::llvm::sanitizers::add_address_sanitizer(&env);
+    }
+
+    if env.pgo == Some(PgoMode::Instrument) {
+        roc_gen_llvm::llvm::pgo::add_profile_writer(&env);
+    }
+
+    if shared_library {
+        roc_gen_llvm::llvm::build::hide_internal_symbols(&env);
+    }
+
     // We are now finished building the LLVM IR.
     let generate_final_ir = all_code_gen_start.elapsed();
     let code_gen_object_start = Instant::now();
 
-    crate::llvm_passes::optimize_llvm_ir(&env, target, opt_level, emit_debug_info, &app_ll_file);
+    crate::llvm_passes::optimize_llvm_ir(
+        &env,
+        target,
+        opt_level,
+        emit_debug_info || emit_source_debug_info,
+        &app_ll_file,
+    );
+
+    if sanitize_address {
+        crate::llvm_passes::run_address_sanitizer(&env, target, opt_level);
+    }
+
+    if cfi {
+        crate::llvm_passes::lower_type_tests(&env, target, opt_level);
+    }
+
+    let mut warnings = match sbf_compute_budget {
+        Some(budget) if target == Target::Sbf => {
+            report_compute_budget_llvm(&env, &loaded.sources, loaded.module_id, budget).warnings
+        }
+        _ => 0,
+    };
+
+    if let Some(path) = roc_gen_llvm::llvm::stack_depth::check_stack_depth(&env) {
+        let limit = env.sbf_stack_limit.unwrap_or_default();
+
+        warnings += report_stack_depth(
+            &loaded.sources,
+            &env.interns,
+            loaded.module_id,
+            &path.frames,
+            path.depth,
+            limit,
+        )
+        .warnings;
+    }
+
+    if emit_llvm_bc {
+        let app_bc_file = roc_file_path.with_extension("bc");
+
+        eprintln!("Emitting LLVM bitcode to {}", &app_bc_file.display());
+        assert!(
+            module.write_bitcode_to_path(&app_bc_file),
+            "Writing .bc file failed"
+        );
+    }
+
+    if emit_asm {
+        let app_asm_file = roc_file_path.with_extension("s");
+        let reloc = if pie {
+            RelocMode::PIC
+        } else {
+            RelocMode::Static
+        };
+        let target_machine =
+            target::target_machine(target, convert_opt_level(opt_level), reloc).unwrap();
+
+        eprintln!("Emitting assembly to {}", &app_asm_file.display());
+        target_machine
+            .write_to_file(env.module, FileType::Assembly, &app_asm_file)
+            .expect("Writing .s file failed");
+    }
 
     let gen_sanitizers = cfg!(feature = "sanitizers") && std::env::var("ROC_SANITIZERS").is_ok();
     let memory_buffer = if fuzz || gen_sanitizers {
@@ -349,7 +807,11 @@ fn gen_from_mono_module_llvm<'a>(
         // the most common ones and then give up.
         let bc_to_object_output = Command::new("llc")
             .args([
-                "-relocation-model=pic",
+                if pie {
+                    "-relocation-model=pic"
+                } else {
+                    "-relocation-model=static"
+                },
                 "-filetype=obj",
                 &temp_app_processed_file_str,
                 "-o",
@@ -372,8 +834,20 @@ fn gen_from_mono_module_llvm<'a>(
 
         // Emit the .o file
         match target.architecture() {
-            Architecture::X86_64 | Architecture::X86_32 | Architecture::Aarch64 => {
-                let reloc = RelocMode::PIC;
+            _ if env.lto => {
+                // the linker generates the code, together with that of the host
+                module.write_bitcode_to_memory()
+            }
+            Architecture::X86_64
+            | Architecture::X86_32
+            | Architecture::Aarch64
+            | Architecture::Riscv64
+            | Architecture::Sbf => {
+                let reloc = if pie {
+                    RelocMode::PIC
+                } else {
+                    RelocMode::Static
+                };
                 let target_machine =
                     target::target_machine(target, convert_opt_level(opt_level), reloc).unwrap();
 
@@ -402,6 +876,9 @@ fn gen_from_mono_module_llvm<'a>(
             generate_final_ir,
             code_gen_object,
             total,
+            deduplicated_functions,
+            warnings,
+            from_cache: false,
         },
         ExpectMetadata {
             interns: env.interns,
@@ -411,19 +888,107 @@ fn gen_from_mono_module_llvm<'a>(
     )
 }
 
+/// Write the specialized procedures of the program next to the app module, in the format of the
+/// mono tests. The procedures are sorted so that the dump of two builds can be diffed.
+fn emit_mono(loaded: &MonomorphizedModule<'_>, roc_file_path: &Path) {
+    let app_mono_file = roc_file_path.with_extension("mono");
+
+    let mut procs_string = loaded
+        .procedures
+        .values()
+        .map(|proc| proc.to_pretty(&loaded.layout_interner, 200, false))
+        .collect::<Vec<_>>();
+
+    procs_string.sort();
+
+    eprintln!("Emitting mono IR to {}", &app_mono_file.display());
+    std::fs::write(&app_mono_file, procs_string.join("\n")).unwrap();
+}
+
+/// Look up the top-level definitions of the app that have a data section. A definition the
+/// program does not use is never built, so it can not be placed either.
+fn resolve_data_sections(
+    loaded: &MonomorphizedModule<'_>,
+    data_sections: &[(String, String)],
+) -> MutMap<Symbol, String> {
+    let home = loaded.module_id;
+    let ident_ids = loaded.interns.all_ident_ids.get(&home);
+
+    data_sections
+        .iter()
+        .map(|(name, section)| {
+            let symbol = ident_ids
+                .and_then(|ident_ids| ident_ids.get_id(name))
+                .map(|ident_id| Symbol::new(home, ident_id))
+                .filter(|symbol| loaded.procedures.keys().any(|(proc, _)| proc == symbol));
+
+            match symbol {
+                Some(symbol) => (symbol, section.clone()),
+                None => user_error!(
+                    "The app has no top-level definition named `{name}` that it uses, so there is nothing to put in the {section} section."
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Warn when the compute units of the functions the host calls into are estimated to be over
+/// `budget`. This runs on the optimized module, after inlining has settled which function the
+/// code ends up in.
+fn report_compute_budget_llvm(
+    env: &roc_gen_llvm::llvm::build::Env<'_, '_, '_>,
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    home: ModuleId,
+    budget: u64,
+) -> Problems {
+    use roc_gen_llvm::llvm::compute_budget::{estimate_compute_units, host_exposed_functions};
+
+    let entry_points = host_exposed_functions(env.module);
+    let estimate = estimate_compute_units(env.module, &entry_points);
+
+    if estimate.total <= budget {
+        return Problems::default();
+    }
+
+    let functions: Vec<_> = estimate
+        .functions
+        .into_iter()
+        .map(|function| (function.name, function.cost))
+        .collect();
+
+    report_compute_budget(
+        sources,
+        &env.interns,
+        home,
+        estimate.total,
+        budget,
+        &functions,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
 fn gen_from_mono_module_dev<'a>(
     arena: &'a bumpalo::Bump,
     loaded: MonomorphizedModule<'a>,
     target: Target,
     built_host_opt: &BuiltHostOpt,
     wasm_dev_stack_bytes: Option<u32>,
+    bulk_memory: bool,
+    wasm_exceptions: bool,
     #[allow(unused_variables)] backend_mode: AssemblyBackendMode,
 ) -> GenFromMono<'a> {
     match (built_host_opt, target.architecture()) {
         (BuiltHostOpt::Additive(host_path), Architecture::Wasm32) => {
             #[cfg(feature = "target-wasm32")]
             {
-                gen_from_mono_module_dev_wasm32(arena, loaded, host_path, wasm_dev_stack_bytes)
+                gen_from_mono_module_dev_wasm32(
+                    arena,
+                    loaded,
+                    host_path,
+                    wasm_dev_stack_bytes,
+                    bulk_memory,
+                    wasm_exceptions,
+                )
             }
 
             #[cfg(not(feature = "target-wasm32"))]
@@ -464,6 +1029,12 @@ fn gen_from_mono_module_dev<'a>(
         (_, Architecture::X86_32) => {
             internal_error!("Dev compiler backend does not support 32 bit x86 architectures")
         }
+        (_, Architecture::Riscv64) => {
+            internal_error!("Dev compiler backend does not support RISC-V architectures")
+        }
+        (_, Architecture::Sbf) => {
+            internal_error!("Dev compiler backend does not support the SBF architecture")
+        }
     }
 }
 
@@ -473,6 +1044,8 @@ fn gen_from_mono_module_dev_wasm32<'a>(
     loaded: MonomorphizedModule<'a>,
     built_host_path: &Path,
     wasm_dev_stack_bytes: Option<u32>,
+    bulk_memory: bool,
+    wasm_exceptions: bool,
 ) -> GenFromMono<'a> {
     let all_code_gen_start = Instant::now();
     let MonomorphizedModule {
@@ -495,6 +1068,8 @@ fn gen_from_mono_module_dev_wasm32<'a>(
         module_id,
         exposed_to_host,
         stack_bytes: wasm_dev_stack_bytes.unwrap_or(roc_gen_wasm::Env::DEFAULT_STACK_BYTES),
+        bulk_memory,
+        exceptions: wasm_exceptions,
     };
 
     let host_bytes = std::fs::read(built_host_path).unwrap_or_else(|_| {
@@ -532,6 +1107,9 @@ fn gen_from_mono_module_dev_wasm32<'a>(
             generate_final_ir,
             code_gen_object,
             total,
+            deduplicated_functions: 0,
+            warnings: 0,
+            from_cache: false,
         },
         ExpectMetadata {
             interns,
@@ -588,6 +1166,9 @@ fn gen_from_mono_module_dev_assembly<'a>(
             generate_final_ir,
             code_gen_object,
             total,
+            deduplicated_functions: 0,
+            warnings: 0,
+            from_cache: false,
         },
         ExpectMetadata {
             interns,
@@ -614,6 +1195,9 @@ pub struct BuiltFile<'a> {
     pub problems: Problems,
     pub total_time: Duration,
     pub expect_metadata: ExpectMetadata<'a>,
+    /// The files of the modules that were built, without the builtins
+    pub module_paths: Vec<PathBuf>,
+    pub platform_main_roc_path: PathBuf,
 }
 
 pub enum BuildOrdering {
@@ -650,12 +1234,16 @@ impl<'a> BuildFileError<'a> {
 pub fn handle_error_module(
     mut module: roc_load::LoadedModule,
     total_time: std::time::Duration,
+    render: RenderTarget,
 ) -> std::io::Result<i32> {
     debug_assert!(module.total_problems() > 0);
 
-    let problems = report_problems_typechecked(&mut module);
+    let problems = report_problems_typechecked(&mut module, render);
 
-    problems.print_error_warning_count(total_time);
+    // As JSON, the exit code is all there is to say about the problems as a whole
+    if !matches!(render, RenderTarget::Json) {
+        problems.print_error_warning_count(total_time, render.has_color());
+    }
 
     Ok(problems.exit_code())
 }
@@ -688,10 +1276,14 @@ pub fn standard_load_config(
     LoadConfig {
         target,
         function_kind: FunctionKind::from_env(),
-        render: RenderTarget::ColorTerminal,
+        render: RenderTarget::ColorTerminal(ColorChoice::Always),
         palette: DEFAULT_PALETTE,
         threading,
         exec_mode,
+        expect_filter: None,
+        finalizers: Vec::new(),
+        types_cache_dir: None,
+        fold_constants: true,
     }
 }
 
@@ -706,6 +1298,7 @@ pub fn build_file<'a>(
     linking_strategy: LinkingStrategy,
     build_host: bool,
     suppress_build_host_warning: bool,
+    use_cache: bool,
     wasm_dev_stack_bytes: Option<u32>,
     roc_cache_dir: RocCacheDir<'_>,
     load_config: LoadConfig,
@@ -714,6 +1307,20 @@ pub fn build_file<'a>(
 ) -> Result<BuiltFile<'a>, BuildFileError<'a>> {
     let compilation_start = Instant::now();
 
+    let render = load_config.render;
+
+    // the cache dir we are given is the one for packages, preprocessed hosts, the object code of
+    // apps and the solved types of modules go next to it
+    let cache_dir = match roc_cache_dir {
+        RocCacheDir::Persistent(_) if use_cache => Some(roc_packaging::cache::roc_cache_dir()),
+        _ => None,
+    };
+
+    let load_config = LoadConfig {
+        types_cache_dir: cache_dir.clone(),
+        ..load_config
+    };
+
     let loaded = roc_load::load_and_monomorphize(
         arena,
         app_module_path.clone(),
@@ -733,9 +1340,11 @@ pub fn build_file<'a>(
         linking_strategy,
         build_host,
         suppress_build_host_warning,
+        cache_dir.as_deref(),
         wasm_dev_stack_bytes,
         loaded,
         compilation_start,
+        render,
         out_path,
         verbose,
     )
@@ -759,6 +1368,7 @@ fn build_and_preprocess_host(
     linking_strategy: LinkingStrategy,
     platform_main_roc: &Path,
     preprocessed_host_path: &Path,
+    cache_dir: Option<&Path>,
     target: Target,
 ) -> BuiltHostOpt {
     let rebuild_thread = match linking_strategy {
@@ -781,6 +1391,7 @@ fn build_and_preprocess_host(
                 preprocessed_path,
                 preprocessed_host_path.to_owned(),
                 metadata_path,
+                cache_dir.map(Path::to_path_buf),
             )
         }
         LinkingStrategy::Legacy => spawn_legacy_host_build_thread(
@@ -810,9 +1421,11 @@ fn build_loaded_file<'a>(
     linking_strategy: LinkingStrategy,
     build_host_requested: bool,
     suppress_build_host_warning: bool,
+    cache_dir: Option<&Path>,
     wasm_dev_stack_bytes: Option<u32>,
     loaded: roc_load::MonomorphizedModule<'a>,
     compilation_start: Instant,
+    render: RenderTarget,
     out_path: Option<&Path>,
     verbose: bool,
 ) -> Result<BuiltFile<'a>, BuildFileError<'a>> {
@@ -822,6 +1435,19 @@ fn build_loaded_file<'a>(
         _ => unreachable!(),
     };
 
+    // A platform may only ship a legacy host for a target we cross-compile to. Keep linking
+    // those with the legacy linker, which can link for every target with zig.
+    let linking_strategy = if linking_strategy == LinkingStrategy::Surgical
+        && !target.is_native()
+        && !build_host_requested
+        && target.find_surgical_host(&platform_main_roc_path).is_err()
+        && target.find_legacy_host(&platform_main_roc_path).is_ok()
+    {
+        LinkingStrategy::Legacy
+    } else {
+        linking_strategy
+    };
+
     let output_exe_path = get_exe_path(
         out_path,
         app_module_path.as_path(),
@@ -833,9 +1459,14 @@ fn build_loaded_file<'a>(
     let dll_stub_symbols =
         roc_linker::ExposedSymbols::from_exposed_to_host(&loaded.interns, &loaded.exposed_to_host);
 
+    // With the LLVM backend, the wasm32 host is compiled from its source along with the app when
+    // they are linked. The dev backend links a host that it preprocessed itself.
+    let wasm32_host_at_link =
+        target == Target::Wasm32 && linking_strategy != LinkingStrategy::Additive;
+
     let built_host_opt =
         // Not sure if this is correct for all calls with LinkType::Dylib...
-        if link_type == LinkType::None || link_type == LinkType::Dylib || target == Target::Wasm32 {
+        if link_type == LinkType::None || link_type == LinkType::Dylib || wasm32_host_at_link {
             BuiltHostOpt::None
         } else {
             let prebuilt_host = determine_built_host_path(&platform_main_roc_path, target, build_host_requested, link_type, linking_strategy, suppress_build_host_warning);
@@ -843,12 +1474,13 @@ fn build_loaded_file<'a>(
             match prebuilt_host {
                 BuiltHostOpt::None => {
                     build_and_preprocess_host(
-                        code_gen_options,
+                        code_gen_options.clone(),
                         dll_stub_symbols,
                         emit_timings,
                         linking_strategy,
                         &platform_main_roc_path,
                         &output_exe_path,
+                        cache_dir,
                         target,
                     )
                 }
@@ -890,19 +1522,40 @@ fn build_loaded_file<'a>(
     // This only needs to be mutable for report_problems. This can't be done
     // inside a nested scope without causing a borrow error!
     let mut loaded = loaded;
-    let problems = report_problems_monomorphized(&mut loaded);
+    let mut problems = report_problems_monomorphized(&mut loaded, render);
     let loaded = loaded;
 
+    if let Some(max_frame_size) = code_gen_options
+        .max_stack_frame
+        .or_else(|| target.max_stack_frame_size())
+    {
+        let stack_problems = report_stack_frames_monomorphized(&loaded, max_frame_size, render);
+
+        problems.warnings += stack_problems.warnings;
+    }
+
+    problems.errors += report_weak_refs_monomorphized(&loaded, target, render).errors;
+
+    let module_paths = loaded
+        .sources
+        .iter()
+        .filter(|(module_id, _)| !module_id.is_builtin())
+        .map(|(_, (path, _))| path.clone())
+        .collect();
+
     let (roc_app_bytes, code_gen_timing, expect_metadata) = gen_from_mono_module(
         arena,
         loaded,
         &app_module_path,
         target,
-        code_gen_options,
+        code_gen_options.clone(),
         &built_host_opt,
         wasm_dev_stack_bytes,
+        cache_dir,
     );
 
+    problems.warnings += code_gen_timing.warnings;
+
     buf.push('\n');
     buf.push_str("    ");
     buf.push_str("Code Generation");
@@ -917,6 +1570,21 @@ fn build_loaded_file<'a>(
     buf.push('\n');
     report_timing(buf, "Total", code_gen_timing.total);
 
+    if code_gen_timing.deduplicated_functions > 0 {
+        use std::fmt::Write;
+
+        writeln!(
+            buf,
+            "\n    Merged {} functions that compiled to identical code",
+            code_gen_timing.deduplicated_functions,
+        )
+        .unwrap();
+    }
+
+    if code_gen_timing.from_cache {
+        buf.push_str("\n    Reused the object code of an earlier build of the same program\n");
+    }
+
     let compilation_end = compilation_start.elapsed();
     let size = roc_app_bytes.len();
 
@@ -952,10 +1620,11 @@ fn build_loaded_file<'a>(
             std::fs::write(&output_exe_path, &*roc_app_bytes).unwrap();
         }
         (LinkingStrategy::Legacy, _) => {
-            let extension = if target == Target::Wasm32 {
+            let extension = if target == Target::Wasm32 || code_gen_options.lto {
                 // Legacy linker is only by used llvm wasm backend, not dev.
                 // llvm wasm backend directly emits a bitcode file when targeting wasi, not a `.o` or `.wasm` file.
                 // If we set the extension wrong, zig will print a ton of warnings when linking.
+                // With --lto, the app is bitcode on every target.
                 "bc"
             } else {
                 target.object_file_ext()
@@ -1007,8 +1676,16 @@ fn build_loaded_file<'a>(
                 inputs.push(builtins_host_tempfile.path().to_str().unwrap());
             }
 
-            let (mut child, _) = link(target, output_exe_path.clone(), &inputs, link_type)
-                .map_err(|_| todo!("linker failed to spawn."))?;
+            let (mut child, _) = link(
+                target,
+                output_exe_path.clone(),
+                &inputs,
+                link_type,
+                code_gen_options.lto,
+                code_gen_options.pie,
+                code_gen_options.stack_size,
+            )
+            .map_err(|_| todo!("linker failed to spawn."))?;
 
             let exit_status = child
                 .wait()
@@ -1037,6 +1714,8 @@ fn build_loaded_file<'a>(
         problems,
         total_time,
         expect_metadata,
+        module_paths,
+        platform_main_roc_path,
     })
 }
 
@@ -1073,6 +1752,9 @@ fn determine_built_host_path(
 
                 match legacy_host_path_res {
                     Ok(legacy_host_path) => BuiltHostOpt::Legacy(legacy_host_path),
+                    Err(_) if !target.is_native() => {
+                        report_missing_cross_host(platform_main_roc_path, target)
+                    }
                     Err(err_msg) => {
                         eprintln!("Legacy linking failed: {}", err_msg);
                         #[cfg(target_os = "linux")]
@@ -1088,6 +1770,9 @@ fn determine_built_host_path(
 
                 match surgical_artifacts {
                     Ok(surgical_artifacts) => BuiltHostOpt::Surgical(surgical_artifacts),
+                    Err(_) if !target.is_native() => {
+                        report_missing_cross_host(platform_main_roc_path, target)
+                    }
                     Err(paths_str) => {
                         // TODO improve error message
                         eprintln!(
@@ -1099,13 +1784,31 @@ fn determine_built_host_path(
                     }
                 }
             }
-            LinkingStrategy::Additive => {
-                unimplemented!()
-            }
+            // A platform does not ship a host that is preprocessed for the dev backend, so it is
+            // built from the platform's source every time
+            LinkingStrategy::Additive => BuiltHostOpt::None,
         }
     }
 }
 
+/// We cannot build the host of a platform for a target other than the system, so the platform has
+/// to ship one that either linker can link.
+fn report_missing_cross_host(platform_main_roc_path: &Path, target: Target) -> ! {
+    let host_path = |file_name: String| platform_main_roc_path.with_file_name(file_name);
+
+    eprintln!(
+        "I cannot link for {target}, because the platform does not have a prebuilt host for it. \
+        The platform needs to ship either of these:\n    \
+        {} and {}, for the surgical linker\n    \
+        {} or {}, for the legacy linker",
+        host_path(target.prebuilt_surgical_host()).display(),
+        host_path(target.metadata_file_name()).display(),
+        host_path(target.prebuilt_static_library()).display(),
+        host_path(target.prebuilt_static_object()).display(),
+    );
+    std::process::exit(1);
+}
+
 /// Get outut path for the executable.
 ///
 /// If you specified a path that ends in in a directory separator, then
@@ -1249,6 +1952,10 @@ fn spawn_wasm32_host_build_thread(
     })
 }
 
+/// Printed instead of preprocessing a rebuilt host, when the host has not changed since the last
+/// time it was preprocessed
+pub const PREPROCESSED_HOST_CACHED: &str = "🔨 Reusing the preprocessed host from the cache ...";
+
 /// Note this will copy the preprocessed host to the executable location
 /// where the surgical linker will modify that copy in-place.
 fn spawn_surgical_host_build_thread(
@@ -1259,6 +1966,7 @@ fn spawn_surgical_host_build_thread(
     preprocessed_path: PathBuf,
     output_exe_path: PathBuf,
     metadata_path: PathBuf,
+    cache_dir: Option<PathBuf>,
 ) -> std::thread::JoinHandle<(u128, BuiltHostOpt)> {
     std::thread::spawn(move || {
         // Printing to stderr because we want stdout to contain only the output of the roc program.
@@ -1283,15 +1991,33 @@ fn spawn_surgical_host_build_thread(
             Some(&stub_lib),
         );
 
-        roc_linker::preprocess_host(
-            target,
-            host_exe.as_path(),
-            metadata_path.as_path(),
-            preprocessed_path.as_path(),
-            &stub_lib,
-            false,
-            false,
-        );
+        match cache_dir {
+            Some(cache_dir) => {
+                let cached = roc_linker::preprocess_host_cached(
+                    target,
+                    host_exe.as_path(),
+                    metadata_path.as_path(),
+                    preprocessed_path.as_path(),
+                    &stub_lib,
+                    &cache_dir,
+                    false,
+                    false,
+                );
+
+                if cached {
+                    eprintln!("{PREPROCESSED_HOST_CACHED}");
+                }
+            }
+            None => roc_linker::preprocess_host(
+                target,
+                host_exe.as_path(),
+                metadata_path.as_path(),
+                preprocessed_path.as_path(),
+                &stub_lib,
+                false,
+                false,
+            ),
+        }
 
         // Copy preprocessed host to executable location.
         // The surgical linker will modify that copy in-place.
@@ -1334,7 +2060,9 @@ pub fn check_file<'a>(
     opt_main_path: Option<PathBuf>,
     emit_timings: bool,
     roc_cache_dir: RocCacheDir<'_>,
+    use_cache: bool,
     threading: Threading,
+    render: RenderTarget,
 ) -> Result<(Problems, Duration), LoadingProblem<'a>> {
     let compilation_start = Instant::now();
 
@@ -1347,11 +2075,17 @@ pub fn check_file<'a>(
     let load_config = LoadConfig {
         target,
         function_kind: FunctionKind::from_env(),
-        // TODO: expose this from CLI?
-        render: RenderTarget::ColorTerminal,
+        render,
         palette: DEFAULT_PALETTE,
         threading,
         exec_mode: ExecutionMode::Check,
+        expect_filter: None,
+        finalizers: Vec::new(),
+        types_cache_dir: match roc_cache_dir {
+            RocCacheDir::Persistent(_) if use_cache => Some(roc_packaging::cache::roc_cache_dir()),
+            _ => None,
+        },
+        fold_constants: true,
     };
     let mut loaded = roc_load::load_and_typecheck(
         arena,
@@ -1403,14 +2137,20 @@ pub fn check_file<'a>(
         println!("Finished checking in {} ms\n", compilation_end.as_millis(),);
     }
 
-    Ok((report_problems_typechecked(&mut loaded), compilation_end))
+    Ok((
+        report_problems_typechecked(&mut loaded, render),
+        compilation_end,
+    ))
 }
 
+/// With `pool_alloc_with_valgrind`, the tags of recursive unions are allocated from pools, and
+/// Valgrind is told about every one of them, like with `--pool-alloc --valgrind`.
 pub fn build_str_test<'a>(
     arena: &'a Bump,
     app_module_path: &Path,
     app_module_source: &'a str,
     build_host_requested: bool,
+    pool_alloc_with_valgrind: bool,
 ) -> Result<BuiltFile<'a>, BuildFileError<'a>> {
     let target = target_lexicon::Triple::host().into();
 
@@ -1418,8 +2158,32 @@ pub fn build_str_test<'a>(
         backend: CodeGenBackend::Llvm(LlvmBackendMode::Binary),
         opt_level: OptLevel::Normal,
         emit_debug_info: false,
+        emit_source_debug_info: false,
         emit_llvm_ir: false,
+        emit_llvm_bc: false,
+        emit_mono: false,
+        emit_asm: false,
         fuzz: false,
+        max_stack_frame: None,
+        host_panic: false,
+        sanitize_address: false,
+        check_alignment: false,
+        inline_threshold: None,
+        optimize_stack_alloc: false,
+        overflow_trap: false,
+        pgo: None,
+        lto: false,
+        sbf_compute_budget: None,
+        sbf_stack_limit: None,
+        stack_size: None,
+        data_sections: Vec::new(),
+        shared_library: false,
+        pie: true,
+        cfi: false,
+        pool_alloc: pool_alloc_with_valgrind,
+        valgrind: pool_alloc_with_valgrind,
+        bulk_memory: false,
+        wasm_exceptions: false,
     };
 
     let emit_timings = false;
@@ -1460,9 +2224,11 @@ pub fn build_str_test<'a>(
         linking_strategy,
         build_host_requested,
         suppress_build_host_warning,
+        None,
         wasm_dev_stack_bytes,
         loaded,
         compilation_start,
+        RenderTarget::ColorTerminal(ColorChoice::Always),
         None,
         false,
     )
@@ -15,12 +15,14 @@ pub fn target_triple_str(target: Target) -> &'static str {
     // https://stackoverflow.com/questions/15036909/clang-how-to-list-supported-target-architectures
     match target {
         Target::LinuxArm64 => "aarch64-unknown-linux-gnu",
+        Target::LinuxRiscv64 => "riscv64gc-unknown-linux-gnu",
         Target::LinuxX32 => "i386-unknown-linux-gnu",
         Target::LinuxX64 => "x86_64-unknown-linux-gnu",
         Target::MacArm64 => "aarch64-apple-darwin",
         Target::MacX64 => "x86_64-unknown-darwin10",
         Target::Wasm32 => "wasm32-unknown-unknown",
         Target::WinX64 => "x86_64-pc-windows-gnu",
+        Target::Sbf => "sbf-solana-solana",
         _ => internal_error!("TODO gracefully handle unsupported target: {:?}", target),
     }
 }
@@ -41,6 +43,12 @@ pub fn init_arch(target: Target) {
         Architecture::Wasm32 if cfg!(feature = "target-wasm32") => {
             LlvmTarget::initialize_webassembly(&InitializationConfig::default());
         }
+        Architecture::Riscv64 if cfg!(feature = "target-riscv64") => {
+            LlvmTarget::initialize_riscv(&InitializationConfig::default());
+        }
+        Architecture::Sbf if cfg!(feature = "target-sbf") => {
+            LlvmTarget::initialize_bpf(&InitializationConfig::default());
+        }
         _ => internal_error!(
             "TODO gracefully handle unsupported target architecture: {:?}",
             target.architecture()
@@ -61,6 +69,8 @@ pub fn arch_str(target: Target) -> &'static str {
         roc_target::Architecture::Aarch64 if cfg!(feature = "target-aarch64") => "aarch64",
         roc_target::Architecture::Aarch32 if cfg!(feature = "target-arm") => "arm",
         roc_target::Architecture::Wasm32 if cfg!(feature = "target-wasm32") => "wasm32",
+        roc_target::Architecture::Riscv64 if cfg!(feature = "target-riscv64") => "riscv64",
+        roc_target::Architecture::Sbf if cfg!(feature = "target-sbf") => "sbf",
         _ => internal_error!(
             "TODO gracefully handle unsupported target architecture: {:?}",
             target.architecture()
@@ -92,10 +102,17 @@ pub fn target_machine(
         _ => CodeModel::Default,
     };
 
+    let (cpu, features) = match target.architecture() {
+        // RV64GC: the base integer ISA plus the M, A, F, D and C extensions.
+        // LLVM picks the matching lp64d ABI from the D extension.
+        Architecture::Riscv64 => ("generic-rv64", "+m,+a,+f,+d,+c"),
+        _ => ("generic", ""),
+    };
+
     LlvmTarget::from_name(arch).unwrap().create_target_machine(
         &TargetTriple::create(target_triple_str(target)),
-        "generic",
-        "",
+        cpu,
+        features,
         opt,
         reloc,
         code_model,
@@ -38,11 +38,23 @@ pub fn build(b: *Build) void {
         .os_tag = std.Target.Os.Tag.linux,
         .abi = std.Target.Abi.none,
     });
+    const linux_riscv64_target = b.resolveTargetQuery(.{
+        .cpu_arch = std.Target.Cpu.Arch.riscv64,
+        .cpu_model = .{ .explicit = &std.Target.riscv.cpu.baseline_rv64 },
+        .os_tag = std.Target.Os.Tag.linux,
+        .abi = std.Target.Abi.none,
+    });
     const windows64_target = b.resolveTargetQuery(.{
         .cpu_arch = std.Target.Cpu.Arch.x86_64,
         .os_tag = std.Target.Os.Tag.windows,
         .abi = std.Target.Abi.none,
     });
+    const sbf_target = b.resolveTargetQuery(.{
+        // Solana's SBF is an extension of little-endian eBPF
+        .cpu_arch = std.Target.Cpu.Arch.bpfel,
+        .os_tag = std.Target.Os.Tag.freestanding,
+        .abi = std.Target.Abi.none,
+    });
     const wasm32_target = b.resolveTargetQuery(.{
         // 32-bit wasm
         .cpu_arch = std.Target.Cpu.Arch.wasm32,
@@ -55,8 +67,10 @@ pub fn build(b: *Build) void {
     generateLlvmIrFile(b, mode, linux32_target, main_path, "ir-x86", "builtins-x86");
     generateLlvmIrFile(b, mode, linux_x64_target, main_path, "ir-x86_64", "builtins-x86_64");
     generateLlvmIrFile(b, mode, linux_aarch64_target, main_path, "ir-aarch64", "builtins-aarch64");
+    generateLlvmIrFile(b, mode, linux_riscv64_target, main_path, "ir-riscv64", "builtins-riscv64");
     generateLlvmIrFile(b, mode, windows64_target, main_path, "ir-windows-x86_64", "builtins-windows-x86_64");
     generateLlvmIrFile(b, mode, wasm32_target, main_path, "ir-wasm32", "builtins-wasm32");
+    generateLlvmIrFile(b, mode, sbf_target, main_path, "ir-sbf", "builtins-sbf");
 
     // Generate Object Files
     generateObjectFile(b, mode, host_target, main_path, "object", "builtins-host");
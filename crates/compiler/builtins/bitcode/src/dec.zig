@@ -435,10 +435,12 @@ pub const RocDec = extern struct {
     pub fn mulSaturated(self: RocDec, other: RocDec) RocDec {
         const answer = RocDec.mulWithOverflow(self, other);
         if (answer.has_overflowed) {
-            if (answer.value.num < 0) {
-                return RocDec.max;
-            } else {
+            // Unlike for addition, the wrapped value says nothing about the sign of the answer
+            const is_answer_negative = (self.num < 0) != (other.num < 0);
+            if (is_answer_negative) {
                 return RocDec.min;
+            } else {
+                return RocDec.max;
             }
         } else {
             return answer.value;
@@ -1219,6 +1221,15 @@ test "mul: by 2" {
     try expectEqual(RocDec.fromU64(30), dec.mul(RocDec.fromU64(2)));
 }
 
+test "mulSaturated: overflow" {
+    const two = RocDec.fromU64(2);
+
+    try expectEqual(RocDec.max, RocDec.max.mulSaturated(two));
+    try expectEqual(RocDec.min, RocDec.max.mulSaturated(two.negate().?));
+    try expectEqual(RocDec.max, RocDec.min.mulSaturated(two.negate().?));
+    try expectEqual(RocDec.min, RocDec.min.mulSaturated(two));
+}
+
 test "div: 0 / 2" {
     var dec: RocDec = RocDec.fromU64(0);
 
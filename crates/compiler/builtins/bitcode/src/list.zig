@@ -346,6 +346,20 @@ pub fn listDecref(list: RocList, alignment: u32, element_width: usize, elements_
     list.decref(alignment, element_width, elements_refcounted, dec);
 }
 
+// Decrement lists whose elements are not refcounted, and free the unique ones together
+pub fn listDecrefBatch(lists: [*]const RocList, count: usize, alignment: u32) callconv(.C) void {
+    var batch = utils.DecrefBatch.init(alignment);
+
+    for (lists[0..count]) |list| {
+        // like `decref`, use the raw capacity to also decrement seamless slices
+        if (list.capacity_or_alloc_ptr != 0) {
+            batch.decref(list.getAllocationDataPtr());
+        }
+    }
+
+    batch.finish();
+}
+
 pub fn listWithCapacity(
     capacity: u64,
     alignment: u32,
@@ -84,6 +84,7 @@ comptime {
     exportListFn(list.listConcatUtf8, "concat_utf8");
     exportListFn(list.listIncref, "incref");
     exportListFn(list.listDecref, "decref");
+    exportListFn(list.listDecrefBatch, "decref_batch");
 }
 
 // Num Module
@@ -217,6 +218,7 @@ comptime {
     exportStrFn(str.strCloneTo, "clone_to");
     exportStrFn(str.withCapacityC, "with_capacity");
     exportStrFn(str.strAllocationPtr, "allocation_ptr");
+    exportStrFn(str.strDecrefBatch, "decref_batch");
     exportStrFn(str.strReleaseExcessCapacity, "release_excess_capacity");
     exportStrFn(str.strWithAsciiLowercased, "with_ascii_lowercased");
     exportStrFn(str.strWithAsciiUppercased, "with_ascii_uppercased");
@@ -240,13 +242,19 @@ comptime {
     exportUtilsFn(utils.increfRcPtrC, "incref_rc_ptr");
     exportUtilsFn(utils.decrefRcPtrC, "decref_rc_ptr");
     exportUtilsFn(utils.freeRcPtrC, "free_rc_ptr");
+    exportUtilsFn(utils.increfWeakRcPtrC, "incref_weak_rc_ptr");
+    exportUtilsFn(utils.decrefWeakRcPtrC, "decref_weak_rc_ptr");
+    exportUtilsFn(utils.weakRcPtrIsAlive, "weak_rc_ptr_is_alive");
     exportUtilsFn(utils.increfDataPtrC, "incref_data_ptr");
     exportUtilsFn(utils.decrefDataPtrC, "decref_data_ptr");
     exportUtilsFn(utils.freeDataPtrC, "free_data_ptr");
     exportUtilsFn(utils.isUnique, "is_unique");
     exportUtilsFn(utils.decrefCheckNullC, "decref_check_null");
     exportUtilsFn(utils.allocateWithRefcountC, "allocate_with_refcount");
+    exportUtilsFn(utils.allocateWithRefcountPooled, "allocate_pooled_with_refcount");
     exportUtilsFn(utils.dictPseudoSeed, "dict_pseudo_seed");
+    exportUtilsFn(utils.arenaEnter, "arena_enter");
+    exportUtilsFn(utils.arenaExit, "arena_exit");
 
     @export(panic_utils.panic, .{ .name = "roc_builtins.utils." ++ "panic", .linkage = .weak });
     @export(dbg_utils.dbg_impl, .{ .name = "roc_builtins.utils." ++ "dbg_impl", .linkage = .weak });
@@ -114,6 +114,69 @@ fn fluxsort_direct(
     }
 }
 
+test "fluxsort is stable" {
+    // Lengths on both sides of the quadsort cutoff, with records on both sides of the indirect cutoff.
+    inline for (.{ TestRecord(1), TestRecord(MAX_ELEMENT_BUFFER_SIZE / @sizeOf(u64)) }) |Record| {
+        for ([_]usize{ 20, 131, 132, 1000, 5000 }) |len| {
+            for (0..4) |seed| {
+                try test_fluxsort_stable(Record, len, seed);
+            }
+        }
+    }
+}
+
+/// Sorted by `key` only. Keys repeat, so `index` shows whether equal keys kept their order.
+fn TestRecord(comptime padding_words: usize) type {
+    return extern struct {
+        key: u64,
+        index: u64,
+        padding: [padding_words]u64,
+
+        const Self = @This();
+
+        fn init(key: u64, index: usize) Self {
+            return .{ .key = key, .index = index, .padding = [_]u64{~@as(u64, index)} ** padding_words };
+        }
+
+        fn lessThan(_: void, a: Self, b: Self) bool {
+            return a.key < b.key;
+        }
+
+        fn compare(_: Opaque, a_ptr: Opaque, b_ptr: Opaque) callconv(.C) u8 {
+            const a = @as(*Self, @alignCast(@ptrCast(a_ptr))).*;
+            const b = @as(*Self, @alignCast(@ptrCast(b_ptr))).*;
+
+            const gt = @as(u8, @intFromBool(a.key > b.key));
+            const lt = @as(u8, @intFromBool(a.key < b.key));
+
+            return lt + lt + gt;
+        }
+
+        fn copy(dst_ptr: Opaque, src_ptr: Opaque) callconv(.C) void {
+            @as(*Self, @alignCast(@ptrCast(dst_ptr))).* = @as(*Self, @alignCast(@ptrCast(src_ptr))).*;
+        }
+    };
+}
+
+/// Compare fluxsort element-for-element with a stable insertion sort.
+fn test_fluxsort_stable(comptime Record: type, len: usize, seed: u64) !void {
+    const arr = try testing.allocator.alloc(Record, len);
+    defer testing.allocator.free(arr);
+    const expected = try testing.allocator.alloc(Record, len);
+    defer testing.allocator.free(expected);
+
+    var rng = std.rand.DefaultPrng.init(seed);
+    for (arr, 0..) |*record, i| {
+        record.* = Record.init(rng.random().uintLessThan(u64, len / 4 + 1), i);
+    }
+
+    @memcpy(expected, arr);
+    std.sort.insertion(Record, expected, {}, Record.lessThan);
+
+    fluxsort(@ptrCast(arr.ptr), len, &Record.compare, null, false, &test_inc_n_data, @sizeOf(Record), @alignOf(Record), &Record.copy);
+    try testing.expectEqualSlices(Record, expected, arr);
+}
+
 /// This value is used to help stay within l3 cache when sorting.
 /// It technically should be tuned based on l3 cache size.
 /// This is important for large arrays with pointers to other data.
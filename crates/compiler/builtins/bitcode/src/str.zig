@@ -2725,6 +2725,19 @@ pub fn strAllocationPtr(
     return string.getAllocationPtr();
 }
 
+// Decrement strings, and free the unique ones together
+pub fn strDecrefBatch(strings: [*]const RocStr, count: usize) callconv(.C) void {
+    var batch = utils.DecrefBatch.init(RocStr.alignment);
+
+    for (strings[0..count]) |string| {
+        if (!string.isSmallStr() and string.capacity_or_alloc_ptr != 0) {
+            batch.decref(string.getAllocationPtr());
+        }
+    }
+
+    batch.finish();
+}
+
 pub fn strReleaseExcessCapacity(
     string: RocStr,
 ) callconv(.C) RocStr {
@@ -1,5 +1,6 @@
 const std = @import("std");
 const builtin = @import("builtin");
+const valgrind = @import("valgrind.zig");
 
 const DEBUG_INCDEC = false;
 const DEBUG_TESTING_ALLOC = false;
@@ -146,6 +147,12 @@ fn testing_roc_panic(c_ptr: *anyopaque, tag_id: u32) callconv(.C) void {
 }
 
 pub fn alloc(size: usize, alignment: u32) ?[*]u8 {
+    if (ARENAS_SUPPORTED) {
+        if (current_arena) |arena| {
+            return arenaAlloc(arena, size, alignment);
+        }
+    }
+
     return @as(?[*]u8, @ptrCast(roc_alloc(size, alignment)));
 }
 
@@ -153,13 +160,362 @@ pub fn realloc(c_ptr: [*]u8, new_size: usize, old_size: usize, alignment: u32) [
     if (DEBUG_INCDEC and builtin.target.cpu.arch != .wasm32) {
         std.debug.print("- realloc {*}\n", .{c_ptr});
     }
+
+    if (ARENAS_SUPPORTED) {
+        if (current_arena) |arena| {
+            const new_ptr = arenaAlloc(arena, new_size, alignment) orelse unreachable;
+            const kept = @min(old_size, new_size);
+            @memcpy(new_ptr[0..kept], c_ptr[0..kept]);
+            dealloc(c_ptr, alignment);
+
+            return new_ptr;
+        }
+    }
+
     return @as([*]u8, @ptrCast(roc_realloc(c_ptr, new_size, old_size, alignment)));
 }
 
 pub fn dealloc(c_ptr: [*]u8, alignment: u32) void {
+    if (poolFree(c_ptr) or arenaOwns(c_ptr)) {
+        return;
+    }
+
     return roc_dealloc(c_ptr, alignment);
 }
 
+// Pools for heap values of a few fixed sizes, used with `--pool-alloc`.
+//
+// The compiler emits the allocation size of every size class in `roc__pool_size_classes`, and
+// allocates a value of a fixed size with `allocateWithRefcountPooled`, passing the index of the
+// size class that it fits in. A pool gets its memory from `roc_alloc` in slabs, which are never
+// given back, and hands out the slots that were freed before carving new ones out of a slab.
+//
+// `dealloc` does not know the size of an allocation, so it looks for the slab that the
+// allocation is in. Every slab of a pool is twice as big as the one before, so there are only
+// ever a few of them. The pools are not thread safe.
+//
+// With `--valgrind`, every slot that is handed out is a block of its own to Valgrind, see
+// valgrind.zig.
+//
+// SBF programs can't keep anything between instructions, so they never use the pools, and this
+// makes sure they don't even reference the state of the pools.
+const POOLS_SUPPORTED = builtin.target.cpu.arch != .bpfel;
+pub const POOL_SIZE_CLASSES: usize = 8;
+const POOL_ALIGNMENT: u32 = 16;
+const POOL_MAX_SLABS: usize = 32;
+const POOL_FIRST_SLAB_SLOTS: usize = 64;
+const POOL_MAX_SLAB_DOUBLINGS: usize = 16;
+
+const PoolSlab = struct {
+    start: usize,
+    end: usize,
+};
+
+const PoolFreeSlot = struct {
+    next: ?*PoolFreeSlot,
+};
+
+const Pool = struct {
+    free: ?*PoolFreeSlot = null,
+    // the part of the newest slab that was never handed out
+    unused_start: usize = 0,
+    unused_end: usize = 0,
+    slabs: [POOL_MAX_SLABS]PoolSlab = undefined,
+    slab_count: usize = 0,
+};
+
+var pools: [POOL_SIZE_CLASSES]Pool = [_]Pool{.{}} ** POOL_SIZE_CLASSES;
+var pool_slab_count: usize = 0;
+
+fn poolSlotSize(size_class: u32) ?usize {
+    const size_classes = @extern(?*const [POOL_SIZE_CLASSES]u32, .{ .name = "roc__pool_size_classes", .linkage = .weak }) orelse return null;
+
+    if (size_class >= POOL_SIZE_CLASSES or size_classes[size_class] == 0) {
+        return null;
+    }
+
+    return size_classes[size_class];
+}
+
+fn poolAlloc(size_class: u32, size: usize, alignment: u32) ?[*]u8 {
+    if (!POOLS_SUPPORTED) {
+        return null;
+    }
+
+    // a slab taken from an arena would be freed with it
+    if (current_arena != null) {
+        return null;
+    }
+
+    const slot_size = poolSlotSize(size_class) orelse return null;
+    if (size > slot_size or alignment > POOL_ALIGNMENT) {
+        return null;
+    }
+
+    const pool = &pools[size_class];
+
+    if (pool.free) |slot| {
+        // Memcheck does not let us read a freed slot
+        valgrind.makeMemDefined(@ptrCast(slot), @sizeOf(PoolFreeSlot));
+        pool.free = slot.next;
+        valgrind.mallocLikeBlock(@ptrCast(slot), slot_size);
+
+        return @ptrCast(slot);
+    }
+
+    if (pool.unused_start == pool.unused_end) {
+        if (pool.slab_count == POOL_MAX_SLABS) {
+            return null;
+        }
+
+        const slots = POOL_FIRST_SLAB_SLOTS << @intCast(@min(pool.slab_count, POOL_MAX_SLAB_DOUBLINGS));
+        const bytes = slots * slot_size;
+        const start = @intFromPtr(alloc(bytes, POOL_ALIGNMENT) orelse return null);
+
+        pool.slabs[pool.slab_count] = .{ .start = start, .end = start + bytes };
+        pool.slab_count += 1;
+        pool_slab_count += 1;
+
+        pool.unused_start = start;
+        pool.unused_end = start + bytes;
+    }
+
+    const slot = pool.unused_start;
+    pool.unused_start += slot_size;
+    valgrind.mallocLikeBlock(@ptrFromInt(slot), slot_size);
+
+    return @ptrFromInt(slot);
+}
+
+fn poolFree(c_ptr: [*]u8) bool {
+    if (!POOLS_SUPPORTED or pool_slab_count == 0) {
+        return false;
+    }
+
+    const address = @intFromPtr(c_ptr);
+
+    for (&pools) |*pool| {
+        for (pool.slabs[0..pool.slab_count]) |slab| {
+            if (address >= slab.start and address < slab.end) {
+                const slot: *PoolFreeSlot = @ptrCast(@alignCast(c_ptr));
+                slot.* = .{ .next = pool.free };
+                pool.free = slot;
+                valgrind.freeLikeBlock(c_ptr);
+
+                return true;
+            }
+        }
+    }
+
+    return false;
+}
+
+// Arenas for `with_arena!`, an effect that platforms declare and the compiler implements, see
+// ir/arena.rs in roc_mono. The compiler calls `arenaEnter` before running the function that
+// `with_arena!` is given, and `arenaExit` once it returned. In between, `alloc` bumps a cursor
+// through the current arena instead of calling `roc_alloc`, `realloc` copies into the arena, and
+// `dealloc` leaves anything that is in an arena alone. Exiting gives the chunks of the arena back
+// to `roc_dealloc`. Every chunk is twice as big as the one before it, so usually there is only
+// the first one.
+//
+// The header of an arena is at the start of its first chunk. Arenas nest: entering one makes it
+// the current arena until it is exited, and then the arena around it is current again. Like the
+// pools, the arenas are not thread safe, and SBF programs can't keep a current arena, so there
+// `with_arena!` allocates with `roc_alloc` as usual.
+//
+// With `--valgrind`, every arena is a memory pool to Valgrind, which frees everything that was
+// allocated from it once it is exited.
+const ARENAS_SUPPORTED = builtin.target.cpu.arch != .bpfel;
+const ARENA_ALIGNMENT: u32 = 16;
+const ARENA_FIRST_CHUNK_SIZE: usize = 64 * 1024;
+
+const ArenaChunk = struct {
+    previous: ?*ArenaChunk,
+    end: usize,
+};
+
+const Arena = struct {
+    newest: *ArenaChunk,
+    cursor: usize,
+    enclosing: ?*Arena,
+};
+
+const ARENA_CHUNK_HEADER_SIZE = std.mem.alignForward(usize, @sizeOf(ArenaChunk), ARENA_ALIGNMENT);
+const ARENA_HEADER_SIZE = std.mem.alignForward(usize, @sizeOf(Arena), ARENA_ALIGNMENT);
+
+var current_arena: ?*Arena = null;
+
+fn arenaNewChunk(previous: ?*ArenaChunk, min_size: usize) ?*ArenaChunk {
+    const size = if (previous) |chunk|
+        @max(2 * (chunk.end - @intFromPtr(chunk)), ARENA_CHUNK_HEADER_SIZE + min_size)
+    else
+        @max(ARENA_FIRST_CHUNK_SIZE, ARENA_CHUNK_HEADER_SIZE + min_size);
+
+    const start = @intFromPtr(roc_alloc(size, ARENA_ALIGNMENT) orelse return null);
+    const chunk: *ArenaChunk = @ptrFromInt(start);
+    chunk.* = .{ .previous = previous, .end = start + size };
+
+    return chunk;
+}
+
+fn arenaAlloc(arena: *Arena, size: usize, alignment: u32) ?[*]u8 {
+    var start = std.mem.alignForward(usize, arena.cursor, alignment);
+
+    if (start + size > arena.newest.end) {
+        const chunk = arenaNewChunk(arena.newest, size + alignment) orelse return null;
+        arena.newest = chunk;
+
+        start = std.mem.alignForward(usize, @intFromPtr(chunk) + ARENA_CHUNK_HEADER_SIZE, alignment);
+    }
+
+    arena.cursor = start + size;
+    valgrind.mempoolAlloc(arena, @ptrFromInt(start), size);
+
+    return @ptrFromInt(start);
+}
+
+fn arenaOwns(c_ptr: [*]u8) bool {
+    if (!ARENAS_SUPPORTED) {
+        return false;
+    }
+
+    const address = @intFromPtr(c_ptr);
+
+    var arena = current_arena;
+    while (arena) |current| : (arena = current.enclosing) {
+        var chunk: ?*ArenaChunk = current.newest;
+        while (chunk) |allocated| : (chunk = allocated.previous) {
+            if (address >= @intFromPtr(allocated) and address < allocated.end) {
+                return true;
+            }
+        }
+    }
+
+    return false;
+}
+
+pub fn arenaEnter() callconv(.C) ?*anyopaque {
+    if (!ARENAS_SUPPORTED) {
+        return null;
+    }
+
+    const chunk = arenaNewChunk(null, ARENA_HEADER_SIZE) orelse return null;
+    const arena: *Arena = @ptrFromInt(@intFromPtr(chunk) + ARENA_CHUNK_HEADER_SIZE);
+    arena.* = .{
+        .newest = chunk,
+        .cursor = @intFromPtr(arena) + ARENA_HEADER_SIZE,
+        .enclosing = current_arena,
+    };
+
+    current_arena = arena;
+    valgrind.createMempool(arena);
+
+    return arena;
+}
+
+pub fn arenaExit(arena_or_null: ?*anyopaque) callconv(.C) void {
+    if (!ARENAS_SUPPORTED) {
+        return;
+    }
+
+    const arena: *Arena = @ptrCast(@alignCast(arena_or_null orelse return));
+    current_arena = arena.enclosing;
+    valgrind.destroyMempool(arena);
+
+    // the header of the arena is in its first chunk, which is freed last
+    var chunk: ?*ArenaChunk = arena.newest;
+    while (chunk) |allocated| {
+        chunk = allocated.previous;
+        roc_dealloc(allocated, ARENA_ALIGNMENT);
+    }
+}
+
+// Batches of decrements, for several lists or strings that are dropped one after the other, see
+// decref_batch.rs in roc_gen_llvm. A batch decrements every refcount, and frees the allocations
+// that were unique together once the batch is finished (or full). Everything in a batch has the
+// same alignment and no refcounted elements, so freeing only takes the allocation pointers.
+//
+// A host can define `roc_dealloc_batch` to free all of them with a single call, for instance to
+// take an allocator lock only once. It is a weak symbol, so hosts that do not define it still
+// link, and then every allocation is given to `roc_dealloc` as usual.
+pub const DECREF_BATCH_SIZE: usize = 16;
+
+pub const DecrefBatch = struct {
+    alignment: u32,
+    unique: [DECREF_BATCH_SIZE][*]u8 = undefined,
+    unique_count: usize = 0,
+
+    pub fn init(element_alignment: u32) DecrefBatch {
+        return .{ .alignment = @max(@sizeOf(usize), element_alignment) };
+    }
+
+    pub fn decref(self: *DecrefBatch, data_ptr_or_null: ?[*]u8) void {
+        if (RC_TYPE == .none) return;
+
+        const data_ptr = data_ptr_or_null orelse return;
+        const refcount_ptr = @as([*]isize, @ptrCast(@alignCast(data_ptr))) - 1;
+
+        if (!decrementWasLast(refcount_ptr)) {
+            return;
+        }
+
+        const extra_bytes = @max(@sizeOf(usize), self.alignment);
+        self.unique[self.unique_count] = @as([*]u8, @ptrCast(refcount_ptr)) - (extra_bytes - @sizeOf(usize));
+        self.unique_count += 1;
+
+        if (self.unique_count == DECREF_BATCH_SIZE) {
+            self.finish();
+        }
+    }
+
+    pub fn finish(self: *DecrefBatch) void {
+        deallocBatch(self.unique[0..self.unique_count], self.alignment);
+        self.unique_count = 0;
+    }
+};
+
+inline fn decrementWasLast(refcount_ptr: [*]isize) bool {
+    const refcount: isize = refcount_ptr[0];
+    if (rcConstant(refcount)) {
+        return false;
+    }
+
+    switch (RC_TYPE) {
+        .normal => {
+            refcount_ptr[0] = refcount -% 1;
+            return refcount == 1;
+        },
+        .atomic => {
+            return @atomicRmw(isize, &refcount_ptr[0], .Sub, 1, .monotonic) == 1;
+        },
+        .none => return false,
+    }
+}
+
+fn deallocBatch(allocation_ptrs: [][*]u8, alignment: u32) void {
+    // the pools and arenas take back what is theirs themselves
+    var count: usize = 0;
+    for (allocation_ptrs) |allocation_ptr| {
+        if (!(poolFree(allocation_ptr) or arenaOwns(allocation_ptr))) {
+            allocation_ptrs[count] = allocation_ptr;
+            count += 1;
+        }
+    }
+
+    if (count == 0) {
+        return;
+    }
+
+    const roc_dealloc_batch = @extern(?*const fn ([*]const [*]u8, usize, u32) callconv(.C) void, .{ .name = "roc_dealloc_batch", .linkage = .weak });
+    if (roc_dealloc_batch) |dealloc_batch| {
+        return dealloc_batch(allocation_ptrs.ptr, count, alignment);
+    }
+
+    for (allocation_ptrs[0..count]) |allocation_ptr| {
+        roc_dealloc(allocation_ptr, alignment);
+    }
+}
+
 // indirection because otherwise zig creates an alias to the panic function which our LLVM code
 // does not know how to deal with
 pub fn test_panic(c_ptr: *anyopaque, crash_tag: u32) callconv(.C) void {
@@ -257,6 +613,16 @@ pub fn decrefCheckNullC(
     }
 }
 
+/// Set in the value of the immediate variant of a `TaggedPointer` union, which holds a small
+/// integer instead of pointing to the heap. Only used on 64-bit targets, where pointers to the heap
+/// never have it set.
+const IMMEDIATE_BIT: usize = if (@sizeOf(usize) == 8) @as(usize, 1) << (@bitSizeOf(usize) - 1) else 0;
+
+/// An immediate has no refcount, like a null pointer
+inline fn isImmediate(ptr: usize) bool {
+    return ptr & IMMEDIATE_BIT != 0;
+}
+
 pub fn decrefDataPtrC(
     bytes_or_null: ?[*]u8,
     alignment: u32,
@@ -265,6 +631,7 @@ pub fn decrefDataPtrC(
     const bytes = bytes_or_null orelse return;
 
     const data_ptr = @intFromPtr(bytes);
+    if (isImmediate(data_ptr)) return;
     const tag_mask: usize = if (@sizeOf(usize) == 8) 0b111 else 0b11;
     const unmasked_ptr = data_ptr & ~tag_mask;
 
@@ -281,6 +648,8 @@ pub fn increfDataPtrC(
     const bytes = bytes_or_null orelse return;
 
     const ptr = @intFromPtr(bytes);
+    if (isImmediate(ptr)) return;
+
     const tag_mask: usize = if (@sizeOf(usize) == 8) 0b111 else 0b11;
     const masked_ptr = ptr & ~tag_mask;
 
@@ -297,6 +666,8 @@ pub fn freeDataPtrC(
     const bytes = bytes_or_null orelse return;
 
     const ptr = @intFromPtr(bytes);
+    if (isImmediate(ptr)) return;
+
     const tag_mask: usize = if (@sizeOf(usize) == 8) 0b111 else 0b11;
     const masked_ptr = ptr & ~tag_mask;
 
@@ -312,9 +683,63 @@ pub fn freeRcPtrC(
     elements_refcounted: bool,
 ) callconv(.C) void {
     const bytes = bytes_or_null orelse return;
+
+    // A value with a single owner is freed without a decrement, but a weak reference may still
+    // point to it. Then the weak reference frees the allocation instead.
+    if (RC_TYPE != .none and bytes[0] >= WEAK_REFCOUNT_ONE) {
+        if (subRefcount(&bytes[0], 1) != 1) return;
+    }
+
     return free_ptr_to_refcount(bytes, alignment, elements_refcounted);
 }
 
+/// The weak references to a box are counted in the upper half of its refcount, so a box that
+/// is only weakly referenced has a refcount other than 1, and is never unique. When the last box
+/// goes away, the lower half becomes 0, but only the last weak reference frees the allocation.
+const WEAK_REFCOUNT_ONE: isize = 1 << (@bitSizeOf(isize) / 2);
+const STRONG_REFCOUNT_MASK: isize = WEAK_REFCOUNT_ONE - 1;
+
+pub fn increfWeakRcPtrC(ptr_to_refcount: *isize, amount: isize) callconv(.C) void {
+    if (RC_TYPE == .none) return;
+
+    // a constant is never freed, so it needs no weak references either
+    if (rcConstant(ptr_to_refcount.*)) return;
+
+    _ = subRefcount(ptr_to_refcount, -%(amount *% WEAK_REFCOUNT_ONE));
+}
+
+/// Returns whether this was the last reference of any kind, so that the caller has to decrement
+/// the value in the box and free the allocation.
+pub fn decrefWeakRcPtrC(ptr_to_refcount: *isize) callconv(.C) bool {
+    if (RC_TYPE == .none) return false;
+
+    if (rcConstant(ptr_to_refcount.*)) return false;
+
+    return subRefcount(ptr_to_refcount, WEAK_REFCOUNT_ONE) == WEAK_REFCOUNT_ONE;
+}
+
+/// Subtracts `amount` from the refcount and returns the refcount from before
+inline fn subRefcount(ptr_to_refcount: *isize, amount: isize) isize {
+    switch (RC_TYPE) {
+        .normal => {
+            const last = ptr_to_refcount.*;
+            ptr_to_refcount.* = last -% amount;
+            return last;
+        },
+        .atomic => {
+            return @atomicRmw(isize, ptr_to_refcount, .Sub, amount, .monotonic);
+        },
+        .none => unreachable,
+    }
+}
+
+/// Whether a box that the weak reference points to still exists
+pub fn weakRcPtrIsAlive(ptr_to_refcount: *isize) callconv(.C) bool {
+    const refcount = ptr_to_refcount.*;
+
+    return rcConstant(refcount) or (refcount & STRONG_REFCOUNT_MASK) != 0;
+}
+
 pub fn decref(
     bytes_or_null: ?[*]u8,
     data_bytes: usize,
@@ -400,6 +825,8 @@ pub fn isUnique(
     const bytes = bytes_or_null orelse return true;
 
     const ptr = @intFromPtr(bytes);
+    if (isImmediate(ptr)) return true;
+
     const tag_mask: usize = if (@sizeOf(usize) == 8) 0b111 else 0b11;
     const masked_ptr = ptr & ~tag_mask;
 
@@ -514,6 +941,30 @@ pub fn allocateWithRefcount(
 
     const new_bytes: [*]u8 = alloc(length, alignment) orelse unreachable;
 
+    return initRefcount(new_bytes, data_bytes, alignment, extra_bytes);
+}
+
+// Like allocateWithRefcount, but takes the allocation from the pool of `size_class` when it fits.
+// Only used for values whose elements are not refcounted.
+pub fn allocateWithRefcountPooled(
+    data_bytes: usize,
+    element_alignment: u32,
+    size_class: u32,
+) callconv(.C) [*]u8 {
+    const ptr_width = @sizeOf(usize);
+    const alignment = @max(ptr_width, element_alignment);
+    const extra_bytes = @max(ptr_width, element_alignment);
+    const length = extra_bytes + data_bytes;
+
+    const new_bytes: [*]u8 = poolAlloc(size_class, length, alignment) orelse
+        return allocateWithRefcount(data_bytes, element_alignment, false);
+
+    return initRefcount(new_bytes, data_bytes, alignment, extra_bytes);
+}
+
+inline fn initRefcount(new_bytes: [*]u8, data_bytes: usize, alignment: u32, extra_bytes: usize) [*]u8 {
+    const ptr_width = @sizeOf(usize);
+
     if (DEBUG_ALLOC and builtin.target.cpu.arch != .wasm32) {
         std.debug.print("+ allocated {*} ({} bytes with alignment {})\n", .{ new_bytes, data_bytes, alignment });
     }
@@ -583,14 +1034,85 @@ test "increfC, static data" {
     try std.testing.expectEqual(mock_rc, REFCOUNT_MAX_ISIZE);
 }
 
-// This returns a compilation dependent pseudo random seed for dictionaries.
-// The seed is the address of this function.
+test "arena, freed in one dealloc" {
+    const arena = arenaEnter();
+
+    const first = allocateWithRefcount(100, 8, false);
+    const grown = realloc(first - 8, 4000, 108, 8);
+    _ = allocateWithRefcount(ARENA_FIRST_CHUNK_SIZE, 8, false);
+
+    try std.testing.expect(arenaOwns(grown));
+    dealloc(grown, 8);
+
+    // the testing allocator reports the chunks if they are not all freed
+    arenaExit(arena);
+    try std.testing.expect(current_arena == null);
+}
+
+test "decref batch, frees the unique allocations" {
+    const shared = allocateWithRefcount(24, 8, false);
+    var batch = DecrefBatch.init(8);
+
+    for (0..DECREF_BATCH_SIZE + 3) |_| {
+        batch.decref(allocateWithRefcount(24, 8, false));
+    }
+
+    increfDataPtrC(shared, 1);
+    batch.decref(shared);
+    batch.decref(null);
+
+    // the testing allocator reports the allocations that are not freed
+    batch.finish();
+    try std.testing.expectEqual(@as(usize, 0), batch.unique_count);
+
+    batch.decref(shared);
+    batch.finish();
+}
+
+test "weak ref, frees the allocation after the last box" {
+    const data_ptr = allocateWithRefcount(24, 8, false);
+    const ptr_to_refcount: *isize = @ptrCast(@alignCast(data_ptr - @sizeOf(usize)));
+
+    increfWeakRcPtrC(ptr_to_refcount, 1);
+
+    // the box goes away, but its value stays until the weak reference is dropped
+    freeDataPtrC(data_ptr, 8, false);
+    try std.testing.expect(!weakRcPtrIsAlive(ptr_to_refcount));
+
+    // the testing allocator reports the allocation if it is not freed
+    try std.testing.expect(decrefWeakRcPtrC(ptr_to_refcount));
+    freeRcPtrC(@ptrCast(ptr_to_refcount), 8, false);
+}
+
+/// The seed of all dictionaries on targets that load programs at a fixed address, like SBF, when
+/// the host does not define `roc_random_seed`. It is the first of the wyhash secrets.
+pub const DEFAULT_DICT_SEED: u64 = 0xa0761d6478bd642f;
+
+// This returns the seed that every dictionary hashes its keys with.
+// A host can define `roc_random_seed` to give every process its own, for instance from the OS's
+// random number generator. That is the only real protection against keys picked to collide.
+// It is a weak symbol, so hosts that do not define it still link.
+//
+// Otherwise, this returns a compilation dependent pseudo random seed: the address of this function.
 // This avoids all roc Dicts using a known seed and being trivial to DOS.
 // Still not as secure as true random, but a lot better.
-// This value must not change between calls unless Dict is changed to store the seed on creation.
 // Note: On esstentially all OSes, this will be affected by ASLR and different each run.
 // In wasm, the value will be constant to the build as a whole.
 // Either way, it can not be know by an attacker unless they get access to the executable.
+// SBF programs always run at the same address, so there this returns DEFAULT_DICT_SEED, which
+// at least does not change when the program is rebuilt.
+//
+// This value must not change between calls unless Dict is changed to store the seed on creation.
+// So `roc_random_seed` must return the same value every time it is called.
 pub fn dictPseudoSeed() callconv(.C) u64 {
+    const roc_random_seed = @extern(?*const fn () callconv(.C) u64, .{ .name = "roc_random_seed", .linkage = .weak });
+    if (roc_random_seed) |random_seed| {
+        return random_seed();
+    }
+
+    if (builtin.target.cpu.arch == .bpfel) {
+        return DEFAULT_DICT_SEED;
+    }
+
     return @as(u64, @intCast(@intFromPtr(&dictPseudoSeed)));
 }
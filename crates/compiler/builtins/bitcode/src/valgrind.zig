@@ -0,0 +1,90 @@
+// Valgrind client requests for the allocations that the builtins make themselves, used with
+// `--valgrind`.
+//
+// The pools and arenas in utils.zig carve many allocations out of one block that they got from
+// `roc_alloc`, so all Valgrind sees is that block. With these requests, Memcheck tracks every
+// allocation in it like a block of its own: it reports the ones that are leaked or used after
+// they were freed, and ignores the block they are in when it looks for leaks.
+//
+// A client request is a sequence of instructions that does nothing, unless the program runs
+// under Valgrind, which recognizes it. The compiler defines `roc__valgrind` with `--valgrind`,
+// and without it the builtins do not make any requests.
+const builtin = @import("builtin");
+
+const MALLOCLIKE_BLOCK: usize = 0x1301;
+const FREELIKE_BLOCK: usize = 0x1302;
+const CREATE_MEMPOOL: usize = 0x1303;
+const DESTROY_MEMPOOL: usize = 0x1304;
+const MEMPOOL_ALLOC: usize = 0x1305;
+
+// the requests of Memcheck start at the tool base of 'M', 'C'
+const MAKE_MEM_DEFINED: usize = (@as(usize, 'M') << 24 | @as(usize, 'C') << 16) + 2;
+
+fn enabled() bool {
+    return @extern(?*const u8, .{ .name = "roc__valgrind", .linkage = .weak }) != null;
+}
+
+fn clientRequest(request: usize, a1: usize, a2: usize, a3: usize, a4: usize) void {
+    if (!enabled()) {
+        return;
+    }
+
+    const args = [6]usize{ request, a1, a2, a3, a4, 0 };
+
+    // the magic sequences are the ones in valgrind.h, every other architecture is left out
+    switch (builtin.target.cpu.arch) {
+        .x86_64 => {
+            _ = asm volatile (
+                \\ rolq $3,  %%rdi ; rolq $13, %%rdi
+                \\ rolq $61, %%rdi ; rolq $51, %%rdi
+                \\ xchgq %%rbx,%%rbx
+                : [_] "={rdx}" (-> usize),
+                : [_] "{rax}" (&args),
+                  [_] "{rdx}" (@as(usize, 0)),
+                : "cc", "memory"
+            );
+        },
+        .aarch64 => {
+            _ = asm volatile (
+                \\ ror x12, x12, #3  ;  ror x12, x12, #13
+                \\ ror x12, x12, #51 ;  ror x12, x12, #61
+                \\ orr x10, x10, x10
+                : [_] "={x3}" (-> usize),
+                : [_] "{x4}" (&args),
+                  [_] "{x3}" (@as(usize, 0)),
+                : "cc", "memory"
+            );
+        },
+        else => {},
+    }
+}
+
+// `size` bytes at `ptr` were handed out, as if by malloc
+pub fn mallocLikeBlock(ptr: [*]u8, size: usize) void {
+    clientRequest(MALLOCLIKE_BLOCK, @intFromPtr(ptr), size, 0, 0);
+}
+
+// the block at `ptr` that was announced with `mallocLikeBlock` was freed
+pub fn freeLikeBlock(ptr: [*]u8) void {
+    clientRequest(FREELIKE_BLOCK, @intFromPtr(ptr), 0, 0, 0);
+}
+
+// Memcheck does not let a program access a freed block. This allows accessing `size` bytes of it
+// again, for the allocator to read what it keeps there.
+pub fn makeMemDefined(ptr: [*]u8, size: usize) void {
+    clientRequest(MAKE_MEM_DEFINED, @intFromPtr(ptr), size, 0, 0);
+}
+
+// Allocations from a memory pool are freed all at once, when the pool is destroyed. `pool` can be
+// any address that identifies the pool.
+pub fn createMempool(pool: *const anyopaque) void {
+    clientRequest(CREATE_MEMPOOL, @intFromPtr(pool), 0, 0, 0);
+}
+
+pub fn destroyMempool(pool: *const anyopaque) void {
+    clientRequest(DESTROY_MEMPOOL, @intFromPtr(pool), 0, 0, 0);
+}
+
+pub fn mempoolAlloc(pool: *const anyopaque, ptr: [*]u8, size: usize) void {
+    clientRequest(MEMPOOL_ALLOC, @intFromPtr(pool), @intFromPtr(ptr), size, 0);
+}
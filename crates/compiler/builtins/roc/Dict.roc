@@ -544,7 +544,12 @@ remove_helper = |buckets, bucket_index, dist_and_fingerprint, data, key|
 ## ```
 update : Dict k v, k, (Result v [Missing] -> Result v [Missing]) -> Dict k v
 update = |@Dict({ buckets, data, max_bucket_capacity, max_load_factor, shifts }), key, alter|
-    { bucket_index, result } = find(@Dict({ buckets, data, max_bucket_capacity, max_load_factor, shifts }), key)
+    # The key is hashed once, for both the lookup and the insert.
+    hash = hash_key(key)
+    base_dist_and_fingerprint = dist_and_fingerprint_from_hash(hash)
+    base_bucket_index = bucket_index_from_hash(hash, shifts)
+
+    { bucket_index, result } = find_hashed(buckets, data, base_bucket_index, base_dist_and_fingerprint, key)
     when result is
         Ok(value) ->
             when alter(Ok(value)) is
@@ -565,10 +570,6 @@ update = |@Dict({ buckets, data, max_bucket_capacity, max_load_factor, shifts })
                     else
                         # Can skip work by jumping staight to the found bucket.
                         # That will be the location we want to insert in.
-                        hash = hash_key(key)
-                        base_dist_and_fingerprint = dist_and_fingerprint_from_hash(hash)
-                        base_bucket_index = bucket_index_from_hash(hash, shifts)
-
                         # Due to the unrolling of loops in find along with loop optimizations,
                         # The bucket_index is not guaranteed to be correct here.
                         # It is only correct if we have traversed past the number of find unrolls.
@@ -762,6 +763,11 @@ find = |@Dict({ buckets, data, shifts }), key|
     dist_and_fingerprint = dist_and_fingerprint_from_hash(hash)
     bucket_index = bucket_index_from_hash(hash, shifts)
 
+    find_hashed(buckets, data, bucket_index, dist_and_fingerprint, key)
+
+# Like `find`, starting from the bucket and fingerprint of a key that is already hashed.
+find_hashed : List Bucket, List (k, v), U64, U32, k -> { bucket_index : U64, result : Result v [KeyNotFound] } where k implements Eq
+find_hashed = |buckets, data, bucket_index, dist_and_fingerprint, key|
     if !(List.is_empty(data)) then
         # TODO: this is true in the C++ code, confirm it in Roc as well.
         # unrolled loop. *Always* check a few directly, then enter the loop. This is faster.
@@ -56,7 +56,9 @@ InspectFormatter implements
     set : set, ElemWalker state set elem, (elem -> Inspector f) -> Inspector f where f implements InspectFormatter
     dict : dict, KeyValWalker state dict key value, (key -> Inspector f), (value -> Inspector f) -> Inspector f where f implements InspectFormatter
 
-    # In text, this would render as `<opaque>`
+    # In text, this would render as `<opaque>`. An opaque type can show its contents instead by
+    # implementing `Inspect` with its own `to_inspector`, which is then used wherever it appears.
+    # The values shown for a failed `expect` do not go through `Inspect`, so they don't use it.
     # TODO: Pass the type name to opaque so that it can be displayed.
     opaque : * -> Inspector f where f implements InspectFormatter
 
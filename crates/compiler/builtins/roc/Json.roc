@@ -0,0 +1,738 @@
+module [
+    Json,
+    FieldNameMapping,
+    utf8,
+    utf8_with,
+    from_bytes,
+]
+
+import Bool exposing [Bool]
+import Result exposing [Result]
+import List
+import Str
+import Num exposing [U8, U16, U32, U64, U128, I8, I16, I32, I64, I128, F32, F64, Dec]
+import Encode exposing [Encoder, EncoderFormatting]
+import Decode exposing [Decoder, DecoderFormatting, DecodeError, DecodeResult, Decoding]
+
+## How the names of record fields are written in a JSON document.
+##
+## `Default` uses the name of the field as it is. `CamelCase` turns `user_id` into `userId`,
+## and `SnakeCase` turns `userId` into `user_id`. When decoding, a key that is not a field
+## of the record is converted back before it is skipped.
+FieldNameMapping : [Default, SnakeCase, CamelCase]
+
+## A format that encodes values as UTF-8 JSON, and decodes them from it.
+##
+## Records become objects, lists and tuples become arrays, and a tag becomes an object with the
+## name of the tag as its only key, and its payload as an array: `Ok(1)` is `{"Ok":[1]}`.
+Json := { field_name_mapping : FieldNameMapping }
+    implements [
+        EncoderFormatting {
+            u8: encode_u8,
+            u16: encode_u16,
+            u32: encode_u32,
+            u64: encode_u64,
+            u128: encode_u128,
+            i8: encode_i8,
+            i16: encode_i16,
+            i32: encode_i32,
+            i64: encode_i64,
+            i128: encode_i128,
+            f32: encode_f32,
+            f64: encode_f64,
+            dec: encode_dec,
+            bool: encode_bool,
+            string: encode_string,
+            list: encode_list,
+            record: encode_record,
+            tuple: encode_tuple,
+            tag: encode_tag,
+        },
+        DecoderFormatting {
+            u8: decode_u8,
+            u16: decode_u16,
+            u32: decode_u32,
+            u64: decode_u64,
+            u128: decode_u128,
+            i8: decode_i8,
+            i16: decode_i16,
+            i32: decode_i32,
+            i64: decode_i64,
+            i128: decode_i128,
+            f32: decode_f32,
+            f64: decode_f64,
+            dec: decode_dec,
+            bool: decode_bool,
+            string: decode_string,
+            list: decode_list,
+            record: decode_record,
+            tuple: decode_tuple,
+        },
+    ]
+
+## The JSON format, with the record fields named as they are in Roc.
+## ```roc
+## expect Encode.to_bytes({ name: "Roc" }, Json.utf8) == Str.to_utf8("{\"name\":\"Roc\"}")
+## ```
+utf8 : Json
+utf8 = @Json({ field_name_mapping: Default })
+
+## The JSON format, with the record fields named according to `field_name_mapping`.
+## ```roc
+## expect Encode.to_bytes({ user_id: 1 }, Json.utf8_with({ field_name_mapping: CamelCase })) == Str.to_utf8("{\"userId\":1}")
+## ```
+utf8_with : { field_name_mapping ?? FieldNameMapping } -> Json
+utf8_with = |{ field_name_mapping ?? Default }| @Json({ field_name_mapping })
+
+## Decode a JSON document, which may have whitespace before and after the value.
+##
+## [Decode.from_bytes] only says that decoding failed. This also says where: `offset` is the
+## index of the first byte that could not be decoded, or of the first byte after the value if
+## there is more than whitespace after it.
+## ```roc
+## expect Json.from_bytes(Str.to_utf8("[1, 2, x]"), Json.utf8) == Err(InvalidJson({ offset: 7 }))
+## ```
+from_bytes : List U8, Json -> Result val [InvalidJson { offset : U64 }] where val implements Decoding
+from_bytes = |bytes, fmt|
+    { result, rest } = Decode.from_bytes_partial(bytes, fmt)
+
+    when result is
+        Ok(val) ->
+            remaining = skip_whitespace(rest)
+
+            if List.is_empty(remaining) then
+                Ok(val)
+            else
+                Err(InvalidJson({ offset: List.len(bytes) - List.len(remaining) }))
+
+        Err(TooShort) ->
+            # the decoders leave `rest` at the byte they could not decode
+            Err(InvalidJson({ offset: List.len(bytes) - List.len(rest) }))
+
+# ENCODE
+
+encode_number : Num * -> Encoder Json
+encode_number = |num|
+    Encode.custom(|bytes, @Json(_)| List.concat(bytes, Str.to_utf8(Num.to_str(num))))
+
+encode_u8 : U8 -> Encoder Json
+encode_u8 = encode_number
+
+encode_u16 : U16 -> Encoder Json
+encode_u16 = encode_number
+
+encode_u32 : U32 -> Encoder Json
+encode_u32 = encode_number
+
+encode_u64 : U64 -> Encoder Json
+encode_u64 = encode_number
+
+encode_u128 : U128 -> Encoder Json
+encode_u128 = encode_number
+
+encode_i8 : I8 -> Encoder Json
+encode_i8 = encode_number
+
+encode_i16 : I16 -> Encoder Json
+encode_i16 = encode_number
+
+encode_i32 : I32 -> Encoder Json
+encode_i32 = encode_number
+
+encode_i64 : I64 -> Encoder Json
+encode_i64 = encode_number
+
+encode_i128 : I128 -> Encoder Json
+encode_i128 = encode_number
+
+encode_f32 : F32 -> Encoder Json
+encode_f32 = encode_number
+
+encode_f64 : F64 -> Encoder Json
+encode_f64 = encode_number
+
+encode_dec : Dec -> Encoder Json
+encode_dec = encode_number
+
+encode_bool : Bool -> Encoder Json
+encode_bool = |b|
+    text = if b then "true" else "false"
+
+    Encode.custom(|bytes, @Json(_)| List.concat(bytes, Str.to_utf8(text)))
+
+encode_string : Str -> Encoder Json
+encode_string = |str|
+    Encode.custom(|bytes, @Json(_)| append_string(bytes, str))
+
+append_string : List U8, Str -> List U8
+append_string = |bytes, str|
+    utf8_bytes = Str.to_utf8(str)
+    with_quote = List.append(bytes, '"')
+
+    escaped =
+        if List.any(utf8_bytes, needs_escape) then
+            List.walk(utf8_bytes, List.reserve(with_quote, List.len(utf8_bytes)), append_escaped)
+        else
+            List.concat(with_quote, utf8_bytes)
+
+    List.append(escaped, '"')
+
+needs_escape : U8 -> Bool
+needs_escape = |byte| byte == '"' || byte == '\\' || byte < 0x20
+
+append_escaped : List U8, U8 -> List U8
+append_escaped = |bytes, byte|
+    when byte is
+        '"' -> List.concat(bytes, ['\\', '"'])
+        '\\' -> List.concat(bytes, ['\\', '\\'])
+        '\n' -> List.concat(bytes, ['\\', 'n'])
+        '\r' -> List.concat(bytes, ['\\', 'r'])
+        '\t' -> List.concat(bytes, ['\\', 't'])
+        0x08 -> List.concat(bytes, ['\\', 'b'])
+        0x0C -> List.concat(bytes, ['\\', 'f'])
+        _ if byte < 0x20 ->
+            # the other control characters have no short escape
+            List.concat(bytes, ['\\', 'u', '0', '0', hex_digit(Num.shift_right_zf_by(byte, 4)), hex_digit(Num.bitwise_and(byte, 0xF))])
+
+        _ -> List.append(bytes, byte)
+
+hex_digit : U8 -> U8
+hex_digit = |digit|
+    if digit < 10 then
+        '0' + digit
+    else
+        'a' + digit - 10
+
+encode_list : List elem, (elem -> Encoder Json) -> Encoder Json
+encode_list = |lst, encode_elem|
+    Encode.custom(
+        |bytes, @Json(options)|
+            (with_elems, _) = List.walk(
+                lst,
+                (List.append(bytes, '['), Bool.false),
+                |(buf, prepend_sep), elem|
+                    with_sep = if prepend_sep then List.append(buf, ',') else buf
+
+                    (Encode.append_with(with_sep, encode_elem(elem), @Json(options)), Bool.true),
+            )
+
+            List.append(with_elems, ']'),
+    )
+
+encode_record : List { key : Str, value : Encoder Json } -> Encoder Json
+encode_record = |fields|
+    Encode.custom(
+        |bytes, @Json(options)|
+            (with_fields, _) = List.walk(
+                fields,
+                (List.append(bytes, '{'), Bool.false),
+                |(buf, prepend_sep), { key, value }|
+                    with_sep = if prepend_sep then List.append(buf, ',') else buf
+
+                    with_key =
+                        append_string(with_sep, to_json_name(key, options.field_name_mapping))
+                        |> List.append(':')
+
+                    (Encode.append_with(with_key, value, @Json(options)), Bool.true),
+            )
+
+            List.append(with_fields, '}'),
+    )
+
+encode_tuple : List (Encoder Json) -> Encoder Json
+encode_tuple = |elems| encode_list(elems, |elem| elem)
+
+encode_tag : Str, List (Encoder Json) -> Encoder Json
+encode_tag = |name, payload|
+    Encode.custom(
+        |bytes, @Json(options)|
+            with_name =
+                List.append(bytes, '{')
+                |> append_string(name)
+                |> List.append(':')
+
+            Encode.append_with(with_name, encode_tuple(payload), @Json(options))
+            |> List.append('}'),
+    )
+
+## The name of a record field in the document
+to_json_name : Str, FieldNameMapping -> Str
+to_json_name = |name, mapping|
+    when mapping is
+        Default -> name
+        SnakeCase -> to_snake_case(name)
+        CamelCase -> to_camel_case(name)
+
+## The name of the record field for a key in the document that is not one of the fields as it is
+from_json_name : Str, FieldNameMapping -> Str
+from_json_name = |key, mapping|
+    when mapping is
+        Default -> key
+        SnakeCase -> to_camel_case(key)
+        CamelCase -> to_snake_case(key)
+
+to_camel_case : Str -> Str
+to_camel_case = |name|
+    (bytes, _) = List.walk(
+        Str.to_utf8(name),
+        ([], Bool.false),
+        |(buf, at_word_start), byte|
+            if byte == '_' then
+                (buf, Bool.true)
+            else if at_word_start && byte >= 'a' && byte <= 'z' then
+                (List.append(buf, byte - 32), Bool.false)
+            else
+                (List.append(buf, byte), Bool.false),
+    )
+
+    # only ASCII letters and underscores were changed, so this is still valid UTF-8
+    Result.with_default(Str.from_utf8(bytes), name)
+
+to_snake_case : Str -> Str
+to_snake_case = |name|
+    bytes = List.walk(
+        Str.to_utf8(name),
+        [],
+        |buf, byte|
+            if byte >= 'A' && byte <= 'Z' && List.is_empty(buf) then
+                List.append(buf, byte + 32)
+            else if byte >= 'A' && byte <= 'Z' then
+                List.concat(buf, ['_', byte + 32])
+            else
+                List.append(buf, byte),
+    )
+
+    Result.with_default(Str.from_utf8(bytes), name)
+
+# DECODE
+#
+# Every decoder skips the whitespace in front of its value. When it fails, it leaves `rest` at the
+# byte it could not decode, which is what `from_bytes` reports the offset of.
+
+skip_whitespace : List U8 -> List U8
+skip_whitespace = |bytes|
+    when bytes is
+        [byte, .. as rest] if byte == ' ' || byte == '\n' || byte == '\r' || byte == '\t' ->
+            skip_whitespace(rest)
+
+        _ -> bytes
+
+decode_number : (Str -> Result n err) -> Decoder n Json
+decode_number = |from_str|
+    Decode.custom(
+        |bytes, @Json(_)|
+            start = skip_whitespace(bytes)
+            { before, others } = List.split_at(start, number_len(start))
+
+            # a number is all ASCII, so `before` is valid UTF-8
+            when from_str(Result.with_default(Str.from_utf8(before), "")) is
+                Ok(num) -> { result: Ok(num), rest: others }
+                Err(_) -> { result: Err(TooShort), rest: start },
+    )
+
+## The length of the JSON number at the start of `bytes`, or 0 if it does not start with one
+number_len : List U8 -> U64
+number_len = |bytes|
+    int_start = if List.get(bytes, 0) == Ok('-') then 1 else 0
+    int_end = digits_end(bytes, int_start)
+
+    if int_end == int_start then
+        0
+    else
+        frac_end =
+            if List.get(bytes, int_end) == Ok('.') && digits_end(bytes, int_end + 1) > int_end + 1 then
+                digits_end(bytes, int_end + 1)
+            else
+                int_end
+
+        when List.get(bytes, frac_end) is
+            Ok(e) if e == 'e' || e == 'E' ->
+                exponent_start =
+                    when List.get(bytes, frac_end + 1) is
+                        Ok(sign) if sign == '+' || sign == '-' -> frac_end + 2
+                        _ -> frac_end + 1
+
+                exponent_end = digits_end(bytes, exponent_start)
+
+                if exponent_end > exponent_start then exponent_end else frac_end
+
+            _ -> frac_end
+
+digits_end : List U8, U64 -> U64
+digits_end = |bytes, index|
+    when List.get(bytes, index) is
+        Ok(byte) if byte >= '0' && byte <= '9' -> digits_end(bytes, index + 1)
+        _ -> index
+
+decode_u8 : Decoder U8 Json
+decode_u8 = decode_number(Str.to_u8)
+
+decode_u16 : Decoder U16 Json
+decode_u16 = decode_number(Str.to_u16)
+
+decode_u32 : Decoder U32 Json
+decode_u32 = decode_number(Str.to_u32)
+
+decode_u64 : Decoder U64 Json
+decode_u64 = decode_number(Str.to_u64)
+
+decode_u128 : Decoder U128 Json
+decode_u128 = decode_number(Str.to_u128)
+
+decode_i8 : Decoder I8 Json
+decode_i8 = decode_number(Str.to_i8)
+
+decode_i16 : Decoder I16 Json
+decode_i16 = decode_number(Str.to_i16)
+
+decode_i32 : Decoder I32 Json
+decode_i32 = decode_number(Str.to_i32)
+
+decode_i64 : Decoder I64 Json
+decode_i64 = decode_number(Str.to_i64)
+
+decode_i128 : Decoder I128 Json
+decode_i128 = decode_number(Str.to_i128)
+
+decode_f32 : Decoder F32 Json
+decode_f32 = decode_number(Str.to_f32)
+
+decode_f64 : Decoder F64 Json
+decode_f64 = decode_number(Str.to_f64)
+
+decode_dec : Decoder Dec Json
+decode_dec = decode_number(dec_from_str)
+
+## `Str.to_dec` takes no exponent, so the number is written out without one first: `1.5e3`
+## becomes `1500`, and `25e-3` becomes `0.025`.
+dec_from_str : Str -> Result Dec [InvalidNumStr]
+dec_from_str = |str|
+    bytes = Str.to_utf8(str)
+
+    when List.find_first_index(bytes, |byte| byte == 'e' || byte == 'E') is
+        Err(NotFound) -> Str.to_dec(str)
+        Ok(index) ->
+            { before: mantissa, others } = List.split_at(bytes, index)
+            exponent = parse_exponent(List.drop_first(others, 1))
+
+            negative = List.first(mantissa) == Ok('-')
+            unsigned = if negative then List.drop_first(mantissa, 1) else mantissa
+
+            { before: int_digits, others: point_and_frac } =
+                when List.find_first_index(unsigned, |byte| byte == '.') is
+                    Ok(point_index) -> List.split_at(unsigned, point_index)
+                    Err(NotFound) -> { before: unsigned, others: [] }
+
+            digits = List.concat(int_digits, List.drop_first(point_and_frac, 1))
+            point = Num.to_i64(List.len(int_digits)) + exponent
+            digit_count = Num.to_i64(List.len(digits))
+
+            { whole, frac } =
+                if point <= 0 then
+                    { whole: ['0'], frac: List.concat(List.repeat('0', Num.to_u64(Num.neg(point))), digits) }
+                else if point >= digit_count then
+                    { whole: List.concat(digits, List.repeat('0', Num.to_u64(point - digit_count))), frac: [] }
+                else
+                    { before, others: after_point } = List.split_at(digits, Num.to_u64(point))
+
+                    { whole: before, frac: after_point }
+
+            written =
+                when trim_trailing_zeros(frac) is
+                    [] -> whole
+                    frac_digits -> whole |> List.append('.') |> List.concat(frac_digits)
+
+            with_sign = if negative then List.prepend(written, '-') else written
+
+            when Str.from_utf8(with_sign) is
+                Ok(plain) -> Str.to_dec(plain)
+                Err(_) -> Err(InvalidNumStr)
+
+## The digits of an exponent, with an optional sign. A Dec has fewer than 40 digits, so anything
+## past 1000 is as good as infinite, and saturating there keeps the written out number small.
+parse_exponent : List U8 -> I64
+parse_exponent = |bytes|
+    magnitude = |digits|
+        List.walk(
+            digits,
+            0,
+            |exponent, digit|
+                if exponent > 1000 then
+                    exponent
+                else
+                    exponent * 10 + Num.to_i64(digit - '0'),
+        )
+
+    when bytes is
+        ['-', .. as digits] -> Num.neg(magnitude(digits))
+        ['+', .. as digits] -> magnitude(digits)
+        digits -> magnitude(digits)
+
+## A Dec has 18 decimal places, so `0.100000000000000000000` only fits without its zeros
+trim_trailing_zeros : List U8 -> List U8
+trim_trailing_zeros = |digits|
+    when digits is
+        [.. as rest, '0'] -> trim_trailing_zeros(rest)
+        _ -> digits
+
+decode_bool : Decoder Bool Json
+decode_bool = Decode.custom(
+    |bytes, @Json(_)|
+        when skip_whitespace(bytes) is
+            ['t', 'r', 'u', 'e', .. as rest] -> { result: Ok(Bool.true), rest }
+            ['f', 'a', 'l', 's', 'e', .. as rest] -> { result: Ok(Bool.false), rest }
+            start -> { result: Err(TooShort), rest: start },
+)
+
+decode_string : Decoder Str Json
+decode_string = Decode.custom(
+    |bytes, @Json(_)|
+        when skip_whitespace(bytes) is
+            ['"', .. as after_quote] ->
+                when unescape(after_quote, []) is
+                    Done(utf8_bytes, rest) ->
+                        when Str.from_utf8(utf8_bytes) is
+                            Ok(str) -> { result: Ok(str), rest }
+                            Err(_) -> { result: Err(TooShort), rest: after_quote }
+
+                    Invalid(at) -> { result: Err(TooShort), rest: at }
+
+            start -> { result: Err(TooShort), rest: start },
+)
+
+## Decodes the escapes of a string up to its closing quote, and returns the unescaped bytes with
+## everything after the quote. `Invalid` is the rest of the string from the byte that is not
+## allowed: a control character, an unknown escape, a lone surrogate, or the end of the input.
+unescape : List U8, List U8 -> [Done (List U8) (List U8), Invalid (List U8)]
+unescape = |bytes, buf|
+    when bytes is
+        ['"', .. as rest] -> Done(buf, rest)
+        ['\\', '"', .. as rest] -> unescape(rest, List.append(buf, '"'))
+        ['\\', '\\', .. as rest] -> unescape(rest, List.append(buf, '\\'))
+        ['\\', '/', .. as rest] -> unescape(rest, List.append(buf, '/'))
+        ['\\', 'b', .. as rest] -> unescape(rest, List.append(buf, 0x08))
+        ['\\', 'f', .. as rest] -> unescape(rest, List.append(buf, 0x0C))
+        ['\\', 'n', .. as rest] -> unescape(rest, List.append(buf, '\n'))
+        ['\\', 'r', .. as rest] -> unescape(rest, List.append(buf, '\r'))
+        ['\\', 't', .. as rest] -> unescape(rest, List.append(buf, '\t'))
+        ['\\', 'u', a, b, c, d, .. as rest] ->
+            when hex_code_unit(a, b, c, d) is
+                Ok(high) if high >= 0xD800 && high <= 0xDBFF ->
+                    # characters outside the basic multilingual plane are a UTF-16 surrogate pair
+                    when rest is
+                        ['\\', 'u', e, f, g, h, .. as after] ->
+                            when hex_code_unit(e, f, g, h) is
+                                Ok(low) if low >= 0xDC00 && low <= 0xDFFF ->
+                                    code_point = 0x10000 + Num.shift_left_by(high - 0xD800, 10) + (low - 0xDC00)
+
+                                    unescape(after, append_utf8(buf, code_point))
+
+                                _ -> Invalid(bytes)
+
+                        _ -> Invalid(bytes)
+
+                Ok(low) if low >= 0xDC00 && low <= 0xDFFF -> Invalid(bytes)
+                Ok(code_point) -> unescape(rest, append_utf8(buf, code_point))
+                Err(NotHex) -> Invalid(bytes)
+
+        [byte, .. as rest] if byte >= 0x20 && byte != '\\' -> unescape(rest, List.append(buf, byte))
+        _ -> Invalid(bytes)
+
+hex_code_unit : U8, U8, U8, U8 -> Result U32 [NotHex]
+hex_code_unit = |a, b, c, d|
+    List.walk_try([a, b, c, d], 0, |unit, byte| Result.map_ok(hex_value(byte), |value| unit * 16 + value))
+
+hex_value : U8 -> Result U32 [NotHex]
+hex_value = |byte|
+    if byte >= '0' && byte <= '9' then
+        Ok(Num.to_u32(byte - '0'))
+    else if byte >= 'a' && byte <= 'f' then
+        Ok(Num.to_u32(byte - 'a' + 10))
+    else if byte >= 'A' && byte <= 'F' then
+        Ok(Num.to_u32(byte - 'A' + 10))
+    else
+        Err(NotHex)
+
+append_utf8 : List U8, U32 -> List U8
+append_utf8 = |bytes, code_point|
+    continuation = |shift| Num.to_u8(Num.bitwise_or(0x80, Num.bitwise_and(Num.shift_right_zf_by(code_point, shift), 0x3F)))
+
+    if code_point < 0x80 then
+        List.append(bytes, Num.to_u8(code_point))
+    else if code_point < 0x800 then
+        List.concat(bytes, [Num.to_u8(Num.bitwise_or(0xC0, Num.shift_right_zf_by(code_point, 6))), continuation(0)])
+    else if code_point < 0x10000 then
+        List.concat(bytes, [Num.to_u8(Num.bitwise_or(0xE0, Num.shift_right_zf_by(code_point, 12))), continuation(6), continuation(0)])
+    else
+        List.concat(bytes, [Num.to_u8(Num.bitwise_or(0xF0, Num.shift_right_zf_by(code_point, 18))), continuation(12), continuation(6), continuation(0)])
+
+decode_list : Decoder elem Json -> Decoder (List elem) Json
+decode_list = |elem_decoder|
+    Decode.custom(
+        |bytes, @Json(options)|
+            when skip_whitespace(bytes) is
+                ['[', .. as after_bracket] ->
+                    when skip_whitespace(after_bracket) is
+                        [']', .. as rest] -> { result: Ok([]), rest }
+                        elems -> decode_elems(elems, [], elem_decoder, @Json(options))
+
+                start -> { result: Err(TooShort), rest: start },
+    )
+
+decode_elems : List U8, List elem, Decoder elem Json, Json -> DecodeResult (List elem)
+decode_elems = |bytes, elems, elem_decoder, fmt|
+    when Decode.decode_with(bytes, elem_decoder, fmt) is
+        { result: Ok(elem), rest } ->
+            when skip_whitespace(rest) is
+                [',', .. as next] -> decode_elems(next, List.append(elems, elem), elem_decoder, fmt)
+                [']', .. as after] -> { result: Ok(List.append(elems, elem)), rest: after }
+                other -> { result: Err(TooShort), rest: other }
+
+        { result: Err(err), rest } -> { result: Err(err), rest }
+
+decode_record : state, (state, Str -> [Keep (Decoder state Json), Skip]), (state, Json -> Result val DecodeError) -> Decoder val Json
+decode_record = |initial_state, step_field, finalizer|
+    Decode.custom(
+        |bytes, @Json(options)|
+            when skip_whitespace(bytes) is
+                ['{', .. as after_brace] ->
+                    when skip_whitespace(after_brace) is
+                        ['}', .. as rest] -> { result: finalizer(initial_state, @Json(options)), rest }
+                        fields -> decode_fields(fields, initial_state, step_field, finalizer, @Json(options))
+
+                start -> { result: Err(TooShort), rest: start },
+    )
+
+decode_fields : List U8, state, (state, Str -> [Keep (Decoder state Json), Skip]), (state, Json -> Result val DecodeError), Json -> DecodeResult val
+decode_fields = |bytes, state, step_field, finalizer, @Json(options)|
+    when Decode.decode_with(bytes, decode_string, @Json(options)) is
+        { result: Ok(key), rest: after_key } ->
+            when skip_whitespace(after_key) is
+                [':', .. as value_bytes] ->
+                    field =
+                        when step_field(state, key) is
+                            Keep(value_decoder) -> Keep(value_decoder)
+                            Skip -> step_field(state, from_json_name(key, options.field_name_mapping))
+
+                    decoded =
+                        when field is
+                            Keep(value_decoder) -> Decode.decode_with(value_bytes, value_decoder, @Json(options))
+                            Skip ->
+                                when skip_value(value_bytes) is
+                                    Ok(rest) -> { result: Ok(state), rest }
+                                    Err(at) -> { result: Err(TooShort), rest: at }
+
+                    when decoded is
+                        { result: Ok(next_state), rest } ->
+                            when skip_whitespace(rest) is
+                                [',', .. as next] -> decode_fields(next, next_state, step_field, finalizer, @Json(options))
+                                ['}', .. as after] -> { result: finalizer(next_state, @Json(options)), rest: after }
+                                other -> { result: Err(TooShort), rest: other }
+
+                        { result: Err(err), rest } -> { result: Err(err), rest }
+
+                other -> { result: Err(TooShort), rest: other }
+
+        { result: Err(err), rest } -> { result: Err(err), rest }
+
+## Skips over a value the record has no field for. Such a value can be nested arbitrarily deep,
+## so rather than recursing into it, this keeps a stack of the brackets that are still open.
+skip_value : List U8 -> Result (List U8) (List U8)
+skip_value = |bytes| skip_help(skip_whitespace(bytes), [])
+
+skip_help : List U8, List U8 -> Result (List U8) (List U8)
+skip_help = |bytes, closers|
+    when bytes is
+        ['"', .. as rest] ->
+            when skip_string(rest) is
+                Ok(after) if List.is_empty(closers) -> Ok(after)
+                Ok(after) -> skip_help(after, closers)
+                Err(at) -> Err(at)
+
+        ['[', .. as rest] -> skip_help(rest, List.append(closers, ']'))
+        ['{', .. as rest] -> skip_help(rest, List.append(closers, '}'))
+        [byte, .. as rest] if List.last(closers) == Ok(byte) ->
+            if List.len(closers) == 1 then
+                Ok(rest)
+            else
+                skip_help(rest, List.drop_last(closers, 1))
+
+        # inside an array or an object, everything else is some scalar, or separates two values
+        [byte, .. as rest] if !(List.is_empty(closers)) && byte != ']' && byte != '}' ->
+            skip_help(rest, closers)
+
+        _ if List.is_empty(closers) -> skip_scalar(bytes)
+        _ -> Err(bytes)
+
+skip_string : List U8 -> Result (List U8) (List U8)
+skip_string = |bytes|
+    when bytes is
+        ['"', .. as rest] -> Ok(rest)
+        ['\\', _, .. as rest] -> skip_string(rest)
+        [byte, .. as rest] if byte >= 0x20 -> skip_string(rest)
+        _ -> Err(bytes)
+
+skip_scalar : List U8 -> Result (List U8) (List U8)
+skip_scalar = |bytes|
+    when bytes is
+        ['t', 'r', 'u', 'e', .. as rest] -> Ok(rest)
+        ['f', 'a', 'l', 's', 'e', .. as rest] -> Ok(rest)
+        ['n', 'u', 'l', 'l', .. as rest] -> Ok(rest)
+        _ ->
+            len = number_len(bytes)
+
+            if len == 0 then
+                Err(bytes)
+            else
+                Ok(List.drop_first(bytes, len))
+
+decode_tuple : state, (state, U64 -> [Next (Decoder state Json), TooLong]), (state -> Result val DecodeError) -> Decoder val Json
+decode_tuple = |initial_state, step_elem, finalizer|
+    Decode.custom(
+        |bytes, @Json(options)|
+            when skip_whitespace(bytes) is
+                ['[', .. as elems] -> decode_tuple_elems(elems, initial_state, 0, step_elem, finalizer, @Json(options))
+                start -> { result: Err(TooShort), rest: start },
+    )
+
+decode_tuple_elems : List U8, state, U64, (state, U64 -> [Next (Decoder state Json), TooLong]), (state -> Result val DecodeError), Json -> DecodeResult val
+decode_tuple_elems = |bytes, state, index, step_elem, finalizer, fmt|
+    when step_elem(state, index) is
+        Next(elem_decoder) ->
+            when Decode.decode_with(bytes, elem_decoder, fmt) is
+                { result: Ok(next_state), rest } ->
+                    when skip_whitespace(rest) is
+                        [',', .. as next] -> decode_tuple_elems(next, next_state, index + 1, step_elem, finalizer, fmt)
+                        [']', .. as after] -> { result: finalizer(next_state), rest: after }
+                        other -> { result: Err(TooShort), rest: other }
+
+                { result: Err(err), rest } -> { result: Err(err), rest }
+
+        TooLong -> { result: Err(TooShort), rest: skip_whitespace(bytes) }
+
+expect
+    actual = Encode.to_bytes({ name: "Roc", tags: ["fast", "fun"] }, utf8)
+    actual == Str.to_utf8("{\"name\":\"Roc\",\"tags\":[\"fast\",\"fun\"]}")
+
+expect
+    actual = Encode.to_bytes("quote \" backslash \\ tab \t bell \u(7)", utf8)
+    actual == Str.to_utf8("\"quote \\\" backslash \\\\ tab \\t bell \\u0007\"")
+
+expect
+    actual = Encode.to_bytes({ user_id: 1 }, utf8_with({ field_name_mapping: CamelCase }))
+    actual == Str.to_utf8("{\"userId\":1}")
+
+expect
+    actual : Result Str _
+    actual = from_bytes(Str.to_utf8("\"\\u00e9 \\uD83D\\uDE00\""), utf8)
+    actual == Ok("é 😀")
+
+expect
+    actual : Result { user_id : U8 } _
+    actual = from_bytes(Str.to_utf8(" { \"userId\" : 7 } "), utf8_with({ field_name_mapping: CamelCase }))
+    actual == Ok({ user_id: 7 })
+
+expect
+    actual : Result (List U8) _
+    actual = from_bytes(Str.to_utf8("[1, 2, x]"), utf8)
+    actual == Err(InvalidJson({ offset: 7 }))
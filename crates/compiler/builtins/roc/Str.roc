@@ -905,8 +905,13 @@ to_i128 = |string| str_to_num_help(string)
 ## Encode a [Str] to an unsigned [U64] integer. A [U64] value can hold numbers
 ## from `0` to `18_446_744_073_709_551_615` (over 18 quintillion). It
 ## can be specified with a u64 suffix.
+##
+## Like number literals, the string can use a `0x`, `0o` or `0b` prefix for
+## hexadecimal, octal or binary, and underscores between digits.
 ## ```roc
 ## expect Str.to_u64("1500") == Ok(1500u64)
+## expect Str.to_u64("0xFFFF_FFFF_FFFF_FFFF") == Ok(18_446_744_073_709_551_615u64)
+## expect Str.to_u64("0b102") == Err(InvalidNumStr)
 ## expect Str.to_u64("0.1") == Err(InvalidNumStr)
 ## expect Str.to_u64("-1") == Err(InvalidNumStr)
 ## expect Str.to_u64("not a number") == Err(InvalidNumStr)
@@ -917,9 +922,13 @@ to_u64 = |string| str_to_num_help(string)
 ## Encode a [Str] to a signed [I64] integer. A [I64] value can hold numbers
 ## from `-9_223_372_036_854_775_808` to `9_223_372_036_854_775_807`. It can be
 ## specified with a i64 suffix.
+##
+## Like number literals, the string can use a `0x`, `0o` or `0b` prefix for
+## hexadecimal, octal or binary, after the sign, and underscores between digits.
 ## ```roc
 ## expect Str.to_i64("1500") == Ok(1500i64)
 ## expect Str.to_i64("-1") == Ok(-1i64)
+## expect Str.to_i64("-0x8000_0000_0000_0000") == Ok(-9_223_372_036_854_775_808i64)
 ## expect Str.to_i64("0.1") == Err(InvalidNumStr)
 ## expect Str.to_i64("not a number") == Err(InvalidNumStr)
 ## ```
@@ -0,0 +1,35 @@
+## A weak reference points to a boxed value without keeping it alive.
+##
+## Roc frees a value when its last reference goes away, so values that refer to each other in
+## a cycle are never freed. An observer that holds a [WeakRef] to the value it observes, rather
+## than a [Box], does not keep that value alive, and so does not create a cycle.
+module [WeakRef, downgrade, get]
+
+import Bool exposing [Bool]
+
+## A reference to the value in a [Box] that does not keep the value alive. When every [Box]
+## of the value is gone, [get] returns `None`.
+WeakRef a := Box a
+
+## Makes a weak reference to the value in a box.
+## ```roc
+## boxed = Box.box("observed")
+##
+## expect WeakRef.get(WeakRef.downgrade(boxed)) == Some("observed")
+## ```
+downgrade : Box a -> WeakRef a
+
+## Returns the value that the weak reference points to, or `None` when the value has been freed
+## because there is no [Box] of it anymore.
+get : WeakRef a -> [Some a, None]
+get = |weak|
+    if is_alive(weak) then
+        Some(get_unsafe(weak))
+    else
+        None
+
+# whether a box of the value still exists
+is_alive : WeakRef a -> Bool
+
+# only call this when the value is alive; it increments the refcount of the value it returns
+get_unsafe : WeakRef a -> a
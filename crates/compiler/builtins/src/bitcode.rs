@@ -43,7 +43,7 @@ impl FloatWidth {
         match self {
             F32 => 4,
             F64 => match target.architecture() {
-                X86_64 | Aarch64 | Wasm32 => 8,
+                X86_64 | Aarch64 | Riscv64 | Sbf | Wasm32 => 8,
                 X86_32 | Aarch32 => 4,
             },
         }
@@ -117,6 +117,8 @@ impl IntWidth {
                 Architecture::X86_64
                 | Architecture::Aarch64
                 | Architecture::Aarch32
+                | Architecture::Riscv64
+                | Architecture::Sbf
                 | Architecture::Wasm32 => 8,
                 Architecture::X86_32 => 4,
             },
@@ -127,7 +129,11 @@ impl IntWidth {
                 // however, rust does not always think that this is true
                 // Our alignmets here are correct, but they will not match rust/zig/llvm until they update to llvm version 18.
                 match target.architecture() {
-                    Architecture::X86_64 | Architecture::Aarch64 | Architecture::X86_32 => 16,
+                    Architecture::X86_64
+                    | Architecture::Aarch64
+                    | Architecture::X86_32
+                    | Architecture::Riscv64
+                    | Architecture::Sbf => 16,
                     Architecture::Aarch32 | Architecture::Wasm32 => 8,
                 }
             }
@@ -364,6 +370,7 @@ pub const STR_RESERVE: &str = "roc_builtins.str.reserve";
 pub const STR_CLONE_TO: &str = "roc_builtins.str.clone_to";
 pub const STR_WITH_CAPACITY: &str = "roc_builtins.str.with_capacity";
 pub const STR_ALLOCATION_PTR: &str = "roc_builtins.str.allocation_ptr";
+pub const STR_DECREF_BATCH: &str = "roc_builtins.str.decref_batch";
 pub const STR_RELEASE_EXCESS_CAPACITY: &str = "roc_builtins.str.release_excess_capacity";
 pub const STR_WITH_ASCII_LOWERCASED: &str = "roc_builtins.str.with_ascii_lowercased";
 pub const STR_WITH_ASCII_UPPERCASED: &str = "roc_builtins.str.with_ascii_uppercased";
@@ -392,6 +399,7 @@ pub const LIST_RELEASE_EXCESS_CAPACITY: &str = "roc_builtins.list.release_excess
 pub const LIST_CONCAT_UTF8: &str = "roc_builtins.list.concat_utf8";
 pub const LIST_INCREF: &str = "roc_builtins.list.incref";
 pub const LIST_DECREF: &str = "roc_builtins.list.decref";
+pub const LIST_DECREF_BATCH: &str = "roc_builtins.list.decref_batch";
 
 pub const DEC_ABS: &str = "roc_builtins.dec.abs";
 pub const DEC_ACOS: &str = "roc_builtins.dec.acos";
@@ -430,15 +438,22 @@ pub const DEC_CEILING: IntrinsicName = int_intrinsic!("roc_builtins.dec.ceiling"
 pub const UTILS_DBG_IMPL: &str = "roc_builtins.utils.dbg_impl";
 pub const UTILS_TEST_PANIC: &str = "roc_builtins.utils.test_panic";
 pub const UTILS_ALLOCATE_WITH_REFCOUNT: &str = "roc_builtins.utils.allocate_with_refcount";
+pub const UTILS_ALLOCATE_POOLED_WITH_REFCOUNT: &str =
+    "roc_builtins.utils.allocate_pooled_with_refcount";
 pub const UTILS_INCREF_RC_PTR: &str = "roc_builtins.utils.incref_rc_ptr";
 pub const UTILS_DECREF_RC_PTR: &str = "roc_builtins.utils.decref_rc_ptr";
 pub const UTILS_FREE_RC_PTR: &str = "roc_builtins.utils.free_rc_ptr";
+pub const UTILS_INCREF_WEAK_RC_PTR: &str = "roc_builtins.utils.incref_weak_rc_ptr";
+pub const UTILS_DECREF_WEAK_RC_PTR: &str = "roc_builtins.utils.decref_weak_rc_ptr";
+pub const UTILS_WEAK_RC_PTR_IS_ALIVE: &str = "roc_builtins.utils.weak_rc_ptr_is_alive";
 pub const UTILS_INCREF_DATA_PTR: &str = "roc_builtins.utils.incref_data_ptr";
 pub const UTILS_DECREF_DATA_PTR: &str = "roc_builtins.utils.decref_data_ptr";
 pub const UTILS_FREE_DATA_PTR: &str = "roc_builtins.utils.free_data_ptr";
 pub const UTILS_IS_UNIQUE: &str = "roc_builtins.utils.is_unique";
 pub const UTILS_DECREF_CHECK_NULL: &str = "roc_builtins.utils.decref_check_null";
 pub const UTILS_DICT_PSEUDO_SEED: &str = "roc_builtins.utils.dict_pseudo_seed";
+pub const UTILS_ARENA_ENTER: &str = "roc_builtins.utils.arena_enter";
+pub const UTILS_ARENA_EXIT: &str = "roc_builtins.utils.arena_exit";
 
 pub const UTILS_EXPECT_FAILED_START_SHARED_BUFFER: &str =
     "roc_builtins.utils.expect_failed_start_shared_buffer";
@@ -0,0 +1,191 @@
+//! Caches what the compiler made in a previous build, so that the work is skipped for the input
+//! that did not change since: the object code that is generated for a program, and the solved
+//! types of a module.
+//!
+//! An entry is keyed by a hash of everything that the work depends on, which the caller puts into
+//! a [KeyBuilder]. For object code, that is the monomorphized procedures and their layouts, the
+//! sources they were compiled from, the target and the options, and the compiler itself. Nothing
+//! is ever invalidated, an input that changed just has a different key.
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// The directories in the roc cache dir that hold the entries
+const CODE_CACHE_DIR_NAME: &str = "app-objects";
+const TYPES_CACHE_DIR_NAME: &str = "module-types";
+
+/// Bump the number whenever what an entry holds changes, so that the entries that a compiler with
+/// the old format wrote are not used.
+const FORMAT_VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), ".1");
+
+/// The hash an entry is stored under
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CacheKey(String);
+
+impl CacheKey {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Hashes the parts of a [CacheKey]
+#[derive(Clone)]
+pub struct KeyBuilder {
+    hasher: blake3::Hasher,
+}
+
+impl KeyBuilder {
+    /// Starts with the compiler that is running, by the path, size and modification time of its
+    /// executable. A compiler built from source keeps the same version through every change to
+    /// it, but not the same executable.
+    pub fn new() -> io::Result<Self> {
+        let exe_path = std::env::current_exe()?;
+        let metadata = fs::metadata(&exe_path)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+
+        let mut builder = Self {
+            hasher: blake3::Hasher::new(),
+        };
+
+        builder
+            .add(FORMAT_VERSION)
+            .add(exe_path.to_string_lossy().as_bytes())
+            .add(metadata.len().to_le_bytes())
+            .add(modified.to_le_bytes());
+
+        Ok(builder)
+    }
+
+    /// Every part is prefixed with its length, so that no two sequences of parts hash the same
+    /// bytes
+    pub fn add(&mut self, part: impl AsRef<[u8]>) -> &mut Self {
+        let part = part.as_ref();
+
+        self.hasher.update(&(part.len() as u64).to_le_bytes());
+        self.hasher.update(part);
+
+        self
+    }
+
+    pub fn finish(&self) -> CacheKey {
+        CacheKey(self.hasher.finalize().to_hex().to_string())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    /// The cache of object code in `roc_cache_dir`, which is created when the first entry is
+    /// written
+    pub fn app_objects(roc_cache_dir: &Path) -> Self {
+        Self {
+            dir: roc_cache_dir.join(CODE_CACHE_DIR_NAME),
+        }
+    }
+
+    /// The cache of solved types in `roc_cache_dir`, like [Cache::app_objects]
+    pub fn module_types(roc_cache_dir: &Path) -> Self {
+        Self {
+            dir: roc_cache_dir.join(TYPES_CACHE_DIR_NAME),
+        }
+    }
+
+    fn entry_path(&self, key: &CacheKey) -> PathBuf {
+        self.dir.join(key.as_str())
+    }
+
+    /// The bytes stored under `key`. A cache that cannot be read is the same as an empty
+    /// one.
+    pub fn read(&self, key: &CacheKey) -> Option<Vec<u8>> {
+        fs::read(self.entry_path(key)).ok()
+    }
+
+    /// Store `bytes` under `key`. The entry is written to a new file, which is then moved into
+    /// place, so that a build running at the same time never reads half an entry.
+    pub fn write(&self, key: &CacheKey, bytes: &[u8]) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+
+        let mut file = tempfile::NamedTempFile::new_in(&self.dir)?;
+        file.write_all(bytes)?;
+        file.persist(self.entry_path(key))
+            .map(|_| ())
+            .map_err(|err| err.error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Build `source` the way a build would: reuse the entry for it, or "generate code" and store
+    /// that. Returns whether the code was generated.
+    fn build(cache: &Cache, source: &str, generated: &Cell<usize>) -> (Vec<u8>, bool) {
+        let key = KeyBuilder::new()
+            .unwrap()
+            .add("linux-x86_64")
+            .add(source)
+            .finish();
+
+        match cache.read(&key) {
+            Some(bytes) => (bytes, false),
+            None => {
+                generated.set(generated.get() + 1);
+
+                let bytes = format!("object code of {source}").into_bytes();
+                cache.write(&key, &bytes).unwrap();
+
+                (bytes, true)
+            }
+        }
+    }
+
+    #[test]
+    fn skips_an_unchanged_program() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::app_objects(dir.path());
+        let generated = Cell::new(0);
+
+        let (first, was_generated) = build(&cache, "main = 1", &generated);
+        assert!(was_generated);
+
+        let (second, was_generated) = build(&cache, "main = 1", &generated);
+        assert!(!was_generated, "the unchanged program was compiled again");
+        assert_eq!(first, second);
+        assert_eq!(generated.get(), 1);
+    }
+
+    #[test]
+    fn compiles_a_changed_program() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::app_objects(dir.path());
+        let generated = Cell::new(0);
+
+        build(&cache, "main = 1", &generated);
+
+        let (bytes, was_generated) = build(&cache, "main = 2", &generated);
+        assert!(was_generated, "the changed program came from the cache");
+        assert_eq!(bytes, b"object code of main = 2");
+
+        // the first version is still there, for when the change is undone
+        let (_, was_generated) = build(&cache, "main = 1", &generated);
+        assert!(!was_generated);
+        assert_eq!(generated.get(), 2);
+    }
+
+    #[test]
+    fn parts_are_not_concatenated() {
+        let split_early = KeyBuilder::new().unwrap().add("ab").add("c").finish();
+        let split_late = KeyBuilder::new().unwrap().add("a").add("bc").finish();
+
+        assert_ne!(split_early, split_late);
+    }
+}
@@ -98,6 +98,9 @@ macro_rules! map_symbol_to_lowlevel_and_arity {
                 LowLevel::LongJmp => unimplemented!(),
                 LowLevel::SetLongJmpBuffer => unimplemented!(),
 
+                LowLevel::ArenaEnter => unimplemented!(),
+                LowLevel::ArenaExit => unimplemented!(),
+
                 // these are not implemented, not sure why
                 LowLevel::StrFromInt => unimplemented!(),
                 LowLevel::StrFromFloat => unimplemented!(),
@@ -227,6 +230,9 @@ map_symbol_to_lowlevel_and_arity! {
     UnboxExpr; BOX_UNBOX; 1,
     Unreachable; LIST_UNREACHABLE; 1,
     DictPseudoSeed; DICT_PSEUDO_SEED; 1,
+    WeakRefDowngrade; WEAK_REF_DOWNGRADE; 1,
+    WeakRefIsAlive; WEAK_REF_IS_ALIVE; 1,
+    WeakRefGetUnsafe; WEAK_REF_GET_UNSAFE; 1,
 }
 
 /// Some builtins cannot be constructed in code gen alone, and need to be defined
@@ -12,11 +12,14 @@ use crate::derive;
 use crate::env::Env;
 use crate::expr::canonicalize_record;
 use crate::expr::get_lookup_symbols;
+use crate::expr::str_interpolation;
 use crate::expr::AnnotatedMark;
 use crate::expr::ClosureData;
 use crate::expr::Declarations;
+use crate::expr::ExpectLookup;
 use crate::expr::Expr::{self, *};
 use crate::expr::StructAccessorData;
+use crate::expr::EXPECT_MESSAGE;
 use crate::expr::{canonicalize_expr, Output, Recursive};
 use crate::pattern::{canonicalize_def_header_pattern, BindingsFromPattern, Pattern};
 use crate::procedure::QualifiedReference;
@@ -177,6 +180,8 @@ pub(crate) struct CanDefs {
 #[derive(Clone, Debug)]
 pub struct ExpectsOrDbgs {
     pub conditions: Vec<Expr>,
+    /// The def that binds the message of an `expect cond, message`
+    pub messages: Vec<Option<Def>>,
     pub regions: Vec<Region>,
     pub preceding_comment: Vec<Region>,
 }
@@ -185,13 +190,20 @@ impl ExpectsOrDbgs {
     fn with_capacity(capacity: usize) -> Self {
         Self {
             conditions: Vec::with_capacity(capacity),
+            messages: Vec::with_capacity(capacity),
             regions: Vec::with_capacity(capacity),
             preceding_comment: Vec::with_capacity(capacity),
         }
     }
 
-    fn push(&mut self, loc_can_condition: Loc<Expr>, preceding_comment: Region) {
+    fn push(
+        &mut self,
+        loc_can_condition: Loc<Expr>,
+        message: Option<Def>,
+        preceding_comment: Region,
+    ) {
         self.conditions.push(loc_can_condition.value);
+        self.messages.push(message);
         self.regions.push(loc_can_condition.region);
         self.preceding_comment.push(preceding_comment);
     }
@@ -1246,7 +1258,7 @@ fn canonicalize_value_defs<'a>(
             &pending.condition.value,
         );
 
-        dbgs.push(loc_can_condition, pending.preceding_comment);
+        dbgs.push(loc_can_condition, None, pending.preceding_comment);
 
         output.union(can_output);
     }
@@ -1260,9 +1272,24 @@ fn canonicalize_value_defs<'a>(
             &pending.condition.value,
         );
 
-        expects.push(loc_can_condition, pending.preceding_comment);
-
         output.union(can_output);
+
+        let message = pending.message.map(|loc_message| {
+            let (loc_can_message, can_output) = canonicalize_expr(
+                env,
+                var_store,
+                scope,
+                loc_message.region,
+                &loc_message.value,
+            );
+
+            output.union(can_output);
+            output.references.insert_call(Symbol::STR_CONCAT);
+
+            expect_message_def(var_store, scope, loc_can_message)
+        });
+
+        expects.push(loc_can_condition, message, pending.preceding_comment);
     }
 
     let can_defs = CanDefs {
@@ -1696,13 +1723,20 @@ pub(crate) fn sort_top_level_can_defs(
     let it = expects
         .conditions
         .into_iter()
+        .zip(expects.messages)
         .zip(expects.regions)
         .zip(expects.preceding_comment);
 
-    for ((condition, region), preceding_comment) in it {
+    for (((condition, message), region), preceding_comment) in it {
         // an `expect` does not have a user-defined name, but we'll need a name to call the expectation
         let name = scope.gen_unique_symbol();
 
+        // the message becomes the first lookup once the expect is turned into an inline one
+        let condition = match message {
+            Some(def) => LetNonRec(Box::new(def), Box::new(Loc::at(region, condition))),
+            None => condition,
+        };
+
         declarations.push_expect(preceding_comment, name, Loc::at(region, condition));
     }
 
@@ -2831,14 +2865,31 @@ fn decl_to_let(decl: Declaration, loc_ret: Loc<Expr>) -> Loc<Expr> {
             let mut loc_ret = loc_ret;
 
             let conditions = expects.conditions.into_iter().rev();
+            let messages = expects.messages.into_iter().rev();
             let condition_regions = expects.regions.into_iter().rev();
             let expect_regions = expects.preceding_comment.into_iter().rev();
 
-            let it = expect_regions.zip(condition_regions).zip(conditions);
+            let it = expect_regions
+                .zip(condition_regions)
+                .zip(conditions)
+                .zip(messages);
 
-            for ((expect_region, condition_region), condition) in it {
+            for (((expect_region, condition_region), condition), message) in it {
                 let region = Region::span_across(&expect_region, &loc_ret.region);
-                let lookups_in_cond = get_lookup_symbols(&condition);
+                let mut lookups_in_cond = get_lookup_symbols(&condition);
+
+                if let Some(def) = &message {
+                    let (symbol, var) = def.pattern_vars.iter().next().unwrap();
+
+                    lookups_in_cond.insert(
+                        0,
+                        ExpectLookup {
+                            symbol: *symbol,
+                            var: *var,
+                            ability_info: None,
+                        },
+                    );
+                }
 
                 let expr = Expr::Expect {
                     loc_condition: Box::new(Loc::at(condition_region, condition)),
@@ -2846,6 +2897,12 @@ fn decl_to_let(decl: Declaration, loc_ret: Loc<Expr>) -> Loc<Expr> {
                     lookups_in_cond,
                 };
 
+                // bind the message right before the expect, so it can be looked up when it fails
+                let expr = match message {
+                    Some(def) => LetNonRec(Box::new(def), Box::new(Loc::at(region, expr))),
+                    None => expr,
+                };
+
                 loc_ret = Loc::at(region, expr);
             }
 
@@ -2854,6 +2911,26 @@ fn decl_to_let(decl: Declaration, loc_ret: Loc<Expr>) -> Loc<Expr> {
     }
 }
 
+/// Binds the message of an `expect cond, message` to a symbol the user can't refer to, so that
+/// it is handed to the failure report like any other lookup. The message is interpolated into a
+/// string to make sure it is a `Str`.
+fn expect_message_def(var_store: &mut VarStore, scope: &mut Scope, loc_message: Loc<Expr>) -> Def {
+    let region = loc_message.region;
+    let symbol = scope.scopeless_symbol(&Ident::from(EXPECT_MESSAGE), region);
+
+    let mut pattern_vars = SendMap::default();
+    pattern_vars.insert(symbol, var_store.fresh());
+
+    Def {
+        loc_pattern: Loc::at(region, Pattern::Identifier(symbol)),
+        loc_expr: Loc::at(region, str_interpolation(var_store, loc_message)),
+        expr_var: var_store.fresh(),
+        pattern_vars,
+        annotation: None,
+        kind: DefKind::Let,
+    }
+}
+
 fn to_pending_alias_or_opaque<'a>(
     env: &mut Env<'a>,
     scope: &mut Scope,
@@ -3049,6 +3126,7 @@ enum PendingValue<'a> {
 
 struct PendingExpectOrDbg<'a> {
     condition: &'a Loc<ast::Expr<'a>>,
+    message: Option<&'a Loc<ast::Expr<'a>>>,
     preceding_comment: Region,
 }
 
@@ -3168,14 +3246,17 @@ fn to_pending_value_def<'a>(
             preceding_comment,
         } => PendingValue::Dbg(PendingExpectOrDbg {
             condition,
+            message: None,
             preceding_comment: *preceding_comment,
         }),
 
         Expect {
             condition,
+            message,
             preceding_comment,
         } => PendingValue::Expect(PendingExpectOrDbg {
             condition,
+            message: *message,
             preceding_comment: *preceding_comment,
         }),
 
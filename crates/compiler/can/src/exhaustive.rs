@@ -4,11 +4,13 @@ use roc_collections::all::HumanIndex;
 use roc_collections::VecMap;
 use roc_error_macros::internal_error;
 use roc_exhaustive::{
-    is_useful, Ctor, CtorName, Error, Guard, ListArity, Literal, Pattern, RenderAs, TagId, Union,
+    is_useful, Ctor, CtorName, Error, Guard, IntRange, ListArity, Literal, Pattern, RenderAs,
+    TagId, Union,
 };
 use roc_module::ident::{Lowercase, TagIdIntType, TagName};
 use roc_module::symbol::Symbol;
 use roc_region::all::{Loc, Region};
+use roc_types::num::IntLitWidth;
 use roc_types::subs::{
     Content, FlatType, GetSubsSlice, RedundantMark, SortedTagsIterator, Subs, SubsFmtContent,
     Variable,
@@ -255,11 +257,68 @@ fn index_var(
     }
 }
 
+/// The values of `var`, if it is an integer type of a known width. `U128` has values that do not
+/// fit an [IntRange], so its literals are checked like any other literal.
+fn int_domain(subs: &Subs, mut var: Variable) -> Option<IntRange> {
+    use IntLitWidth::*;
+
+    loop {
+        let Content::Alias(symbol, _, real_var, kind) = subs.get_content_without_compacting(var)
+        else {
+            return None;
+        };
+
+        let width = match *symbol {
+            Symbol::NUM_U8 | Symbol::NUM_UNSIGNED8 => U8,
+            Symbol::NUM_U16 | Symbol::NUM_UNSIGNED16 => U16,
+            Symbol::NUM_U32 | Symbol::NUM_UNSIGNED32 => U32,
+            Symbol::NUM_U64 | Symbol::NUM_UNSIGNED64 => U64,
+            Symbol::NUM_I8 | Symbol::NUM_SIGNED8 => I8,
+            Symbol::NUM_I16 | Symbol::NUM_SIGNED16 => I16,
+            Symbol::NUM_I32 | Symbol::NUM_SIGNED32 => I32,
+            Symbol::NUM_I64 | Symbol::NUM_SIGNED64 => I64,
+            Symbol::NUM_I128 | Symbol::NUM_SIGNED128 => I128,
+            // `Num (Integer range)`
+            Symbol::NUM_NUM | Symbol::NUM_INTEGER => {
+                var = *real_var;
+                continue;
+            }
+            _ if matches!(kind, AliasKind::Structural) => {
+                var = *real_var;
+                continue;
+            }
+            _ => return None,
+        };
+
+        return Some(IntRange {
+            start: width.min_value(),
+            end: width.max_value() as i128,
+        });
+    }
+}
+
 impl SketchedPattern {
     fn reify(self, subs: &Subs, real_var: Variable) -> Result<Pattern, TypeError> {
         match self {
             Self::Anything => Ok(Pattern::Anything),
-            Self::Literal(lit) => Ok(Pattern::Literal(lit)),
+            Self::Literal(lit) => {
+                let value = match lit {
+                    Literal::Int(bytes) => i128::from_ne_bytes(bytes),
+                    Literal::Byte(byte) => byte as i128,
+                    _ => return Ok(Pattern::Literal(lit)),
+                };
+
+                match int_domain(subs, real_var) {
+                    Some(domain) => {
+                        let range = IntRange {
+                            start: value,
+                            end: value,
+                        };
+                        Ok(Pattern::Range(range, domain))
+                    }
+                    None => Ok(Pattern::Literal(lit)),
+                }
+            }
             Self::KnownCtor(union, tag_id, patterns) => {
                 let index_ctor = IndexCtor::of_union(&union, tag_id);
                 let arg_vars = index_var(subs, real_var, index_ctor, &union.render_as)?;
@@ -634,6 +693,7 @@ fn is_inhabited_pattern(pat: &Pattern) -> bool {
         match pat {
             Pattern::Anything => {}
             Pattern::Literal(_) => {}
+            Pattern::Range(..) => {}
             Pattern::Ctor(union, id, pats) => {
                 if !union.alternatives.iter().any(|alt| alt.tag_id == *id) {
                     // The tag ID was dropped from the union, which means that this tag ID is one
@@ -355,6 +355,10 @@ pub enum TryKind {
     OperatorSuffix,
 }
 
+/// The name of the value that holds the message of an `expect condition, message`. It is looked
+/// up like the values in the condition, and is the first lookup of its expectation.
+pub const EXPECT_MESSAGE: &str = "#expect_message";
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct ExpectLookup {
     pub symbol: Symbol,
@@ -2520,6 +2524,11 @@ fn flatten_str_lines<'a>(
 
 /// Resolve string interpolations by desugaring a sequence of StrSegments
 /// into nested calls to Str.concat
+/// `"${loc_expr}"`, which is a type error when `loc_expr` is not a `Str`
+pub(crate) fn str_interpolation(var_store: &mut VarStore, loc_expr: Loc<Expr>) -> Expr {
+    desugar_str_segments(var_store, vec![StrSegment::Interpolation(loc_expr)])
+}
+
 fn desugar_str_segments(var_store: &mut VarStore, segments: Vec<StrSegment>) -> Expr {
     use StrSegment::*;
 
@@ -150,7 +150,7 @@ impl FlatInspectable {
                         }
                         AliasKind::Opaque if sym.is_builtin() => Self::from_var(subs, real_var),
                         AliasKind::Opaque => {
-                            // There are two cases in which `Inspect` can be derived for an opaque
+                            // There are three cases in which `Inspect` can be derived for an opaque
                             // type.
                             //   1. An opaque type claims to implement `Inspect` and asks us to
                             //      auto-derive it. E.g.
@@ -163,7 +163,18 @@ impl FlatInspectable {
                             //      canonicalization that defers to `inspect`ing the inner type. As
                             //      such, this case is never reached in this branch.
                             //
-                            //   2. An opaque type does not explicitly claim to implement
+                            //   2. An opaque type implements `Inspect` with its own
+                            //      `to_inspector`. E.g.
+                            //
+                            //      ```text
+                            //      Op := {} implements [Inspect { to_inspector: op_to_inspector }]
+                            //      ```
+                            //
+                            //      The solver resolves the specialization to `op_to_inspector`,
+                            //      including when `Op` is inside a container whose inspector is
+                            //      derived, so this case is never reached in this branch either.
+                            //
+                            //   3. An opaque type does not explicitly claim to implement
                             //      `Inspect`. In this case, we print a default opaque string for
                             //      the opaque type.
                             Immediate(Symbol::INSPECT_OPAQUE)
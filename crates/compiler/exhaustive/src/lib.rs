@@ -71,10 +71,51 @@ pub struct Ctor {
 pub enum Pattern {
     Anything,
     Literal(Literal),
+    /// The integers in the first range, of a type whose values are the second range
+    Range(IntRange, IntRange),
     Ctor(Union, TagId, std::vec::Vec<Pattern>),
     List(ListArity, std::vec::Vec<Pattern>),
 }
 
+/// The integers from `start` to `end`, both included
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IntRange {
+    pub start: i128,
+    pub end: i128,
+}
+
+impl IntRange {
+    pub fn contains(&self, other: &IntRange) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    /// Split `self` at the bounds of `ranges`, so that every piece is either inside or outside of
+    /// each of them. The pieces are in order.
+    fn split(self, ranges: impl IntoIterator<Item = IntRange>) -> Vec<IntRange> {
+        let mut starts = vec![self.start];
+
+        for range in ranges {
+            if self.start < range.start && range.start <= self.end {
+                starts.push(range.start);
+            }
+            if self.start <= range.end && range.end < self.end {
+                starts.push(range.end + 1);
+            }
+        }
+
+        starts.sort_unstable();
+        starts.dedup();
+
+        let ends = starts.iter().skip(1).map(|next| next - 1).chain([self.end]);
+
+        starts
+            .iter()
+            .zip(ends)
+            .map(|(&start, end)| IntRange { start, end })
+            .collect()
+    }
+}
+
 /// The arity of list pattern.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ListArity {
@@ -302,6 +343,51 @@ fn is_exhaustive(matrix: &RefPatternMatrix, n: usize) -> PatternMatrix {
 
             alt_lists.into_iter().flat_map(is_alt_exhaustive).collect()
         }
+        CollectedCtors::Ints(domain) => {
+            let pieces = domain.split(filter_matrix_int_ranges(matrix));
+            let is_covered =
+                |piece: &IntRange| filter_matrix_int_ranges(matrix).any(|r| r.contains(piece));
+
+            // Two neighbouring pieces are split at the bound of a range that covers one of them,
+            // so the missing pieces are as compact as they can be.
+            let missing: Vec<_> = pieces.iter().filter(|p| !is_covered(p)).copied().collect();
+
+            if !missing.is_empty() {
+                let new_matrix: Vec<_> = matrix
+                    .iter()
+                    .filter_map(|row| specialize_row_by_anything(row))
+                    .collect();
+                let rest: Vec<Vec<Pattern>> = is_exhaustive(&new_matrix, n - 1);
+
+                let mut result = Vec::new();
+
+                for range in missing {
+                    for mut row in rest.clone() {
+                        row.push(Range(range, domain));
+
+                        result.push(row);
+                    }
+                }
+
+                result
+            } else {
+                let is_piece_exhaustive = |piece: IntRange| {
+                    let new_matrix: Vec<_> = matrix
+                        .iter()
+                        .filter_map(|row| specialize_row_by_int(piece, row.to_owned()))
+                        .collect();
+
+                    let rest = is_exhaustive(&new_matrix, n - 1);
+
+                    rest.into_iter().map(move |mut row| {
+                        row.push(Range(piece, domain));
+                        row
+                    })
+                };
+
+                pieces.into_iter().flat_map(is_piece_exhaustive).collect()
+            }
+        }
     }
 }
 
@@ -416,6 +502,31 @@ pub fn is_useful(mut old_matrix: PatternMatrix, mut vector: Row) -> bool {
                         }
                     }
 
+                    Range(range, _) => {
+                        // Rows whose ranges overlap only part of this one are checked against
+                        // every piece of it on its own.
+                        let pieces = range.split(filter_matrix_int_ranges(&old_matrix));
+
+                        if pieces.len() == 1 {
+                            specialize_matrix_by_int(pieces[0], &mut old_matrix, &mut matrix);
+
+                            std::mem::swap(&mut old_matrix, &mut matrix);
+                        } else {
+                            for piece in pieces {
+                                let mut old_matrix = old_matrix.clone();
+                                let mut spec_matrix = Vec::with_capacity(old_matrix.len());
+
+                                specialize_matrix_by_int(piece, &mut old_matrix, &mut spec_matrix);
+
+                                if is_useful(spec_matrix, vector.clone()) {
+                                    return true;
+                                }
+                            }
+
+                            return false;
+                        }
+                    }
+
                     Anything => {
                         // check if all alternatives appear in matrix
                         match is_complete(&old_matrix) {
@@ -455,6 +566,21 @@ pub fn is_useful(mut old_matrix: PatternMatrix, mut vector: Row) -> bool {
                                     }
                                 }
 
+                                break false;
+                            }
+                            Complete::Ints(pieces) => {
+                                // Every integer is covered, but maybe not together with
+                                // everything the rest of this vector matches.
+                                for piece in pieces {
+                                    let mut old_matrix = old_matrix.clone();
+                                    let mut matrix = vec![];
+                                    specialize_matrix_by_int(piece, &mut old_matrix, &mut matrix);
+
+                                    if is_useful(matrix, vector.clone()) {
+                                        break 'outer true;
+                                    }
+                                }
+
                                 break false;
                             }
                         }
@@ -479,6 +605,8 @@ pub fn is_useful(mut old_matrix: PatternMatrix, mut vector: Row) -> bool {
 
                                 Some(List(..)) => internal_error!("After type checking, lists and literals should never align in exhaustiveness checking"),
 
+                                Some(Range(..)) => internal_error!("Integers of a known type and literals should never align in exhaustiveness checking"),
+
                                 Some(Ctor(_, _, _)) => panic!(
                                     r#"Compiler bug! After type checking, constructors and literals should never align in pattern match exhaustiveness checks."#
                                 ),
@@ -594,11 +722,41 @@ fn specialize_row_by_list(spec_arity: ListArity, mut row: Row) -> Option<Row> {
             Some(spec_patterns)
         }
         Some(Ctor(..)) => internal_error!("After type checking, lists and constructors should never align in exhaustiveness checking"),
-        Some(Literal(..)) => internal_error!("After type checking, lists and literals should never align in exhaustiveness checking"),
+        Some(Literal(..) | Range(..)) => internal_error!("After type checking, lists and literals should never align in exhaustiveness checking"),
         None => internal_error!("Empty matrices should not get specialized"),
     }
 }
 
+fn specialize_matrix_by_int(
+    piece: IntRange,
+    old_matrix: &mut PatternMatrix,
+    matrix: &mut PatternMatrix,
+) {
+    for row in old_matrix.drain(..) {
+        if let Some(spec_row) = specialize_row_by_int(piece, row) {
+            matrix.push(spec_row);
+        }
+    }
+}
+
+/// Keep the rows that match every integer in `piece`, which is either inside or outside of the
+/// range of each row.
+///
+/// INVARIANT: (length row == N) ==> (length result == N-1)
+fn specialize_row_by_int(piece: IntRange, mut row: Row) -> Option<Row> {
+    match row.pop() {
+        Some(Range(range, _)) => range.contains(&piece).then_some(row),
+        Some(Anything) => Some(row),
+        Some(Literal(_)) => internal_error!(
+            "Integers of a known type and literals should never align in exhaustiveness checking"
+        ),
+        Some(Ctor(..) | List(..)) => internal_error!(
+            "After type checking, integers can never align with constructors or lists"
+        ),
+        None => internal_error!("Empty matrices should not get specialized."),
+    }
+}
+
 /// INVARIANT: (length row == N) ==> (length result == arity + N - 1)
 fn specialize_matrix_by_ctor(
     tag_id: TagId,
@@ -634,7 +792,7 @@ fn specialize_row_by_ctor(tag_id: TagId, arity: usize, mut row: Row) -> Option<R
         Some(List(..)) => {
             internal_error!(r#"After type checking, a constructor can never align with a list"#)
         }
-        Some(Literal(_)) => internal_error!(
+        Some(Literal(_) | Range(..)) => internal_error!(
             r#"After type checking, a constructor can never align with a literal: that should be a type error!"#
         ),
         None => internal_error!("Empty matrices should not get specialized."),
@@ -655,6 +813,8 @@ fn specialize_row_by_anything(row: &RefRow) -> Option<Row> {
 
 pub enum Complete {
     Yes(Vec<Ctor>),
+    /// Every integer of the type is covered, by the ranges split into these pieces
+    Ints(Vec<IntRange>),
     No,
 }
 
@@ -677,6 +837,18 @@ fn is_complete(matrix: &RefPatternMatrix) -> Complete {
                 }
             }
         }
+        CollectedCtors::Ints(domain) => {
+            let pieces = domain.split(filter_matrix_int_ranges(matrix));
+
+            if pieces
+                .iter()
+                .all(|piece| filter_matrix_int_ranges(matrix).any(|r| r.contains(piece)))
+            {
+                Complete::Ints(pieces)
+            } else {
+                Complete::No
+            }
+        }
     }
 }
 
@@ -691,6 +863,8 @@ enum CollectedCtors {
     NonExhaustiveAny,
     NonExhaustiveList(Vec<ListArity>),
     Ctors(MutMap<TagId, Union>),
+    /// Integers of a type whose values are this range
+    Ints(IntRange),
 }
 
 fn collect_ctors(matrix: &RefPatternMatrix) -> CollectedCtors {
@@ -704,6 +878,7 @@ fn collect_ctors(matrix: &RefPatternMatrix) -> CollectedCtors {
         match ctor {
             Anything => CollectedCtors::NonExhaustiveAny,
             Pattern::Literal(_) => CollectedCtors::NonExhaustiveAny,
+            Range(_, domain) => CollectedCtors::Ints(*domain),
             List(_, _) => {
                 let list_ctors = build_list_ctors_covering_patterns(
                     ListArity::ANY,
@@ -903,3 +1078,10 @@ fn filter_matrix_list_ctors(matrix: &RefPatternMatrix) -> impl Iterator<Item = L
         _ => None,
     })
 }
+
+fn filter_matrix_int_ranges(matrix: &RefPatternMatrix) -> impl Iterator<Item = IntRange> + '_ {
+    matrix.iter().filter_map(|row| match row.last() {
+        Some(Range(range, _)) => Some(*range),
+        _ => None,
+    })
+}
@@ -257,6 +257,24 @@ pub fn valdef_lift_spaces<'a, 'b: 'a>(
         }
         ValueDef::Expect {
             condition,
+            message: Some(message),
+            preceding_comment,
+        } => {
+            let message_lifted = expr_lift_spaces_after(Parens::NotNeeded, arena, &message.value);
+
+            Spaces {
+                before: &[],
+                item: ValueDef::Expect {
+                    condition,
+                    message: Some(&*arena.alloc(Loc::at(message.region, message_lifted.item))),
+                    preceding_comment,
+                },
+                after: message_lifted.after,
+            }
+        }
+        ValueDef::Expect {
+            condition,
+            message: None,
             preceding_comment,
         } => {
             let condition_lifted =
@@ -266,6 +284,7 @@ pub fn valdef_lift_spaces<'a, 'b: 'a>(
                 before: &[],
                 item: ValueDef::Expect {
                     condition: arena.alloc(Loc::at(condition.region, condition_lifted.item)),
+                    message: None,
                     preceding_comment,
                 },
                 after: condition_lifted.after,
@@ -388,11 +407,13 @@ pub fn valdef_lift_spaces_before<'a, 'b: 'a>(
         },
         ValueDef::Expect {
             condition,
+            message,
             preceding_comment,
         } => SpacesBefore {
             before: &[],
             item: ValueDef::Expect {
                 condition,
+                message,
                 preceding_comment,
             },
         },
@@ -803,7 +824,9 @@ impl<'a> Formattable for ValueDef<'a> {
             }
             Body(loc_pattern, loc_expr) => loc_pattern.is_multiline() || loc_expr.is_multiline(),
             AnnotatedBody { .. } => true,
-            Expect { condition, .. } => condition.is_multiline(),
+            Expect {
+                condition, message, ..
+            } => condition.is_multiline() || message.is_some_and(|message| message.is_multiline()),
             Dbg { condition, .. } => condition.is_multiline(),
             ModuleImport(module_import) => module_import.is_multiline(),
             IngestedFileImport(ingested_file_import) => ingested_file_import.is_multiline(),
@@ -835,7 +858,9 @@ impl<'a> Formattable for ValueDef<'a> {
                 fmt_body(buf, &loc_pattern.value, &loc_expr.value, indent);
             }
             Dbg { condition, .. } => fmt_dbg_in_def(buf, condition, self.is_multiline(), indent),
-            Expect { condition, .. } => fmt_expect(buf, condition, self.is_multiline(), indent),
+            Expect {
+                condition, message, ..
+            } => fmt_expect(buf, condition, *message, self.is_multiline(), indent),
             AnnotatedBody {
                 ann_pattern,
                 ann_type,
@@ -929,7 +954,13 @@ fn fmt_dbg_in_def<'a>(buf: &mut Buf, condition: &'a Loc<Expr<'a>>, _: bool, inde
     condition.format(buf, indent);
 }
 
-fn fmt_expect<'a>(buf: &mut Buf, condition: &'a Loc<Expr<'a>>, is_multiline: bool, indent: u16) {
+fn fmt_expect<'a>(
+    buf: &mut Buf,
+    condition: &'a Loc<Expr<'a>>,
+    message: Option<&'a Loc<Expr<'a>>>,
+    is_multiline: bool,
+    indent: u16,
+) {
     buf.ensure_ends_with_newline();
     buf.indent(indent);
     buf.push_str("expect");
@@ -943,6 +974,12 @@ fn fmt_expect<'a>(buf: &mut Buf, condition: &'a Loc<Expr<'a>>, is_multiline: boo
     };
 
     condition.format(buf, return_indent);
+
+    if let Some(message) = message {
+        buf.push(',');
+        buf.spaces(1);
+        message.format(buf, return_indent);
+    }
 }
 
 pub fn fmt_defs(buf: &mut Buf, defs: &Defs, indent: u16) {
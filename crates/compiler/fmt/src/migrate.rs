@@ -1616,12 +1616,19 @@ impl Fmt for ValueDef<'_> {
             }
             ValueDef::Expect {
                 condition,
+                message,
                 preceding_comment: _,
             } => {
                 buf.indent(indent);
                 buf.push_str("expect");
                 buf.spaces(1);
                 fmt_expr(buf, indent, &condition.value, Suffix::None)?;
+
+                if let Some(message) = message {
+                    buf.push(',');
+                    buf.spaces(1);
+                    fmt_expr(buf, indent, &message.value, Suffix::None)?;
+                }
             }
             ValueDef::ModuleImport(module_import) => {
                 module_import.fmt(buf, indent, Suffix::None)?;
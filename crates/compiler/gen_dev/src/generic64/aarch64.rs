@@ -1713,7 +1713,7 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     }
     #[inline(always)]
     fn mov_base32_freg32(buf: &mut Vec<'_, u8>, offset: i32, src: AArch64FloatReg) {
-        Self::mov_mem64_offset32_freg64(buf, AArch64GeneralReg::FP, offset, src)
+        Self::mov_mem32_offset32_freg32(buf, AArch64GeneralReg::FP, offset, src)
     }
     #[inline(always)]
     fn movesd_mem64_offset32_freg64(
@@ -1762,17 +1762,17 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         offset: i32,
         src: AArch64FloatReg,
     ) {
-        if (-256..256).contains(&offset) {
-            stur_freg64_reg64_imm9(buf, src, dst, offset as i16)
-        } else if (0..=u16::MAX as i32).contains(&offset) {
-            debug_assert!(offset % 8 == 0);
-            str_freg64_reg64_imm12(buf, src, dst, (offset as u16) >> 3);
-        } else {
-            let tmp = AArch64GeneralReg::X15;
-            Self::mov_reg64_imm64(buf, tmp, offset as i64);
-            Self::add_reg64_reg64_reg64(buf, tmp, tmp, dst);
-            str_freg64_reg64_imm12(buf, src, tmp, 0);
-        }
+        Self::mov_mem_offset32_freg(buf, FloatWidth::F64, dst, offset, src)
+    }
+
+    #[inline(always)]
+    fn mov_mem32_offset32_freg32(
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64GeneralReg,
+        offset: i32,
+        src: AArch64FloatReg,
+    ) {
+        Self::mov_mem_offset32_freg(buf, FloatWidth::F32, dst, offset, src)
     }
 
     #[inline(always)]
@@ -2114,38 +2114,66 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src: AArch64GeneralReg,
         offset: i32,
     ) {
+        Self::mov_freg_mem_offset32(buf, FloatWidth::F64, dst, src, offset)
+    }
+
+    fn mov_freg32_mem32_offset32(
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64FloatReg,
+        src: AArch64GeneralReg,
+        offset: i32,
+    ) {
+        Self::mov_freg_mem_offset32(buf, FloatWidth::F32, dst, src, offset)
+    }
+}
+
+impl AArch64Assembler {
+    /// Load a float from memory. Only the bytes of the float are accessed: an `f32` in a struct or
+    /// a list is followed by other data, which a whole `D` register would read or overwrite.
+    fn mov_freg_mem_offset32(
+        buf: &mut Vec<'_, u8>,
+        ftype: FloatWidth,
+        dst: AArch64FloatReg,
+        src: AArch64GeneralReg,
+        offset: i32,
+    ) {
+        let size = ftype.stack_size() as i32;
+
         if (-256..256).contains(&offset) {
-            ldur_freg64_reg64_imm9(buf, dst, src, offset as i16)
-        } else if (0..=u16::MAX as i32).contains(&offset) {
-            debug_assert!(offset % 8 == 0);
-            ldr_freg64_reg64_imm12(buf, dst, src, (offset as u16) >> 3);
+            ldur_freg_reg64_imm9(buf, ftype, dst, src, offset as i16)
+        } else if (0..=0xfff * size).contains(&offset) && offset % size == 0 {
+            ldr_freg_reg64_imm12(buf, ftype, dst, src, (offset / size) as u16);
         } else {
             let tmp = AArch64GeneralReg::X15;
             Self::mov_reg64_imm64(buf, tmp, offset as i64);
             Self::add_reg64_reg64_reg64(buf, tmp, tmp, src);
-            ldr_freg64_reg64_imm12(buf, dst, tmp, 0);
+            ldr_freg_reg64_imm12(buf, ftype, dst, tmp, 0);
         }
     }
 
-    fn mov_freg32_mem32_offset32(
+    /// Store a float to memory, like [Self::mov_freg_mem_offset32] loads it
+    fn mov_mem_offset32_freg(
         buf: &mut Vec<'_, u8>,
-        dst: AArch64FloatReg,
-        src: AArch64GeneralReg,
+        ftype: FloatWidth,
+        dst: AArch64GeneralReg,
         offset: i32,
+        src: AArch64FloatReg,
     ) {
-        if offset < 0 {
-            ldur_freg64_reg64_imm9(buf, dst, src, offset as i16)
-        } else if (0..=u16::MAX as i32).contains(&offset) {
-            debug_assert!(offset % 8 == 0);
-            ldr_freg64_reg64_imm12(buf, dst, src, (offset as u16) >> 3);
+        let size = ftype.stack_size() as i32;
+
+        if (-256..256).contains(&offset) {
+            stur_freg_reg64_imm9(buf, ftype, src, dst, offset as i16)
+        } else if (0..=0xfff * size).contains(&offset) && offset % size == 0 {
+            str_freg_reg64_imm12(buf, ftype, src, dst, (offset / size) as u16);
         } else {
-            todo!("base offsets over 32k for AArch64");
+            let tmp = AArch64GeneralReg::X15;
+            Self::mov_reg64_imm64(buf, tmp, offset as i64);
+            Self::add_reg64_reg64_reg64(buf, tmp, tmp, dst);
+            str_freg_reg64_imm12(buf, ftype, src, tmp, 0);
         }
     }
 }
 
-impl AArch64Assembler {}
-
 // Instructions
 // ARM manual section C3
 // https://developer.arm.com/documentation/ddi0487/ga
@@ -3495,17 +3523,27 @@ fn ldur_reg_reg_imm9(
     buf.extend(inst.bytes());
 }
 
-/// `LDR Xt, [Xn, #offset]` -> Load Xn + Offset Xt. ZRSP is SP.
-/// Note: imm12 is the offest divided by 8.
+/// The `size` of a load or store of a float register: a single or a double word.
+#[inline(always)]
+fn float_load_store_size(ftype: FloatWidth) -> Integer<u8, packed_bits::Bits<2>> {
+    match ftype {
+        FloatWidth::F32 => 0b10.into(),
+        FloatWidth::F64 => 0b11.into(),
+    }
+}
+
+/// `LDR St/Dt, [Xn, #offset]` -> Load Xn + Offset St/Dt. ZRSP is SP.
+/// Note: imm12 is the offest divided by the size of the float.
 #[inline(always)]
-fn ldr_freg64_reg64_imm12(
+fn ldr_freg_reg64_imm12(
     buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
     dst: AArch64FloatReg,
     base: AArch64GeneralReg,
     imm12: u16,
 ) {
     let inst = LoadStoreRegisterImmediate {
-        size: 0b11.into(), // 64-bit
+        size: float_load_store_size(ftype),
         fixed: 0b111.into(),
         fixed2: true,
         fixed3: 0b01.into(),
@@ -3518,9 +3556,12 @@ fn ldr_freg64_reg64_imm12(
     buf.extend(inst.bytes());
 }
 
+/// `LDUR St/Dt, [Xn, #offset]` -> Load Xn + Offset St/Dt, with a signed offset that is not
+/// scaled.
 #[inline(always)]
-fn ldur_freg64_reg64_imm9(
+fn ldur_freg_reg64_imm9(
     buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
     dst: AArch64FloatReg,
     base: AArch64GeneralReg,
     imm9: i16,
@@ -3532,7 +3573,7 @@ fn ldur_freg64_reg64_imm9(
     let imm12 = (imm9 & 0b0001_1111_1111) << 2;
 
     let inst = LoadStoreRegisterImmediate {
-        size: 0b11.into(), // 64-bit
+        size: float_load_store_size(ftype),
         fixed: 0b111.into(),
         fixed2: true,
         fixed3: 0b00.into(),
@@ -3739,9 +3780,12 @@ fn str_reg_reg_imm12(
     buf.extend(inst.bytes());
 }
 
+/// `STUR St/Dt, [Xn, #offset]` -> Store St/Dt to Xn + Offset, with a signed offset that is not
+/// scaled.
 #[inline(always)]
-fn stur_freg64_reg64_imm9(
+fn stur_freg_reg64_imm9(
     buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
     src: AArch64FloatReg,
     base: AArch64GeneralReg,
     imm9: i16,
@@ -3753,7 +3797,7 @@ fn stur_freg64_reg64_imm9(
     let imm12 = (imm9 & 0b0001_1111_1111) << 2;
 
     let inst = LoadStoreRegisterImmediate {
-        size: 0b11.into(), // 64-bit
+        size: float_load_store_size(ftype),
         fixed: 0b111.into(),
         fixed2: true,
         fixed3: 0b00.into(),
@@ -3766,15 +3810,18 @@ fn stur_freg64_reg64_imm9(
     buf.extend(inst.bytes());
 }
 
+/// `STR St/Dt, [Xn, #offset]` -> Store St/Dt to Xn + Offset. ZRSP is SP.
+/// Note: imm12 is the offest divided by the size of the float.
 #[inline(always)]
-fn str_freg64_reg64_imm12(
+fn str_freg_reg64_imm12(
     buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
     src: AArch64FloatReg,
     base: AArch64GeneralReg,
     imm12: u16,
 ) {
     let inst = LoadStoreRegisterImmediate {
-        size: 0b11.into(), // 64-bit
+        size: float_load_store_size(ftype),
         fixed: 0b111.into(),
         fixed2: true,
         fixed3: 0b01.into(),
@@ -4781,15 +4828,16 @@ mod tests {
     }
 
     #[test]
-    fn test_ldr_freg64_reg64_imm12() {
+    fn test_ldr_freg_reg64_imm12() {
         disassembler_test!(
-            ldr_freg64_reg64_imm12,
-            |reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm| format!(
+            ldr_freg_reg64_imm12,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm| format!(
                 "ldr {}, [{}, #0x{:x}]",
-                reg1.capstone_string(FloatWidth::F64),
+                reg1.capstone_string(ftype),
                 reg2.capstone_string(UsesSP),
-                imm << 3
+                imm * ftype.stack_size() as u16
             ),
+            ALL_FLOAT_TYPES,
             ALL_FLOAT_REGS,
             ALL_GENERAL_REGS,
             [0x123]
@@ -4797,15 +4845,16 @@ mod tests {
     }
 
     #[test]
-    fn test_ludr_freg64_reg64_imm9() {
+    fn test_ludr_freg_reg64_imm9() {
         disassembler_test!(
-            ldur_freg64_reg64_imm9,
-            |reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm| format!(
+            ldur_freg_reg64_imm9,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm| format!(
                 "ldur {}, [{}, {}]",
-                reg1.capstone_string(FloatWidth::F64),
+                reg1.capstone_string(ftype),
                 reg2.capstone_string(UsesSP),
                 signed_hex_i16(imm)
             ),
+            ALL_FLOAT_TYPES,
             ALL_FLOAT_REGS,
             ALL_GENERAL_REGS,
             [4, -4]
@@ -5033,15 +5082,16 @@ mod tests {
     }
 
     #[test]
-    fn test_str_freg64_reg64_imm12() {
+    fn test_str_freg_reg64_imm12() {
         disassembler_test!(
-            str_freg64_reg64_imm12,
-            |reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm| format!(
+            str_freg_reg64_imm12,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm| format!(
                 "str {}, [{}, #0x{:x}]",
-                reg1.capstone_string(FloatWidth::F64),
+                reg1.capstone_string(ftype),
                 reg2.capstone_string(UsesSP),
-                imm << 3
+                imm * ftype.stack_size() as u16
             ),
+            ALL_FLOAT_TYPES,
             ALL_FLOAT_REGS,
             ALL_GENERAL_REGS,
             [0x123]
@@ -5049,15 +5099,16 @@ mod tests {
     }
 
     #[test]
-    fn test_stur_freg64_reg64_imm9() {
+    fn test_stur_freg_reg64_imm9() {
         disassembler_test!(
-            stur_freg64_reg64_imm9,
-            |reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm| format!(
+            stur_freg_reg64_imm9,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm| format!(
                 "stur {}, [{}, {}]",
-                reg1.capstone_string(FloatWidth::F64),
+                reg1.capstone_string(ftype),
                 reg2.capstone_string(UsesSP),
                 signed_hex_i16(imm),
             ),
+            ALL_FLOAT_TYPES,
             ALL_FLOAT_REGS,
             ALL_GENERAL_REGS,
             [4, -4]
@@ -529,6 +529,12 @@ pub trait Assembler<GeneralReg: RegTrait, FloatReg: RegTrait>: Sized + Copy {
         offset: i32,
         src: FloatReg,
     );
+    fn mov_mem32_offset32_freg32(
+        buf: &mut Vec<'_, u8>,
+        dst: GeneralReg,
+        offset: i32,
+        src: FloatReg,
+    );
     fn mov_freg64_stack32(buf: &mut Vec<'_, u8>, dst: FloatReg, offset: i32);
     fn mov_reg64_stack32(buf: &mut Vec<'_, u8>, dst: GeneralReg, offset: i32);
     fn mov_stack32_freg64(buf: &mut Vec<'_, u8>, offset: i32, src: FloatReg);
@@ -3423,7 +3429,24 @@ impl<
 
                 self.free_symbol(&mask_symbol)
             }
-            UnionLayout::Recursive(tag_layouts) => {
+            UnionLayout::TaggedPointer { tags, .. } if union_layout.tag_is_immediate(tag_id) => {
+                debug_assert_eq!(index, 0);
+
+                let payload_repr = self.layout_interner.get_repr(tags[tag_id as usize][0]);
+                let width = RegisterWidth::try_from_layout(payload_repr).unwrap();
+
+                let ptr_reg = self
+                    .storage_manager
+                    .load_to_general_reg(&mut self.buf, structure);
+                let dst_reg = self.storage_manager.claim_general_reg(&mut self.buf, sym);
+
+                // dropping the bits above the payload also drops the immediate bit
+                ASM::movzx_reg_reg(&mut self.buf, width, dst_reg, ptr_reg);
+            }
+            UnionLayout::Recursive(tag_layouts)
+            | UnionLayout::TaggedPointer {
+                tags: tag_layouts, ..
+            } => {
                 let other_fields = tag_layouts[tag_id as usize];
                 let element_layout = other_fields[index as usize];
 
@@ -3525,7 +3548,12 @@ impl<
 
                 self.free_symbol(&mask_symbol);
             }
-            UnionLayout::Recursive(tag_layouts) => {
+            UnionLayout::Recursive(tag_layouts)
+            | UnionLayout::TaggedPointer {
+                tags: tag_layouts, ..
+            } => {
+                debug_assert!(!union_layout.tag_is_immediate(tag_id));
+
                 let other_fields = tag_layouts[tag_id as usize];
 
                 let ptr_reg = self
@@ -3817,6 +3845,45 @@ impl<
                 self.free_symbol(&tmp);
             }
 
+            UnionLayout::TaggedPointer { immediate_id, .. } => {
+                // an immediate has bits of its payload where a pointer has the tag id, so pick
+                // between the two with a mask of all ones for an immediate and all zeros otherwise
+                let dst_reg = self.storage_manager.claim_general_reg(&mut self.buf, sym);
+
+                let tmp = Symbol::DEV_TMP5;
+                let reg = self.storage_manager.claim_general_reg(&mut self.buf, &tmp);
+                let mask = Symbol::DEV_TMP4;
+                let mask_reg = self.storage_manager.claim_general_reg(&mut self.buf, &mask);
+
+                let ptr_reg = self
+                    .storage_manager
+                    .load_to_general_reg(&mut self.buf, structure);
+
+                // the immediate bit is the sign bit
+                ASM::mov_reg64_imm64(&mut self.buf, reg, 0);
+                ASM::signed_compare_reg64(
+                    &mut self.buf,
+                    RegisterWidth::W64,
+                    CompareOperation::LessThan,
+                    mask_reg,
+                    ptr_reg,
+                    reg,
+                );
+                ASM::neg_reg64_reg64(&mut self.buf, mask_reg, mask_reg);
+
+                // mask the 3 lowest bits
+                ASM::mov_reg64_imm64(&mut self.buf, reg, 0b111);
+                ASM::and_reg64_reg64_reg64(&mut self.buf, dst_reg, ptr_reg, reg);
+
+                // tag_id = pointer_tag_id ^ ((pointer_tag_id ^ immediate_id) & mask)
+                ASM::mov_reg64_imm64(&mut self.buf, reg, *immediate_id as i64);
+                ASM::xor_reg64_reg64_reg64(&mut self.buf, reg, reg, dst_reg);
+                ASM::and_reg64_reg64_reg64(&mut self.buf, reg, reg, mask_reg);
+                ASM::xor_reg64_reg64_reg64(&mut self.buf, dst_reg, dst_reg, reg);
+
+                self.free_symbol(&mask);
+                self.free_symbol(&tmp);
+            }
             UnionLayout::Recursive(_) => {
                 let dst_reg = self.storage_manager.claim_general_reg(&mut self.buf, sym);
 
@@ -4096,7 +4163,29 @@ impl<
                     }
                 }
             }
-            UnionLayout::Recursive(tags) => {
+            UnionLayout::TaggedPointer { tags, .. } if union_layout.tag_is_immediate(tag_id) => {
+                // the immediate tag is never allocated, so it has no reuse token
+                debug_assert!(reuse.is_none());
+
+                self.load_literal_symbols(fields);
+
+                let payload_repr = self.layout_interner.get_repr(tags[tag_id as usize][0]);
+                let width = RegisterWidth::try_from_layout(payload_repr).unwrap();
+
+                let payload_reg = self
+                    .storage_manager
+                    .load_to_general_reg(&mut self.buf, &fields[0]);
+                let dst_reg = self.storage_manager.claim_general_reg(&mut self.buf, sym);
+                ASM::movzx_reg_reg(&mut self.buf, width, dst_reg, payload_reg);
+
+                let tmp = Symbol::DEV_TMP5;
+                let reg = self.storage_manager.claim_general_reg(&mut self.buf, &tmp);
+                ASM::mov_reg64_imm64(&mut self.buf, reg, UnionLayout::IMMEDIATE_BIT as i64);
+                ASM::or_reg64_reg64_reg64(&mut self.buf, dst_reg, dst_reg, reg);
+
+                self.free_symbol(&tmp);
+            }
+            UnionLayout::Recursive(tags) | UnionLayout::TaggedPointer { tags, .. } => {
                 self.load_literal_symbols(fields);
 
                 let tag_id_symbol = self.debug_symbol("tag_id_symbol");
@@ -5303,10 +5392,14 @@ impl<
                 let sym_reg = storage_manager.load_to_general_reg(buf, &value);
                 ASM::mov_mem8_offset32_reg8(buf, ptr_reg, element_offset, sym_reg);
             }
-            LayoutRepr::Builtin(Builtin::Float(FloatWidth::F64 | FloatWidth::F32)) => {
+            LayoutRepr::Builtin(Builtin::Float(FloatWidth::F64)) => {
                 let sym_reg = storage_manager.load_to_float_reg(buf, &value);
                 ASM::movesd_mem64_offset32_freg64(buf, ptr_reg, element_offset, sym_reg);
             }
+            LayoutRepr::Builtin(Builtin::Float(FloatWidth::F32)) => {
+                let sym_reg = storage_manager.load_to_float_reg(buf, &value);
+                ASM::mov_mem32_offset32_freg32(buf, ptr_reg, element_offset, sym_reg);
+            }
             pointer_layouts!() => {
                 let sym_reg = storage_manager.load_to_general_reg(buf, &value);
                 ASM::mov_mem64_offset32_reg64(buf, ptr_reg, element_offset, sym_reg);
@@ -5486,7 +5579,8 @@ macro_rules! pointer_layouts {
                 UnionLayout::Recursive(_)
                     | UnionLayout::NonNullableUnwrapped(_)
                     | UnionLayout::NullableWrapped { .. }
-                    | UnionLayout::NullableUnwrapped { .. },
+                    | UnionLayout::NullableUnwrapped { .. }
+                    | UnionLayout::TaggedPointer { .. },
             )
             | LayoutRepr::FunctionPointer(_)
     };
@@ -1869,6 +1869,20 @@ trait Backend<'a> {
                 arg_layouts,
                 ret_layout,
             ),
+            LowLevel::ArenaEnter => self.build_fn_call(
+                sym,
+                bitcode::UTILS_ARENA_ENTER.to_string(),
+                args,
+                arg_layouts,
+                ret_layout,
+            ),
+            LowLevel::ArenaExit => self.build_fn_call(
+                sym,
+                bitcode::UTILS_ARENA_EXIT.to_string(),
+                args,
+                arg_layouts,
+                ret_layout,
+            ),
             LowLevel::NumToStr => {
                 let arg_layout = arg_layouts[0];
                 let intrinsic = match self.interner().get_repr(arg_layout) {
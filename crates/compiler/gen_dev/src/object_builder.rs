@@ -406,6 +406,12 @@ fn create_relocation(target: Target, symbol: SymbolId, offset: u64) -> write::Re
                 )
             }
         }
+        arch @ (roc_target::Architecture::Riscv64 | roc_target::Architecture::Sbf) => {
+            internal_error!(
+                "The dev backend has no relocations for the {:?} target",
+                arch
+            )
+        }
         roc_target::Architecture::Wasm32 => todo!(),
         roc_target::Architecture::X86_32 => todo!(),
         roc_target::Architecture::X86_64 => (
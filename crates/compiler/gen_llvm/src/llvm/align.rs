@@ -20,6 +20,7 @@ impl LlvmAlignment<'_> for IntWidth {
             IntWidth::U128 | IntWidth::I128 => {
                 // 128-bit integers are not consistently represented by LLVM.
                 // - AArch64 uses 16-byte alignment (https://godbolt.org/z/dYrfG5o4b)
+                // - RISC-V 64 uses 16-byte alignment, like AArch64
                 // - x86-64 uses 8-byte alignment (https://godbolt.org/z/qj5Mann6b)
                 let arch = interner.target().architecture();
                 match arch {
@@ -99,7 +100,8 @@ impl<'a> LlvmAlignment<'a> for UnionLayout<'a> {
             Recursive(_)
             | NullableWrapped { .. }
             | NullableUnwrapped { .. }
-            | NonNullableUnwrapped(_) => interner.target().ptr_width() as u32,
+            | NonNullableUnwrapped(_)
+            | TaggedPointer { .. } => interner.target().ptr_width() as u32,
         }
     }
 }
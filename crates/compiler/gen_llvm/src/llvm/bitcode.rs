@@ -116,6 +116,12 @@ fn call_bitcode_fn_help<'ctx>(
         .get_function(fn_name)
         .unwrap_or_else(|| panic!("Unrecognized builtin function: {fn_name:?} - if you're working on the Roc compiler, do you need to rebuild the bitcode? See compiler/builtins/bitcode/README.md"));
 
+    let fn_val = if env.target == roc_target::Target::Sbf {
+        super::sbf::adapt_bitcode_fn(env, fn_val, args)
+    } else {
+        fn_val
+    };
+
     let call = env
         .builder
         .new_build_call(fn_val, &arguments, "call_builtin");
@@ -1133,7 +1139,7 @@ pub(crate) fn call_str_bitcode_fn<'ctx>(
 
             returns.call_and_load_32bit(env, &arguments, fn_name)
         }
-        X86_64 | Aarch64 => {
+        X86_64 | Aarch64 | Riscv64 | Sbf => {
             let capacity = other_arguments.len() + strings.len() + returns.additional_arguments();
             let mut arguments: Vec<BasicValueEnum> = Vec::with_capacity_in(capacity, env.arena);
 
@@ -1188,7 +1194,7 @@ pub(crate) fn call_void_list_bitcode_fn<'ctx>(
 
             call_void_bitcode_fn(env, &arguments, fn_name);
         }
-        X86_64 | Aarch64 => {
+        X86_64 | Aarch64 | Riscv64 | Sbf => {
             let capacity = other_arguments.len() + lists.len();
             let mut arguments: Vec<BasicValueEnum> = Vec::with_capacity_in(capacity, env.arena);
 
@@ -1240,7 +1246,7 @@ pub(crate) fn call_list_bitcode_fn<'ctx>(
 
             returns.call_and_load_32bit(env, &arguments, fn_name)
         }
-        X86_64 | Aarch64 => {
+        X86_64 | Aarch64 | Riscv64 | Sbf => {
             let capacity = other_arguments.len() + lists.len() + returns.additional_arguments();
             let mut arguments: Vec<BasicValueEnum> = Vec::with_capacity_in(capacity, env.arena);
 
@@ -5,13 +5,15 @@ use crate::llvm::build_list::{
 use crate::llvm::convert::{
     argument_type_from_layout, basic_type_from_builtin, basic_type_from_layout, zig_str_type,
 };
+use crate::llvm::debug_info::DebugInfoBuilder as RocDebugInfo;
 use crate::llvm::expect::{clone_to_shared_memory, SharedMemoryPointer};
-use crate::llvm::memcpy::build_memcpy;
+use crate::llvm::memcpy::{build_memcpy, build_memcpy_raw, build_memset_raw};
 use crate::llvm::refcounting::{
     build_reset, decrement_refcount_layout, increment_refcount_layout, PointerToRefcount,
 };
+use crate::llvm::sbf::{call_sol_log_dbg, call_sol_log_failed_expect, call_sol_panic};
 use crate::llvm::struct_::{struct_from_fields, RocStruct};
-use crate::llvm::{erased, fn_ptr};
+use crate::llvm::{cfi, erased, fn_ptr, pool, sections, tagged_pointer, valgrind};
 use bumpalo::collections::Vec;
 use bumpalo::Bump;
 use inkwell::attributes::{Attribute, AttributeLoc};
@@ -34,7 +36,7 @@ use inkwell::values::{
     PointerMathValue, PointerValue, StructValue,
 };
 use inkwell::FloatPredicate;
-use inkwell::{AddressSpace, IntPredicate};
+use inkwell::{AddressSpace, DLLStorageClass, GlobalVisibility, IntPredicate};
 use morphic_lib::{
     CalleeSpecVar, FuncName, FuncSpec, FuncSpecSolutions, ModSolutions, UpdateMode, UpdateModeVar,
 };
@@ -45,6 +47,7 @@ use roc_debug_flags::dbg_do;
 use roc_debug_flags::ROC_PRINT_LLVM_FN_VERIFICATION;
 use roc_error_macros::{internal_error, todo_lambda_erasure};
 use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_mono::abi::{abi_version, ABI_VERSION_SYMBOL};
 use roc_mono::ir::{
     BranchInfo, CallType, CrashTag, EntryPoint, GlueLayouts, HostExposedLambdaSet,
     HostExposedLambdaSets, ListLiteralElement, ModifyRc, OptLevel, ProcLayout, SingleEntryPoint,
@@ -53,18 +56,23 @@ use roc_mono::layout::{
     Builtin, InLayout, LambdaName, LambdaSet, Layout, LayoutIds, LayoutInterner, LayoutRepr, Niche,
     RawFunctionLayout, STLayoutInterner, TagIdIntType, UnionLayout,
 };
+use roc_mono::pool::SizeClasses;
+use roc_region::all::LineColumn;
 use roc_std::RocDec;
 use roc_target::{PtrWidth, Target};
 use std::convert::TryInto;
 use std::path::Path;
 
 use super::convert::{struct_type_from_union_layout, RocUnion};
-use super::intrinsics::{
-    add_intrinsics, LLVM_FRAME_ADDRESS, LLVM_MEMSET_I32, LLVM_MEMSET_I64, LLVM_SETJMP,
-    LLVM_STACK_SAVE,
-};
+use super::decref_batch::build_decref_batch;
+use super::inline::add_inline_attributes;
+use super::intrinsics::{add_intrinsics, LLVM_FRAME_ADDRESS, LLVM_SETJMP, LLVM_STACK_SAVE};
+use super::lifetime::StackLifetimes;
 use super::lowlevel::run_higher_order_low_level;
-use super::scope::Scope;
+use super::pgo::PgoMode;
+use super::scope::{PureExpr, Scope};
+use super::stack_alloc::{non_escaping_lists, stack_list_literal};
+use super::stack_guard::build_stack_check;
 
 pub(crate) trait BuilderExt<'ctx> {
     fn new_build_struct_gep(
@@ -730,12 +738,67 @@ impl LlvmBackendMode {
     }
 }
 
+/// How a Roc panic is reported to the outside world
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PanicStrategy {
+    /// Call `roc_panic`, which is provided by the host (or by us, when there is no host)
+    Host,
+    /// Log the message with the `sol_log_` syscall, then abort with `sol_panic_`, passing the
+    /// source location. Only valid on SBF, where a minimal host does not define `roc_panic`.
+    SolPanic,
+}
+
+impl PanicStrategy {
+    /// SBF uses the syscalls, unless the platform asked for its own `roc_panic`
+    pub fn for_target(target: Target, host_panic: bool) -> Self {
+        match target {
+            Target::Sbf if !host_panic => PanicStrategy::SolPanic,
+            _ => PanicStrategy::Host,
+        }
+    }
+}
+
 pub struct Env<'a, 'ctx, 'env> {
     pub arena: &'a Bump,
     pub context: &'ctx Context,
     pub builder: &'env Builder<'ctx>,
     pub dibuilder: &'env DebugInfoBuilder<'ctx>,
     pub compile_unit: &'env DICompileUnit<'ctx>,
+    /// Set when debug info should point into the Roc source rather than at placeholders
+    pub debug_info: Option<&'env RocDebugInfo<'ctx>>,
+    pub panic_strategy: PanicStrategy,
+    /// Check the alignment of pointers before memory is accessed through them, see
+    /// [super::sanitizers::add_alignment_checks]
+    pub check_alignment: bool,
+    /// Overrides LLVM's inline threshold for calls to Roc procedures, see
+    /// [super::inline::add_inline_attributes]
+    pub inline_threshold: Option<u32>,
+    /// Build lists that never leave their procedure on the stack, see [super::stack_alloc]
+    pub optimize_stack_alloc: bool,
+    /// Report integer overflow through [OVERFLOW_TRAP], with the source location of the
+    /// procedure that overflowed, instead of with a plain `roc_panic`
+    pub overflow_trap: bool,
+    /// Instrument the program to collect a profile, or optimize it with one, see [super::pgo]
+    pub pgo: Option<PgoMode>,
+    /// Emit LLVM bitcode instead of an object file, and optimize it for link-time optimization
+    pub lto: bool,
+    /// Warn when the frames along the deepest call path take more than this many bytes of stack
+    /// together, see [super::stack_depth]
+    pub sbf_stack_limit: Option<u32>,
+    /// Check at the start of every procedure that the stack has not overflowed, see
+    /// [super::stack_guard]
+    pub stack_guard: bool,
+    /// The sections to place the constant data of some top-level definitions in, see
+    /// [super::sections]
+    pub data_sections: MutMap<Symbol, String>,
+    /// Check that every call through a function pointer calls a function of the right type, see
+    /// [super::cfi]
+    pub cfi: bool,
+    /// Allocate tags of these sizes from pools instead of with `roc_alloc`, see [super::pool]
+    pub pool_size_classes: Option<&'a SizeClasses>,
+    /// Make Valgrind client requests for the allocations from pools and arenas, see
+    /// [super::valgrind]
+    pub valgrind: bool,
     pub module: &'ctx Module<'ctx>,
     pub interns: Interns,
     pub target: Target,
@@ -812,6 +875,14 @@ impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
         })
     }
 
+    /// The calling convention of functions that are called by the host, or that call into it.
+    pub fn host_call_conv(&self) -> u32 {
+        match self.target {
+            Target::WinX64 => WIN64_CALL_CONV,
+            _ => C_CALL_CONV,
+        }
+    }
+
     pub fn alignment_type(&self) -> IntType<'ctx> {
         self.context.i32_type()
     }
@@ -870,23 +941,8 @@ impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
         bytes_ptr: PointerValue<'ctx>,
         filler: IntValue<'ctx>,
         length: IntValue<'ctx>,
-    ) -> CallSiteValue<'ctx> {
-        let false_val = self.context.bool_type().const_int(0, false);
-
-        let intrinsic_name = match self.target.ptr_width() {
-            roc_target::PtrWidth::Bytes8 => LLVM_MEMSET_I64,
-            roc_target::PtrWidth::Bytes4 => LLVM_MEMSET_I32,
-        };
-
-        self.build_intrinsic_call(
-            intrinsic_name,
-            &[
-                bytes_ptr.into(),
-                filler.into(),
-                length.into(),
-                false_val.into(),
-            ],
-        )
+    ) {
+        build_memset_raw(self, bytes_ptr, 1, filler, length)
     }
 
     pub fn call_panic(
@@ -895,6 +951,12 @@ impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
         message: BasicValueEnum<'ctx>,
         tag: CrashTag,
     ) {
+        if let PanicStrategy::SolPanic = self.panic_strategy {
+            // the tag only tells a host how to report the panic, and there is no host to tell
+            call_sol_panic(env, message);
+            return;
+        }
+
         let function = self.module.get_function("roc_panic").unwrap();
         let tag_id = self.context.i32_type().const_int(tag as u32 as u64, false);
 
@@ -1009,6 +1071,11 @@ impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
     }
 }
 
+/// The compiler-rt functions that 128-bit multiplication, division and remainder are lowered to
+/// on SBF.
+const SBF_COMPILER_RT_LIBCALLS: [&str; 5] =
+    ["__multi3", "__divti3", "__modti3", "__udivti3", "__umodti3"];
+
 pub fn module_from_builtins<'ctx>(
     target: Target,
     ctx: &'ctx Context,
@@ -1032,9 +1099,15 @@ pub fn module_from_builtins<'ctx>(
             Target::LinuxArm64 => {
                 include_bytes!("../../../builtins/bitcode/zig-out/builtins-aarch64.bc")
             }
+            Target::LinuxRiscv64 => {
+                include_bytes!("../../../builtins/bitcode/zig-out/builtins-riscv64.bc")
+            }
             Target::WinX64 => {
                 include_bytes!("../../../builtins/bitcode/zig-out/builtins-windows-x86_64.bc")
             }
+            Target::Sbf => {
+                include_bytes!("../../../builtins/bitcode/zig-out/builtins-sbf.bc")
+            }
             _ => panic!("The zig builtins are not currently built for this target: {target:?}"),
         }
     };
@@ -1066,6 +1139,8 @@ pub fn module_from_builtins<'ctx>(
         "__muloti4",
         "__udivti3",
         "__umodti3",
+        // SBF has no 64x64->128 bit multiply, so Dec multiplication goes through compiler-rt
+        "__multi3",
         // Roc special functions
         "__roc_force_longjmp",
         "__roc_force_setjmp",
@@ -1087,6 +1162,22 @@ pub fn module_from_builtins<'ctx>(
 
     mpm.run_on(&module);
 
+    if target == Target::Sbf {
+        // SBF has no 128-bit multiply or divide, and LLVM lowers those to compiler-rt calls only
+        // when the object file is emitted. Nothing else provides them to a Solana program.
+        for name in SBF_COMPILER_RT_LIBCALLS {
+            let defined = module
+                .get_function(name)
+                .is_some_and(|function| function.count_basic_blocks() > 0);
+
+            if !defined {
+                internal_error!(
+                    "The SBF builtins do not define `{name}`, so 128-bit arithmetic cannot be linked. Were they built without compiler-rt?"
+                );
+            }
+        }
+    }
+
     // Now that the unused compiler-rt functions have been removed,
     // mark that the builtin functions are allowed to be DCE'd if they aren't used.
     for func in module.get_functions() {
@@ -1310,7 +1401,10 @@ pub fn build_exp_literal<'a, 'ctx>(
     }
 }
 
-fn build_string_literal<'ctx>(env: &Env<'_, 'ctx, '_>, str_literal: &str) -> BasicValueEnum<'ctx> {
+pub(crate) fn build_string_literal<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    str_literal: &str,
+) -> BasicValueEnum<'ctx> {
     if str_literal.len() < env.small_str_bytes() as usize {
         match env.small_str_bytes() {
             24 => small_str_ptr_width_8(env, str_literal).into(),
@@ -1411,6 +1505,7 @@ pub(crate) fn build_exp_call<'a, 'ctx>(
     parent: FunctionValue<'ctx>,
     layout: InLayout<'a>,
     call: &roc_mono::ir::Call<'a>,
+    tail_call: TailCall,
 ) -> BasicValueEnum<'ctx> {
     let roc_mono::ir::Call {
         call_type,
@@ -1442,6 +1537,7 @@ pub(crate) fn build_exp_call<'a, 'ctx>(
                 *name,
                 FuncBorrowSpec::Some(func_spec),
                 arg_tuples.into_bump_slice(),
+                tail_call,
             )
         }
 
@@ -1456,7 +1552,12 @@ pub(crate) fn build_exp_call<'a, 'ctx>(
                 args.push(scope.load_symbol(symbol));
             }
 
-            let pointer = scope.load_symbol(pointer).into_pointer_value();
+            let (pointer, pointer_layout) = scope.load_symbol_and_layout(pointer);
+            let pointer = pointer.into_pointer_value();
+
+            if env.cfi {
+                cfi::build_type_check(env, layout_interner, pointer, pointer_layout);
+            }
 
             roc_call_erased_with_args(
                 env,
@@ -1465,6 +1566,7 @@ pub(crate) fn build_exp_call<'a, 'ctx>(
                 arg_layouts,
                 *ret_layout,
                 args.into_bump_slice(),
+                tail_call,
             )
         }
 
@@ -1586,6 +1688,7 @@ pub(crate) fn build_exp_expr<'a, 'ctx>(
             parent,
             layout,
             call,
+            TailCall::No,
         ),
 
         Struct(sorted_fields) => RocStruct::build(
@@ -1618,7 +1721,7 @@ pub(crate) fn build_exp_expr<'a, 'ctx>(
         }
 
         FunctionPointer { lambda_name } => {
-            let alloca = fn_ptr::build(env, *lambda_name);
+            let alloca = fn_ptr::build(env, layout_interner, *lambda_name, layout);
             alloca.into()
         }
         ErasedMake { value, callee } => {
@@ -1909,6 +2012,38 @@ pub(crate) fn build_exp_expr<'a, 'ctx>(
                         target_loaded_type,
                     )
                 }
+                UnionLayout::TaggedPointer { tags, .. } => {
+                    debug_assert!(argument.is_pointer_value());
+
+                    let target_loaded_type = basic_type_from_layout(
+                        env,
+                        layout_interner,
+                        layout_interner.get_repr(layout),
+                    );
+
+                    if union_layout.tag_is_immediate(*tag_id) {
+                        debug_assert_eq!(*index, 0);
+
+                        tagged_pointer::load_immediate(
+                            env,
+                            argument.into_pointer_value(),
+                            target_loaded_type.into_int_type(),
+                        )
+                        .into()
+                    } else {
+                        let ptr = tag_pointer_clear_tag_id(env, argument.into_pointer_value());
+
+                        lookup_at_index_ptr(
+                            env,
+                            layout_interner,
+                            tags[*tag_id as usize],
+                            *index as usize,
+                            ptr,
+                            None,
+                            target_loaded_type,
+                        )
+                    }
+                }
                 UnionLayout::NonNullableUnwrapped(field_layouts) => {
                     let struct_layout = LayoutRepr::struct_(field_layouts);
 
@@ -2007,8 +2142,15 @@ pub(crate) fn build_exp_expr<'a, 'ctx>(
 
             let pointer_value = match union_layout {
                 UnionLayout::NonRecursive(_) => unreachable!(),
-                UnionLayout::Recursive(tag_layouts) => {
+                UnionLayout::Recursive(tag_layouts)
+                | UnionLayout::TaggedPointer {
+                    tags: tag_layouts, ..
+                } => {
                     debug_assert!(argument.is_pointer_value());
+                    debug_assert!(
+                        !union_layout.tag_is_immediate(tag_id as _),
+                        "the immediate tag of a TaggedPointer is not stored in memory"
+                    );
 
                     let field_layouts = tag_layouts[tag_id as usize];
 
@@ -2324,6 +2466,32 @@ fn build_tag<'a, 'ctx>(
                 parent,
             )
         }
+        UnionLayout::TaggedPointer { immediate_id, tags } => {
+            debug_assert!(union_size > 1);
+
+            if tag_id == *immediate_id {
+                debug_assert_eq!(arguments.len(), 1);
+
+                let payload = scope.load_symbol(&arguments[0]).into_int_value();
+
+                return tagged_pointer::build_immediate(env, payload).into();
+            }
+
+            let tag_field_layouts = &tags[tag_id as usize];
+
+            build_wrapped_tag(
+                env,
+                layout_interner,
+                scope,
+                union_layout,
+                tag_id as _,
+                arguments,
+                tag_field_layouts,
+                tags,
+                reuse_allocation,
+                parent,
+            )
+        }
         UnionLayout::NullableWrapped {
             nullable_id,
             other_tags: tags,
@@ -2652,6 +2820,47 @@ pub fn get_tag_id<'a, 'ctx>(
                 .new_build_load(tag_id_int_type, result, "load_result")
                 .into_int_value()
         }
+        UnionLayout::TaggedPointer { immediate_id, .. } => {
+            let argument_ptr = argument.into_pointer_value();
+
+            // an immediate has bits of its payload where a pointer has the tag id
+            let is_immediate = tagged_pointer::is_immediate(env, argument_ptr);
+
+            let ctx = env.context;
+            let then_block = ctx.append_basic_block(parent, "then");
+            let else_block = ctx.append_basic_block(parent, "else");
+            let cont_block = ctx.append_basic_block(parent, "cont");
+
+            let result = create_entry_block_alloca(env, tag_id_int_type, "result");
+
+            env.builder
+                .new_build_conditional_branch(is_immediate, then_block, else_block);
+
+            {
+                env.builder.position_at_end(then_block);
+                let tag_id = tag_id_int_type.const_int(*immediate_id as u64, false);
+                env.builder.new_build_store(result, tag_id);
+                env.builder.new_build_unconditional_branch(cont_block);
+            }
+
+            {
+                env.builder.position_at_end(else_block);
+
+                let tag_id = if union_layout.stores_tag_id_as_data(env.target) {
+                    get_tag_id_wrapped(env, layout_interner, *union_layout, argument_ptr)
+                } else {
+                    tag_pointer_read_tag_id(env, argument_ptr)
+                };
+                env.builder.new_build_store(result, tag_id);
+                env.builder.new_build_unconditional_branch(cont_block);
+            }
+
+            env.builder.position_at_end(cont_block);
+
+            env.builder
+                .new_build_load(tag_id_int_type, result, "load_result")
+                .into_int_value()
+        }
         UnionLayout::NullableUnwrapped { nullable_id, .. } => {
             let argument_ptr = argument.into_pointer_value();
             let is_null = env.builder.new_build_is_null(argument_ptr, "is_null");
@@ -2777,6 +2986,10 @@ fn reserve_union_with_refcount_help<'ctx>(
     stack_size: u32,
     alignment_bytes: u32,
 ) -> PointerValue<'ctx> {
+    if let Some(ptr) = pool::allocate_with_refcount(env, stack_size, alignment_bytes) {
+        return ptr;
+    }
+
     let len_type = env.ptr_int();
 
     let value_bytes_intvalue = len_type.const_int(stack_size as u64, false);
@@ -2816,9 +3029,6 @@ fn list_literal<'a, 'ctx>(
     element_layout: InLayout<'a>,
     elems: &[ListLiteralElement],
 ) -> BasicValueEnum<'ctx> {
-    let ctx = env.context;
-    let builder = env.builder;
-
     let element_type = basic_type_from_layout(
         env,
         layout_interner,
@@ -2925,40 +3135,63 @@ fn list_literal<'a, 'ctx>(
         let byte_size = env
             .ptr_int()
             .const_int(list_length as u64 * element_width as u64, false);
-        builder
-            .build_memcpy(data_ptr, alignment, const_data_ptr, alignment, byte_size)
-            .unwrap();
+        build_memcpy_raw(
+            env,
+            data_ptr,
+            alignment,
+            const_data_ptr,
+            alignment,
+            byte_size,
+        );
 
         super::build_list::store_list(env, data_ptr, list_length_intval).into()
     } else {
         let ptr = allocate_list(env, layout_interner, element_layout, list_length_intval);
 
-        // Copy the elements from the list literal into the array
-        for (index, element) in elems.iter().enumerate() {
-            let val = match element {
-                ListLiteralElement::Literal(literal) => {
-                    build_exp_literal(env, layout_interner, element_layout, literal)
-                }
-                ListLiteralElement::Symbol(symbol) => scope.load_symbol(symbol),
-            };
-            let index_val = ctx.i64_type().const_int(index as u64, false);
-            let elem_ptr = unsafe {
-                builder.new_build_in_bounds_gep(element_type, ptr, &[index_val], "index")
-            };
-
-            store_roc_value(
-                env,
-                layout_interner,
-                layout_interner.get_repr(element_layout),
-                elem_ptr,
-                val,
-            );
-        }
+        store_list_literal_elements(env, layout_interner, scope, element_layout, ptr, elems);
 
         super::build_list::store_list(env, ptr, list_length_intval).into()
     }
 }
 
+/// Copy the elements from a list literal into the array at `ptr`
+pub(crate) fn store_list_literal_elements<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    scope: &Scope<'a, 'ctx>,
+    element_layout: InLayout<'a>,
+    ptr: PointerValue<'ctx>,
+    elems: &[ListLiteralElement],
+) {
+    let element_type = basic_type_from_layout(
+        env,
+        layout_interner,
+        layout_interner.get_repr(element_layout),
+    );
+
+    for (index, element) in elems.iter().enumerate() {
+        let val = match element {
+            ListLiteralElement::Literal(literal) => {
+                build_exp_literal(env, layout_interner, element_layout, literal)
+            }
+            ListLiteralElement::Symbol(symbol) => scope.load_symbol(symbol),
+        };
+        let index_val = env.context.i64_type().const_int(index as u64, false);
+        let elem_ptr = unsafe {
+            env.builder
+                .new_build_in_bounds_gep(element_type, ptr, &[index_val], "index")
+        };
+
+        store_roc_value(
+            env,
+            layout_interner,
+            layout_interner.get_repr(element_layout),
+            elem_ptr,
+            val,
+        );
+    }
+}
+
 pub fn load_roc_value<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
     layout_interner: &STLayoutInterner<'a>,
@@ -3071,22 +3304,78 @@ pub(crate) fn build_exp_stmt<'a, 'ctx>(
 
             let mut stack = Vec::with_capacity_in(queue.len(), env.arena);
 
-            for (symbol, expr, layout) in queue {
+            // `let x = f args in ret x` is a call in tail position
+            let tail_symbol = match cont {
+                Ret(ret_symbol) => Some(*ret_symbol),
+                _ => None,
+            };
+            let last_index = queue.len() - 1;
+
+            let mut lifetimes = StackLifetimes::new(&queue, cont);
+
+            for (index, (symbol, expr, layout)) in queue.into_iter().enumerate() {
                 debug_assert!(!matches!(
                     layout_interner.get_repr(*layout),
                     LayoutRepr::RecursivePointer(_)
                 ));
 
-                let val = build_exp_expr(
-                    env,
-                    layout_interner,
-                    layout_ids,
-                    func_spec_solutions,
-                    scope,
-                    parent,
-                    *layout,
-                    expr,
-                );
+                let binding_start = lifetimes.binding_start(env, index);
+
+                let val = match expr {
+                    roc_mono::ir::Expr::Call(call)
+                        if index == last_index && tail_symbol == Some(*symbol) =>
+                    {
+                        build_exp_call(
+                            env,
+                            layout_interner,
+                            layout_ids,
+                            func_spec_solutions,
+                            scope,
+                            parent,
+                            *layout,
+                            call,
+                            TailCall::Yes,
+                        )
+                    }
+                    roc_mono::ir::Expr::Array { elem_layout, elems }
+                        if scope.is_stack_list(symbol) =>
+                    {
+                        stack_list_literal(env, layout_interner, scope, *elem_layout, elems)
+                    }
+                    _ => {
+                        let pure_expr = PureExpr::from_expr(expr);
+                        let emitted = match (&pure_expr, env.builder.get_insert_block()) {
+                            (Some(pure_expr), Some(block)) => scope.emitted_pure(pure_expr, block),
+                            _ => None,
+                        };
+
+                        match emitted {
+                            Some(value) => value,
+                            None => {
+                                let value = build_exp_expr(
+                                    env,
+                                    layout_interner,
+                                    layout_ids,
+                                    func_spec_solutions,
+                                    scope,
+                                    parent,
+                                    *layout,
+                                    expr,
+                                );
+
+                                // a checked operation continues in a new block, so this is where
+                                // the value can be used next
+                                if let (Some(pure_expr), Some(block)) =
+                                    (pure_expr, env.builder.get_insert_block())
+                                {
+                                    scope.insert_pure(pure_expr, block, value);
+                                }
+
+                                value
+                            }
+                        }
+                    }
+                };
 
                 // Make a new scope which includes the binding we just encountered.
                 // This should be done *after* compiling the bound expr, since any
@@ -3095,8 +3384,10 @@ pub(crate) fn build_exp_stmt<'a, 'ctx>(
                 // access itself!
                 // scope = scope.clone();
 
+                lifetimes.binding_end(env, layout_interner, index, *layout, val, binding_start);
+
                 scope.insert(*symbol, *layout, val);
-                stack.push(*symbol);
+                stack.push((*symbol, *layout, val));
             }
 
             let result = build_exp_stmt(
@@ -3109,7 +3400,12 @@ pub(crate) fn build_exp_stmt<'a, 'ctx>(
                 cont,
             );
 
-            for symbol in stack {
+            for (symbol, layout, val) in stack {
+                // the continuation has been built, so every binding is followed by an instruction
+                if let Some(debug_info) = env.debug_info {
+                    debug_info.declare_local(env, layout_interner, symbol, layout, val);
+                }
+
                 scope.remove(&symbol);
             }
 
@@ -3269,15 +3565,14 @@ pub(crate) fn build_exp_stmt<'a, 'ctx>(
                             layout_interner.get_repr(layout),
                         );
                         let tmp = create_entry_block_alloca(env, basic_type, "tmp_output_for_jmp");
-                        builder
-                            .build_memcpy(
-                                tmp,
-                                alignment,
-                                value.into_pointer_value(),
-                                alignment,
-                                env.ptr_int().const_int(size as _, false),
-                            )
-                            .unwrap();
+                        build_memcpy_raw(
+                            env,
+                            tmp,
+                            alignment,
+                            value.into_pointer_value(),
+                            alignment,
+                            env.ptr_int().const_int(size as _, false),
+                        );
                         to_resolve.push((alloca, tmp, alignment, size));
                     }
                     crate::llvm::scope::JoinPointArg::Phi(phi) => {
@@ -3286,15 +3581,14 @@ pub(crate) fn build_exp_stmt<'a, 'ctx>(
                 }
             }
             for (alloca, tmp, alignment, size) in to_resolve {
-                builder
-                    .build_memcpy(
-                        *alloca,
-                        alignment,
-                        tmp,
-                        alignment,
-                        env.ptr_int().const_int(size as _, false),
-                    )
-                    .unwrap();
+                build_memcpy_raw(
+                    env,
+                    *alloca,
+                    alignment,
+                    tmp,
+                    alignment,
+                    env.ptr_int().const_int(size as _, false),
+                );
             }
 
             builder.new_build_unconditional_branch(*cont_block);
@@ -3303,6 +3597,19 @@ pub(crate) fn build_exp_stmt<'a, 'ctx>(
             context.i64_type().const_zero().into()
         }
 
+        Refcounting(modify, cont) if scope.is_stack_list(&modify.get_symbol()) => {
+            // a list on the stack has no refcount to update, and must never be freed
+            build_exp_stmt(
+                env,
+                layout_interner,
+                layout_ids,
+                func_spec_solutions,
+                scope,
+                parent,
+                cont,
+            )
+        }
+
         Refcounting(modify, cont) => {
             use ModifyRc::*;
 
@@ -3331,6 +3638,18 @@ pub(crate) fn build_exp_stmt<'a, 'ctx>(
                     )
                 }
                 Dec(symbol) => {
+                    if let Some(rest) = build_decref_batch(env, layout_interner, scope, stmt) {
+                        return build_exp_stmt(
+                            env,
+                            layout_interner,
+                            layout_ids,
+                            func_spec_solutions,
+                            scope,
+                            parent,
+                            rest,
+                        );
+                    }
+
                     let (value, layout) = scope.load_symbol_and_layout(symbol);
 
                     if layout_interner.contains_refcounted(layout) {
@@ -3390,6 +3709,23 @@ pub(crate) fn build_exp_stmt<'a, 'ctx>(
                                 let condition = env
                                     .builder
                                     .new_build_is_not_null(value_ptr, "box_is_not_null");
+
+                                // an immediate is not allocated either
+                                let condition = match other_layout {
+                                    LayoutRepr::Union(UnionLayout::TaggedPointer { .. }) => {
+                                        let is_immediate =
+                                            tagged_pointer::is_immediate(env, value_ptr);
+                                        let is_allocated =
+                                            env.builder.new_build_not(is_immediate, "is_allocated");
+
+                                        env.builder.new_build_and(
+                                            condition,
+                                            is_allocated,
+                                            "box_is_allocated",
+                                        )
+                                    }
+                                    _ => condition,
+                                };
                                 env.builder.new_build_conditional_branch(
                                     condition, then_block, done_block,
                                 );
@@ -3471,10 +3807,16 @@ pub(crate) fn build_exp_stmt<'a, 'ctx>(
             variable: _,
             remainder,
         } => {
-            let location = build_string_literal(env, source_location);
-            let source = build_string_literal(env, source);
             let message = scope.load_symbol(symbol);
-            env.call_dbg(env, location, source, message);
+
+            if matches!(env.target, Target::Sbf) {
+                // the Solana runtime has no stderr, only the program log
+                call_sol_log_dbg(env, source_location, source, message);
+            } else {
+                let location = build_string_literal(env, source_location);
+                let source = build_string_literal(env, source);
+                env.call_dbg(env, location, source, message);
+            }
 
             build_exp_stmt(
                 env,
@@ -3509,9 +3851,15 @@ pub(crate) fn build_exp_stmt<'a, 'ctx>(
             let then_block = context.append_basic_block(parent, "then_block");
             let throw_block = context.append_basic_block(parent, "throw_block");
 
-            bd.new_build_conditional_branch(condition, then_block, throw_block);
+            build_error_check_branch(env, condition, then_block, throw_block, ColdBranch::Else);
 
-            if env.mode.runs_expects() {
+            if env.mode.runs_expects() && matches!(env.target, Target::Sbf) {
+                // there is no parent process to report to, so log the expect and abort
+                bd.position_at_end(throw_block);
+
+                call_sol_log_failed_expect(env, *region);
+                throw_internal_exception(env, "An expectation failed!");
+            } else if env.mode.runs_expects() {
                 bd.position_at_end(throw_block);
 
                 match env.target.ptr_width() {
@@ -4589,7 +4937,8 @@ fn expose_function_to_host_help_c_abi_v2<'a, 'ctx>(
     );
 
     // a temporary solution to be able to pass RocStr by-value from a host language.
-    {
+    // Windows x64 has no by-value stack arguments: the host passes a pointer to its own copy.
+    if !matches!(env.target, Target::WinX64) {
         let extra = match cc_return {
             CCReturn::Return => 0,
             CCReturn::ByPointer => 1,
@@ -4694,11 +5043,12 @@ fn expose_function_to_host_help_c_abi_v2<'a, 'ctx>(
                 // not pretty, but seems to cover all our current cases
                 if arg_type.is_pointer_type() && !fastcc_type.is_pointer_type() {
                     // On x86_*, Modify the argument to specify it is passed by value and nonnull
-                    // Aarch*, just passes in the pointer directly.
+                    // Aarch* and Windows x64 just pass in the pointer directly.
                     if matches!(
                         env.target.architecture(),
                         roc_target::Architecture::X86_32 | roc_target::Architecture::X86_64
-                    ) {
+                    ) && !matches!(env.target, Target::WinX64)
+                    {
                         let c_abi_type = match layout_interner.get_repr(*layout) {
                             LayoutRepr::Builtin(Builtin::Str | Builtin::List(_)) => {
                                 c_abi_roc_str_type
@@ -5049,8 +5399,13 @@ fn set_jump_and_catch_long_jump<'a, 'ctx>(
         "to_bool",
     );
 
-    env.builder
-        .new_build_conditional_branch(panicked_bool, catch_block, then_block);
+    build_error_check_branch(
+        env,
+        panicked_bool,
+        catch_block,
+        then_block,
+        ColdBranch::Then,
+    );
 
     // all went well
     {
@@ -5318,6 +5673,29 @@ pub(crate) fn build_proc_headers<'a, 'r, 'ctx>(
 )> {
     // Populate Procs further and get the low-level Expr from the canonical Expr
     let mut headers = std::vec::Vec::with_capacity(procedures.len());
+
+    // The specializations are made on several threads that share one layout interner, so neither
+    // the order of `procedures` nor the interned layouts are the same from one build to the next.
+    // The id of a module depends on the order the modules were loaded in, so the symbols are
+    // compared by name. Sorting by those names and the layout itself makes the same module, with
+    // the same names.
+    let mut procedures: std::vec::Vec<_> = procedures.into_iter().collect();
+    procedures.sort_by_cached_key(|((symbol, layout), _)| {
+        let name = format!(
+            "{}.{}",
+            env.interns.module_name(symbol.module_id()),
+            symbol.as_str(&env.interns)
+        );
+        let layout = format!(
+            "{:?} {:?} {:?}",
+            layout_interner.dbg_stable_iter(layout.arguments),
+            layout_interner.dbg_stable(layout.result),
+            layout.niche.dbg_stable(layout_interner),
+        );
+
+        (name, layout, *symbol)
+    });
+
     for ((symbol, layout), proc) in procedures {
         let name_bytes = roc_alias_analysis::func_name_bytes(&proc);
         let func_name = FuncName(&name_bytes);
@@ -5366,6 +5744,12 @@ pub fn build_procedures<'a>(
     debug_output_file: Option<&Path>,
     glue_layouts: &GlueLayouts<'a>,
 ) {
+    if let EntryPoint::Program(entry_points) = entry_point {
+        if env.mode.has_host() {
+            add_abi_version(env, layout_interner, entry_points);
+        }
+    }
+
     let mod_solutions = build_procedures_help(
         env,
         layout_interner,
@@ -5411,6 +5795,90 @@ pub fn build_procedures<'a>(
             getter_name,
         );
     }
+
+    if env.target.operating_system() == roc_target::OperatingSystem::Windows {
+        export_host_functions_from_dll(env);
+    }
+}
+
+/// Embed the ABI version of the entry points, so the host can check that it was built for them
+fn add_abi_version<'a>(
+    env: &Env<'a, '_, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    entry_points: &[SingleEntryPoint<'a>],
+) {
+    let version = abi_version(
+        layout_interner,
+        entry_points.iter().map(|entry_point| {
+            let layout = entry_point.layout;
+
+            (entry_point.name, layout.arguments, layout.result)
+        }),
+    );
+
+    let i64_typ = env.context.i64_type();
+    let global = env.module.add_global(i64_typ, None, ABI_VERSION_SYMBOL);
+
+    global.set_initializer(&i64_typ.const_int(version, false));
+    global.set_constant(true);
+}
+
+/// Whether the host may call this function. They are all called `roc__*`.
+fn is_host_exposed(function: FunctionValue<'_>) -> bool {
+    function.get_linkage() == Linkage::External
+        && function.count_basic_blocks() > 0
+        && function.get_name().to_bytes().starts_with(b"roc__")
+}
+
+/// A DLL only exports the symbols that are marked `dllexport`, so mark every function that the
+/// host may call, and the ABI version it checks.
+fn export_host_functions_from_dll(env: &Env<'_, '_, '_>) {
+    for function in env.module.get_functions() {
+        if is_host_exposed(function) {
+            function
+                .as_global_value()
+                .set_dll_storage_class(DLLStorageClass::Export);
+        }
+    }
+
+    if let Some(global) = env.module.get_global(ABI_VERSION_SYMBOL) {
+        global.set_dll_storage_class(DLLStorageClass::Export);
+    }
+}
+
+/// A shared library exports every symbol that it defines and that is not hidden. Hide everything
+/// the app defines except the functions the host calls and the ABI version, so that the builtins
+/// and the procedures of the app can not clash with the symbols of the program that loads it.
+pub fn hide_internal_symbols(env: &Env<'_, '_, '_>) {
+    let defined_functions = env
+        .module
+        .get_functions()
+        .filter(|function| function.count_basic_blocks() > 0)
+        .map(|function| (function.as_global_value(), is_host_exposed(function)));
+
+    let defined_globals = env
+        .module
+        .get_globals()
+        .filter(|global| global.get_initializer().is_some())
+        .map(|global| {
+            (
+                global,
+                global.get_name().to_bytes() == ABI_VERSION_SYMBOL.as_bytes(),
+            )
+        });
+
+    for (global, exported) in defined_functions.chain(defined_globals) {
+        // symbols with local linkage are not exported anyway, and must keep the default visibility
+        if matches!(global.get_linkage(), Linkage::Private | Linkage::Internal) {
+            continue;
+        }
+
+        global.set_visibility(if exported {
+            GlobalVisibility::Default
+        } else {
+            GlobalVisibility::Hidden
+        });
+    }
 }
 
 pub fn build_wasm_test_wrapper<'a, 'ctx>(
@@ -5568,6 +6036,14 @@ fn build_procedures_help<'a>(
     let mut layout_ids = roc_mono::layout::LayoutIds::default();
     let mut scope = Scope::default();
 
+    if let Some(size_classes) = env.pool_size_classes {
+        pool::add_size_classes(env, size_classes);
+    }
+
+    if env.valgrind {
+        valgrind::add_valgrind_symbol(env);
+    }
+
     let it1 = procedures.iter().map(|x| x.1);
     let it2 = host_exposed_lambda_sets.iter().map(|(_, _, hels)| hels);
 
@@ -5592,7 +6068,7 @@ fn build_procedures_help<'a>(
     // Add all the Proc headers to the module.
     // We have to do this in a separate pass first,
     // because their bodies may reference each other.
-    let headers = build_proc_headers(
+    let mut headers = build_proc_headers(
         env,
         layout_interner,
         mod_solutions,
@@ -5601,7 +6077,14 @@ fn build_procedures_help<'a>(
         &mut layout_ids,
     );
 
+    // String literals are shared between the procedures that use them, and each one is created
+    // by the first procedure that is built. Building the definitions that have a data section
+    // first puts the literals they share with other procedures in that section as well.
+    headers.sort_by_key(|(proc, _)| !env.data_sections.contains_key(&proc.name.name()));
+
     for (proc, fn_vals) in headers {
+        let data_section = env.data_sections.get(&proc.name.name());
+
         for (func_spec_solutions, fn_val) in fn_vals {
             let mut current_scope = scope.clone();
 
@@ -5610,6 +6093,8 @@ fn build_procedures_help<'a>(
             let home = proc.name.name().module_id();
             current_scope.retain_top_level_thunks_for_module(home);
 
+            let globals_before = data_section.map(|_| sections::globals(env.module));
+
             build_proc(
                 env,
                 layout_interner,
@@ -5620,6 +6105,10 @@ fn build_procedures_help<'a>(
                 fn_val,
             );
 
+            if let (Some(section), Some(before)) = (data_section, &globals_before) {
+                sections::place_new_constants(env.module, before, section);
+            }
+
             // call finalize() before any code generation/verification
             env.dibuilder.finalize();
 
@@ -5751,7 +6240,10 @@ fn build_proc_header<'a, 'ctx>(
         Linkage::Internal,
     );
 
-    let subprogram = env.new_subprogram(&fn_name);
+    let subprogram = match env.debug_info {
+        Some(debug_info) => debug_info.new_subprogram(env, symbol, &fn_name),
+        None => env.new_subprogram(&fn_name),
+    };
     fn_val.set_subprogram(subprogram);
 
     debug_info_init!(env, fn_val);
@@ -5770,19 +6262,7 @@ fn build_proc_header<'a, 'ctx>(
         );
     }
 
-    if false {
-        let kind_id = Attribute::get_named_enum_kind_id("alwaysinline");
-        debug_assert!(kind_id > 0);
-        let enum_attr = env.context.create_enum_attribute(kind_id, 0);
-        fn_val.add_attribute(AttributeLoc::Function, enum_attr);
-    }
-
-    if false {
-        let kind_id = Attribute::get_named_enum_kind_id("noinline");
-        debug_assert!(kind_id > 0);
-        let enum_attr = env.context.create_enum_attribute(kind_id, 0);
-        fn_val.add_attribute(AttributeLoc::Function, enum_attr);
-    }
+    add_inline_attributes(env, fn_val, symbol);
 
     fn_val
 }
@@ -6048,6 +6528,18 @@ fn build_proc<'a, 'ctx>(
 
     debug_info_init!(env, fn_val);
 
+    if let Some(debug_info) = env.debug_info {
+        debug_info.set_proc_location(env, proc.name.name(), fn_val);
+    }
+
+    if env.stack_guard {
+        build_stack_check(env, fn_val);
+    }
+
+    if env.optimize_stack_alloc {
+        scope.insert_stack_lists(non_escaping_lists(layout_interner, proc));
+    }
+
     // Add args to scope
     for (arg_val, (layout, arg_symbol)) in fn_val.get_param_iter().zip(args) {
         arg_val.set_name(arg_symbol.as_str(&env.interns));
@@ -6070,6 +6562,23 @@ fn build_proc<'a, 'ctx>(
             builder.new_build_return(Some(&body));
         }
     }
+
+    if let Some(debug_info) = env.debug_info {
+        for (arg_no, (arg_val, (layout, arg_symbol))) in
+            fn_val.get_param_iter().zip(args).enumerate()
+        {
+            debug_info.declare_parameter(
+                env,
+                layout_interner,
+                proc.name.name(),
+                fn_val,
+                arg_no as u32 + 1,
+                *arg_symbol,
+                *layout,
+                arg_val,
+            );
+        }
+    }
 }
 
 pub fn verify_fn(fn_val: FunctionValue<'_>) {
@@ -6115,15 +6624,23 @@ fn roc_call_direct_with_args<'a, 'ctx>(
     name: LambdaName<'a>,
     func_spec: FuncBorrowSpec,
     arguments: &[BasicValueEnum<'ctx>],
+    tail_call: TailCall,
 ) -> BasicValueEnum<'ctx> {
     let fn_val = function_value_by_func_spec(env, func_spec, name.name());
 
-    call_direct_roc_function(
+    let build_call = |arguments: &[BasicMetadataValueEnum<'ctx>]| {
+        env.builder.new_build_call(fn_val, arguments, "call")
+    };
+    debug_assert_eq!(fn_val.get_call_conventions(), FAST_CALL_CONV);
+
+    call_roc_function_help(
         env,
         layout_interner,
-        fn_val,
+        build_call,
+        fn_val.get_type(),
         layout_interner.get_repr(result_layout),
         arguments,
+        tail_call,
     )
 }
 
@@ -6135,6 +6652,7 @@ fn roc_call_erased_with_args<'a, 'ctx>(
     argument_layouts: &[InLayout<'a>],
     result_layout: InLayout<'a>,
     arguments: &[BasicValueEnum<'ctx>],
+    tail_call: TailCall,
 ) -> BasicValueEnum<'ctx> {
     let function_type =
         fn_ptr::function_type(env, layout_interner, argument_layouts, result_layout);
@@ -6154,6 +6672,7 @@ fn roc_call_erased_with_args<'a, 'ctx>(
         function_type,
         layout_interner.get_repr(result_layout),
         arguments,
+        tail_call,
     )
 }
 
@@ -6178,9 +6697,34 @@ pub(crate) fn call_direct_roc_function<'a, 'ctx>(
         function_type,
         result_layout,
         arguments,
+        TailCall::No,
     )
 }
 
+/// Whether a call is in tail position, i.e. its result is immediately returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TailCall {
+    Yes,
+    No,
+}
+
+/// The LLVM `tail` marker promises that the callee does not access allocas of the caller.
+/// We conservatively assume that any pointer not loaded from memory, returned by a call or
+/// received as a parameter may point into the current stack frame.
+fn may_point_into_frame(value: BasicValueEnum<'_>) -> bool {
+    if !value.is_pointer_value() {
+        return false;
+    }
+
+    match value.as_instruction_value() {
+        None => false,
+        Some(instruction) => !matches!(
+            instruction.get_opcode(),
+            InstructionOpcode::Load | InstructionOpcode::Call
+        ),
+    }
+}
+
 fn call_roc_function_help<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
     layout_interner: &STLayoutInterner<'a>,
@@ -6188,8 +6732,13 @@ fn call_roc_function_help<'a, 'ctx>(
     roc_function_type: FunctionType<'ctx>,
     result_layout: LayoutRepr<'a>,
     arguments: &[BasicValueEnum<'ctx>],
+    tail_call: TailCall,
 ) -> BasicValueEnum<'ctx> {
     let pass_by_pointer = roc_function_type.get_param_types().len() == arguments.len() + 1;
+    let is_tail_call = tail_call == TailCall::Yes
+        && !arguments
+            .iter()
+            .any(|argument| may_point_into_frame(*argument));
 
     match RocReturn::from_layout(layout_interner, result_layout) {
         RocReturn::ByPointer if !pass_by_pointer => {
@@ -6247,6 +6796,13 @@ fn call_roc_function_help<'a, 'ctx>(
             // roc functions should have the fast calling convention
             call.set_call_convention(FAST_CALL_CONV);
 
+            // The result is handed straight to our own `ret`, so the caller's frame can be
+            // reused. Results returned by pointer live in an alloca in this frame, so those
+            // calls never qualify.
+            if is_tail_call {
+                call.set_tail_call(true);
+            }
+
             call.try_as_basic_value()
                 .left()
                 .unwrap_or_else(|| internal_error!("LLVM error: Invalid call by name",))
@@ -6264,7 +6820,7 @@ pub fn get_call_conventions(cc: target_lexicon::CallingConvention) -> u32 {
     match cc {
         SystemV => C_CALL_CONV,
         WasmBasicCAbi => C_CALL_CONV,
-        WindowsFastcall => C_CALL_CONV,
+        WindowsFastcall => WIN64_CALL_CONV,
         AppleAarch64 => C_CALL_CONV,
         _ => C_CALL_CONV,
     }
@@ -6274,6 +6830,9 @@ pub fn get_call_conventions(cc: target_lexicon::CallingConvention) -> u32 {
 pub const C_CALL_CONV: u32 = 0;
 pub const FAST_CALL_CONV: u32 = 8;
 pub const COLD_CALL_CONV: u32 = 9;
+/// The Windows x64 calling convention. LLVM also picks it for `C_CALL_CONV` on Windows triples,
+/// but saying so explicitly keeps the IR correct whatever triple it is compiled with.
+pub const WIN64_CALL_CONV: u32 = 79;
 
 pub struct RocFunctionCall<'ctx> {
     pub caller: PointerValue<'ctx>,
@@ -6359,7 +6918,9 @@ fn to_cc_type<'a, 'ctx>(
         LayoutRepr::Struct(_) => {
             let stack_type = basic_type_from_layout(env, layout_interner, layout_repr);
 
-            if layout_repr.is_passed_by_reference(layout_interner) {
+            if layout_repr.is_passed_by_reference(layout_interner)
+                || win64_passes_by_reference(env, layout_interner, layout)
+            {
                 env.context.ptr_type(AddressSpace::default()).into()
             } else {
                 stack_type
@@ -6372,6 +6933,17 @@ fn to_cc_type<'a, 'ctx>(
     }
 }
 
+/// On Windows x64, a struct that is not 1, 2, 4 or 8 bytes is passed as a pointer to a copy that
+/// the caller makes. LLVM would otherwise split it over several registers.
+fn win64_passes_by_reference<'a>(
+    env: &Env<'a, '_, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    layout: InLayout<'a>,
+) -> bool {
+    matches!(env.target, Target::WinX64)
+        && !matches!(layout_interner.stack_size(layout), 0 | 1 | 2 | 4 | 8)
+}
+
 fn to_cc_type_builtin<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
     builtin: &Builtin<'a>,
@@ -6487,7 +7059,7 @@ impl<'ctx> FunctionSpec<'ctx> {
 
         Self {
             typ,
-            call_conv: C_CALL_CONV,
+            call_conv: env.host_call_conv(),
             cconv_stack_return_type: opt_sret_parameter,
         }
     }
@@ -6707,7 +7279,7 @@ fn build_foreign_symbol<'a, 'ctx>(
                 let call = env
                     .builder
                     .new_build_call(cc_function, &cc_arguments, "tmp");
-                call.set_call_convention(C_CALL_CONV);
+                call.set_call_convention(cc_function.get_call_conventions());
 
                 match roc_return {
                     RocReturn::Return => {
@@ -6837,6 +7409,51 @@ fn define_global_str_literal<'ctx>(
     }
 }
 
+/// The arm of a conditional branch that only leads to an error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ColdBranch {
+    Then,
+    Else,
+}
+
+/// How much more likely the other arm is than the one that leads to an error
+const HOT_BRANCH_WEIGHT: u64 = 2000;
+
+/// Like `new_build_conditional_branch`, for a check where one arm only leads to an error, such
+/// as an overflow or a failed expect. The branch gets `!prof` branch weights, so LLVM moves the
+/// error path out of the way of the hot one.
+pub(crate) fn build_error_check_branch<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    comparison: IntValue<'ctx>,
+    then_block: BasicBlock<'ctx>,
+    else_block: BasicBlock<'ctx>,
+    cold: ColdBranch,
+) -> InstructionValue<'ctx> {
+    let ctx = env.context;
+    let i32_type = ctx.i32_type();
+
+    let branch = env
+        .builder
+        .new_build_conditional_branch(comparison, then_block, else_block);
+
+    let (then_weight, else_weight) = match cold {
+        ColdBranch::Then => (1, HOT_BRANCH_WEIGHT),
+        ColdBranch::Else => (HOT_BRANCH_WEIGHT, 1),
+    };
+
+    let weights = ctx.metadata_node(&[
+        ctx.metadata_string("branch_weights").into(),
+        i32_type.const_int(then_weight, false).into(),
+        i32_type.const_int(else_weight, false).into(),
+    ]);
+
+    branch
+        .set_metadata(weights, ctx.get_kind_id("prof"))
+        .unwrap();
+
+    branch
+}
+
 pub(crate) fn throw_internal_exception(env: &Env<'_, '_, '_>, message: &str) {
     let builder = env.builder;
 
@@ -6847,6 +7464,89 @@ pub(crate) fn throw_internal_exception(env: &Env<'_, '_, '_>, message: &str) {
     builder.new_build_unreachable();
 }
 
+/// The function that is called when integer arithmetic overflows with `--overflow=trap`. It gets
+/// a `Str` that says which operation overflowed and where, passed the same way as to
+/// `roc_panic`, and never returns. A platform can define it to report overflows its own way; the
+/// weak definition we provide passes the message on to `roc_panic`.
+pub const OVERFLOW_TRAP: &str = "__roc_overflow_trap";
+
+/// Like [throw_internal_exception], but through [OVERFLOW_TRAP], and with the location of the
+/// procedure being built appended to the message.
+pub(crate) fn throw_overflow_trap(env: &Env<'_, '_, '_>, message: &str) {
+    let location = env
+        .debug_info
+        .and_then(|debug_info| debug_info.current_source_location());
+
+    let message = match location {
+        Some((path, LineColumn { line, column })) => {
+            format!(
+                "{} at {path}:{line}:{column}",
+                message.trim_end_matches('!')
+            )
+        }
+        None => message.to_string(),
+    };
+
+    let str = build_string_literal(env, &message);
+    let arg = env.string_to_arg(env, str);
+
+    let call =
+        env.builder
+            .new_build_call(overflow_trap_function(env), &[arg.into()], "overflow_trap");
+    call.set_call_convention(C_CALL_CONV);
+
+    env.builder.new_build_unreachable();
+}
+
+fn overflow_trap_function<'ctx>(env: &Env<'_, 'ctx, '_>) -> FunctionValue<'ctx> {
+    if let Some(function) = env.module.get_function(OVERFLOW_TRAP) {
+        return function;
+    }
+
+    let ptr_type = env.context.ptr_type(AddressSpace::default());
+    let fn_type = env.context.void_type().fn_type(&[ptr_type.into()], false);
+
+    let function = env
+        .module
+        .add_function(OVERFLOW_TRAP, fn_type, Some(Linkage::WeakAny));
+    function.set_call_conventions(C_CALL_CONV);
+
+    for name in ["cold", "noreturn"] {
+        let kind_id = Attribute::get_named_enum_kind_id(name);
+        debug_assert!(kind_id > 0);
+        let enum_attr = env.context.create_enum_attribute(kind_id, 0);
+        function.add_attribute(AttributeLoc::Function, enum_attr);
+    }
+
+    let block = env.builder.get_insert_block().expect("to be in a function");
+    let di_location = env.builder.get_current_debug_location();
+
+    // this function has no subprogram, so its instructions cannot have a debug location
+    env.builder.unset_current_debug_location();
+
+    let entry = env.context.append_basic_block(function, "entry");
+    env.builder.position_at_end(entry);
+
+    let arg = function.get_nth_param(0).unwrap();
+    let message = match env.target.ptr_width() {
+        PtrWidth::Bytes4 => {
+            env.builder
+                .new_build_load(zig_str_type(env), arg.into_pointer_value(), "message")
+        }
+        PtrWidth::Bytes8 => arg,
+    };
+
+    env.call_panic(env, message, CrashTag::Roc);
+    env.builder.new_build_unreachable();
+
+    env.builder.position_at_end(block);
+    if let Some(di_location) = di_location {
+        env.builder.set_current_debug_location(di_location);
+    }
+
+    function
+}
+
 pub(crate) fn throw_exception<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
     scope: &mut Scope<'a, 'ctx>,
@@ -0,0 +1,97 @@
+//! Control-flow integrity for calls through function pointers.
+//!
+//! Closures are usually called directly, because their lambda set says which functions they can
+//! be. An erased closure only carries a pointer to its function, and calling it is an indirect
+//! call, which could jump anywhere if that pointer was overwritten. With CFI, every function whose
+//! address is taken gets `!type` metadata that names its [LayoutRepr::FunctionPointer], and every
+//! indirect call first checks with `llvm.type.test` that its callee has the type it is called as.
+//! A failed check traps.
+//!
+//! The checks are lowered into jump tables by LLVM's `lowertypetests` pass, see
+//! `roc_build::llvm_passes::lower_type_tests`. Roc has no vtables, so there are no loads for
+//! `llvm.type.checked.load` to check.
+
+use inkwell::values::{FunctionValue, PointerValue};
+use inkwell::AddressSpace;
+use roc_mono::layout::{InLayout, LayoutInterner, LayoutRepr, STLayoutInterner};
+
+use super::build::{BuilderExt, Env};
+use super::intrinsics::LLVM_TRAP;
+use super::memcpy::get_or_add_function;
+
+static LLVM_TYPE_TEST: &str = "llvm.type.test";
+
+/// The CFI type identifier of a function pointer layout. Both the functions a pointer can point
+/// to and the calls through it have the same layout, so they get the same identifier.
+fn type_id<'a>(layout_interner: &STLayoutInterner<'a>, layout: InLayout<'a>) -> String {
+    debug_assert!(matches!(
+        layout_interner.get_repr(layout),
+        LayoutRepr::FunctionPointer(_)
+    ));
+
+    // `dbg_stable` follows the interned layouts, and leaves out the names of symbols
+    format!("roc.{:?}", layout_interner.dbg_stable(layout))
+}
+
+/// Mark `function` as a valid target of indirect calls through a function pointer of `layout`
+pub(crate) fn add_type_metadata<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    function: FunctionValue<'ctx>,
+    layout: InLayout<'a>,
+) {
+    let type_id = env
+        .context
+        .metadata_string(&type_id(layout_interner, layout));
+    let node = env
+        .context
+        .metadata_node(&[env.context.i64_type().const_zero().into(), type_id.into()]);
+
+    function
+        .as_global_value()
+        .set_metadata(node, env.context.get_kind_id("type"));
+}
+
+/// Trap unless `pointer` points to a function that was marked with the type of `layout`, at the
+/// builder's position.
+pub(crate) fn build_type_check<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    pointer: PointerValue<'ctx>,
+    layout: InLayout<'a>,
+) {
+    let context = env.context;
+    let builder = env.builder;
+
+    let type_test = get_or_add_function(
+        env,
+        LLVM_TYPE_TEST,
+        context.bool_type().fn_type(
+            &[
+                context.ptr_type(AddressSpace::default()).into(),
+                context.metadata_type().into(),
+            ],
+            false,
+        ),
+    );
+
+    let type_id = context.metadata_string(&type_id(layout_interner, layout));
+    let is_valid = builder
+        .new_build_call(type_test, &[pointer.into(), type_id.into()], "cfi_check")
+        .try_as_basic_value()
+        .left()
+        .unwrap()
+        .into_int_value();
+
+    let parent = builder.get_insert_block().unwrap().get_parent().unwrap();
+    let valid_block = context.append_basic_block(parent, "cfi_valid");
+    let invalid_block = context.append_basic_block(parent, "cfi_invalid");
+
+    builder.new_build_conditional_branch(is_valid, valid_block, invalid_block);
+
+    builder.position_at_end(invalid_block);
+    env.build_intrinsic_call(LLVM_TRAP, &[]);
+    builder.new_build_unreachable();
+
+    builder.position_at_end(valid_block);
+}
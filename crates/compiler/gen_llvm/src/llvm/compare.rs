@@ -17,6 +17,7 @@ use super::build::{create_entry_block_alloca, load_roc_value, BuilderExt};
 use super::convert::{argument_type_from_layout, argument_type_from_union_layout};
 use super::lowlevel::dec_binop_with_unchecked;
 use super::struct_;
+use super::tagged_pointer;
 
 pub fn generic_eq<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
@@ -1068,6 +1069,96 @@ fn build_tag_eq_help<'a, 'ctx>(
 
             env.builder.new_build_switch(id1, default, &cases);
         }
+        TaggedPointer { immediate_id, tags } => {
+            let ptr_equal = env.builder.new_build_int_compare(
+                IntPredicate::EQ,
+                env.builder
+                    .new_build_ptr_to_int(tag1.into_pointer_value(), env.ptr_int(), "pti"),
+                env.builder
+                    .new_build_ptr_to_int(tag2.into_pointer_value(), env.ptr_int(), "pti"),
+                "compare_pointers",
+            );
+
+            let check_for_immediate = ctx.append_basic_block(parent, "check_for_immediate");
+            let compare_tag_ids = ctx.append_basic_block(parent, "compare_tag_ids");
+
+            env.builder
+                .new_build_conditional_branch(ptr_equal, return_true, check_for_immediate);
+
+            // check for immediates
+
+            env.builder.position_at_end(check_for_immediate);
+
+            let is_immediate_1 = tagged_pointer::is_immediate(env, tag1.into_pointer_value());
+            let is_immediate_2 = tagged_pointer::is_immediate(env, tag2.into_pointer_value());
+
+            let either_immediate =
+                env.builder
+                    .new_build_or(is_immediate_1, is_immediate_2, "either_immediate");
+
+            // logic: an immediate is equal to another value only if their words are the same,
+            // and they are not
+            env.builder.new_build_conditional_branch(
+                either_immediate,
+                return_false,
+                compare_tag_ids,
+            );
+
+            env.builder.position_at_end(compare_tag_ids);
+
+            let id1 = get_tag_id(env, layout_interner, parent, union_layout, tag1);
+            let id2 = get_tag_id(env, layout_interner, parent, union_layout, tag2);
+
+            // clear the tag_id so we get a pointer to the actual data
+            let tag1 = tag_pointer_clear_tag_id(env, tag1.into_pointer_value());
+            let tag2 = tag_pointer_clear_tag_id(env, tag2.into_pointer_value());
+
+            let compare_tag_fields = ctx.append_basic_block(parent, "compare_tag_fields");
+
+            let same_tag =
+                env.builder
+                    .new_build_int_compare(IntPredicate::EQ, id1, id2, "compare_tag_id");
+
+            env.builder
+                .new_build_conditional_branch(same_tag, compare_tag_fields, return_false);
+
+            env.builder.position_at_end(compare_tag_fields);
+
+            // switch on all the tag ids but the immediate one
+
+            let mut cases = Vec::with_capacity_in(tags.len(), env.arena);
+
+            for (tag_id, field_layouts) in tags.iter().enumerate() {
+                if tag_id == *immediate_id as usize {
+                    continue;
+                }
+
+                let block = env.context.append_basic_block(parent, "tag_id_modify");
+                env.builder.position_at_end(block);
+
+                let struct_layout = LayoutRepr::struct_(field_layouts);
+
+                let answer = eq_ptr_to_struct(
+                    env,
+                    layout_interner,
+                    layout_ids,
+                    struct_layout,
+                    field_layouts,
+                    tag1,
+                    tag2,
+                );
+
+                env.builder.new_build_return(Some(&answer));
+
+                cases.push((id1.get_type().const_int(tag_id as u64, false), block));
+            }
+
+            env.builder.position_at_end(compare_tag_fields);
+
+            let default = cases.pop().unwrap().1;
+
+            env.builder.new_build_switch(id1, default, &cases);
+        }
         NullableUnwrapped { other_fields, .. } => {
             let ptr_equal = env.builder.new_build_int_compare(
                 IntPredicate::EQ,
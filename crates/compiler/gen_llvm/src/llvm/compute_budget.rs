@@ -0,0 +1,184 @@
+//! An estimate of the compute units a Solana program uses.
+//!
+//! The Solana runtime charges a compute unit for every SBF instruction a program executes, plus a
+//! fixed cost for every syscall, and aborts the transaction when the program runs out of its
+//! budget. This walks the LLVM IR of the functions that can be reached from the entry points and
+//! adds up roughly how many SBF instructions every LLVM instruction becomes.
+//!
+//! The estimate is static: every instruction is counted once, so a loop costs as much as a single
+//! iteration, and a function costs the same however often it is called. That makes it a measure
+//! of how much code a program runs through, which is good for finding the functions that are
+//! worth looking at, but it is not a prediction of what a transaction will be charged.
+
+use inkwell::module::{Linkage, Module};
+use inkwell::values::{BasicValueEnum, FunctionValue, InstructionOpcode, InstructionValue};
+use roc_collections::all::MutSet;
+
+/// Every syscall costs at least this much, whatever it does
+const SYSCALL_COST: u64 = 100;
+
+/// SBF has no floating point instructions, so these are calls into a software implementation
+const FLOAT_COST: u64 = 30;
+
+/// Multiplication and division of 128-bit integers are calls into compiler-rt
+const WIDE_ARITHMETIC_COST: u64 = 60;
+
+/// Other operations on 128-bit integers are done on both halves, with a carry in between
+const WIDE_INT_COST: u64 = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionCost {
+    pub name: String,
+    pub cost: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ComputeEstimate {
+    pub total: u64,
+    /// Every function that was counted, the most expensive first
+    pub functions: Vec<FunctionCost>,
+}
+
+/// The functions the host calls into. They are all called `roc__*`.
+pub fn host_exposed_functions<'ctx>(module: &Module<'ctx>) -> Vec<FunctionValue<'ctx>> {
+    module
+        .get_functions()
+        .filter(|function| {
+            function.get_linkage() == Linkage::External
+                && function.count_basic_blocks() > 0
+                && function.get_name().to_bytes().starts_with(b"roc__")
+        })
+        .collect()
+}
+
+/// Estimate the compute units of `entry_points` and of every function they (indirectly) call.
+pub fn estimate_compute_units<'ctx>(
+    module: &Module<'ctx>,
+    entry_points: &[FunctionValue<'ctx>],
+) -> ComputeEstimate {
+    let mut visited = MutSet::default();
+    let mut stack = entry_points.to_vec();
+    let mut functions = Vec::new();
+
+    while let Some(function) = stack.pop() {
+        let name = function.get_name().to_string_lossy().into_owned();
+
+        if !visited.insert(name.clone()) {
+            continue;
+        }
+
+        let mut cost = 0;
+
+        for block in function.get_basic_blocks() {
+            let mut next = block.get_first_instruction();
+
+            while let Some(instruction) = next {
+                next = instruction.get_next_instruction();
+
+                match called_function(module, instruction) {
+                    Some(callee) if callee.count_basic_blocks() > 0 => {
+                        cost += call_cost(instruction);
+                        stack.push(callee);
+                    }
+                    Some(callee) => cost += declaration_call_cost(callee, instruction),
+                    None => cost += instruction_cost(instruction),
+                }
+            }
+        }
+
+        functions.push(FunctionCost { name, cost });
+    }
+
+    functions.sort_by(|a, b| b.cost.cmp(&a.cost).then_with(|| a.name.cmp(&b.name)));
+
+    ComputeEstimate {
+        total: functions.iter().map(|function| function.cost).sum(),
+        functions,
+    }
+}
+
+/// The function a call instruction calls directly, if it is one
+pub(crate) fn called_function<'ctx>(
+    module: &Module<'ctx>,
+    instruction: InstructionValue<'ctx>,
+) -> Option<FunctionValue<'ctx>> {
+    if !matches!(
+        instruction.get_opcode(),
+        InstructionOpcode::Call | InstructionOpcode::Invoke
+    ) {
+        return None;
+    }
+
+    // the callee is the last operand
+    let callee = instruction
+        .get_operand(instruction.get_num_operands().checked_sub(1)?)?
+        .left()?;
+
+    match callee {
+        // an indirect call goes through a pointer that was loaded or computed, not a constant
+        BasicValueEnum::PointerValue(pointer) if pointer.is_const() => {
+            module.get_function(pointer.get_name().to_str().ok()?)
+        }
+        _ => None,
+    }
+}
+
+/// Moving the arguments into registers, and the call itself
+fn call_cost(instruction: InstructionValue<'_>) -> u64 {
+    instruction.get_num_operands() as u64
+}
+
+/// The cost of calling a function that is not defined in this module: a syscall, an LLVM
+/// intrinsic, or a function of the host
+fn declaration_call_cost(callee: FunctionValue<'_>, instruction: InstructionValue<'_>) -> u64 {
+    let name = callee.get_name().to_bytes();
+
+    if name.starts_with(b"llvm.dbg.") || name.starts_with(b"llvm.lifetime.") {
+        // only there for the optimizer and the debugger
+        0
+    } else if name.starts_with(b"sol_") {
+        SYSCALL_COST + call_cost(instruction)
+    } else if !name.starts_with(b"llvm.") {
+        call_cost(instruction)
+    } else if !has_wide_operand(instruction) {
+        // intrinsics become instructions
+        1
+    } else if name.starts_with(b"llvm.smul") || name.starts_with(b"llvm.umul") {
+        // or calls into compiler-rt, to multiply 128-bit integers
+        WIDE_ARITHMETIC_COST
+    } else {
+        WIDE_INT_COST
+    }
+}
+
+fn instruction_cost(instruction: InstructionValue<'_>) -> u64 {
+    use InstructionOpcode::*;
+
+    match instruction.get_opcode() {
+        // stack slots are reserved in the prologue, phis become moves that are usually
+        // coalesced away, and pointers are integers
+        Alloca | Phi | BitCast | PtrToInt | IntToPtr | AddrSpaceCast | Freeze | Unreachable => 0,
+        FAdd | FSub | FMul | FDiv | FRem | FNeg | FCmp | FPToSI | FPToUI | SIToFP | UIToFP
+        | FPExt | FPTrunc => FLOAT_COST,
+        Mul | UDiv | SDiv | URem | SRem if has_wide_operand(instruction) => WIDE_ARITHMETIC_COST,
+        // SBF has no jump tables, so a switch is a compare and a branch for every case. The
+        // operands are the condition and the default block, then a value and a block per case.
+        Switch => {
+            let cases = instruction.get_num_operands().saturating_sub(2) as u64 / 2;
+            2 * cases + 1
+        }
+        _ if has_wide_operand(instruction) => WIDE_INT_COST,
+        _ => 1,
+    }
+}
+
+/// Whether the first operand is an integer that does not fit in a 64-bit register
+fn has_wide_operand(instruction: InstructionValue<'_>) -> bool {
+    match instruction
+        .get_operand(0)
+        .and_then(|operand| operand.left())
+    {
+        Some(BasicValueEnum::IntValue(value)) => value.get_type().get_bit_width() > 64,
+        _ => false,
+    }
+}
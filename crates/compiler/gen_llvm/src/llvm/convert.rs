@@ -84,6 +84,7 @@ pub fn struct_type_from_union_layout<'a, 'ctx>(
             RocUnion::tagged_from_slices(layout_interner, env.context, tags).struct_type()
         }
         Recursive(tags)
+        | TaggedPointer { tags, .. }
         | NullableWrapped {
             other_tags: tags, ..
         } => {
@@ -117,7 +118,8 @@ fn basic_type_from_union_layout<'a, 'ctx>(
         Recursive(_)
         | NonNullableUnwrapped(_)
         | NullableWrapped { .. }
-        | NullableUnwrapped { .. } => env.context.ptr_type(AddressSpace::default()).into(),
+        | NullableUnwrapped { .. }
+        | TaggedPointer { .. } => env.context.ptr_type(AddressSpace::default()).into(),
     }
 }
 
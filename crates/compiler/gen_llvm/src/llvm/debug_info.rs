@@ -0,0 +1,354 @@
+//! DWARF debug information that points back to the Roc source.
+//!
+//! Without this, every function gets a placeholder subprogram in a file called `roc_app`, which
+//! is just enough to keep LLVM happy when inlining. With it, procedures get a subprogram in the
+//! `.roc` file of their module, at the line where they are defined, and their parameters and
+//! let-bound names show up as variables in a debugger.
+//!
+//! The mono IR does not keep a region per statement, so locations have the granularity of a
+//! top-level definition: every instruction of a procedure is attributed to its first line.
+
+use inkwell::debug_info::{
+    AsDIScope, DIFile, DIFlags, DIFlagsConstants, DISubprogram, DIType,
+    DebugInfoBuilder as DIBuilder,
+};
+use inkwell::values::{BasicValue, BasicValueEnum, FunctionValue, InstructionOpcode};
+use roc_builtins::bitcode::IntWidth;
+use roc_collections::all::MutMap;
+use roc_module::symbol::{ModuleId, Symbol};
+use roc_mono::layout::{Builtin, InLayout, LayoutInterner, LayoutRepr, STLayoutInterner};
+use roc_region::all::{LineColumn, LineInfo, Region};
+use std::cell::Cell;
+use std::path::PathBuf;
+
+use super::build::Env;
+
+// DWARF base type encodings, see section 7.8 of the DWARF 5 standard
+const DW_ATE_BOOLEAN: u32 = 0x02;
+const DW_ATE_FLOAT: u32 = 0x04;
+const DW_ATE_SIGNED: u32 = 0x05;
+const DW_ATE_UNSIGNED: u32 = 0x08;
+
+struct SourceFile<'ctx> {
+    file: DIFile<'ctx>,
+    path: String,
+    source: Box<str>,
+    line_info: LineInfo,
+}
+
+/// Maps Roc symbols and regions to DWARF metadata. Lives next to the [DIBuilder] in [Env], and is
+/// only created when source-level debug info is requested.
+pub struct DebugInfoBuilder<'ctx> {
+    files: MutMap<ModuleId, SourceFile<'ctx>>,
+    symbol_regions: MutMap<Symbol, Region>,
+    /// The procedure whose body is being built
+    current_proc: Cell<Option<Symbol>>,
+}
+
+impl<'ctx> DebugInfoBuilder<'ctx> {
+    pub fn new(
+        dibuilder: &DIBuilder<'ctx>,
+        sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+        symbol_regions: MutMap<Symbol, Region>,
+    ) -> Self {
+        let files = sources
+            .iter()
+            .map(|(module_id, (path, src))| {
+                let filename = path.file_name().unwrap_or_default().to_string_lossy();
+                let directory = match path.parent() {
+                    Some(parent) if !parent.as_os_str().is_empty() => parent.to_string_lossy(),
+                    _ => ".".into(),
+                };
+
+                let source_file = SourceFile {
+                    file: dibuilder.create_file(&filename, &directory),
+                    path: path.to_string_lossy().into_owned(),
+                    source: src.clone(),
+                    line_info: LineInfo::new(src),
+                };
+
+                (*module_id, source_file)
+            })
+            .collect();
+
+        Self {
+            files,
+            symbol_regions,
+            current_proc: Cell::new(None),
+        }
+    }
+
+    /// The file a symbol was defined in; builtins and generated code fall back to the file of
+    /// the compile unit.
+    fn file(&self, env: &Env<'_, 'ctx, '_>, symbol: Symbol) -> DIFile<'ctx> {
+        match self.files.get(&symbol.module_id()) {
+            Some(source_file) => source_file.file,
+            None => env.compile_unit.get_file(),
+        }
+    }
+
+    /// The 1-based line and column of a region in the given module, as DWARF expects them.
+    /// Line 0 means "no source location".
+    pub fn line_column(&self, module_id: ModuleId, region: Region) -> LineColumn {
+        match self.files.get(&module_id) {
+            Some(source_file) => {
+                let start = source_file.line_info.convert_region(region).start;
+
+                LineColumn {
+                    line: start.line + 1,
+                    column: start.column + 1,
+                }
+            }
+            None => LineColumn { line: 0, column: 0 },
+        }
+    }
+
+    fn symbol_line_column(&self, symbol: Symbol) -> LineColumn {
+        match self.symbol_regions.get(&symbol) {
+            Some(region) => self.line_column(symbol.module_id(), *region),
+            None => LineColumn { line: 0, column: 0 },
+        }
+    }
+
+    /// Like [Env::new_subprogram], but in the source file and at the line of `symbol`.
+    pub fn new_subprogram(
+        &self,
+        env: &Env<'_, 'ctx, '_>,
+        symbol: Symbol,
+        function_name: &str,
+    ) -> DISubprogram<'ctx> {
+        let file = self.file(env, symbol);
+        let LineColumn { line, .. } = self.symbol_line_column(symbol);
+
+        let subroutine_type = env.dibuilder.create_subroutine_type(
+            file,
+            /* return type */ None,
+            /* parameter types */ &[],
+            DIFlags::PUBLIC,
+        );
+
+        env.dibuilder.create_function(
+            /* scope */ file.as_debug_info_scope(),
+            /* func name */ symbol.as_str(&env.interns),
+            /* linkage_name */ Some(function_name),
+            /* file */ file,
+            /* line_no */ line,
+            /* DIType */ subroutine_type,
+            /* is_local_to_unit */ true,
+            /* is_definition */ true,
+            /* scope_line */ line,
+            /* flags */ DIFlags::PUBLIC,
+            /* is_optimized */ false,
+        )
+    }
+
+    /// Attribute the instructions that are built from now on to the definition of `symbol`.
+    /// Must be called with the builder positioned in `function`.
+    pub fn set_proc_location(
+        &self,
+        env: &Env<'_, 'ctx, '_>,
+        symbol: Symbol,
+        function: FunctionValue<'ctx>,
+    ) {
+        let subprogram = function.get_subprogram().expect("subprogram");
+        let LineColumn { line, column } = self.symbol_line_column(symbol);
+
+        let location = env.dibuilder.create_debug_location(
+            env.context,
+            line,
+            column,
+            subprogram.as_debug_info_scope(),
+            /* inlined_at */ None,
+        );
+
+        env.builder.set_current_debug_location(location);
+        self.current_proc.set(Some(symbol));
+    }
+
+    /// The source file and position of the procedure whose body is being built, if it is known.
+    pub fn current_source_location(&self) -> Option<(&str, LineColumn)> {
+        let symbol = self.current_proc.get()?;
+        let source_file = self.files.get(&symbol.module_id())?;
+
+        Some((&source_file.path, self.symbol_line_column(symbol)))
+    }
+
+    /// The file, position and source code of a region in the module of the procedure whose body
+    /// is being built, if it is known.
+    pub fn current_region_source(&self, region: Region) -> Option<(&str, LineColumn, &str)> {
+        let symbol = self.current_proc.get()?;
+        let source_file = self.files.get(&symbol.module_id())?;
+
+        let start = region.start().offset as usize;
+        let end = region.end().offset as usize;
+        let text = source_file.source.get(start..end)?;
+
+        let line_column = self.line_column(symbol.module_id(), region);
+
+        Some((&source_file.path, line_column, text))
+    }
+
+    /// Describe a let-bound value as a local variable, so debuggers can print it.
+    ///
+    /// The `llvm.dbg.value` is placed right after the instruction that produced the value, which
+    /// must therefore already be followed by another instruction.
+    pub fn declare_local<'a>(
+        &self,
+        env: &Env<'a, 'ctx, '_>,
+        layout_interner: &STLayoutInterner<'a>,
+        symbol: Symbol,
+        layout: InLayout<'a>,
+        value: BasicValueEnum<'ctx>,
+    ) {
+        if symbol.is_generated(&env.interns) {
+            return;
+        }
+
+        let Some(instruction) = value.as_instruction_value() else {
+            return;
+        };
+
+        let Some(next) = instruction.get_next_instruction() else {
+            return;
+        };
+
+        // nothing may be inserted between phi nodes
+        if next.get_opcode() == InstructionOpcode::Phi {
+            return;
+        }
+
+        let Some(location) = env.builder.get_current_debug_location() else {
+            return;
+        };
+
+        let Some(ty) = self.value_type(env, layout_interner, layout, value) else {
+            return;
+        };
+
+        // local names have no region of their own, so they get the line of their procedure
+        let variable = env.dibuilder.create_auto_variable(
+            location.get_scope(),
+            symbol.as_str(&env.interns),
+            self.file(env, symbol),
+            location.get_line(),
+            ty,
+            /* always_preserve */ true,
+            DIFlags::ZERO,
+            /* align_in_bits */ layout_interner.alignment_bytes(layout) * 8,
+        );
+
+        env.dibuilder
+            .insert_dbg_value_before(value, variable, None, location, next);
+    }
+
+    /// Describe a parameter of the procedure `proc_symbol` as a formal parameter, so debuggers
+    /// show it in backtraces. `arg_no` is 1-based.
+    ///
+    /// Must be called once the body is built: the `llvm.dbg.value` goes before the first
+    /// instruction of the entry block.
+    #[allow(clippy::too_many_arguments)]
+    pub fn declare_parameter<'a>(
+        &self,
+        env: &Env<'a, 'ctx, '_>,
+        layout_interner: &STLayoutInterner<'a>,
+        proc_symbol: Symbol,
+        function: FunctionValue<'ctx>,
+        arg_no: u32,
+        symbol: Symbol,
+        layout: InLayout<'a>,
+        value: BasicValueEnum<'ctx>,
+    ) {
+        if symbol.is_generated(&env.interns) {
+            return;
+        }
+
+        let Some(first) = function
+            .get_first_basic_block()
+            .and_then(|entry| entry.get_first_instruction())
+        else {
+            return;
+        };
+
+        let Some(ty) = self.value_type(env, layout_interner, layout, value) else {
+            return;
+        };
+
+        let scope = function
+            .get_subprogram()
+            .expect("subprogram")
+            .as_debug_info_scope();
+        let LineColumn { line, column } = self.symbol_line_column(proc_symbol);
+
+        let variable = env.dibuilder.create_parameter_variable(
+            scope,
+            symbol.as_str(&env.interns),
+            arg_no,
+            self.file(env, proc_symbol),
+            line,
+            ty,
+            /* always_preserve */ true,
+            DIFlags::ZERO,
+        );
+
+        let location = env.dibuilder.create_debug_location(
+            env.context,
+            line,
+            column,
+            scope,
+            /* inlined_at */ None,
+        );
+
+        env.dibuilder
+            .insert_dbg_value_before(value, variable, None, location, first);
+    }
+
+    /// A DWARF base type for the value of a let binding. Values that are passed by reference
+    /// are described as an address.
+    fn value_type<'a>(
+        &self,
+        env: &Env<'a, 'ctx, '_>,
+        layout_interner: &STLayoutInterner<'a>,
+        layout: InLayout<'a>,
+        value: BasicValueEnum<'ctx>,
+    ) -> Option<DIType<'ctx>> {
+        let (name, size, encoding) = if value.is_pointer_value() {
+            ("ptr", env.target.ptr_width() as u32, DW_ATE_UNSIGNED)
+        } else {
+            match layout_interner.get_repr(layout) {
+                LayoutRepr::Builtin(Builtin::Bool) => ("Bool", 1, DW_ATE_BOOLEAN),
+                LayoutRepr::Builtin(Builtin::Int(int_width)) => {
+                    let encoding = if int_width.is_signed() {
+                        DW_ATE_SIGNED
+                    } else {
+                        DW_ATE_UNSIGNED
+                    };
+
+                    (int_width.type_name(), int_width.stack_size(), encoding)
+                }
+                LayoutRepr::Builtin(Builtin::Float(float_width)) => (
+                    float_width.type_name(),
+                    float_width.stack_size(),
+                    DW_ATE_FLOAT,
+                ),
+                LayoutRepr::Builtin(Builtin::Decimal) => {
+                    ("Dec", IntWidth::I128.stack_size(), DW_ATE_SIGNED)
+                }
+                other => (
+                    "roc_value",
+                    other.stack_size(layout_interner),
+                    DW_ATE_UNSIGNED,
+                ),
+            }
+        };
+
+        if size == 0 {
+            // zero-sized values have nothing to inspect
+            return None;
+        }
+
+        env.dibuilder
+            .create_basic_type(name, size as u64 * 8, encoding, DIFlags::PUBLIC)
+            .ok()
+            .map(|basic_type| basic_type.as_type())
+    }
+}
@@ -0,0 +1,151 @@
+//! Decrement several lists or strings with one call, when they are dropped one after the other.
+//!
+//! The end of a procedure often drops a few values in a row, and every decrement is a call to
+//! its own `decrement_list` or `decrement_str` function that frees the allocation when it was
+//! unique. A run of `Dec` statements for lists whose elements are not refcounted (and that have
+//! the same element alignment), or for strings, becomes a single call to `list.decref_batch` or
+//! `str.decref_batch` instead. Those decrement every refcount and free the unique allocations
+//! together, see `DecrefBatch` in utils.zig. Decrementing these values never decrements anything
+//! else, so doing them together does not change what gets freed.
+
+use inkwell::types::BasicType;
+use inkwell::values::BasicValueEnum;
+use roc_builtins::bitcode;
+use roc_module::symbol::Symbol;
+use roc_mono::ir::{ModifyRc, Stmt};
+use roc_mono::layout::{Builtin, LayoutInterner, LayoutRepr, STLayoutInterner};
+
+use super::bitcode::call_void_bitcode_fn;
+use super::build::{create_entry_block_alloca, BuilderExt, Env};
+use super::convert::{zig_list_type, zig_str_type};
+use super::scope::Scope;
+
+/// Shorter runs are decremented one at a time
+const MIN_BATCH_LEN: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BatchKind {
+    Str,
+    /// A list whose elements are not refcounted, with the alignment of its elements
+    List {
+        alignment: u32,
+    },
+}
+
+fn batch_kind<'a>(
+    layout_interner: &STLayoutInterner<'a>,
+    scope: &Scope<'a, '_>,
+    symbol: &Symbol,
+) -> Option<BatchKind> {
+    // a list on the stack is never decremented
+    if scope.is_stack_list(symbol) {
+        return None;
+    }
+
+    let (_, layout) = scope.load_symbol_and_layout(symbol);
+
+    match layout_interner.runtime_representation(layout) {
+        LayoutRepr::Builtin(Builtin::Str) => Some(BatchKind::Str),
+        LayoutRepr::Builtin(Builtin::List(element_layout))
+            if !layout_interner.contains_refcounted(element_layout) =>
+        {
+            Some(BatchKind::List {
+                alignment: layout_interner.alignment_bytes(element_layout),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Decrement the run of `Dec` statements that `stmt` starts with in one call, if it is long
+/// enough, and give back the statement after the run
+pub(crate) fn build_decref_batch<'a, 'ctx, 's>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    scope: &Scope<'a, 'ctx>,
+    stmt: &'s Stmt<'a>,
+) -> Option<&'s Stmt<'a>> {
+    let Stmt::Refcounting(ModifyRc::Dec(first), _) = stmt else {
+        return None;
+    };
+
+    let kind = batch_kind(layout_interner, scope, first)?;
+
+    let mut symbols = Vec::new();
+    let mut rest = stmt;
+
+    while let Stmt::Refcounting(ModifyRc::Dec(symbol), cont) = rest {
+        if batch_kind(layout_interner, scope, symbol) != Some(kind) {
+            break;
+        }
+
+        symbols.push(*symbol);
+        rest = cont;
+    }
+
+    if symbols.len() < MIN_BATCH_LEN {
+        return None;
+    }
+
+    let zig_type = match kind {
+        BatchKind::Str => zig_str_type(env),
+        BatchKind::List { .. } => zig_list_type(env),
+    };
+
+    let values: Vec<BasicValueEnum<'ctx>> = symbols
+        .iter()
+        .map(|symbol| {
+            let value = scope.load_symbol(symbol);
+
+            // a string can be passed by reference
+            if value.is_pointer_value() {
+                env.builder
+                    .new_build_load(zig_type, value.into_pointer_value(), "load_to_batch")
+            } else {
+                value
+            }
+        })
+        .collect();
+
+    let value_type = values[0].get_type();
+    let array_type = value_type.array_type(values.len() as u32);
+    let array = create_entry_block_alloca(env, array_type, "decref_batch");
+
+    for (index, value) in values.iter().enumerate() {
+        let element = unsafe {
+            env.builder.new_build_in_bounds_gep(
+                value_type,
+                array,
+                &[env.ptr_int().const_int(index as u64, false)],
+                "batch_element",
+            )
+        };
+
+        env.builder.new_build_store(element, *value);
+    }
+
+    let count = env.ptr_int().const_int(values.len() as u64, false);
+
+    match kind {
+        BatchKind::Str => {
+            call_void_bitcode_fn(
+                env,
+                &[array.into(), count.into()],
+                bitcode::STR_DECREF_BATCH,
+            );
+        }
+        BatchKind::List { alignment } => {
+            call_void_bitcode_fn(
+                env,
+                &[
+                    array.into(),
+                    count.into(),
+                    env.alignment_const(alignment).into(),
+                ],
+                bitcode::LIST_DECREF_BATCH,
+            );
+        }
+    }
+
+    Some(rest)
+}
@@ -0,0 +1,120 @@
+//! Merge procedures that compile to identical code.
+//!
+//! Specializations are already keyed by their (interned) layout in mono, so a single function is
+//! never specialized twice at the same layout. But distinct functions often end up with the same
+//! body once types are erased: an opaque newtype has the layout of the value that it wraps, so
+//! e.g. `double : Meters -> Meters` and `double : Feet -> Feet` become the same machine code.
+//!
+//! This pass finds such functions after code generation, keeps one of them, and points every use
+//! of the others at it.
+
+use inkwell::module::Linkage;
+use inkwell::values::{AnyValue, FunctionValue};
+use roc_collections::all::MutMap;
+
+use super::build::{Env, FAST_CALL_CONV};
+
+/// Merge identical Roc procedures until no more merges are possible, and return the number of
+/// functions that were removed.
+///
+/// Nothing is merged when source-level debug info is emitted, because a merged function can only
+/// point back to one of the definitions it came from.
+pub fn merge_identical_functions(env: &Env<'_, '_, '_>) -> usize {
+    if env.debug_info.is_some() {
+        return 0;
+    }
+
+    let mut merged = 0;
+
+    loop {
+        // merging functions can make their callers identical too
+        let merged_this_round = merge_round(env);
+
+        if merged_this_round == 0 {
+            return merged;
+        }
+
+        merged += merged_this_round;
+    }
+}
+
+fn merge_round(env: &Env<'_, '_, '_>) -> usize {
+    let mut by_body: MutMap<String, Vec<FunctionValue<'_>>> = MutMap::default();
+
+    for function in env.module.get_functions() {
+        if is_candidate(function) {
+            by_body
+                .entry(structural_key(function))
+                .or_default()
+                .push(function);
+        }
+    }
+
+    let mut merged = 0;
+
+    for mut functions in by_body.into_values() {
+        if functions.len() < 2 {
+            continue;
+        }
+
+        // pick a canonical function that does not depend on hash map order
+        functions.sort_by(|a, b| a.get_name().cmp(b.get_name()));
+
+        let (canonical, duplicates) = functions.split_first().unwrap();
+        let canonical_ptr = canonical.as_global_value().as_pointer_value();
+
+        for duplicate in duplicates {
+            duplicate
+                .as_global_value()
+                .as_pointer_value()
+                .replace_all_uses_with(canonical_ptr);
+
+            unsafe { duplicate.delete() };
+
+            merged += 1;
+        }
+    }
+
+    merged
+}
+
+/// Only Roc procedures are merged: anything that the host or the builtins can see by name must
+/// stay where it is.
+fn is_candidate(function: FunctionValue<'_>) -> bool {
+    function.count_basic_blocks() > 0
+        && function.get_linkage() == Linkage::Internal
+        && function.get_call_conventions() == FAST_CALL_CONV
+}
+
+/// The printed IR of a function, without anything that differs between otherwise identical
+/// functions: their own name, and their (placeholder) debug locations.
+fn structural_key(function: FunctionValue<'_>) -> String {
+    let name = function.get_name().to_string_lossy();
+    let printed = function.print_to_string().to_string();
+
+    // the name also shows up in recursive calls, so the key is about the shape of the recursion
+    let printed = printed
+        .replace(&format!("@{name}("), "@roc_self(")
+        .replace(&format!("@\"{name}\"("), "@roc_self(");
+
+    strip_debug_locations(&printed)
+}
+
+fn strip_debug_locations(printed: &str) -> String {
+    const DBG: &str = "!dbg !";
+
+    let mut key = String::with_capacity(printed.len());
+    let mut rest = printed;
+
+    while let Some(start) = rest.find(DBG) {
+        let before = rest[..start].trim_end_matches(' ').trim_end_matches(',');
+        key.push_str(before);
+
+        let after = &rest[start + DBG.len()..];
+        rest = after.trim_start_matches(|c: char| c.is_ascii_digit());
+    }
+
+    key.push_str(rest);
+
+    key
+}
@@ -5,6 +5,7 @@ use crate::llvm::build::{
 };
 use crate::llvm::build_list::{self, incrementing_elem_loop};
 use crate::llvm::convert::{basic_type_from_layout, RocUnion};
+use crate::llvm::memcpy::build_memcpy_raw;
 use inkwell::builder::Builder;
 use inkwell::module::Linkage;
 use inkwell::types::{BasicMetadataTypeEnum, BasicType, BasicTypeEnum};
@@ -24,6 +25,7 @@ use super::build::{add_func, FunctionSpec, LlvmBackendMode};
 use super::convert::struct_type_from_union_layout;
 use super::scope::Scope;
 use super::struct_::RocStruct;
+use super::tagged_pointer;
 
 pub(crate) struct SharedMemoryPointer<'ctx>(PointerValue<'ctx>);
 
@@ -846,6 +848,84 @@ fn build_clone_tag_help<'a, 'ctx>(
                 env.builder.new_build_return(Some(&extra_offset));
             }
         }
+        TaggedPointer { immediate_id, tags } => {
+            let switch_block = env.context.append_basic_block(parent, "switch_block");
+            let immediate_block = env.context.append_basic_block(parent, "immediate_block");
+
+            let id = get_tag_id(env, layout_interner, parent, &union_layout, tag_value);
+
+            let comparison = tagged_pointer::is_immediate(env, tag_value.into_pointer_value());
+
+            env.builder
+                .new_build_conditional_branch(comparison, immediate_block, switch_block);
+
+            {
+                let mut cases = Vec::with_capacity_in(tags.len(), env.arena);
+
+                for (i, fields) in tags.iter().enumerate() {
+                    if i == immediate_id as usize {
+                        continue;
+                    }
+
+                    let block = env.context.append_basic_block(parent, "tag_id_modify");
+                    env.builder.position_at_end(block);
+
+                    // write the "pointer" of the current offset
+                    write_pointer_with_tag_id(env, ptr, offset, extra_offset, union_layout, i);
+
+                    let layout = LayoutRepr::struct_(fields);
+                    let basic_type = basic_type_from_layout(env, layout_interner, layout);
+
+                    let (width, _) = union_layout.data_size_and_alignment(layout_interner);
+
+                    let cursors = Cursors {
+                        offset: extra_offset,
+                        extra_offset: env.builder.new_build_int_add(
+                            extra_offset,
+                            env.ptr_int().const_int(width as _, false),
+                            "new_offset",
+                        ),
+                    };
+
+                    let tag_value = tag_pointer_clear_tag_id(env, tag_value.into_pointer_value());
+                    let data =
+                        load_tag_data(env, layout_interner, union_layout, tag_value, basic_type);
+
+                    let answer =
+                        build_clone(env, layout_interner, layout_ids, ptr, cursors, data, layout);
+
+                    env.builder.new_build_return(Some(&answer));
+
+                    cases.push((id.get_type().const_int(i as u64, false), block));
+                }
+
+                env.builder.position_at_end(switch_block);
+
+                match cases.pop() {
+                    Some((_, default)) => {
+                        env.builder.new_build_switch(id, default, &cases);
+                    }
+                    None => {
+                        // only the immediate tag is left; this code is effectively unreachable
+                        env.builder.new_build_unreachable();
+                    }
+                }
+            }
+
+            {
+                // an immediate is copied as it is, it does not point into the heap
+                env.builder.position_at_end(immediate_block);
+
+                let value = env.builder.new_build_ptr_to_int(
+                    tag_value.into_pointer_value(),
+                    env.ptr_int(),
+                    "immediate_to_int",
+                );
+                build_copy(env, ptr, offset, value.into());
+
+                env.builder.new_build_return(Some(&extra_offset));
+            }
+        }
         NullableUnwrapped { other_fields, .. } => {
             let other_block = env.context.append_basic_block(parent, "other_block");
             let null_block = env.context.append_basic_block(parent, "null_block");
@@ -1016,7 +1096,7 @@ fn build_clone_builtin<'a, 'ctx>(
                     env.context.ptr_type(AddressSpace::default()),
                     "to_bytes_pointer",
                 );
-                bd.build_memcpy(dest, 1, src, 1, elements_width).unwrap();
+                build_memcpy_raw(env, dest, 1, src, 1, elements_width);
 
                 bd.new_build_int_add(elements_start_offset, elements_width, "new_offset")
             } else {
@@ -1,7 +1,7 @@
 use crate::debug_info_init;
 use crate::llvm::bitcode::call_void_bitcode_fn;
 use crate::llvm::build::{add_func, get_panic_msg_ptr, get_panic_tag_ptr, BuilderExt, C_CALL_CONV};
-use crate::llvm::build::{CCReturn, Env, FunctionSpec};
+use crate::llvm::build::{CCReturn, Env, FunctionSpec, PanicStrategy};
 use crate::llvm::convert::zig_str_type;
 use inkwell::module::Linkage;
 use inkwell::types::BasicType;
@@ -10,6 +10,7 @@ use roc_builtins::bitcode;
 
 use super::build::get_sjlj_buffer;
 use super::intrinsics::LLVM_LONGJMP;
+use super::sbf::add_sol_panic_roc_panic;
 
 /// Define functions for roc_alloc, roc_realloc, and roc_dealloc
 /// which use libc implementations (malloc, realloc, and free)
@@ -36,6 +37,11 @@ pub fn add_default_roc_externs(env: &Env<'_, '_, '_>) {
         }
     }
 
+    if env.mode.has_host() && env.panic_strategy == PanicStrategy::SolPanic {
+        // the builtins still call roc_panic, which a minimal Solana host does not define
+        add_sol_panic_roc_panic(env);
+    }
+
     if !env.mode.has_host() {
         // roc_alloc
         {
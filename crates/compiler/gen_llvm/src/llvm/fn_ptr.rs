@@ -10,6 +10,7 @@ use super::{
     build::{
         function_value_by_func_spec, BuilderExt, Env, FuncBorrowSpec, FunctionSpec, RocReturn,
     },
+    cfi,
     convert::{argument_type_from_layout, basic_type_from_layout},
 };
 
@@ -33,9 +34,19 @@ pub fn function_type<'a, 'ctx>(
     fn_spec.typ
 }
 
-pub fn build<'a, 'ctx>(env: &Env<'a, 'ctx, '_>, lambda_name: LambdaName<'a>) -> PointerValue<'ctx> {
+pub fn build<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    lambda_name: LambdaName<'a>,
+    layout: InLayout<'a>,
+) -> PointerValue<'ctx> {
     let func_value: FunctionValue<'ctx> =
         function_value_by_func_spec(env, FuncBorrowSpec::Erased, lambda_name.name());
+
+    if env.cfi {
+        cfi::add_type_metadata(env, layout_interner, func_value, layout);
+    }
+
     func_value.as_global_value().as_pointer_value()
 }
 
@@ -0,0 +1,74 @@
+//! Inlining hints for the functions we generate.
+//!
+//! LLVM's default inline threshold is tuned for C-like code. For Roc it is often too conservative
+//! about tiny builtins like `Result.is_ok`, which are just a tag id comparison once specialized,
+//! and too eager about large builtins like `Dict.insert`, which are called from many places and
+//! blow up code size when they are inlined into each of them. That matters most on SBF, where
+//! programs have a size limit.
+
+use inkwell::attributes::{Attribute, AttributeLoc};
+use inkwell::values::FunctionValue;
+use roc_module::symbol::Symbol;
+
+use super::build::Env;
+
+/// The function attribute that overrides LLVM's inline threshold for calls to a function
+const INLINE_THRESHOLD_ATTRIBUTE: &str = "function-inline-threshold";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InlineHint {
+    /// Like `#[inline(always)]`: inline at every call site, even without optimizations
+    Always,
+    /// Like `#[inline(never)]`: never inline, no matter the threshold
+    Never,
+}
+
+/// The hint for a builtin that is defined in Roc. Everything else is left to the inliner.
+pub fn builtin_inline_hint(symbol: Symbol) -> Option<InlineHint> {
+    match symbol {
+        // a single tag id comparison or field read after specialization
+        Symbol::RESULT_IS_OK
+        | Symbol::RESULT_IS_ERR
+        | Symbol::RESULT_WITH_DEFAULT
+        | Symbol::LIST_IS_EMPTY => Some(InlineHint::Always),
+
+        // large, and used all over the place through the other `Dict` functions
+        Symbol::DICT_INSERT => Some(InlineHint::Never),
+
+        _ => None,
+    }
+}
+
+/// Add the inlining attributes of the Roc procedure `symbol` to its definition.
+///
+/// A hint wins over the threshold, because LLVM ignores the threshold for functions that are
+/// `alwaysinline` or `noinline`.
+pub(crate) fn add_inline_attributes(
+    env: &Env<'_, '_, '_>,
+    function: FunctionValue<'_>,
+    symbol: Symbol,
+) {
+    match builtin_inline_hint(symbol) {
+        Some(InlineHint::Always) => add_enum_attribute(env, function, "alwaysinline"),
+        Some(InlineHint::Never) => add_enum_attribute(env, function, "noinline"),
+        None => {
+            if let Some(threshold) = env.inline_threshold {
+                let attribute = env
+                    .context
+                    .create_string_attribute(INLINE_THRESHOLD_ATTRIBUTE, &threshold.to_string());
+
+                function.add_attribute(AttributeLoc::Function, attribute);
+            }
+        }
+    }
+}
+
+fn add_enum_attribute(env: &Env<'_, '_, '_>, function: FunctionValue<'_>, name: &str) {
+    let kind_id = Attribute::get_named_enum_kind_id(name);
+    debug_assert!(kind_id > 0);
+
+    function.add_attribute(
+        AttributeLoc::Function,
+        env.context.create_enum_attribute(kind_id, 0),
+    );
+}
@@ -107,6 +107,17 @@ pub(crate) fn add_intrinsics<'ctx>(ctx: &'ctx Context, module: &Module<'ctx>) {
         i8_ptr_type.fn_type(&[], false),
     );
 
+    add_intrinsic(ctx, module, LLVM_TRAP, void_type.fn_type(&[], false));
+
+    for name in [LLVM_LIFETIME_START, LLVM_LIFETIME_END] {
+        // the zig builtins usually use (and so declare) these already
+        if module.get_function(name).is_none() {
+            let fn_type = void_type.fn_type(&[ctx.i64_type().into(), i8_ptr_type.into()], false);
+
+            add_intrinsic(ctx, module, name, fn_type);
+        }
+    }
+
     add_int_intrinsic(ctx, module, &LLVM_ADD_WITH_OVERFLOW, |t| {
         let fields = [t.into(), i1_type.into()];
         ctx.struct_type(&fields, false)
@@ -134,11 +145,11 @@ pub(crate) fn add_intrinsics<'ctx>(ctx: &'ctx Context, module: &Module<'ctx>) {
     });
 }
 
-pub static LLVM_MEMSET_I64: &str = "llvm.memset.p0i8.i64";
-pub static LLVM_MEMSET_I32: &str = "llvm.memset.p0i8.i32";
-
 pub static LLVM_FRAME_ADDRESS: &str = "llvm.frameaddress.p0";
 pub static LLVM_STACK_SAVE: &str = "llvm.stacksave.p0";
+pub static LLVM_TRAP: &str = "llvm.trap";
+pub static LLVM_LIFETIME_START: &str = "llvm.lifetime.start.p0";
+pub static LLVM_LIFETIME_END: &str = "llvm.lifetime.end.p0";
 
 pub static LLVM_SETJMP: &str = "llvm.eh.sjlj.setjmp";
 pub static LLVM_LONGJMP: &str = "llvm.eh.sjlj.longjmp";
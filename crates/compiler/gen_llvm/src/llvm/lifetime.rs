@@ -0,0 +1,318 @@
+//! Lifetime markers for values that are built on the stack.
+//!
+//! Every stack value gets its own `alloca` in the entry block, so without markers LLVM must assume
+//! that all of them are live for the whole function, and can never put two of them in the same
+//! stack slot. That makes stack frames much larger than they need to be, which hurts most on SBF,
+//! where a frame can be at most 4KB.
+//!
+//! Within a chain of `let`s, the mono IR tells us exactly where a struct or tag that was built on
+//! the stack is used for the last time. Such a value gets `llvm.lifetime.start` where it is built,
+//! and `llvm.lifetime.end` after its last use.
+
+use inkwell::basic_block::BasicBlock;
+use inkwell::values::{
+    BasicValue, BasicValueEnum, InstructionOpcode, InstructionValue, PointerValue,
+};
+use roc_collections::all::{MutMap, MutSet};
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+use roc_mono::ir::{Call, CallType, Expr, ListLiteralElement, Stmt};
+use roc_mono::layout::{InLayout, LayoutInterner, STLayoutInterner, UnionLayout};
+
+use super::build::{BuilderExt, Env};
+use super::intrinsics::{LLVM_LIFETIME_END, LLVM_LIFETIME_START};
+
+/// The lifetimes of the stack values bound in one chain of `let`s.
+pub(crate) struct StackLifetimes<'ctx> {
+    /// For every binding, the binding after which its stack value is dead. `None` when it is not
+    /// a stack value, or when it may still be used after the chain.
+    last_uses: Vec<Option<usize>>,
+    /// Stack values whose lifetime has started, with the binding after which it ends
+    live: Vec<(usize, PointerValue<'ctx>, u64)>,
+}
+
+/// Where the code of a binding begins, so that a lifetime can start there once we know the
+/// binding is a stack value.
+pub(crate) struct BindingStart<'ctx> {
+    block: BasicBlock<'ctx>,
+    last_instruction: Option<InstructionValue<'ctx>>,
+}
+
+impl<'ctx> StackLifetimes<'ctx> {
+    pub fn new<'a>(bindings: &[(&Symbol, &Expr<'a>, &InLayout<'a>)], cont: &Stmt<'a>) -> Self {
+        let mut candidates = MutMap::default();
+
+        for (index, (symbol, expr, _)) in bindings.iter().enumerate() {
+            if is_built_on_stack(expr) {
+                candidates.insert(**symbol, index);
+            }
+        }
+
+        let mut last_uses = vec![None; bindings.len()];
+
+        if candidates.is_empty() {
+            return Self {
+                last_uses,
+                live: Vec::new(),
+            };
+        }
+
+        let mut escaped = MutSet::default();
+        symbols_in_stmt(cont, &mut escaped);
+
+        let mut uses = MutMap::default();
+        for (index, (_, expr, _)) in bindings.iter().enumerate() {
+            let mut used = MutSet::default();
+            symbols_in_expr(expr, &mut used);
+
+            for symbol in used {
+                if may_keep_pointer(expr) {
+                    escaped.insert(symbol);
+                } else {
+                    uses.insert(symbol, index);
+                }
+            }
+        }
+
+        for (symbol, index) in candidates {
+            if !escaped.contains(&symbol) {
+                last_uses[index] = Some(uses.get(&symbol).copied().unwrap_or(index));
+            }
+        }
+
+        Self {
+            last_uses,
+            live: Vec::new(),
+        }
+    }
+
+    /// Call before building binding `index`.
+    pub fn binding_start(
+        &self,
+        env: &Env<'_, 'ctx, '_>,
+        index: usize,
+    ) -> Option<BindingStart<'ctx>> {
+        self.last_uses[index]?;
+
+        let block = env.builder.get_insert_block()?;
+
+        Some(BindingStart {
+            block,
+            last_instruction: block.get_last_instruction(),
+        })
+    }
+
+    /// Call after building binding `index`: starts its lifetime if it is a stack value, and ends
+    /// the lifetimes of the stack values that are not used anymore.
+    pub fn binding_end<'a>(
+        &mut self,
+        env: &Env<'a, 'ctx, '_>,
+        layout_interner: &STLayoutInterner<'a>,
+        index: usize,
+        layout: InLayout<'a>,
+        value: BasicValueEnum<'ctx>,
+        start: Option<BindingStart<'ctx>>,
+    ) {
+        if let (Some(start), Some(last_use)) = (start, self.last_uses[index]) {
+            let is_alloca = value.is_pointer_value()
+                && value.as_instruction_value().map(|i| i.get_opcode())
+                    == Some(InstructionOpcode::Alloca);
+
+            // building a struct or tag does not branch, so the whole binding is in one block
+            if is_alloca && env.builder.get_insert_block() == Some(start.block) {
+                let alloca = value.into_pointer_value();
+                let size = layout_interner.stack_size(layout) as u64;
+
+                start_lifetime(env, &start, alloca, size);
+                self.live.push((last_use, alloca, size));
+            }
+        }
+
+        let (ended, live) = std::mem::take(&mut self.live)
+            .into_iter()
+            .partition(|(last_use, _, _)| *last_use == index);
+        self.live = live;
+
+        for (_, alloca, size) in ended {
+            build_lifetime_call(env, LLVM_LIFETIME_END, alloca, size);
+        }
+    }
+}
+
+fn start_lifetime<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    start: &BindingStart<'ctx>,
+    alloca: PointerValue<'ctx>,
+    size: u64,
+) {
+    let mut first = match start.last_instruction {
+        Some(instruction) => instruction.get_next_instruction(),
+        None => start.block.get_first_instruction(),
+    };
+
+    // in the entry block, the alloca itself (and others) may have been put before the binding
+    while let Some(instruction) = first {
+        if instruction.get_opcode() != InstructionOpcode::Alloca {
+            break;
+        }
+
+        first = instruction.get_next_instruction();
+    }
+
+    let builder = env.context.create_builder();
+    match first {
+        Some(instruction) => builder.position_before(&instruction),
+        None => builder.position_at_end(start.block),
+    }
+
+    let intrinsic = env.module.get_function(LLVM_LIFETIME_START).unwrap();
+    let size = env.context.i64_type().const_int(size, false);
+
+    builder.new_build_call(intrinsic, &[size.into(), alloca.into()], "");
+}
+
+fn build_lifetime_call<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    intrinsic_name: &'static str,
+    alloca: PointerValue<'ctx>,
+    size: u64,
+) {
+    let size = env.context.i64_type().const_int(size, false);
+
+    env.build_intrinsic_call(intrinsic_name, &[size.into(), alloca.into()]);
+}
+
+/// Expressions that always put their value in a new `alloca` (when it is passed by reference)
+fn is_built_on_stack(expr: &Expr<'_>) -> bool {
+    match expr {
+        Expr::Struct(fields) => !fields.is_empty(),
+        Expr::Tag {
+            tag_layout: UnionLayout::NonRecursive(_),
+            ..
+        } => true,
+        _ => false,
+    }
+}
+
+/// Whether the value of `expr` may point into the stack slot of one of its arguments. Every other
+/// expression copies what it needs out of its arguments.
+fn may_keep_pointer(expr: &Expr<'_>) -> bool {
+    match expr {
+        Expr::GetElementPointer { .. }
+        | Expr::ErasedMake { .. }
+        | Expr::ErasedLoad { .. }
+        | Expr::Alloca { .. }
+        | Expr::Reset { .. }
+        | Expr::ResetRef { .. } => true,
+        Expr::Call(Call {
+            call_type: CallType::LowLevel { op, .. },
+            ..
+        }) => matches!(
+            op,
+            LowLevel::PtrCast | LowLevel::PtrStore | LowLevel::PtrLoad | LowLevel::PtrClearTagId
+        ),
+        _ => false,
+    }
+}
+
+fn symbols_in_expr(expr: &Expr<'_>, symbols: &mut MutSet<Symbol>) {
+    match expr {
+        Expr::Literal(_) | Expr::NullPointer | Expr::EmptyArray | Expr::FunctionPointer { .. } => {}
+        Expr::Call(call) => {
+            symbols.extend(call.arguments.iter().copied());
+
+            match &call.call_type {
+                CallType::ByPointer { pointer, .. } => {
+                    symbols.insert(*pointer);
+                }
+                CallType::HigherOrder(higher_order) => {
+                    symbols.insert(higher_order.passed_function.captured_environment);
+                }
+                CallType::ByName { .. } | CallType::Foreign { .. } | CallType::LowLevel { .. } => {}
+            }
+        }
+        Expr::Tag { arguments, .. } | Expr::Struct(arguments) => {
+            symbols.extend(arguments.iter().copied());
+        }
+        Expr::Array { elems, .. } => {
+            for elem in elems.iter() {
+                if let ListLiteralElement::Symbol(symbol) = elem {
+                    symbols.insert(*symbol);
+                }
+            }
+        }
+        Expr::StructAtIndex { structure, .. }
+        | Expr::GetTagId { structure, .. }
+        | Expr::UnionAtIndex { structure, .. }
+        | Expr::GetElementPointer { structure, .. } => {
+            symbols.insert(*structure);
+        }
+        Expr::ErasedMake { value, callee } => {
+            symbols.extend(*value);
+            symbols.insert(*callee);
+        }
+        Expr::ErasedLoad { symbol, .. }
+        | Expr::Reset { symbol, .. }
+        | Expr::ResetRef { symbol, .. } => {
+            symbols.insert(*symbol);
+        }
+        Expr::Alloca { initializer, .. } => {
+            symbols.extend(*initializer);
+        }
+    }
+}
+
+fn symbols_in_stmt(stmt: &Stmt<'_>, symbols: &mut MutSet<Symbol>) {
+    match stmt {
+        Stmt::Let(_, expr, _, continuation) => {
+            symbols_in_expr(expr, symbols);
+            symbols_in_stmt(continuation, symbols);
+        }
+        Stmt::Switch {
+            cond_symbol,
+            branches,
+            default_branch,
+            ..
+        } => {
+            symbols.insert(*cond_symbol);
+
+            for (_, _, branch) in branches.iter() {
+                symbols_in_stmt(branch, symbols);
+            }
+
+            symbols_in_stmt(default_branch.1, symbols);
+        }
+        Stmt::Ret(symbol) | Stmt::Crash(symbol, _) => {
+            symbols.insert(*symbol);
+        }
+        Stmt::Refcounting(modify_rc, continuation) => {
+            symbols.insert(modify_rc.get_symbol());
+            symbols_in_stmt(continuation, symbols);
+        }
+        Stmt::Expect {
+            condition,
+            lookups,
+            remainder,
+            ..
+        } => {
+            symbols.insert(*condition);
+            symbols.extend(lookups.iter().copied());
+            symbols_in_stmt(remainder, symbols);
+        }
+        Stmt::Dbg {
+            symbol, remainder, ..
+        } => {
+            symbols.insert(*symbol);
+            symbols_in_stmt(remainder, symbols);
+        }
+        Stmt::Join {
+            body, remainder, ..
+        } => {
+            symbols_in_stmt(body, symbols);
+            symbols_in_stmt(remainder, symbols);
+        }
+        Stmt::Jump(_, arguments) => {
+            symbols.extend(arguments.iter().copied());
+        }
+    }
+}
@@ -54,12 +54,16 @@ use crate::llvm::{
         LLVM_SUB_SATURATED,
         LLVM_SUB_WITH_OVERFLOW,
     },
+    memcpy::build_memset,
     refcounting::PointerToRefcount,
 };
 
 use super::{build::Env, convert::zig_dec_type};
 use super::{
-    build::{throw_internal_exception, use_roc_value, FAST_CALL_CONV},
+    build::{
+        build_error_check_branch, throw_internal_exception, throw_overflow_trap, use_roc_value,
+        ColdBranch, FAST_CALL_CONV,
+    },
     convert::zig_with_overflow_roc_dec,
     scope::Scope,
 };
@@ -255,7 +259,7 @@ pub(crate) fn run_low_level<'a, 'ctx>(
                         }
                     }
                 }
-                Aarch64 | X86_64 => {
+                Aarch64 | X86_64 | Riscv64 | Sbf => {
                     let (type_name, width) = {
                         match layout_interner.get_repr(number_layout) {
                             LayoutRepr::Builtin(Builtin::Int(int_width)) => {
@@ -1423,13 +1427,10 @@ pub(crate) fn run_low_level<'a, 'ctx>(
                     basic_type.const_zero()
                 }
                 RocReturn::ByPointer => {
-                    let basic_type = basic_type_from_layout(
-                        env,
-                        layout_interner,
-                        layout_interner.get_repr(layout),
-                    );
+                    let repr = layout_interner.get_repr(layout);
+                    let basic_type = basic_type_from_layout(env, layout_interner, repr);
                     let ptr = create_entry_block_alloca(env, basic_type, "unreachable_alloca");
-                    env.builder.new_build_store(ptr, basic_type.const_zero());
+                    build_memset(env, layout_interner, repr, ptr, 0);
 
                     ptr.into()
                 }
@@ -1441,12 +1442,67 @@ pub(crate) fn run_low_level<'a, 'ctx>(
             call_bitcode_fn(env, &[], bitcode::UTILS_DICT_PSEUDO_SEED)
         }
 
+        ArenaEnter => call_bitcode_fn(env, &[], bitcode::UTILS_ARENA_ENTER),
+
+        ArenaExit => {
+            arguments!(arena);
+
+            call_void_bitcode_fn(env, &[arena], bitcode::UTILS_ARENA_EXIT);
+
+            env.context.struct_type(&[], false).const_zero().into()
+        }
+
+        WeakRefDowngrade => {
+            arguments!(data_ptr);
+
+            check_weak_ref_target(env.target);
+
+            let refcount_ptr =
+                PointerToRefcount::from_ptr_to_data(env, data_ptr.into_pointer_value());
+            refcount_ptr.increment_weak(env);
+
+            // the weak reference is the pointer to the box
+            data_ptr
+        }
+
+        WeakRefIsAlive => {
+            arguments!(data_ptr);
+
+            check_weak_ref_target(env.target);
+
+            let refcount_ptr =
+                PointerToRefcount::from_ptr_to_data(env, data_ptr.into_pointer_value());
+
+            BasicValueEnum::IntValue(refcount_ptr.is_alive(env))
+        }
+
+        WeakRefGetUnsafe => {
+            arguments!(data_ptr);
+
+            // the value is incremented by the caller, see inc_dec.rs
+            load_roc_value(
+                env,
+                layout_interner,
+                layout_interner.get_repr(layout),
+                data_ptr.into_pointer_value(),
+                "weak_ref_get",
+            )
+        }
+
         ListIncref | ListDecref | SetJmp | LongJmp | SetLongJmpBuffer => {
             unreachable!("only inserted in dev backend codegen")
         }
     }
 }
 
+/// Weak references are counted in the upper half of the refcount, which leaves too few bits for
+/// either count on 32-bit targets. Mono replaces them by a crash there.
+fn check_weak_ref_target(target: Target) {
+    if let PtrWidth::Bytes4 = target.ptr_width() {
+        internal_error!("WeakRef lowlevels should not reach the backend on {target:?}");
+    }
+}
+
 fn intwidth_from_layout(layout: InLayout) -> IntWidth {
     layout.to_int_width()
 }
@@ -1911,7 +1967,7 @@ fn throw_on_overflow<'ctx>(
     let then_block = context.append_basic_block(parent, "then_block");
     let throw_block = context.append_basic_block(parent, "throw_block");
 
-    bd.new_build_conditional_branch(condition, then_block, throw_block);
+    build_error_check_branch(env, condition, then_block, throw_block, ColdBranch::Else);
 
     bd.position_at_end(throw_block);
 
@@ -1924,6 +1980,12 @@ fn throw_on_overflow<'ctx>(
 }
 
 fn throw_because_overflow(env: &Env<'_, '_, '_>, message: &str) {
+    if env.overflow_trap {
+        // every overflow gets its own message, with its location
+        throw_overflow_trap(env, message);
+        return;
+    }
+
     let block = env.builder.get_insert_block().expect("to be in a function");
     let di_location = env.builder.get_current_debug_location().unwrap();
 
@@ -2056,7 +2118,9 @@ fn dec_to_str<'ctx>(env: &Env<'_, 'ctx, '_>, dec: BasicValueEnum<'ctx>) -> Basic
                 bitcode::DEC_TO_STR,
             )
         }
-        Target::Wasm32 => call_str_bitcode_fn(
+        // The RISC-V C ABI passes the 16-byte aligned RocDec struct as a single i128, and on SBF
+        // the i128 is converted to whatever zig expects when the builtin is called
+        Target::Wasm32 | Target::LinuxRiscv64 | Target::Sbf => call_str_bitcode_fn(
             env,
             &[],
             &[dec.into()],
@@ -2084,7 +2148,9 @@ fn dec_unary_op<'ctx>(
             let (low, high) = dec_split_into_words(env, dec);
             call_bitcode_fn(env, &[low.into(), high.into()], fn_name)
         }
-        Target::Wasm32 => call_bitcode_fn(env, &[dec.into()], fn_name),
+        Target::Wasm32 | Target::LinuxRiscv64 | Target::Sbf => {
+            call_bitcode_fn(env, &[dec.into()], fn_name)
+        }
         _ => call_bitcode_fn(env, &[dec_alloca(env, dec)], fn_name),
     }
 }
@@ -2115,7 +2181,9 @@ fn dec_binary_op<'ctx>(
                 .build_load(env.context.i128_type(), ptr, "to_i128")
                 .unwrap()
         }
-        Target::Wasm32 => call_bitcode_fn(env, &[dec1.into(), dec2.into()], fn_name),
+        Target::Wasm32 | Target::LinuxRiscv64 | Target::Sbf => {
+            call_bitcode_fn(env, &[dec1.into(), dec2.into()], fn_name)
+        }
         _ => call_bitcode_fn(
             env,
             &[dec_alloca(env, dec1), dec_alloca(env, dec2)],
@@ -2152,7 +2220,7 @@ fn dec_binop_with_overflow<'ctx>(
                 fn_name,
             );
         }
-        Target::Wasm32 => {
+        Target::Wasm32 | Target::LinuxRiscv64 | Target::Sbf => {
             call_void_bitcode_fn(
                 env,
                 &[return_alloca.into(), lhs.into(), rhs.into()],
@@ -2201,7 +2269,9 @@ pub(crate) fn dec_binop_with_unchecked<'ctx>(
                 fn_name,
             )
         }
-        Target::Wasm32 => call_bitcode_fn(env, &[lhs.into(), rhs.into()], fn_name),
+        Target::Wasm32 | Target::LinuxRiscv64 | Target::Sbf => {
+            call_bitcode_fn(env, &[lhs.into(), rhs.into()], fn_name)
+        }
         _ => call_bitcode_fn(env, &[dec_alloca(env, lhs), dec_alloca(env, rhs)], fn_name),
     }
 }
@@ -2601,8 +2671,7 @@ fn int_neg_raise_on_overflow<'ctx>(
     let then_block = env.context.append_basic_block(parent, "then");
     let else_block = env.context.append_basic_block(parent, "else");
 
-    env.builder
-        .new_build_conditional_branch(condition, then_block, else_block);
+    build_error_check_branch(env, condition, then_block, else_block, ColdBranch::Then);
 
     builder.position_at_end(then_block);
 
@@ -2631,8 +2700,7 @@ fn int_abs_raise_on_overflow<'ctx>(
     let then_block = env.context.append_basic_block(parent, "then");
     let else_block = env.context.append_basic_block(parent, "else");
 
-    env.builder
-        .new_build_conditional_branch(condition, then_block, else_block);
+    build_error_check_branch(env, condition, then_block, else_block, ColdBranch::Then);
 
     builder.position_at_end(then_block);
 
@@ -1,7 +1,18 @@
-use inkwell::{types::BasicType, values::PointerValue};
+use inkwell::{
+    module::Linkage,
+    types::{BasicType, FunctionType},
+    values::{BasicValue, FunctionValue, IntValue, PointerValue},
+    AddressSpace,
+};
 use roc_mono::layout::{LayoutRepr, STLayoutInterner};
+use roc_target::{Architecture, Target};
 
-use super::{align::LlvmAlignment, build::Env, convert::basic_type_from_layout};
+use super::{
+    align::LlvmAlignment,
+    build::{BuilderExt, Env, C_CALL_CONV},
+    convert::basic_type_from_layout,
+    sanitizers::build_alignment_check,
+};
 
 pub fn build_memcpy<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
@@ -11,13 +22,449 @@ pub fn build_memcpy<'a, 'ctx>(
     source: PointerValue<'ctx>,
 ) {
     let align_bytes = layout.llvm_alignment_bytes(layout_interner);
+
+    if align_bytes == 0 {
+        // There is nothing to memcpy.
+        return;
+    }
+
+    // `size_of` is a constant expression rather than a constant integer, so use the size of the
+    // layout to spot the copies that can be unrolled
+    let bytes = layout.stack_size(layout_interner) as u64;
+    if build_memcpy_vectorized(env, destination, source, bytes, align_bytes)
+        || build_unrolled_copy(env, destination, align_bytes, source, align_bytes, bytes)
+    {
+        return;
+    }
+
+    let width = basic_type_from_layout(env, layout_interner, layout)
+        .size_of()
+        .unwrap();
+
+    build_memcpy_raw(env, destination, align_bytes, source, align_bytes, width);
+}
+
+/// Constant-size copies of at most this many bytes are unrolled by [build_unrolled_copy].
+fn max_unrolled_copy_bytes(target: Target) -> u64 {
+    match target {
+        // a load and a store cost a compute unit each, while `sol_memcpy_` costs at least 10 plus
+        // the setup of its arguments, and the libc `memcpy` below SBF_MEM_SYSCALL_THRESHOLD is a
+        // loop that costs even more
+        Target::Sbf => 48,
+        _ => 64,
+    }
+}
+
+/// Copy a constant number of bytes with a sequence of integer loads and stores, each as wide as
+/// the alignment allows, up to the width of a pointer. This is cheaper than calling `memcpy`,
+/// especially on SBF, where every call costs compute units and the call depth is limited, and
+/// it does not rely on LLVM to clean up the copy at `-O0`.
+///
+/// Returns `false`, and emits nothing, when `bytes` is zero or more than
+/// [max_unrolled_copy_bytes], or when the alignment would need more than 8 loads and stores.
+fn build_unrolled_copy<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    destination: PointerValue<'ctx>,
+    destination_align_bytes: u32,
+    source: PointerValue<'ctx>,
+    source_align_bytes: u32,
+    bytes: u64,
+) -> bool {
+    const MAX_UNROLLED_COPIES: u64 = 8;
+
+    if bytes == 0 || bytes > max_unrolled_copy_bytes(env.target) {
+        return false;
+    }
+
+    let align = destination_align_bytes.min(source_align_bytes).max(1) as u64;
+    let chunk_bytes = align.min(env.target.ptr_size() as u64);
+
+    // the chunks are powers of two, so the tail takes one copy per bit of its size
+    let copies = bytes / chunk_bytes + (bytes % chunk_bytes).count_ones() as u64;
+    if copies > MAX_UNROLLED_COPIES {
+        return false;
+    }
+
+    let builder = env.builder;
+    let i8_type = env.context.i8_type();
+    let i64_type = env.context.i64_type();
+
+    let mut offset = 0;
+    let mut chunk_bytes = chunk_bytes;
+
+    while offset < bytes {
+        while offset + chunk_bytes > bytes {
+            chunk_bytes /= 2;
+        }
+
+        let int_type = env.context.custom_width_int_type(chunk_bytes as u32 * 8);
+
+        let (source_chunk, destination_chunk) = if offset == 0 {
+            (source, destination)
+        } else {
+            let index = [i64_type.const_int(offset, false)];
+
+            // every offset is within the `bytes` that are copied
+            unsafe {
+                (
+                    builder.new_build_in_bounds_gep(i8_type, source, &index, "source_chunk"),
+                    builder.new_build_in_bounds_gep(
+                        i8_type,
+                        destination,
+                        &index,
+                        "destination_chunk",
+                    ),
+                )
+            }
+        };
+
+        // every offset is a multiple of the chunks before it, so each chunk is aligned to its size
+        let value = builder.new_build_load(int_type, source_chunk, "small_copy");
+        value
+            .as_instruction_value()
+            .unwrap()
+            .set_alignment(chunk_bytes as u32)
+            .unwrap();
+
+        let store = builder.new_build_store(destination_chunk, value);
+        store.set_alignment(chunk_bytes as u32).unwrap();
+
+        offset += chunk_bytes;
+    }
+
+    true
+}
+
+/// Copies of at most this many bytes are unrolled by [build_memcpy_vectorized]. Beyond that,
+/// `memcpy` is better at moving memory than straight-line code, and the code size adds up.
+pub const MAX_VECTORIZED_COPY_BYTES: u64 = 256;
+
+/// Copy `size_bytes` with an unrolled sequence of 128-bit vector loads and stores, which become
+/// SSE2 or NEON moves on x86-64 and Aarch64.
+///
+/// Returns `false`, and emits nothing, unless the target is one of those, `size_bytes` is a
+/// multiple of 16 no larger than [MAX_VECTORIZED_COPY_BYTES], and both sides are aligned to at
+/// least 16 bytes.
+fn build_memcpy_vectorized<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    destination: PointerValue<'ctx>,
+    source: PointerValue<'ctx>,
+    size_bytes: u64,
+    align: u32,
+) -> bool {
+    if !matches!(
+        env.target.architecture(),
+        Architecture::X86_64 | Architecture::Aarch64
+    ) {
+        return false;
+    }
+
+    if align < 16 || size_bytes == 0 || size_bytes % 16 != 0 {
+        return false;
+    }
+
+    if size_bytes > MAX_VECTORIZED_COPY_BYTES {
+        return false;
+    }
+
+    let builder = env.builder;
+    let i8_type = env.context.i8_type();
+    let i64_type = env.context.i64_type();
+    let vector_type = i64_type.vec_type(2);
+
+    for offset in (0..size_bytes).step_by(16) {
+        let index = [i64_type.const_int(offset, false)];
+
+        // every offset is within the `size_bytes` that are copied
+        let (source_chunk, destination_chunk) = unsafe {
+            (
+                builder.new_build_in_bounds_gep(i8_type, source, &index, "source_chunk"),
+                builder.new_build_in_bounds_gep(i8_type, destination, &index, "destination_chunk"),
+            )
+        };
+
+        let chunk = builder.new_build_load(vector_type, source_chunk, "vector_copy");
+        chunk
+            .as_instruction_value()
+            .unwrap()
+            .set_alignment(16)
+            .unwrap();
+
+        let store = builder.new_build_store(destination_chunk, chunk);
+        store.set_alignment(16).unwrap();
+    }
+
+    true
+}
+
+/// Copy `size` bytes from `source` to `destination`. Copies of a small constant size are
+/// unrolled into loads and stores, see [build_unrolled_copy].
+///
+/// On SBF, `llvm.memcpy.inline` requires an immediate size, so we call `memcpy` (or the
+/// `sol_memcpy_` syscall, see [SBF_MEM_SYSCALL_THRESHOLD]) directly rather than going
+/// through the intrinsic.
+///
+/// Don't attach `nosanitize` metadata to these operations: AddressSanitizer (see
+/// [super::sanitizers::add_address_sanitizer]) has to check them like any other access.
+///
+/// With `--check-alignment`, the pointers of a `memcpy` call are checked here, because the
+/// post-pass in [super::sanitizers::add_alignment_checks] only sees loads and stores.
+pub fn build_memcpy_raw<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    destination: PointerValue<'ctx>,
+    destination_align_bytes: u32,
+    source: PointerValue<'ctx>,
+    source_align_bytes: u32,
+    size: IntValue<'ctx>,
+) {
+    if let Some(bytes) = size.get_zero_extended_constant() {
+        if build_unrolled_copy(
+            env,
+            destination,
+            destination_align_bytes,
+            source,
+            source_align_bytes,
+            bytes,
+        ) {
+            return;
+        }
+    }
+
+    if env.check_alignment {
+        check_copy_alignment(env, destination, destination_align_bytes);
+        check_copy_alignment(env, source, source_align_bytes);
+    }
+
+    if matches!(env.target, Target::Sbf) {
+        build_sbf_copy_call(env, "memcpy", "sol_memcpy_", destination, source, size);
+    } else {
+        env.builder
+            .build_memcpy(
+                destination,
+                destination_align_bytes,
+                source,
+                source_align_bytes,
+                size,
+            )
+            .unwrap();
+    }
+}
+
+fn check_copy_alignment<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    pointer: PointerValue<'ctx>,
+    align_bytes: u32,
+) {
+    let function = env
+        .builder
+        .get_insert_block()
+        .and_then(|block| block.get_parent())
+        .unwrap();
+
+    let function_name = function.get_name().to_string_lossy();
+    build_alignment_check(env, pointer, align_bytes, &function_name);
+}
+
+/// On SBF, memory operations of at least this many bytes, or of a size only known at runtime,
+/// use the Solana `sol_memcpy_`/`sol_memmove_`/`sol_memset_` syscalls, which cost far fewer
+/// compute units than the compiler-builtins loops. For smaller sizes the fixed cost of the
+/// syscall dominates, so those still call the libc symbols.
+pub const SBF_MEM_SYSCALL_THRESHOLD: u64 = 32;
+
+fn use_sbf_mem_syscall(size: IntValue<'_>) -> bool {
+    match size.get_zero_extended_constant() {
+        Some(bytes) => bytes >= SBF_MEM_SYSCALL_THRESHOLD,
+        None => true,
+    }
+}
+
+pub(crate) fn get_or_add_function<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    function_name: &str,
+    fn_type: FunctionType<'ctx>,
+) -> FunctionValue<'ctx> {
+    env.module.get_function(function_name).unwrap_or_else(|| {
+        env.module
+            .add_function(function_name, fn_type, Some(Linkage::External))
+    })
+}
+
+/// Call either a libc function with the `memcpy` signature (i.e. `memcpy` or `memmove`),
+/// or its Solana syscall counterpart, which has the same arguments but returns nothing.
+fn build_sbf_copy_call<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    libc_name: &str,
+    syscall_name: &str,
+    destination: PointerValue<'ctx>,
+    source: PointerValue<'ctx>,
+    size: IntValue<'ctx>,
+) {
+    let ptr_type = env.context.ptr_type(AddressSpace::default());
+    let i64_type = env.context.i64_type();
+    let arg_types = [ptr_type.into(), ptr_type.into(), i64_type.into()];
+
+    let (function_name, fn_type) = if use_sbf_mem_syscall(size) {
+        (
+            syscall_name,
+            env.context.void_type().fn_type(&arg_types, false),
+        )
+    } else {
+        (libc_name, ptr_type.fn_type(&arg_types, false))
+    };
+
+    let function = get_or_add_function(env, function_name, fn_type);
+
+    let size = env
+        .builder
+        .build_int_z_extend_or_bit_cast(size, i64_type, "copy_size")
+        .unwrap();
+
+    let call = env.builder.new_build_call(
+        function,
+        &[destination.into(), source.into(), size.into()],
+        function_name,
+    );
+
+    call.set_call_convention(C_CALL_CONV);
+}
+
+/// Like [build_memcpy], but `source` and `destination` are allowed to overlap.
+pub fn build_memmove<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    layout: LayoutRepr<'a>,
+    destination: PointerValue<'ctx>,
+    source: PointerValue<'ctx>,
+) {
+    let align_bytes = layout.llvm_alignment_bytes(layout_interner);
     let width = basic_type_from_layout(env, layout_interner, layout)
         .size_of()
         .unwrap();
     if align_bytes > 0 {
-        // There is actually something to memcpy.
+        // There is actually something to memmove.
+        build_memmove_raw(env, destination, align_bytes, source, align_bytes, width);
+    }
+}
+
+/// Move `size` bytes from `source` to `destination`, where the two regions may overlap.
+///
+/// Like [build_memcpy_raw], this calls `memmove` or `sol_memmove_` directly on SBF instead of
+/// using the intrinsic.
+pub fn build_memmove_raw<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    destination: PointerValue<'ctx>,
+    destination_align_bytes: u32,
+    source: PointerValue<'ctx>,
+    source_align_bytes: u32,
+    size: IntValue<'ctx>,
+) {
+    if matches!(env.target, Target::Sbf) {
+        build_sbf_copy_call(env, "memmove", "sol_memmove_", destination, source, size);
+    } else {
         env.builder
-            .build_memcpy(destination, align_bytes, source, align_bytes, width)
+            .build_memmove(
+                destination,
+                destination_align_bytes,
+                source,
+                source_align_bytes,
+                size,
+            )
             .unwrap();
     }
 }
+
+/// Fill the memory occupied by a value of this layout with the `value` byte.
+pub fn build_memset<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    layout: LayoutRepr<'a>,
+    destination: PointerValue<'ctx>,
+    value: u8,
+) {
+    let align_bytes = layout.llvm_alignment_bytes(layout_interner);
+    let width = basic_type_from_layout(env, layout_interner, layout)
+        .size_of()
+        .unwrap();
+    let value = env.context.i8_type().const_int(value as u64, false);
+
+    build_memset_raw(env, destination, align_bytes, value, width);
+}
+
+/// Fill `size` bytes at `destination` with the `value` byte.
+///
+/// Like [build_memcpy_raw], this calls `memset` or `sol_memset_` directly on SBF instead of
+/// using the intrinsic.
+/// An alignment of 0 means the destination is zero-sized, and nothing is emitted.
+pub fn build_memset_raw<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    destination: PointerValue<'ctx>,
+    destination_align_bytes: u32,
+    value: IntValue<'ctx>,
+    size: IntValue<'ctx>,
+) {
+    if destination_align_bytes == 0 {
+        return;
+    }
+
+    let value = env
+        .builder
+        .new_build_int_cast(value, env.context.i8_type(), "memset_value");
+
+    if matches!(env.target, Target::Sbf) {
+        build_memset_call(env, destination, value, size);
+    } else {
+        env.builder
+            .build_memset(destination, destination_align_bytes, value, size)
+            .unwrap();
+    }
+}
+
+fn build_memset_call<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    destination: PointerValue<'ctx>,
+    value: IntValue<'ctx>,
+    size: IntValue<'ctx>,
+) {
+    let ptr_type = env.context.ptr_type(AddressSpace::default());
+    let i32_type = env.context.i32_type();
+    let i64_type = env.context.i64_type();
+
+    let size = env
+        .builder
+        .build_int_z_extend_or_bit_cast(size, i64_type, "memset_size")
+        .unwrap();
+
+    let call = if use_sbf_mem_syscall(size) {
+        // sol_memset_ takes the fill byte as a u8 and returns nothing
+        let fn_type = env.context.void_type().fn_type(
+            &[
+                ptr_type.into(),
+                env.context.i8_type().into(),
+                i64_type.into(),
+            ],
+            false,
+        );
+        let function = get_or_add_function(env, "sol_memset_", fn_type);
+
+        env.builder.new_build_call(
+            function,
+            &[destination.into(), value.into(), size.into()],
+            "sol_memset_",
+        )
+    } else {
+        // memset takes the fill byte as a C int
+        let fn_type = ptr_type.fn_type(&[ptr_type.into(), i32_type.into(), i64_type.into()], false);
+        let function = get_or_add_function(env, "memset", fn_type);
+        let value = env
+            .builder
+            .new_build_int_z_extend(value, i32_type, "memset_byte");
+
+        env.builder.new_build_call(
+            function,
+            &[destination.into(), value.into(), size.into()],
+            "memset",
+        )
+    };
+
+    call.set_call_convention(C_CALL_CONV);
+}
@@ -3,16 +3,33 @@ pub mod build;
 pub mod build_list;
 pub mod build_str;
 pub mod compare;
+pub mod compute_budget;
 pub mod convert;
 mod expect;
 pub mod externs;
+pub mod inline;
 mod intrinsics;
+mod lifetime;
 mod lowlevel;
+pub mod memcpy;
+pub mod pgo;
 pub mod refcounting;
+pub mod sanitizers;
 
 mod align;
+pub mod cfi;
+pub mod debug_info;
+mod decref_batch;
+pub mod dedup;
 mod erased;
 mod fn_ptr;
-mod memcpy;
+mod pool;
+mod sbf;
+mod sections;
+mod stack_alloc;
+pub mod stack_depth;
+pub mod stack_guard;
 mod scope;
 mod struct_;
+mod tagged_pointer;
+mod valgrind;
@@ -0,0 +1,138 @@
+//! Profile-guided optimization.
+//!
+//! A program is first built with `--pgo-instrument`. LLVM's `pgo-instr-gen` pass then puts an
+//! `llvm.instrprof.increment` counter on the edges of the control flow graph of every function,
+//! and the `instrprof` pass lowers those counters to globals that the profile runtime writes to a
+//! `.profraw` file. After merging the raw profiles with `llvm-profdata merge`, the program is
+//! built again with `--pgo-use=<profile.profdata>`, and the `pgo-instr-use` pass turns the counts
+//! into `!prof` branch weights and function entry counts. The optimizations that run after it
+//! (the inliner in particular) take their hot and cold paths from those.
+//!
+//! The counters are matched with the functions they were collected for by name and by a hash of
+//! the control flow graph, so the profile only applies to the same program built with the same
+//! options.
+
+use std::ffi::{c_char, c_int, CString};
+use std::path::{Path, PathBuf};
+
+use inkwell::module::Linkage;
+use inkwell::AddressSpace;
+use roc_error_macros::internal_error;
+
+use super::build::{BuilderExt, Env, C_CALL_CONV};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PgoMode {
+    /// Count how often every branch is taken
+    Instrument,
+    /// Optimize with the counts of an earlier instrumented run, merged into a `.profdata` file
+    Use(PathBuf),
+}
+
+impl PgoMode {
+    /// The passes that go in front of the optimization pipeline. They run on the same IR in both
+    /// modes, so the control flow graphs match.
+    pub fn passes(&self) -> &'static str {
+        match self {
+            PgoMode::Instrument => "pgo-instr-gen,instrprof,",
+            PgoMode::Use(_) => "pgo-instr-use,",
+        }
+    }
+}
+
+/// Writes the counters to the file named by `LLVM_PROFILE_FILE` (default.profraw by default)
+const PROFILE_WRITE_FILE: &str = "__llvm_profile_write_file";
+
+/// Write the profile when the program exits.
+///
+/// The profile runtime can do this by itself, but only when the linker pulls in its
+/// `__llvm_profile_runtime` hook, which only clang's driver asks for. A Roc app is usually linked
+/// into a host that was built without it. So the module registers a destructor that calls
+/// [PROFILE_WRITE_FILE] directly; the runtime skips the second write when both are there.
+///
+/// The host must be linked against the profile runtime, for instance with
+/// `clang -fprofile-instr-generate`.
+pub fn add_profile_writer(env: &Env<'_, '_, '_>) {
+    let ctx = env.context;
+    let module = env.module;
+    let builder = env.builder;
+
+    let write_file = module.get_function(PROFILE_WRITE_FILE).unwrap_or_else(|| {
+        module.add_function(
+            PROFILE_WRITE_FILE,
+            ctx.i32_type().fn_type(&[], false),
+            Some(Linkage::External),
+        )
+    });
+
+    let dtor = module.add_function(
+        "roc_pgo_write_profile",
+        ctx.void_type().fn_type(&[], false),
+        Some(Linkage::Internal),
+    );
+
+    let entry = ctx.append_basic_block(dtor, "entry");
+    builder.position_at_end(entry);
+
+    // this function has no debug info, so it must not inherit the location of the last one
+    builder.unset_current_debug_location();
+
+    let call = builder.new_build_call(write_file, &[], "call_profile_write_file");
+    call.set_call_convention(C_CALL_CONV);
+
+    builder.new_build_return(None);
+
+    // append our destructor to `llvm.global_dtors`, which is an array of
+    // { priority, destructor, associated data }
+    let ptr_type = ctx.ptr_type(AddressSpace::default());
+    let entry_type = ctx.struct_type(
+        &[ctx.i32_type().into(), ptr_type.into(), ptr_type.into()],
+        false,
+    );
+
+    let dtor_entry = entry_type.const_named_struct(&[
+        ctx.i32_type().const_int(65535, false).into(),
+        dtor.as_global_value().as_pointer_value().into(),
+        ptr_type.const_null().into(),
+    ]);
+
+    if module.get_global("llvm.global_dtors").is_some() {
+        // Nothing in the builtins or the Roc program has a destructor at the moment.
+        // If that changes, the existing entries need to be merged with ours.
+        internal_error!("the module already defines llvm.global_dtors");
+    }
+
+    let dtors = entry_type.const_array(&[dtor_entry]);
+    let global = module.add_global(dtors.get_type(), None, "llvm.global_dtors");
+    global.set_linkage(Linkage::Appending);
+    global.set_initializer(&dtors);
+
+    if cfg!(debug_assertions) {
+        crate::llvm::build::verify_fn(dtor);
+    }
+}
+
+extern "C" {
+    // part of LLVM's C API, which inkwell does not wrap
+    fn LLVMParseCommandLineOptions(
+        argc: c_int,
+        argv: *const *const c_char,
+        overview: *const c_char,
+    );
+}
+
+/// Point the `pgo-instr-use` pass at `profile`.
+///
+/// The pass takes its file from a global LLVM option, so this affects every module that is
+/// optimized afterwards. An option can only be given once, so this may only be called once per
+/// process.
+pub fn use_profile(profile: &Path) {
+    let program = CString::new("roc").unwrap();
+    let option = CString::new(format!("-pgo-test-profile-file={}", profile.display()))
+        .unwrap_or_else(|_| internal_error!("the path of the profile contains a nul byte"));
+    let overview = CString::new("").unwrap();
+
+    let argv = [program.as_ptr(), option.as_ptr()];
+
+    unsafe { LLVMParseCommandLineOptions(argv.len() as c_int, argv.as_ptr(), overview.as_ptr()) };
+}
@@ -0,0 +1,78 @@
+//! Allocate heap values of a fixed size from pools, see [roc_mono::pool].
+//!
+//! The size classes go into [POOL_SIZE_CLASSES_SYMBOL], where the builtins look them up. Every
+//! tag allocation whose size is known here picks the size class it fits in, and passes its index
+//! to `allocate_pooled_with_refcount`, so the builtins never have to search for the right pool.
+//!
+//! This is enabled with `--pool-alloc`.
+
+use inkwell::module::Linkage;
+use inkwell::values::PointerValue;
+use inkwell::AddressSpace;
+use roc_builtins::bitcode;
+use roc_mono::pool::{allocation_size, SizeClasses, MAX_SIZE_CLASSES};
+
+use super::bitcode::call_bitcode_fn;
+use super::build::{BuilderExt, Env};
+
+/// The global the builtins read the size classes from, as `[MAX_SIZE_CLASSES x u32]`. Unused
+/// size classes are 0.
+pub const POOL_SIZE_CLASSES_SYMBOL: &str = "roc__pool_size_classes";
+
+/// Define [POOL_SIZE_CLASSES_SYMBOL]. The builtins declare it as a weak symbol, so that without
+/// pools it is null.
+pub(crate) fn add_size_classes(env: &Env<'_, '_, '_>, size_classes: &SizeClasses) {
+    let u32_type = env.context.i32_type();
+    let array_type = u32_type.array_type(MAX_SIZE_CLASSES as u32);
+
+    let mut sizes: Vec<_> = size_classes
+        .sizes()
+        .iter()
+        .map(|size| u32_type.const_int(*size as u64, false))
+        .collect();
+    sizes.resize(MAX_SIZE_CLASSES, u32_type.const_zero());
+
+    let global = match env.module.get_global(POOL_SIZE_CLASSES_SYMBOL) {
+        Some(global) => global,
+        None => env
+            .module
+            .add_global(array_type, None, POOL_SIZE_CLASSES_SYMBOL),
+    };
+
+    global.set_initializer(&u32_type.const_array(&sizes));
+    global.set_linkage(Linkage::Internal);
+    global.set_constant(true);
+}
+
+/// Allocate `data_bytes` behind a refcount from the pool they fit in, if there is one
+pub(crate) fn allocate_with_refcount<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    data_bytes: u32,
+    alignment_bytes: u32,
+) -> Option<PointerValue<'ctx>> {
+    let size_classes = env.pool_size_classes?;
+
+    let size = allocation_size(env.target, data_bytes, alignment_bytes);
+    let size_class = size_classes.class_for(size, alignment_bytes)?;
+
+    let ptr = call_bitcode_fn(
+        env,
+        &[
+            env.ptr_int().const_int(data_bytes as u64, false).into(),
+            env.alignment_const(alignment_bytes).into(),
+            env.context
+                .i32_type()
+                .const_int(size_class as u64, false)
+                .into(),
+        ],
+        bitcode::UTILS_ALLOCATE_POOLED_WITH_REFCOUNT,
+    )
+    .into_pointer_value();
+
+    let ptr_type = env.context.ptr_type(AddressSpace::default());
+
+    Some(
+        env.builder
+            .new_build_pointer_cast(ptr, ptr_type, "alloc_cast_to_desired"),
+    )
+}
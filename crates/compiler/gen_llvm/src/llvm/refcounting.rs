@@ -1,5 +1,7 @@
 use crate::debug_info_init;
-use crate::llvm::bitcode::{build_dec_wrapper, call_void_bitcode_fn, call_void_list_bitcode_fn};
+use crate::llvm::bitcode::{
+    build_dec_wrapper, call_bitcode_fn, call_void_bitcode_fn, call_void_list_bitcode_fn,
+};
 use crate::llvm::build::BuilderExt;
 use crate::llvm::build::{
     add_func, cast_basic_basic, get_tag_id, tag_pointer_clear_tag_id, Env, FAST_CALL_CONV,
@@ -25,6 +27,7 @@ use roc_mono::layout::{
 use super::build::{cast_if_necessary_for_opaque_recursive_pointers, load_roc_value, FunctionSpec};
 use super::convert::{argument_type_from_layout, argument_type_from_union_layout};
 use super::erased;
+use super::tagged_pointer;
 
 pub struct PointerToRefcount<'ctx> {
     value: PointerValue<'ctx>,
@@ -130,6 +133,27 @@ impl<'ctx> PointerToRefcount<'ctx> {
         decref_pointer(env, self.value, alignment, layout);
     }
 
+    /// Count one more weak reference to the allocation, see `WEAK_REFCOUNT_ONE` in utils.zig
+    pub fn increment_weak<'a, 'env>(&self, env: &Env<'a, 'ctx, 'env>) {
+        let amount = env.ptr_int().const_int(1, false);
+
+        call_void_bitcode_fn(
+            env,
+            &[self.value.into(), amount.into()],
+            bitcode::UTILS_INCREF_WEAK_RC_PTR,
+        );
+    }
+
+    /// Whether a strong reference to the allocation still exists
+    pub fn is_alive<'a, 'env>(&self, env: &Env<'a, 'ctx, 'env>) -> IntValue<'ctx> {
+        call_bitcode_fn(
+            env,
+            &[self.value.into()],
+            bitcode::UTILS_WEAK_RC_PTR_IS_ALIVE,
+        )
+        .into_int_value()
+    }
+
     pub fn deallocate<'a, 'env>(
         &self,
         env: &Env<'a, 'ctx, 'env>,
@@ -628,8 +652,21 @@ fn modify_refcount_layout_build_function<'a, 'ctx>(
                     Some(function)
                 }
 
+                NonNullableUnwrapped(_) if layout_interner.is_weak_ref(layout) => {
+                    let function = build_weak_ref(env, layout_interner, layout_ids, mode, variant);
+
+                    Some(function)
+                }
+
                 _ => {
-                    let function = build_rec_union(env, layout_interner, layout_ids, mode, variant);
+                    let function = build_rec_union(
+                        env,
+                        layout_interner,
+                        layout_ids,
+                        mode,
+                        variant,
+                        layout_interner.finalizer(layout),
+                    );
 
                     Some(function)
                 }
@@ -1015,6 +1052,7 @@ fn build_rec_union<'a, 'ctx>(
     layout_ids: &mut LayoutIds<'a>,
     mode: Mode,
     union_layout: UnionLayout<'a>,
+    finalizer: Option<&'a str>,
 ) -> FunctionValue<'ctx> {
     let layout = LayoutRepr::Union(union_layout);
 
@@ -1027,6 +1065,12 @@ fn build_rec_union<'a, 'ctx>(
         mode,
     );
 
+    // an opaque type with a finalizer has the same runtime representation as the box it wraps
+    let fn_name = match finalizer {
+        Some(finalizer) => format!("{fn_name}_{finalizer}"),
+        None => fn_name,
+    };
+
     let function = match env.module.get_function(fn_name.as_str()) {
         Some(function_value) => function_value,
         None => {
@@ -1042,6 +1086,7 @@ fn build_rec_union<'a, 'ctx>(
                 layout_ids,
                 mode,
                 union_layout,
+                finalizer,
                 function_value,
             );
 
@@ -1062,6 +1107,7 @@ fn build_rec_union_help<'a, 'ctx>(
     layout_ids: &mut LayoutIds<'a>,
     mode: Mode,
     union_layout: UnionLayout<'a>,
+    finalizer: Option<&'a str>,
     fn_val: FunctionValue<'ctx>,
 ) {
     let tags = union_layout_tags(env.arena, &union_layout);
@@ -1097,13 +1143,11 @@ fn build_rec_union_help<'a, 'ctx>(
     let should_recurse_block = env.context.append_basic_block(parent, "should_recurse");
 
     let ctx = env.context;
-    if union_layout.is_nullable() {
-        let is_null = env.builder.new_build_is_null(value_ptr, "is_null");
-
+    if let Some(is_unallocated) = is_unallocated_tag(env, &union_layout, value_ptr) {
         let then_block = ctx.append_basic_block(parent, "then");
 
         env.builder
-            .new_build_conditional_branch(is_null, then_block, should_recurse_block);
+            .new_build_conditional_branch(is_unallocated, then_block, should_recurse_block);
 
         {
             env.builder.position_at_end(then_block);
@@ -1149,6 +1193,11 @@ fn build_rec_union_help<'a, 'ctx>(
             {
                 env.builder.position_at_end(do_recurse_block);
 
+                // the finalizer still sees the fields, which are decremented after it returns
+                if let Some(finalizer) = finalizer {
+                    call_finalizer(env, finalizer, value_ptr);
+                }
+
                 build_rec_union_recursive_decrement(
                     env,
                     layout_interner,
@@ -1168,6 +1217,157 @@ fn build_rec_union_help<'a, 'ctx>(
     }
 }
 
+/// A `WeakRef` has the layout of the box it points to, but is counted in the upper half of the
+/// refcount of the box, see `WEAK_REFCOUNT_ONE` in utils.zig
+fn build_weak_ref<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    layout_ids: &mut LayoutIds<'a>,
+    mode: Mode,
+    union_layout: UnionLayout<'a>,
+) -> FunctionValue<'ctx> {
+    let layout = LayoutRepr::Union(union_layout);
+
+    let (_, fn_name) = function_name_from_mode(
+        layout_ids,
+        &env.interns,
+        "increment_weak_ref",
+        "decrement_weak_ref",
+        layout,
+        mode,
+    );
+
+    // a weak reference has the same runtime representation as the box it points to
+    let fn_name = format!("{fn_name}_weak");
+
+    let function = match env.module.get_function(fn_name.as_str()) {
+        Some(function_value) => function_value,
+        None => {
+            let block = env.builder.get_insert_block().expect("to be in a function");
+            let di_location = env.builder.get_current_debug_location().unwrap();
+
+            let basic_type = basic_type_from_layout(env, layout_interner, layout);
+            let function_value = build_header(env, basic_type, mode, &fn_name);
+
+            build_weak_ref_help(
+                env,
+                layout_interner,
+                layout_ids,
+                mode,
+                union_layout,
+                function_value,
+            );
+
+            env.builder.position_at_end(block);
+            env.builder.set_current_debug_location(di_location);
+
+            function_value
+        }
+    };
+
+    function
+}
+
+fn build_weak_ref_help<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    layout_ids: &mut LayoutIds<'a>,
+    mode: Mode,
+    union_layout: UnionLayout<'a>,
+    fn_val: FunctionValue<'ctx>,
+) {
+    let tags = union_layout_tags(env.arena, &union_layout);
+    debug_assert_eq!(tags.tags.len(), 1);
+
+    let builder = env.builder;
+
+    let entry = env.context.append_basic_block(fn_val, "entry");
+    builder.position_at_end(entry);
+
+    debug_info_init!(env, fn_val);
+
+    let arg_val = fn_val.get_param_iter().next().unwrap();
+    arg_val.set_name(Symbol::ARG_1.as_str(&env.interns));
+
+    let value_ptr = arg_val.into_pointer_value();
+    let refcount_ptr = PointerToRefcount::from_ptr_to_data(env, value_ptr);
+
+    match mode {
+        Mode::Inc => {
+            let amount = fn_val.get_nth_param(1).unwrap();
+
+            call_void_bitcode_fn(
+                env,
+                &[refcount_ptr.value.into(), amount],
+                bitcode::UTILS_INCREF_WEAK_RC_PTR,
+            );
+
+            builder.new_build_return(None);
+        }
+        Mode::Dec => {
+            let is_last = call_bitcode_fn(
+                env,
+                &[refcount_ptr.value.into()],
+                bitcode::UTILS_DECREF_WEAK_RC_PTR,
+            )
+            .into_int_value();
+
+            let free_block = env.context.append_basic_block(fn_val, "free");
+            let done_block = env.context.append_basic_block(fn_val, "done");
+
+            builder.new_build_conditional_branch(is_last, free_block, done_block);
+
+            {
+                builder.position_at_end(done_block);
+                builder.new_build_return(None);
+            }
+
+            {
+                builder.position_at_end(free_block);
+
+                // The last box is gone, so its value has not been decremented yet. Give the
+                // allocation back the reference of that box so the usual decrement frees it.
+                refcount_ptr.set_refcount(env, env.ptr_int().const_int(1, false));
+
+                let tag_id = env.context.i8_type().const_zero();
+
+                build_rec_union_recursive_decrement(
+                    env,
+                    layout_interner,
+                    layout_ids,
+                    fn_val,
+                    fn_val,
+                    union_layout,
+                    tags,
+                    value_ptr,
+                    tag_id,
+                    refcount_ptr,
+                    free_block,
+                    DecOrReuse::Dec,
+                )
+            }
+        }
+    }
+}
+
+/// Give the allocation of a value that is about to be freed to the host function `finalizer`,
+/// which has the C signature `void finalizer(void *data)`
+fn call_finalizer<'ctx>(env: &Env<'_, 'ctx, '_>, finalizer: &str, data_ptr: PointerValue<'ctx>) {
+    let function = match env.module.get_function(finalizer) {
+        Some(function) => function,
+        None => {
+            let ptr_type = env.context.ptr_type(AddressSpace::default());
+            let fn_type = env.context.void_type().fn_type(&[ptr_type.into()], false);
+
+            env.module
+                .add_function(finalizer, fn_type, Some(Linkage::External))
+        }
+    };
+
+    env.builder
+        .new_build_call(function, &[data_ptr.into()], "finalize");
+}
+
 enum DecOrReuse {
     Dec,
     Reuse,
@@ -1409,6 +1609,16 @@ fn union_layout_tags<'a>(
             nullable_id: None,
             tags,
         },
+        // the immediate tag is not allocated, like the null tag of a nullable union
+        TaggedPointer { immediate_id, tags } => UnionLayoutTags {
+            nullable_id: Some(*immediate_id),
+            tags: arena.alloc_slice_fill_iter(
+                tags.iter()
+                    .enumerate()
+                    .filter(|(tag_id, _)| *tag_id != *immediate_id as usize)
+                    .map(|(_, fields)| *fields),
+            ),
+        },
         NonRecursive(tags) => UnionLayoutTags {
             nullable_id: None,
             tags,
@@ -1416,6 +1626,21 @@ fn union_layout_tags<'a>(
     }
 }
 
+/// Whether `value` is a tag that has nothing on the heap, for unions that have such a tag: the
+/// null tag of a nullable union, or the immediate tag of a [UnionLayout::TaggedPointer]
+fn is_unallocated_tag<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    union_layout: &UnionLayout<'_>,
+    value: PointerValue<'ctx>,
+) -> Option<IntValue<'ctx>> {
+    match union_layout {
+        // clearing the tag id bits of a pointer leaves the immediate bit as it was
+        UnionLayout::TaggedPointer { .. } => Some(tagged_pointer::is_immediate(env, value)),
+        _ if union_layout.is_nullable() => Some(env.builder.new_build_is_null(value, "is_null")),
+        _ => None,
+    }
+}
+
 pub fn build_reset<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
     layout_interner: &STLayoutInterner<'a>,
@@ -1429,7 +1654,15 @@ pub fn build_reset<'a, 'ctx>(
     let fn_name = layout_id.to_symbol_string(Symbol::DEC, &env.interns);
     let fn_name = format!("{fn_name}_reset");
 
-    let dec_function = build_rec_union(env, layout_interner, layout_ids, Mode::Dec, union_layout);
+    // a value with a finalizer is never reset
+    let dec_function = build_rec_union(
+        env,
+        layout_interner,
+        layout_ids,
+        Mode::Dec,
+        union_layout,
+        None,
+    );
 
     let function = match env.module.get_function(fn_name.as_str()) {
         Some(function_value) => function_value,
@@ -1502,13 +1735,11 @@ fn build_reuse_rec_union_help<'a, 'ctx>(
     let should_recurse_block = env.context.append_basic_block(parent, "should_recurse");
 
     let ctx = env.context;
-    if union_layout.is_nullable() {
-        let is_null = env.builder.new_build_is_null(value_ptr, "is_null");
-
+    if let Some(is_unallocated) = is_unallocated_tag(env, &union_layout, value_ptr) {
         let then_block = ctx.append_basic_block(parent, "then");
 
         env.builder
-            .new_build_conditional_branch(is_null, then_block, should_recurse_block);
+            .new_build_conditional_branch(is_unallocated, then_block, should_recurse_block);
 
         {
             env.builder.position_at_end(then_block);
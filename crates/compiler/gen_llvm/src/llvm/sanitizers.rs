@@ -0,0 +1,414 @@
+use inkwell::attributes::{Attribute, AttributeLoc};
+use inkwell::module::Linkage;
+use inkwell::values::{
+    BasicValue, FunctionValue, InstructionOpcode, InstructionValue, IntValue, PointerValue,
+};
+use inkwell::{AddressSpace, IntPredicate};
+use roc_error_macros::internal_error;
+use roc_target::Target;
+
+use super::build::{build_error_check_branch, BuilderExt, ColdBranch, Env, C_CALL_CONV};
+use super::intrinsics::LLVM_TRAP;
+use super::memcpy::get_or_add_function;
+
+/// Prepare the module for LLVM's AddressSanitizer pass.
+///
+/// The pass only instruments functions that have the `sanitize_address` attribute, so it is
+/// added to every function with a body, including the builtins. Memory intrinsics are left as
+/// they are (in particular, they get no `nosanitize` metadata), so the pass replaces them with
+/// their checked `__asan_mem*` versions.
+///
+/// The host must be linked against the AddressSanitizer runtime. This also registers a module
+/// constructor that initializes that runtime before any Roc code can run.
+pub fn add_address_sanitizer(env: &Env<'_, '_, '_>) {
+    let kind_id = Attribute::get_named_enum_kind_id("sanitize_address");
+    debug_assert!(kind_id > 0);
+    let enum_attr = env.context.create_enum_attribute(kind_id, 0);
+
+    for function in env.module.get_functions() {
+        if function.count_basic_blocks() > 0 {
+            function.add_attribute(AttributeLoc::Function, enum_attr);
+        }
+    }
+
+    add_asan_init_constructor(env);
+}
+
+fn add_asan_init_constructor(env: &Env<'_, '_, '_>) {
+    let ctx = env.context;
+    let module = env.module;
+    let builder = env.builder;
+
+    let void_fn_type = ctx.void_type().fn_type(&[], false);
+
+    let asan_init = module.get_function("__asan_init").unwrap_or_else(|| {
+        module.add_function("__asan_init", void_fn_type, Some(Linkage::External))
+    });
+
+    let ctor = module.add_function("roc_asan_init", void_fn_type, Some(Linkage::Internal));
+
+    let entry = ctx.append_basic_block(ctor, "entry");
+    builder.position_at_end(entry);
+
+    // this function has no debug info, so it must not inherit the location of the last one
+    builder.unset_current_debug_location();
+
+    let call = builder.new_build_call(asan_init, &[], "call_asan_init");
+    call.set_call_convention(C_CALL_CONV);
+
+    builder.new_build_return(None);
+
+    // append our constructor to `llvm.global_ctors`, which is an array of
+    // { priority, constructor, associated data }
+    let ptr_type = ctx.ptr_type(AddressSpace::default());
+    let entry_type = ctx.struct_type(
+        &[ctx.i32_type().into(), ptr_type.into(), ptr_type.into()],
+        false,
+    );
+
+    let ctor_entry = entry_type.const_named_struct(&[
+        // the default priority, which is also what the sanitizer pass itself uses
+        ctx.i32_type().const_int(65535, false).into(),
+        ctor.as_global_value().as_pointer_value().into(),
+        ptr_type.const_null().into(),
+    ]);
+
+    if module.get_global("llvm.global_ctors").is_some() {
+        // Nothing in the builtins or the Roc program has a constructor at the moment.
+        // If that changes, the existing entries need to be merged with ours.
+        internal_error!("the module already defines llvm.global_ctors");
+    }
+
+    let ctors = entry_type.const_array(&[ctor_entry]);
+    let global = module.add_global(ctors.get_type(), None, "llvm.global_ctors");
+    global.set_linkage(Linkage::Appending);
+    global.set_initializer(&ctors);
+
+    if cfg!(debug_assertions) {
+        crate::llvm::build::verify_fn(ctor);
+    }
+}
+
+/// Checks that a pointer is aligned, and calls [ALIGNMENT_FAIL] if it is not:
+/// `roc_alignment_check(pointer, alignment - 1, function name, function name length)`
+const ALIGNMENT_CHECK: &str = "roc_alignment_check";
+
+/// Reports a misaligned pointer and traps:
+/// `__roc_alignment_fail(pointer, alignment, function name, function name length)`
+const ALIGNMENT_FAIL: &str = "__roc_alignment_fail";
+
+/// Check the alignment of the pointer of every load and store in the module at runtime, for
+/// `--check-alignment`. Pointers to stack slots and globals are aligned by construction, and are
+/// not checked.
+///
+/// A misaligned access prints the pointer, the alignment it should have had, and the name of the
+/// function that made it, then traps. On SBF this goes to the program log, elsewhere it is
+/// written to stderr with `dprintf`, so the host must link libc.
+pub fn add_alignment_checks(env: &Env<'_, '_, '_>) {
+    let mut accesses = Vec::new();
+
+    for function in env.module.get_functions() {
+        let name = function.get_name().to_string_lossy();
+        if name == ALIGNMENT_CHECK || name == ALIGNMENT_FAIL {
+            continue;
+        }
+
+        for block in function.get_basic_blocks() {
+            let mut next = block.get_first_instruction();
+
+            while let Some(instruction) = next {
+                next = instruction.get_next_instruction();
+
+                if let Some(access) = checked_access(instruction) {
+                    accesses.push((function, instruction, access));
+                }
+            }
+        }
+    }
+
+    // the checks are inserted after the fact, so they are not attributed to any source location
+    env.builder.unset_current_debug_location();
+
+    for (function, instruction, (pointer, align_bytes)) in accesses {
+        env.builder.position_before(&instruction);
+
+        let function_name = function.get_name().to_string_lossy();
+        build_alignment_check(env, pointer, align_bytes, &function_name);
+    }
+}
+
+/// The pointer and alignment of a load or store that needs a check
+fn checked_access<'ctx>(instruction: InstructionValue<'ctx>) -> Option<(PointerValue<'ctx>, u32)> {
+    let pointer_operand = match instruction.get_opcode() {
+        InstructionOpcode::Load => 0,
+        InstructionOpcode::Store => 1,
+        _ => return None,
+    };
+
+    let align_bytes = instruction.get_alignment().ok()?;
+    if align_bytes <= 1 {
+        return None;
+    }
+
+    let pointer = instruction
+        .get_operand(pointer_operand)?
+        .left()?
+        .into_pointer_value();
+
+    let is_alloca = pointer
+        .as_instruction_value()
+        .is_some_and(|pointer| pointer.get_opcode() == InstructionOpcode::Alloca);
+
+    if is_alloca || pointer.is_const() {
+        return None;
+    }
+
+    Some((pointer, align_bytes))
+}
+
+/// Check that `pointer` is aligned to `align_bytes` before it is used, at the builder's position.
+/// `function_name` is reported if the check fails.
+pub(crate) fn build_alignment_check<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    pointer: PointerValue<'ctx>,
+    align_bytes: u32,
+    function_name: &str,
+) {
+    if align_bytes <= 1 {
+        return;
+    }
+
+    let i64_type = env.context.i64_type();
+    let check = get_or_add_alignment_check(env);
+
+    let (name, name_len) = function_name_constant(env, function_name);
+    let mask = i64_type.const_int(align_bytes as u64 - 1, false);
+
+    let call = env.builder.new_build_call(
+        check,
+        &[pointer.into(), mask.into(), name.into(), name_len.into()],
+        "",
+    );
+
+    call.set_call_convention(C_CALL_CONV);
+}
+
+fn get_or_add_alignment_check<'ctx>(env: &Env<'_, 'ctx, '_>) -> FunctionValue<'ctx> {
+    if let Some(check) = env.module.get_function(ALIGNMENT_CHECK) {
+        return check;
+    }
+
+    let ctx = env.context;
+    let builder = env.builder;
+    let ptr_type = ctx.ptr_type(AddressSpace::default());
+    let i64_type = ctx.i64_type();
+
+    let fn_type = ctx.void_type().fn_type(
+        &[
+            ptr_type.into(),
+            i64_type.into(),
+            ptr_type.into(),
+            i64_type.into(),
+        ],
+        false,
+    );
+
+    let check = env
+        .module
+        .add_function(ALIGNMENT_CHECK, fn_type, Some(Linkage::Internal));
+    add_enum_attribute(env, check, "alwaysinline");
+
+    // build the check without disturbing the function that is currently being built
+    let previous_block = builder.get_insert_block();
+    let previous_location = builder.get_current_debug_location();
+    builder.unset_current_debug_location();
+
+    let fail = add_alignment_fail(env);
+
+    let entry = ctx.append_basic_block(check, "entry");
+    let misaligned = ctx.append_basic_block(check, "misaligned");
+    let aligned = ctx.append_basic_block(check, "aligned");
+
+    builder.position_at_end(entry);
+
+    let params = check.get_params();
+    let pointer = params[0].into_pointer_value();
+    let mask = params[1].into_int_value();
+
+    let address = builder.new_build_ptr_to_int(pointer, i64_type, "address");
+    let low_bits = builder.new_build_and(address, mask, "low_bits");
+    let is_aligned = builder.new_build_int_compare(
+        IntPredicate::EQ,
+        low_bits,
+        i64_type.const_zero(),
+        "is_aligned",
+    );
+
+    build_error_check_branch(env, is_aligned, aligned, misaligned, ColdBranch::Else);
+
+    builder.position_at_end(misaligned);
+
+    let align_bytes = builder.new_build_int_add(mask, i64_type.const_int(1, false), "align_bytes");
+    let call = builder.new_build_call(
+        fail,
+        &[
+            pointer.into(),
+            align_bytes.into(),
+            params[2].into(),
+            params[3].into(),
+        ],
+        "",
+    );
+    call.set_call_convention(C_CALL_CONV);
+    builder.new_build_unreachable();
+
+    builder.position_at_end(aligned);
+    builder.new_build_return(None);
+
+    if let Some(block) = previous_block {
+        builder.position_at_end(block);
+    }
+
+    if let Some(location) = previous_location {
+        builder.set_current_debug_location(location);
+    }
+
+    if cfg!(debug_assertions) {
+        crate::llvm::build::verify_fn(check);
+    }
+
+    check
+}
+
+fn add_alignment_fail<'ctx>(env: &Env<'_, 'ctx, '_>) -> FunctionValue<'ctx> {
+    let ctx = env.context;
+    let builder = env.builder;
+    let ptr_type = ctx.ptr_type(AddressSpace::default());
+    let i32_type = ctx.i32_type();
+    let i64_type = ctx.i64_type();
+
+    let fn_type = ctx.void_type().fn_type(
+        &[
+            ptr_type.into(),
+            i64_type.into(),
+            ptr_type.into(),
+            i64_type.into(),
+        ],
+        false,
+    );
+
+    let fail = env
+        .module
+        .add_function(ALIGNMENT_FAIL, fn_type, Some(Linkage::Internal));
+
+    // keep the failure path out of line, so the checks stay small
+    add_enum_attribute(env, fail, "noinline");
+    add_enum_attribute(env, fail, "cold");
+    add_enum_attribute(env, fail, "noreturn");
+
+    let entry = ctx.append_basic_block(fail, "entry");
+    builder.position_at_end(entry);
+
+    let params = fail.get_params();
+    let pointer = params[0].into_pointer_value();
+    let align_bytes = params[1].into_int_value();
+    let name = params[2].into_pointer_value();
+    let name_len = params[3].into_int_value();
+
+    if matches!(env.target, Target::Sbf) {
+        super::sbf::call_sol_log_const(env, "Roc crashed: misaligned access in");
+        super::sbf::call_sol_log(env, name, name_len);
+
+        // sol_log_64_ prints its five arguments in hex
+        let fn_type = ctx.void_type().fn_type(&[i64_type.into(); 5], false);
+        let sol_log_64 = get_or_add_function(env, "sol_log_64_", fn_type);
+
+        let address = builder.new_build_ptr_to_int(pointer, i64_type, "address");
+        let zero = i64_type.const_zero();
+        let call = builder.new_build_call(
+            sol_log_64,
+            &[
+                address.into(),
+                align_bytes.into(),
+                zero.into(),
+                zero.into(),
+                zero.into(),
+            ],
+            "",
+        );
+        call.set_call_convention(C_CALL_CONV);
+    } else {
+        let fn_type = i32_type.fn_type(&[i32_type.into(), ptr_type.into()], true);
+        let dprintf = get_or_add_function(env, "dprintf", fn_type);
+
+        let format = builder
+            .build_global_string_ptr(
+                "Roc crashed: misaligned access to %p, which must be %llu-byte aligned, in %.*s\n",
+                "alignment_fail_format",
+            )
+            .unwrap();
+
+        let stderr = i32_type.const_int(2, false);
+        let name_len = builder.new_build_int_cast(name_len, i32_type, "name_len");
+
+        let call = builder.new_build_call(
+            dprintf,
+            &[
+                stderr.into(),
+                format.as_pointer_value().into(),
+                pointer.into(),
+                align_bytes.into(),
+                name_len.into(),
+                name.into(),
+            ],
+            "",
+        );
+        call.set_call_convention(C_CALL_CONV);
+    }
+
+    env.build_intrinsic_call(LLVM_TRAP, &[]);
+    builder.new_build_unreachable();
+
+    if cfg!(debug_assertions) {
+        crate::llvm::build::verify_fn(fail);
+    }
+
+    fail
+}
+
+fn add_enum_attribute(env: &Env<'_, '_, '_>, function: FunctionValue<'_>, name: &str) {
+    let kind_id = Attribute::get_named_enum_kind_id(name);
+    debug_assert!(kind_id > 0);
+
+    function.add_attribute(
+        AttributeLoc::Function,
+        env.context.create_enum_attribute(kind_id, 0),
+    );
+}
+
+/// A pointer to a private constant holding the function name, and its length
+fn function_name_constant<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    function_name: &str,
+) -> (PointerValue<'ctx>, IntValue<'ctx>) {
+    let global_name = format!("roc_alignment_check.{function_name}");
+
+    let global = env.module.get_global(&global_name).unwrap_or_else(|| {
+        let value = env.context.const_string(function_name.as_bytes(), false);
+        let global = env.module.add_global(value.get_type(), None, &global_name);
+
+        global.set_initializer(&value);
+        global.set_constant(true);
+        global.set_unnamed_addr(true);
+        global.set_linkage(Linkage::Private);
+
+        global
+    });
+
+    let len = env
+        .context
+        .i64_type()
+        .const_int(function_name.len() as u64, false);
+
+    (global.as_pointer_value(), len)
+}
@@ -0,0 +1,437 @@
+//! Solana syscalls used on the SBF target.
+//!
+//! A Solana program is loaded without a libc, and a minimal host does not define the `roc_*`
+//! functions either, so some operations are lowered directly to the syscalls the runtime
+//! provides.
+
+use bumpalo::collections::Vec;
+use inkwell::attributes::{Attribute, AttributeLoc};
+use inkwell::module::Linkage;
+use inkwell::types::{BasicMetadataTypeEnum, BasicType, BasicTypeEnum};
+use inkwell::values::{
+    BasicMetadataValueEnum, BasicValue, BasicValueEnum, FunctionValue, IntValue, PointerValue,
+};
+use inkwell::{AddressSpace, IntPredicate};
+use roc_region::all::{LineColumn, Region};
+
+use crate::debug_info_init;
+
+use super::build::{create_entry_block_alloca, BuilderExt, Env, C_CALL_CONV};
+use super::convert::zig_str_type;
+use super::memcpy::get_or_add_function;
+
+/// `sol_log_(message: *const u8, len: u64)`
+pub(crate) fn call_sol_log<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    bytes: PointerValue<'ctx>,
+    len: IntValue<'ctx>,
+) {
+    let ptr_type = env.context.ptr_type(AddressSpace::default());
+    let i64_type = env.context.i64_type();
+
+    let fn_type = env
+        .context
+        .void_type()
+        .fn_type(&[ptr_type.into(), i64_type.into()], false);
+    let function = get_or_add_function(env, "sol_log_", fn_type);
+
+    let call = env
+        .builder
+        .new_build_call(function, &[bytes.into(), len.into()], "sol_log_");
+
+    call.set_call_convention(C_CALL_CONV);
+}
+
+/// Log the contents of a `RocStr`, which is held by reference.
+pub(crate) fn call_sol_log_str<'ctx>(env: &Env<'_, 'ctx, '_>, roc_str: PointerValue<'ctx>) {
+    let (bytes, len) = str_bytes_and_len(env, roc_str);
+
+    call_sol_log(env, bytes, len);
+}
+
+/// Log a string that is known at compile time.
+pub(crate) fn call_sol_log_const(env: &Env<'_, '_, '_>, message: &str) {
+    let len = env
+        .context
+        .i64_type()
+        .const_int(message.len() as u64, false);
+
+    call_sol_log(env, const_bytes_ptr(env, message), len);
+}
+
+/// What `roc_dbg` prints in a regular host, but as two log lines: the location and the source
+/// of the `dbg`, then the inspected value. The runtime has no way to write to stderr.
+pub(crate) fn call_sol_log_dbg<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    location: &str,
+    source: &str,
+    message: BasicValueEnum<'ctx>,
+) {
+    call_sol_log_const(env, &format!("[{location}] {source} ="));
+
+    // SBF is a 64-bit target, so strings are held by reference
+    call_sol_log_str(env, message.into_pointer_value());
+}
+
+/// Log the source of an `expect` that failed. There is no parent process that could render
+/// a report, so the caller should abort afterwards.
+pub(crate) fn call_sol_log_failed_expect(env: &Env<'_, '_, '_>, region: Region) {
+    let source = env
+        .debug_info
+        .and_then(|debug_info| debug_info.current_region_source(region));
+
+    match source {
+        Some((path, LineColumn { line, column }, text)) => {
+            call_sol_log_const(
+                env,
+                &format!("This expectation failed in {path}:{line}:{column}:\n\n{text}"),
+            );
+        }
+        None => call_sol_log_const(env, "This expectation failed."),
+    }
+}
+
+/// Log the panic message, then abort the program with `sol_panic_`, which reports the source
+/// location of the procedure that panicked. This is what `roc_panic` does in a host that
+/// defines it, so callers still need to end the block with an `unreachable`.
+pub(crate) fn call_sol_panic<'ctx>(env: &Env<'_, 'ctx, '_>, message: BasicValueEnum<'ctx>) {
+    // SBF is a 64-bit target, so strings are passed by reference
+    call_sol_log_str(env, message.into_pointer_value());
+
+    let location = env
+        .debug_info
+        .and_then(|debug_info| debug_info.current_source_location());
+
+    let i64_type = env.context.i64_type();
+
+    let (file, file_len, line, column) = match location {
+        Some((path, line_column)) => (
+            const_bytes_ptr(env, path),
+            path.len() as u64,
+            line_column.line as u64,
+            line_column.column as u64,
+        ),
+        None => (const_bytes_ptr(env, ""), 0, 0, 0),
+    };
+
+    build_sol_panic_call(
+        env,
+        file,
+        i64_type.const_int(file_len, false),
+        i64_type.const_int(line, false),
+        i64_type.const_int(column, false),
+    );
+}
+
+/// `sol_panic_(file: *const u8, len: u64, line: u64, column: u64) -> !`
+fn build_sol_panic_call<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    file: PointerValue<'ctx>,
+    file_len: IntValue<'ctx>,
+    line: IntValue<'ctx>,
+    column: IntValue<'ctx>,
+) {
+    let ptr_type = env.context.ptr_type(AddressSpace::default());
+    let i64_type = env.context.i64_type();
+
+    let fn_type = env.context.void_type().fn_type(
+        &[
+            ptr_type.into(),
+            i64_type.into(),
+            i64_type.into(),
+            i64_type.into(),
+        ],
+        false,
+    );
+    let function = get_or_add_function(env, "sol_panic_", fn_type);
+
+    let call = env.builder.new_build_call(
+        function,
+        &[file.into(), file_len.into(), line.into(), column.into()],
+        "sol_panic_",
+    );
+
+    call.set_call_convention(C_CALL_CONV);
+}
+
+/// Define `roc_panic` in terms of `sol_log_` and `sol_panic_`, for the builtins that call it.
+/// The builtins don't know where in the Roc source they were called from, so no location is
+/// reported.
+pub(crate) fn add_sol_panic_roc_panic(env: &Env<'_, '_, '_>) {
+    let ctx = env.context;
+    let builder = env.builder;
+
+    // The type of this function (but not the implementation) should have
+    // already been defined by the builtins, which rely on it.
+    let fn_val: FunctionValue = env.module.get_function("roc_panic").unwrap();
+    let roc_str_arg = fn_val.get_first_param().unwrap();
+
+    // the host must not provide its own roc_panic
+    fn_val.set_linkage(Linkage::Internal);
+
+    let subprogram = env.new_subprogram("roc_panic");
+    fn_val.set_subprogram(subprogram);
+
+    let entry = ctx.append_basic_block(fn_val, "entry");
+
+    builder.position_at_end(entry);
+
+    debug_info_init!(env, fn_val);
+
+    call_sol_log_str(env, roc_str_arg.into_pointer_value());
+
+    let i64_zero = ctx.i64_type().const_zero();
+    build_sol_panic_call(env, const_bytes_ptr(env, ""), i64_zero, i64_zero, i64_zero);
+
+    builder.new_build_unreachable();
+
+    if cfg!(debug_assertions) {
+        crate::llvm::build::verify_fn(fn_val);
+    }
+}
+
+/// The bytes and length of a `RocStr`, taking the small string optimization into account.
+fn str_bytes_and_len<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    roc_str: PointerValue<'ctx>,
+) -> (PointerValue<'ctx>, IntValue<'ctx>) {
+    let builder = env.builder;
+    let str_type = zig_str_type(env);
+    let ptr_type = env.context.ptr_type(AddressSpace::default());
+    let i64_type = env.context.i64_type();
+
+    let bytes_ptr = builder.new_build_struct_gep(str_type, roc_str, 0, "str_bytes_ptr");
+    let bytes = builder
+        .new_build_load(ptr_type, bytes_ptr, "str_bytes")
+        .into_pointer_value();
+
+    let length_ptr = builder.new_build_struct_gep(str_type, roc_str, 1, "str_length_ptr");
+    let length = builder
+        .new_build_load(i64_type, length_ptr, "str_length")
+        .into_int_value();
+
+    let capacity_ptr = builder.new_build_struct_gep(str_type, roc_str, 2, "str_capacity_ptr");
+    let capacity = builder
+        .new_build_load(i64_type, capacity_ptr, "str_capacity")
+        .into_int_value();
+
+    // a small string has the high bit of its capacity set, and stores its bytes inline
+    let is_small = builder.new_build_int_compare(
+        IntPredicate::SLT,
+        capacity,
+        i64_type.const_zero(),
+        "is_small_str",
+    );
+
+    // the length of a small string is in the last byte, next to the marker bit
+    let small_len = builder.new_build_and(
+        builder.new_build_right_shift(capacity, i64_type.const_int(56, false), false, "last_byte"),
+        i64_type.const_int(0x7f, false),
+        "small_str_len",
+    );
+
+    // the high bit of the length of a big string marks a seamless slice
+    let big_len = builder.new_build_and(
+        length,
+        i64_type.const_int(i64::MAX as u64, false),
+        "big_str_len",
+    );
+
+    let len = builder
+        .new_build_select(is_small, small_len, big_len, "str_len")
+        .into_int_value();
+    let bytes = builder
+        .new_build_select(is_small, roc_str, bytes, "str_bytes")
+        .into_pointer_value();
+
+    (bytes, len)
+}
+
+/// A pointer to a private constant holding `string`, without a NULL terminator.
+fn const_bytes_ptr<'ctx>(env: &Env<'_, 'ctx, '_>, string: &str) -> PointerValue<'ctx> {
+    let name = format!("sol_str.{string}");
+
+    let global = env.module.get_global(&name).unwrap_or_else(|| {
+        let value = env.context.const_string(string.as_bytes(), false);
+        let global = env.module.add_global(value.get_type(), None, &name);
+
+        global.set_initializer(&value);
+        global.set_constant(true);
+        global.set_unnamed_addr(true);
+        global.set_linkage(Linkage::Private);
+
+        global
+    });
+
+    global.as_pointer_value()
+}
+
+/// Zig has no C ABI for SBF, so the builtins take and return their aggregates by value. The rest
+/// of the backend calls them like on the other 64-bit targets: aggregates are passed by reference,
+/// a `RocDec` as an `i128`, and larger return values through a pointer in the first argument.
+///
+/// Returns a function that can be called with `args`: `function` itself when the two agree, or
+/// else an internal wrapper that converts between them.
+pub(crate) fn adapt_bitcode_fn<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    function: FunctionValue<'ctx>,
+    args: &[BasicValueEnum<'ctx>],
+) -> FunctionValue<'ctx> {
+    let fn_type = function.get_type();
+    let param_types = fn_type.get_param_types();
+    let return_type = fn_type.get_return_type();
+
+    // the caller expects the return value to be written to its first argument
+    let returns_by_pointer = return_type.is_some() && args.len() == param_types.len() + 1;
+    let value_args = if returns_by_pointer { &args[1..] } else { args };
+
+    // a struct with a single field, like a RocDec, is returned as that field
+    let unwrapped_return = match return_type {
+        Some(BasicTypeEnum::StructType(struct_type))
+            if !returns_by_pointer && struct_type.count_fields() == 1 =>
+        {
+            struct_type.get_field_type_at_index(0)
+        }
+        _ => None,
+    };
+
+    let args_agree = value_args.len() == param_types.len()
+        && value_args
+            .iter()
+            .zip(param_types.iter())
+            .all(|(arg, param_type)| arg.get_type() == *param_type);
+
+    if args_agree && !returns_by_pointer && unwrapped_return.is_none() {
+        return function;
+    }
+
+    let wrapper_name = format!("{}.sbf_abi", function.get_name().to_str().unwrap());
+
+    if let Some(wrapper) = env.module.get_function(&wrapper_name) {
+        return wrapper;
+    }
+
+    let ctx = env.context;
+    let builder = env.builder;
+
+    let wrapper_param_types = Vec::from_iter_in(
+        args.iter()
+            .map(|arg| BasicMetadataTypeEnum::from(arg.get_type())),
+        env.arena,
+    );
+
+    let wrapper_type = match (returns_by_pointer, unwrapped_return, return_type) {
+        (true, _, _) | (false, _, None) => ctx.void_type().fn_type(&wrapper_param_types, false),
+        (false, Some(field_type), _) => field_type.fn_type(&wrapper_param_types, false),
+        (false, None, Some(return_type)) => return_type.fn_type(&wrapper_param_types, false),
+    };
+
+    let wrapper = env
+        .module
+        .add_function(&wrapper_name, wrapper_type, Some(Linkage::Internal));
+
+    let kind_id = Attribute::get_named_enum_kind_id("alwaysinline");
+    debug_assert!(kind_id > 0);
+    wrapper.add_attribute(
+        AttributeLoc::Function,
+        ctx.create_enum_attribute(kind_id, 0),
+    );
+
+    // build the wrapper without disturbing the function that is currently being built
+    let previous_block = builder.get_insert_block();
+    let previous_location = builder.get_current_debug_location();
+    builder.unset_current_debug_location();
+
+    let entry = ctx.append_basic_block(wrapper, "entry");
+    builder.position_at_end(entry);
+
+    let wrapper_params = wrapper.get_params();
+    let (out_ptr, value_params) = if returns_by_pointer {
+        (
+            Some(wrapper_params[0].into_pointer_value()),
+            &wrapper_params[1..],
+        )
+    } else {
+        (None, &wrapper_params[..])
+    };
+
+    let call_args = Vec::from_iter_in(
+        value_params
+            .iter()
+            .zip(param_types.iter())
+            .map(|(param, param_type)| {
+                BasicMetadataValueEnum::from(coerce_through_memory(env, *param, *param_type))
+            }),
+        env.arena,
+    );
+
+    let call = builder.new_build_call(function, &call_args, "call_builtin");
+    call.set_call_convention(function.get_call_conventions());
+
+    match call.try_as_basic_value().left() {
+        Some(result) => match (out_ptr, unwrapped_return) {
+            (Some(out_ptr), _) => {
+                builder.new_build_store(out_ptr, result);
+                builder.new_build_return(None);
+            }
+            (None, Some(_)) => {
+                let field = builder
+                    .build_extract_value(result.into_struct_value(), 0, "unwrap_return")
+                    .unwrap();
+                builder.new_build_return(Some(&field));
+            }
+            (None, None) => {
+                builder.new_build_return(Some(&result));
+            }
+        },
+        None => {
+            builder.new_build_return(None);
+        }
+    }
+
+    if let Some(block) = previous_block {
+        builder.position_at_end(block);
+    }
+
+    if let Some(location) = previous_location {
+        builder.set_current_debug_location(location);
+    }
+
+    if cfg!(debug_assertions) {
+        crate::llvm::build::verify_fn(wrapper);
+    }
+
+    wrapper
+}
+
+/// Reinterpret `value` as `target_type`: a value that is expected behind a pointer is spilled to
+/// the stack, a pointer to a value is loaded, and values of the same size are cast through memory.
+fn coerce_through_memory<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    value: BasicValueEnum<'ctx>,
+    target_type: BasicTypeEnum<'ctx>,
+) -> BasicValueEnum<'ctx> {
+    let builder = env.builder;
+    let value_type = value.get_type();
+
+    if value_type == target_type {
+        value
+    } else if value_type.is_pointer_type() {
+        builder.new_build_load(target_type, value.into_pointer_value(), "load_arg")
+    } else {
+        let alloca = create_entry_block_alloca(env, value_type, "sbf_arg");
+        alloca
+            .as_instruction_value()
+            .unwrap()
+            .set_alignment(16)
+            .unwrap();
+
+        builder.new_build_store(alloca, value);
+
+        if target_type.is_pointer_type() {
+            alloca.into()
+        } else {
+            builder.new_build_load(target_type, alloca, "cast_arg")
+        }
+    }
+}
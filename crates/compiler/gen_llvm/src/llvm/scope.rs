@@ -2,10 +2,11 @@ use inkwell::{
     basic_block::BasicBlock,
     values::{BasicValue, BasicValueEnum, FunctionValue, PhiValue, PointerValue},
 };
-use roc_collections::ImMap;
+use roc_collections::{ImMap, ImSet};
+use roc_module::low_level::LowLevel;
 use roc_module::symbol::{ModuleId, Symbol};
 use roc_mono::{
-    ir::{JoinPointId, Param, ProcLayout},
+    ir::{Call, CallType, Expr, JoinPointId, Param, ProcLayout},
     layout::InLayout,
 };
 
@@ -14,11 +15,90 @@ pub(crate) struct Scope<'a, 'ctx> {
     symbols: ImMap<Symbol, (InLayout<'a>, BasicValueEnum<'ctx>)>,
     top_level_thunks: ImMap<Symbol, (ProcLayout<'a>, FunctionValue<'ctx>)>,
     join_points: ImMap<JoinPointId, (BasicBlock<'ctx>, Vec<JoinPointArg<'ctx>>)>,
+    /// Lists that are built on the stack, see [super::stack_alloc]
+    stack_lists: ImSet<Symbol>,
+    /// The pure expressions that were emitted, with the block they were emitted in
+    pure_exprs: ImMap<PureExpr<'a>, (BasicBlock<'ctx>, BasicValueEnum<'ctx>)>,
 }
 
 #[derive(Debug)]
 pub(crate) struct JoinPointNotFound;
 
+/// An expression whose value only depends on its operands, by its opcode and operands. When one
+/// is emitted again in the same basic block, the value from the first time is used instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum PureExpr<'a> {
+    StructAtIndex {
+        structure: Symbol,
+        index: u64,
+    },
+    LowLevel {
+        op: LowLevel,
+        arguments: &'a [Symbol],
+    },
+}
+
+impl<'a> PureExpr<'a> {
+    pub fn from_expr(expr: &Expr<'a>) -> Option<Self> {
+        match expr {
+            Expr::StructAtIndex {
+                structure, index, ..
+            } => Some(Self::StructAtIndex {
+                structure: *structure,
+                index: *index,
+            }),
+            Expr::Call(Call {
+                call_type: CallType::LowLevel { op, .. },
+                arguments,
+            }) if is_pure(*op) => Some(Self::LowLevel {
+                op: *op,
+                arguments: *arguments,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// The lowlevels that compute a value from their arguments alone, other than by crashing. Values
+/// are immutable, and a list is passed by value, so none of them read memory that can change.
+fn is_pure(op: LowLevel) -> bool {
+    use LowLevel::*;
+
+    matches!(
+        op,
+        NumAdd
+            | NumAddWrap
+            | NumAddSaturated
+            | NumSub
+            | NumSubWrap
+            | NumSubSaturated
+            | NumMul
+            | NumMulWrap
+            | NumMulSaturated
+            | NumGt
+            | NumGte
+            | NumLt
+            | NumLte
+            | NumDivFrac
+            | NumDivTruncUnchecked
+            | NumDivCeilUnchecked
+            | NumRemUnchecked
+            | NumAbs
+            | NumNeg
+            | NumBitwiseAnd
+            | NumBitwiseXor
+            | NumBitwiseOr
+            | NumShiftLeftBy
+            | NumShiftRightBy
+            | NumShiftRightZfBy
+            | NumIntCast
+            | NumToFloatCast
+            | Not
+            | ListLenU64
+            | ListLenUsize
+    )
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) enum JoinPointArg<'ctx> {
     Alloca(PointerValue<'ctx>),
@@ -59,6 +139,35 @@ impl<'a, 'ctx> Scope<'a, 'ctx> {
         self.symbols.remove(symbol);
     }
 
+    pub fn insert_stack_lists(&mut self, symbols: impl IntoIterator<Item = Symbol>) {
+        self.stack_lists.extend(symbols);
+    }
+
+    pub fn is_stack_list(&self, symbol: &Symbol) -> bool {
+        self.stack_lists.contains(symbol)
+    }
+
+    /// The value of `expr` if it was already emitted in `block`
+    pub fn emitted_pure(
+        &self,
+        expr: &PureExpr<'a>,
+        block: BasicBlock<'ctx>,
+    ) -> Option<BasicValueEnum<'ctx>> {
+        match self.pure_exprs.get(expr) {
+            Some((emitted_in, value)) if *emitted_in == block => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn insert_pure(
+        &mut self,
+        expr: PureExpr<'a>,
+        block: BasicBlock<'ctx>,
+        value: BasicValueEnum<'ctx>,
+    ) {
+        self.pure_exprs.insert(expr, (block, value));
+    }
+
     pub fn retain_top_level_thunks_for_module(&mut self, module_id: ModuleId) {
         self.top_level_thunks
             .retain(|s, _| s.module_id() == module_id);
@@ -0,0 +1,30 @@
+//! Named sections for the constant data of top-level definitions.
+//!
+//! A platform can have the data of some definitions placed in sections of its choosing, so a
+//! linker script can put them where the host expects them. A top-level definition is built as a
+//! procedure that returns its value. The literals in that procedure that can not be built in
+//! place, which are strings too long to be small strings and lists of constant numbers, become
+//! private constant globals, and those are what is placed in the section. A value that is only
+//! computed when the procedure runs has no global to place.
+
+use inkwell::module::Module;
+use inkwell::values::GlobalValue;
+use roc_collections::all::MutSet;
+
+/// The globals the module has so far
+pub(crate) fn globals<'ctx>(module: &Module<'ctx>) -> MutSet<GlobalValue<'ctx>> {
+    module.get_globals().collect()
+}
+
+/// Place the constant globals that were added to the module after `before` in `section`
+pub(crate) fn place_new_constants<'ctx>(
+    module: &Module<'ctx>,
+    before: &MutSet<GlobalValue<'ctx>>,
+    section: &str,
+) {
+    for global in module.get_globals() {
+        if global.is_constant() && !before.contains(&global) {
+            global.set_section(Some(section));
+        }
+    }
+}
@@ -0,0 +1,282 @@
+//! Build list literals that never leave their procedure on the stack.
+//!
+//! A list literal normally gets a heap allocation, which is freed when its refcount drops to zero.
+//! When the list is only ever read in the procedure that creates it, that allocation can live in
+//! the stack frame instead. The list gets a refcount of 0, which the builtins treat as "constant"
+//! (see `rcConstant` in utils.zig): they never change it or free the list, and never consider the
+//! list unique, so any attempt at updating it in place makes a (heap) copy. The refcount
+//! statements for the list itself are skipped altogether.
+//!
+//! The analysis is conservative and stays within one procedure: a list escapes when it is passed
+//! to any call other than a lowlevel that borrows it, when it is stored in another value, or when
+//! it is returned or passed to a join point. Since the list is never decremented, its elements
+//! are never decremented either, so only lists whose elements are not refcounted qualify.
+//!
+//! This is enabled with `--optimize-stack-alloc`.
+
+use inkwell::values::{BasicValue, BasicValueEnum};
+use roc_collections::all::MutSet;
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+use roc_mono::inc_dec::{lowlevel_borrow_signature, Ownership};
+use roc_mono::ir::{CallType, Expr, ListLiteralElement, Proc, Stmt};
+use roc_mono::layout::{InLayout, LayoutInterner, STLayoutInterner};
+
+use super::build::{create_entry_block_alloca, store_list_literal_elements, BuilderExt, Env};
+use super::build_list::store_list;
+use super::scope::Scope;
+
+/// Larger lists stay on the heap, so they don't blow up the stack frame (which can be at most 4KB
+/// on SBF)
+const MAX_STACK_LIST_BYTES: u32 = 256;
+
+/// The list literals of `proc` that can be built on the stack.
+pub(crate) fn non_escaping_lists<'a>(
+    layout_interner: &STLayoutInterner<'a>,
+    proc: &Proc<'a>,
+) -> MutSet<Symbol> {
+    let mut lists = MutSet::default();
+    find_candidates(layout_interner, &proc.body, &mut lists);
+
+    if !lists.is_empty() {
+        remove_escaping(&proc.body, &mut lists);
+    }
+
+    lists
+}
+
+fn find_candidates<'a>(
+    layout_interner: &STLayoutInterner<'a>,
+    stmt: &Stmt<'a>,
+    candidates: &mut MutSet<Symbol>,
+) {
+    match stmt {
+        Stmt::Let(symbol, expr, _, continuation) => {
+            if let Expr::Array { elem_layout, elems } = expr {
+                let bytes = elems.len() as u32 * layout_interner.stack_size(*elem_layout);
+
+                if !elems.is_empty()
+                    && bytes <= MAX_STACK_LIST_BYTES
+                    && !layout_interner.contains_refcounted(*elem_layout)
+                {
+                    candidates.insert(*symbol);
+                }
+            }
+
+            find_candidates(layout_interner, continuation, candidates);
+        }
+        Stmt::Switch {
+            branches,
+            default_branch,
+            ..
+        } => {
+            for (_, _, branch) in branches.iter() {
+                find_candidates(layout_interner, branch, candidates);
+            }
+
+            find_candidates(layout_interner, default_branch.1, candidates);
+        }
+        Stmt::Join {
+            body, remainder, ..
+        } => {
+            find_candidates(layout_interner, body, candidates);
+            find_candidates(layout_interner, remainder, candidates);
+        }
+        Stmt::Refcounting(_, continuation)
+        | Stmt::Expect {
+            remainder: continuation,
+            ..
+        }
+        | Stmt::Dbg {
+            remainder: continuation,
+            ..
+        } => find_candidates(layout_interner, continuation, candidates),
+        Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => {}
+    }
+}
+
+fn remove_escaping(stmt: &Stmt<'_>, lists: &mut MutSet<Symbol>) {
+    match stmt {
+        Stmt::Let(_, expr, _, continuation) => {
+            remove_escaping_in_expr(expr, lists);
+            remove_escaping(continuation, lists);
+        }
+        Stmt::Switch {
+            cond_symbol,
+            branches,
+            default_branch,
+            ..
+        } => {
+            lists.remove(cond_symbol);
+
+            for (_, _, branch) in branches.iter() {
+                remove_escaping(branch, lists);
+            }
+
+            remove_escaping(default_branch.1, lists);
+        }
+        Stmt::Ret(symbol) | Stmt::Crash(symbol, _) => {
+            lists.remove(symbol);
+        }
+        // the refcount statements of a list on the stack are skipped
+        Stmt::Refcounting(_, continuation) => remove_escaping(continuation, lists),
+        Stmt::Expect {
+            condition,
+            lookups,
+            remainder,
+            ..
+        } => {
+            lists.remove(condition);
+            remove_all(lookups, lists);
+            remove_escaping(remainder, lists);
+        }
+        Stmt::Dbg {
+            symbol, remainder, ..
+        } => {
+            lists.remove(symbol);
+            remove_escaping(remainder, lists);
+        }
+        Stmt::Join {
+            body, remainder, ..
+        } => {
+            remove_escaping(body, lists);
+            remove_escaping(remainder, lists);
+        }
+        Stmt::Jump(_, arguments) => remove_all(arguments, lists),
+    }
+}
+
+fn remove_escaping_in_expr(expr: &Expr<'_>, lists: &mut MutSet<Symbol>) {
+    match expr {
+        Expr::Literal(_) | Expr::NullPointer | Expr::EmptyArray | Expr::FunctionPointer { .. } => {}
+        Expr::Call(call) => match &call.call_type {
+            CallType::LowLevel { op, .. } => match op {
+                // inserted after borrow inference, so they have no borrow signature
+                LowLevel::PtrClearTagId
+                | LowLevel::RefCountIncRcPtr
+                | LowLevel::RefCountDecRcPtr
+                | LowLevel::RefCountIncDataPtr
+                | LowLevel::RefCountDecDataPtr
+                | LowLevel::RefCountIsUnique
+                | LowLevel::SetJmp
+                | LowLevel::LongJmp
+                | LowLevel::SetLongJmpBuffer => remove_all(call.arguments, lists),
+                _ => {
+                    let borrow_signature = lowlevel_borrow_signature(*op);
+
+                    // a borrowed list is only read, and nothing it returns points into the list
+                    for (argument, ownership) in call.arguments.iter().zip(borrow_signature) {
+                        if let Ownership::Owned = ownership {
+                            lists.remove(argument);
+                        }
+                    }
+                }
+            },
+            CallType::ByPointer { pointer, .. } => {
+                lists.remove(pointer);
+                remove_all(call.arguments, lists);
+            }
+            CallType::HigherOrder(higher_order) => {
+                lists.remove(&higher_order.passed_function.captured_environment);
+                remove_all(call.arguments, lists);
+            }
+            // the callee may hold on to its arguments
+            CallType::ByName { .. } | CallType::Foreign { .. } => {
+                remove_all(call.arguments, lists);
+            }
+        },
+        Expr::Tag { arguments, .. } | Expr::Struct(arguments) => remove_all(arguments, lists),
+        Expr::Array { elems, .. } => {
+            for elem in elems.iter() {
+                if let ListLiteralElement::Symbol(symbol) = elem {
+                    lists.remove(symbol);
+                }
+            }
+        }
+        Expr::StructAtIndex { structure, .. }
+        | Expr::GetTagId { structure, .. }
+        | Expr::UnionAtIndex { structure, .. }
+        | Expr::GetElementPointer { structure, .. } => {
+            lists.remove(structure);
+        }
+        Expr::ErasedMake { value, callee } => {
+            if let Some(value) = value {
+                lists.remove(value);
+            }
+
+            lists.remove(callee);
+        }
+        Expr::ErasedLoad { symbol, .. }
+        | Expr::Reset { symbol, .. }
+        | Expr::ResetRef { symbol, .. } => {
+            lists.remove(symbol);
+        }
+        Expr::Alloca { initializer, .. } => {
+            if let Some(initializer) = initializer {
+                lists.remove(initializer);
+            }
+        }
+    }
+}
+
+fn remove_all(symbols: &[Symbol], lists: &mut MutSet<Symbol>) {
+    for symbol in symbols {
+        lists.remove(symbol);
+    }
+}
+
+/// Like `list_literal`, but the elements go in an `alloca`, behind a constant refcount.
+pub(crate) fn stack_list_literal<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &STLayoutInterner<'a>,
+    scope: &Scope<'a, 'ctx>,
+    element_layout: InLayout<'a>,
+    elems: &[ListLiteralElement],
+) -> BasicValueEnum<'ctx> {
+    let ptr_width = env.target.ptr_width() as u32;
+    let element_width = layout_interner.stack_size(element_layout);
+
+    // the same layout as a heap allocation: the refcount is right before the elements, which are
+    // aligned to at least a pointer
+    let alignment = layout_interner
+        .alignment_bytes(element_layout)
+        .max(ptr_width);
+    let bytes = alignment + elems.len() as u32 * element_width;
+
+    let byte_array_type = env.context.i8_type().array_type(bytes);
+    let allocation = create_entry_block_alloca(env, byte_array_type, "stack_list");
+    allocation
+        .as_instruction_value()
+        .unwrap()
+        .set_alignment(alignment)
+        .unwrap();
+
+    let (refcount_ptr, data_ptr) = unsafe {
+        let i8_type = env.context.i8_type();
+        let offset = |bytes: u32| [env.ptr_int().const_int(bytes as u64, false)];
+
+        (
+            env.builder.new_build_in_bounds_gep(
+                i8_type,
+                allocation,
+                &offset(alignment - ptr_width),
+                "stack_list_refcount",
+            ),
+            env.builder.new_build_in_bounds_gep(
+                i8_type,
+                allocation,
+                &offset(alignment),
+                "stack_list_data",
+            ),
+        )
+    };
+
+    env.builder
+        .new_build_store(refcount_ptr, env.ptr_int().const_zero());
+
+    store_list_literal_elements(env, layout_interner, scope, element_layout, data_ptr, elems);
+
+    let length = env.ptr_int().const_int(elems.len() as u64, false);
+
+    store_list(env, data_ptr, length).into()
+}
@@ -0,0 +1,153 @@
+//! The deepest call path of a program, and how much stack its frames take together.
+//!
+//! [roc_mono::stack_frame] looks at every procedure on its own, before any code is generated.
+//! This runs on the optimized LLVM module instead, where inlining has settled which functions
+//! remain, and follows the calls from the functions the host calls into. The frame of a function
+//! is estimated as the sum of its stack slots, as laid out for the target. Registers the code
+//! generator spills are not counted.
+//!
+//! A recursive call can make the stack arbitrarily deep, so a function that is already on the
+//! path is not entered again: the path counts one frame of every function on it.
+
+use inkwell::module::Module;
+use inkwell::targets::TargetData;
+use inkwell::values::{FunctionValue, InstructionOpcode, InstructionValue};
+use roc_collections::all::{MutMap, MutSet};
+
+use super::build::Env;
+use super::compute_budget::{called_function, host_exposed_functions};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CallPath {
+    /// The size of all frames on the path together, in bytes
+    pub depth: u64,
+    /// The functions on the path, starting at the one the host calls, with the size of their
+    /// frame in bytes
+    pub frames: Vec<(String, u64)>,
+}
+
+/// The deepest call path from the functions the host calls into, if it is deeper than
+/// [Env::sbf_stack_limit].
+pub fn check_stack_depth(env: &Env<'_, '_, '_>) -> Option<CallPath> {
+    let limit = env.sbf_stack_limit?;
+
+    let path = deepest_call_path(env.module, &host_exposed_functions(env.module));
+
+    (path.depth > limit as u64).then_some(path)
+}
+
+/// The deepest call path that starts at one of `entry_points`.
+pub fn deepest_call_path<'ctx>(
+    module: &Module<'ctx>,
+    entry_points: &[FunctionValue<'ctx>],
+) -> CallPath {
+    let target_data = TargetData::create(&module.get_data_layout().as_str().to_string_lossy());
+
+    let mut walk = Walk {
+        module,
+        target_data: &target_data,
+        deepest: MutMap::default(),
+        on_path: MutSet::default(),
+    };
+
+    let deepest_entry = entry_points
+        .iter()
+        .map(|function| (walk.depth_of(*function), *function))
+        .max_by_key(|(depth, _)| *depth);
+
+    let (depth, mut next) = match deepest_entry {
+        Some((depth, function)) => (depth, Some(function)),
+        None => (0, None),
+    };
+
+    let mut frames = Vec::new();
+
+    while let Some(function) = next {
+        let (_, frame_size, callee) = walk.deepest[&function];
+
+        frames.push((
+            function.get_name().to_string_lossy().into_owned(),
+            frame_size,
+        ));
+        next = callee;
+    }
+
+    CallPath { depth, frames }
+}
+
+struct Walk<'m, 'ctx> {
+    module: &'m Module<'ctx>,
+    target_data: &'m TargetData,
+    /// The depth of the deepest path from a function, the size of its own frame, and the callee
+    /// that the path continues with
+    deepest: MutMap<FunctionValue<'ctx>, (u64, u64, Option<FunctionValue<'ctx>>)>,
+    on_path: MutSet<FunctionValue<'ctx>>,
+}
+
+impl<'m, 'ctx> Walk<'m, 'ctx> {
+    fn depth_of(&mut self, function: FunctionValue<'ctx>) -> u64 {
+        if let Some((depth, _, _)) = self.deepest.get(&function) {
+            return *depth;
+        }
+
+        self.on_path.insert(function);
+
+        let mut frame_size = 0;
+        let mut deepest_callee: Option<(u64, FunctionValue<'ctx>)> = None;
+
+        for block in function.get_basic_blocks() {
+            let mut next = block.get_first_instruction();
+
+            while let Some(instruction) = next {
+                next = instruction.get_next_instruction();
+
+                if instruction.get_opcode() == InstructionOpcode::Alloca {
+                    frame_size += self.slot_size(instruction);
+                }
+
+                let callee = match called_function(self.module, instruction) {
+                    Some(callee) if callee.count_basic_blocks() > 0 => callee,
+                    _ => continue,
+                };
+
+                if self.on_path.contains(&callee) {
+                    continue;
+                }
+
+                let depth = self.depth_of(callee);
+
+                if deepest_callee.map_or(true, |(deepest, _)| depth > deepest) {
+                    deepest_callee = Some((depth, callee));
+                }
+            }
+        }
+
+        self.on_path.remove(&function);
+
+        let callees_depth = deepest_callee.map_or(0, |(depth, _)| depth);
+        let depth = frame_size + callees_depth;
+
+        self.deepest.insert(
+            function,
+            (depth, frame_size, deepest_callee.map(|(_, callee)| callee)),
+        );
+
+        depth
+    }
+
+    /// The size of the stack slot an `alloca` reserves, in bytes
+    fn slot_size(&self, alloca: InstructionValue<'ctx>) -> u64 {
+        let Ok(slot_type) = alloca.get_allocated_type() else {
+            return 0;
+        };
+
+        // `alloca <type>, <count>` reserves room for `count` values
+        let count = alloca
+            .get_operand(0)
+            .and_then(|operand| operand.left())
+            .and_then(|count| count.into_int_value().get_zero_extended_constant())
+            .unwrap_or(1);
+
+        self.target_data.get_abi_size(&slot_type) * count
+    }
+}
@@ -0,0 +1,124 @@
+//! Check at the start of every procedure that the stack has room left.
+//!
+//! A procedure that recurses too deeply runs off the end of the stack. When there is only one
+//! guard page below the stack, a large enough frame jumps over it, and writes to whatever memory
+//! lies beyond. With `--stack-size`, every procedure first compares its frame address with the
+//! thread-local [STACK_LIMIT], and calls [STACK_OVERFLOW] when it is lower.
+//!
+//! The limit starts out as 0, so nothing is checked until the host sets it. A host that calls Roc
+//! sets it on every such thread, to the lowest address of the thread's stack plus some room: the
+//! frame of the procedure making the check, and whatever `roc_panic` needs, still have to fit
+//! below the limit.
+
+use inkwell::attributes::{Attribute, AttributeLoc};
+use inkwell::module::Linkage;
+use inkwell::values::{FunctionValue, GlobalValue};
+use inkwell::IntPredicate;
+use roc_mono::ir::CrashTag;
+
+use super::build::{
+    build_error_check_branch, build_string_literal, BuilderExt, ColdBranch, Env, C_CALL_CONV,
+};
+use super::intrinsics::LLVM_FRAME_ADDRESS;
+
+/// The thread-local lowest address that the frame of a Roc procedure may start at, as a
+/// pointer-sized integer. The weak definition we provide is 0, which disables the check.
+pub const STACK_LIMIT: &str = "__roc_stack_limit";
+
+/// The function that is called when a procedure starts below [STACK_LIMIT]. It never returns. A
+/// platform can define it to report the overflow its own way; the weak definition we provide
+/// calls `roc_panic`.
+pub const STACK_OVERFLOW: &str = "__roc_stack_overflow";
+
+const STACK_OVERFLOW_MESSAGE: &str = "Stack overflow";
+
+/// Check the stack of `fn_val`, whose entry block the builder is at. The rest of the procedure
+/// goes after the check, so the builder is left at the end of the block that continues it.
+pub(crate) fn build_stack_check<'ctx>(env: &Env<'_, 'ctx, '_>, fn_val: FunctionValue<'ctx>) {
+    let builder = env.builder;
+    let ptr_int = env.ptr_int();
+
+    let limit = builder
+        .new_build_load(ptr_int, stack_limit(env).as_pointer_value(), "stack_limit")
+        .into_int_value();
+
+    let frame_address = env
+        .call_intrinsic(
+            LLVM_FRAME_ADDRESS,
+            &[env.context.i32_type().const_zero().into()],
+        )
+        .into_pointer_value();
+    let frame_address = builder.new_build_ptr_to_int(frame_address, ptr_int, "frame_address");
+
+    let has_room =
+        builder.new_build_int_compare(IntPredicate::UGE, frame_address, limit, "has_room");
+
+    let body = env.context.append_basic_block(fn_val, "body");
+    let overflow = env.context.append_basic_block(fn_val, "stack_overflow");
+
+    build_error_check_branch(env, has_room, body, overflow, ColdBranch::Else);
+
+    builder.position_at_end(overflow);
+
+    let call = builder.new_build_call(stack_overflow_function(env), &[], "stack_overflow");
+    call.set_call_convention(C_CALL_CONV);
+
+    builder.new_build_unreachable();
+
+    builder.position_at_end(body);
+}
+
+fn stack_limit<'ctx>(env: &Env<'_, 'ctx, '_>) -> GlobalValue<'ctx> {
+    if let Some(global) = env.module.get_global(STACK_LIMIT) {
+        return global;
+    }
+
+    let ptr_int = env.ptr_int();
+
+    let global = env.module.add_global(ptr_int, None, STACK_LIMIT);
+    global.set_linkage(Linkage::WeakAny);
+    global.set_thread_local(true);
+    global.set_initializer(&ptr_int.const_zero());
+
+    global
+}
+
+fn stack_overflow_function<'ctx>(env: &Env<'_, 'ctx, '_>) -> FunctionValue<'ctx> {
+    if let Some(function) = env.module.get_function(STACK_OVERFLOW) {
+        return function;
+    }
+
+    let fn_type = env.context.void_type().fn_type(&[], false);
+
+    let function = env
+        .module
+        .add_function(STACK_OVERFLOW, fn_type, Some(Linkage::WeakAny));
+    function.set_call_conventions(C_CALL_CONV);
+
+    for name in ["cold", "noreturn"] {
+        let kind_id = Attribute::get_named_enum_kind_id(name);
+        debug_assert!(kind_id > 0);
+        let enum_attr = env.context.create_enum_attribute(kind_id, 0);
+        function.add_attribute(AttributeLoc::Function, enum_attr);
+    }
+
+    let block = env.builder.get_insert_block().expect("to be in a function");
+    let di_location = env.builder.get_current_debug_location();
+
+    // this function has no subprogram, so its instructions cannot have a debug location
+    env.builder.unset_current_debug_location();
+
+    let entry = env.context.append_basic_block(function, "entry");
+    env.builder.position_at_end(entry);
+
+    let message = build_string_literal(env, STACK_OVERFLOW_MESSAGE);
+    env.call_panic(env, message, CrashTag::Roc);
+    env.builder.new_build_unreachable();
+
+    env.builder.position_at_end(block);
+    if let Some(di_location) = di_location {
+        env.builder.set_current_debug_location(di_location);
+    }
+
+    function
+}
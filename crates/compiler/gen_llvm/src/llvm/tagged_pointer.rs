@@ -0,0 +1,70 @@
+//! The immediate variant of a [UnionLayout::TaggedPointer], which is stored in the union value
+//! itself rather than allocated.
+//!
+//! The union value of the immediate variant is the integer it holds, zero-extended to 64 bits,
+//! with [UnionLayout::IMMEDIATE_BIT] set. That bit is clear in every pointer to the heap, so it
+//! must be checked before the tag id bits of a pointer are read, or the pointer is followed. An
+//! immediate has no refcount, so refcounting treats it like the null pointer of a nullable union.
+
+use inkwell::types::IntType;
+use inkwell::values::{IntValue, PointerValue};
+use inkwell::{AddressSpace, IntPredicate};
+use roc_mono::layout::UnionLayout;
+
+use super::build::{BuilderExt, Env};
+
+/// The union value of the immediate variant that holds `payload`
+pub(crate) fn build_immediate<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    payload: IntValue<'ctx>,
+) -> PointerValue<'ctx> {
+    let word_type = env.context.i64_type();
+
+    let word = env
+        .builder
+        .new_build_int_z_extend(payload, word_type, "immediate_payload");
+    let immediate_bit = word_type.const_int(UnionLayout::IMMEDIATE_BIT, false);
+    let immediate = env
+        .builder
+        .new_build_or(word, immediate_bit, "set_immediate_bit");
+
+    env.builder.new_build_int_to_ptr(
+        immediate,
+        env.context.ptr_type(AddressSpace::default()),
+        "to_immediate",
+    )
+}
+
+/// Whether `value` is the immediate variant, as an `i1`
+pub(crate) fn is_immediate<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    value: PointerValue<'ctx>,
+) -> IntValue<'ctx> {
+    let word_type = env.context.i64_type();
+    let word = env
+        .builder
+        .new_build_ptr_to_int(value, word_type, "to_word");
+
+    // the immediate bit is the sign bit
+    env.builder.new_build_int_compare(
+        IntPredicate::SLT,
+        word,
+        word_type.const_zero(),
+        "is_immediate",
+    )
+}
+
+/// The integer that the immediate variant `value` holds
+pub(crate) fn load_immediate<'ctx>(
+    env: &Env<'_, 'ctx, '_>,
+    value: PointerValue<'ctx>,
+    payload_type: IntType<'ctx>,
+) -> IntValue<'ctx> {
+    let word = env
+        .builder
+        .new_build_ptr_to_int(value, env.context.i64_type(), "to_word");
+
+    // dropping the bits above the payload also drops the immediate bit
+    env.builder
+        .new_build_int_cast(word, payload_type, "immediate_payload")
+}
@@ -0,0 +1,26 @@
+//! Tell Valgrind about the allocations the builtins make from their pools and arenas, see
+//! valgrind.zig in the builtins, which makes the client requests.
+//!
+//! This is enabled with `--valgrind`.
+
+use inkwell::module::Linkage;
+
+use super::build::Env;
+
+/// The builtins make client requests only when this global is defined
+pub const VALGRIND_SYMBOL: &str = "roc__valgrind";
+
+/// Define [VALGRIND_SYMBOL]. The builtins declare it as a weak symbol, so that without
+/// `--valgrind` it is null.
+pub(crate) fn add_valgrind_symbol(env: &Env<'_, '_, '_>) {
+    let i8_type = env.context.i8_type();
+
+    let global = match env.module.get_global(VALGRIND_SYMBOL) {
+        Some(global) => global,
+        None => env.module.add_global(i8_type, None, VALGRIND_SYMBOL),
+    };
+
+    global.set_initializer(&i8_type.const_int(1, false));
+    global.set_linkage(Linkage::Internal);
+    global.set_constant(true);
+}
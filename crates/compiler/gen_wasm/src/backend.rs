@@ -31,7 +31,8 @@ use crate::layout::{ReturnMethod, WasmLayout};
 use crate::low_level::{call_higher_order_lowlevel, LowLevelCall};
 use crate::storage::{AddressValue, Storage, StoredValue, StoredVarKind};
 use crate::{
-    copy_memory, CopyMemoryConfig, Env, DEBUG_SETTINGS, MEMORY_NAME, PTR_SIZE, PTR_TYPE, TARGET,
+    copy_memory, fill_memory, CopyMemoryConfig, Env, FillMemoryConfig, DEBUG_SETTINGS,
+    EXCEPTION_TAG_NAME, MEMORY_NAME, PTR_SIZE, PTR_TYPE, TARGET,
 };
 
 #[derive(Clone, Copy, Debug)]
@@ -40,6 +41,8 @@ pub enum ProcSource {
     Helper,
     /// Wrapper function for higher-order calls from Zig to Roc
     HigherOrderCompare(usize),
+    /// Wrapper function for calls from the host to Roc, that passes Roc exceptions on to `roc_panic`
+    CatchExceptions(usize),
 }
 
 #[derive(Debug)]
@@ -64,6 +67,8 @@ pub struct WasmBackend<'a, 'r> {
     host_lookup: Vec<'a, (&'a str, u32)>,
     helper_proc_gen: CodeGenHelp<'a>,
     can_relocate_heap: bool,
+    /// The tag of the exceptions that Roc crashes throw, if it uses exceptions
+    exception_tag: Option<u32>,
 
     // Function-level data
     pub code_builder: CodeBuilder<'a>,
@@ -112,6 +117,24 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
             )
         }
 
+        let exception_tag = env.exceptions.then(|| {
+            // The values of the exception are the arguments of roc_panic
+            let signature_index = module.types.insert(Signature {
+                param_types: bumpalo::vec![in env.arena; ValueType::I32; 2],
+                ret_type: None,
+            });
+            let tag_index = module.tag.append(signature_index);
+
+            // Export it, so that the host can recognise Roc exceptions
+            module.export.append(Export {
+                name: EXCEPTION_TAG_NAME,
+                ty: ExportType::Tag,
+                index: tag_index,
+            });
+
+            tag_index
+        });
+
         let import_fn_count = module.import.function_count();
         let host_function_count = import_fn_count
             + module.code.dead_import_dummy_count as usize
@@ -134,12 +157,13 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
             host_lookup,
             helper_proc_gen,
             can_relocate_heap: has_heap_base && has_heap_end,
+            exception_tag,
 
             // Function-level data
             block_depth: 0,
             joinpoint_label_map: MutMap::default(),
             code_builder: CodeBuilder::new(env.arena),
-            storage: Storage::new(env.arena),
+            storage: Storage::new(env.arena, env.bulk_memory),
         }
     }
 
@@ -540,6 +564,71 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         self.reset();
     }
 
+    /// Build a wrapper around a Roc proc that the host calls, that catches the exceptions of Roc
+    /// crashes and calls `roc_panic` with their values, while the stack frames they unwound are
+    /// still intact. It has the same signature as the proc.
+    pub fn build_exception_catcher(&mut self, wrapper_lookup_idx: usize, inner_lookup_idx: usize) {
+        let tag_index = self
+            .exception_tag
+            .unwrap_or_else(|| internal_error!("Exception catcher without an exception tag"));
+
+        let ProcLookupData {
+            name: wrapper_name,
+            layout: proc_layout,
+            ..
+        } = self.proc_lookup[wrapper_lookup_idx];
+
+        let mut param_types =
+            Vec::with_capacity_in(proc_layout.arguments.len() + 1, self.env.arena);
+        let ret_type =
+            match WasmLayout::new(self.layout_interner, proc_layout.result).return_method() {
+                ReturnMethod::Primitive(ty, _) => Some(ty),
+                ReturnMethod::NoReturnValue => None,
+                ReturnMethod::WriteToPointerArg => {
+                    param_types.push(PTR_TYPE);
+                    None
+                }
+            };
+        for arg in proc_layout.arguments {
+            param_types.extend_from_slice(WasmLayout::new(self.layout_interner, *arg).arg_types());
+        }
+
+        let ret_local = LocalId(param_types.len() as u32);
+
+        self.code_builder.try_();
+        for i in 0..param_types.len() {
+            self.code_builder.get_local(LocalId(i as u32));
+        }
+        self.code_builder
+            .call(self.fn_index_offset + inner_lookup_idx as u32);
+        if ret_type.is_some() {
+            self.code_builder.set_local(ret_local);
+        }
+        self.code_builder.catch(tag_index);
+        self.call_host_fn_after_loading_args("roc_panic");
+        self.code_builder.unreachable_();
+        self.code_builder.end();
+
+        let local_types = match ret_type {
+            Some(ty) => {
+                self.code_builder.get_local(ret_local);
+                bumpalo::vec![in self.env.arena; ty]
+            }
+            None => bumpalo::vec![in self.env.arena],
+        };
+
+        self.code_builder
+            .build_fn_header_and_footer(&local_types, 0, None);
+
+        self.module.add_function_signature(Signature {
+            param_types,
+            ret_type,
+        });
+
+        self.append_proc_debug_name(wrapper_name);
+        self.reset();
+    }
+
     fn dereference_boxed_value(&mut self, inner: InLayout) {
         use Align::*;
 
@@ -692,6 +781,7 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
                         to_offset: 0,
                         size: *size,
                         alignment_bytes: *alignment_bytes,
+                        bulk_memory: self.env.bulk_memory,
                     },
                 );
             }
@@ -923,9 +1013,13 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         // load the pointer
         self.storage.load_symbols(&mut self.code_builder, &[msg]);
         self.code_builder.i32_const(tag as _);
-        self.call_host_fn_after_loading_args("roc_panic");
 
-        self.code_builder.unreachable_();
+        if let Some(tag_index) = self.exception_tag {
+            self.code_builder.throw(tag_index);
+        } else {
+            self.call_host_fn_after_loading_args("roc_panic");
+            self.code_builder.unreachable_();
+        }
     }
 
     /**********************************************************
@@ -1502,12 +1596,17 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
 
             // Store 12 bytes of zeros { elements: null, length: 0, capacity: 0 }
             debug_assert_eq!(Builtin::LIST_WORDS, 3);
-            self.code_builder.get_local(local_id);
-            self.code_builder.i64_const(0);
-            self.code_builder.i64_store(Align::Bytes4, offset);
-            self.code_builder.get_local(local_id);
-            self.code_builder.i32_const(0);
-            self.code_builder.i32_store(Align::Bytes4, offset + 8);
+            fill_memory(
+                &mut self.code_builder,
+                FillMemoryConfig {
+                    to_ptr: local_id,
+                    to_offset: offset,
+                    size: 3 * PTR_SIZE,
+                    alignment_bytes: PTR_SIZE,
+                    byte: 0,
+                    bulk_memory: self.env.bulk_memory,
+                },
+            );
         } else {
             internal_error!("Unexpected storage for {:?}", sym)
         }
@@ -1643,6 +1742,9 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
                 self.code_builder.else_();
                 Some(local_id)
             }
+            TaggedPointer { .. } => {
+                internal_error!("TaggedPointer unions are only used on 64-bit targets")
+            }
             NullableUnwrapped { nullable_id, .. } => {
                 self.code_builder.i32_const(!(*nullable_id) as i32);
                 self.code_builder.i32_const(*nullable_id as i32);
@@ -1709,6 +1811,9 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
                 other_tags[index]
             }
             NullableUnwrapped { other_fields, .. } => *other_fields,
+            TaggedPointer { .. } => {
+                internal_error!("TaggedPointer unions are only used on 64-bit targets")
+            }
         };
 
         let field_offset: u32 = field_layouts
@@ -1775,6 +1880,9 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
                 other_tags[index]
             }
             NullableUnwrapped { other_fields, .. } => *other_fields,
+            TaggedPointer { .. } => {
+                internal_error!("TaggedPointer unions are only used on 64-bit targets")
+            }
         };
 
         let field_offset: u32 = field_layouts
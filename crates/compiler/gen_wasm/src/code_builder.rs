@@ -4,7 +4,7 @@ use bumpalo::Bump;
 use roc_wasm_module::linking::IndexRelocType;
 
 use roc_error_macros::internal_error;
-use roc_wasm_module::opcodes::{OpCode, OpCode::*};
+use roc_wasm_module::opcodes::{MemoryInstruction, OpCode, OpCode::*};
 use roc_wasm_module::serialize::SerialBuffer;
 use roc_wasm_module::{
     round_up_to_alignment, Align, LocalId, RelocationEntry, ValueType, WasmModule,
@@ -325,6 +325,18 @@ impl<'a> CodeBuilder<'a> {
     pub fn end(&mut self) {
         self.inst(END);
     }
+    /// try, from the exception handling proposal
+    pub fn try_(&mut self) {
+        self.inst_block(TRY);
+    }
+    /// catch, from the exception handling proposal. Pushes the values of the exception.
+    pub fn catch(&mut self, tag_index: u32) {
+        self.inst_imm32(CATCH, tag_index);
+    }
+    /// throw, from the exception handling proposal
+    pub fn throw(&mut self, tag_index: u32) {
+        self.inst_imm32(THROW, tag_index);
+    }
     pub fn br(&mut self, levels: u32) {
         self.inst_imm32(BR, levels);
     }
@@ -420,6 +432,19 @@ impl<'a> CodeBuilder<'a> {
         self.inst(GROWMEMORY);
         self.code.push(0);
     }
+    /// memory.copy, from the bulk memory proposal
+    pub fn memory_copy(&mut self) {
+        self.inst(MEMORY);
+        self.code.encode_u32(MemoryInstruction::MemoryCopy as u32);
+        self.code.push(0); // destination memory
+        self.code.push(0); // source memory
+    }
+    /// memory.fill, from the bulk memory proposal
+    pub fn memory_fill(&mut self) {
+        self.inst(MEMORY);
+        self.code.encode_u32(MemoryInstruction::MemoryFill as u32);
+        self.code.push(0);
+    }
 
     fn log_const<T>(&self, opcode: OpCode, x: T)
     where
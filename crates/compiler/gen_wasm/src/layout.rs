@@ -94,7 +94,8 @@ impl WasmLayout {
                 Recursive(_)
                 | NonNullableUnwrapped(_)
                 | NullableWrapped { .. }
-                | NullableUnwrapped { .. },
+                | NullableUnwrapped { .. }
+                | TaggedPointer { .. },
             )
             | LayoutRepr::Ptr(_)
             | LayoutRepr::RecursivePointer(_) => Self::Primitive(PTR_TYPE, PTR_SIZE),
@@ -14,16 +14,19 @@ use bumpalo::collections::Vec;
 use bumpalo::{self, Bump};
 
 use roc_collections::all::{MutMap, MutSet};
+use roc_error_macros::user_error;
 use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_mono::code_gen_help::CodeGenHelp;
 use roc_mono::ir::{Proc, ProcLayout};
 use roc_mono::layout::{LayoutIds, STLayoutInterner};
 use roc_target::Target;
 use roc_wasm_module::parse::ParseError;
+use roc_wasm_module::sections::ImportDesc;
 use roc_wasm_module::{Align, LocalId, ValueType, WasmModule};
 
 use crate::backend::{ProcLookupData, ProcSource, WasmBackend};
 use crate::code_builder::CodeBuilder;
+use crate::layout::{ReturnMethod, WasmLayout};
 
 const TARGET: Target = Target::Wasm32;
 const PTR_SIZE: u32 = {
@@ -38,12 +41,21 @@ const PTR_SIZE: u32 = {
 const PTR_TYPE: ValueType = ValueType::I32;
 
 pub const MEMORY_NAME: &str = "memory";
+/// The module that a WASI host imports its system calls from
+pub const WASI_MODULE_NAME: &str = "wasi_snapshot_preview1";
+pub const EXCEPTION_TAG_NAME: &str = "roc_exception_tag";
 
 pub struct Env<'a> {
     pub arena: &'a Bump,
     pub module_id: ModuleId,
     pub exposed_to_host: MutSet<Symbol>,
     pub stack_bytes: u32,
+    /// Copy and fill memory with the instructions of the bulk memory proposal, `memory.copy` and
+    /// `memory.fill`, instead of loads and stores
+    pub bulk_memory: bool,
+    /// Crash by throwing an exception of the exception handling proposal, which the functions
+    /// exposed to the host catch and pass on to `roc_panic`
+    pub exceptions: bool,
 }
 
 impl Env<'_> {
@@ -74,11 +86,57 @@ pub fn build_app_binary<'a, 'r>(
 
     wasm_module.eliminate_dead_code(env.arena, called_fns);
 
+    check_wasi_imports(&wasm_module);
+
     let mut buffer = std::vec::Vec::with_capacity(wasm_module.size());
     wasm_module.serialize(&mut buffer);
     buffer
 }
 
+/// A host that runs under WASI imports its system calls from [WASI_MODULE_NAME], and there is no
+/// JavaScript to provide any other import. Whatever is still imported from elsewhere is either a
+/// function that the host was meant to define, like `roc_panic`, or a function of the app that
+/// the host calls but we did not generate.
+fn check_wasi_imports(module: &WasmModule<'_>) {
+    let imports = &module.import.imports;
+
+    if !imports
+        .iter()
+        .any(|import| import.module == WASI_MODULE_NAME)
+    {
+        return;
+    }
+
+    let missing: std::vec::Vec<_> = imports
+        .iter()
+        .filter(|import| {
+            import.module != WASI_MODULE_NAME
+                && matches!(import.description, ImportDesc::Func { .. })
+        })
+        .map(|import| import.name)
+        .collect();
+
+    if missing.is_empty() {
+        return;
+    }
+
+    let (app_fns, host_fns): (std::vec::Vec<_>, std::vec::Vec<_>) = missing
+        .into_iter()
+        .partition(|name| name.starts_with("roc__"));
+
+    if !host_fns.is_empty() {
+        user_error!(
+            "This WASI host does not define these functions, which the app calls:\n    {}\n\nA WASI runtime cannot provide them, so the host has to define them.",
+            host_fns.join("\n    ")
+        );
+    }
+
+    user_error!(
+        "This WASI host calls these functions of the app, which the wasm32 dev backend does not generate:\n    {}\n\nBuild without --dev to use the LLVM backend instead.",
+        app_fns.join("\n    ")
+    );
+}
+
 /// Generate an unserialized Wasm module
 /// Shared by all consumers of gen_wasm: roc_build, roc_repl_wasm, and test_gen
 /// (roc_repl_wasm and test_gen will add more generated code for a wrapper function
@@ -95,6 +153,7 @@ pub fn build_app_module<'a, 'r>(
     let mut proc_lookup = Vec::with_capacity_in(procedures.len() * 2, env.arena);
     let mut host_to_app_map = Vec::with_capacity_in(env.exposed_to_host.len(), env.arena);
     let mut maybe_main_fn_index = None;
+    let mut exception_catchers = Vec::with_capacity_in(0, env.arena);
 
     // Adjust Wasm function indices to account for functions from the object file
     let fn_index_offset: u32 =
@@ -102,10 +161,17 @@ pub fn build_app_module<'a, 'r>(
 
     // Pre-pass over the procedure names & layouts
     // Create a lookup to tell us the final index of each proc in the output file
+    let proc_count = procedures.len();
     for (i, ((sym, proc_layout), proc)) in procedures.into_iter().enumerate() {
-        let fn_index = fn_index_offset + i as u32;
+        let mut fn_index = fn_index_offset + i as u32;
         procs.push(proc);
         if env.exposed_to_host.contains(&sym) {
+            if env.exceptions {
+                // The host calls a wrapper that catches the exceptions, which goes after all the procs
+                fn_index = fn_index_offset + (proc_count + exception_catchers.len()) as u32;
+                exception_catchers.push((i, proc_layout));
+            }
+
             maybe_main_fn_index = Some(fn_index);
 
             let exposed_name = layout_ids
@@ -115,6 +181,18 @@ pub fn build_app_module<'a, 'r>(
             let exposed_name_bump: &'a str = env.arena.alloc_str(&exposed_name);
 
             host_to_app_map.push((exposed_name_bump, fn_index));
+
+            // A value that is written to a pointer in the first argument is returned the same way
+            // by the generic version of the function, so a host that calls that one gets this one
+            if WasmLayout::new(layout_interner, proc_layout.result).return_method()
+                == ReturnMethod::WriteToPointerArg
+            {
+                let generic_name = layout_ids
+                    .get_toplevel(sym, &proc_layout)
+                    .to_exposed_generic_symbol_string(sym, interns);
+
+                host_to_app_map.push((env.arena.alloc_str(&generic_name), fn_index));
+            }
         }
 
         proc_lookup.push(ProcLookupData {
@@ -124,6 +202,18 @@ pub fn build_app_module<'a, 'r>(
         });
     }
 
+    for (inner_idx, proc_layout) in exception_catchers {
+        let inner_name = proc_lookup[inner_idx].name;
+        let ident_ids = interns.all_ident_ids.get_mut(&env.module_id).unwrap();
+        let ident_id = ident_ids.add_str(&format!("#catch#{inner_name:?}"));
+
+        proc_lookup.push(ProcLookupData {
+            name: Symbol::new(env.module_id, ident_id),
+            layout: proc_layout,
+            source: ProcSource::CatchExceptions(inner_idx),
+        });
+    }
+
     let mut backend = WasmBackend::new(
         env,
         layout_interner,
@@ -177,6 +267,7 @@ pub fn build_app_module<'a, 'r>(
             Roc => { /* already generated */ }
             Helper => backend.build_proc(helper_iter.next().unwrap()),
             HigherOrderCompare(inner_idx) => backend.build_higher_order_compare(idx, *inner_idx),
+            CatchExceptions(inner_idx) => backend.build_exception_catcher(idx, *inner_idx),
         }
     }
 
@@ -194,6 +285,20 @@ pub struct CopyMemoryConfig {
     to_offset: u32,
     size: u32,
     alignment_bytes: u32,
+    bulk_memory: bool,
+}
+
+/// With bulk memory, anything bigger than a single load and store is copied or filled with one
+/// instruction
+const BULK_MEMORY_MIN_SIZE: u32 = 8;
+
+/// Push the address `ptr + offset`
+fn push_address(code_builder: &mut CodeBuilder, ptr: LocalId, offset: u32) {
+    code_builder.get_local(ptr);
+    if offset > 0 {
+        code_builder.i32_const(offset as i32);
+        code_builder.i32_add();
+    }
 }
 
 pub fn copy_memory(code_builder: &mut CodeBuilder, config: CopyMemoryConfig) {
@@ -204,6 +309,14 @@ pub fn copy_memory(code_builder: &mut CodeBuilder, config: CopyMemoryConfig) {
         return;
     }
 
+    if config.bulk_memory && config.size > BULK_MEMORY_MIN_SIZE {
+        push_address(code_builder, config.to_ptr, config.to_offset);
+        push_address(code_builder, config.from_ptr, config.from_offset);
+        code_builder.i32_const(config.size as i32);
+        code_builder.memory_copy();
+        return;
+    }
+
     let alignment = Align::from(config.alignment_bytes);
     let mut i = 0;
     while config.size - i >= 8 {
@@ -229,6 +342,52 @@ pub fn copy_memory(code_builder: &mut CodeBuilder, config: CopyMemoryConfig) {
     }
 }
 
+pub struct FillMemoryConfig {
+    to_ptr: LocalId,
+    to_offset: u32,
+    size: u32,
+    alignment_bytes: u32,
+    byte: u8,
+    bulk_memory: bool,
+}
+
+/// Set every byte of a block of memory to the same value, like `memset`
+pub fn fill_memory(code_builder: &mut CodeBuilder, config: FillMemoryConfig) {
+    if config.size == 0 {
+        return;
+    }
+
+    if config.bulk_memory && config.size > BULK_MEMORY_MIN_SIZE {
+        push_address(code_builder, config.to_ptr, config.to_offset);
+        code_builder.i32_const(config.byte as i32);
+        code_builder.i32_const(config.size as i32);
+        code_builder.memory_fill();
+        return;
+    }
+
+    let alignment = Align::from(config.alignment_bytes);
+    let bytes = u64::from_ne_bytes([config.byte; 8]);
+    let mut i = 0;
+    while config.size - i >= 8 {
+        code_builder.get_local(config.to_ptr);
+        code_builder.i64_const(bytes as i64);
+        code_builder.i64_store(alignment, i + config.to_offset);
+        i += 8;
+    }
+    if config.size - i >= 4 {
+        code_builder.get_local(config.to_ptr);
+        code_builder.i32_const(bytes as i32);
+        code_builder.i32_store(alignment, i + config.to_offset);
+        i += 4;
+    }
+    while config.size - i > 0 {
+        code_builder.get_local(config.to_ptr);
+        code_builder.i32_const(config.byte as i32);
+        code_builder.i32_store8(alignment, i + config.to_offset);
+        i += 1;
+    }
+}
+
 pub struct WasmDebugSettings {
     proc_start_end: bool,
     user_procs_ir: bool,
@@ -1399,7 +1399,7 @@ impl<'a> LowLevelCall<'a> {
                 match CodeGenNumType::for_symbol(backend, self.arguments[0]) {
                     F32 => backend.code_builder.f32_div(),
                     F64 => backend.code_builder.f64_div(),
-                    Decimal => self.load_args_and_call_zig(backend, bitcode::DEC_DIV),
+                    Decimal => backend.call_host_fn_after_loading_args(bitcode::DEC_DIV),
                     x => todo!("{:?} for {:?}", self.lowlevel, x),
                 }
             }
@@ -1599,6 +1599,7 @@ impl<'a> LowLevelCall<'a> {
                     }
                     F32 => backend.code_builder.f32_abs(),
                     F64 => backend.code_builder.f64_abs(),
+                    Decimal => backend.call_host_fn_after_loading_args(bitcode::DEC_ABS),
                     _ => todo!("{:?} for {:?}", self.lowlevel, self.ret_layout),
                 }
             }
@@ -1632,7 +1633,7 @@ impl<'a> LowLevelCall<'a> {
                     }
                     F32 => backend.code_builder.f32_neg(),
                     F64 => backend.code_builder.f64_neg(),
-                    Decimal => self.load_args_and_call_zig(backend, bitcode::DEC_NEGATE),
+                    Decimal => backend.call_host_fn_after_loading_args(bitcode::DEC_NEGATE),
                     _ => todo!("{:?} for {:?}", self.lowlevel, self.ret_layout),
                 }
             }
@@ -1703,7 +1704,7 @@ impl<'a> LowLevelCall<'a> {
                             false => IntWidth::U32,
                         };
 
-                        self.load_args_and_call_zig(backend, &bitcode::DEC_FROM_INT[int_width]);
+                        backend.call_host_fn_after_loading_args(&bitcode::DEC_FROM_INT[int_width]);
                     }
                     (Decimal, I64) => {
                         let int_width = match arg_is_signed {
@@ -1711,17 +1712,15 @@ impl<'a> LowLevelCall<'a> {
                             false => IntWidth::U64,
                         };
 
-                        self.load_args_and_call_zig(backend, &bitcode::DEC_FROM_INT[int_width]);
+                        backend.call_host_fn_after_loading_args(&bitcode::DEC_FROM_INT[int_width]);
                     }
                     (Decimal, F32) => {
-                        self.load_args_and_call_zig(
-                            backend,
+                        backend.call_host_fn_after_loading_args(
                             &bitcode::DEC_FROM_FLOAT[FloatWidth::F32],
                         );
                     }
                     (Decimal, F64) => {
-                        self.load_args_and_call_zig(
-                            backend,
+                        backend.call_host_fn_after_loading_args(
                             &bitcode::DEC_FROM_FLOAT[FloatWidth::F64],
                         );
                     }
@@ -2249,6 +2248,15 @@ impl<'a> LowLevelCall<'a> {
                 StoredValue::StackMemory { .. } => { /* do nothing */ }
             },
             DictPseudoSeed => self.load_args_and_call_zig(backend, bitcode::UTILS_DICT_PSEUDO_SEED),
+            ArenaEnter => self.load_args_and_call_zig(backend, bitcode::UTILS_ARENA_ENTER),
+            ArenaExit => self.load_args_and_call_zig(backend, bitcode::UTILS_ARENA_EXIT),
+
+            // weak references need the upper half of a 64-bit refcount
+            WeakRefDowngrade | WeakRefIsAlive | WeakRefGetUnsafe => {
+                const PANIC_MSG: &str = "WeakRef is not yet supported by the wasm backend";
+
+                backend.stmt_internal_error(PANIC_MSG);
+            }
 
             SetJmp | LongJmp | SetLongJmpBuffer => {
                 unreachable!("only inserted in dev backend codegen")
@@ -67,10 +67,11 @@ pub struct Storage<'a> {
     pub symbol_storage_map: MutMap<Symbol, StoredValue>,
     pub stack_frame_pointer: Option<LocalId>,
     pub stack_frame_size: i32,
+    bulk_memory: bool,
 }
 
 impl<'a> Storage<'a> {
-    pub fn new(arena: &'a Bump) -> Self {
+    pub fn new(arena: &'a Bump, bulk_memory: bool) -> Self {
         Storage {
             return_var: None,
             arg_types: Vec::with_capacity_in(8, arena),
@@ -79,6 +80,7 @@ impl<'a> Storage<'a> {
             symbol_storage_map: MutMap::default(),
             stack_frame_pointer: None,
             stack_frame_size: 0,
+            bulk_memory,
         }
     }
 
@@ -408,6 +410,7 @@ impl<'a> Storage<'a> {
                             to_offset,
                             size,
                             alignment_bytes,
+                            bulk_memory: self.bulk_memory,
                         },
                     );
                 }
@@ -481,6 +484,7 @@ impl<'a> Storage<'a> {
                         to_offset,
                         size,
                         alignment_bytes,
+                        bulk_memory: self.bulk_memory,
                     },
                 );
             }
@@ -574,6 +578,7 @@ impl<'a> Storage<'a> {
                         to_offset,
                         size: *from_size,
                         alignment_bytes: *from_alignment_bytes,
+                        bulk_memory: self.bulk_memory,
                     },
                 );
             }
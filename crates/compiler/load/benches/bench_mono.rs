@@ -0,0 +1,113 @@
+use bumpalo::Bump;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use roc_load::{ExecutionMode, FunctionKind, LoadConfig, Threading};
+use roc_packaging::cache::RocCacheDir;
+use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
+use roc_target::Target;
+use std::fmt::Write;
+use std::path::Path;
+
+const MODULE_COUNT: usize = 50;
+
+const PLATFORM: &str = r#"platform "bench"
+    requires {} { main : U64 -> U64 }
+    exposes []
+    packages {}
+    imports []
+    provides [main_for_host]
+
+main_for_host : U64 -> U64
+main_for_host = \n -> main(n)
+"#;
+
+/// Every module specializes its generic helpers at a few layouts of its own, so that each one has
+/// about the same amount of work for the specialization threads.
+fn module_source(index: usize) -> String {
+    format!(
+        r#"module [run]
+
+twice = \f, x -> f(f(x))
+
+sum_with = \list, f -> List.walk(list, 0, \acc, x -> acc + f(x))
+
+run : U64 -> U64
+run = \n ->
+    list = List.range({{ start: At 0, end: Before n }})
+    ints = sum_with(list, \x -> twice(\y -> y * {index} + 1, x))
+    floats = sum_with(List.map(list, Num.to_f64), \x -> x / 2)
+    strs = List.map(list, Num.to_str) |> List.walk(0, \acc, s -> acc + Str.count_utf8_bytes(s))
+
+    ints + Num.floor(floats) + strs
+"#
+    )
+}
+
+fn write_project(dir: &Path) -> std::path::PathBuf {
+    std::fs::create_dir(dir.join("platform")).unwrap();
+    std::fs::write(dir.join("platform").join("main.roc"), PLATFORM).unwrap();
+
+    let mut app = String::from("app [main] { pf: platform \"platform/main.roc\" }\n\n");
+
+    for index in 0..MODULE_COUNT {
+        std::fs::write(dir.join(format!("Mod{index}.roc")), module_source(index)).unwrap();
+        writeln!(app, "import Mod{index}").unwrap();
+    }
+
+    let calls: Vec<_> = (0..MODULE_COUNT)
+        .map(|index| format!("Mod{index}.run(n)"))
+        .collect();
+    write!(app, "\nmain = \\n -> {}\n", calls.join(" + ")).unwrap();
+
+    let app_path = dir.join("app.roc");
+    std::fs::write(&app_path, app).unwrap();
+
+    app_path
+}
+
+pub fn mono_benchmark(c: &mut Criterion) {
+    let dir = tempfile::tempdir().unwrap();
+    let app_path = write_project(dir.path());
+
+    let mut group = c.benchmark_group(format!("monomorphize {MODULE_COUNT} modules"));
+    group.sample_size(10);
+
+    for (name, threading) in [
+        ("single-threaded", Threading::Single),
+        ("all threads", Threading::AllAvailable),
+    ] {
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| {
+                let arena = Bump::new();
+
+                let load_config = LoadConfig {
+                    target: Target::default(),
+                    function_kind: FunctionKind::LambdaSet,
+                    render: RenderTarget::Generic,
+                    palette: DEFAULT_PALETTE,
+                    threading,
+                    exec_mode: ExecutionMode::Executable,
+                    expect_filter: None,
+                    finalizers: Vec::new(),
+                    types_cache_dir: None,
+                    fold_constants: true,
+                };
+
+                let loaded = roc_load::load_and_monomorphize(
+                    &arena,
+                    app_path.clone(),
+                    None,
+                    RocCacheDir::Disallowed,
+                    load_config,
+                )
+                .unwrap_or_else(|_| panic!("the benchmark project did not compile"));
+
+                black_box(loaded.procedures.len());
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, mono_benchmark);
+criterion_main!(benches);
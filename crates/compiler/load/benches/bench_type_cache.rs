@@ -0,0 +1,145 @@
+use bumpalo::Bump;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use roc_load::{ExecutionMode, FunctionKind, LoadConfig, Threading};
+use roc_packaging::cache::RocCacheDir;
+use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
+use roc_target::Target;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+const MODULE_COUNT: usize = 60;
+
+/// Every module but the first imports the one before it, so that a module is only solved once
+/// the types of the modules it imports are known.
+fn module_source(index: usize) -> String {
+    let (import, previous) = match index.checked_sub(1) {
+        Some(previous) => (
+            format!("import Mod{previous}\n"),
+            format!("Mod{previous}.run(n)"),
+        ),
+        None => (String::new(), "n".to_string()),
+    };
+
+    format!(
+        r#"module [run]
+
+{import}
+twice = \f, x -> f(f(x))
+
+sum_with = \list, f -> List.walk(list, 0, \acc, x -> acc + f(x))
+
+run : U64 -> U64
+run = \n ->
+    list = List.range({{ start: At 0, end: Before {previous} }})
+    ints = sum_with(list, \x -> twice(\y -> y * {index} + 1, x))
+    strs = List.map(list, Num.to_str) |> List.walk(0, \acc, s -> acc + Str.count_utf8_bytes(s))
+
+    ints + strs
+"#
+    )
+}
+
+/// The module that changes between builds, which imports all the others
+fn main_source(edit: usize) -> String {
+    let mut main = String::from("module [main]\n\n");
+
+    for index in 0..MODULE_COUNT {
+        writeln!(main, "import Mod{index}").unwrap();
+    }
+
+    let calls: Vec<_> = (0..MODULE_COUNT)
+        .map(|index| format!("Mod{index}.run(n)"))
+        .collect();
+    write!(main, "\nmain = \\n -> {} + {edit}\n", calls.join(" + ")).unwrap();
+
+    main
+}
+
+fn write_project(dir: &Path) -> PathBuf {
+    for index in 0..MODULE_COUNT {
+        std::fs::write(dir.join(format!("Mod{index}.roc")), module_source(index)).unwrap();
+    }
+
+    let main_path = dir.join("Main.roc");
+    std::fs::write(&main_path, main_source(0)).unwrap();
+
+    main_path
+}
+
+fn check(main_path: &Path, types_cache_dir: Option<&Path>) {
+    let arena = Bump::new();
+
+    let load_config = LoadConfig {
+        target: Target::default(),
+        function_kind: FunctionKind::LambdaSet,
+        render: RenderTarget::Generic,
+        palette: DEFAULT_PALETTE,
+        threading: Threading::AllAvailable,
+        exec_mode: ExecutionMode::Check,
+        expect_filter: None,
+        finalizers: Vec::new(),
+        types_cache_dir: types_cache_dir.map(Path::to_path_buf),
+        fold_constants: true,
+    };
+
+    let loaded = roc_load::load_and_typecheck(
+        &arena,
+        main_path.to_path_buf(),
+        None,
+        RocCacheDir::Disallowed,
+        load_config,
+    )
+    .unwrap_or_else(|_| panic!("the benchmark project did not type check"));
+
+    black_box(loaded.timings.len());
+}
+
+pub fn type_cache_benchmark(c: &mut Criterion) {
+    let dir = tempfile::tempdir().unwrap();
+    let main_path = write_project(dir.path());
+
+    let mut group = c.benchmark_group(format!("check {MODULE_COUNT} modules"));
+    group.sample_size(10);
+
+    group.bench_function(BenchmarkId::from_parameter("without the cache"), |b| {
+        b.iter(|| check(&main_path, None))
+    });
+
+    group.bench_function(BenchmarkId::from_parameter("empty cache"), |b| {
+        b.iter_batched(
+            || tempfile::tempdir().unwrap(),
+            |cache_dir| {
+                check(&main_path, Some(cache_dir.path()));
+
+                // removed outside of the measurement
+                cache_dir
+            },
+            BatchSize::PerIteration,
+        )
+    });
+
+    let cache_dir = tempfile::tempdir().unwrap();
+    check(&main_path, Some(cache_dir.path()));
+
+    group.bench_function(BenchmarkId::from_parameter("nothing changed"), |b| {
+        b.iter(|| check(&main_path, Some(cache_dir.path())))
+    });
+
+    // the common case: only the module that imports all the others changed since the last build
+    let mut edit = 0;
+    group.bench_function(BenchmarkId::from_parameter("main changed"), |b| {
+        b.iter_batched(
+            || {
+                edit += 1;
+                std::fs::write(&main_path, main_source(edit)).unwrap();
+            },
+            |()| check(&main_path, Some(cache_dir.path())),
+            BatchSize::PerIteration,
+        )
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, type_cache_benchmark);
+criterion_main!(benches);
@@ -25,6 +25,8 @@ const MODULES: &[(ModuleId, &str)] = &[
     (ModuleId::DECODE, "Decode.roc"),
     (ModuleId::HASH, "Hash.roc"),
     (ModuleId::INSPECT, "Inspect.roc"),
+    (ModuleId::JSON, "Json.roc"),
+    (ModuleId::WEAK_REF, "WeakRef.roc"),
 ];
 
 fn main() {
@@ -79,7 +81,9 @@ fn write_types_for_module_real(module_id: ModuleId, filename: &str, output_path:
         Default::default(),
         target,
         function_kind,
-        roc_reporting::report::RenderTarget::ColorTerminal,
+        roc_reporting::report::RenderTarget::ColorTerminal(
+            roc_reporting::report::ColorChoice::Always,
+        ),
         roc_reporting::report::DEFAULT_PALETTE,
         RocCacheDir::Disallowed,
         Threading::AllAvailable,
@@ -100,6 +104,9 @@ fn write_types_for_module_real(module_id: ModuleId, filename: &str, output_path:
         &module.interns,
         &mut module.can_problems,
         &mut module.type_problems,
+        roc_reporting::report::RenderTarget::ColorTerminal(
+            roc_reporting::report::ColorChoice::Always,
+        ),
     );
 
     if problems.errors + problems.warnings > 0 {
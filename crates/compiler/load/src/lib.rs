@@ -71,6 +71,10 @@ pub fn load_single_threaded<'a>(
         render,
         palette,
         exec_mode,
+        None,
+        Vec::new(),
+        None,
+        true,
         roc_cache_dir,
     )
 }
@@ -256,6 +260,8 @@ fn read_cached_types() -> MutMap<ModuleId, TypeState> {
     let mod_decode = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Decode.dat"));
     let mod_hash = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Hash.dat"));
     let mod_inspect = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Inspect.dat"));
+    let mod_json = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/Json.dat"));
+    let mod_weak_ref = include_bytes_align_as!(u128, concat!(env!("OUT_DIR"), "/WeakRef.dat"));
 
     let mut output = MutMap::default();
 
@@ -279,6 +285,8 @@ fn read_cached_types() -> MutMap<ModuleId, TypeState> {
 
         output.insert(ModuleId::HASH, deserialize_help(mod_hash));
         output.insert(ModuleId::INSPECT, deserialize_help(mod_inspect));
+        output.insert(ModuleId::JSON, deserialize_help(mod_json));
+        output.insert(ModuleId::WEAK_REF, deserialize_help(mod_weak_ref));
     }
 
     output
@@ -21,9 +21,10 @@ mod test_reporting {
     use roc_parse::test_helpers::parse_expr_with;
     use roc_problem::Severity;
     use roc_region::all::LineInfo;
+    use roc_reporting::json::Span;
     use roc_reporting::report::{
-        can_problem, parse_problem, type_problem, RenderTarget, Report, ANSI_STYLE_CODES,
-        DEFAULT_PALETTE,
+        can_problem, parse_problem, type_problem, ColorChoice, RenderTarget, Report,
+        ANSI_STYLE_CODES, DEFAULT_PALETTE,
     };
     use roc_reporting::report::{RocDocAllocator, RocDocBuilder};
     use roc_solve::FunctionKind;
@@ -97,6 +98,7 @@ mod test_reporting {
         subdir: &str,
         arena: &'a Bump,
         src: &'a str,
+        render: RenderTarget,
     ) -> (String, Result<LoadedModule, LoadingProblem<'a>>) {
         use std::fs::File;
         use std::io::Write;
@@ -125,11 +127,15 @@ mod test_reporting {
             writeln!(file, "{module_src}").unwrap();
             let load_config = LoadConfig {
                 target: roc_target::Target::LinuxX64,
-                render: RenderTarget::Generic,
+                render,
                 palette: DEFAULT_PALETTE,
                 threading: Threading::Single,
                 exec_mode: ExecutionMode::Check,
                 function_kind: FunctionKind::LambdaSet,
+                expect_filter: None,
+                finalizers: Vec::new(),
+                types_cache_dir: None,
+                fold_constants: true,
             };
             let result = roc_load::load_and_typecheck(
                 arena,
@@ -161,7 +167,8 @@ mod test_reporting {
         ),
         LoadingProblem<'a>,
     > {
-        let (module_src, result) = run_load_and_infer(subdir, arena, expr_src);
+        let (module_src, result) =
+            run_load_and_infer(subdir, arena, expr_src, RenderTarget::Generic);
         let LoadedModule {
             module_id: home,
             mut can_problems,
@@ -461,6 +468,64 @@ mod test_reporting {
         }
     }
 
+    /// The problems of `src` as `--json-errors` prints them, one JSON object per line
+    fn json_reports(test_name: &str, src: &str) -> String {
+        let arena = Bump::new();
+
+        let filename = filename_from_string(r"/code/proj/Main.roc");
+
+        let (module_src, result) = run_load_and_infer(test_name, &arena, src, RenderTarget::Json);
+
+        match result {
+            Err(LoadingProblem::FormattedReport(fail, _)) => fail,
+            Ok(LoadedModule {
+                module_id: home,
+                mut can_problems,
+                mut type_problems,
+                interns,
+                ..
+            }) => {
+                let lines = LineInfo::new(&module_src);
+                let src_lines: Vec<&str> = module_src.split('\n').collect();
+                let alloc = RocDocAllocator::new(&src_lines, home, &interns);
+
+                let mut buf = String::new();
+
+                for problem in can_problems.remove(&home).unwrap_or_default() {
+                    let span = problem.region().map(|region| Span::new(region, &lines));
+                    let report = can_problem(&alloc, &lines, filename.clone(), problem);
+
+                    report.render_json(&mut buf, span);
+                }
+
+                for problem in type_problems.remove(&home).unwrap_or_default() {
+                    let span = problem.region().map(|region| Span::new(region, &lines));
+
+                    if let Some(report) = type_problem(&alloc, &lines, filename.clone(), problem) {
+                        report.render_json(&mut buf, span);
+                    }
+                }
+
+                buf
+            }
+            Err(other) => {
+                panic!("failed to load: {other:?}");
+            }
+        }
+    }
+
+    macro_rules! test_json_report {
+        ($(#[$meta:meta])* $test_name:ident, $program:expr, @$output:literal) => {
+            #[test]
+            $(#[$meta])*
+            fn $test_name() {
+                let buf = json_reports(std::stringify!($test_name), $program);
+
+                insta::assert_snapshot!(buf, @$output)
+            }
+        };
+    }
+
     fn human_readable(str: &str) -> String {
         str.replace(ANSI_STYLE_CODES.red, "<red>")
             .replace(ANSI_STYLE_CODES.white, "<white>")
@@ -2441,6 +2506,70 @@ mod test_reporting {
     "
     );
 
+    test_report!(
+        record_access_typo,
+        indoc!(
+            r"
+            User : { email : Str, id : U64, nickname : Str }
+
+            get_email : User -> Str
+            get_email = \usr -> usr.emial
+
+            get_email
+            "
+        ),
+        @r"
+    ── TYPE MISMATCH in /code/proj/Main.roc ────────────────────────────────────────
+
+    This `usr` record doesn’t have a `emial` field:
+
+    7│      get_email = \usr -> usr.emial
+                                ^^^^^^^^^
+
+    There may be a typo. These `usr` fields are the most similar:
+
+        {
+            email : Str,
+            id : U64,
+            nickname : Str,
+        }
+
+    Maybe `.emial` should be `.email` instead?
+    "
+    );
+
+    test_report!(
+        record_destructure_typo,
+        indoc!(
+            r"
+            User : { email : Str, id : U64, nickname : Str }
+
+            greet : User -> Str
+            greet = \{ nickname, emial } -> Str.concat nickname emial
+
+            greet
+            "
+        ),
+        @r"
+    ── TYPE MISMATCH in /code/proj/Main.roc ────────────────────────────────────────
+
+    This record doesn’t have a `emial` field:
+
+    7│      greet = \{ nickname, emial } -> Str.concat nickname emial
+                     ^^^^^^^^^^^^^^^^^^^
+
+    There may be a typo. These fields on the record are the most similar:
+
+        {
+            email : Str,
+            id : U64,
+            nickname : Str,
+        }
+
+    Maybe `emial` should be `email` instead?
+    "
+    );
+
     test_report!(
         plus_on_str,
         indoc!(
@@ -8474,7 +8603,79 @@ All branches in an `if` must have the same type!
 
     Other possibilities include:
 
-        @F _
+        @F 0
+        @F 3..255
+
+    I would have to crash if I saw one of those! Add branches for them!
+    "#
+    );
+
+    test_no_problem!(
+        when_u8_covers_every_value,
+        &format!(
+            "x : U8\n\nwhen x is\n{}",
+            (0..=255)
+                .map(|n| format!("    {n} -> \"\"\n"))
+                .collect::<String>()
+        )
+    );
+
+    test_report!(
+        when_i8_not_exhaustive_ranges,
+        indoc!(
+            r#"
+            x : I8
+
+            when x is
+                -128 -> ""
+                -1 -> ""
+                0 -> ""
+                1 -> ""
+            "#
+        ),
+        @r#"
+    ── UNSAFE PATTERN in /code/proj/Main.roc ───────────────────────────────────────
+
+    This `when` does not cover all the possibilities:
+
+     6│>      when x is
+     7│>          -128 -> ""
+     8│>          -1 -> ""
+     9│>          0 -> ""
+    10│>          1 -> ""
+
+    Other possibilities include:
+
+        -127..-2
+        2..127
+
+    I would have to crash if I saw one of those! Add branches for them!
+    "#
+    );
+
+    test_report!(
+        when_u64_not_exhaustive_ranges,
+        indoc!(
+            r#"
+            x : U64
+
+            when x is
+                0 -> ""
+                1 -> ""
+            "#
+        ),
+        @r#"
+    ── UNSAFE PATTERN in /code/proj/Main.roc ───────────────────────────────────────
+
+    This `when` does not cover all the possibilities:
+
+    6│>      when x is
+    7│>          0 -> ""
+    8│>          1 -> ""
+
+    Other possibilities include:
+
+        2..18446744073709551615
 
     I would have to crash if I saw one of those! Add branches for them!
     "#
@@ -16055,4 +16256,161 @@ All branches in an `if` must have the same type!
     underscore _ instead.
     "###
     );
+
+    #[test]
+    fn json_report_schema() {
+        let buf = json_reports(
+            "json_report_schema",
+            indoc!(
+                r"
+                x = 1
+                y = 2
+
+                x
+                "
+            ),
+        );
+
+        // Newline-delimited, so every diagnostic can be read as soon as its line is complete
+        assert!(buf.ends_with('\n'));
+        assert_eq!(buf.lines().count(), 1);
+
+        for line in buf.lines() {
+            let diagnostic: serde_json::Value = serde_json::from_str(line).unwrap();
+            let object = diagnostic.as_object().unwrap();
+
+            let mut keys: Vec<&str> = object.keys().map(String::as_str).collect();
+            keys.sort_unstable();
+            assert_eq!(
+                keys,
+                ["code", "file", "hints", "message", "severity", "span", "title"]
+            );
+
+            assert!(object["file"].is_string());
+            assert!(["error", "warning", "fatal"].contains(&object["severity"].as_str().unwrap()));
+            assert!(object["code"]
+                .as_str()
+                .unwrap()
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-'));
+            assert!(object["title"].is_string());
+            assert!(!object["message"].as_str().unwrap().contains('\u{1b}'));
+            assert!(object["hints"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .all(serde_json::Value::is_string));
+
+            let span = &object["span"];
+            for location in [&span["start"], &span["end"]] {
+                for field in ["offset", "line", "column"] {
+                    assert!(location[field].is_u64(), "{field} of {location}");
+                }
+            }
+            assert!(span["start"]["offset"].as_u64() <= span["end"]["offset"].as_u64());
+        }
+    }
+
+    test_json_report!(
+        json_type_error,
+        indoc!(
+            r#"
+            if "foo" then 2 else 3
+            "#
+        ),
+        @r###"
+    {"file":"/code/proj/Main.roc","severity":"error","code":"type-mismatch","title":"TYPE MISMATCH","message":"This `if` condition needs to be a Bool:\n\n4│      if \"foo\" then 2 else 3\n           ^^^^^\n\nRight now it’s a string of type:\n\n    Str\n\nBut I need every `if` condition to evaluate to a Bool—either `Bool.true`\nor `Bool.false`.","hints":[],"span":{"start":{"offset":58,"line":3,"column":7},"end":{"offset":63,"line":3,"column":12}}}
+    "###
+    );
+
+    test_json_report!(
+        json_parse_error,
+        indoc!(
+            r"
+            f x y = x
+            "
+        ),
+        @r###"
+    {"file":"tmp/json_parse_error/Test.roc","severity":"error","code":"arguments-before-equals","title":"ARGUMENTS BEFORE EQUALS","message":"I am partway through parsing a definition, but I got stuck here:\n\n1│  app \"test\" provides [main] to \"./platform\"\n2│\n3│  main =\n4│      f x y = x\n          ^^^\n\nLooks like you are trying to define a function. In Roc, functions are\nalways written as a lambda, like increment = |n| n + 1.","hints":[],"span":{"start":{"offset":61,"line":3,"column":10},"end":{"offset":61,"line":3,"column":10}}}
+    "###
+    );
+
+    test_json_report!(
+        json_warning,
+        indoc!(
+            r"
+            Foo a : [Foo]
+
+            f : Foo Num.I64
+
+            f
+            "
+        ),
+        @r###"
+    {"file":"/code/proj/Main.roc","severity":"warning","code":"unused-type-alias-parameter","title":"UNUSED TYPE ALIAS PARAMETER","message":"The `a` type parameter is not used in the `Foo` alias definition:\n\n4│      Foo a : [Foo]\n            ^\n\nRoc does not allow unused type parameters!\n\nTip: If you want an unused type parameter (a so-called \"phantom\ntype\"), read the guide section on phantom values.","hints":["Tip: If you want an unused type parameter (a so-called \"phantom type\"), read the guide section on phantom values."],"span":{"start":{"offset":59,"line":3,"column":8},"end":{"offset":60,"line":3,"column":9}}}
+    "###
+    );
+
+    /// A report as `target` renders it
+    fn render_for(target: RenderTarget) -> String {
+        let interns = Interns::default();
+        let alloc = RocDocAllocator::new(&[], test_home(), &interns);
+
+        let report = Report {
+            title: "SOME PROBLEM".to_string(),
+            filename: filename_from_string(r"/code/proj/Main.roc"),
+            doc: alloc.reflow("Something went wrong."),
+            severity: Severity::RuntimeError,
+        };
+
+        let mut buf = String::new();
+        report.render(target, &mut buf, &alloc, &DEFAULT_PALETTE);
+
+        buf
+    }
+
+    #[test]
+    fn color_choice_rendering() {
+        let always = render_for(RenderTarget::ColorTerminal(ColorChoice::Always));
+        let never = render_for(RenderTarget::ColorTerminal(ColorChoice::Never));
+        let auto = render_for(RenderTarget::ColorTerminal(ColorChoice::Auto));
+
+        assert!(always.contains('\u{1b}'));
+        assert!(!never.contains('\u{1b}'));
+        assert!(never.contains("SOME PROBLEM"));
+        assert_eq!(auto.contains('\u{1b}'), ColorChoice::Auto.use_color());
+    }
+
+    #[test]
+    fn color_choice_environment() {
+        fn auto_with(vars: &[(&str, &str)], is_terminal: bool) -> bool {
+            let var = |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.into())
+            };
+
+            ColorChoice::Auto.use_color_in(var, is_terminal)
+        }
+
+        assert!(auto_with(&[], true));
+        assert!(!auto_with(&[], false));
+
+        assert!(!auto_with(&[("NO_COLOR", "1")], true));
+        assert!(auto_with(&[("NO_COLOR", "")], true));
+
+        assert!(auto_with(&[("CLICOLOR_FORCE", "1")], false));
+        assert!(!auto_with(&[("CLICOLOR_FORCE", "0")], false));
+        assert!(!auto_with(&[("CLICOLOR_FORCE", "")], false));
+
+        // NO_COLOR wins over CLICOLOR_FORCE
+        assert!(!auto_with(
+            &[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")],
+            false
+        ));
+
+        // an explicit choice ignores the environment
+        assert!(ColorChoice::Always.use_color_in(|_| Some("1".into()), false));
+        assert!(!ColorChoice::Never.use_color_in(|_| None, true));
+    }
 }
@@ -7,6 +7,7 @@ use crate::module::{
     ModuleTiming, MonomorphizedModule, ParsedModule, ToplevelExpects, TypeCheckedModule,
 };
 use crate::module_cache::ModuleCache;
+use crate::type_cache::{TypeCache, TypeCacheEntry};
 use bumpalo::{collections::CollectIn, Bump};
 use crossbeam::channel::{bounded, Sender};
 use crossbeam::deque::{Injector, Worker};
@@ -34,7 +35,7 @@ use roc_debug_flags::{
     ROC_PRINT_LOAD_LOG,
 };
 use roc_derive::SharedDerivedModule;
-use roc_error_macros::internal_error;
+use roc_error_macros::{internal_error, user_error};
 use roc_late_solve::{AbilitiesView, WorldAbilities};
 use roc_module::ident::{Ident, ModuleName, QualifiedModuleName};
 use roc_module::symbol::{
@@ -49,7 +50,9 @@ use roc_mono::layout::{
     GlobalLayoutInterner, LambdaName, Layout, LayoutCache, LayoutProblem, Niche, STLayoutInterner,
 };
 use roc_mono::reset_reuse;
-use roc_mono::{drop_specialization, inc_dec};
+use roc_mono::{
+    bounds_check, constant_folding, drop_specialization, inc_dec, inc_dec_pairs, single_owner,
+};
 use roc_packaging::cache::RocCacheDir;
 use roc_parse::ast::{self, CommentOrNewline, ExtractSpaces, Spaced, ValueDef};
 use roc_parse::header::parse_module_defs;
@@ -59,7 +62,7 @@ use roc_parse::header::{
 };
 use roc_parse::parser::{FileError, SourceError, SyntaxError};
 use roc_problem::Severity;
-use roc_region::all::{LineInfo, Loc, Region};
+use roc_region::all::{LineInfo, Loc, Position, Region};
 use roc_reporting::error::r#type::suggest;
 #[cfg(not(target_family = "wasm"))]
 use roc_reporting::report::to_https_problem_report_string;
@@ -116,6 +119,19 @@ pub struct LoadConfig {
     pub threading: Threading,
     pub exec_mode: ExecutionMode,
     pub function_kind: FunctionKind,
+    /// In [ExecutionMode::Test], only build the top-level expects whose enclosing definition or
+    /// module name contains this
+    pub expect_filter: Option<String>,
+    /// The host function that runs before a value of an opaque type is freed, by the name of the
+    /// opaque type. A `Module.Type` name is looked up in that module, and a bare `Type` in the
+    /// root module. The opaque type has to wrap a `Box`.
+    pub finalizers: Vec<(String, String)>,
+    /// The roc cache dir to keep the solved types of modules in, so that a module that did not
+    /// change since the last build is not solved again. With `None`, every module is solved.
+    pub types_cache_dir: Option<PathBuf>,
+    /// Fold arithmetic on literals in the mono IR. Only mono tests that show the calls turn this
+    /// off.
+    pub fold_constants: bool,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -315,10 +331,23 @@ fn start_phase<'a>(
                     }
                 }
 
+                let is_host_exposed = state.root_id == module_id;
+                let from_type_cache = state
+                    .type_cache
+                    .as_mut()
+                    .and_then(|type_cache| type_cache.add_module(&parsed, is_host_exposed))
+                    .and_then(|entry| entry.read());
+
                 let skip_constraint_gen = {
                     // Give this its own scope to make sure that the Guard from the lock() is dropped
                     // immediately after contains_key returns
-                    state.cached_types.lock().contains_key(&module_id)
+                    let mut cached_types = state.cached_types.lock();
+
+                    if let Some(type_state) = from_type_cache {
+                        cached_types.insert(module_id, type_state);
+                    }
+
+                    cached_types.contains_key(&module_id)
                 };
 
                 BuildTask::CanonicalizeAndConstrain {
@@ -364,6 +393,10 @@ fn start_phase<'a>(
                 };
 
                 let is_host_exposed = state.root_id == module.module_id;
+                let type_cache_entry = state
+                    .type_cache
+                    .as_ref()
+                    .and_then(|type_cache| type_cache.entry(module_id));
 
                 BuildTask::solve_module(
                     module,
@@ -380,6 +413,7 @@ fn start_phase<'a>(
                     dep_idents,
                     declarations,
                     state.cached_types.clone(),
+                    type_cache_entry,
                     derived_module,
                     state.exec_mode,
                     is_host_exposed,
@@ -431,8 +465,18 @@ fn start_phase<'a>(
 
                 let derived_module = SharedDerivedModule::clone(&state.derived_module);
 
-                let build_expects =
-                    matches!(state.exec_mode, ExecutionMode::Test) && expectations.is_some();
+                // `roc test path/To/Module.roc` only runs the expects of that module
+                let build_expects = matches!(state.exec_mode, ExecutionMode::Test)
+                    && expectations.is_some()
+                    && (matches!(state.root_type, RootType::Main) || module_id == state.root_id);
+
+                // if the name of the module matches, so do all of its expects
+                let expect_filter = state.expect_filter.clone().filter(|filter| {
+                    let module_ids = state.arc_modules.lock();
+                    let module_name = module_ids.get_name(module_id).map(|name| name.as_inner());
+
+                    !module_name.is_some_and(|name| name.as_str().contains(filter.as_str()))
+                });
 
                 BuildTask::BuildPendingSpecializations {
                     layout_cache,
@@ -449,6 +493,7 @@ fn start_phase<'a>(
                     derived_module,
                     expectations,
                     build_expects,
+                    expect_filter,
                 }
             }
             Phase::MakeSpecializations => {
@@ -474,7 +519,7 @@ fn start_phase<'a>(
                     mut subs,
                     expectations,
                     mut procs_base,
-                    layout_cache,
+                    mut layout_cache,
                     mut module_timing,
                 ) = if state.make_specializations_pass.current_pass() == 1
                     && module_id == ModuleId::DERIVED_GEN
@@ -538,6 +583,9 @@ fn start_phase<'a>(
                     )
                 };
 
+                // every module is known by now, so every finalizer has to be found
+                layout_cache.finalizers = resolve_finalizers(state, module_id, &ident_ids, true);
+
                 if module_id == ModuleId::DERIVED_GEN {
                     load_derived_partial_procs(
                         module_id,
@@ -576,6 +624,58 @@ fn start_phase<'a>(
     vec![task]
 }
 
+/// The opaque types of [LoadConfig::finalizers], as far as the modules that define them are known.
+/// `home` is the module the layouts are for, whose [IdentIds] may not be in the state yet.
+fn resolve_finalizers(
+    state: &State,
+    home: ModuleId,
+    home_ident_ids: &IdentIds,
+    must_resolve: bool,
+) -> MutMap<Symbol, String> {
+    let module_ids = state.arc_modules.lock();
+    let mut finalizers = MutMap::default();
+
+    for (type_name, finalizer) in state.finalizers.iter() {
+        let (module_id, type_name) = match type_name.rsplit_once('.') {
+            Some((module_name, type_name)) => {
+                let module_id = module_ids
+                    .available_modules()
+                    .find(|name| name.as_inner().as_str() == module_name)
+                    .and_then(|name| module_ids.get_id(name));
+
+                (module_id, type_name)
+            }
+            None => (Some(state.root_id), type_name.as_str()),
+        };
+
+        let ident_ids = module_id.and_then(|module_id| {
+            if module_id == home {
+                Some(home_ident_ids)
+            } else {
+                state.constrained_ident_ids.get(&module_id)
+            }
+        });
+
+        let symbol = module_id.zip(ident_ids).and_then(|(module_id, ident_ids)| {
+            let ident_id = ident_ids.get_id(type_name)?;
+
+            Some(Symbol::new(module_id, ident_id))
+        });
+
+        match symbol {
+            Some(symbol) => {
+                finalizers.insert(symbol, finalizer.clone());
+            }
+            None if must_resolve => user_error!(
+                "There is no opaque type named `{type_name}` for `{finalizer}` to finalize."
+            ),
+            None => {}
+        }
+    }
+
+    finalizers
+}
+
 /// Values used to render expect output
 pub struct ExpectMetadata<'a> {
     pub interns: Interns,
@@ -741,8 +841,12 @@ struct State<'a> {
     pub procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
     pub host_exposed_lambda_sets: HostExposedLambdaSets<'a>,
     pub toplevel_expects: MutMap<ModuleId, ToplevelExpects>,
+    pub filtered_out_expects: usize,
     pub exposed_to_host: ExposedToHost,
 
+    /// Where each top-level definition was defined, used for debug info
+    pub symbol_regions: MutMap<Symbol, Region>,
+
     /// This is the "final" list of IdentIds, after canonicalization and constraint gen
     /// have completed for a given module.
     pub constrained_ident_ids: IdentIdsByModule,
@@ -770,6 +874,9 @@ struct State<'a> {
     pub render: RenderTarget,
     pub palette: Palette,
     pub exec_mode: ExecutionMode,
+    pub expect_filter: Option<String>,
+    pub finalizers: Vec<(String, String)>,
+    pub fold_constants: bool,
 
     /// All abilities across all modules.
     pub world_abilities: WorldAbilities,
@@ -779,6 +886,9 @@ struct State<'a> {
     // cached types (used for builtin modules, could include packages in the future too)
     cached_types: CachedTypeState,
 
+    /// The solved types of the modules of previous builds
+    type_cache: Option<TypeCache>,
+
     layout_interner: GlobalLayoutInterner<'a>,
 }
 
@@ -805,9 +915,15 @@ impl<'a> State<'a> {
         palette: Palette,
         number_of_workers: usize,
         exec_mode: ExecutionMode,
+        expect_filter: Option<String>,
+        finalizers: Vec<(String, String)>,
+        types_cache_dir: Option<PathBuf>,
+        fold_constants: bool,
     ) -> Self {
         let cache_dir = roc_packaging::cache::roc_cache_packages_dir();
         let dependencies = Dependencies::new(exec_mode.goal_phase());
+        let type_cache =
+            types_cache_dir.and_then(|dir| TypeCache::new(&dir, exec_mode, function_kind));
 
         Self {
             root_id,
@@ -825,7 +941,9 @@ impl<'a> State<'a> {
             procedures: MutMap::default(),
             host_exposed_lambda_sets: std::vec::Vec::new(),
             toplevel_expects: MutMap::default(),
+            filtered_out_expects: 0,
             exposed_to_host: ExposedToHost::default(),
+            symbol_regions: MutMap::default(),
             exposed_modules: &[],
             exposed_types,
             arc_modules,
@@ -838,9 +956,13 @@ impl<'a> State<'a> {
             timings: MutMap::default(),
             layout_caches: std::vec::Vec::with_capacity(number_of_workers),
             cached_types: Arc::new(Mutex::new(cached_types)),
+            type_cache,
             render,
             palette,
             exec_mode,
+            expect_filter,
+            finalizers,
+            fold_constants,
             make_specializations_pass: MakeSpecializationsPass::Pass(1),
             world_abilities: Default::default(),
             layout_interner: GlobalLayoutInterner::with_capacity(128, target),
@@ -870,7 +992,11 @@ impl std::fmt::Display for ModuleTiming {
         report_timing(f, "Parse body", module_timing.parse_body)?;
         report_timing(f, "Canonicalize", module_timing.canonicalize)?;
         report_timing(f, "Constrain", module_timing.constrain)?;
-        report_timing(f, "Solve", module_timing.solve)?;
+        if module_timing.types_from_cache {
+            report_timing(f, "Solve (types from cache)", module_timing.solve)?;
+        } else {
+            report_timing(f, "Solve", module_timing.solve)?;
+        }
         report_timing(
             f,
             "Find Specializations",
@@ -939,6 +1065,7 @@ enum BuildTask<'a> {
         declarations: Declarations,
         dep_idents: IdentIdsByModule,
         cached_subs: CachedTypeState,
+        type_cache_entry: Option<TypeCacheEntry>,
         derived_module: SharedDerivedModule,
         exec_mode: ExecutionMode,
         is_host_exposed: bool,
@@ -960,6 +1087,7 @@ enum BuildTask<'a> {
         derived_module: SharedDerivedModule,
         expectations: Option<Expectations>,
         build_expects: bool,
+        expect_filter: Option<String>,
     },
     MakeSpecializations {
         module_id: ModuleId,
@@ -1144,6 +1272,10 @@ pub fn load_and_typecheck_str<'a>(
         threading,
         exec_mode: ExecutionMode::Check,
         function_kind,
+        expect_filter: None,
+        finalizers: Vec::new(),
+        types_cache_dir: None,
+        fold_constants: true,
     };
 
     match load(
@@ -1563,6 +1695,10 @@ pub fn load<'a>(
             load_config.render,
             load_config.palette,
             load_config.exec_mode,
+            load_config.expect_filter,
+            load_config.finalizers,
+            load_config.types_cache_dir,
+            load_config.fold_constants,
             roc_cache_dir,
         ),
         Threads::Many(threads) => load_multi_threaded(
@@ -1576,6 +1712,10 @@ pub fn load<'a>(
             load_config.palette,
             threads,
             load_config.exec_mode,
+            load_config.expect_filter,
+            load_config.finalizers,
+            load_config.types_cache_dir,
+            load_config.fold_constants,
             roc_cache_dir,
         ),
     }
@@ -1592,6 +1732,10 @@ pub fn load_single_threaded<'a>(
     render: RenderTarget,
     palette: Palette,
     exec_mode: ExecutionMode,
+    expect_filter: Option<String>,
+    finalizers: Vec<(String, String)>,
+    types_cache_dir: Option<PathBuf>,
+    fold_constants: bool,
     roc_cache_dir: RocCacheDir<'_>,
 ) -> Result<LoadResult<'a>, LoadingProblem<'a>> {
     let LoadStart {
@@ -1630,6 +1774,10 @@ pub fn load_single_threaded<'a>(
         palette,
         number_of_workers,
         exec_mode,
+        expect_filter,
+        finalizers,
+        types_cache_dir,
+        fold_constants,
     );
 
     // We'll add tasks to this, and then worker threads will take tasks from it.
@@ -1973,6 +2121,10 @@ fn load_multi_threaded<'a>(
     palette: Palette,
     available_threads: usize,
     exec_mode: ExecutionMode,
+    expect_filter: Option<String>,
+    finalizers: Vec<(String, String)>,
+    types_cache_dir: Option<PathBuf>,
+    fold_constants: bool,
     roc_cache_dir: RocCacheDir<'_>,
 ) -> Result<LoadResult<'a>, LoadingProblem<'a>> {
     let LoadStart {
@@ -2026,6 +2178,10 @@ fn load_multi_threaded<'a>(
         palette,
         num_workers,
         exec_mode,
+        expect_filter,
+        finalizers,
+        types_cache_dir,
+        fold_constants,
     );
 
     // an arena for every worker, stored in an arena-allocated bumpalo vec to make the lifetimes work
@@ -2389,6 +2545,8 @@ fn update<'a>(
                 extend_module_with_builtin_import(parsed, ModuleId::DECODE);
                 extend_module_with_builtin_import(parsed, ModuleId::HASH);
                 extend_module_with_builtin_import(parsed, ModuleId::INSPECT);
+                extend_module_with_builtin_import(parsed, ModuleId::JSON);
+                extend_module_with_builtin_import(parsed, ModuleId::WEAK_REF);
             }
             state
                 .module_cache
@@ -2656,10 +2814,21 @@ fn update<'a>(
                 );
 
                 if state.goal_phase() > Phase::SolveTypes || state.exec_mode.build_if_checks() {
-                    let layout_cache = state.layout_caches.pop().unwrap_or_else(|| {
+                    state.symbol_regions.extend(
+                        decls
+                            .symbols
+                            .iter()
+                            .map(|loc_symbol| (loc_symbol.value, loc_symbol.region)),
+                    );
+
+                    let mut layout_cache = state.layout_caches.pop().unwrap_or_else(|| {
                         LayoutCache::new(state.layout_interner.fork(), state.target)
                     });
 
+                    // the modules that are not known yet do not use the types of this one
+                    layout_cache.finalizers =
+                        resolve_finalizers(&state, module_id, &ident_ids, false);
+
                     let typechecked = TypeCheckedModule {
                         module_id,
                         layout_cache,
@@ -2724,6 +2893,8 @@ fn update<'a>(
 
             let subs = solved_subs.into_inner();
 
+            state.filtered_out_expects += toplevel_expects.filtered_out;
+
             if !toplevel_expects.pure.is_empty() {
                 state.toplevel_expects.insert(module_id, toplevel_expects);
             }
@@ -2912,7 +3083,24 @@ fn update<'a>(
 
                     debug_print_ir!(state, &layout_interner, ROC_PRINT_IR_AFTER_TRMC);
 
-                    inc_dec::insert_inc_dec_operations(
+                    bounds_check::elide_bounds_checks(
+                        arena,
+                        &layout_interner,
+                        module_id,
+                        ident_ids,
+                        &mut update_mode_ids,
+                        &mut state.procedures,
+                    );
+
+                    if state.fold_constants {
+                        constant_folding::fold_constants(
+                            arena,
+                            &layout_interner,
+                            &mut state.procedures,
+                        );
+                    }
+
+                    let borrow_signatures = inc_dec::insert_inc_dec_operations(
                         arena,
                         &layout_interner,
                         &mut state.procedures,
@@ -2934,6 +3122,12 @@ fn update<'a>(
                         ROC_PRINT_IR_AFTER_DROP_SPECIALIZATION
                     );
 
+                    inc_dec_pairs::remove_inc_dec_pairs(
+                        arena,
+                        borrow_signatures,
+                        &mut state.procedures,
+                    );
+
                     reset_reuse::insert_reset_reuse_operations(
                         arena,
                         &layout_interner,
@@ -2946,6 +3140,12 @@ fn update<'a>(
 
                     debug_print_ir!(state, &layout_interner, ROC_PRINT_IR_AFTER_RESET_REUSE);
 
+                    single_owner::free_single_owner_values(
+                        arena,
+                        &layout_interner,
+                        &mut state.procedures,
+                    );
+
                     // This is not safe with the new non-recursive RC updates that we do for tag unions
                     //
                     // Proc::optimize_refcount_operations(
@@ -3203,7 +3403,7 @@ fn log_layout_stats(module_id: ModuleId, layout_cache: &LayoutCache) {
 
 fn finish_specialization<'a>(
     arena: &'a Bump,
-    state: State<'a>,
+    mut state: State<'a>,
     subs: Subs,
     layout_interner: STLayoutInterner<'a>,
     exposed_to_host: ExposedToHost,
@@ -3303,12 +3503,32 @@ fn finish_specialization<'a>(
         }
     }?;
 
+    if let EntryPoint::Executable {
+        exposed_to_host, ..
+    } = &entry_point
+    {
+        // the host calls the exposed functions, and the closures they return through the
+        // callers of their lambda sets
+        let roots = exposed_to_host.iter().map(|(_, symbol, _)| *symbol).chain(
+            state
+                .host_exposed_lambda_sets
+                .iter()
+                .flat_map(|(lambda_name, symbol, lambda_set)| {
+                    [lambda_name.name(), *symbol, lambda_set.symbol]
+                }),
+        );
+
+        roc_mono::dead_procs::remove_dead_procs(&mut state.procedures, roots);
+    }
+
     let State {
         toplevel_expects,
+        filtered_out_expects,
         procedures,
         host_exposed_lambda_sets,
         module_cache,
         platform_data,
+        symbol_regions,
         ..
     } = state;
 
@@ -3345,8 +3565,10 @@ fn finish_specialization<'a>(
         host_exposed_lambda_sets,
         entry_point,
         sources,
+        symbol_regions,
         timings: state.timings,
         toplevel_expects,
+        filtered_out_expects,
         glue_layouts: GlueLayouts { getters: vec![] },
         needs_prebuilt_host,
     })
@@ -3747,6 +3969,8 @@ fn load_module<'a>(
         "Decode", ModuleId::DECODE
         "Hash", ModuleId::HASH
         "Inspect", ModuleId::INSPECT
+        "Json", ModuleId::JSON
+        "WeakRef", ModuleId::WEAK_REF
     }
 
     let (filename, opt_shorthand) = module_name_to_path(src_dir, &module_name, arc_shorthands);
@@ -4423,6 +4647,7 @@ impl<'a> BuildTask<'a> {
         dep_idents: IdentIdsByModule,
         declarations: Declarations,
         cached_subs: CachedTypeState,
+        type_cache_entry: Option<TypeCacheEntry>,
         derived_module: SharedDerivedModule,
         exec_mode: ExecutionMode,
         is_host_exposed: bool,
@@ -4448,6 +4673,7 @@ impl<'a> BuildTask<'a> {
             dep_idents,
             module_timing,
             cached_subs,
+            type_cache_entry,
             derived_module,
             exec_mode,
             is_host_exposed,
@@ -4888,6 +5114,49 @@ fn run_solve_solve(
     }
 }
 
+/// Store the types of a module that was just solved, for the next build
+fn write_to_type_cache(entry: &TypeCacheEntry, solve_result: SolveResult) -> SolveResult {
+    let SolveResult {
+        solved,
+        solved_implementations,
+        exposed_vars_by_symbol,
+        problems,
+        abilities_store,
+        imported_modules_with_params,
+
+        #[cfg(debug_assertions)]
+        checkmate,
+    } = solve_result;
+
+    let type_state = TypeState {
+        subs: solved.into_inner(),
+        exposed_vars_by_symbol,
+        abilities: abilities_store,
+        solved_implementations,
+    };
+
+    entry.write(&type_state);
+
+    let TypeState {
+        subs,
+        exposed_vars_by_symbol,
+        abilities,
+        solved_implementations,
+    } = type_state;
+
+    SolveResult {
+        solved: Solved(subs),
+        solved_implementations,
+        exposed_vars_by_symbol,
+        problems,
+        abilities_store: abilities,
+        imported_modules_with_params,
+
+        #[cfg(debug_assertions)]
+        checkmate,
+    }
+}
+
 fn run_solve<'a>(
     module: Module,
     ident_ids: IdentIds,
@@ -4902,6 +5171,7 @@ fn run_solve<'a>(
     decls: Declarations,
     dep_idents: IdentIdsByModule,
     cached_types: CachedTypeState,
+    type_cache_entry: Option<TypeCacheEntry>,
     derived_module: SharedDerivedModule,
     exec_mode: ExecutionMode,
     is_host_exposed: bool,
@@ -4923,43 +5193,12 @@ fn run_solve<'a>(
     let has_dbgs = module.has_dbgs;
     let module = module;
 
-    let solve_result = {
-        if module_id.is_builtin() {
-            match cached_types.lock().remove(&module_id) {
-                None => run_solve_solve(
-                    exposed_for_module,
-                    types,
-                    constraints,
-                    constraint,
-                    function_kind,
-                    pending_derives,
-                    var_store,
-                    module,
-                    derived_module,
-                    is_host_exposed,
-                    //
-                    #[cfg(debug_assertions)]
-                    checkmate,
-                ),
-                Some(TypeState {
-                    subs,
-                    exposed_vars_by_symbol,
-                    abilities,
-                    solved_implementations,
-                }) => SolveResult {
-                    solved: Solved(subs),
-                    solved_implementations,
-                    exposed_vars_by_symbol,
-                    problems: vec![],
-                    abilities_store: abilities,
-                    imported_modules_with_params: vec![],
+    // the lock is released before solving, so that other modules can be solved meanwhile
+    let cached_type_state = cached_types.lock().remove(&module_id);
 
-                    #[cfg(debug_assertions)]
-                    checkmate: None,
-                },
-            }
-        } else {
-            run_solve_solve(
+    let solve_result = match cached_type_state {
+        None => {
+            let solve_result = run_solve_solve(
                 exposed_for_module,
                 types,
                 constraints,
@@ -4973,7 +5212,35 @@ fn run_solve<'a>(
                 //
                 #[cfg(debug_assertions)]
                 checkmate,
-            )
+            );
+
+            // a cached module has no problems to report
+            match type_cache_entry {
+                Some(entry) if solve_result.problems.is_empty() => {
+                    write_to_type_cache(&entry, solve_result)
+                }
+                _ => solve_result,
+            }
+        }
+        Some(TypeState {
+            subs,
+            exposed_vars_by_symbol,
+            abilities,
+            solved_implementations,
+        }) => {
+            module_timing.types_from_cache = true;
+
+            SolveResult {
+                solved: Solved(subs),
+                solved_implementations,
+                exposed_vars_by_symbol,
+                problems: vec![],
+                abilities_store: abilities,
+                imported_modules_with_params: vec![],
+
+                #[cfg(debug_assertions)]
+                checkmate: None,
+            }
         }
     };
 
@@ -5371,6 +5638,8 @@ fn canonicalize_and_constrain<'a>(
                         | ModuleId::SET
                         | ModuleId::HASH
                         | ModuleId::INSPECT
+                        | ModuleId::JSON
+                        | ModuleId::WEAK_REF
                         | ModuleId::STR
                 );
 
@@ -5805,6 +6074,28 @@ fn make_specializations<'a>(
     }
 }
 
+/// The name of the last top-level definition before `position`, which is what a top-level expect
+/// that follows it usually tests
+fn enclosing_def_name<'i>(
+    declarations: &Declarations,
+    ident_ids: &'i IdentIds,
+    position: Position,
+) -> Option<&'i str> {
+    use roc_can::expr::DeclarationTag::*;
+
+    (0..declarations.len())
+        .filter(|index| {
+            matches!(
+                declarations.declarations[*index],
+                Value | Function(_) | Recursive(_) | TailRecursive(_)
+            )
+        })
+        .map(|index| declarations.symbols[index])
+        .filter(|symbol| symbol.region.start() < position)
+        .max_by_key(|symbol| symbol.region.start())
+        .and_then(|symbol| ident_ids.get_name(symbol.value.ident_id()))
+}
+
 fn build_pending_specializations<'a>(
     arena: &'a Bump,
     solved_subs: Solved<Subs>,
@@ -5821,6 +6112,7 @@ fn build_pending_specializations<'a>(
     derived_module: SharedDerivedModule,
     mut expectations: Option<Expectations>,
     build_expects: bool,
+    expect_filter: Option<String>,
 ) -> Msg<'a> {
     let find_specializations_start = Instant::now();
 
@@ -6094,6 +6386,17 @@ fn build_pending_specializations<'a>(
                     continue;
                 }
 
+                if let Some(filter) = &expect_filter {
+                    let expect_start = declarations.expressions[index].region.start();
+                    let enclosing_def =
+                        enclosing_def_name(&declarations, mono_env.ident_ids, expect_start);
+
+                    if !enclosing_def.is_some_and(|name| name.contains(filter.as_str())) {
+                        toplevel_expects.filtered_out += 1;
+                        continue;
+                    }
+                }
+
                 // mark this symbol as a top-level thunk before any other work on the procs
                 module_thunks.push(symbol);
 
@@ -6410,6 +6713,7 @@ fn run_task<'a>(
             declarations,
             dep_idents,
             cached_subs,
+            type_cache_entry,
             derived_module,
             exec_mode,
             is_host_exposed,
@@ -6430,6 +6734,7 @@ fn run_task<'a>(
             declarations,
             dep_idents,
             cached_subs,
+            type_cache_entry,
             derived_module,
             exec_mode,
             is_host_exposed,
@@ -6451,6 +6756,7 @@ fn run_task<'a>(
             derived_module,
             expectations,
             build_expects,
+            expect_filter,
         } => Ok(build_pending_specializations(
             arena,
             solved_subs,
@@ -6467,6 +6773,7 @@ fn run_task<'a>(
             derived_module,
             expectations,
             build_expects,
+            expect_filter,
         )),
         MakeSpecializations {
             module_id,
@@ -6844,6 +7151,7 @@ fn to_parse_problem_report<'a>(
     render: RenderTarget,
     palette: Palette,
 ) -> String {
+    use roc_reporting::json::Span;
     use roc_reporting::report::{parse_problem, RocDocAllocator};
 
     // TODO this is not in fact safe
@@ -6866,6 +7174,8 @@ fn to_parse_problem_report<'a>(
 
     let lines = LineInfo::new(src);
 
+    let region = problem.problem.problem.get_region();
+
     let report = parse_problem(
         &alloc,
         &lines,
@@ -6876,7 +7186,12 @@ fn to_parse_problem_report<'a>(
 
     let mut buf = String::new();
 
-    report.render(render, &mut buf, &alloc, &palette);
+    match render {
+        RenderTarget::Json => {
+            report.render_json(&mut buf, region.map(|region| Span::new(region, &lines)));
+        }
+        _ => report.render(render, &mut buf, &alloc, &palette),
+    }
 
     buf
 }
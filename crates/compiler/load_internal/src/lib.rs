@@ -8,6 +8,7 @@ pub mod docs;
 pub mod file;
 pub mod module;
 mod module_cache;
+mod type_cache;
 
 #[cfg(target_family = "wasm")]
 mod wasm_instant;
@@ -25,4 +26,6 @@ pub const BUILTIN_MODULES: &[(ModuleId, &str)] = &[
     (ModuleId::DECODE, "Decode"),
     (ModuleId::HASH, "Hash"),
     (ModuleId::INSPECT, "Inspect"),
+    (ModuleId::JSON, "Json"),
+    (ModuleId::WEAK_REF, "WeakRef"),
 ];
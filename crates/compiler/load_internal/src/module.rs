@@ -167,6 +167,8 @@ pub(crate) struct LateSpecializationsModule<'a> {
 #[derive(Debug, Default)]
 pub struct ToplevelExpects {
     pub pure: VecMap<Symbol, Region>,
+    /// How many expects did not match the expect filter, and were not built
+    pub filtered_out: usize,
 }
 
 #[derive(Debug)]
@@ -180,9 +182,13 @@ pub struct MonomorphizedModule<'a> {
     pub procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
     pub host_exposed_lambda_sets: HostExposedLambdaSets<'a>,
     pub toplevel_expects: MutMap<ModuleId, ToplevelExpects>,
+    /// How many top-level expects were left out by [crate::file::LoadConfig::expect_filter]
+    pub filtered_out_expects: usize,
     pub entry_point: EntryPoint<'a>,
     pub exposed_to_host: ExposedToHost,
     pub sources: MutMap<ModuleId, (PathBuf, Box<str>)>,
+    /// The region of every top-level definition
+    pub symbol_regions: MutMap<Symbol, Region>,
     pub timings: MutMap<ModuleId, ModuleTiming>,
     pub expectations: VecMap<ModuleId, Expectations>,
     pub needs_prebuilt_host: bool,
@@ -246,6 +252,8 @@ pub struct ModuleTiming {
     pub canonicalize: Duration,
     pub constrain: Duration,
     pub solve: Duration,
+    /// The types were taken from a cache instead of solved
+    pub types_from_cache: bool,
     pub find_specializations: Duration,
     // indexed by make specializations pass
     pub make_specializations: Vec<Duration>,
@@ -266,6 +274,7 @@ impl ModuleTiming {
             canonicalize: Duration::default(),
             constrain: Duration::default(),
             solve: Duration::default(),
+            types_from_cache: false,
             find_specializations: Duration::default(),
             make_specializations: Vec::with_capacity(2),
             start_time,
@@ -287,6 +296,7 @@ impl ModuleTiming {
             canonicalize,
             constrain,
             solve,
+            types_from_cache: _,
             find_specializations,
             make_specializations,
             start_time,
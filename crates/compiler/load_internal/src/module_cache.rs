@@ -110,6 +110,8 @@ impl Default for ModuleCache<'_> {
             DECODE,
             HASH,
             INSPECT,
+            JSON,
+            WEAK_REF,
         }
 
         Self {
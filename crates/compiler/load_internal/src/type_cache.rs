@@ -0,0 +1,128 @@
+//! The solved types of modules, kept between builds so that a module that did not change is not
+//! solved again.
+//!
+//! A module is solved against the types of the modules it imports, so its entry is keyed by its
+//! own source and by the keys of the modules it imports: when a module changes, so do the keys of
+//! all the modules that depend on it, however indirectly. The key also has the [ModuleId]s,
+//! because the solved types refer to other modules by them, and which id a module gets depends on
+//! the order in which the modules were found.
+//!
+//! Only `module` files are cached. An app is the module that changes in almost every build, and
+//! the main module of a platform uses the symbols of the app that it `requires`.
+use crate::file::ExecutionMode;
+use crate::module::ParsedModule;
+use roc_cache::{Cache, CacheKey, KeyBuilder};
+use roc_can::module::TypeState;
+use roc_collections::MutMap;
+use roc_module::symbol::ModuleId;
+use roc_parse::header::HeaderType;
+use roc_solve::FunctionKind;
+use std::path::Path;
+
+pub(crate) struct TypeCache {
+    cache: Cache,
+    /// The compiler and the options, which every key starts with
+    base_key: KeyBuilder,
+    keys: MutMap<ModuleId, CacheKey>,
+}
+
+impl TypeCache {
+    /// The cache in `roc_cache_dir`, or `None` if the compiler cannot be identified
+    pub fn new(
+        roc_cache_dir: &Path,
+        exec_mode: ExecutionMode,
+        function_kind: FunctionKind,
+    ) -> Option<Self> {
+        let mut base_key = KeyBuilder::new().ok()?;
+
+        // constraint gen differs by mode: params are lowered, and expects are kept, in some
+        base_key.add(format!("{exec_mode:?} {function_kind:?}"));
+
+        Some(Self {
+            cache: Cache::module_types(roc_cache_dir),
+            base_key,
+            keys: MutMap::default(),
+        })
+    }
+
+    /// Find the key of a module that is about to be canonicalized, after the modules it imports.
+    /// There is none for a module that is not cached, or that imports a module without a key.
+    pub fn add_module(
+        &mut self,
+        parsed: &ParsedModule,
+        is_host_exposed: bool,
+    ) -> Option<TypeCacheEntry> {
+        if !matches!(parsed.header_type, HeaderType::Module { .. }) {
+            return None;
+        }
+
+        let mut key = self.base_key.clone();
+
+        key.add(parsed.module_id.to_ne_bytes())
+            .add([is_host_exposed as u8])
+            .add(parsed.src);
+
+        let mut imported: Vec<ModuleId> = parsed
+            .available_modules
+            .keys()
+            .chain(parsed.deps_by_name.values())
+            .copied()
+            .collect();
+        imported.sort_by_key(|module_id| u32::from_ne_bytes(module_id.to_ne_bytes()));
+        imported.dedup();
+
+        for module_id in imported {
+            key.add(module_id.to_ne_bytes());
+
+            // the builtins change only with the compiler
+            if !module_id.is_builtin() {
+                key.add(self.keys.get(&module_id)?.as_str());
+            }
+        }
+
+        let key = key.finish();
+        self.keys.insert(parsed.module_id, key.clone());
+
+        self.entry(parsed.module_id)
+    }
+
+    pub fn entry(&self, module_id: ModuleId) -> Option<TypeCacheEntry> {
+        Some(TypeCacheEntry {
+            cache: self.cache.clone(),
+            key: self.keys.get(&module_id)?.clone(),
+        })
+    }
+}
+
+/// Where the solved types of one module are cached
+#[derive(Debug)]
+pub(crate) struct TypeCacheEntry {
+    cache: Cache,
+    key: CacheKey,
+}
+
+impl TypeCacheEntry {
+    pub fn read(&self) -> Option<TypeState> {
+        let bytes = self.cache.read(&self.key)?;
+
+        // the serialized slices are used in place, so the bytes need the alignment that the
+        // cached types of the builtins are included with
+        let mut aligned = vec![0u128; bytes.len().div_ceil(16)];
+        let aligned_bytes =
+            unsafe { std::slice::from_raw_parts_mut(aligned.as_mut_ptr() as *mut u8, bytes.len()) };
+        aligned_bytes.copy_from_slice(&bytes);
+
+        let (type_state, len) = TypeState::deserialize(aligned_bytes);
+
+        (len == bytes.len()).then_some(type_state)
+    }
+
+    /// A cache that cannot be written is the same as an empty one
+    pub fn write(&self, type_state: &TypeState) {
+        let mut bytes = Vec::new();
+
+        if type_state.serialize(&mut bytes).is_ok() {
+            let _ = self.cache.write(&self.key, &bytes);
+        }
+    }
+}
@@ -63,6 +63,10 @@ fn load_and_typecheck(
         palette: DEFAULT_PALETTE,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Check,
+        expect_filter: None,
+        finalizers: Vec::new(),
+        types_cache_dir: None,
+        fold_constants: true,
     };
 
     match roc_load_internal::file::load(
@@ -2183,3 +2187,213 @@ fn roc_package_depends_on_other_package() {
 
     assert!(result.is_ok());
 }
+
+#[test]
+fn unchanged_module_types_come_from_cache() {
+    fn load(dir: &TmpDir, cache_dir: &TmpDir) -> HashMap<&'static str, bool> {
+        let arena = Bump::new();
+        let load_start = LoadStart::from_path(
+            &arena,
+            dir.path().join("Main.roc"),
+            None,
+            RenderTarget::Generic,
+            RocCacheDir::Disallowed,
+            DEFAULT_PALETTE,
+        )
+        .unwrap();
+        let load_config = LoadConfig {
+            target: TARGET,
+            function_kind: FunctionKind::LambdaSet,
+            render: RenderTarget::Generic,
+            palette: DEFAULT_PALETTE,
+            threading: Threading::Single,
+            exec_mode: ExecutionMode::Check,
+            expect_filter: None,
+            finalizers: Vec::new(),
+            types_cache_dir: Some(cache_dir.path().to_path_buf()),
+            fold_constants: true,
+        };
+
+        let loaded = match roc_load_internal::file::load(
+            &arena,
+            load_start,
+            Default::default(),
+            Default::default(),
+            RocCacheDir::Disallowed,
+            load_config,
+        ) {
+            Ok(LoadResult::TypeChecked(loaded)) => loaded,
+            Ok(LoadResult::Monomorphized(_)) => unreachable!(),
+            Err(problem) => panic!("{problem:?}"),
+        };
+
+        assert!(loaded.type_problems.values().all(Vec::is_empty));
+
+        ["Dep", "A", "B"]
+            .into_iter()
+            .map(|name| {
+                let module_id = loaded
+                    .interns
+                    .module_ids
+                    .get_id(&ModuleName::from(name))
+                    .unwrap();
+
+                (name, loaded.timings[&module_id].types_from_cache)
+            })
+            .collect()
+    }
+
+    let dir = TmpDir::new("tmp/unchanged_module_types_come_from_cache");
+    let cache_dir = TmpDir::new("tmp/unchanged_module_types_come_from_cache_cache");
+
+    let write = |name: &str, source: &str| std::fs::write(dir.path().join(name), source).unwrap();
+
+    write("Dep.roc", "module [dep]\n\ndep = \\n -> n + 1\n");
+    // A is solved against the types of Dep, B does not import either one
+    write(
+        "A.roc",
+        "module [a]\n\nimport Dep\n\na = \\n -> Dep.dep(n) * 2\n",
+    );
+    write("B.roc", "module [b]\n\nb = \\s -> Str.concat(s, \"!\")\n");
+    write(
+        "Main.roc",
+        "module [main]\n\nimport A\nimport B\n\nmain = \\n -> B.b(Num.to_str(A.a(n)))\n",
+    );
+
+    let nothing_cached = hashmap! { "Dep" => false, "A" => false, "B" => false };
+    assert_eq!(load(&dir, &cache_dir), nothing_cached);
+
+    let all_cached = hashmap! { "Dep" => true, "A" => true, "B" => true };
+    assert_eq!(load(&dir, &cache_dir), all_cached);
+
+    // changing an unrelated module does not solve A again
+    write("B.roc", "module [b]\n\nb = \\s -> Str.concat(s, \"?\")\n");
+    assert_eq!(
+        load(&dir, &cache_dir),
+        hashmap! { "Dep" => true, "A" => true, "B" => false }
+    );
+
+    // the type of dep changes, so A has to be solved against the new one
+    write(
+        "Dep.roc",
+        "module [dep]\n\ndep = \\n -> Num.to_u64(n) + 1\n",
+    );
+    assert_eq!(
+        load(&dir, &cache_dir),
+        hashmap! { "Dep" => false, "A" => false, "B" => true }
+    );
+}
+
+#[test]
+fn vendored_url_package_is_loaded_offline() {
+    use roc_packaging::tarball::{self, Compression};
+    use std::fs;
+
+    let dir = TmpDir::new("tmp/vendored_url_package_is_loaded_offline");
+    let write = |name: &str, source: &str| {
+        let path = dir.path().join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, source).unwrap();
+    };
+
+    write("greeting/main.roc", "package [Greeting] {}\n");
+    write(
+        "greeting/Greeting.roc",
+        "module [greet]\n\ngreet = \\name -> \"Hello, ${name}!\"\n",
+    );
+    let filename =
+        tarball::build(&dir.path().join("greeting/main.roc"), Compression::Gzip).unwrap();
+
+    let package_dir = dir.path().join("vendor");
+    fs::create_dir(&package_dir).unwrap();
+    fs::rename(
+        dir.path().join("greeting").join(&filename),
+        package_dir.join(&filename),
+    )
+    .unwrap();
+
+    write(
+        "platform/main.roc",
+        indoc!(
+            r#"
+            platform "testplatform"
+                requires {} { main : Str }
+                exposes []
+                packages {}
+                imports []
+                provides [main_for_host]
+
+            main_for_host : Str
+            main_for_host = main
+            "#
+        ),
+    );
+    // nothing can be downloaded from a .invalid domain
+    write(
+        "main.roc",
+        &formatdoc!(
+            r#"
+            app [main] {{
+                pf: platform "platform/main.roc",
+                greeting: "https://roc.invalid/packages/{filename}",
+            }}
+
+            import greeting.Greeting
+
+            main = Greeting.greet("World")
+            "#
+        ),
+    );
+
+    // no other test in this file loads a package from a URL
+    std::env::set_var("ROC_PACKAGE_DIR", &package_dir);
+
+    let cache_dir = dir.path().join("cache");
+    let arena = Bump::new();
+    let load_start = LoadStart::from_path(
+        &arena,
+        dir.path().join("main.roc"),
+        None,
+        RenderTarget::Generic,
+        RocCacheDir::Persistent(&cache_dir),
+        DEFAULT_PALETTE,
+    )
+    .unwrap();
+    let load_config = LoadConfig {
+        target: TARGET,
+        function_kind: FunctionKind::LambdaSet,
+        render: RenderTarget::Generic,
+        palette: DEFAULT_PALETTE,
+        threading: Threading::Single,
+        exec_mode: ExecutionMode::Check,
+        expect_filter: None,
+        finalizers: Vec::new(),
+        types_cache_dir: None,
+        fold_constants: true,
+    };
+
+    let loaded = match roc_load_internal::file::load(
+        &arena,
+        load_start,
+        Default::default(),
+        Default::default(),
+        RocCacheDir::Persistent(&cache_dir),
+        load_config,
+    ) {
+        Ok(LoadResult::TypeChecked(loaded)) => loaded,
+        Ok(LoadResult::Monomorphized(_)) => unreachable!(),
+        Err(LoadingProblem::FormattedReport(report, _)) => panic!("{report}"),
+        Err(problem) => panic!("{problem:?}"),
+    };
+
+    assert!(loaded.can_problems.values().all(Vec::is_empty));
+    assert!(loaded.type_problems.values().all(Vec::is_empty));
+
+    // the package was installed into the cache from the vendored tarball
+    let (greeting_path, _) = loaded
+        .sources
+        .values()
+        .find(|(path, _)| path.ends_with("Greeting.roc"))
+        .unwrap();
+    assert!(greeting_path.starts_with(&cache_dir));
+}
@@ -130,6 +130,8 @@ impl ModuleName {
     pub const DECODE: &'static str = "Decode";
     pub const HASH: &'static str = "Hash";
     pub const INSPECT: &'static str = "Inspect";
+    pub const JSON: &'static str = "Json";
+    pub const WEAK_REF: &'static str = "WeakRef";
 
     pub fn as_str(&self) -> &str {
         self.0.as_str()
@@ -3,7 +3,7 @@ use crate::symbol::Symbol;
 /// Low-level operations that get translated directly into e.g. LLVM instructions.
 /// These are always wrapped when exposed to end users, and can only make it
 /// into an Expr when added directly by can::builtins
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum LowLevel {
     StrConcat,
     StrJoinWith,
@@ -137,6 +137,11 @@ pub enum LowLevel {
     SetJmp,
     LongJmp,
     SetLongJmpBuffer,
+    ArenaEnter,
+    ArenaExit,
+    WeakRefDowngrade,
+    WeakRefIsAlive,
+    WeakRefGetUnsafe,
 }
 
 macro_rules! higher_order {
@@ -244,6 +249,9 @@ macro_rules! map_symbol_to_lowlevel {
                 LowLevel::LongJmp => unimplemented!(),
                 LowLevel::SetLongJmpBuffer => unimplemented!(),
 
+                LowLevel::ArenaEnter => unimplemented!(),
+                LowLevel::ArenaExit => unimplemented!(),
+
                 // these are not implemented, not sure why
                 LowLevel::StrFromInt => unimplemented!(),
                 LowLevel::StrFromFloat => unimplemented!(),
@@ -364,4 +372,7 @@ map_symbol_to_lowlevel! {
     Not <= BOOL_NOT;
     Unreachable <= LIST_UNREACHABLE;
     DictPseudoSeed <= DICT_PSEUDO_SEED;
+    WeakRefDowngrade <= WEAK_REF_DOWNGRADE;
+    WeakRefIsAlive <= WEAK_REF_IS_ALIVE;
+    WeakRefGetUnsafe <= WEAK_REF_GET_UNSAFE;
 }
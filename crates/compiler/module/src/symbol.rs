@@ -407,6 +407,10 @@ impl ModuleId {
     pub fn is_automatically_imported(self) -> bool {
         self.is_builtin()
     }
+
+    pub const fn to_ne_bytes(self) -> [u8; 4] {
+        self.0.get().to_ne_bytes()
+    }
 }
 
 impl fmt::Debug for ModuleId {
@@ -1736,6 +1740,20 @@ define_builtins! {
         32 INSPECT_TO_INSPECTOR: "to_inspector"
         33 INSPECT_TO_STR: "to_str"
     }
+    15 JSON: "Json" => {
+        0 JSON_JSON: "Json"
+        1 JSON_FIELD_NAME_MAPPING: "FieldNameMapping"
+        2 JSON_UTF8: "utf8"
+        3 JSON_UTF8_WITH: "utf8_with"
+        4 JSON_FROM_BYTES: "from_bytes"
+    }
+    16 WEAK_REF: "WeakRef" => {
+        0 WEAK_REF_WEAK_REF: "WeakRef"
+        1 WEAK_REF_DOWNGRADE: "downgrade"
+        2 WEAK_REF_GET: "get"
+        3 WEAK_REF_IS_ALIVE: "is_alive"
+        4 WEAK_REF_GET_UNSAFE: "get_unsafe"
+    }
 
-    num_modules: 15 // Keep this count up to date by hand! (TODO: see the mut_map! macro for how we could determine this count correctly in the macro)
+    num_modules: 17 // Keep this count up to date by hand! (TODO: see the mut_map! macro for how we could determine this count correctly in the macro)
 }
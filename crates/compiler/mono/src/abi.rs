@@ -0,0 +1,198 @@
+//! A version hash of the ABI between a platform's host and a Roc application.
+//!
+//! A host is compiled against the layouts of the types its platform exposes, and an application
+//! can be built much later against a platform that changed in the meantime. Linking the two
+//! works even when a field was added to a record or the reference counting convention changed,
+//! and the host then reads the wrong bytes. The application embeds the hash of its ABI as the
+//! [ABI_VERSION_SYMBOL] global, and `roc gen-header` gives the host the hash it was built for,
+//! so the host can refuse to run an application built for a different ABI.
+//!
+//! The hash only covers the shape of layouts, not their sizes: those follow from the shape and
+//! the target, and the host and the application are always built for the same target. Closures
+//! are hashed without looking inside them, because which closures a value can hold depends on
+//! the application.
+
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+
+use crate::layout::{Builtin, InLayout, LayoutInterner, LayoutRepr, UnionLayout};
+
+/// The global in the application object that holds its ABI version, as a `u64`
+pub const ABI_VERSION_SYMBOL: &str = "__roc_abi_version";
+
+/// Bump this when the reference counting protocol that hosts have to follow changes: where the
+/// refcount is stored relative to an allocation, the value that marks a unique or a static
+/// allocation, or how seamless slices are encoded.
+pub const RC_PROTOCOL_VERSION: u32 = 1;
+
+/// The ABI version of the entry points a platform exposes to its host, given as their names,
+/// argument layouts and result layouts. A value that is not a function has no arguments.
+pub fn abi_version<'a, 'b, I>(
+    interner: &I,
+    entry_points: impl IntoIterator<Item = (&'b str, &'b [InLayout<'a>], InLayout<'a>)>,
+) -> u64
+where
+    'a: 'b,
+    I: LayoutInterner<'a>,
+{
+    let mut entry_points: Vec<_> = entry_points.into_iter().collect();
+
+    // the order of the entry points is not part of the ABI
+    entry_points.sort_by_key(|(name, _, _)| *name);
+
+    let mut hasher = AbiHasher::new();
+
+    hasher.write_u32(RC_PROTOCOL_VERSION);
+
+    for (name, arguments, result) in entry_points {
+        hasher.write_len(name.len());
+        hasher.write(name.as_bytes());
+        hasher.write_layouts(interner, arguments);
+        hasher.write_layout(interner, result);
+    }
+
+    hasher.finish()
+}
+
+/// FNV-1a, because the hash has to be the same for every build of the compiler
+struct AbiHasher(u64);
+
+impl AbiHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    fn new() -> Self {
+        AbiHasher(Self::OFFSET_BASIS)
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn write_u8(&mut self, value: u8) {
+        self.write(&[value]);
+    }
+
+    fn write_u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn write_len(&mut self, len: usize) {
+        self.write_u32(len as u32);
+    }
+
+    fn write_layouts<'a>(&mut self, interner: &impl LayoutInterner<'a>, layouts: &[InLayout<'a>]) {
+        self.write_len(layouts.len());
+
+        for layout in layouts {
+            self.write_layout(interner, *layout);
+        }
+    }
+
+    fn write_tags<'a>(&mut self, interner: &impl LayoutInterner<'a>, tags: &[&[InLayout<'a>]]) {
+        self.write_len(tags.len());
+
+        for fields in tags {
+            self.write_layouts(interner, fields);
+        }
+    }
+
+    fn write_layout<'a>(&mut self, interner: &impl LayoutInterner<'a>, layout: InLayout<'a>) {
+        match interner.get_repr(layout) {
+            LayoutRepr::Builtin(builtin) => {
+                self.write_u8(0);
+                self.write_builtin(interner, builtin);
+            }
+            LayoutRepr::Struct(fields) => {
+                self.write_u8(1);
+                self.write_layouts(interner, fields);
+            }
+            LayoutRepr::Ptr(pointee) => {
+                self.write_u8(2);
+                self.write_layout(interner, pointee);
+            }
+            LayoutRepr::Union(union_layout) => {
+                self.write_u8(3);
+                self.write_union(interner, union_layout);
+            }
+            // the union that this points to is already being hashed
+            LayoutRepr::RecursivePointer(_) => self.write_u8(4),
+            LayoutRepr::LambdaSet(_) | LayoutRepr::FunctionPointer(_) | LayoutRepr::Erased(_) => {
+                self.write_u8(5)
+            }
+        }
+    }
+
+    fn write_builtin<'a>(&mut self, interner: &impl LayoutInterner<'a>, builtin: Builtin<'a>) {
+        match builtin {
+            Builtin::Int(width) => {
+                self.write_u8(0);
+                self.write_int_width(width);
+            }
+            Builtin::Float(width) => {
+                self.write_u8(1);
+                self.write_float_width(width);
+            }
+            Builtin::Bool => self.write_u8(2),
+            Builtin::Decimal => self.write_u8(3),
+            Builtin::Str => self.write_u8(4),
+            Builtin::List(element) => {
+                self.write_u8(5);
+                self.write_layout(interner, element);
+            }
+        }
+    }
+
+    fn write_union<'a>(&mut self, interner: &impl LayoutInterner<'a>, union: UnionLayout<'a>) {
+        match union {
+            UnionLayout::NonRecursive(tags) => {
+                self.write_u8(0);
+                self.write_tags(interner, tags);
+            }
+            UnionLayout::Recursive(tags) => {
+                self.write_u8(1);
+                self.write_tags(interner, tags);
+            }
+            UnionLayout::NonNullableUnwrapped(fields) => {
+                self.write_u8(2);
+                self.write_layouts(interner, fields);
+            }
+            UnionLayout::NullableWrapped {
+                nullable_id,
+                other_tags,
+            } => {
+                self.write_u8(3);
+                self.write(&nullable_id.to_le_bytes());
+                self.write_tags(interner, other_tags);
+            }
+            UnionLayout::NullableUnwrapped {
+                nullable_id,
+                other_fields,
+            } => {
+                self.write_u8(4);
+                self.write_u8(nullable_id as u8);
+                self.write_layouts(interner, other_fields);
+            }
+            UnionLayout::TaggedPointer { immediate_id, tags } => {
+                self.write_u8(5);
+                self.write(&immediate_id.to_le_bytes());
+                self.write_tags(interner, tags);
+            }
+        }
+    }
+
+    fn write_int_width(&mut self, width: IntWidth) {
+        self.write_u8(width.stack_size() as u8);
+        self.write_u8(width.is_signed() as u8);
+    }
+
+    fn write_float_width(&mut self, width: FloatWidth) {
+        self.write_u8(width.stack_size() as u8);
+    }
+}
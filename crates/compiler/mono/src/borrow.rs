@@ -95,7 +95,7 @@ impl std::ops::Index<usize> for BorrowSignature {
     }
 }
 
-pub(crate) struct BorrowSignatures<'a> {
+pub struct BorrowSignatures<'a> {
     pub(crate) procs: MutMap<(Symbol, ProcLayout<'a>), BorrowSignature>,
 }
 
@@ -0,0 +1,317 @@
+use bumpalo::collections::vec::Vec;
+use bumpalo::collections::CollectIn;
+use bumpalo::Bump;
+
+use roc_collections::{ImMap, ImSet, MutMap};
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::{IdentIds, ModuleId, Symbol};
+
+use crate::ir::{Call, CallType, Expr, Proc, ProcLayout, Stmt, UpdateModeIds};
+use crate::layout::{Builtin, InLayout, LayoutInterner, LayoutRepr, STLayoutInterner, UnionLayout};
+
+/**
+Skip the bounds check of a `List.get` whose index is already known to be in bounds.
+
+`List.get` compares the index with the length of the list before it reads the element. In the
+`then` branch of `if index < List.len list` (or `if List.len list > index`), that comparison is
+always true, so the call is replaced by the `ListGetUnsafe` it guards, wrapped in an `Ok`.
+Symbols are never reassigned, so the fact holds for the rest of the branch, including the join
+points defined in it. Nothing is concluded across a jump: the parameters of a join point are
+new symbols.
+
+This runs before reference counting is inserted, which then treats the `ListGetUnsafe` like any
+other. `List.set` keeps its check: an in-place update goes through an update mode that alias
+analysis needs to know about.
+*/
+pub fn elide_bounds_checks<'a, 'i>(
+    arena: &'a Bump,
+    layout_interner: &'i STLayoutInterner<'a>,
+    home: ModuleId,
+    ident_ids: &'i mut IdentIds,
+    update_mode_ids: &'i mut UpdateModeIds,
+    procs: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) {
+    let mut env = BoundsCheckEnv {
+        arena,
+        layout_interner,
+        home,
+        ident_ids,
+        update_mode_ids,
+        symbol_layouts: MutMap::default(),
+    };
+
+    for proc in procs.values_mut() {
+        for (layout, symbol) in proc.args.iter().copied() {
+            env.symbol_layouts.insert(symbol, layout);
+        }
+
+        let body = arena.alloc(proc.body.clone());
+        proc.body = env.elide_stmt(&mut Facts::default(), body).clone();
+
+        env.symbol_layouts.clear();
+    }
+}
+
+struct BoundsCheckEnv<'a, 'i> {
+    arena: &'a Bump,
+    layout_interner: &'i STLayoutInterner<'a>,
+    home: ModuleId,
+    ident_ids: &'i mut IdentIds,
+    update_mode_ids: &'i mut UpdateModeIds,
+    symbol_layouts: MutMap<Symbol, InLayout<'a>>,
+}
+
+/// What is known about the symbols in scope. Every branch gets its own copy.
+#[derive(Clone, Default)]
+struct Facts {
+    /// `len = List.len list`, by `len`
+    lengths: ImMap<Symbol, Symbol>,
+    /// `cond = index < List.len list`, as `(list, index)` by `cond`
+    comparisons: ImMap<Symbol, (Symbol, Symbol)>,
+    /// `(list, index)` pairs for which `index < List.len list`
+    in_bounds: ImSet<(Symbol, Symbol)>,
+}
+
+impl<'a, 'i> BoundsCheckEnv<'a, 'i> {
+    fn elide_stmt(&mut self, facts: &mut Facts, stmt: &'a Stmt<'a>) -> &'a Stmt<'a> {
+        let arena = self.arena;
+
+        match stmt {
+            Stmt::Let(symbol, expr, layout, continuation) => {
+                self.symbol_layouts.insert(*symbol, *layout);
+
+                if let Expr::Call(call) = expr {
+                    match called_function(call) {
+                        Some(Function::ListLen) => {
+                            facts.lengths.insert(*symbol, call.arguments[0]);
+                        }
+                        Some(Function::LessThan) => {
+                            let (index, len) = (call.arguments[0], call.arguments[1]);
+
+                            if let Some(list) = facts.lengths.get(&len).copied() {
+                                facts.comparisons.insert(*symbol, (list, index));
+                            }
+                        }
+                        Some(Function::GreaterThan) => {
+                            let (len, index) = (call.arguments[0], call.arguments[1]);
+
+                            if let Some(list) = facts.lengths.get(&len).copied() {
+                                facts.comparisons.insert(*symbol, (list, index));
+                            }
+                        }
+                        Some(Function::ListGet) => {
+                            let (list, index) = (call.arguments[0], call.arguments[1]);
+
+                            if facts.in_bounds.contains(&(list, index)) {
+                                if let Some((element, get, element_layout, ok)) =
+                                    self.unchecked_get(*layout, list, index)
+                                {
+                                    let continuation = arena_let(
+                                        arena,
+                                        *symbol,
+                                        ok,
+                                        *layout,
+                                        self.elide_stmt(facts, continuation),
+                                    );
+
+                                    return arena_let(
+                                        arena,
+                                        element,
+                                        get,
+                                        element_layout,
+                                        continuation,
+                                    );
+                                }
+                            }
+                        }
+                        None => {}
+                    }
+                }
+
+                arena.alloc(Stmt::Let(
+                    *symbol,
+                    expr.clone(),
+                    *layout,
+                    self.elide_stmt(facts, continuation),
+                ))
+            }
+            Stmt::Switch {
+                cond_symbol,
+                cond_layout,
+                branches,
+                default_branch,
+                ret_layout,
+            } => {
+                let known = facts.comparisons.get(cond_symbol).copied();
+
+                let branches = branches
+                    .iter()
+                    .map(|(label, info, branch)| {
+                        // `if cond then` is a switch with a single branch for `1`
+                        let mut facts = facts.clone();
+
+                        if let (Some(list_index), 1) = (known, *label) {
+                            facts.in_bounds.insert(list_index);
+                        }
+
+                        let branch = self.elide_stmt(&mut facts, branch);
+
+                        (*label, info.clone(), branch.clone())
+                    })
+                    .collect_in::<Vec<_>>(arena)
+                    .into_bump_slice();
+
+                let default_branch = (
+                    default_branch.0.clone(),
+                    self.elide_stmt(&mut facts.clone(), default_branch.1),
+                );
+
+                arena.alloc(Stmt::Switch {
+                    cond_symbol: *cond_symbol,
+                    cond_layout: *cond_layout,
+                    branches,
+                    default_branch,
+                    ret_layout: *ret_layout,
+                })
+            }
+            Stmt::Refcounting(modify_rc, continuation) => arena.alloc(Stmt::Refcounting(
+                *modify_rc,
+                self.elide_stmt(facts, continuation),
+            )),
+            Stmt::Expect {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => arena.alloc(Stmt::Expect {
+                condition: *condition,
+                region: *region,
+                lookups: *lookups,
+                variables: *variables,
+                remainder: self.elide_stmt(facts, remainder),
+            }),
+            Stmt::Dbg {
+                source_location,
+                source,
+                symbol,
+                variable,
+                remainder,
+            } => arena.alloc(Stmt::Dbg {
+                source_location: *source_location,
+                source: *source,
+                symbol: *symbol,
+                variable: *variable,
+                remainder: self.elide_stmt(facts, remainder),
+            }),
+            Stmt::Join {
+                id,
+                parameters,
+                body,
+                remainder,
+            } => {
+                for param in parameters.iter() {
+                    self.symbol_layouts.insert(param.symbol, param.layout);
+                }
+
+                arena.alloc(Stmt::Join {
+                    id: *id,
+                    parameters: *parameters,
+                    body: self.elide_stmt(&mut facts.clone(), body),
+                    remainder: self.elide_stmt(facts, remainder),
+                })
+            }
+            Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => stmt,
+        }
+    }
+
+    /// The parts of `let element = lowlevel ListGetUnsafe list index; let result = Ok element`,
+    /// for a result with the `Result element [OutOfBounds]` layout that `List.get` returns.
+    fn unchecked_get(
+        &mut self,
+        result_layout: InLayout<'a>,
+        list: Symbol,
+        index: Symbol,
+    ) -> Option<(Symbol, Expr<'a>, InLayout<'a>, Expr<'a>)> {
+        let list_layout = *self.symbol_layouts.get(&list)?;
+
+        let element_layout = match self.layout_interner.get_repr(list_layout) {
+            LayoutRepr::Builtin(Builtin::List(element_layout)) => element_layout,
+            _ => return None,
+        };
+
+        // tags are sorted by name, so `Err` comes before `Ok`
+        let tag_layout = match self.layout_interner.get_repr(result_layout) {
+            LayoutRepr::Union(union_layout @ UnionLayout::NonRecursive([_err, [ok]]))
+                if *ok == element_layout =>
+            {
+                union_layout
+            }
+            _ => return None,
+        };
+
+        let element = Symbol::new(self.home, self.ident_ids.add_str("unchecked_element"));
+        self.symbol_layouts.insert(element, element_layout);
+
+        let get = Expr::Call(Call {
+            call_type: CallType::LowLevel {
+                op: LowLevel::ListGetUnsafe,
+                update_mode: self.update_mode_ids.next_id(),
+            },
+            arguments: self.arena.alloc([list, index]),
+        });
+
+        let ok = Expr::Tag {
+            tag_layout,
+            tag_id: 1,
+            arguments: self.arena.alloc([element]),
+            reuse: None,
+        };
+
+        Some((element, get, element_layout, ok))
+    }
+}
+
+fn arena_let<'a>(
+    arena: &'a Bump,
+    symbol: Symbol,
+    expr: Expr<'a>,
+    layout: InLayout<'a>,
+    continuation: &'a Stmt<'a>,
+) -> &'a Stmt<'a> {
+    arena.alloc(Stmt::Let(symbol, expr, layout, continuation))
+}
+
+enum Function {
+    ListLen,
+    ListGet,
+    LessThan,
+    GreaterThan,
+}
+
+/// The functions this pass knows about, called either directly or through their wrapper
+fn called_function(call: &Call<'_>) -> Option<Function> {
+    let function = match &call.call_type {
+        CallType::ByName { name, .. } => match name.name() {
+            Symbol::LIST_LEN_U64 => Function::ListLen,
+            Symbol::LIST_GET => Function::ListGet,
+            Symbol::NUM_LT => Function::LessThan,
+            Symbol::NUM_GT => Function::GreaterThan,
+            _ => return None,
+        },
+        CallType::LowLevel { op, .. } => match op {
+            LowLevel::ListLenU64 => Function::ListLen,
+            LowLevel::NumLt => Function::LessThan,
+            LowLevel::NumGt => Function::GreaterThan,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let arity = match function {
+        Function::ListLen => 1,
+        Function::ListGet | Function::LessThan | Function::GreaterThan => 2,
+    };
+
+    (call.arguments.len() == arity).then_some(function)
+}
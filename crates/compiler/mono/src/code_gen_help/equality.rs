@@ -212,7 +212,7 @@ fn eq_tag_union<'a>(
             NullableId::None,
         ),
 
-        Recursive(tags) => eq_tag_union_help(
+        Recursive(tags) | TaggedPointer { tags, .. } => eq_tag_union_help(
             root,
             ident_ids,
             ctx,
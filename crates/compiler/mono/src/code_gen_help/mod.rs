@@ -11,7 +11,7 @@ use crate::ir::{
 };
 use crate::layout::{
     Builtin, InLayout, LambdaName, Layout, LayoutInterner, LayoutRepr, LayoutWrapper, Niche,
-    STLayoutInterner, UnionLayout,
+    STLayoutInterner, TagIdIntType, UnionLayout,
 };
 
 mod copy;
@@ -652,6 +652,11 @@ impl<'a> CodeGenHelp<'a> {
 
             Recursive(tags) => self.union_tail_recursion_fields_help(union_in_layout, tags),
 
+            TaggedPointer { immediate_id, tags } => self.union_tail_recursion_fields_help(
+                union_in_layout,
+                allocated_tags(self.arena, tags, immediate_id),
+            ),
+
             NonNullableUnwrapped(field_layouts) => {
                 self.union_tail_recursion_fields_help(union_in_layout, &[field_layouts])
             }
@@ -1055,6 +1060,22 @@ fn let_lowlevel<'a>(
     )
 }
 
+/// The tags of a [UnionLayout::TaggedPointer] that are allocated, which are all but the
+/// immediate one. The immediate tag is then handled like the null tag of a nullable union.
+fn allocated_tags<'a>(
+    arena: &'a Bump,
+    tags: &'a [&'a [InLayout<'a>]],
+    immediate_id: TagIdIntType,
+) -> &'a [&'a [InLayout<'a>]] {
+    let allocated = tags
+        .iter()
+        .enumerate()
+        .filter(|(tag_id, _)| *tag_id != immediate_id as usize)
+        .map(|(_, fields)| *fields);
+
+    arena.alloc_slice_fill_iter(allocated)
+}
+
 fn layout_needs_helper_proc<'a>(
     layout_interner: &STLayoutInterner<'a>,
     layout: InLayout<'a>,
@@ -6,7 +6,7 @@ use roc_error_macros::todo_lambda_erasure;
 use roc_module::low_level::{LowLevel, LowLevel::*};
 use roc_module::symbol::{IdentIds, Symbol};
 
-use crate::code_gen_help::let_lowlevel;
+use crate::code_gen_help::{allocated_tags, let_lowlevel};
 use crate::ir::{
     BranchInfo, Call, CallType, Expr, JoinPointId, Literal, ModifyRc, Param, Stmt, UpdateModeId,
 };
@@ -283,6 +283,10 @@ pub fn refcount_reset_proc_body<'a>(
             Recursive(tags) => {
                 tag_layouts = tags;
             }
+            TaggedPointer { immediate_id, tags } => {
+                null_id = Some(immediate_id);
+                tag_layouts = allocated_tags(root.arena, tags, immediate_id);
+            }
             NonNullableUnwrapped(field_layouts) => {
                 tag_layouts = root.arena.alloc([field_layouts]);
             }
@@ -1165,6 +1169,38 @@ fn refcount_union<'a>(
                 )
             }
         }
+
+        // the immediate tag is not allocated, so it is refcounted like the null tag of a
+        // nullable union
+        TaggedPointer { immediate_id, tags } => {
+            let null_id = Some(immediate_id);
+            let tags = allocated_tags(root.arena, tags, immediate_id);
+            let tailrec_idx = root.union_tail_recursion_fields(union_in_layout, union);
+            if let (Some(tail_idx), true) = (tailrec_idx, ctx.op.is_dec()) {
+                refcount_union_tailrec(
+                    root,
+                    ident_ids,
+                    ctx,
+                    layout_interner,
+                    union,
+                    tags,
+                    null_id,
+                    tail_idx,
+                    structure,
+                )
+            } else {
+                refcount_union_rec(
+                    root,
+                    ident_ids,
+                    ctx,
+                    layout_interner,
+                    union,
+                    tags,
+                    null_id,
+                    structure,
+                )
+            }
+        }
     };
 
     ctx.recursive_union = parent_rec_ptr_layout;
@@ -0,0 +1,468 @@
+use bumpalo::collections::vec::Vec;
+use bumpalo::collections::CollectIn;
+use bumpalo::Bump;
+
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_collections::{MutMap, MutSet};
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+use roc_std::RocDec;
+
+use crate::ir::{Call, CallType, Expr, ListLiteralElement, Literal, Proc, ProcLayout, Stmt};
+use crate::layout::{Builtin, InLayout, LayoutInterner, LayoutRepr, STLayoutInterner};
+
+/**
+Evaluate the arithmetic on literals at compile time.
+
+`Num.add`, `Num.sub`, `Num.mul`, `Num.div` and `Num.div_trunc` of two literals, `Bool.not` of a
+literal and `List.len` of a list literal are replaced by the literal they evaluate to, which is
+then known to the calls after it. The backends cannot do this themselves: the arithmetic is a call
+of the wrapper of a lowlevel, which checks for overflow. A call that would crash at runtime, on an
+overflow or a division by zero, is left as it is.
+
+The literals that were only used by folded calls are removed, so that `x = 3 + 4` in the end is
+just `x = 7`. This runs before reference counting, which then does not see the list literals that
+were only there to be measured.
+*/
+pub fn fold_constants<'a>(
+    arena: &'a Bump,
+    layout_interner: &STLayoutInterner<'a>,
+    procs: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) {
+    let mut env = FoldEnv {
+        arena,
+        layout_interner,
+        literals: MutMap::default(),
+        list_lengths: MutMap::default(),
+        uses: MutMap::default(),
+        folded_arguments: MutSet::default(),
+    };
+
+    for proc in procs.values_mut() {
+        env.uses = count_uses(&proc.body);
+
+        let body = arena.alloc(proc.body.clone());
+        proc.body = env.fold_stmt(body).clone();
+
+        env.literals.clear();
+        env.list_lengths.clear();
+        env.folded_arguments.clear();
+    }
+}
+
+struct FoldEnv<'a, 'i> {
+    arena: &'a Bump,
+    layout_interner: &'i STLayoutInterner<'a>,
+    /// `x = <literal>`, by `x`. Symbols are never reassigned, so this holds wherever `x` is in
+    /// scope.
+    literals: MutMap<Symbol, Literal<'a>>,
+    /// `list = [..]`, the length by `list`
+    list_lengths: MutMap<Symbol, u64>,
+    /// How often every symbol is used, not counting the calls that were folded
+    uses: MutMap<Symbol, usize>,
+    /// The arguments of the calls that were folded
+    folded_arguments: MutSet<Symbol>,
+}
+
+impl<'a, 'i> FoldEnv<'a, 'i> {
+    fn fold_stmt(&mut self, stmt: &'a Stmt<'a>) -> &'a Stmt<'a> {
+        let arena = self.arena;
+
+        match stmt {
+            Stmt::Let(symbol, expr, layout, continuation) => {
+                let expr = match expr {
+                    Expr::Call(call) => match self.fold_call(call, *layout) {
+                        Some(literal) => Expr::Literal(literal),
+                        None => expr.clone(),
+                    },
+                    _ => expr.clone(),
+                };
+
+                match &expr {
+                    Expr::Literal(literal) => {
+                        self.literals.insert(*symbol, *literal);
+                    }
+                    Expr::Array { elems, .. } => {
+                        self.list_lengths.insert(*symbol, elems.len() as u64);
+                    }
+                    Expr::EmptyArray => {
+                        self.list_lengths.insert(*symbol, 0);
+                    }
+                    _ => {}
+                }
+
+                let continuation = self.fold_stmt(continuation);
+
+                // the literals and lists are left unused by folding only after the continuation
+                // is folded
+                let unused = self.folded_arguments.contains(symbol)
+                    && self.uses.get(symbol).copied().unwrap_or_default() == 0;
+
+                if unused {
+                    continuation
+                } else {
+                    arena.alloc(Stmt::Let(*symbol, expr, *layout, continuation))
+                }
+            }
+            Stmt::Switch {
+                cond_symbol,
+                cond_layout,
+                branches,
+                default_branch,
+                ret_layout,
+            } => {
+                let branches = branches
+                    .iter()
+                    .map(|(label, info, branch)| {
+                        (*label, info.clone(), self.fold_stmt(branch).clone())
+                    })
+                    .collect_in::<Vec<_>>(arena)
+                    .into_bump_slice();
+
+                let default_branch = (default_branch.0.clone(), self.fold_stmt(default_branch.1));
+
+                arena.alloc(Stmt::Switch {
+                    cond_symbol: *cond_symbol,
+                    cond_layout: *cond_layout,
+                    branches,
+                    default_branch,
+                    ret_layout: *ret_layout,
+                })
+            }
+            Stmt::Refcounting(modify_rc, continuation) => {
+                arena.alloc(Stmt::Refcounting(*modify_rc, self.fold_stmt(continuation)))
+            }
+            Stmt::Expect {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => arena.alloc(Stmt::Expect {
+                condition: *condition,
+                region: *region,
+                lookups: *lookups,
+                variables: *variables,
+                remainder: self.fold_stmt(remainder),
+            }),
+            Stmt::Dbg {
+                source_location,
+                source,
+                symbol,
+                variable,
+                remainder,
+            } => arena.alloc(Stmt::Dbg {
+                source_location: *source_location,
+                source: *source,
+                symbol: *symbol,
+                variable: *variable,
+                remainder: self.fold_stmt(remainder),
+            }),
+            Stmt::Join {
+                id,
+                parameters,
+                body,
+                remainder,
+            } => arena.alloc(Stmt::Join {
+                id: *id,
+                parameters: *parameters,
+                body: self.fold_stmt(body),
+                remainder: self.fold_stmt(remainder),
+            }),
+            Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => stmt,
+        }
+    }
+
+    /// The literal that `call` evaluates to, if its arguments are known
+    fn fold_call(&mut self, call: &Call<'a>, layout: InLayout<'a>) -> Option<Literal<'a>> {
+        let literal = match called_function(call)? {
+            Function::Arithmetic(op) => {
+                let a = self.literals.get(&call.arguments[0])?;
+                let b = self.literals.get(&call.arguments[1])?;
+
+                fold_arithmetic(op, a, b, self.layout_interner.get_repr(layout))?
+            }
+            Function::Not => match self.literals.get(&call.arguments[0])? {
+                Literal::Bool(b) => Literal::Bool(!b),
+                _ => return None,
+            },
+            Function::ListLen => {
+                let len = self.list_lengths.get(&call.arguments[0])?;
+
+                Literal::Int((*len as i128).to_ne_bytes())
+            }
+        };
+
+        for argument in call.arguments {
+            if let Some(uses) = self.uses.get_mut(argument) {
+                *uses -= 1;
+            }
+
+            self.folded_arguments.insert(*argument);
+        }
+
+        Some(literal)
+    }
+}
+
+fn fold_arithmetic<'a>(
+    op: Arithmetic,
+    a: &Literal<'a>,
+    b: &Literal<'a>,
+    repr: LayoutRepr<'a>,
+) -> Option<Literal<'a>> {
+    match repr {
+        LayoutRepr::Builtin(Builtin::Int(IntWidth::U128)) => {
+            let (a, b) = (to_u128(a)?, to_u128(b)?);
+
+            let n = match op {
+                Arithmetic::Add => a.checked_add(b),
+                Arithmetic::Sub => a.checked_sub(b),
+                Arithmetic::Mul => a.checked_mul(b),
+                Arithmetic::Div => a.checked_div(b),
+            }?;
+
+            // like the literals in the source, the ones that fit an i128 are stored as one
+            Some(match i128::try_from(n) {
+                Ok(n) => Literal::Int(n.to_ne_bytes()),
+                Err(_) => Literal::U128(n.to_ne_bytes()),
+            })
+        }
+        LayoutRepr::Builtin(Builtin::Int(width)) => {
+            let (a, b) = (to_i128(a)?, to_i128(b)?);
+
+            let n = match op {
+                Arithmetic::Add => a.checked_add(b),
+                Arithmetic::Sub => a.checked_sub(b),
+                Arithmetic::Mul => a.checked_mul(b),
+                Arithmetic::Div => a.checked_div(b),
+            }?;
+
+            fits_in(n, width).then_some(Literal::Int(n.to_ne_bytes()))
+        }
+        LayoutRepr::Builtin(Builtin::Float(width)) => {
+            let (Literal::Float(a), Literal::Float(b)) = (a, b) else {
+                return None;
+            };
+
+            // the arithmetic is done at the width of the layout, to round like the lowlevel
+            let n = match width {
+                FloatWidth::F32 => f64::from(float_arithmetic(op, *a as f32, *b as f32)),
+                FloatWidth::F64 => float_arithmetic(op, *a, *b),
+            };
+
+            Some(Literal::Float(n))
+        }
+        LayoutRepr::Builtin(Builtin::Decimal) => {
+            let (Literal::Decimal(a), Literal::Decimal(b)) = (a, b) else {
+                return None;
+            };
+
+            let (a, b) = (RocDec::from_ne_bytes(*a), RocDec::from_ne_bytes(*b));
+
+            let n = match op {
+                Arithmetic::Add => a.checked_add(b),
+                Arithmetic::Sub => a.checked_sub(b),
+                Arithmetic::Mul => a.checked_mul(b),
+                Arithmetic::Div => a.checked_div(b),
+            }?;
+
+            Some(Literal::Decimal(n.to_ne_bytes()))
+        }
+        _ => None,
+    }
+}
+
+fn float_arithmetic<F>(op: Arithmetic, a: F, b: F) -> F
+where
+    F: std::ops::Add<Output = F>
+        + std::ops::Sub<Output = F>
+        + std::ops::Mul<Output = F>
+        + std::ops::Div<Output = F>,
+{
+    match op {
+        Arithmetic::Add => a + b,
+        Arithmetic::Sub => a - b,
+        Arithmetic::Mul => a * b,
+        Arithmetic::Div => a / b,
+    }
+}
+
+fn to_i128(literal: &Literal<'_>) -> Option<i128> {
+    match literal {
+        Literal::Int(bytes) => Some(i128::from_ne_bytes(*bytes)),
+        Literal::U128(bytes) => i128::try_from(u128::from_ne_bytes(*bytes)).ok(),
+        _ => None,
+    }
+}
+
+fn to_u128(literal: &Literal<'_>) -> Option<u128> {
+    match literal {
+        Literal::Int(bytes) => u128::try_from(i128::from_ne_bytes(*bytes)).ok(),
+        Literal::U128(bytes) => Some(u128::from_ne_bytes(*bytes)),
+        _ => None,
+    }
+}
+
+/// Whether an integer of `width` can hold `n`, for any width but `U128`
+fn fits_in(n: i128, width: IntWidth) -> bool {
+    let bits = width.stack_size() * 8;
+
+    match (width.is_signed(), bits) {
+        (true, 128) => true,
+        (true, _) => (-(1 << (bits - 1))..(1 << (bits - 1))).contains(&n),
+        (false, _) => (0..(1 << bits)).contains(&n),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Arithmetic {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+enum Function {
+    Arithmetic(Arithmetic),
+    Not,
+    ListLen,
+}
+
+/// The functions this pass knows about, called either directly or through their wrapper
+fn called_function(call: &Call<'_>) -> Option<Function> {
+    let function = match &call.call_type {
+        CallType::ByName { name, .. } => match name.name() {
+            Symbol::NUM_ADD => Function::Arithmetic(Arithmetic::Add),
+            Symbol::NUM_SUB => Function::Arithmetic(Arithmetic::Sub),
+            Symbol::NUM_MUL => Function::Arithmetic(Arithmetic::Mul),
+            // `div` is only defined on fractions, and `div_trunc` only on integers
+            Symbol::NUM_DIV_FRAC | Symbol::NUM_DIV_TRUNC => Function::Arithmetic(Arithmetic::Div),
+            Symbol::BOOL_NOT => Function::Not,
+            Symbol::LIST_LEN_U64 => Function::ListLen,
+            _ => return None,
+        },
+        CallType::LowLevel { op, .. } => match op {
+            LowLevel::NumAdd => Function::Arithmetic(Arithmetic::Add),
+            LowLevel::NumSub => Function::Arithmetic(Arithmetic::Sub),
+            LowLevel::NumMul => Function::Arithmetic(Arithmetic::Mul),
+            LowLevel::NumDivFrac | LowLevel::NumDivTruncUnchecked => {
+                Function::Arithmetic(Arithmetic::Div)
+            }
+            LowLevel::Not => Function::Not,
+            LowLevel::ListLenU64 => Function::ListLen,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let arity = match function {
+        Function::Not | Function::ListLen => 1,
+        Function::Arithmetic(_) => 2,
+    };
+
+    (call.arguments.len() == arity).then_some(function)
+}
+
+/// How often every symbol is used in `body`
+fn count_uses(body: &Stmt<'_>) -> MutMap<Symbol, usize> {
+    let mut uses = MutMap::default();
+    let mut used = |symbol: &Symbol| *uses.entry(*symbol).or_insert(0) += 1;
+
+    // a body is a long chain of statements, so it is walked without recursion
+    let mut stack = vec![body];
+
+    while let Some(stmt) = stack.pop() {
+        match stmt {
+            Stmt::Let(_, expr, _, continuation) => {
+                match expr {
+                    Expr::Call(Call {
+                        call_type,
+                        arguments,
+                    }) => {
+                        arguments.iter().for_each(&mut used);
+
+                        match call_type {
+                            CallType::ByPointer { pointer, .. } => used(pointer),
+                            CallType::HigherOrder(higher_order) => {
+                                used(&higher_order.passed_function.captured_environment)
+                            }
+                            CallType::ByName { .. }
+                            | CallType::Foreign { .. }
+                            | CallType::LowLevel { .. } => {}
+                        }
+                    }
+                    Expr::Tag {
+                        arguments, reuse, ..
+                    } => {
+                        arguments.iter().for_each(&mut used);
+                        reuse.iter().for_each(|token| used(&token.symbol));
+                    }
+                    Expr::Struct(fields) => fields.iter().for_each(&mut used),
+                    Expr::StructAtIndex { structure, .. }
+                    | Expr::GetTagId { structure, .. }
+                    | Expr::UnionAtIndex { structure, .. }
+                    | Expr::GetElementPointer { structure, .. } => used(structure),
+                    Expr::Array { elems, .. } => elems
+                        .iter()
+                        .filter_map(ListLiteralElement::to_symbol)
+                        .for_each(|symbol| used(&symbol)),
+                    Expr::ErasedMake { value, callee } => {
+                        value.iter().for_each(&mut used);
+                        used(callee);
+                    }
+                    Expr::ErasedLoad { symbol, .. }
+                    | Expr::Reset { symbol, .. }
+                    | Expr::ResetRef { symbol, .. } => used(symbol),
+                    Expr::Alloca { initializer, .. } => initializer.iter().for_each(&mut used),
+                    Expr::Literal(_)
+                    | Expr::NullPointer
+                    | Expr::EmptyArray
+                    | Expr::FunctionPointer { .. } => {}
+                }
+
+                stack.push(continuation);
+            }
+            Stmt::Switch {
+                cond_symbol,
+                branches,
+                default_branch,
+                ..
+            } => {
+                used(cond_symbol);
+                stack.extend(branches.iter().map(|(_, _, branch)| branch));
+                stack.push(default_branch.1);
+            }
+            Stmt::Ret(symbol) | Stmt::Crash(symbol, _) => used(symbol),
+            Stmt::Refcounting(modify_rc, continuation) => {
+                used(&modify_rc.get_symbol());
+                stack.push(continuation);
+            }
+            Stmt::Expect {
+                condition,
+                lookups,
+                remainder,
+                ..
+            } => {
+                used(condition);
+                lookups.iter().for_each(&mut used);
+                stack.push(remainder);
+            }
+            Stmt::Dbg {
+                symbol, remainder, ..
+            } => {
+                used(symbol);
+                stack.push(remainder);
+            }
+            Stmt::Join {
+                body, remainder, ..
+            } => {
+                stack.push(body);
+                stack.push(remainder);
+            }
+            Stmt::Jump(_, arguments) => arguments.iter().for_each(&mut used),
+        }
+    }
+
+    uses
+}
@@ -0,0 +1,144 @@
+use roc_collections::{MutMap, MutSet};
+use roc_module::symbol::Symbol;
+
+use crate::ir::{Call, CallType, Expr, Proc, ProcLayout, Stmt};
+
+/**
+Remove the procedures that can never be called.
+
+Specialization makes a procedure for every `(symbol, layout)` that some specialized code asks
+for, but the code that asked for it does not always survive: the request can come from code that
+is itself never reached from what the host calls. Starting from the procedures of `roots`, this
+marks every procedure that is called by name or passed to a higher-order lowlevel, at the layout
+of the call, and sweeps the rest.
+
+A procedure that is only referenced as a function pointer keeps every specialization of its
+symbol, because a pointer does not name a layout.
+
+This runs once the entry points are known, for executables only: the expects of `roc test` are
+roots that the host does not call.
+*/
+pub fn remove_dead_procs<'a>(
+    procs: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    roots: impl IntoIterator<Item = Symbol>,
+) {
+    let mut layouts_of_symbol: MutMap<Symbol, Vec<ProcLayout<'a>>> = MutMap::default();
+
+    for (symbol, layout) in procs.keys() {
+        layouts_of_symbol.entry(*symbol).or_default().push(*layout);
+    }
+
+    let mut reachable: MutSet<(Symbol, ProcLayout<'a>)> = MutSet::default();
+    let mut worklist = Vec::new();
+
+    let reach_symbol = |symbol: Symbol, worklist: &mut Vec<_>| {
+        for layout in layouts_of_symbol.get(&symbol).into_iter().flatten() {
+            worklist.push((symbol, *layout));
+        }
+    };
+
+    for symbol in roots {
+        reach_symbol(symbol, &mut worklist);
+    }
+
+    while let Some(key) = worklist.pop() {
+        if !reachable.insert(key) {
+            continue;
+        }
+
+        if let Some(proc) = procs.get(&key) {
+            for callee in references(&proc.body) {
+                match callee {
+                    Reference::Proc(symbol, layout) if procs.contains_key(&(symbol, layout)) => {
+                        worklist.push((symbol, layout))
+                    }
+                    // a layout that matches no specialization is not trusted to mean that the
+                    // procedure is dead
+                    Reference::Proc(symbol, _) | Reference::Symbol(symbol) => {
+                        reach_symbol(symbol, &mut worklist)
+                    }
+                }
+            }
+        }
+    }
+
+    procs.retain(|key, _| reachable.contains(key));
+}
+
+enum Reference<'a> {
+    /// A call of one specialization
+    Proc(Symbol, ProcLayout<'a>),
+    /// A function pointer, to any specialization of the symbol
+    Symbol(Symbol),
+}
+
+fn references<'a>(body: &Stmt<'a>) -> Vec<Reference<'a>> {
+    let mut refs = Vec::new();
+
+    // a body is a long chain of statements, so it is walked without recursion
+    let mut stack = vec![body];
+
+    while let Some(stmt) = stack.pop() {
+        match stmt {
+            Stmt::Let(_, expr, _, cont) => {
+                match expr {
+                    Expr::Call(Call { call_type, .. }) => match call_type {
+                        CallType::ByName {
+                            name,
+                            ret_layout,
+                            arg_layouts,
+                            ..
+                        } => refs.push(Reference::Proc(
+                            name.name(),
+                            ProcLayout {
+                                arguments: arg_layouts,
+                                result: *ret_layout,
+                                niche: name.niche(),
+                            },
+                        )),
+                        CallType::HigherOrder(higher_order) => {
+                            let passed = &higher_order.passed_function;
+
+                            refs.push(Reference::Proc(
+                                passed.name.name(),
+                                ProcLayout {
+                                    arguments: passed.argument_layouts,
+                                    result: passed.return_layout,
+                                    niche: passed.name.niche(),
+                                },
+                            ))
+                        }
+                        CallType::ByPointer { .. }
+                        | CallType::Foreign { .. }
+                        | CallType::LowLevel { .. } => {}
+                    },
+                    Expr::FunctionPointer { lambda_name } => {
+                        refs.push(Reference::Symbol(lambda_name.name()))
+                    }
+                    _ => {}
+                }
+
+                stack.push(cont);
+            }
+            Stmt::Switch {
+                branches,
+                default_branch,
+                ..
+            } => {
+                stack.extend(branches.iter().map(|(_, _, branch)| branch));
+                stack.push(default_branch.1);
+            }
+            Stmt::Refcounting(_, cont) => stack.push(cont),
+            Stmt::Expect { remainder, .. } | Stmt::Dbg { remainder, .. } => stack.push(remainder),
+            Stmt::Join {
+                body, remainder, ..
+            } => {
+                stack.push(body);
+                stack.push(remainder);
+            }
+            Stmt::Ret(_) | Stmt::Jump(..) | Stmt::Crash(..) => {}
+        }
+    }
+
+    refs
+}
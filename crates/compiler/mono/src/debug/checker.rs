@@ -878,7 +878,7 @@ fn get_tag_id_payloads(union_layout: UnionLayout, tag_id: TagIdIntType) -> TagPa
             let payloads = union[tag_id as usize];
             TagPayloads::Payloads(payloads)
         }
-        UnionLayout::Recursive(union) => {
+        UnionLayout::Recursive(union) | UnionLayout::TaggedPointer { tags: union, .. } => {
             check_tag_id_oob!(union.len());
             let payloads = union[tag_id as usize];
             TagPayloads::Payloads(payloads)
@@ -531,16 +531,23 @@ fn specialize_drops_stmt<'a, 'i>(
                             &mut incremented_children,
                             continuation,
                         ),
-                        LayoutRepr::Union(union_layout) => specialize_union(
-                            arena,
-                            layout_interner,
-                            ident_ids,
-                            environment,
-                            symbol,
-                            union_layout,
-                            &mut incremented_children,
-                            continuation,
-                        ),
+                        // Only the decrement runs the finalizer, so it must not be replaced. The
+                        // decrement of a weak reference does not decrement the box.
+                        LayoutRepr::Union(union_layout)
+                            if layout_interner.finalizer(*in_layout).is_none()
+                                && !layout_interner.is_weak_ref(*in_layout) =>
+                        {
+                            specialize_union(
+                                arena,
+                                layout_interner,
+                                ident_ids,
+                                environment,
+                                symbol,
+                                union_layout,
+                                &mut incremented_children,
+                                continuation,
+                            )
+                        }
                         LayoutRepr::Builtin(Builtin::List(layout)) => specialize_list(
                             arena,
                             layout_interner,
@@ -988,7 +995,8 @@ fn specialize_union<'a, 'i>(
                         UnionLayout::Recursive(_)
                         | UnionLayout::NonNullableUnwrapped(_)
                         | UnionLayout::NullableWrapped { .. }
-                        | UnionLayout::NullableUnwrapped { .. } => {
+                        | UnionLayout::NullableUnwrapped { .. }
+                        | UnionLayout::TaggedPointer { .. } => {
                             branch_uniqueness(
                                 arena,
                                 ident_ids,
@@ -1213,6 +1221,17 @@ fn get_union_tag_layout(union_layout: UnionLayout<'_>, tag: Option<Tag>) -> Unio
             field_layouts: union_layouts[tag as usize],
             tag,
         },
+        (UnionLayout::TaggedPointer { immediate_id, tags }, Some(tag)) => {
+            if tag == immediate_id {
+                // the union is the integer itself, there is nothing to drop.
+                UnionFieldLayouts::Null
+            } else {
+                UnionFieldLayouts::Found {
+                    field_layouts: tags[tag as usize],
+                    tag,
+                }
+            }
+        }
         (UnionLayout::NonNullableUnwrapped(union_layouts), None) => {
             // This union has just a single tag. So the tag is 0.
             UnionFieldLayouts::Found {
@@ -1614,6 +1633,9 @@ fn low_level_no_rc(lowlevel: &LowLevel) -> RC {
         | NumDecFromBits => RC::NoRc,
         NumWithoutDecimalPoint | NumWithDecimalPoint => RC::NoRc,
         DictPseudoSeed => RC::NoRc,
+        ArenaEnter | ArenaExit => RC::NoRc,
+        // these read the refcount of the box
+        WeakRefDowngrade | WeakRefIsAlive | WeakRefGetUnsafe => RC::Rc,
         StrStartsWith | StrEndsWith => RC::NoRc,
         StrFromUtf8 => RC::Rc,
         StrFromUtf8Lossy => RC::Rc,
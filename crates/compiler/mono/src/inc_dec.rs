@@ -28,12 +28,13 @@ use crate::{
 
 /**
 Insert the reference count operations for procedures.
+Returns the borrow signatures that were inferred for them.
 */
 pub fn insert_inc_dec_operations<'a>(
     arena: &'a Bump,
     layout_interner: &STLayoutInterner<'a>,
     procedures: &mut HashMap<(Symbol, ProcLayout<'a>), Proc<'a>, BuildHasherDefault<WyHash>>,
-) {
+) -> &'a crate::borrow::BorrowSignatures<'a> {
     let borrow_signatures =
         crate::borrow::infer_borrow_signatures(arena, layout_interner, procedures);
     let borrow_signatures = arena.alloc(borrow_signatures);
@@ -52,6 +53,8 @@ pub fn insert_inc_dec_operations<'a>(
             insert_inc_dec_operations_proc(arena, symbol_rc_types_env, borrow_signatures, proc);
         }
     }
+
+    borrow_signatures
 }
 
 /// Enum indicating whether a symbol should be reference counted or not.
@@ -225,17 +228,17 @@ impl<'a, 'i> SymbolRcTypesEnv<'a, 'i> {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub(crate) enum Ownership {
+pub enum Ownership {
     Owned,
     Borrowed,
 }
 
 impl Ownership {
-    pub(crate) fn is_owned(&self) -> bool {
+    pub fn is_owned(&self) -> bool {
         matches!(self, Ownership::Owned)
     }
 
-    pub(crate) fn is_borrowed(&self) -> bool {
+    pub fn is_borrowed(&self) -> bool {
         matches!(self, Ownership::Borrowed)
     }
 }
@@ -1066,6 +1069,24 @@ fn insert_refcount_operations_binding<'a>(
                         };
                         new_let!(newer_stmt)
                     }
+                    // Like List get unsafe, this returns the value in the box that the weak
+                    // reference points to, so its reference count has to be incremented.
+                    LowLevel::WeakRefGetUnsafe => {
+                        let weak = match arguments {
+                            [weak] => *weak,
+                            _ => unreachable!("WeakRef get unsafe should have one argument"),
+                        };
+                        let new_stmt = dec_borrowed!([weak], stmt);
+                        let newer_stmt = if matches!(
+                            environment.get_symbol_rc_type(binding),
+                            VarRcType::ReferenceCounted
+                        ) {
+                            insert_inc_stmt(arena, *binding, 1, new_stmt)
+                        } else {
+                            new_stmt
+                        };
+                        new_let!(newer_stmt)
+                    }
                     // Otherwise, perform regular reference counting using the lowlevel borrow signature.
                     _ => {
                         let borrow_signature = lowlevel_borrow_signature(operator);
@@ -1243,7 +1264,7 @@ fn insert_dec_stmt<'a>(
 /**
  * Retrieve the borrow signature of a low-level operation.
  */
-pub(crate) fn lowlevel_borrow_signature(op: LowLevel) -> &'static [Ownership] {
+pub fn lowlevel_borrow_signature(op: LowLevel) -> &'static [Ownership] {
     use LowLevel::*;
 
     const IRRELEVANT: Ownership = Ownership::Owned;
@@ -1260,6 +1281,9 @@ pub(crate) fn lowlevel_borrow_signature(op: LowLevel) -> &'static [Ownership] {
     match op {
         Unreachable => &[IRRELEVANT],
         DictPseudoSeed => &[IRRELEVANT],
+        ArenaEnter => &[],
+        ArenaExit => &[IRRELEVANT],
+        WeakRefDowngrade | WeakRefIsAlive | WeakRefGetUnsafe => &[BORROWED],
         ListLenU64 | ListLenUsize | StrIsEmpty | StrCountUtf8Bytes | ListGetCapacity => &[BORROWED],
         ListWithCapacity | StrWithCapacity => &[IRRELEVANT],
         ListReplaceUnsafe => &[OWNED, IRRELEVANT, IRRELEVANT],
@@ -0,0 +1,662 @@
+use bumpalo::collections::vec::Vec;
+use bumpalo::collections::CollectIn;
+use bumpalo::Bump;
+
+use roc_collections::{MutMap, MutSet};
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+
+use crate::borrow::BorrowSignatures;
+use crate::inc_dec::{lowlevel_borrow_signature, Ownership};
+use crate::ir::{Call, CallType, Expr, ListLiteralElement, ModifyRc, Proc, ProcLayout, Stmt};
+use crate::layout::Niche;
+
+/**
+Remove an `inc x` that is cancelled out by a later `dec x` in the same block.
+
+Between the two, the reference that `inc` adds is never used: the extra reference count only
+matters to code that consumes `x` or looks at its reference count. So the pair can go when the
+statements between them:
+
+- cannot free anything, which could free `x` while it is one reference short. That rules out
+  every `dec`, every call to a procedure, and every lowlevel with an owned argument.
+- do not store `x` in another value, which would then hold a reference to it.
+- do not check the uniqueness of anything.
+
+One call may come before the `dec x` when it takes `x` as an owned argument, and `x` is a field
+that only it reads from an argument of the procedure. The `inc` then gives the call a reference
+that the `dec` would give back when the field is dropped, so the field's own reference can move
+into the call instead. Nothing after the call may use `x`.
+
+The search stays within a single block: it stops at a `switch`, a join point, a `jump` and a
+`ret`, and never looks inside the body of a higher-order call.
+
+This runs after drop specialization, which cancels increments of the children of a value
+against the decrement of the value itself.
+*/
+pub fn remove_inc_dec_pairs<'a>(
+    arena: &'a Bump,
+    borrow_signatures: &BorrowSignatures<'a>,
+    procs: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) {
+    for proc in procs.values_mut() {
+        let env = Env {
+            arena,
+            borrow_signatures,
+            movable_fields: movable_fields(arena, borrow_signatures, proc),
+        };
+
+        let body = arena.alloc(proc.body.clone());
+        proc.body = remove_pairs_stmt(&env, body).clone();
+    }
+}
+
+struct Env<'a, 'b> {
+    arena: &'a Bump,
+    borrow_signatures: &'b BorrowSignatures<'a>,
+    /// Fields that may move into a call, see [movable_fields].
+    movable_fields: MutSet<Symbol>,
+}
+
+impl<'a> Env<'a, '_> {
+    /// Whether `call` takes `symbol` as its only owned use of it.
+    fn moves_into(&self, call: &Call<'a>, symbol: Symbol) -> bool {
+        let CallType::ByName {
+            name,
+            arg_layouts,
+            ret_layout,
+            ..
+        } = call.call_type.clone().replace_lowlevel_wrapper()
+        else {
+            return false;
+        };
+
+        let mut positions = call
+            .arguments
+            .iter()
+            .enumerate()
+            .filter(|(_, argument)| **argument == symbol)
+            .map(|(index, _)| index);
+        let (Some(index), None) = (positions.next(), positions.next()) else {
+            return false;
+        };
+
+        let proc_layout = ProcLayout {
+            arguments: arg_layouts,
+            result: ret_layout,
+            niche: Niche::NONE,
+        };
+
+        match self
+            .borrow_signatures
+            .procs
+            .get(&(name.name(), proc_layout))
+        {
+            Some(signature) => matches!(signature.iter().nth(index), Some(Ownership::Owned)),
+            None => false,
+        }
+    }
+}
+
+fn remove_pairs_stmt<'a>(env: &Env<'a, '_>, stmt: &'a Stmt<'a>) -> &'a Stmt<'a> {
+    let arena = env.arena;
+
+    match stmt {
+        Stmt::Refcounting(ModifyRc::Inc(symbol, count), continuation) => {
+            let movable = env.movable_fields.contains(symbol);
+
+            match without_matching_dec(env, *symbol, movable, continuation) {
+                Some(continuation) if *count == 1 => remove_pairs_stmt(env, continuation),
+                Some(continuation) => remove_pairs_stmt(
+                    env,
+                    arena.alloc(Stmt::Refcounting(
+                        ModifyRc::Inc(*symbol, count - 1),
+                        continuation,
+                    )),
+                ),
+                None => arena.alloc(Stmt::Refcounting(
+                    ModifyRc::Inc(*symbol, *count),
+                    remove_pairs_stmt(env, continuation),
+                )),
+            }
+        }
+        Stmt::Refcounting(modify_rc, continuation) => arena.alloc(Stmt::Refcounting(
+            *modify_rc,
+            remove_pairs_stmt(env, continuation),
+        )),
+        Stmt::Let(symbol, expr, layout, continuation) => arena.alloc(Stmt::Let(
+            *symbol,
+            expr.clone(),
+            *layout,
+            remove_pairs_stmt(env, continuation),
+        )),
+        Stmt::Switch {
+            cond_symbol,
+            cond_layout,
+            branches,
+            default_branch,
+            ret_layout,
+        } => {
+            let branches = branches
+                .iter()
+                .map(|(label, info, branch)| {
+                    (*label, info.clone(), remove_pairs_stmt(env, branch).clone())
+                })
+                .collect_in::<Vec<_>>(arena)
+                .into_bump_slice();
+
+            let default_branch = (
+                default_branch.0.clone(),
+                remove_pairs_stmt(env, default_branch.1),
+            );
+
+            arena.alloc(Stmt::Switch {
+                cond_symbol: *cond_symbol,
+                cond_layout: *cond_layout,
+                branches,
+                default_branch,
+                ret_layout: *ret_layout,
+            })
+        }
+        Stmt::Expect {
+            condition,
+            region,
+            lookups,
+            variables,
+            remainder,
+        } => arena.alloc(Stmt::Expect {
+            condition: *condition,
+            region: *region,
+            lookups: *lookups,
+            variables: *variables,
+            remainder: remove_pairs_stmt(env, remainder),
+        }),
+        Stmt::Dbg {
+            source_location,
+            source,
+            symbol,
+            variable,
+            remainder,
+        } => arena.alloc(Stmt::Dbg {
+            source_location: *source_location,
+            source: *source,
+            symbol: *symbol,
+            variable: *variable,
+            remainder: remove_pairs_stmt(env, remainder),
+        }),
+        Stmt::Join {
+            id,
+            parameters,
+            body,
+            remainder,
+        } => arena.alloc(Stmt::Join {
+            id: *id,
+            parameters: *parameters,
+            body: remove_pairs_stmt(env, body),
+            remainder: remove_pairs_stmt(env, remainder),
+        }),
+        Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => stmt,
+    }
+}
+
+/// The statements after an `inc symbol`, with the `dec symbol` that cancels it removed. `None`
+/// when something before that `dec` could observe the missing reference.
+fn without_matching_dec<'a>(
+    env: &Env<'a, '_>,
+    symbol: Symbol,
+    movable: bool,
+    stmt: &'a Stmt<'a>,
+) -> Option<&'a Stmt<'a>> {
+    let arena = env.arena;
+
+    match stmt {
+        Stmt::Refcounting(ModifyRc::Dec(dec_symbol), continuation) if *dec_symbol == symbol => {
+            Some(continuation)
+        }
+        // an increment never frees anything
+        Stmt::Refcounting(modify_rc @ ModifyRc::Inc(..), continuation) => {
+            let continuation = without_matching_dec(env, symbol, movable, continuation)?;
+
+            Some(arena.alloc(Stmt::Refcounting(*modify_rc, continuation)))
+        }
+        Stmt::Let(let_symbol, expr, layout, continuation) if keeps_references(expr, symbol) => {
+            let continuation = without_matching_dec(env, symbol, movable, continuation)?;
+
+            Some(arena.alloc(Stmt::Let(*let_symbol, expr.clone(), *layout, continuation)))
+        }
+        Stmt::Let(let_symbol, expr, layout, continuation)
+            if movable && matches!(expr, Expr::Call(call) if env.moves_into(call, symbol)) =>
+        {
+            let continuation = without_dec_after_move(arena, symbol, continuation)?;
+
+            Some(arena.alloc(Stmt::Let(*let_symbol, expr.clone(), *layout, continuation)))
+        }
+        _ => None,
+    }
+}
+
+/// The statements after a call that `symbol` moved into, with the `dec symbol` that drops the
+/// field it came from removed. `None` when something before that `dec` still uses `symbol`.
+fn without_dec_after_move<'a>(
+    arena: &'a Bump,
+    symbol: Symbol,
+    stmt: &'a Stmt<'a>,
+) -> Option<&'a Stmt<'a>> {
+    match stmt {
+        Stmt::Refcounting(ModifyRc::Dec(dec_symbol), continuation) if *dec_symbol == symbol => {
+            Some(continuation)
+        }
+        Stmt::Refcounting(modify_rc, continuation) if modify_rc.get_symbol() != symbol => {
+            let continuation = without_dec_after_move(arena, symbol, continuation)?;
+
+            Some(arena.alloc(Stmt::Refcounting(*modify_rc, continuation)))
+        }
+        Stmt::Let(let_symbol, expr, layout, continuation) if !mentions(expr, symbol) => {
+            let continuation = without_dec_after_move(arena, symbol, continuation)?;
+
+            Some(arena.alloc(Stmt::Let(*let_symbol, expr.clone(), *layout, continuation)))
+        }
+        _ => None,
+    }
+}
+
+/**
+The fields of the procedure's owned arguments that can move into a call, in `let x =
+StructAtIndex i arg; inc x`. While a field is read by no other symbol, and its argument is only
+ever used to read fields, `x` is the only way to reach the field.
+*/
+fn movable_fields<'a>(
+    arena: &'a Bump,
+    borrow_signatures: &BorrowSignatures<'a>,
+    proc: &Proc<'a>,
+) -> MutSet<Symbol> {
+    let mut movable_fields = MutSet::default();
+
+    let Some(signature) = borrow_signatures
+        .procs
+        .get(&(proc.name.name(), proc.proc_layout(arena)))
+    else {
+        return movable_fields;
+    };
+
+    let mut uses = FieldUses::default();
+    uses.stmt(&proc.body);
+
+    for ((structure, _), readers) in uses.readers {
+        let is_owned_argument =
+            proc.args
+                .iter()
+                .zip(signature.iter())
+                .any(|((_, argument), ownership)| {
+                    *argument == structure && matches!(ownership, Ownership::Owned)
+                });
+
+        if !is_owned_argument || uses.other.contains(&structure) {
+            continue;
+        }
+
+        if let [field] = readers.as_slice() {
+            if uses.increment_after_read.contains(field) {
+                movable_fields.insert(*field);
+            }
+        }
+    }
+
+    movable_fields
+}
+
+#[derive(Default)]
+struct FieldUses {
+    /// The symbols that read each field of a structure.
+    readers: MutMap<(Symbol, u64), std::vec::Vec<Symbol>>,
+    /// Fields that are incremented right after they are read.
+    increment_after_read: MutSet<Symbol>,
+    /// Symbols that are used other than by reading a field.
+    other: MutSet<Symbol>,
+}
+
+impl FieldUses {
+    fn stmt(&mut self, stmt: &Stmt<'_>) {
+        match stmt {
+            Stmt::Let(
+                symbol,
+                Expr::StructAtIndex {
+                    index, structure, ..
+                },
+                _,
+                continuation,
+            ) => {
+                self.readers
+                    .entry((*structure, *index))
+                    .or_default()
+                    .push(*symbol);
+
+                if let Stmt::Refcounting(ModifyRc::Inc(inc_symbol, _), _) = continuation {
+                    if inc_symbol == symbol {
+                        self.increment_after_read.insert(*symbol);
+                    }
+                }
+
+                self.stmt(continuation);
+            }
+            Stmt::Let(_, expr, _, continuation) => {
+                for_each_symbol(expr, |symbol| {
+                    self.other.insert(symbol);
+                });
+                self.stmt(continuation);
+            }
+            Stmt::Switch {
+                cond_symbol,
+                branches,
+                default_branch,
+                ..
+            } => {
+                self.other.insert(*cond_symbol);
+
+                for (_, _, branch) in branches.iter() {
+                    self.stmt(branch);
+                }
+
+                self.stmt(default_branch.1);
+            }
+            Stmt::Ret(symbol) | Stmt::Crash(symbol, _) => {
+                self.other.insert(*symbol);
+            }
+            Stmt::Refcounting(modify_rc, continuation) => {
+                self.other.insert(modify_rc.get_symbol());
+                self.stmt(continuation);
+            }
+            Stmt::Expect {
+                condition,
+                lookups,
+                remainder,
+                ..
+            } => {
+                self.other.insert(*condition);
+                self.other.extend(lookups.iter().copied());
+                self.stmt(remainder);
+            }
+            Stmt::Dbg {
+                symbol, remainder, ..
+            } => {
+                self.other.insert(*symbol);
+                self.stmt(remainder);
+            }
+            Stmt::Join {
+                body, remainder, ..
+            } => {
+                self.stmt(body);
+                self.stmt(remainder);
+            }
+            Stmt::Jump(_, arguments) => {
+                self.other.extend(arguments.iter().copied());
+            }
+        }
+    }
+}
+
+/// Whether evaluating `expr` uses `symbol`.
+fn mentions(expr: &Expr<'_>, symbol: Symbol) -> bool {
+    let mut found = false;
+    for_each_symbol(expr, |used| found |= used == symbol);
+
+    found
+}
+
+/// Call `f` with every symbol that evaluating `expr` uses.
+fn for_each_symbol(expr: &Expr<'_>, mut f: impl FnMut(Symbol)) {
+    let mut all = |symbols: &[Symbol]| symbols.iter().copied().for_each(&mut f);
+
+    match expr {
+        Expr::Literal(_) | Expr::NullPointer | Expr::EmptyArray | Expr::FunctionPointer { .. } => {}
+        Expr::Call(call) => {
+            all(call.arguments);
+
+            match &call.call_type {
+                CallType::ByPointer { pointer, .. } => all(&[*pointer]),
+                CallType::HigherOrder(higher_order) => {
+                    all(&[higher_order.passed_function.captured_environment])
+                }
+                CallType::ByName { .. } | CallType::Foreign { .. } | CallType::LowLevel { .. } => {}
+            }
+        }
+        Expr::Tag {
+            arguments, reuse, ..
+        } => {
+            all(arguments);
+
+            if let Some(token) = reuse {
+                all(&[token.symbol]);
+            }
+        }
+        Expr::Struct(arguments) => all(arguments),
+        Expr::StructAtIndex { structure, .. }
+        | Expr::GetTagId { structure, .. }
+        | Expr::UnionAtIndex { structure, .. }
+        | Expr::GetElementPointer { structure, .. } => all(&[*structure]),
+        Expr::Array { elems, .. } => {
+            for elem in elems.iter() {
+                if let ListLiteralElement::Symbol(symbol) = elem {
+                    all(&[*symbol]);
+                }
+            }
+        }
+        Expr::ErasedMake { value, callee } => {
+            all(&[*callee]);
+            all(value.as_slice());
+        }
+        Expr::ErasedLoad { symbol, .. }
+        | Expr::Reset { symbol, .. }
+        | Expr::ResetRef { symbol, .. } => all(&[*symbol]),
+        Expr::Alloca { initializer, .. } => all(initializer.as_slice()),
+    }
+}
+
+/// Whether evaluating `expr` leaves every reference count alone, and does not make a new
+/// reference to `symbol`.
+fn keeps_references(expr: &Expr<'_>, symbol: Symbol) -> bool {
+    match expr {
+        Expr::Literal(_)
+        | Expr::NullPointer
+        | Expr::EmptyArray
+        | Expr::FunctionPointer { .. }
+        // reading a field borrows the structure
+        | Expr::StructAtIndex { .. }
+        | Expr::GetTagId { .. }
+        | Expr::UnionAtIndex { .. }
+        | Expr::GetElementPointer { .. } => true,
+        // other values move into the new value without being freed
+        Expr::Struct(arguments)
+        | Expr::Tag {
+            arguments,
+            reuse: None,
+            ..
+        } => !arguments.contains(&symbol),
+        Expr::Array { elems, .. } => !elems
+            .iter()
+            .any(|elem| matches!(elem, ListLiteralElement::Symbol(s) if *s == symbol)),
+        Expr::Call(call) => match &call.call_type {
+            CallType::LowLevel { op, .. } => match op {
+                // inserted after borrow inference, so they have no borrow signature
+                LowLevel::PtrClearTagId
+                | LowLevel::RefCountIncRcPtr
+                | LowLevel::RefCountDecRcPtr
+                | LowLevel::RefCountIncDataPtr
+                | LowLevel::RefCountDecDataPtr
+                | LowLevel::RefCountIsUnique
+                | LowLevel::SetJmp
+                | LowLevel::LongJmp
+                | LowLevel::SetLongJmpBuffer => false,
+                // a lowlevel that borrows all of its arguments only reads them
+                _ => lowlevel_borrow_signature(*op)
+                    .iter()
+                    .all(|ownership| matches!(ownership, Ownership::Borrowed)),
+            },
+            // the callee may consume its arguments, or anything reachable from them
+            CallType::ByName { .. }
+            | CallType::ByPointer { .. }
+            | CallType::Foreign { .. }
+            | CallType::HigherOrder(_) => false,
+        },
+        Expr::Tag { reuse: Some(_), .. }
+        | Expr::ErasedMake { .. }
+        | Expr::ErasedLoad { .. }
+        | Expr::Reset { .. }
+        | Expr::ResetRef { .. }
+        | Expr::Alloca { .. } => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::ir::{Call, UpdateModeId};
+    use crate::layout::Layout;
+
+    fn str_len<'a>(arena: &'a Bump, string: Symbol) -> Expr<'a> {
+        Expr::Call(Call {
+            call_type: CallType::LowLevel {
+                op: LowLevel::StrCountUtf8Bytes,
+                update_mode: UpdateModeId::BACKEND_DUMMY,
+            },
+            arguments: arena.alloc([string]),
+        })
+    }
+
+    /// `let len = StrCountUtf8Bytes string; <continuation>`
+    fn let_str_len<'a>(
+        arena: &'a Bump,
+        len: Symbol,
+        string: Symbol,
+        continuation: &'a Stmt<'a>,
+    ) -> &'a Stmt<'a> {
+        arena.alloc(Stmt::Let(
+            len,
+            str_len(arena, string),
+            Layout::U64,
+            continuation,
+        ))
+    }
+
+    fn remove_pairs<'a>(arena: &'a Bump, stmt: &'a Stmt<'a>) -> &'a Stmt<'a> {
+        let borrow_signatures = BorrowSignatures {
+            procs: MutMap::default(),
+        };
+
+        let env = Env {
+            arena,
+            borrow_signatures: &borrow_signatures,
+            movable_fields: MutSet::default(),
+        };
+
+        remove_pairs_stmt(&env, stmt)
+    }
+
+    fn modify_rc<'a>(
+        arena: &'a Bump,
+        modify_rc: ModifyRc,
+        continuation: &'a Stmt<'a>,
+    ) -> &'a Stmt<'a> {
+        arena.alloc(Stmt::Refcounting(modify_rc, continuation))
+    }
+
+    #[test]
+    fn pair_around_borrowed_use_is_removed() {
+        let arena = Bump::new();
+        let (string, len) = (Symbol::ARG_1, Symbol::ARG_2);
+        let ret: &Stmt = arena.alloc(Stmt::Ret(len));
+
+        // inc string; let len = StrCountUtf8Bytes string; dec string; ret len
+        let dec = modify_rc(&arena, ModifyRc::Dec(string), ret);
+        let stmt = modify_rc(
+            &arena,
+            ModifyRc::Inc(string, 1),
+            let_str_len(&arena, len, string, dec),
+        );
+
+        let expected = let_str_len(&arena, len, string, ret);
+
+        assert_eq!(remove_pairs(&arena, stmt), expected);
+    }
+
+    #[test]
+    fn pair_around_other_dec_is_kept() {
+        let arena = Bump::new();
+        let (string, other, len) = (Symbol::ARG_1, Symbol::ARG_2, Symbol::ARG_3);
+        let ret: &Stmt = arena.alloc(Stmt::Ret(len));
+
+        // `other` may hold the only other reference to `string`
+        let dec = modify_rc(&arena, ModifyRc::Dec(string), ret);
+        let stmt = modify_rc(
+            &arena,
+            ModifyRc::Inc(string, 1),
+            modify_rc(
+                &arena,
+                ModifyRc::Dec(other),
+                let_str_len(&arena, len, string, dec),
+            ),
+        );
+
+        assert_eq!(remove_pairs(&arena, stmt), stmt);
+    }
+
+    #[test]
+    fn multiple_increment_is_reduced() {
+        let arena = Bump::new();
+        let (string, len) = (Symbol::ARG_1, Symbol::ARG_2);
+        let ret: &Stmt = arena.alloc(Stmt::Ret(len));
+
+        // inc string 2; let len = StrCountUtf8Bytes string; dec string; ret len
+        let dec = modify_rc(&arena, ModifyRc::Dec(string), ret);
+        let stmt = modify_rc(
+            &arena,
+            ModifyRc::Inc(string, 2),
+            let_str_len(&arena, len, string, dec),
+        );
+
+        let expected = modify_rc(
+            &arena,
+            ModifyRc::Inc(string, 1),
+            let_str_len(&arena, len, string, ret),
+        );
+
+        assert_eq!(remove_pairs(&arena, stmt), expected);
+    }
+
+    #[test]
+    fn dec_after_move_is_removed() {
+        let arena = Bump::new();
+        let (string, other, len) = (Symbol::ARG_1, Symbol::ARG_2, Symbol::ARG_3);
+        let ret: &Stmt = arena.alloc(Stmt::Ret(len));
+
+        // let len = StrCountUtf8Bytes other; dec string; ret len
+        let stmt = let_str_len(
+            &arena,
+            len,
+            other,
+            modify_rc(&arena, ModifyRc::Dec(string), ret),
+        );
+
+        let expected = let_str_len(&arena, len, other, ret);
+
+        assert_eq!(without_dec_after_move(&arena, string, stmt), Some(expected));
+    }
+
+    #[test]
+    fn dec_after_move_is_kept_when_used_again() {
+        let arena = Bump::new();
+        let (string, len) = (Symbol::ARG_1, Symbol::ARG_2);
+        let ret: &Stmt = arena.alloc(Stmt::Ret(len));
+
+        // the call may have freed `string` already
+        let stmt = let_str_len(
+            &arena,
+            len,
+            string,
+            modify_rc(&arena, ModifyRc::Dec(string), ret),
+        );
+
+        assert_eq!(without_dec_after_move(&arena, string, stmt), None);
+    }
+}
@@ -31,7 +31,7 @@ use roc_module::symbol::{IdentIds, ModuleId, Symbol};
 use roc_problem::can::{RuntimeError, ShadowKind};
 use roc_region::all::{Loc, Region};
 use roc_std::RocDec;
-use roc_target::Target;
+use roc_target::{PtrWidth, Target};
 use roc_types::subs::{
     instantiate_rigids, storage_copy_var_to, Content, ExhaustiveMark, FlatType, RedundantMark,
     StorageSubs, Subs, Variable, VariableSubsSlice,
@@ -43,6 +43,7 @@ use pattern::{from_can_pattern, store_pattern, Pattern};
 
 pub use literal::{ListLiteralElement, Literal};
 
+mod arena;
 mod boxed;
 mod decision_tree;
 mod erased;
@@ -3640,6 +3641,7 @@ fn specialize_proc_help<'a>(
                                     | UnionLayout::Recursive(_)
                                     | UnionLayout::NullableUnwrapped { .. }
                                     | UnionLayout::NullableWrapped { .. }
+                                    | UnionLayout::TaggedPointer { .. }
                             ));
                             debug_assert_eq!(field_layouts.len(), captured.len());
 
@@ -5699,6 +5701,32 @@ pub fn with_hole<'a>(
             }
         }
 
+        ForeignCall {
+            foreign_symbol,
+            mut args,
+            ret_var,
+        } if foreign_symbol.as_str() == arena::WITH_ARENA_FOREIGN_SYMBOL && args.len() == 1 => {
+            let (thunk_var, thunk) = args.pop().unwrap();
+
+            let layout = return_on_layout_error!(
+                env,
+                layout_cache.from_var(env.arena, ret_var, env.subs),
+                "with_arena!"
+            );
+
+            arena::with_arena(
+                env,
+                procs,
+                layout_cache,
+                thunk_var,
+                thunk,
+                ret_var,
+                layout,
+                assigned,
+                hole,
+            )
+        }
+
         ForeignCall {
             foreign_symbol,
             args,
@@ -5851,6 +5879,13 @@ pub fn with_hole<'a>(
 
                     Stmt::Let(assigned, expr, layout, hole)
                 }
+                // weak references are counted in the upper half of the refcount, which leaves too
+                // few bits for either count on 32-bit targets
+                WeakRefDowngrade | WeakRefIsAlive | WeakRefGetUnsafe
+                    if env.target.ptr_width() == PtrWidth::Bytes4 =>
+                {
+                    runtime_error(env, "WeakRef is only supported on 64-bit targets")
+                }
                 _ => {
                     let call = self::Call {
                         call_type: CallType::LowLevel {
@@ -6603,7 +6638,10 @@ fn convert_tag_union<'a>(
 
             use WrappedVariant::*;
             let (tag, union_layout) = match variant {
-                Recursive { sorted_tag_layouts } => {
+                Recursive { sorted_tag_layouts }
+                | TaggedPointer {
+                    sorted_tag_layouts, ..
+                } => {
                     debug_assert!(sorted_tag_layouts.len() > 1);
 
                     field_symbols = {
@@ -6721,6 +6759,35 @@ fn convert_tag_union<'a>(
                         reuse: None,
                     };
 
+                    (tag, union_layout)
+                }
+                NullableBox { nullable_id, .. } => {
+                    field_symbols = {
+                        let mut temp = Vec::with_capacity_in(field_symbols_temp.len(), arena);
+
+                        temp.extend(field_symbols_temp.iter().map(|r| r.1));
+
+                        temp.into_bump_slice()
+                    };
+
+                    let tag = if nullable_id == (tag_id != 0) {
+                        Expr::Tag {
+                            tag_layout: union_layout,
+                            tag_id: tag_id as _,
+                            arguments: &[],
+                            reuse: None,
+                        }
+                    } else {
+                        // the box is the value
+                        Expr::Call(Call {
+                            call_type: CallType::LowLevel {
+                                op: LowLevel::PtrCast,
+                                update_mode: env.next_update_mode_id(),
+                            },
+                            arguments: field_symbols,
+                        })
+                    };
+
                     (tag, union_layout)
                 }
             };
@@ -10253,7 +10320,7 @@ where
                         stack.push(layout_interner.get(*in_layout));
                     }
                 }
-                UnionLayout::Recursive(tags) => {
+                UnionLayout::Recursive(tags) | UnionLayout::TaggedPointer { tags, .. } => {
                     for in_layout in tags.iter().flat_map(|e| e.iter()) {
                         stack.push(layout_interner.get(*in_layout));
                     }
@@ -0,0 +1,116 @@
+//! `with_arena!`, an effect that a platform declares and the compiler implements.
+//!
+//! A platform that handles one request at a time can declare
+//!
+//! ```roc
+//! with_arena! : ({} => a) => a
+//! ```
+//!
+//! in one of its hosted modules, and wrap the handling of a request in it. Everything that is
+//! allocated while the function it is given runs then comes from an arena, which is bumped
+//! instead of calling `roc_alloc`, and freed as a whole when the function returns: freeing a
+//! value in between costs nothing.
+//!
+//! The arena is entered with [LowLevel::ArenaEnter], which gives back the arena, and exited with
+//! [LowLevel::ArenaExit], which takes it. Most allocations are made by the builtins, so rather
+//! than passing the arena to each of them, entering an arena makes it the arena that `alloc` in
+//! utils.zig takes memory from, until it is exited.
+//!
+//! Nothing that is allocated in the arena may outlive it, so the result may not contain
+//! refcounted values. Values that the host is given while the arena is entered must not be kept
+//! by the host either.
+
+use roc_module::called_via::CalledVia;
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+use roc_region::all::Loc;
+use roc_types::subs::{Content, FlatType, Variable};
+
+use crate::layout::{InLayout, Layout, LayoutCache, LayoutInterner};
+
+use super::{runtime_error, with_hole, Call, CallType, Env, Expr, Procs, Stmt};
+
+/// The foreign symbol of a hosted `with_arena!`
+pub const WITH_ARENA_FOREIGN_SYMBOL: &str = "roc_fx_with_arena";
+
+/// Call `thunk` with `{}` in an arena, instead of calling `with_arena!` on the host
+#[allow(clippy::too_many_arguments)]
+pub(super) fn with_arena<'a>(
+    env: &mut Env<'a, '_>,
+    procs: &mut Procs<'a>,
+    layout_cache: &mut LayoutCache<'a>,
+    thunk_var: Variable,
+    thunk: roc_can::expr::Expr,
+    ret_var: Variable,
+    ret_layout: InLayout<'a>,
+    assigned: Symbol,
+    hole: &'a Stmt<'a>,
+) -> Stmt<'a> {
+    if layout_cache.interner.contains_refcounted(ret_layout) {
+        return runtime_error(
+            env,
+            "with_arena! can only return values that are not refcounted, because everything it allocates is freed when it returns",
+        );
+    }
+
+    let (lambda_set_var, fx_var) = match env.subs.get_content_without_compacting(thunk_var) {
+        Content::Structure(FlatType::Func(_, lambda_set_var, _, fx_var)) => {
+            (*lambda_set_var, *fx_var)
+        }
+        _ => return runtime_error(env, "with_arena! must be given a function"),
+    };
+
+    let arena_symbol = env.unique_symbol();
+    let exited_symbol = env.unique_symbol();
+
+    let exit = Stmt::Let(
+        exited_symbol,
+        Expr::Call(Call {
+            call_type: CallType::LowLevel {
+                op: LowLevel::ArenaExit,
+                update_mode: env.next_update_mode_id(),
+            },
+            arguments: env.arena.alloc([arena_symbol]),
+        }),
+        Layout::UNIT,
+        hole,
+    );
+
+    let call = roc_can::expr::Expr::Call(
+        Box::new((
+            thunk_var,
+            Loc::at_zero(thunk),
+            lambda_set_var,
+            ret_var,
+            fx_var,
+        )),
+        vec![(
+            Variable::EMPTY_RECORD,
+            Loc::at_zero(roc_can::expr::Expr::EmptyRecord),
+        )],
+        CalledVia::Space,
+    );
+
+    let result = with_hole(
+        env,
+        call,
+        ret_var,
+        procs,
+        layout_cache,
+        assigned,
+        env.arena.alloc(exit),
+    );
+
+    Stmt::Let(
+        arena_symbol,
+        Expr::Call(Call {
+            call_type: CallType::LowLevel {
+                op: LowLevel::ArenaEnter,
+                update_mode: env.next_update_mode_id(),
+            },
+            arguments: &[],
+        }),
+        Layout::OPAQUE_PTR,
+        env.arena.alloc(result),
+    )
+}
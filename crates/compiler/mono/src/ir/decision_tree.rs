@@ -364,7 +364,11 @@ fn tests_are_complete_help(last_test: &Test, number_of_tests: usize) -> bool {
         Test::IsCtor { union, .. } => number_of_tests == union.alternatives.len(),
         Test::IsByte { num_alts, .. } => number_of_tests == *num_alts,
         Test::IsBit(_) => number_of_tests == 2,
-        Test::IsInt(_, _) => false,
+        Test::IsInt(_, width) => {
+            // every value of the integer has its own test
+            let bits = 8 * width.stack_size();
+            bits < usize::BITS && number_of_tests == 1 << bits
+        }
         Test::IsFloat(_, _) => false,
         Test::IsDecimal(_) => false,
         Test::IsStr(_) => false,
@@ -1049,7 +1053,8 @@ fn to_relevant_branch_help<'a>(
                         UnionLayout::NonRecursive(_)
                         | UnionLayout::Recursive(_)
                         | UnionLayout::NullableWrapped { .. }
-                        | UnionLayout::NullableUnwrapped { .. } => {
+                        | UnionLayout::NullableUnwrapped { .. }
+                        | UnionLayout::TaggedPointer { .. } => {
                             let sub_positions =
                                 arguments
                                     .into_iter()
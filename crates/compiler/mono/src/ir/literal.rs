@@ -111,8 +111,8 @@ pub fn make_num_literal<'a>(
         },
         LayoutRepr::Builtin(Builtin::Decimal) => {
             let dec = match RocDec::from_str(num_str) {
-                Some(d) => d,
-                None => internal_error!(
+                Ok(d) => d,
+                Err(_) => internal_error!(
                     "Invalid decimal for float literal = {}. This should be a type error!",
                     num_str
                 ),
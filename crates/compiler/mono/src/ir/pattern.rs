@@ -655,6 +655,10 @@ fn from_can_pattern_help<'a>(
 
                         Recursive {
                             sorted_tag_layouts: ref tags,
+                        }
+                        | TaggedPointer {
+                            sorted_tag_layouts: ref tags,
+                            ..
                         } => {
                             debug_assert!(tags.len() > 1);
 
@@ -854,6 +858,69 @@ fn from_can_pattern_help<'a>(
                                 layout,
                             }
                         }
+
+                        NullableBox {
+                            other_fields,
+                            nullable_id,
+                            nullable_name,
+                            other_name,
+                            ..
+                        } => {
+                            let nullable_ctor = Ctor {
+                                tag_id: TagId(nullable_id as _),
+                                name: CtorName::Tag(nullable_name.expect_tag_ref().clone()),
+                                arity: 0,
+                            };
+
+                            let other_ctor = Ctor {
+                                tag_id: TagId(!nullable_id as _),
+                                name: CtorName::Tag(other_name.expect_tag_ref().clone()),
+                                arity: other_fields.len(),
+                            };
+
+                            // the alternatives are ordered by tag id
+                            if nullable_id {
+                                ctors.push(other_ctor);
+                                ctors.push(nullable_ctor);
+                            } else {
+                                ctors.push(nullable_ctor);
+                                ctors.push(other_ctor);
+                            }
+
+                            let union = roc_exhaustive::Union {
+                                render_as: RenderAs::Tag,
+                                alternatives: ctors,
+                            };
+
+                            let mut mono_args = Vec::with_capacity_in(arguments.len(), env.arena);
+
+                            let it = if tag_name == nullable_name.expect_tag_ref() {
+                                [].iter()
+                            } else {
+                                argument_layouts.iter()
+                            };
+
+                            for ((_, loc_pat), layout) in arguments.iter().zip(it) {
+                                mono_args.push((
+                                    from_can_pattern_help(
+                                        env,
+                                        procs,
+                                        layout_cache,
+                                        &loc_pat.value,
+                                        assignments,
+                                    )?,
+                                    *layout,
+                                ));
+                            }
+
+                            Pattern::AppliedTag {
+                                tag_name: tag_name.clone(),
+                                tag_id: tag_id as _,
+                                arguments: mono_args,
+                                union,
+                                layout,
+                            }
+                        }
                     }
                 }
             };
@@ -1648,11 +1715,22 @@ fn store_tag_pattern<'a>(
             arg_layout = layout_cache.put_in_direct_no_semantic(LayoutRepr::Union(union_layout));
         }
 
-        let load = Expr::UnionAtIndex {
-            index: index as u64,
-            structure,
-            tag_id,
-            union_layout,
+        let load = if union_layout.is_nullable_box(&layout_cache.interner, arg_layout) {
+            // the union value is the box
+            Expr::Call(Call {
+                call_type: CallType::LowLevel {
+                    op: LowLevel::PtrCast,
+                    update_mode: env.next_update_mode_id(),
+                },
+                arguments: env.arena.alloc([structure]),
+            })
+        } else {
+            Expr::UnionAtIndex {
+                index: index as u64,
+                structure,
+                tag_id,
+                union_layout,
+            }
         };
 
         match argument {
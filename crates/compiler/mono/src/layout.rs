@@ -6,7 +6,7 @@ use bumpalo::Bump;
 use roc_builtins::bitcode::{FloatWidth, IntWidth};
 use roc_collections::all::{default_hasher, FnvMap, MutMap};
 use roc_collections::{SmallVec, VecSet};
-use roc_error_macros::{internal_error, todo_abilities};
+use roc_error_macros::{internal_error, todo_abilities, user_error};
 use roc_module::ident::{Lowercase, TagName};
 use roc_module::symbol::{Interns, Symbol};
 use roc_problem::can::RuntimeError;
@@ -17,8 +17,8 @@ use roc_types::subs::{
     UnsortedUnionLabels, Variable, VariableSubsSlice,
 };
 use roc_types::types::{
-    gather_fields_unsorted_iter, gather_tuple_elems_unsorted_iter, RecordField, RecordFieldsError,
-    TupleElemsError,
+    gather_fields_unsorted_iter, gather_tuple_elems_unsorted_iter, AliasKind, RecordField,
+    RecordFieldsError, TupleElemsError,
 };
 use std::cmp::Ordering;
 use std::collections::hash_map::Entry;
@@ -120,6 +120,9 @@ pub struct LayoutCache<'a> {
 
     pub interner: TLLayoutInterner<'a>,
 
+    /// The host function that runs before a value of each of these opaque types is freed
+    pub finalizers: MutMap<Symbol, String>,
+
     /// Statistics on the usage of the layout cache.
     #[cfg(debug_assertions)]
     stats: CacheStatistics,
@@ -140,6 +143,8 @@ impl<'a> LayoutCache<'a> {
 
             interner,
 
+            finalizers: MutMap::default(),
+
             #[cfg(debug_assertions)]
             stats: CacheStatistics::default(),
             #[cfg(debug_assertions)]
@@ -466,6 +471,7 @@ macro_rules! cached {
 pub type TagIdIntType = u16;
 pub const MAX_ENUM_SIZE: usize = std::mem::size_of::<TagIdIntType>() * 8;
 const GENERATE_NULLABLE: bool = true;
+const GENERATE_TAGGED_POINTER: bool = true;
 
 #[derive(Debug, Clone, Copy)]
 pub enum LayoutProblem {
@@ -771,6 +777,19 @@ pub enum UnionLayout<'a> {
         nullable_id: bool,
         other_fields: &'a [InLayout<'a>],
     },
+    /// A recursive tag union without an empty variant, where one variant holds just a small
+    /// integer (of at most 32 bits)
+    /// Optimization: Store that integer in the union value itself, rather than allocating for it
+    /// e.g. `Expr : [Add Expr Expr, Num I32, Mul Expr Expr]`
+    ///
+    /// The union value is the integer with [UnionLayout::IMMEDIATE_BIT] set, which no pointer
+    /// to the heap has. The other variants are stored like those of a `Recursive` union, with
+    /// the tag id in the pointer, and `tags[immediate_id]` is the layout of the integer. Only
+    /// used on 64-bit targets.
+    TaggedPointer {
+        immediate_id: u16,
+        tags: &'a [&'a [InLayout<'a>]],
+    },
 }
 
 impl<'a> UnionLayout<'a> {
@@ -823,6 +842,26 @@ impl<'a> UnionLayout<'a> {
                     .append(alloc.intersperse(tags_doc, ", "))
                     .append(alloc.text("]"))
             }
+            TaggedPointer { immediate_id, tags } => {
+                let tags_doc = tags.iter().enumerate().map(|(tag_id, fields)| {
+                    let fields_doc = alloc.intersperse(
+                        fields
+                            .iter()
+                            .map(|x| interner.to_doc(*x, alloc, seen_rec, Parens::InTypeParam)),
+                        " ",
+                    );
+
+                    if tag_id == immediate_id as usize {
+                        alloc.text("<immediate> ").append(fields_doc)
+                    } else {
+                        alloc.text("C ").append(fields_doc)
+                    }
+                });
+                alloc
+                    .text("[<rtp>")
+                    .append(alloc.intersperse(tags_doc, ", "))
+                    .append(alloc.text("]"))
+            }
             NonNullableUnwrapped(fields) => {
                 let fields_doc = alloc.text("C ").append(
                     alloc.intersperse(
@@ -898,7 +937,10 @@ impl<'a> UnionLayout<'a> {
                 // this cannot be recursive; return immediately
                 return field_layouts[index];
             }
-            UnionLayout::Recursive(tag_layouts) => {
+            UnionLayout::Recursive(tag_layouts)
+            | UnionLayout::TaggedPointer {
+                tags: tag_layouts, ..
+            } => {
                 let field_layouts = tag_layouts[tag_id as usize];
 
                 field_layouts[index]
@@ -940,7 +982,9 @@ impl<'a> UnionLayout<'a> {
 
     pub fn number_of_tags(&'a self) -> usize {
         match self {
-            UnionLayout::NonRecursive(tags) | UnionLayout::Recursive(tags) => tags.len(),
+            UnionLayout::NonRecursive(tags)
+            | UnionLayout::Recursive(tags)
+            | UnionLayout::TaggedPointer { tags, .. } => tags.len(),
 
             UnionLayout::NullableWrapped { other_tags, .. } => other_tags.len() + 1,
             UnionLayout::NonNullableUnwrapped(_) => 1,
@@ -951,7 +995,9 @@ impl<'a> UnionLayout<'a> {
     pub fn discriminant(&self) -> Discriminant {
         match self {
             UnionLayout::NonRecursive(tags) => Discriminant::from_number_of_tags(tags.len()),
-            UnionLayout::Recursive(tags) => Discriminant::from_number_of_tags(tags.len()),
+            UnionLayout::Recursive(tags) | UnionLayout::TaggedPointer { tags, .. } => {
+                Discriminant::from_number_of_tags(tags.len())
+            }
 
             UnionLayout::NullableWrapped { other_tags, .. } => {
                 Discriminant::from_number_of_tags(other_tags.len() + 1)
@@ -972,6 +1018,10 @@ impl<'a> UnionLayout<'a> {
     pub const POINTER_MASK_32BIT: usize = 0b0000_0111;
     pub const POINTER_MASK_64BIT: usize = 0b0000_0011;
 
+    /// Set in the value of the immediate variant of a [UnionLayout::TaggedPointer]. Pointers to
+    /// the heap on 64-bit targets are user space addresses, which leave it clear.
+    pub const IMMEDIATE_BIT: u64 = 1 << 63;
+
     pub fn tag_id_pointer_bits_and_mask(target: Target) -> (usize, usize) {
         match target.ptr_width() {
             PtrWidth::Bytes8 => (3, Self::POINTER_MASK_64BIT),
@@ -984,6 +1034,7 @@ impl<'a> UnionLayout<'a> {
         match self {
             UnionLayout::NonRecursive(_) => true,
             UnionLayout::Recursive(tags)
+            | UnionLayout::TaggedPointer { tags, .. }
             | UnionLayout::NullableWrapped {
                 other_tags: tags, ..
             } => !Self::stores_tag_id_in_pointer_bits(tags, target),
@@ -995,6 +1046,7 @@ impl<'a> UnionLayout<'a> {
         match self {
             UnionLayout::NonRecursive(_) => false,
             UnionLayout::Recursive(tags)
+            | UnionLayout::TaggedPointer { tags, .. }
             | UnionLayout::NullableWrapped {
                 other_tags: tags, ..
             } => Self::stores_tag_id_in_pointer_bits(tags, target),
@@ -1006,17 +1058,56 @@ impl<'a> UnionLayout<'a> {
         match self {
             UnionLayout::NonRecursive(_)
             | UnionLayout::NonNullableUnwrapped(_)
-            | UnionLayout::Recursive(_) => false,
+            | UnionLayout::Recursive(_)
+            | UnionLayout::TaggedPointer { .. } => false,
             UnionLayout::NullableWrapped { nullable_id, .. } => *nullable_id == tag_id,
             UnionLayout::NullableUnwrapped { nullable_id, .. } => *nullable_id == (tag_id != 0),
         }
     }
 
+    /// Whether the tag is stored in the union value itself, see [UnionLayout::TaggedPointer]
+    pub fn tag_is_immediate(&self, tag_id: TagIdIntType) -> bool {
+        matches!(self, UnionLayout::TaggedPointer { immediate_id, .. } if *immediate_id == tag_id)
+    }
+
+    /// The tag that is not allocated on the heap, if any: the null tag of a nullable union, or the
+    /// immediate tag of a [UnionLayout::TaggedPointer]
+    pub fn unallocated_tag_id(&self) -> Option<TagIdIntType> {
+        match self {
+            UnionLayout::NonRecursive(_)
+            | UnionLayout::NonNullableUnwrapped(_)
+            | UnionLayout::Recursive(_) => None,
+            UnionLayout::NullableWrapped { nullable_id, .. } => Some(*nullable_id),
+            UnionLayout::NullableUnwrapped { nullable_id, .. } => {
+                Some(*nullable_id as TagIdIntType)
+            }
+            UnionLayout::TaggedPointer { immediate_id, .. } => Some(*immediate_id),
+        }
+    }
+
+    /// Whether `field_layout`, the layout that a tag gives to its only field, is a box that is
+    /// stored as the union value itself (see [WrappedVariant::NullableBox]), rather than as a
+    /// field of the union.
+    pub fn is_nullable_box<I>(&self, interner: &I, field_layout: InLayout<'a>) -> bool
+    where
+        I: LayoutInterner<'a>,
+    {
+        match self {
+            UnionLayout::NullableUnwrapped { other_fields, .. } => matches!(
+                interner.get_repr(field_layout),
+                LayoutRepr::Union(UnionLayout::NonNullableUnwrapped(boxed_fields))
+                    if boxed_fields == *other_fields
+            ),
+            _ => false,
+        }
+    }
+
     pub fn is_nullable(&self) -> bool {
         match self {
             UnionLayout::NonRecursive(_)
             | UnionLayout::Recursive(_)
-            | UnionLayout::NonNullableUnwrapped { .. } => false,
+            | UnionLayout::NonNullableUnwrapped { .. }
+            | UnionLayout::TaggedPointer { .. } => false,
             UnionLayout::NullableWrapped { .. } | UnionLayout::NullableUnwrapped { .. } => true,
         }
     }
@@ -1037,7 +1128,9 @@ impl<'a> UnionLayout<'a> {
     {
         let allocation = match self {
             UnionLayout::NonRecursive(tags) => Self::tags_alignment_bytes(interner, tags),
-            UnionLayout::Recursive(tags) => Self::tags_alignment_bytes(interner, tags),
+            UnionLayout::Recursive(tags) | UnionLayout::TaggedPointer { tags, .. } => {
+                Self::tags_alignment_bytes(interner, tags)
+            }
             UnionLayout::NonNullableUnwrapped(field_layouts) => {
                 LayoutRepr::struct_(field_layouts).alignment_bytes(interner)
             }
@@ -1103,7 +1196,9 @@ impl<'a> UnionLayout<'a> {
     {
         match self {
             Self::NonRecursive(tags) => Layout::stack_size_and_alignment_slices(interner, tags),
-            Self::Recursive(tags) => Layout::stack_size_and_alignment_slices(interner, tags),
+            Self::Recursive(tags) | Self::TaggedPointer { tags, .. } => {
+                Layout::stack_size_and_alignment_slices(interner, tags)
+            }
             Self::NonNullableUnwrapped(fields) => {
                 Layout::stack_size_and_alignment_slices(interner, &[fields])
             }
@@ -1123,6 +1218,7 @@ impl<'a> UnionLayout<'a> {
         match self {
             UnionLayout::NonRecursive(tags)
             | UnionLayout::Recursive(tags)
+            | UnionLayout::TaggedPointer { tags, .. }
             | UnionLayout::NullableWrapped {
                 other_tags: tags, ..
             } => Some(Self::tag_id_offset_help(interner, tags)),
@@ -1152,7 +1248,8 @@ impl<'a> UnionLayout<'a> {
             UnionLayout::Recursive(_)
             | UnionLayout::NonNullableUnwrapped(_)
             | UnionLayout::NullableWrapped { .. }
-            | UnionLayout::NullableUnwrapped { .. } => interner.target().ptr_width() as u32,
+            | UnionLayout::NullableUnwrapped { .. }
+            | UnionLayout::TaggedPointer { .. } => interner.target().ptr_width() as u32,
         }
     }
 
@@ -1164,7 +1261,8 @@ impl<'a> UnionLayout<'a> {
             Recursive(_)
             | NonNullableUnwrapped(_)
             | NullableWrapped { .. }
-            | NullableUnwrapped { .. } => true,
+            | NullableUnwrapped { .. }
+            | TaggedPointer { .. } => true,
         }
     }
 }
@@ -1646,7 +1744,7 @@ impl<'a> LambdaSet<'a> {
                             union_layout: union,
                         }
                     }
-                    UnionLayout::Recursive(_) => {
+                    UnionLayout::Recursive(_) | UnionLayout::TaggedPointer { .. } => {
                         let (index, (name, fields)) = self
                             .set
                             .iter()
@@ -2215,6 +2313,11 @@ pub enum Builtin<'a> {
     Bool,
     Decimal,
     Str,
+    /// A pointer to the elements, the length, and the capacity: three words on every target.
+    ///
+    /// The header is shared with the zig builtins, `roc_std` and every host, so it is the same
+    /// for all lists. In particular the capacity can not be packed together with the length: for
+    /// a seamless slice, that word holds (a shifted) pointer to the original allocation instead.
     List(InLayout<'a>),
 }
 
@@ -2487,7 +2590,7 @@ impl<'a> Layout<'a> {
             }
             Structure(flat_type) => layout_from_flat_type(env, flat_type),
 
-            Alias(symbol, _args, actual_var, _) => {
+            Alias(symbol, _args, actual_var, kind) => {
                 if let Some(int_width) = IntWidth::try_from_symbol(symbol) {
                     return cacheable(Ok(Layout::int_width(int_width)));
                 }
@@ -2514,6 +2617,14 @@ impl<'a> Layout<'a> {
                         cacheable(Ok(Layout::default_float()))
                     }
 
+                    Symbol::WEAK_REF_WEAK_REF => weak_ref_layout(env, actual_var),
+
+                    _ if kind == AliasKind::Opaque
+                        && env.cache.finalizers.contains_key(&symbol) =>
+                    {
+                        finalized_layout(env, symbol, actual_var)
+                    }
+
                     _ => Self::from_var(env, actual_var),
                 }
             }
@@ -2659,7 +2770,8 @@ impl<'a> LayoutRepr<'a> {
                     Recursive(_)
                     | NullableWrapped { .. }
                     | NullableUnwrapped { .. }
-                    | NonNullableUnwrapped(_) => {
+                    | NonNullableUnwrapped(_)
+                    | TaggedPointer { .. } => {
                         // a recursive union will always contain a pointer, and is thus not safe to memcpy
                         false
                     }
@@ -2807,7 +2919,8 @@ impl<'a> LayoutRepr<'a> {
                     Recursive(_)
                     | NullableWrapped { .. }
                     | NullableUnwrapped { .. }
-                    | NonNullableUnwrapped(_) => interner.target().ptr_width() as u32,
+                    | NonNullableUnwrapped(_)
+                    | TaggedPointer { .. } => interner.target().ptr_width() as u32,
                 }
             }
             LambdaSet(lambda_set) => interner
@@ -2876,6 +2989,7 @@ impl<'a> LayoutRepr<'a> {
                 UnionLayout::NonRecursive(_) => false,
                 UnionLayout::Recursive(_) => false,
                 UnionLayout::NonNullableUnwrapped(_) => false,
+                UnionLayout::TaggedPointer { .. } => false,
                 UnionLayout::NullableWrapped { .. } => true,
                 UnionLayout::NullableUnwrapped { .. } => true,
             },
@@ -2909,7 +3023,8 @@ impl<'a> LayoutRepr<'a> {
                     Recursive(_)
                     | NullableWrapped { .. }
                     | NullableUnwrapped { .. }
-                    | NonNullableUnwrapped(_) => true,
+                    | NonNullableUnwrapped(_)
+                    | TaggedPointer { .. } => true,
                 }
             }
             LambdaSet(lambda_set) => interner
@@ -2956,7 +3071,9 @@ impl<'a> LayoutRepr<'a> {
                         .map(|interned| interner.get_repr(*interned)),
                 ),
                 Union(tag_union) => match tag_union {
-                    UnionLayout::NonRecursive(tags) | UnionLayout::Recursive(tags) => {
+                    UnionLayout::NonRecursive(tags)
+                    | UnionLayout::Recursive(tags)
+                    | UnionLayout::TaggedPointer { tags, .. } => {
                         for tag in tags {
                             stack.extend(tag.iter().map(|interned| interner.get_repr(*interned)));
                         }
@@ -3054,7 +3171,8 @@ impl<'a> Layout<'a> {
                 UnionLayout::NullableUnwrapped { .. }
                     | UnionLayout::Recursive(_)
                     | UnionLayout::NullableWrapped { .. }
-                    | UnionLayout::NonNullableUnwrapped { .. },
+                    | UnionLayout::NonNullableUnwrapped { .. }
+                    | UnionLayout::TaggedPointer { .. },
             )
         )
     }
@@ -3640,6 +3758,28 @@ pub enum WrappedVariant<'a> {
         other_name: TagOrClosure,
         other_fields: &'a [InLayout<'a>],
     },
+    /// A non-recursive tag union with two variants, where one is empty and the other holds just a
+    /// box, e.g. `[Some (Box a), None]`.
+    /// Optimization: the value is the box pointer itself, and null represents the empty variant.
+    ///
+    /// That has the same representation as a `UnionLayout::NullableUnwrapped` of the fields of the
+    /// box, which is used as its layout. The box is not a field of that layout, so constructing
+    /// the non-empty variant, or getting its box back out, is a pointer cast.
+    NullableBox {
+        nullable_id: bool,
+        nullable_name: TagOrClosure,
+        other_name: TagOrClosure,
+        /// The layout of the box, as the only field of the non-empty variant
+        other_fields: &'a [InLayout<'a>],
+        /// The fields of the boxed value
+        boxed_fields: &'a [InLayout<'a>],
+    },
+    /// See [UnionLayout::TaggedPointer]. The immediate tag is one of the sorted tags, like the
+    /// other tags.
+    TaggedPointer {
+        immediate_id: TagIdIntType,
+        sorted_tag_layouts: Vec<'a, (TagOrClosure, &'a [InLayout<'a>])>,
+    },
 }
 
 impl<'a> WrappedVariant<'a> {
@@ -3647,7 +3787,11 @@ impl<'a> WrappedVariant<'a> {
         use WrappedVariant::*;
 
         match self {
-            Recursive { sorted_tag_layouts } | NonRecursive { sorted_tag_layouts } => {
+            Recursive { sorted_tag_layouts }
+            | NonRecursive { sorted_tag_layouts }
+            | TaggedPointer {
+                sorted_tag_layouts, ..
+            } => {
                 let (tag_id, (_, argument_layouts)) = sorted_tag_layouts
                     .iter()
                     .enumerate()
@@ -3686,6 +3830,13 @@ impl<'a> WrappedVariant<'a> {
                 nullable_name,
                 other_name,
                 other_fields,
+            }
+            | NullableBox {
+                nullable_id,
+                nullable_name,
+                other_name,
+                other_fields,
+                ..
             } => {
                 if tag_name == nullable_name.expect_tag_ref() {
                     (*nullable_id as TagIdIntType, &[] as &[_])
@@ -3703,9 +3854,11 @@ impl<'a> WrappedVariant<'a> {
         use WrappedVariant::*;
 
         match self {
-            Recursive { sorted_tag_layouts } | NonRecursive { sorted_tag_layouts } => {
-                sorted_tag_layouts.len()
-            }
+            Recursive { sorted_tag_layouts }
+            | NonRecursive { sorted_tag_layouts }
+            | TaggedPointer {
+                sorted_tag_layouts, ..
+            } => sorted_tag_layouts.len(),
             NullableWrapped {
                 sorted_tag_layouts, ..
             } => {
@@ -3713,7 +3866,7 @@ impl<'a> WrappedVariant<'a> {
 
                 sorted_tag_layouts.len() + 1
             }
-            NullableUnwrapped { .. } => 2,
+            NullableUnwrapped { .. } | NullableBox { .. } => 2,
             NonNullableUnwrapped { .. } => 1,
         }
     }
@@ -3900,6 +4053,10 @@ where
 
             let mut inhabited_tag_ids = BitVec::<usize>::repeat(true, num_tags);
 
+            let has_box_payload = tags_list
+                .iter()
+                .any(|(_, arguments)| is_box_payload(env.subs, arguments));
+
             for &&(tag_name, arguments) in tags_list.iter() {
                 let mut arg_layouts = Vec::with_capacity_in(arguments.len() + 1, env.arena);
 
@@ -3979,9 +4136,11 @@ where
                     Cacheable(UnionVariant::ByteUnion(tag_names), cache_criteria)
                 }
                 _ => {
-                    let variant = WrappedVariant::NonRecursive {
-                        sorted_tag_layouts: answer,
-                    };
+                    let variant =
+                        nullable_box_variant(env, &answer, has_box_payload, &cache_criteria)
+                            .unwrap_or(WrappedVariant::NonRecursive {
+                                sorted_tag_layouts: answer,
+                            });
 
                     Cacheable(UnionVariant::Wrapped(variant), cache_criteria)
                 }
@@ -4031,6 +4190,96 @@ where
     }
 }
 
+/// The tag of a recursive union that is stored in the union value itself, see
+/// [UnionLayout::TaggedPointer]: the first one that holds just an integer of at most 32 bits.
+/// Unions with an empty tag are nullable instead. The tag id of the other tags must fit in the
+/// pointer, so that it can be read without following the pointer.
+fn find_immediate_tag<'a, 'b>(
+    env: &Env<'a, '_>,
+    mut tag_layouts: impl ExactSizeIterator<Item = &'b [InLayout<'a>]>,
+) -> Option<TagIdIntType>
+where
+    'a: 'b,
+{
+    let target = env.cache.target;
+
+    if !GENERATE_TAGGED_POINTER
+        || target.ptr_width() != PtrWidth::Bytes8
+        || tag_layouts.len() >= target.ptr_width() as usize
+    {
+        return None;
+    }
+
+    tag_layouts
+        .position(|fields| match fields {
+            [field] => matches!(
+                env.cache.get_repr(*field),
+                LayoutRepr::Builtin(Builtin::Int(int_width)) if int_width.stack_size() <= 4
+            ),
+            _ => false,
+        })
+        .map(|tag_id| tag_id as TagIdIntType)
+}
+
+/// Whether the only argument of a tag is a `Box`
+fn is_box_payload(subs: &Subs, variables: &[Variable]) -> bool {
+    let &[mut var] = variables else {
+        return false;
+    };
+
+    loop {
+        match subs.get_content_without_compacting(var) {
+            Content::Alias(_, _, actual, _) => var = *actual,
+            Content::Structure(FlatType::Apply(symbol, _)) => {
+                return *symbol == Symbol::BOX_BOX_TYPE;
+            }
+            _ => return false,
+        }
+    }
+}
+
+/// A `[Some (Box a), None]`-shaped union can store the box pointer directly, see
+/// [WrappedVariant::NullableBox].
+fn nullable_box_variant<'a>(
+    env: &Env<'a, '_>,
+    sorted_tag_layouts: &[(TagOrClosure, &'a [InLayout<'a>])],
+    has_box_payload: bool,
+    criteria: &CacheCriteria,
+) -> Option<WrappedVariant<'a>> {
+    // a naked recursion pointer in the box would have to point past this union
+    if !GENERATE_NULLABLE || !has_box_payload || !criteria.is_cacheable() {
+        return None;
+    }
+
+    let (nullable_id, nullable_name, other_name, other_fields) = match sorted_tag_layouts {
+        [(first_name, []), (second_name, second_fields @ [_])] => {
+            (false, first_name, second_name, *second_fields)
+        }
+        [(first_name, first_fields @ [_]), (second_name, [])] => {
+            (true, second_name, first_name, *first_fields)
+        }
+        _ => return None,
+    };
+
+    // lambda sets are not constructed like tags
+    if !matches!(other_name, TagOrClosure::Tag(_)) {
+        return None;
+    }
+
+    match env.cache.get_repr(other_fields[0]) {
+        LayoutRepr::Union(UnionLayout::NonNullableUnwrapped(boxed_fields)) => {
+            Some(WrappedVariant::NullableBox {
+                nullable_id,
+                nullable_name: nullable_name.clone(),
+                other_name: other_name.clone(),
+                other_fields,
+                boxed_fields,
+            })
+        }
+        _ => None,
+    }
+}
+
 fn union_sorted_tags_help<'a, L>(
     env: &mut Env<'a, '_>,
     mut tags_vec: std::vec::Vec<(L, std::vec::Vec<Variable>)>,
@@ -4116,6 +4365,12 @@ where
                 nullable = find_nullable_tag(tags_vec.iter().map(|(a, b)| (a, b.as_slice())));
             }
 
+            // ... unless they store a box, see WrappedVariant::NullableBox
+            let has_box_payload = !is_recursive
+                && tags_vec
+                    .iter()
+                    .any(|(_, arguments)| is_box_payload(env.subs, arguments));
+
             for (index, (tag_name, arguments)) in tags_vec.into_iter().enumerate() {
                 // reserve space for the tag discriminant
                 if matches!(nullable, Some((i, _)) if i  as usize == index) {
@@ -4237,13 +4492,21 @@ where
                         }
                     } else if is_recursive {
                         debug_assert!(answer.len() > 1);
-                        WrappedVariant::Recursive {
-                            sorted_tag_layouts: answer,
+
+                        match find_immediate_tag(env, answer.iter().map(|(_, fields)| *fields)) {
+                            Some(immediate_id) => WrappedVariant::TaggedPointer {
+                                immediate_id,
+                                sorted_tag_layouts: answer,
+                            },
+                            None => WrappedVariant::Recursive {
+                                sorted_tag_layouts: answer,
+                            },
                         }
                     } else {
-                        WrappedVariant::NonRecursive {
-                            sorted_tag_layouts: answer,
-                        }
+                        nullable_box_variant(env, &answer, has_box_payload, &cache_criteria)
+                            .unwrap_or(WrappedVariant::NonRecursive {
+                                sorted_tag_layouts: answer,
+                            })
                     };
 
                     if let Some(rec_var) = opt_rec_var {
@@ -4395,10 +4658,27 @@ where
                     env.cache.put_in(layout)
                 }
 
+                NullableBox {
+                    nullable_id,
+                    boxed_fields,
+                    ..
+                } => {
+                    let layout = Layout {
+                        repr: LayoutRepr::Union(UnionLayout::NullableUnwrapped {
+                            nullable_id,
+                            other_fields: boxed_fields,
+                        })
+                        .direct(),
+                        semantic: compute_semantic(),
+                    };
+                    env.cache.put_in(layout)
+                }
+
                 Recursive { .. }
                 | NullableWrapped { .. }
                 | NullableUnwrapped { .. }
-                | NonNullableUnwrapped { .. } => {
+                | NonNullableUnwrapped { .. }
+                | TaggedPointer { .. } => {
                     internal_error!("non-recursive tag union has recursive layout")
                 }
             }
@@ -4492,6 +4772,11 @@ where
     } else if tag_layouts.len() == 1 {
         // drop the tag id
         UnionLayout::NonNullableUnwrapped(tag_layouts.pop().unwrap())
+    } else if let Some(immediate_id) = find_immediate_tag(env, tag_layouts.iter().copied()) {
+        UnionLayout::TaggedPointer {
+            immediate_id,
+            tags: tag_layouts.into_bump_slice(),
+        }
     } else {
         UnionLayout::Recursive(tag_layouts.into_bump_slice())
     };
@@ -4615,6 +4900,55 @@ fn layout_from_num_content<'a>(content: &Content) -> Cacheable<LayoutResult<'a>>
     cacheable(result)
 }
 
+/// The layout of an opaque type with a finalizer. It has the runtime representation of the `Box`
+/// that the opaque type wraps, but a semantic of its own, so that its refcounting can run the
+/// finalizer when the box is freed.
+fn finalized_layout<'a>(
+    env: &mut Env<'a, '_>,
+    opaque: Symbol,
+    actual_var: Variable,
+) -> Cacheable<LayoutResult<'a>> {
+    let mut criteria = CACHEABLE;
+
+    let actual_layout = cached!(Layout::from_var(env, actual_var), criteria, env.subs);
+    let finalizer = env.arena.alloc_str(&env.cache.finalizers[&opaque]);
+
+    let repr = match env.cache.get_repr(actual_layout) {
+        repr @ LayoutRepr::Union(UnionLayout::NonNullableUnwrapped(_)) => repr,
+        _ => user_error!(
+            "`{finalizer}` is registered as the finalizer of an opaque type that does not wrap a `Box`, so there is no allocation for it to finalize."
+        ),
+    };
+
+    let finalized_layout = env.cache.put_in(Layout {
+        repr: repr.direct(),
+        semantic: SemanticRepr::finalized(finalizer),
+    });
+
+    Cacheable(Ok(finalized_layout), criteria)
+}
+
+/// The layout of a `WeakRef`. It has the runtime representation of the `Box` it points to, but
+/// its refcounting changes the count of weak references in the allocation of the box.
+fn weak_ref_layout<'a>(env: &mut Env<'a, '_>, actual_var: Variable) -> Cacheable<LayoutResult<'a>> {
+    let mut criteria = CACHEABLE;
+
+    let box_layout = cached!(Layout::from_var(env, actual_var), criteria, env.subs);
+    let repr = env.cache.get_repr(box_layout);
+
+    debug_assert!(matches!(
+        repr,
+        LayoutRepr::Union(UnionLayout::NonNullableUnwrapped(_))
+    ));
+
+    let weak_ref_layout = env.cache.put_in(Layout {
+        repr: repr.direct(),
+        semantic: SemanticRepr::WEAK_REF,
+    });
+
+    Cacheable(Ok(weak_ref_layout), criteria)
+}
+
 pub(crate) fn list_layout_from_elem<'a>(
     env: &mut Env<'a, '_>,
     element_var: Variable,
@@ -199,6 +199,18 @@ pub trait LayoutInterner<'a>: Sized {
         self.get(key).semantic
     }
 
+    /// The host function that has to run before the allocation of `key` is freed, if `key` is
+    /// the layout of an opaque type with a finalizer
+    fn finalizer(&self, key: InLayout<'a>) -> Option<&'a str> {
+        self.get_semantic(key).finalizer()
+    }
+
+    /// Whether `key` is the layout of a `WeakRef`, whose refcounting counts the weak references
+    /// to a box rather than the box itself
+    fn is_weak_ref(&self, key: InLayout<'a>) -> bool {
+        self.get_semantic(key).is_weak_ref()
+    }
+
     fn eq_repr(&self, a: InLayout<'a>, b: InLayout<'a>) -> bool {
         self.get_repr(a) == self.get_repr(b)
     }
@@ -1219,6 +1231,10 @@ mod reify {
                 nullable_id,
                 other_fields: reify_layout_slice(arena, interner, slot, other_fields),
             },
+            UnionLayout::TaggedPointer { immediate_id, tags } => UnionLayout::TaggedPointer {
+                immediate_id,
+                tags: reify_layout_slice_slice(arena, interner, slot, tags),
+            },
         }
     }
 
@@ -1367,6 +1383,21 @@ mod equiv {
                             }
                             equiv_fields!(fields1, fields2)
                         }
+                        (
+                            TaggedPointer {
+                                immediate_id: immediate_id1,
+                                tags: tags1,
+                            },
+                            TaggedPointer {
+                                immediate_id: immediate_id2,
+                                tags: tags2,
+                            },
+                        ) => {
+                            if immediate_id1 != immediate_id2 {
+                                return false;
+                            }
+                            equiv_unions!(tags1, tags2)
+                        }
                         _ => return false,
                     }
                 }
@@ -1528,6 +1559,11 @@ pub mod dbg_deep {
                     .field("nullable_id", &nullable_id)
                     .field("other_tags", &DbgFields(self.0, other_fields))
                     .finish(),
+                UnionLayout::TaggedPointer { immediate_id, tags } => f
+                    .debug_struct("TaggedPointer")
+                    .field("immediate_id", &immediate_id)
+                    .field("tags", &DbgTags(self.0, tags))
+                    .finish(),
             }
         }
     }
@@ -1707,6 +1743,11 @@ pub mod dbg_stable {
                     .field("nullable_id", &nullable_id)
                     .field("other_tags", &DbgFields(self.0, other_fields))
                     .finish(),
+                UnionLayout::TaggedPointer { immediate_id, tags } => f
+                    .debug_struct("TaggedPointer")
+                    .field("immediate_id", &immediate_id)
+                    .field("tags", &DbgTags(self.0, tags))
+                    .finish(),
             }
         }
     }
@@ -28,6 +28,8 @@ enum Inner<'a> {
     Tuple(SemaTuple),
     TagUnion(SemaTagUnion<'a>),
     Lambdas(SemaLambdas<'a>),
+    Finalized(SemaFinalized<'a>),
+    WeakRef,
 }
 
 impl<'a> Inner<'a> {
@@ -47,6 +49,9 @@ impl<'a> SemanticRepr<'a> {
     pub(super) const NONE: Self = Self(Inner::None);
     pub(super) const EMPTY_RECORD: Self = Self::record(&[]);
 
+    /// A `WeakRef`, which points to a box without counting as a reference to it
+    pub(super) const WEAK_REF: Self = Self(Inner::WeakRef);
+
     pub(super) const fn record(fields: &'a [&'a str]) -> Self {
         Self(Inner::Record(SemaRecord { fields }))
     }
@@ -62,6 +67,24 @@ impl<'a> SemanticRepr<'a> {
     pub(super) fn lambdas(lambdas: &'a [Symbol]) -> Self {
         Self(Inner::Lambdas(SemaLambdas { lambdas }))
     }
+
+    /// A boxed opaque type whose allocation is given to the host function `finalizer` right
+    /// before it is freed
+    pub(super) fn finalized(finalizer: &'a str) -> Self {
+        Self(Inner::Finalized(SemaFinalized { finalizer }))
+    }
+
+    /// The host function that has to run before the allocation of this layout is freed
+    pub fn finalizer(&self) -> Option<&'a str> {
+        match self.0 {
+            Inner::Finalized(SemaFinalized { finalizer }) => Some(finalizer),
+            _ => None,
+        }
+    }
+
+    pub fn is_weak_ref(&self) -> bool {
+        matches!(self.0, Inner::WeakRef)
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -83,3 +106,8 @@ struct SemaTagUnion<'a> {
 struct SemaLambdas<'a> {
     lambdas: &'a [Symbol],
 }
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct SemaFinalized<'a> {
+    finalizer: &'a str,
+}
@@ -9,14 +9,22 @@
 // Not a useful lint for us
 #![allow(clippy::too_many_arguments)]
 
+pub mod abi;
 pub mod borrow;
+pub mod bounds_check;
 pub mod code_gen_help;
+pub mod constant_folding;
+pub mod dead_procs;
 pub mod drop_specialization;
 pub mod inc_dec;
+pub mod inc_dec_pairs;
 pub mod ir;
 pub mod layout;
 pub mod low_level;
+pub mod pool;
 pub mod reset_reuse;
+pub mod single_owner;
+pub mod stack_frame;
 pub mod tail_recursion;
 
 pub mod debug;
@@ -0,0 +1,149 @@
+//! Size classes for allocating heap values of a fixed size from pools, with `--pool-alloc`.
+//!
+//! Programs that build trees and other recursive data allocate many tags of the same few unions,
+//! and every tag of a union takes up the same space on the heap. Giving each of those sizes a pool
+//! of its own saves going through `roc_alloc` and `roc_dealloc` for every node. The builtins keep
+//! the pools (see `allocateWithRefcountPooled` in utils.zig); this picks the sizes they are for.
+//!
+//! The size classes are the allocation sizes of the heap-allocated tags the program constructs,
+//! favouring the sizes that are constructed in the most places. An allocation that is smaller
+//! than a size class is taken from the smallest pool it fits in, and anything else still goes
+//! to `roc_alloc`.
+
+use roc_collections::MutMap;
+use roc_target::Target;
+
+use crate::ir::{Expr, Proc, Stmt};
+use crate::layout::{round_up_to_alignment, LayoutInterner, UnionLayout};
+
+/// The number of pools. Matches `POOL_SIZE_CLASSES` in utils.zig.
+pub const MAX_SIZE_CLASSES: usize = 8;
+
+/// Every slot of a pool is aligned to this many bytes
+pub const POOL_ALIGNMENT: u32 = 16;
+
+/// Larger allocations are rare and big enough that pooling them saves little
+pub const MAX_POOLED_SIZE: u32 = 256;
+
+/// The allocation sizes of the pools, from small to large
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SizeClasses {
+    sizes: Vec<u32>,
+}
+
+impl SizeClasses {
+    pub fn from_procedures<'a, 'p, I>(
+        interner: &I,
+        procedures: impl IntoIterator<Item = &'p Proc<'a>>,
+    ) -> Self
+    where
+        'a: 'p,
+        I: LayoutInterner<'a>,
+    {
+        let mut constructions: MutMap<u32, usize> = MutMap::default();
+
+        for proc in procedures {
+            count_constructions(interner, &proc.body, &mut constructions);
+        }
+
+        let mut by_constructions: Vec<_> = constructions.into_iter().collect();
+        by_constructions
+            .sort_by(|(size1, count1), (size2, count2)| count2.cmp(count1).then(size1.cmp(size2)));
+        by_constructions.truncate(MAX_SIZE_CLASSES);
+
+        let mut sizes: Vec<_> = by_constructions.into_iter().map(|(size, _)| size).collect();
+        sizes.sort_unstable();
+
+        Self { sizes }
+    }
+
+    pub fn sizes(&self) -> &[u32] {
+        &self.sizes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sizes.is_empty()
+    }
+
+    /// The size class to take an allocation of `allocation_size` bytes from, if it fits one
+    pub fn class_for(&self, allocation_size: u32, alignment: u32) -> Option<u32> {
+        if alignment > POOL_ALIGNMENT {
+            return None;
+        }
+
+        self.sizes
+            .iter()
+            .position(|size| *size >= allocation_size)
+            .map(|index| index as u32)
+    }
+}
+
+/// The bytes `allocateWithRefcount` asks for to store `data_bytes` of data that is not a list:
+/// a refcount that is aligned like the data, followed by the data, rounded up to a whole slot.
+pub fn allocation_size(target: Target, data_bytes: u32, alignment: u32) -> u32 {
+    let refcount_bytes = alignment.max(target.ptr_width() as u32);
+
+    round_up_to_alignment(refcount_bytes + data_bytes, POOL_ALIGNMENT)
+}
+
+fn heap_allocation_size<'a, I>(interner: &I, union_layout: &UnionLayout<'a>) -> Option<u32>
+where
+    I: LayoutInterner<'a>,
+{
+    if let UnionLayout::NonRecursive(_) = union_layout {
+        return None;
+    }
+
+    let (data_bytes, _) = union_layout.data_size_and_alignment(interner);
+    let alignment = union_layout.allocation_alignment_bytes(interner);
+    let size = allocation_size(interner.target(), data_bytes, alignment);
+
+    (alignment <= POOL_ALIGNMENT && size <= MAX_POOLED_SIZE).then_some(size)
+}
+
+fn count_constructions<'a, I>(interner: &I, stmt: &Stmt<'a>, constructions: &mut MutMap<u32, usize>)
+where
+    I: LayoutInterner<'a>,
+{
+    let mut stack = vec![stmt];
+
+    while let Some(stmt) = stack.pop() {
+        match stmt {
+            Stmt::Let(_, expr, _, continuation) => {
+                if let Expr::Tag {
+                    tag_layout, tag_id, ..
+                } = expr
+                {
+                    // the null tag and the immediate tag of a tagged pointer are not allocated
+                    let size = match tag_layout.unallocated_tag_id() {
+                        Some(unallocated) if unallocated == *tag_id => None,
+                        _ => heap_allocation_size(interner, tag_layout),
+                    };
+
+                    if let Some(size) = size {
+                        *constructions.entry(size).or_default() += 1;
+                    }
+                }
+
+                stack.push(*continuation);
+            }
+            Stmt::Switch {
+                branches,
+                default_branch,
+                ..
+            } => {
+                stack.extend(branches.iter().map(|(_, _, branch)| branch));
+                stack.push(default_branch.1);
+            }
+            Stmt::Refcounting(_, continuation) => stack.push(*continuation),
+            Stmt::Expect { remainder, .. } | Stmt::Dbg { remainder, .. } => stack.push(*remainder),
+            Stmt::Join {
+                body, remainder, ..
+            } => {
+                stack.push(*body);
+                stack.push(*remainder);
+            }
+            Stmt::Ret(_) | Stmt::Jump(..) | Stmt::Crash(..) => {}
+        }
+    }
+}
@@ -1327,6 +1327,10 @@ fn symbol_layout_reusability<'a>(
     layout: &InLayout<'a>,
 ) -> Reuse<'a> {
     match layout_interner.get_repr(*layout) {
+        // Reusing the allocation would skip the finalizer that runs when it is freed.
+        LayoutRepr::Union(_) if layout_interner.finalizer(*layout).is_some() => Reuse::Nonreusable,
+        // A weak reference does not own the allocation it points to.
+        LayoutRepr::Union(_) if layout_interner.is_weak_ref(*layout) => Reuse::Nonreusable,
         LayoutRepr::Union(union_layout) => {
             can_reuse_union_layout_tag(union_layout, environment.get_symbol_tag(symbol))
         }
@@ -1347,6 +1351,12 @@ fn can_reuse_union_layout_tag(union_layout: UnionLayout, tag_id_option: Option<T
             // Non nullable union layouts can always be reused.
             Reuse::Reusable(union_layout)
         }
+        // The immediate tag is not allocated. Only a symbol that is known to be another tag can
+        // be reused, because resetting the immediate tag would read a refcount that is not there.
+        UnionLayout::TaggedPointer { .. } => match tag_id_option {
+            Some(tag_id) if !union_layout.tag_is_immediate(tag_id) => Reuse::Reusable(union_layout),
+            _ => Reuse::Nonreusable,
+        },
         // Nullable union layouts
         UnionLayout::NullableWrapped { .. } | UnionLayout::NullableUnwrapped { .. } => {
             match tag_id_option {
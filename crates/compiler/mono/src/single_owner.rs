@@ -0,0 +1,431 @@
+use bumpalo::collections::vec::Vec;
+use bumpalo::collections::CollectIn;
+use bumpalo::Bump;
+
+use roc_collections::{MutMap, MutSet};
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+
+use crate::inc_dec::{lowlevel_borrow_signature, Ownership};
+use crate::ir::{
+    Call, CallType, Expr, ListLiteralElement, Literal, ModifyRc, Proc, ProcLayout, Stmt,
+};
+use crate::layout::{InLayout, LayoutInterner, STLayoutInterner, UnionLayout};
+
+/**
+Replace the decrement of a heap-allocated value that is never shared by a plain `free`.
+
+A value is a "single owner" when it is allocated in this procedure (a heap-allocated tag of a
+recursive union, without reuse), and it is never incremented, returned, stored, or passed to anything that could
+hold on to it: its only uses are borrowing reads like `GetTagId`, `UnionAtIndex` and lowlevels
+that borrow their argument. Its reference count is then still 1 when it is dropped, so the
+`dec` does not need to look at the reference count, and a `RefCountIsUnique` check on it is
+always true.
+
+Because `free` does not decrement the children of a value, only tags whose fields contain
+nothing refcounted qualify.
+*/
+pub fn free_single_owner_values<'a, 'i>(
+    arena: &'a Bump,
+    layout_interner: &'i STLayoutInterner<'a>,
+    procs: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) {
+    for proc in procs.values_mut() {
+        let mut env = SingleOwnerEnvironment::default();
+
+        for (layout, symbol) in proc.args.iter().copied() {
+            env.symbol_layouts.insert(symbol, layout);
+        }
+
+        env.find_candidates(layout_interner, &proc.body);
+        env.remove_shared(&proc.body);
+
+        if !env.single_owners.is_empty() {
+            let body = arena.alloc(proc.body.clone());
+            proc.body = free_single_owners_stmt(arena, &env.single_owners, body).clone();
+        }
+    }
+}
+
+#[derive(Default)]
+struct SingleOwnerEnvironment<'a> {
+    symbol_layouts: MutMap<Symbol, InLayout<'a>>,
+    single_owners: MutSet<Symbol>,
+}
+
+impl<'a> SingleOwnerEnvironment<'a> {
+    /// Collect the values that are freshly allocated, and whose fields are not refcounted.
+    fn find_candidates(&mut self, layout_interner: &STLayoutInterner<'a>, stmt: &Stmt<'a>) {
+        match stmt {
+            Stmt::Let(symbol, expr, layout, continuation) => {
+                self.symbol_layouts.insert(*symbol, *layout);
+
+                if let Expr::Tag {
+                    tag_layout,
+                    tag_id,
+                    arguments,
+                    reuse: None,
+                    ..
+                } = expr
+                {
+                    // the immediate tag of a tagged pointer is stored in the value itself
+                    let is_allocated = !matches!(tag_layout, UnionLayout::NonRecursive(_))
+                        && !tag_layout.tag_is_immediate(*tag_id);
+
+                    // a tag without arguments may be the null pointer of a nullable union
+                    let has_refcounted_fields = arguments.is_empty()
+                        || arguments.iter().any(|argument| {
+                            self.symbol_layouts
+                                .get(argument)
+                                .map_or(true, |layout| layout_interner.contains_refcounted(*layout))
+                        });
+
+                    // freeing the value would skip its finalizer
+                    let is_finalized = layout_interner.finalizer(*layout).is_some();
+
+                    if is_allocated && !has_refcounted_fields && !is_finalized {
+                        self.single_owners.insert(*symbol);
+                    }
+                }
+
+                self.find_candidates(layout_interner, continuation);
+            }
+            Stmt::Switch {
+                branches,
+                default_branch,
+                ..
+            } => {
+                for (_, _, branch) in branches.iter() {
+                    self.find_candidates(layout_interner, branch);
+                }
+
+                self.find_candidates(layout_interner, default_branch.1);
+            }
+            Stmt::Join {
+                parameters,
+                body,
+                remainder,
+                ..
+            } => {
+                for param in parameters.iter() {
+                    self.symbol_layouts.insert(param.symbol, param.layout);
+                }
+
+                self.find_candidates(layout_interner, body);
+                self.find_candidates(layout_interner, remainder);
+            }
+            Stmt::Refcounting(_, continuation)
+            | Stmt::Expect {
+                remainder: continuation,
+                ..
+            }
+            | Stmt::Dbg {
+                remainder: continuation,
+                ..
+            } => self.find_candidates(layout_interner, continuation),
+            Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => {}
+        }
+    }
+
+    /// Drop every candidate that is used in a way that may share it.
+    fn remove_shared(&mut self, stmt: &Stmt<'a>) {
+        match stmt {
+            Stmt::Let(_, expr, _, continuation) => {
+                self.remove_shared_in_expr(expr);
+                self.remove_shared(continuation);
+            }
+            Stmt::Switch {
+                cond_symbol,
+                branches,
+                default_branch,
+                ..
+            } => {
+                self.single_owners.remove(cond_symbol);
+
+                for (_, _, branch) in branches.iter() {
+                    self.remove_shared(branch);
+                }
+
+                self.remove_shared(default_branch.1);
+            }
+            Stmt::Ret(symbol) | Stmt::Crash(symbol, _) => {
+                self.single_owners.remove(symbol);
+            }
+            Stmt::Refcounting(modify_rc, continuation) => {
+                if let ModifyRc::Inc(symbol, _) = modify_rc {
+                    self.single_owners.remove(symbol);
+                }
+
+                self.remove_shared(continuation);
+            }
+            Stmt::Expect {
+                condition,
+                lookups,
+                remainder,
+                ..
+            } => {
+                self.single_owners.remove(condition);
+                self.remove_all(lookups);
+                self.remove_shared(remainder);
+            }
+            Stmt::Dbg {
+                symbol, remainder, ..
+            } => {
+                self.single_owners.remove(symbol);
+                self.remove_shared(remainder);
+            }
+            Stmt::Join {
+                body, remainder, ..
+            } => {
+                self.remove_shared(body);
+                self.remove_shared(remainder);
+            }
+            Stmt::Jump(_, arguments) => self.remove_all(arguments),
+        }
+    }
+
+    fn remove_shared_in_expr(&mut self, expr: &Expr<'a>) {
+        match expr {
+            Expr::Literal(_)
+            | Expr::NullPointer
+            | Expr::EmptyArray
+            | Expr::FunctionPointer { .. }
+            // reading the tag id or a (non-refcounted) field does not share the value
+            | Expr::GetTagId { .. }
+            | Expr::UnionAtIndex { .. } => {}
+            Expr::Call(call) => match &call.call_type {
+                CallType::LowLevel { op, .. } => match op {
+                    // looking at the refcount does not change it
+                    LowLevel::RefCountIsUnique => {}
+                    // inserted after borrow inference, so they have no borrow signature
+                    LowLevel::PtrClearTagId
+                    | LowLevel::RefCountIncRcPtr
+                    | LowLevel::RefCountDecRcPtr
+                    | LowLevel::RefCountIncDataPtr
+                    | LowLevel::RefCountDecDataPtr
+                    | LowLevel::SetJmp
+                    | LowLevel::LongJmp
+                    | LowLevel::SetLongJmpBuffer => self.remove_all(call.arguments),
+                    _ => {
+                        let borrow_signature = lowlevel_borrow_signature(*op);
+
+                        for (argument, ownership) in call.arguments.iter().zip(borrow_signature) {
+                            if let Ownership::Owned = ownership {
+                                self.single_owners.remove(argument);
+                            }
+                        }
+                    }
+                },
+                CallType::ByPointer { pointer, .. } => {
+                    self.single_owners.remove(pointer);
+                    self.remove_all(call.arguments);
+                }
+                CallType::HigherOrder(higher_order) => {
+                    self.single_owners
+                        .remove(&higher_order.passed_function.captured_environment);
+                    self.remove_all(call.arguments);
+                }
+                CallType::ByName { .. } | CallType::Foreign { .. } => {
+                    self.remove_all(call.arguments);
+                }
+            },
+            Expr::Tag { arguments, .. } | Expr::Struct(arguments) => self.remove_all(arguments),
+            Expr::Array { elems, .. } => {
+                for elem in elems.iter() {
+                    if let ListLiteralElement::Symbol(symbol) = elem {
+                        self.single_owners.remove(symbol);
+                    }
+                }
+            }
+            Expr::StructAtIndex { structure, .. } | Expr::GetElementPointer { structure, .. } => {
+                self.single_owners.remove(structure);
+            }
+            Expr::ErasedMake { value, callee } => {
+                if let Some(value) = value {
+                    self.single_owners.remove(value);
+                }
+
+                self.single_owners.remove(callee);
+            }
+            Expr::ErasedLoad { symbol, .. }
+            | Expr::Reset { symbol, .. }
+            | Expr::ResetRef { symbol, .. } => {
+                self.single_owners.remove(symbol);
+            }
+            Expr::Alloca { initializer, .. } => {
+                if let Some(initializer) = initializer {
+                    self.single_owners.remove(initializer);
+                }
+            }
+        }
+    }
+
+    fn remove_all(&mut self, symbols: &[Symbol]) {
+        for symbol in symbols {
+            self.single_owners.remove(symbol);
+        }
+    }
+}
+
+fn free_single_owners_stmt<'a>(
+    arena: &'a Bump,
+    single_owners: &MutSet<Symbol>,
+    stmt: &'a Stmt<'a>,
+) -> &'a Stmt<'a> {
+    match stmt {
+        Stmt::Let(symbol, expr, layout, continuation) => {
+            let expr = match expr {
+                // drop specialization checks the refcount before freeing a value whose tag it
+                // knows; for a single owner, the answer is known as well
+                Expr::Call(Call {
+                    call_type:
+                        CallType::LowLevel {
+                            op: LowLevel::RefCountIsUnique,
+                            ..
+                        },
+                    arguments: [argument],
+                }) if single_owners.contains(argument) => Expr::Literal(Literal::Bool(true)),
+                other => other.clone(),
+            };
+
+            arena.alloc(Stmt::Let(
+                *symbol,
+                expr,
+                *layout,
+                free_single_owners_stmt(arena, single_owners, continuation),
+            ))
+        }
+        Stmt::Switch {
+            cond_symbol,
+            cond_layout,
+            branches,
+            default_branch,
+            ret_layout,
+        } => {
+            let branches = branches
+                .iter()
+                .map(|(label, info, branch)| {
+                    let branch = free_single_owners_stmt(arena, single_owners, branch);
+
+                    (*label, info.clone(), branch.clone())
+                })
+                .collect_in::<Vec<_>>(arena)
+                .into_bump_slice();
+
+            let default_branch = (
+                default_branch.0.clone(),
+                free_single_owners_stmt(arena, single_owners, default_branch.1),
+            );
+
+            arena.alloc(Stmt::Switch {
+                cond_symbol: *cond_symbol,
+                cond_layout: *cond_layout,
+                branches,
+                default_branch,
+                ret_layout: *ret_layout,
+            })
+        }
+        Stmt::Refcounting(modify_rc, continuation) => {
+            let modify_rc = match modify_rc {
+                ModifyRc::Dec(symbol) if single_owners.contains(symbol) => ModifyRc::Free(*symbol),
+                other => *other,
+            };
+
+            arena.alloc(Stmt::Refcounting(
+                modify_rc,
+                free_single_owners_stmt(arena, single_owners, continuation),
+            ))
+        }
+        Stmt::Expect {
+            condition,
+            region,
+            lookups,
+            variables,
+            remainder,
+        } => arena.alloc(Stmt::Expect {
+            condition: *condition,
+            region: *region,
+            lookups: *lookups,
+            variables: *variables,
+            remainder: free_single_owners_stmt(arena, single_owners, remainder),
+        }),
+        Stmt::Dbg {
+            source_location,
+            source,
+            symbol,
+            variable,
+            remainder,
+        } => arena.alloc(Stmt::Dbg {
+            source_location: *source_location,
+            source: *source,
+            symbol: *symbol,
+            variable: *variable,
+            remainder: free_single_owners_stmt(arena, single_owners, remainder),
+        }),
+        Stmt::Join {
+            id,
+            parameters,
+            body,
+            remainder,
+        } => arena.alloc(Stmt::Join {
+            id: *id,
+            parameters: *parameters,
+            body: free_single_owners_stmt(arena, single_owners, body),
+            remainder: free_single_owners_stmt(arena, single_owners, remainder),
+        }),
+        Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => stmt,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use roc_target::Target;
+
+    use crate::layout::{Layout, LayoutRepr, TagIdIntType};
+
+    /// The candidates in `let tree = <tag_id> x; ret tree`, for `[Leaf I32, Node I32]` stored as
+    /// a tagged pointer with `Leaf` as its immediate tag
+    fn tagged_pointer_candidates(tag_id: TagIdIntType) -> MutSet<Symbol> {
+        let arena = Bump::new();
+        let tags = [&[Layout::I32] as &[_], &[Layout::I32] as &[_]];
+        let mut interner = STLayoutInterner::with_capacity(4, Target::LinuxX64);
+
+        let tag_layout = UnionLayout::TaggedPointer {
+            immediate_id: 0,
+            tags: &tags,
+        };
+        let layout = interner.insert_direct_no_semantic(LayoutRepr::Union(tag_layout));
+
+        let (tree, x) = (Symbol::ARG_1, Symbol::ARG_2);
+        let stmt = Stmt::Let(
+            tree,
+            Expr::Tag {
+                tag_layout,
+                tag_id,
+                arguments: arena.alloc([x]),
+                reuse: None,
+            },
+            layout,
+            arena.alloc(Stmt::Ret(tree)),
+        );
+
+        let mut env = SingleOwnerEnvironment::default();
+        env.symbol_layouts.insert(x, Layout::I32);
+        env.find_candidates(&interner, &stmt);
+
+        env.single_owners
+    }
+
+    #[test]
+    fn allocated_tag_is_a_candidate() {
+        assert!(tagged_pointer_candidates(1).contains(&Symbol::ARG_1));
+    }
+
+    #[test]
+    fn immediate_tag_is_not_a_candidate() {
+        // `Leaf x` is the integer itself, there is no allocation to free
+        assert!(tagged_pointer_candidates(0).is_empty());
+    }
+}
@@ -0,0 +1,109 @@
+//! Estimates the size of the stack frame of specialized procedures.
+//!
+//! Some targets have a hard limit on the size of a single stack frame: a Solana (SBF) program
+//! gets 4096 bytes per function, and overstepping that aborts the program at runtime. The
+//! backends keep every value that is passed by reference in a stack slot, so adding up the sizes
+//! of those values gives a conservative estimate of the frame size before any code is generated.
+
+use roc_collections::MutMap;
+use roc_module::symbol::Symbol;
+
+use crate::ir::{Proc, ProcLayout, Stmt};
+use crate::layout::{InLayout, LayoutInterner, STLayoutInterner};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StackFrame<'a> {
+    /// Estimated size of the whole frame, in bytes
+    pub size: u32,
+    /// The largest single value in the frame, and its size in bytes
+    pub largest: Option<(InLayout<'a>, u32)>,
+}
+
+impl<'a> StackFrame<'a> {
+    pub fn of_proc(interner: &STLayoutInterner<'a>, proc: &Proc<'a>) -> Self {
+        let mut frame = StackFrame {
+            size: 0,
+            largest: None,
+        };
+
+        // Arguments passed by reference live in the frame of the caller, so only the body counts.
+        // Stack slots are not shared between branches, so every branch adds to the total.
+        let mut stack = vec![&proc.body];
+
+        while let Some(stmt) = stack.pop() {
+            match stmt {
+                Stmt::Let(_, _, layout, cont) => {
+                    // also covers the result of a call that returns by pointer, which is written
+                    // directly into the stack slot of the binding
+                    frame.add_slot(interner, *layout);
+                    stack.push(cont);
+                }
+                Stmt::Switch {
+                    branches,
+                    default_branch,
+                    ..
+                } => {
+                    stack.extend(branches.iter().map(|(_, _, branch)| branch));
+                    stack.push(default_branch.1);
+                }
+                Stmt::Join {
+                    parameters,
+                    body,
+                    remainder,
+                    ..
+                } => {
+                    // join point parameters get their own stack slot, which every jump copies into
+                    for param in parameters.iter() {
+                        frame.add_slot(interner, param.layout);
+                    }
+
+                    stack.push(body);
+                    stack.push(remainder);
+                }
+                Stmt::Refcounting(_, cont) => stack.push(cont),
+                Stmt::Expect { remainder, .. } | Stmt::Dbg { remainder, .. } => {
+                    stack.push(remainder)
+                }
+                Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => {}
+            }
+        }
+
+        frame
+    }
+
+    fn add_slot(&mut self, interner: &STLayoutInterner<'a>, layout: InLayout<'a>) {
+        let repr = interner.get_repr(layout);
+
+        if !repr.is_passed_by_reference(interner) {
+            return;
+        }
+
+        let size = repr.stack_size(interner);
+        self.size = self.size.saturating_add(size);
+
+        if self.largest.map_or(true, |(_, largest)| size > largest) {
+            self.largest = Some((layout, size));
+        }
+    }
+}
+
+/// All procedures whose estimated stack frame is larger than `max_frame_size` bytes,
+/// sorted by name so that the output is deterministic.
+pub fn oversized_stack_frames<'a>(
+    interner: &STLayoutInterner<'a>,
+    procedures: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    max_frame_size: u32,
+) -> Vec<(Symbol, StackFrame<'a>)> {
+    let mut oversized: Vec<_> = procedures
+        .iter()
+        .filter_map(|((symbol, _), proc)| {
+            let frame = StackFrame::of_proc(interner, proc);
+
+            (frame.size > max_frame_size).then_some((*symbol, frame))
+        })
+        .collect();
+
+    oversized.sort_by_key(|(symbol, frame)| (*symbol, frame.size));
+
+    oversized
+}
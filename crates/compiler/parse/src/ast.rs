@@ -739,8 +739,11 @@ pub enum ValueDef<'a> {
         preceding_comment: Region,
     },
 
+    /// e.g. `expect List.len(list) == 3, "case ${name}"`. The message is shown above the report
+    /// when the expectation fails.
     Expect {
         condition: &'a Loc<Expr<'a>>,
+        message: Option<&'a Loc<Expr<'a>>>,
         preceding_comment: Region,
     },
 
@@ -986,11 +989,19 @@ impl<'a, 'b> Iterator for RecursiveValueDefIter<'a, 'b> {
                         ValueDef::Dbg {
                             condition,
                             preceding_comment: _,
-                        }
-                        | ValueDef::Expect {
+                        } => self.push_pending_from_expr(&condition.value),
+
+                        ValueDef::Expect {
                             condition,
+                            message,
                             preceding_comment: _,
-                        } => self.push_pending_from_expr(&condition.value),
+                        } => {
+                            if let Some(message) = message {
+                                self.push_pending_from_expr(&message.value);
+                            }
+
+                            self.push_pending_from_expr(&condition.value);
+                        }
 
                         ValueDef::ModuleImport(ModuleImport {
                             before_name: _,
@@ -2684,11 +2695,12 @@ impl<'a> Malformed for ValueDef<'a> {
             ValueDef::Dbg {
                 condition,
                 preceding_comment: _,
-            }
-            | ValueDef::Expect {
+            } => condition.is_malformed(),
+            ValueDef::Expect {
                 condition,
+                message,
                 preceding_comment: _,
-            } => condition.is_malformed(),
+            } => condition.is_malformed() || message.is_some_and(|message| message.is_malformed()),
             ValueDef::ModuleImport(ModuleImport {
                 before_name: _,
                 name: _,
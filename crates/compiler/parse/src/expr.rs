@@ -2689,8 +2689,27 @@ fn expect_help<'a>(
         )
         .map_err(|(_, f)| (MadeProgress, f))?;
 
+        // `expect condition, message`
+        let (message, state) = match byte(b',', EExpect::Expect).parse(arena, state.clone(), 0) {
+            Ok((_, (), state)) => {
+                let (_, message, state) = parse_block(
+                    check_for_arrow,
+                    arena,
+                    state,
+                    true,
+                    EExpect::IndentCondition,
+                    EExpect::Message,
+                )
+                .map_err(|(_, f)| (MadeProgress, f))?;
+
+                (Some(&*arena.alloc(message)), state)
+            }
+            Err(_) => (None, state),
+        };
+
         let vd = ValueDef::Expect {
             condition: arena.alloc(condition),
+            message,
             preceding_comment,
         };
 
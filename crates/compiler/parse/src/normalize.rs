@@ -445,9 +445,11 @@ impl<'a> Normalize<'a> for ValueDef<'a> {
             },
             Expect {
                 condition,
+                message,
                 preceding_comment: _,
             } => Expect {
                 condition: arena.alloc(condition.normalize(arena)),
+                message: message.map(|message| &*arena.alloc(message.normalize(arena))),
                 preceding_comment: Region::zero(),
             },
             ModuleImport(module_import) => ModuleImport(module_import.normalize(arena)),
@@ -1558,6 +1560,9 @@ impl<'a> Normalize<'a> for EExpect<'a> {
                 EExpect::Continuation(arena.alloc(inner_err.normalize(arena)), Position::zero())
             }
             EExpect::IndentCondition(_) => EExpect::IndentCondition(Position::zero()),
+            EExpect::Message(inner_err, _) => {
+                EExpect::Message(arena.alloc(inner_err.normalize(arena)), Position::zero())
+            }
         }
     }
 }
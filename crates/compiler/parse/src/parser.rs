@@ -611,6 +611,17 @@ impl<'a> EExpr<'a> {
             | EExpr::UnexpectedTopLevelExpr(p) => Region::from_pos(*p),
         }
     }
+
+    /// Whether parsing `src` failed because it ended too soon, rather than because of something
+    /// in it that can't be parsed. More source could still make it parse, which is how the repl
+    /// knows to keep reading lines.
+    pub fn is_unexpected_end(&self, src: &str) -> bool {
+        match self {
+            // reported where the string starts, which is not where the source ran out
+            EExpr::Str(EString::EndlessMultiLine(_), _) => true,
+            _ => self.get_region().start().offset as usize >= src.trim_end().len(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -924,12 +935,15 @@ pub enum EExpect<'a> {
     Condition(&'a EExpr<'a>, Position),
     Continuation(&'a EExpr<'a>, Position),
     IndentCondition(Position),
+    Message(&'a EExpr<'a>, Position),
 }
 
 impl<'a> EExpect<'a> {
     pub fn get_region(&self) -> Region {
         match self {
-            EExpect::Condition(expr, _) | EExpect::Continuation(expr, _) => expr.get_region(),
+            EExpect::Condition(expr, _)
+            | EExpect::Continuation(expr, _)
+            | EExpect::Message(expr, _) => expr.get_region(),
             EExpect::Space(_, p)
             | EExpect::Dbg(p)
             | EExpect::Expect(p)
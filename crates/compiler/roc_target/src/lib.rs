@@ -41,6 +41,8 @@ pub enum PtrWidth {
 pub enum Architecture {
     Aarch32,
     Aarch64,
+    Riscv64,
+    Sbf,
     Wasm32,
     X86_32,
     X86_64,
@@ -51,6 +53,8 @@ impl std::fmt::Display for Architecture {
         let arch_str = match self {
             Architecture::Aarch32 => "aarch32",
             Architecture::Aarch64 => "aarch64",
+            Architecture::Riscv64 => "riscv64",
+            Architecture::Sbf => "sbf",
             Architecture::Wasm32 => "wasm32",
             Architecture::X86_32 => "x86_32",
             Architecture::X86_64 => "x86_64",
@@ -64,7 +68,7 @@ impl Architecture {
         use Architecture::*;
 
         match self {
-            X86_64 | Aarch64 => PtrWidth::Bytes8,
+            X86_64 | Aarch64 | Riscv64 | Sbf => PtrWidth::Bytes8,
             X86_32 | Aarch32 | Wasm32 => PtrWidth::Bytes4,
         }
     }
@@ -79,12 +83,14 @@ pub enum Target {
     LinuxX32,
     LinuxX64,
     LinuxArm64,
+    LinuxRiscv64,
     MacX64,
     MacArm64,
     WinX32,
     WinX64,
     WinArm64,
     Wasm32,
+    Sbf,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -100,17 +106,19 @@ impl Target {
             LinuxX32 | WinX32 => Architecture::X86_32,
             LinuxX64 | WinX64 | MacX64 => Architecture::X86_64,
             LinuxArm64 | WinArm64 | MacArm64 => Architecture::Aarch64,
+            LinuxRiscv64 => Architecture::Riscv64,
             Wasm32 => Architecture::Wasm32,
+            Sbf => Architecture::Sbf,
         }
     }
 
     pub const fn operating_system(&self) -> OperatingSystem {
         use Target::*;
         match self {
-            LinuxX32 | LinuxX64 | LinuxArm64 => OperatingSystem::Linux,
+            LinuxX32 | LinuxX64 | LinuxArm64 | LinuxRiscv64 => OperatingSystem::Linux,
             MacX64 | MacArm64 => OperatingSystem::Mac,
             WinX32 | WinX64 | WinArm64 => OperatingSystem::Windows,
-            Wasm32 => OperatingSystem::Freestanding,
+            Wasm32 | Sbf => OperatingSystem::Freestanding,
         }
     }
 
@@ -130,11 +138,41 @@ impl Target {
     }
 
     pub const fn max_by_value_size(&self) -> usize {
-        // Pass values larger than 4 machine words by reference.
-        // This is a reasonable default for most architectures. We want to pass large values by
-        // reference because it's more efficient than copying them around on the stack, and puts
-        // less pressure on CPU registers.
-        self.ptr_size() * 4
+        match self.architecture() {
+            // SBF only has 5 argument registers and a single return register, and every byte
+            // copied through a function's 4KB stack frame counts against it. Returning anything
+            // that does not fit in two registers goes through a caller-provided buffer instead.
+            Architecture::Sbf => self.ptr_size() * 2,
+            // Pass values larger than 4 machine words by reference.
+            // This is a reasonable default for most architectures. We want to pass large values by
+            // reference because it's more efficient than copying them around on the stack, and puts
+            // less pressure on CPU registers.
+            _ => self.ptr_size() * 4,
+        }
+    }
+
+    /// The largest stack frame a single function may use, for targets that enforce one.
+    pub const fn max_stack_frame_size(&self) -> Option<u32> {
+        match self {
+            // The Solana runtime gives every function a fixed 4KB frame
+            Target::Sbf => Some(4096),
+            _ => None,
+        }
+    }
+
+    /// The most stack the frames along a chain of calls may take together, for targets that
+    /// enforce a limit.
+    pub const fn max_stack_depth(&self) -> Option<u32> {
+        match self {
+            Target::Sbf => Some(4096),
+            _ => None,
+        }
+    }
+
+    /// Whether this is the target the compiler runs on, which the tools of the system can build
+    /// and link for.
+    pub fn is_native(&self) -> bool {
+        *self == Self::default()
     }
 
     pub const fn ptr_alignment_bytes(&self) -> usize {
@@ -145,7 +183,7 @@ impl Target {
     pub const fn object_file_ext(&self) -> &str {
         use Target::*;
         match self {
-            LinuxX32 | LinuxX64 | LinuxArm64 | MacX64 | MacArm64 => "o",
+            LinuxX32 | LinuxX64 | LinuxArm64 | LinuxRiscv64 | MacX64 | MacArm64 | Sbf => "o",
             WinX32 | WinX64 | WinArm64 => "obj",
             Wasm32 => "wasm",
         }
@@ -155,7 +193,7 @@ impl Target {
     pub const fn static_library_file_ext(&self) -> &str {
         use Target::*;
         match self {
-            LinuxX32 | LinuxX64 | LinuxArm64 | MacX64 | MacArm64 => "a",
+            LinuxX32 | LinuxX64 | LinuxArm64 | LinuxRiscv64 | MacX64 | MacArm64 | Sbf => "a",
             WinX32 | WinX64 | WinArm64 => "lib",
             Wasm32 => "wasm",
         }
@@ -165,7 +203,7 @@ impl Target {
     pub const fn dynamic_library_file_ext(&self) -> &str {
         use Target::*;
         match self {
-            LinuxX32 | LinuxX64 | LinuxArm64 => "so",
+            LinuxX32 | LinuxX64 | LinuxArm64 | LinuxRiscv64 | Sbf => "so",
             MacX64 | MacArm64 => "dylib",
             WinX32 | WinX64 | WinArm64 => "dll",
             Wasm32 => "wasm",
@@ -176,9 +214,11 @@ impl Target {
     pub const fn executable_file_ext(&self) -> Option<&str> {
         use Target::*;
         match self {
-            LinuxX32 | LinuxX64 | LinuxArm64 | MacX64 | MacArm64 => None,
+            LinuxX32 | LinuxX64 | LinuxArm64 | LinuxRiscv64 | MacX64 | MacArm64 => None,
             WinX32 | WinX64 | WinArm64 => Some("exe"),
             Wasm32 => Some("wasm"),
+            // the Solana runtime loads programs as shared objects
+            Sbf => Some("so"),
         }
     }
 
@@ -187,7 +227,7 @@ impl Target {
     pub fn prebuilt_static_object(&self) -> String {
         use Target::*;
         match self {
-            LinuxX32 | LinuxX64 | LinuxArm64 | MacX64 | MacArm64 | Wasm32 => {
+            LinuxX32 | LinuxX64 | LinuxArm64 | LinuxRiscv64 | MacX64 | MacArm64 | Wasm32 | Sbf => {
                 format!("{}.o", self)
             }
             WinX32 | WinX64 | WinArm64 => {
@@ -201,7 +241,7 @@ impl Target {
     pub fn prebuilt_static_library(&self) -> String {
         use Target::*;
         match self {
-            LinuxX32 | LinuxX64 | LinuxArm64 | MacX64 | MacArm64 | Wasm32 => {
+            LinuxX32 | LinuxX64 | LinuxArm64 | LinuxRiscv64 | MacX64 | MacArm64 | Wasm32 | Sbf => {
                 format!("{}.a", self)
             }
             WinX32 | WinX64 | WinArm64 => {
@@ -328,6 +368,7 @@ impl FromStr for Target {
             "linux-x32" => Ok(LinuxX32),
             "linux-x64" => Ok(LinuxX64),
             "linux-arm64" => Ok(LinuxArm64),
+            "linux-riscv64" => Ok(LinuxRiscv64),
             // TODO: Can we change these to just `mac`.
             // Currently, we need to keep it as `macos` to match platform naming.
             "macos-x64" => Ok(MacX64),
@@ -336,6 +377,7 @@ impl FromStr for Target {
             "windows-x64" => Ok(WinX64),
             "windows-arm64" => Ok(WinArm64),
             "wasm32" => Ok(Wasm32),
+            "sbf" => Ok(Sbf),
             _ => Err(ParseError::InvalidTargetString),
         }
     }
@@ -354,6 +396,7 @@ impl From<&Target> for &'static str {
             LinuxX32 => "linux-x32",
             LinuxX64 => "linux-x64",
             LinuxArm64 => "linux-arm64",
+            LinuxRiscv64 => "linux-riscv64",
             // TODO: Can we change these to just `mac`.
             // Currently, we need to keep it as `macos` to match platform naming.
             MacX64 => "macos-x64",
@@ -362,6 +405,7 @@ impl From<&Target> for &'static str {
             WinX64 => "windows-x64",
             WinArm64 => "windows-arm64",
             Wasm32 => "wasm32",
+            Sbf => "sbf",
         }
     }
 }
@@ -391,6 +435,11 @@ impl From<&Triple> for Target {
                 operating_system: OperatingSystem::Linux,
                 ..
             } => Target::LinuxArm64,
+            Triple {
+                architecture: Architecture::Riscv64(_),
+                operating_system: OperatingSystem::Linux,
+                ..
+            } => Target::LinuxRiscv64,
             Triple {
                 architecture: Architecture::X86_32(_),
                 operating_system: OperatingSystem::Windows,
@@ -420,6 +469,10 @@ impl From<&Triple> for Target {
                 architecture: Architecture::Wasm32,
                 ..
             } => Target::Wasm32,
+            Triple {
+                architecture: Architecture::Bpfel,
+                ..
+            } => Target::Sbf,
             _ => {
                 user_error!("Target triple ({}) is not currently supported by the roc compiler. Feel free to file an issue to request support", triple);
             }
@@ -446,12 +499,14 @@ impl TryFrom<(Architecture, OperatingSystem)> for Target {
             (Architecture::X86_32, OperatingSystem::Linux) => Ok(Target::LinuxX32),
             (Architecture::X86_64, OperatingSystem::Linux) => Ok(Target::LinuxX64),
             (Architecture::Aarch64, OperatingSystem::Linux) => Ok(Target::LinuxArm64),
+            (Architecture::Riscv64, OperatingSystem::Linux) => Ok(Target::LinuxRiscv64),
             (Architecture::X86_32, OperatingSystem::Windows) => Ok(Target::WinX32),
             (Architecture::X86_64, OperatingSystem::Windows) => Ok(Target::WinX64),
             (Architecture::Aarch64, OperatingSystem::Windows) => Ok(Target::WinArm64),
             (Architecture::X86_64, OperatingSystem::Mac) => Ok(Target::MacX64),
             (Architecture::Aarch64, OperatingSystem::Mac) => Ok(Target::MacArm64),
             (Architecture::Wasm32, _) => Ok(Target::Wasm32),
+            (Architecture::Sbf, OperatingSystem::Freestanding) => Ok(Target::Sbf),
             _ => Err(TargetFromTripleError::TripleUnsupported),
         }
     }
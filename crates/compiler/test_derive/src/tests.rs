@@ -4,5 +4,6 @@ mod decoding;
 mod encoding;
 mod eq;
 mod hash;
+mod inspect;
 
 mod util;
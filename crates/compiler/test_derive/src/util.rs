@@ -536,7 +536,9 @@ where
         Default::default(),
         target,
         FunctionKind::LambdaSet,
-        roc_reporting::report::RenderTarget::ColorTerminal,
+        roc_reporting::report::RenderTarget::ColorTerminal(
+            roc_reporting::report::ColorChoice::Always,
+        ),
         roc_reporting::report::DEFAULT_PALETTE,
         RocCacheDir::Disallowed,
         Threading::AllAvailable,
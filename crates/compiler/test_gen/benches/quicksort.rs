@@ -88,6 +88,9 @@ fn roc_function<'a>(
         ignore_problems: false,
         emit_debug_info: true,
         opt_level: OptLevel::Optimize,
+        options: helpers::llvm::BackendOptions::default(),
+        finalized_type: None,
+        stack_guard: false,
     };
 
     let context = inkwell::context::Context::create();
@@ -0,0 +1,113 @@
+#[path = "../src/helpers/mod.rs"]
+mod helpers;
+
+// defines roc_alloc and friends
+pub use helpers::platform_functions::*;
+
+use bumpalo::Bump;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use roc_gen_llvm::{llvm::build::LlvmBackendMode, run_roc::RocCallResult, run_roc_dylib};
+use roc_mono::ir::OptLevel;
+
+// Builds a binary search tree node by node, with every node allocated with roc_alloc, and with
+// the nodes allocated from pools (`--pool-alloc`).
+
+type Input = i64;
+type Output = i64;
+
+type Main<I, O> = unsafe extern "C" fn(I, *mut RocCallResult<O>);
+
+const ROC_TREE: &str = indoc::indoc!(
+    r#"
+    app "bench" provides [main] to "./platform"
+
+    Tree : [Leaf, Node Tree I64 Tree]
+
+    insert : Tree, I64 -> Tree
+    insert = \tree, value ->
+        when tree is
+            Leaf -> Node Leaf value Leaf
+            Node left x right ->
+                if value < x then
+                    Node (insert left value) x right
+                else
+                    Node left x (insert right value)
+
+    sum : Tree -> I64
+    sum = \tree ->
+        when tree is
+            Leaf -> 0
+            Node left x right -> sum left + x + sum right
+
+    build : Tree, I64, I64 -> Tree
+    build = \tree, n, count ->
+        if n == count then
+            tree
+        else
+            build (insert tree ((n * 7919) % count)) (n + 1) count
+
+    main : I64 -> I64
+    main = \count -> sum (build Leaf 0 count)
+    "#
+);
+
+fn roc_function<'a>(
+    arena: &'a Bump,
+    source: &str,
+    pool_alloc: bool,
+) -> libloading::Symbol<'a, Main<Input, Output>> {
+    let config = helpers::llvm::HelperConfig {
+        mode: LlvmBackendMode::GenTest,
+        ignore_problems: false,
+        emit_debug_info: true,
+        opt_level: OptLevel::Optimize,
+        options: helpers::llvm::BackendOptions {
+            pool_alloc,
+            ..Default::default()
+        },
+        finalized_type: None,
+        stack_guard: false,
+    };
+
+    let context = inkwell::context::Context::create();
+    let (main_fn_name, errors, lib) = helpers::llvm::helper(
+        arena,
+        config,
+        source,
+        arena.alloc(context),
+        roc_load::FunctionKind::LambdaSet,
+    );
+
+    assert!(errors.is_empty(), "Encountered errors:\n{errors}");
+
+    run_roc_dylib!(arena.alloc(lib), main_fn_name, Input, Output)
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let arena = Bump::new();
+
+    let roc_alloc_main = roc_function(&arena, ROC_TREE, false);
+    let pool_alloc_main = roc_function(&arena, ROC_TREE, true);
+
+    // a prime, so the values that are inserted are all different
+    let count: i64 = 10_007;
+
+    c.bench_function("roc tree with roc_alloc", |b| {
+        b.iter(|| unsafe {
+            let mut main_result = RocCallResult::default();
+
+            roc_alloc_main(black_box(count), &mut main_result);
+        })
+    });
+
+    c.bench_function("roc tree with pools", |b| {
+        b.iter(|| unsafe {
+            let mut main_result = RocCallResult::default();
+
+            pool_alloc_main(black_box(count), &mut main_result);
+        })
+    });
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
@@ -2364,4 +2364,291 @@ mod inspect {
             RocStr
         );
     }
+
+    #[test]
+    #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+    fn opaque_custom_inspector() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+            app "test" provides [main] to "./platform"
+
+            Meters := U64 implements [Inspect { to_inspector: meters_to_inspector }]
+
+            meters_to_inspector : Meters -> Inspector f where f implements InspectFormatter
+            meters_to_inspector = \@Meters n -> Inspect.str "$(Num.to_str n)m"
+
+            main = Inspect.to_str (@Meters 42)
+            "#
+            ),
+            RocStr::from(r#""42m""#),
+            RocStr
+        );
+    }
+
+    #[test]
+    #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+    fn opaque_custom_inspector_in_containers() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+            app "test" provides [main] to "./platform"
+
+            Meters := U64 implements [Inspect { to_inspector: meters_to_inspector }]
+
+            meters_to_inspector : Meters -> Inspector f where f implements InspectFormatter
+            meters_to_inspector = \@Meters n -> Inspect.str "$(Num.to_str n)m"
+
+            Secret := Str
+
+            main = Inspect.to_str {
+                total: @Meters 8,
+                legs: [@Meters 3, @Meters 5],
+                ends: (@Meters 0, @Meters 8),
+                last: Leg (@Meters 5),
+                key: @Secret "hunter2",
+            }
+            "#
+            ),
+            RocStr::from(
+                r#"{ends: ("0m", "8m"), key: <opaque>, last: (Leg "5m"), legs: ["3m", "5m"], total: "8m"}"#
+            ),
+            RocStr
+        );
+    }
+
+    #[test]
+    #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+    fn opaque_custom_inspector_with_polymorphic_call() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+            app "test" provides [main] to "./platform"
+
+            Meters := U64 implements [Inspect { to_inspector: meters_to_inspector }]
+
+            meters_to_inspector : Meters -> Inspector f where f implements InspectFormatter
+            meters_to_inspector = \@Meters n -> Inspect.str "$(Num.to_str n)m"
+
+            late = \a -> Inspect.to_str { a }
+
+            main = late (@Meters 42)
+            "#
+            ),
+            RocStr::from(r#"{a: "42m"}"#),
+            RocStr
+        );
+    }
+}
+
+mod json {
+    #[cfg(feature = "gen-llvm")]
+    use crate::helpers::llvm::assert_evals_to;
+
+    #[cfg(feature = "gen-wasm")]
+    use crate::helpers::wasm::assert_evals_to;
+
+    #[cfg(all(test, any(feature = "gen-llvm", feature = "gen-wasm")))]
+    use indoc::indoc;
+
+    #[cfg(all(test, any(feature = "gen-llvm", feature = "gen-wasm")))]
+    use roc_std::RocStr;
+
+    #[test]
+    #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+    fn encode_escapes() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main =
+                    Encode.to_bytes ["say \"hi\"", "a\\b", "line\nbreak", "\u(1)"] Json.utf8
+                    |> Str.from_utf8
+                    |> Result.with_default "<bad>"
+                "#
+            ),
+            RocStr::from(r#"["say \"hi\"","a\\b","line\nbreak","\u0001"]"#),
+            RocStr
+        )
+    }
+
+    #[test]
+    #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+    fn encode_record_tuple_and_tag() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main =
+                    value : { pair : (U8, Str), result : [Ok U8, Err Str] }
+                    value = { pair: (1, "a"), result: Ok 2 }
+
+                    Encode.to_bytes value Json.utf8
+                    |> Str.from_utf8
+                    |> Result.with_default "<bad>"
+                "#
+            ),
+            RocStr::from(r#"{"pair":[1,"a"],"result":{"Ok":[2]}}"#),
+            RocStr
+        )
+    }
+
+    #[test]
+    #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+    fn encode_field_names_in_camel_case() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main =
+                    Encode.to_bytes { user_id: 7, display_name: "roc" } (Json.utf8_with { field_name_mapping: CamelCase })
+                    |> Str.from_utf8
+                    |> Result.with_default "<bad>"
+                "#
+            ),
+            RocStr::from(r#"{"displayName":"roc","userId":7}"#),
+            RocStr
+        )
+    }
+
+    #[test]
+    #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+    fn decode_escapes_and_surrogate_pairs() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main =
+                    input = Str.to_utf8 "[\"tab\\there\", \"\\u00e9\\/\", \"\\uD83D\\uDE00\"]"
+
+                    when Json.from_bytes input Json.utf8 is
+                        Ok strs -> Str.join_with strs "|"
+                        Err _ -> "<bad>"
+                "#
+            ),
+            RocStr::from("tab\there|é/|😀"),
+            RocStr
+        )
+    }
+
+    #[test]
+    #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+    fn decode_lone_surrogate_is_an_error() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main =
+                    result : Result Str _
+                    result = Json.from_bytes (Str.to_utf8 "\"ok \\uDE00\"") Json.utf8
+
+                    when result is
+                        Err (InvalidJson { offset }) -> offset
+                        Ok _ -> 0
+                "#
+            ),
+            4,
+            u64
+        )
+    }
+
+    #[test]
+    #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+    fn decode_exponents_into_dec() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main =
+                    input = Str.to_utf8 "[1.5e3, 25e-3, -2E+2, 1.25E1, 0.0e-30]"
+
+                    actual : Result (List Dec) _
+                    actual = Json.from_bytes input Json.utf8
+
+                    actual == Ok [1500, 0.025, -200, 12.5, 0]
+                "#
+            ),
+            true,
+            bool
+        )
+    }
+
+    #[test]
+    #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+    fn decode_error_offset() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main =
+                    result : Result (List (List U8)) _
+                    result = Json.from_bytes (Str.to_utf8 "[[1, 2], [3, x]]") Json.utf8
+
+                    when result is
+                        Err (InvalidJson { offset }) -> offset
+                        Ok _ -> 0
+                "#
+            ),
+            13,
+            u64
+        )
+    }
+
+    #[test]
+    #[cfg(all(
+        any(feature = "gen-llvm", feature = "gen-wasm"),
+        not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+    ))]
+    fn decode_record_in_camel_case() {
+        assert_evals_to!(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main =
+                    input = Str.to_utf8 " { \"displayName\" : \"roc\", \"userId\": 7 } "
+
+                    when Json.from_bytes input (Json.utf8_with { field_name_mapping: CamelCase }) is
+                        Ok { user_id, display_name } -> "$(display_name) $(Num.to_str (user_id + 0u8))"
+                        Err _ -> "<bad>"
+                "#
+            ),
+            RocStr::from("roc 7"),
+            RocStr
+        )
+    }
+
+    #[test]
+    #[cfg(all(
+        any(feature = "gen-llvm", feature = "gen-wasm"),
+        not(debug_assertions) // https://github.com/roc-lang/roc/issues/3898
+    ))]
+    fn decode_skips_deeply_nested_field() {
+        // the unknown field is skipped without recursing, however deep it is
+        assert_evals_to!(
+            indoc!(
+                r#"
+                app "test" provides [main] to "./platform"
+
+                main =
+                    depth = 100000
+                    nested = Str.concat (Str.repeat "[{\"x\":" depth) (Str.repeat "1}]" depth)
+                    input = Str.to_utf8 "{\"skipped\":$(nested),\"kept\":42}"
+
+                    when Json.from_bytes input Json.utf8 is
+                        Ok { kept } -> kept + 0u64
+                        Err _ -> 0
+                "#
+            ),
+            42,
+            u64
+        )
+    }
 }
@@ -550,3 +550,148 @@ fn walk_sum_keys() {
         i64
     );
 }
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn update_inserts_missing_key() {
+    assert_evals_to!(
+        indoc!(
+            r"
+            count : Result I64 [Missing] -> Result I64 [Missing]
+            count = \value ->
+                when value is
+                    Ok n -> Ok (n + 1)
+                    Err Missing -> Ok 1
+
+            dict : Dict.Dict I64 I64
+            dict =
+                Dict.empty {}
+                    |> Dict.insert 1 10
+                    |> Dict.update 2 count
+
+            (Dict.keys dict, Dict.values dict)
+            "
+        ),
+        (RocList::from_slice(&[1, 2]), RocList::from_slice(&[10, 1])),
+        (RocList<i64>, RocList<i64>)
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn update_overwrites_existing_key() {
+    assert_evals_to!(
+        indoc!(
+            r"
+            count : Result I64 [Missing] -> Result I64 [Missing]
+            count = \value ->
+                when value is
+                    Ok n -> Ok (n + 1)
+                    Err Missing -> Ok 1
+
+            dict : Dict.Dict I64 I64
+            dict =
+                Dict.empty {}
+                    |> Dict.insert 1 10
+                    |> Dict.insert 2 20
+                    |> Dict.update 1 count
+
+            (Dict.keys dict, Dict.values dict)
+            "
+        ),
+        (RocList::from_slice(&[1, 2]), RocList::from_slice(&[11, 20])),
+        (RocList<i64>, RocList<i64>)
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn update_removes_key() {
+    assert_evals_to!(
+        indoc!(
+            r"
+            remove : Result I64 [Missing] -> Result I64 [Missing]
+            remove = \_ -> Err Missing
+
+            dict : Dict.Dict I64 I64
+            dict =
+                Dict.empty {}
+                    |> Dict.insert 1 10
+                    |> Dict.insert 2 20
+                    |> Dict.insert 3 30
+                    |> Dict.update 1 remove
+                    |> Dict.update 4 remove
+
+            (Dict.keys dict, Dict.values dict)
+            "
+        ),
+        (RocList::from_slice(&[3, 2]), RocList::from_slice(&[30, 20])),
+        (RocList<i64>, RocList<i64>)
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn update_shared_dict() {
+    assert_evals_to!(
+        indoc!(
+            r"
+            original : Dict.Dict I64 I64
+            original =
+                Dict.empty {}
+                    |> Dict.insert 1 10
+                    |> Dict.insert 2 20
+
+            set : I64 -> (Result I64 [Missing] -> Result I64 [Missing])
+            set = \value -> \_ -> Ok value
+
+            remove : Result I64 [Missing] -> Result I64 [Missing]
+            remove = \_ -> Err Missing
+
+            updated =
+                original
+                    |> Dict.update 1 (set 100)
+                    |> Dict.update 2 remove
+                    |> Dict.update 3 (set 300)
+
+            # the original is still used, so it must not be changed in place
+            (Dict.values original, Dict.values updated)
+            "
+        ),
+        (
+            RocList::from_slice(&[10, 20]),
+            RocList::from_slice(&[100, 300])
+        ),
+        (RocList<i64>, RocList<i64>)
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn eq_ignores_insertion_order() {
+    // keys hash to the same buckets either way, but are stored and walked in insertion order,
+    // so this compares dictionaries whose buckets and entries are laid out differently
+    assert_evals_to!(
+        indoc!(
+            r"
+            forwards : Dict.Dict I64 I64
+            forwards =
+                Dict.empty {}
+                    |> Dict.insert 1 10
+                    |> Dict.insert 2 20
+                    |> Dict.insert 3 30
+
+            backwards : Dict.Dict I64 I64
+            backwards =
+                Dict.empty {}
+                    |> Dict.insert 3 30
+                    |> Dict.insert 2 20
+                    |> Dict.insert 1 10
+
+            (forwards == backwards, Dict.keys forwards == Dict.keys backwards)
+            "
+        ),
+        (true, false),
+        (bool, bool)
+    );
+}
@@ -43,3 +43,28 @@ fn multi_branch_capturing() {
         (u64, u64)
     );
 }
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn cfi_checks_calls_of_erased_closures() {
+    let ir = crate::helpers::llvm::llvm_ir_with_options(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            apply = \f, x -> f x
+
+            main = apply (\n -> n + 1u8) 41u8
+            "#
+        ),
+        roc_target::Target::default(),
+        crate::helpers::llvm::BackendOptions {
+            cfi: true,
+            ..Default::default()
+        },
+    );
+
+    // the closure is marked as a valid target, and `f x` checks that it calls one
+    assert!(ir.contains("!type !"), "{ir}");
+    assert!(ir.contains("call i1 @llvm.type.test("), "{ir}");
+}
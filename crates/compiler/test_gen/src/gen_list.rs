@@ -222,6 +222,23 @@ fn list_append_basic() {
     );
 }
 
+#[test]
+#[cfg(feature = "gen-wasm")]
+fn list_append_with_bulk_memory() {
+    use crate::helpers::wasm::{compile_to_wasm_bytes, compile_to_wasm_bytes_with_bulk_memory};
+    use std::marker::PhantomData;
+
+    let src = "List.append [1, 1] 2";
+    let memory_copies = |bytes: &[u8]| bytes.windows(2).filter(|w| w == &[0xFC, 0x0A]).count();
+
+    let arena = bumpalo::Bump::new();
+    let plain = compile_to_wasm_bytes(&arena, src, PhantomData::<RocList<i64>>);
+    let bulk = compile_to_wasm_bytes_with_bulk_memory(&arena, src, PhantomData::<RocList<i64>>);
+
+    // the host may use memory.copy itself, but the app only does with bulk memory
+    assert!(memory_copies(&bulk) > memory_copies(&plain));
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn list_take_first() {
@@ -1228,6 +1245,114 @@ fn list_walk_until_even_prefix_sum() {
     );
 }
 
+const WALK_UNTIL_RECORD_STATE: &str = indoc!(
+    r#"
+    app "test" provides [main] to "./platform"
+
+    Acc : { a : U64, b : U64 }
+
+    step : Acc, U64 -> [Continue Acc, Break Acc]
+    step = \{ a, b }, x ->
+        if x == 0 then
+            Break { a, b }
+        else
+            Continue { a: a + x, b: b + 1 }
+
+    main = List.walk_until (List.repeat 2u64 10_000) { a: 0, b: 0 } step
+    "#
+);
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn list_walk_until_record_state() {
+    assert_evals_to!(WALK_UNTIL_RECORD_STATE, (20_000, 10_000), (u64, u64));
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn list_walk_until_does_not_allocate() {
+    let ir = crate::helpers::llvm::llvm_ir_for_target(
+        WALK_UNTIL_RECORD_STATE,
+        roc_target::Target::default(),
+    );
+
+    // the `[Continue state, Break state]` union is not recursive, so it is passed by value, and
+    // the only allocation is the one for the list
+    let walk_functions = [
+        "List_walk_until_",
+        "List_iterate_",
+        "List_iter_help_",
+        "_step_",
+    ];
+
+    let mut lines = ir.lines();
+    let mut checked = 0;
+
+    while let Some(line) = lines.next() {
+        let is_walk_function =
+            line.starts_with("define") && walk_functions.iter().any(|name| line.contains(name));
+
+        if is_walk_function {
+            let body = lines
+                .by_ref()
+                .take_while(|line| *line != "}")
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            assert!(!body.contains("allocate_with_refcount"), "{line}\n{body}");
+            assert!(!body.contains("@roc_alloc"), "{line}\n{body}");
+
+            checked += 1;
+        }
+    }
+
+    assert!(checked >= 3, "{ir}");
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn list_walk_is_specialized_for_each_callback() {
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main =
+            sum = List.walk [1, 2, 3] 0 \acc, x -> acc + x
+            product = List.walk [4, 5] 1 \acc, x -> acc * x
+
+            sum + product
+        "#
+    );
+
+    let ir = crate::helpers::llvm::llvm_ir_for_target(src, roc_target::Target::default());
+
+    // the callbacks are in different lambda sets, so each one gets its own copy of the loop,
+    // which calls the callback directly rather than through a function pointer
+    let mut lines = ir.lines();
+    let mut specializations = 0;
+
+    while let Some(line) = lines.next() {
+        if line.starts_with("define") && line.contains("List_walk_help_") {
+            let body = lines
+                .by_ref()
+                .take_while(|line| *line != "}")
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            let indirect_calls = body.lines().filter(|line| match line.split_once("call ") {
+                Some((_, call)) => !call.split('(').next().unwrap_or_default().contains('@'),
+                None => false,
+            });
+
+            assert_eq!(indirect_calls.count(), 0, "{line}\n{body}");
+
+            specializations += 1;
+        }
+    }
+
+    assert_eq!(specializations, 2, "{ir}");
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn list_walk_from_sum() {
@@ -1873,6 +1998,18 @@ fn list_repeat() {
         RocList::from_slice(&[4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4, 4]),
         RocList<i64>
     );
+
+    assert_evals_to!(
+        indoc!(
+            r"
+                n = List.len [1, 2, 3, 4, 5, 6]
+
+                List.repeat 0u8 n
+            "
+        ),
+        RocList::from_slice(&[0u8; 6]),
+        RocList<u8>
+    );
 }
 
 #[test]
@@ -2450,6 +2587,30 @@ fn set_unique_list_oob() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn set_and_get_f32_list() {
+    // an f32 is 4 bytes, so every element but the last is followed by another one
+    assert_evals_to!(
+        "List.set [1.5f32, 2.5, 3.5, 4.5] 1 9.25",
+        RocList::from_slice(&[1.5, 9.25, 3.5, 4.5]),
+        RocList<f32>
+    );
+    assert_evals_to!(
+        indoc!(
+            r"
+            list = [1.5f32, 2.5, 3.5]
+
+            when List.get list 1 is
+                Ok x -> x
+                Err _ -> 0
+            "
+        ),
+        2.5,
+        f32
+    );
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn set_shared_int_list() {
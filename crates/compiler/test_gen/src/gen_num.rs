@@ -545,6 +545,14 @@ fn f32_abs() {
     assert_evals_to!("Num.abs Num.min_f32", f32::MAX, f32);
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn dec_abs() {
+    assert_evals_to!("Num.abs -4.7dec", RocDec::from_str("4.7").unwrap(), RocDec);
+    assert_evals_to!("Num.abs 5.8dec", RocDec::from_str("5.8").unwrap(), RocDec);
+    assert_evals_to!("Num.abs 0dec", RocDec::from(0), RocDec);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
 fn i64_abs() {
@@ -1065,6 +1073,13 @@ fn gen_mul_dec() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[should_panic(expected = r#"Roc failed with message: "Decimal multiplication overflowed"#)]
+fn gen_mul_dec_overflow() {
+    assert_evals_to!("170141183460469231731dec * 2", RocDec::from(0), RocDec);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn gen_sub_f64() {
@@ -1864,6 +1879,13 @@ fn pow_f64() {
     assert_evals_to!("Num.pow 2.0f64 2.0f64", 4.0, f64);
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn pow_f32() {
+    assert_evals_to!("Num.pow 1.5f32 2f32", 2.25, f32);
+    assert_evals_to!("Num.pow 4f32 0.5f32", 2.0, f32);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn pow_dec() {
@@ -1937,6 +1959,36 @@ fn pow_int_overflow() {
     assert_evals_to!("Num.pow_int 2u8 8", 0, u8);
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn pow_int_signed() {
+    assert_evals_to!("Num.pow_int -2i64 3", -8, i64);
+    assert_evals_to!("Num.pow_int -3i16 5", -243, i16);
+    assert_evals_to!("Num.pow_int 7i32 0", 1, i32);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn pow_int_128() {
+    assert_evals_to!(
+        "Num.pow_int 3i128 80",
+        I128::from(147808829414345923316083210206383297601i128),
+        I128
+    );
+    assert_evals_to!(
+        "Num.pow_int 2u128 127",
+        U128::from(170141183460469231731687303715884105728u128),
+        U128
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[should_panic(expected = r#"Roc failed with message: "Integer raised to power overflowed!"#)]
+fn pow_int_128_overflow() {
+    assert_evals_to!("Num.pow_int 2u128 128", U128::from(0), U128);
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn atan() {
@@ -2439,8 +2491,6 @@ fn mul_saturated() {
         f64
     );
 
-    // TODO: This doesn't work anywhere? It returns -1.374607431768211456 : Dec ?
-    /*
     assert_evals_to!(
         "Num.mul_saturated 170_141_183_460_469_231_731dec 2",
         RocDec::from_str("170141183460469231731.687303715884105727").unwrap(),
@@ -2451,7 +2501,138 @@ fn mul_saturated() {
         RocDec::from_str("-170141183460469231731.687303715884105728").unwrap(),
         RocDec
     );
-    */
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn mul_saturated_boundaries() {
+    // MIN * -1 is one more than MAX
+    assert_evals_to!("Num.mul_saturated Num.min_i8 -1", i8::MAX, i8);
+    assert_evals_to!("Num.mul_saturated Num.min_i16 -1", i16::MAX, i16);
+    assert_evals_to!("Num.mul_saturated Num.min_i32 -1", i32::MAX, i32);
+    assert_evals_to!("Num.mul_saturated Num.min_i64 -1", i64::MAX, i64);
+    assert_evals_to!(
+        "Num.mul_saturated Num.min_i128 -1",
+        I128::from(i128::MAX),
+        I128
+    );
+
+    // multiplying MIN by 0 or 1 does not overflow
+    assert_evals_to!("Num.mul_saturated Num.min_i8 1", i8::MIN, i8);
+    assert_evals_to!("Num.mul_saturated Num.min_i64 0", 0, i64);
+    assert_evals_to!(
+        "Num.mul_saturated Num.min_i128 1",
+        I128::from(i128::MIN),
+        I128
+    );
+    assert_evals_to!(
+        "Num.mul_saturated 1 Num.min_i128",
+        I128::from(i128::MIN),
+        I128
+    );
+    assert_evals_to!("Num.mul_saturated Num.min_i128 0", I128::from(0), I128);
+
+    // two negative numbers saturate towards MAX, mixed signs towards MIN
+    assert_evals_to!("Num.mul_saturated -100i8 -2", i8::MAX, i8);
+    assert_evals_to!("Num.mul_saturated Num.min_i64 Num.min_i64", i64::MAX, i64);
+    assert_evals_to!("Num.mul_saturated Num.max_i64 -2", i64::MIN, i64);
+    assert_evals_to!(
+        "Num.mul_saturated Num.min_i128 Num.min_i128",
+        I128::from(i128::MAX),
+        I128
+    );
+    assert_evals_to!(
+        "Num.mul_saturated Num.max_i128 -2",
+        I128::from(i128::MIN),
+        I128
+    );
+
+    // MAX * MAX
+    assert_evals_to!("Num.mul_saturated Num.max_u8 Num.max_u8", u8::MAX, u8);
+    assert_evals_to!("Num.mul_saturated Num.max_u32 Num.max_u32", u32::MAX, u32);
+    assert_evals_to!("Num.mul_saturated Num.max_u64 Num.max_u64", u64::MAX, u64);
+    assert_evals_to!(
+        "Num.mul_saturated Num.max_u128 Num.max_u128",
+        U128::from(u128::MAX),
+        U128
+    );
+    assert_evals_to!(
+        "Num.mul_saturated Num.max_i128 Num.max_i128",
+        I128::from(i128::MAX),
+        I128
+    );
+
+    // results that fit exactly are not clamped
+    assert_evals_to!("Num.mul_saturated -64i8 2", i8::MIN, i8);
+    assert_evals_to!("Num.mul_saturated 85u8 3", u8::MAX, u8);
+    assert_evals_to!(
+        "Num.mul_saturated -85070591730234615865843651857942052864i128 2",
+        I128::from(i128::MIN),
+        I128
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn dec_arithmetic_agrees_with_roc_std() {
+    let pairs = [
+        ("1.5", "2.25"),
+        ("-3.333333333333333333", "7"),
+        ("0.000000001", "-0.0000000019"),
+        ("85070591730234615866", "2"),
+        ("-85070591730234615865", "2"),
+        ("170141183460469231731", "1"),
+        ("-170141183460469231731", "-1"),
+        ("12345.678901234567", "-0.000001"),
+    ];
+
+    for (a, b) in pairs {
+        let x = RocDec::from_str(a).unwrap();
+        let y = RocDec::from_str(b).unwrap();
+
+        let checked = |answer: Option<RocDec>| answer.map_or(RocResult::err(()), RocResult::ok);
+
+        assert_evals_to!(
+            &format!("Num.add_checked {a}dec {b}dec"),
+            checked(x.checked_add(y)),
+            RocResult<RocDec, ()>
+        );
+        assert_evals_to!(
+            &format!("Num.sub_checked {a}dec {b}dec"),
+            checked(x.checked_sub(y)),
+            RocResult<RocDec, ()>
+        );
+        assert_evals_to!(
+            &format!("Num.mul_checked {a}dec {b}dec"),
+            checked(x.checked_mul(y)),
+            RocResult<RocDec, ()>
+        );
+
+        assert_evals_to!(
+            &format!("Num.add_saturated {a}dec {b}dec"),
+            x.saturating_add(y),
+            RocDec
+        );
+        assert_evals_to!(
+            &format!("Num.sub_saturated {a}dec {b}dec"),
+            x.saturating_sub(y),
+            RocDec
+        );
+        assert_evals_to!(
+            &format!("Num.mul_saturated {a}dec {b}dec"),
+            x.saturating_mul(y),
+            RocDec
+        );
+
+        // an overflowing division crashes in Roc
+        if let Some(quotient) = x.checked_div(y) {
+            assert_evals_to!(
+                &format!("Num.div_checked {a}dec {b}dec"),
+                RocResult::ok(quotient),
+                RocResult<RocDec, ()>
+            );
+        }
+    }
 }
 
 #[test]
@@ -3055,6 +3236,22 @@ fn when_on_i16() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn when_on_every_u8() {
+    let branches: String = (0..=255u32)
+        .map(|n| format!("        {n} -> {}\n", 255 - n))
+        .collect();
+
+    assert_evals_to!(
+        &format!(
+            "app \"test\" provides [main] to \"./platform\"\n\nx : U8\nx = 200\n\nmain : U8\nmain =\n    when x is\n{branches}"
+        ),
+        55,
+        u8
+    );
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn num_to_str() {
@@ -4274,3 +4471,90 @@ fn cast_signed_unsigned() {
     assert_evals_to!(r"Num.to_u8 127i8", 127, u8);
     assert_evals_to!(r"Num.to_i8 127u8", 127, i8);
 }
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn i128_division_on_sbf_links_compiler_rt() {
+    // SBF has no 128-bit divide, so LLVM lowers these to compiler-rt calls when emitting code
+    let ir = crate::helpers::llvm::llvm_ir_for_target(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            main =
+                quotient = Num.div_trunc 1_000_000_000_000i128 -7
+                remainder = Num.rem 1_000_000_000_000u128 7
+                ratio : Dec
+                ratio = 10 / 4
+
+                (quotient, remainder, ratio)
+            "#
+        ),
+        roc_target::Target::Sbf,
+    );
+
+    for libcall in ["__divti3", "__modti3", "__udivti3", "__umodti3", "__multi3"] {
+        assert!(
+            ir.lines()
+                .any(|line| line.starts_with("define") && line.contains(&format!("@{libcall}("))),
+            "{libcall} is not defined in the module"
+        );
+    }
+
+    let unresolved: Vec<_> = ir
+        .lines()
+        .filter(|line| line.starts_with("declare") && line.contains("ti3("))
+        .collect();
+
+    assert!(unresolved.is_empty(), "{unresolved:#?}");
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn overflow_checks_are_weighted_towards_the_happy_path() {
+    let ir = crate::helpers::llvm::llvm_ir_for_target(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            step : I64 -> I64
+            step = \x -> Num.neg (x + 1)
+
+            main = step 41
+            "#
+        ),
+        roc_target::Target::default(),
+    );
+
+    let weights_node = |weights: &str| {
+        ir.lines()
+            .find_map(|line| {
+                let (node, definition) = line.split_once(" = ")?;
+                (definition == format!("!{{!\"branch_weights\", {weights}}}")).then_some(node)
+            })
+            .unwrap_or_else(|| panic!("no branch weights {weights} in\n{ir}"))
+    };
+
+    // the overflow arm of the addition is the else arm, the one of the negation the then arm
+    let addition = weights_node("i32 2000, i32 1");
+    let negation = weights_node("i32 1, i32 2000");
+
+    let branches: Vec<_> = ir
+        .lines()
+        .filter(|line| line.trim_start().starts_with("br i1"))
+        .collect();
+
+    assert!(
+        branches
+            .iter()
+            .any(|line| line.contains("%has_not_overflowed")
+                && line.ends_with(&format!("!prof {addition}"))),
+        "{branches:#?}"
+    );
+    assert!(
+        branches.iter().any(
+            |line| line.contains("%is_min_val") && line.ends_with(&format!("!prof {negation}"))
+        ),
+        "{branches:#?}"
+    );
+}
@@ -86,3 +86,67 @@ fn crash_in_passed_closure() {
         RocList<u8>
     );
 }
+
+#[test]
+#[cfg(feature = "gen-wasm")]
+fn crash_with_wasm_exceptions() {
+    use crate::helpers::wasm::{compile_to_wasm_bytes, compile_to_wasm_bytes_with_exceptions};
+    use std::marker::PhantomData;
+
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main = if Bool.true then crash "hello crash" else 1u8
+        "#
+    );
+    let count = |bytes: &[u8], pattern: &[u8]| bytes.windows(2).filter(|w| w == &pattern).count();
+    let try_ = [0x06, 0x40]; // try with no result
+    let throw = [0x08, 0x00]; // throw the first tag
+    let tag_name = "roc_exception_tag".as_bytes();
+
+    let arena = bumpalo::Bump::new();
+    let plain = compile_to_wasm_bytes(&arena, src, PhantomData::<u8>);
+    let exceptions = compile_to_wasm_bytes_with_exceptions(&arena, src, PhantomData::<u8>);
+
+    // the crash throws, and the wrapper the host calls catches it
+    assert!(count(&exceptions, &throw) > count(&plain, &throw));
+    assert!(count(&exceptions, &try_) > count(&plain, &try_));
+    assert!(exceptions
+        .windows(tag_name.len())
+        .any(|window| window == tag_name));
+    assert!(!plain
+        .windows(tag_name.len())
+        .any(|window| window == tag_name));
+}
+
+#[test]
+#[cfg(all(feature = "gen-llvm", target_os = "linux"))]
+fn stack_overflow_is_a_clean_panic() {
+    use roc_mono::ir::CrashTag;
+
+    // appending to the list after the recursive call keeps it from being a tail call
+    let result = crate::helpers::llvm::llvm_evals_on_small_stack::<u64>(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            count_down : U64 -> List U64
+            count_down = \n ->
+                if n == 0 then
+                    []
+                else
+                    List.append (count_down (n - 1)) n
+
+            main = List.len (count_down 1_000_000_000)
+            "#
+        ),
+        512 * 1024,
+    );
+
+    match result {
+        Err((message, CrashTag::Roc)) => assert_eq!(message, "Stack overflow"),
+        Err((message, CrashTag::User)) => panic!(r#"User crash with message: "{message}""#),
+        Ok(length) => panic!("the recursion returned a list of {length} elements"),
+    }
+}
@@ -3249,6 +3249,216 @@ fn box_and_unbox_nonrecursive_tag() {
     )
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn nullable_box_some() {
+    assert_evals_to!(
+        indoc!(
+            r"
+            maybe : [Some (Box U64), None]
+            maybe = Some (Box.box 42)
+
+            when maybe is
+                Some b -> Box.unbox b
+                None -> 0
+            "
+        ),
+        42,
+        u64
+    )
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn nullable_box_none() {
+    assert_evals_to!(
+        indoc!(
+            r"
+            maybe : [Some (Box U64), None]
+            maybe = None
+
+            when maybe is
+                Some b -> Box.unbox b
+                None -> 1
+            "
+        ),
+        1,
+        u64
+    )
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn nullable_box_is_the_box() {
+    // `[Some (Box a), None]` is stored as the box pointer, without a tag id
+    assert_evals_to!(
+        indoc!(
+            r"
+            maybe : [Some (Box U64), None]
+            maybe = Some (Box.box 42)
+
+            maybe
+            "
+        ),
+        RocBox::new(42),
+        RocBox<u64>
+    )
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn nullable_box_through_function() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Maybe : [Some (Box Str), None]
+
+            append : Maybe, Str -> Maybe
+            append = \maybe, suffix ->
+                when maybe is
+                    Some b -> Some (Box.box (Str.concat (Box.unbox b) suffix))
+                    None -> None
+
+            unwrap : Maybe -> Str
+            unwrap = \maybe ->
+                when maybe is
+                    Some b -> Box.unbox b
+                    None -> "none"
+
+            main : Str
+            main =
+                a = append (Some (Box.box "a long enough string ")) "to be heap-allocated"
+                b = append None "!"
+
+                Str.concat (unwrap a) (unwrap b)
+            "#
+        ),
+        RocStr::from("a long enough string to be heap-allocatednone"),
+        RocStr
+    )
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn weak_ref_get_while_boxed() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            boxed = Box.box (Str.concat "a long enough string " "to be heap-allocated")
+            weak = WeakRef.downgrade boxed
+
+            when WeakRef.get weak is
+                Some s -> Str.concat s (Box.unbox boxed)
+                None -> "freed"
+            "#
+        ),
+        RocStr::from(
+            "a long enough string to be heap-allocateda long enough string to be heap-allocated"
+        ),
+        RocStr
+    )
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn weak_ref_get_after_free() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            observe : Str -> WeakRef Str
+            observe = \s -> WeakRef.downgrade (Box.box s)
+
+            main : Str
+            main =
+                when WeakRef.get (observe (Str.concat "a long enough string " "to be heap-allocated")) is
+                    Some s -> s
+                    None -> "freed"
+            "#
+        ),
+        RocStr::from("freed"),
+        RocStr
+    )
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn weak_ref_to_boxed_number() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            observe : U64 -> WeakRef U64
+            observe = \n -> WeakRef.downgrade (Box.box n)
+
+            main : U64
+            main =
+                boxed = Box.box 42
+                alive = WeakRef.get (WeakRef.downgrade boxed)
+                freed = WeakRef.get (observe 7)
+
+                when (alive, freed) is
+                    (Some n, None) -> n + Box.unbox boxed
+                    _ -> 0
+            "#
+        ),
+        84,
+        u64
+    )
+}
+
+#[test]
+#[cfg(feature = "gen-wasm")]
+#[should_panic(
+    expected = r#"Roc failed with message: "WeakRef is only supported on 64-bit targets"#
+)]
+fn weak_ref_is_not_supported_by_wasm() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            boxed = Box.box 42u64
+
+            when WeakRef.get (WeakRef.downgrade boxed) is
+                Some n -> n
+                None -> 0
+            "#
+        ),
+        42,
+        u64
+    )
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn weak_ref_crashes_on_32_bit_targets() {
+    let ir = crate::helpers::llvm::llvm_ir_for_target(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            main : U64
+            main =
+                boxed = Box.box 42u64
+
+                when WeakRef.get (WeakRef.downgrade boxed) is
+                    Some n -> n
+                    None -> 0
+            "#
+        ),
+        roc_target::Target::LinuxX32,
+    );
+
+    // mono compiles the weak reference to a crash, rather than the backend panicking on it
+    assert!(
+        ir.contains("WeakRef is only supported on 64-bit targets"),
+        "{ir}"
+    );
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn box_num() {
@@ -4650,3 +4860,695 @@ fn issue_6139_prefixes() {
         RocList<RocStr>
     );
 }
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn mutually_tail_recursive_list_sum() {
+    // the calls in tail position must not grow the stack, or this overflows
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [ main ] to "./platform"
+
+            sum_help = \list, index, acc ->
+                when List.get list index is
+                    Ok x -> sum_step list (index + 1) (acc + x)
+                    Err _ -> acc
+
+            sum_step = \list, index, acc ->
+                sum_help list index acc
+
+            main = sum_help (List.repeat 1u64 1_000_000) 0 0
+            "#
+        ),
+        1_000_000,
+        u64
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn dbg_logs_with_sol_log_on_sbf() {
+    // there is no stderr in the Solana runtime, so dbg must go to the program log
+    let ir = crate::helpers::llvm::llvm_ir_for_target(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            main =
+                x = 42u64
+
+                dbg x
+
+                x
+            "#
+        ),
+        roc_target::Target::Sbf,
+    );
+
+    assert!(ir.contains("declare void @sol_log_("), "{ir}");
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn small_record_copies_do_not_call_memcpy_on_sbf() {
+    let ir = crate::helpers::llvm::llvm_ir_for_target(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Small : { a : U64 }
+            Big : { a : U64, b : U64, c : U64 }
+
+            pick_small : Bool, Small, Small -> Small
+            pick_small = \condition, yes, no -> if condition then yes else no
+
+            pick_big : Bool, Big, Big -> Big
+            pick_big = \condition, yes, no -> if condition then yes else no
+
+            main =
+                small = pick_small Bool.true { a: 1 } { a: 2 }
+                big = pick_big Bool.false { a: 1, b: 2, c: 3 } { a: 4, b: 5, c: 6 }
+
+                small.a + big.c
+            "#
+        ),
+        roc_target::Target::Sbf,
+    );
+
+    for name in ["_pick_small_", "_pick_big_"] {
+        let body: Vec<_> = ir
+            .lines()
+            .skip_while(|line| !(line.starts_with("define") && line.contains(name)))
+            .take_while(|line| *line != "}")
+            .collect();
+
+        assert!(!body.is_empty(), "no definition of {name} in:\n{ir}");
+
+        let copies: Vec<_> = body.iter().filter(|line| line.contains("memcpy")).collect();
+
+        assert!(copies.is_empty(), "{name} still calls memcpy: {copies:#?}");
+    }
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn str_list_and_dec_builtins_on_sbf() {
+    let ir = crate::helpers::llvm::llvm_ir_for_target(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            main =
+                prices : List Dec
+                prices = [1.5, 2.25]
+
+                List.map prices (\price -> price * 3)
+                |> List.map Num.to_str
+                |> List.walk "" Str.concat
+            "#
+        ),
+        roc_target::Target::Sbf,
+    );
+
+    // every builtin must come from the SBF bitcode, rather than be left for the linker
+    let undefined_builtins: Vec<_> = ir
+        .lines()
+        .filter(|line| line.starts_with("declare") && line.contains("@roc_builtins."))
+        .collect();
+
+    assert!(undefined_builtins.is_empty(), "{undefined_builtins:#?}");
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn host_functions_use_win64_calling_convention() {
+    let ir = crate::helpers::llvm::llvm_ir_for_target(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            main = 42u64
+            "#
+        ),
+        roc_target::Target::WinX64,
+    );
+
+    assert!(ir.contains("define win64cc"), "{ir}");
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn identical_newtype_functions_are_emitted_once() {
+    let ir = crate::helpers::llvm::llvm_ir_for_target(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Meters := U64
+            Feet := U64
+
+            double_meters : Meters -> U64
+            double_meters = \@Meters meters -> Num.mul_wrap meters 2
+
+            double_feet : Feet -> U64
+            double_feet = \@Feet feet -> Num.mul_wrap feet 2
+
+            main = double_meters (@Meters 3) + double_feet (@Feet 4)
+            "#
+        ),
+        roc_target::Target::default(),
+    );
+
+    let doubles: Vec<_> = ir
+        .lines()
+        .filter(|line| line.starts_with("define") && line.contains("_double_"))
+        .collect();
+
+    assert_eq!(doubles.len(), 1, "{ir}");
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn sequential_stack_records_do_not_overlap() {
+    let ir = crate::helpers::llvm::llvm_ir_for_target(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Big : { a : U64, b : U64, c : U64, d : U64, e : U64, f : U64, g : U64, h : U64 }
+
+            sum_ends : Big -> U64
+            sum_ends = \big -> big.a + big.h
+
+            both_sums : {} -> U64
+            both_sums = \{} ->
+                first = sum_ends { a: 1, b: 2, c: 3, d: 4, e: 5, f: 6, g: 7, h: 8 }
+                second = sum_ends { a: 8, b: 7, c: 6, d: 5, e: 4, f: 3, g: 2, h: 1 }
+
+                first + second
+
+            main = both_sums {}
+            "#
+        ),
+        roc_target::Target::default(),
+    );
+
+    let body: Vec<_> = ir
+        .lines()
+        .skip_while(|line| !(line.starts_with("define") && line.contains("_both_sums_")))
+        .take_while(|line| *line != "}")
+        .collect();
+
+    let markers: Vec<_> = body
+        .iter()
+        .filter_map(|line| {
+            if line.contains("@llvm.lifetime.start") {
+                Some("start")
+            } else if line.contains("@llvm.lifetime.end") {
+                Some("end")
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    // the first record is dead before the second one is built, so they can share a stack slot
+    assert_eq!(markers, ["start", "end", "start", "end"], "{body:#?}");
+    assert!(
+        body.iter()
+            .any(|line| line.contains("@llvm.lifetime.start.p0(i64 64")),
+        "{body:#?}"
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn single_owner_tag_is_freed_without_refcount_check() {
+    let ir = crate::helpers::llvm::llvm_ir_for_target(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Tree : [Leaf I64, Node Tree Tree]
+
+            main =
+                tree : Tree
+                tree = Leaf 42
+
+                when tree is
+                    Leaf _ -> 1i64
+                    Node _ _ -> 0
+            "#
+        ),
+        roc_target::Target::default(),
+    );
+
+    // the tree is freed directly, rather than through a decrement helper
+    let decrement_helpers: Vec<_> = ir
+        .lines()
+        .filter(|line| line.starts_with("define") && line.contains("#dec"))
+        .collect();
+
+    assert!(decrement_helpers.is_empty(), "{ir}");
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn single_owner_tag_is_freed_with_one_call() {
+    let ir = crate::helpers::llvm::llvm_ir_for_target(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Tree : [Leaf I64, Node Tree Tree]
+
+            leaf_value : I64 -> I64
+            leaf_value = \x ->
+                tree : Tree
+                tree = Leaf x
+
+                when tree is
+                    Leaf n -> n
+                    Node _ _ -> 0
+
+            main = leaf_value 42
+            "#
+        ),
+        roc_target::Target::default(),
+    );
+
+    let body: Vec<_> = ir
+        .lines()
+        .skip_while(|line| !(line.starts_with("define") && line.contains("_leaf_value_")))
+        .take_while(|line| *line != "}")
+        .collect();
+
+    assert!(!body.is_empty(), "{ir}");
+
+    // the refcount is never loaded or compared: the allocation goes straight back to the allocator
+    let calls_to = |name: &str| body.iter().filter(|line| line.contains(name)).count();
+
+    assert_eq!(calls_to("@roc_builtins.utils.free_rc_ptr"), 1, "{body:#?}");
+    assert_eq!(calls_to("#dec"), 0, "{body:#?}");
+    assert_eq!(calls_to("@roc_builtins.utils.decref"), 0, "{body:#?}");
+}
+
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+const IMMEDIATE_LEAF: &str = indoc!(
+    r#"
+    app "test" provides [main] to "./platform"
+
+    Tree : [Leaf I32, Node Tree Tree]
+
+    leaf_value : I32 -> I32
+    leaf_value = \x ->
+        tree : Tree
+        tree = Leaf x
+
+        when tree is
+            Leaf n -> n
+            Node _ _ -> 0
+
+    main = leaf_value 42
+    "#
+);
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+fn immediate_tag_is_dropped_without_free() {
+    assert_evals_to!(IMMEDIATE_LEAF, 42, i32);
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn immediate_tag_is_not_a_single_owner() {
+    let ir =
+        crate::helpers::llvm::llvm_ir_for_target(IMMEDIATE_LEAF, roc_target::Target::default());
+
+    let body: Vec<_> = ir
+        .lines()
+        .skip_while(|line| !(line.starts_with("define") && line.contains("_leaf_value_")))
+        .take_while(|line| *line != "}")
+        .collect();
+
+    assert!(!body.is_empty(), "{ir}");
+
+    // `Leaf x` is the integer itself, there is no allocation to give back
+    let calls_to = |name: &str| body.iter().filter(|line| line.contains(name)).count();
+
+    assert_eq!(calls_to("@roc_builtins.utils.free_rc_ptr"), 0, "{body:#?}");
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn always_inline_builtin_is_inlined_at_every_call_site() {
+    let ir = crate::helpers::llvm::llvm_ir_for_target(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            count_ok : Result I64 Str, Result I64 Str -> I64
+            count_ok = \first, second ->
+                a = if Result.is_ok first then 1 else 0
+                b = if Result.is_ok second then 1 else 0
+
+                a + b
+
+            main = count_ok (Ok 1) (Err "nope")
+            "#
+        ),
+        roc_target::Target::default(),
+    );
+
+    // once it is inlined everywhere, nothing refers to `Result.is_ok` anymore
+    let uses: Vec<_> = ir
+        .lines()
+        .filter(|line| line.contains("@Result_is_ok_"))
+        .collect();
+
+    assert!(uses.is_empty(), "{ir}");
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn local_list_is_built_on_the_stack() {
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        sum_local : U64 -> U64
+        sum_local = \x ->
+            list = [x, x + 1, x + 2]
+
+            List.len list + x
+
+        main = sum_local 1
+        "#
+    );
+
+    let body = |ir: &str| -> String {
+        ir.lines()
+            .skip_while(|line| !(line.starts_with("define") && line.contains("_sum_local_")))
+            .take_while(|line| *line != "}")
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let heap_ir = crate::helpers::llvm::llvm_ir_for_target(src, roc_target::Target::default());
+    assert!(
+        body(&heap_ir).contains("allocate_with_refcount"),
+        "{heap_ir}"
+    );
+
+    let stack_ir = crate::helpers::llvm::llvm_ir_with_options(
+        src,
+        roc_target::Target::default(),
+        crate::helpers::llvm::BackendOptions {
+            optimize_stack_alloc: true,
+            ..Default::default()
+        },
+    );
+    let stack_body = body(&stack_ir);
+
+    assert!(stack_body.contains("%stack_list = alloca"), "{stack_ir}");
+    assert!(
+        !stack_body.contains("allocate_with_refcount"),
+        "{stack_body}"
+    );
+    assert!(!stack_body.contains("decref"), "{stack_body}");
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn guarded_list_get_has_no_bounds_check() {
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        sum_from : List I64, U64, I64 -> I64
+        sum_from = \list, i, acc ->
+            if i < List.len list then
+                when List.get list i is
+                    Ok x -> sum_from list (i + 1) (acc + x)
+                    Err _ -> acc
+            else
+                acc
+
+        main = sum_from [1, 2, 3] 0 0
+        "#
+    );
+
+    let ir = crate::helpers::llvm::llvm_ir_for_target(src, roc_target::Target::default());
+
+    let body = ir
+        .lines()
+        .skip_while(|line| !(line.starts_with("define") && line.contains("_sum_from_")))
+        .take_while(|line| *line != "}")
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // `List.get` compares the index with the length itself, which the guard already did
+    assert!(!body.is_empty(), "{ir}");
+    assert!(!body.contains("@List_get_"), "{body}");
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn rebuild_linked_list_in_loop() {
+    // every round rebuilds all 1000 cells; with reuse, they take the place of the old ones
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            LinkedList : [Nil, Cons I64 LinkedList]
+
+            build : I64, LinkedList -> LinkedList
+            build = \n, acc ->
+                if n == 0 then acc else build (n - 1) (Cons n acc)
+
+            increment : LinkedList -> LinkedList
+            increment = \list ->
+                when list is
+                    Nil -> Nil
+                    Cons x rest -> Cons (x + 1) (increment rest)
+
+            sum : LinkedList, I64 -> I64
+            sum = \list, acc ->
+                when list is
+                    Nil -> acc
+                    Cons x rest -> sum rest (acc + x)
+
+            repeat : LinkedList, U64 -> LinkedList
+            repeat = \list, times ->
+                if times == 0 then list else repeat (increment list) (times - 1)
+
+            main = sum (repeat (build 1000 Nil) 100) 0
+            "#
+        ),
+        600500,
+        i64
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn tree_update_reuses_cells_on_sbf() {
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        Tree : [Leaf, Node Tree I64 Tree]
+
+        increment : Tree -> Tree
+        increment = \tree ->
+            when tree is
+                Leaf -> Leaf
+                Node left value right -> Node (increment left) (value + 1) (increment right)
+
+        total : Tree -> I64
+        total = \tree ->
+            when tree is
+                Leaf -> 0
+                Node left value right -> total left + value + total right
+
+        main = total (increment (Node (Node Leaf 1 Leaf) 2 Leaf))
+        "#
+    );
+
+    let ir = crate::helpers::llvm::llvm_ir_for_target(src, roc_target::Target::Sbf);
+
+    let body = ir
+        .lines()
+        .skip_while(|line| !(line.starts_with("define") && line.contains("_increment_")))
+        .take_while(|line| *line != "}")
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // a unique node is overwritten in place, only a shared one gets a fresh allocation
+    assert!(body.contains("else_reuse"), "{ir}");
+    assert!(body.contains("then_allocate_fresh"), "{body}");
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn compute_budget_names_the_most_expensive_function_on_sbf() {
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        expensive : U128 -> U128
+        expensive = \x ->
+            a = x * x + 1
+            b = a * a + x
+            c = b * b + a
+            d = c * c + b
+
+            d * d + c
+
+        main = expensive 3
+        "#
+    );
+
+    let estimate = crate::helpers::llvm::compute_estimate_for_target(src, roc_target::Target::Sbf);
+
+    // a 128-bit multiplication alone is a call into compiler-rt
+    let budget = 200;
+    assert!(estimate.total > budget, "{estimate:?}");
+
+    let most_expensive = &estimate.functions[0];
+    assert!(most_expensive.name.contains("_expensive_"), "{estimate:?}");
+    assert!(most_expensive.cost > budget, "{estimate:?}");
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn deep_call_path_is_over_the_stack_limit_on_sbf() {
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        Quad a : { a : a, b : a, c : a, d : a }
+
+        quad : a -> Quad a
+        quad = \x -> { a: x, b: x, c: x, d: x }
+
+        # 512 bytes, which is passed by reference
+        big : U64 -> Quad (Quad (Quad U64))
+        big = \n -> quad (quad (quad n))
+
+        first : Quad (Quad (Quad U64)) -> U64
+        first = \q -> q.a.b.c
+
+        level1 : U64 -> U64
+        level1 = \n -> first (big n)
+
+        level2 : U64 -> U64
+        level2 = \n -> first (big n) + level1 (n + 1)
+
+        level3 : U64 -> U64
+        level3 = \n -> first (big n) + level2 (n + 1)
+
+        level4 : U64 -> U64
+        level4 = \n -> first (big n) + level3 (n + 1)
+
+        level5 : U64 -> U64
+        level5 = \n -> first (big n) + level4 (n + 1)
+
+        level6 : U64 -> U64
+        level6 = \n -> first (big n) + level5 (n + 1)
+
+        level7 : U64 -> U64
+        level7 = \n -> first (big n) + level6 (n + 1)
+
+        level8 : U64 -> U64
+        level8 = \n -> first (big n) + level7 (n + 1)
+
+        level9 : U64 -> U64
+        level9 = \n -> first (big n) + level8 (n + 1)
+
+        level10 : U64 -> U64
+        level10 = \n -> first (big n) + level9 (n + 1)
+
+        main = level10 1
+        "#
+    );
+
+    let target = roc_target::Target::Sbf;
+    let path = crate::helpers::llvm::deepest_call_path_for_target(src, target);
+
+    let limit = target.max_stack_depth().unwrap() as u64;
+    assert!(path.depth > limit, "{path:?}");
+
+    // every level keeps a `big` value in its frame, and calls the level below it
+    let levels: Vec<_> = path
+        .frames
+        .iter()
+        .filter_map(|(name, _)| (1..=10).find(|level| name.contains(&format!("_level{level}_"))))
+        .collect();
+
+    assert_eq!(levels, (1..=10).rev().collect::<Vec<_>>(), "{path:?}");
+    assert_eq!(
+        path.depth,
+        path.frames.iter().map(|(_, size)| size).sum::<u64>()
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn unused_functions_are_not_compiled() {
+    let mut src = String::from("app \"test\" provides [main] to \"./platform\"\n\n");
+
+    for index in 0..100 {
+        src.push_str(&format!(
+            "f{index} : U64 -> U64\nf{index} = \\n -> n * {index} + 1\n\n"
+        ));
+    }
+
+    src.push_str("main = f0 1 + f1 2 + f2 3 + f3 4 + f4 5\n");
+
+    let ir = crate::helpers::llvm::llvm_ir_for_target(&src, roc_target::Target::default());
+
+    let defined: Vec<_> = ir
+        .lines()
+        .filter(|line| {
+            line.starts_with("define")
+                && (0..100).any(|index| line.contains(&format!("_f{index}_")))
+        })
+        .collect();
+
+    assert!(defined.len() <= 10, "{defined:#?}");
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn arithmetic_on_literals_is_folded() {
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        main =
+            x = Num.add 3 4
+            x
+        "#
+    );
+
+    let ir = crate::helpers::llvm::llvm_ir_for_target(src, roc_target::Target::default());
+
+    let body = ir
+        .lines()
+        .skip_while(|line| {
+            !(line.starts_with("define") && line.contains("_main_") && !line.contains("roc__"))
+        })
+        .take_while(|line| *line != "}")
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    assert!(!body.is_empty(), "{ir}");
+    assert!(body.contains("ret i64 7"), "{body}");
+
+    // the wrapper of the add, which checks for overflow, is never called, so it is not compiled
+    assert!(!ir.contains("@Num_add_"), "{ir}");
+    assert!(!body.contains("sadd"), "{body}");
+}
@@ -308,6 +308,20 @@ fn f64_record2_literal() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn f32_record2_literal() {
+    assert_evals_to!(
+        indoc!(
+            r"
+                   { x: 3.5f32, y: 5.25f32 }
+                "
+        ),
+        (3.5, 5.25),
+        (f32, f32)
+    );
+}
+
 // #[test]
 // #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
 // fn f64_record3_literal() {
@@ -1182,3 +1196,196 @@ fn pass_record_of_u8s() {
         bool
     )
 }
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn pass_zeroed_record_by_pointer() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            zeroes : { a : U64, b : U64, c : U64, d : U64, e : U64 }
+            zeroes = { a: 0, b: 0, c: 0, d: 0, e: 0 }
+
+            id = \rec -> rec
+
+            main =
+                id zeroes
+            "#
+        ),
+        (0u64, 0u64, 0u64, 0u64, 0u64),
+        (u64, u64, u64, u64, u64)
+    )
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn round_trip_kilobyte_record() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Quarter : { a : U64, b : U64, c : U64, d : U64, e : U64, f : U64, g : U64, h : U64 }
+            Half : { w : Quarter, x : Quarter, y : Quarter, z : Quarter }
+            Whole : { p : Half, q : Half, r : Half, s : Half }
+
+            make_quarter : U64 -> Quarter
+            make_quarter = \n -> { a: n, b: n, c: n, d: n, e: n, f: n, g: n, h: n }
+
+            make_half : U64 -> Half
+            make_half = \n ->
+                { w: make_quarter n, x: make_quarter (n + 1), y: make_quarter (n + 2), z: make_quarter (n + 3) }
+
+            make_whole : U64 -> Whole
+            make_whole = \n ->
+                { p: make_half n, q: make_half (n + 4), r: make_half (n + 8), s: make_half (n + 12) }
+
+            swap : Whole -> Whole
+            swap = \whole -> { whole & p: whole.s, s: whole.p }
+
+            main =
+                whole = swap (make_whole 0)
+
+                (whole.p.w.a, whole.q.x.b, whole.s.z.h)
+            "#
+        ),
+        (12u64, 5u64, 3u64),
+        (u64, u64, u64)
+    )
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn records_over_two_words_are_returned_through_a_pointer_on_sbf() {
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        make_pair : U64 -> { a : U64, b : U64 }
+        make_pair = \n -> { a: n, b: n + 1 }
+
+        make_triple : U64 -> { a : U64, b : U64, c : U64 }
+        make_triple = \n -> { a: n, b: n + 1, c: n + 2 }
+
+        main = (make_pair 1).b + (make_triple 2).c
+        "#
+    );
+
+    let returns_void = |target, name| {
+        let ir = crate::helpers::llvm::llvm_ir_for_target(src, target);
+        let define = ir
+            .lines()
+            .find(|line| line.starts_with("define") && line.contains(name))
+            .unwrap_or_else(|| panic!("no definition of {name} in:\n{ir}"));
+
+        // a value returned through a pointer is written to the last argument instead
+        define.contains(" void @")
+    };
+
+    assert!(!returns_void(roc_target::Target::Sbf, "_make_pair_"));
+    assert!(returns_void(roc_target::Target::Sbf, "_make_triple_"));
+
+    // other targets return up to four words by value
+    assert!(!returns_void(roc_target::Target::LinuxX64, "_make_triple_"));
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn return_sixteen_byte_aligned_record() {
+    use roc_std::U128;
+
+    // a record of this size and alignment is copied with 128-bit vector moves on x86-64 and
+    // Aarch64, so every byte must end up where a plain memcpy would have put it
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Quad : { a : U128, b : U128, c : U128, d : U128 }
+
+            rotate : Quad -> Quad
+            rotate = \{ a, b, c, d } -> { a: b, b: c, c: d, d: a }
+
+            main =
+                quad = rotate {
+                    a: 0x0102030405060708090a0b0c0d0e0f10,
+                    b: 0x1112131415161718191a1b1c1d1e1f20,
+                    c: 0x2122232425262728292a2b2c2d2e2f30,
+                    d: 0x3132333435363738393a3b3c3d3e3f40,
+                }
+
+                (quad.a, quad.b, quad.c, quad.d)
+            "#
+        ),
+        (
+            U128::from(0x1112131415161718191a1b1c1d1e1f20),
+            U128::from(0x2122232425262728292a2b2c2d2e2f30),
+            U128::from(0x3132333435363738393a3b3c3d3e3f40),
+            U128::from(0x0102030405060708090a0b0c0d0e0f10)
+        ),
+        (U128, U128, U128, U128)
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn return_sixteen_byte_aligned_record_with_padding() {
+    use roc_std::RocDec;
+
+    // 48 bytes: two Decs and a byte, padded to a multiple of the 16-byte alignment
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Prices : { low : Dec, high : Dec, count : U8 }
+
+            widen : Prices -> Prices
+            widen = \{ low, high, count } -> { low: low - 1, high: high + 1, count: count + 1 }
+
+            main =
+                prices = widen { low: 1.5, high: 2.25, count: 7 }
+
+                (prices.low, prices.high, prices.count)
+            "#
+        ),
+        (
+            RocDec::from_str("0.5").unwrap(),
+            RocDec::from_str("3.25").unwrap(),
+            8
+        ),
+        (RocDec, RocDec, u8)
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn repeated_field_access_is_loaded_once() {
+    let src = indoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        Big : { a : I64, b : I64, c : I64, d : I64, e : I64, f : I64, g : I64, h : I64 }
+
+        sum_a : Big -> I64
+        sum_a = \r -> r.a + r.a + r.a + r.a + r.a
+
+        main = sum_a { a: 1, b: 2, c: 3, d: 4, e: 5, f: 6, g: 7, h: 8 }
+        "#
+    );
+
+    let ir = crate::helpers::llvm::llvm_ir_for_target(src, roc_target::Target::default());
+
+    let body = ir
+        .lines()
+        .skip_while(|line| !(line.starts_with("define") && line.contains("_sum_a_")))
+        .take_while(|line| *line != "}")
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // the record is too big to be passed by value, so reading one of its fields is a load
+    assert!(!body.is_empty(), "{ir}");
+    assert_eq!(body.matches(" = load ").count(), 1, "{body}");
+}
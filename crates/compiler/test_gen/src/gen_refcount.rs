@@ -522,6 +522,61 @@ fn union_recursive_dec() {
     );
 }
 
+#[test]
+#[cfg(feature = "gen-wasm")]
+fn union_recursive_single_owner_free() {
+    assert_refcounts!(
+        indoc!(
+            r#"
+                Tree : [Leaf I64, Node Tree Tree]
+
+                t : Tree
+                t = Leaf 42
+
+                when t is
+                    Leaf n -> n
+                    Node _ _ -> 0
+            "#
+        ),
+        i64,
+        &[
+            (StandardRC, Deallocated) // t
+        ]
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-wasm")]
+fn list_map_fresh_list_with_single_owner_tag() {
+    // lists are left to their decrement, next to a tag that is freed directly
+    assert_refcounts!(
+        indoc!(
+            r#"
+                Tree : [Leaf I64, Node Tree Tree]
+
+                t : Tree
+                t = Leaf 2
+
+                factor =
+                    when t is
+                        Leaf n -> n
+                        Node _ _ -> 0
+
+                list = [0x111, 0x222, 0x333]
+
+                List.map list (\n -> n * factor)
+                |> List.len
+            "#
+        ),
+        u64,
+        &[
+            (StandardRC, Deallocated), // t
+            (StandardRC, Deallocated), // list
+            (StandardRC, Deallocated), // result
+        ]
+    );
+}
+
 #[test]
 #[cfg(feature = "gen-wasm")]
 fn refcount_different_rosetrees_inc() {
@@ -746,7 +801,8 @@ fn boxed_str_dec() {
                     DeallocateEverything
             "#
         ),
-        (i32, i32),
+        // the union is stored as the box pointer
+        Pointer,
         &[
             (StandardRC, Deallocated), // s
             (StandardRC, Deallocated), // b
@@ -754,6 +810,53 @@ fn boxed_str_dec() {
     );
 }
 
+#[test]
+#[cfg(feature = "gen-wasm")]
+fn nullable_box_shares_the_box() {
+    assert_refcounts!(
+        indoc!(
+            r#"
+                s = Str.concat "A long enough string " "to be heap-allocated"
+                b = Box.box s
+
+                maybe : [Some (Box Str), None]
+                maybe = Some b
+
+                Tuple maybe b
+            "#
+        ),
+        (Pointer, Pointer),
+        &[
+            (StandardRC, Live(1)), // s
+            (StandardRC, Live(2)), // b, which is also the value of maybe
+        ]
+    );
+}
+
+#[test]
+#[cfg(feature = "gen-wasm")]
+fn nullable_box_dec() {
+    assert_refcounts!(
+        indoc!(
+            r#"
+                s = Str.concat "A long enough string " "to be heap-allocated"
+
+                maybe : [Some (Box Str), None]
+                maybe = Some (Box.box s)
+
+                when maybe is
+                    Some b -> Str.count_utf8_bytes (Box.unbox b)
+                    None -> 0
+            "#
+        ),
+        u64,
+        &[
+            (StandardRC, Deallocated), // s
+            (StandardRC, Deallocated), // the box
+        ]
+    );
+}
+
 #[test]
 #[cfg(feature = "gen-wasm")]
 fn non_nullable_unwrapped_alignment_8() {
@@ -857,3 +960,57 @@ fn basic_cli_parser() {
         ]
     );
 }
+
+#[cfg(feature = "gen-llvm")]
+const HANDLE: &str = indoc::indoc!(
+    r#"
+    Handle := Box U64
+
+    open : U64 -> Handle
+    open = \descriptor -> @Handle (Box.box descriptor)
+
+    fd : Handle -> U64
+    fd = \@Handle box -> Box.unbox box
+    "#
+);
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn finalizer_runs_once_after_all_clones_are_dropped() {
+    let src = format!(
+        "app \"test\" provides [main] to \"./platform\"\n\n{HANDLE}\n{}",
+        indoc!(
+            r#"
+            main : U64
+            main =
+                a = open 1
+                b = open 2
+
+                List.len [a, b, a, b, a]
+            "#
+        )
+    );
+
+    crate::helpers::llvm::assert_evals_to_finalized!(&src, 5, u64, "Handle", 2);
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn finalizer_runs_once_for_clones_in_records() {
+    let src = format!(
+        "app \"test\" provides [main] to \"./platform\"\n\n{HANDLE}\n{}",
+        indoc!(
+            r#"
+            main : U64
+            main =
+                handle = open 3
+                first = { handle, name: "first" }
+                second = { handle, name: "second" }
+
+                fd first.handle + fd second.handle
+            "#
+        )
+    );
+
+    crate::helpers::llvm::assert_evals_to_finalized!(&src, 6, u64, "Handle", 1);
+}
@@ -1684,6 +1684,126 @@ fn str_to_u64() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn str_to_u64_with_prefix() {
+    assert_evals_to!(
+        r#"Str.to_u64 "0xFFFF_FFFF_FFFF_FFFF""#,
+        RocResult::ok(u64::MAX),
+        RocResult<u64, ()>
+    );
+    assert_evals_to!(
+        r#"Str.to_u64 "0xdead_BEEF""#,
+        RocResult::ok(0xdead_beef),
+        RocResult<u64, ()>
+    );
+    assert_evals_to!(
+        r#"Str.to_u64 "0o1777777777777777777777""#,
+        RocResult::ok(u64::MAX),
+        RocResult<u64, ()>
+    );
+    assert_evals_to!(
+        r#"Str.to_u64 "0b1010_1010""#,
+        RocResult::ok(0b1010_1010),
+        RocResult<u64, ()>
+    );
+    assert_evals_to!(
+        r#"Str.to_u64 "1_000_000""#,
+        RocResult::ok(1_000_000),
+        RocResult<u64, ()>
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn str_to_u64_with_prefix_invalid() {
+    // one past the maximum, in every base
+    assert_evals_to!(
+        r#"Str.to_u64 "0x1_0000_0000_0000_0000""#,
+        RocResult::err(()),
+        RocResult<u64, ()>
+    );
+    assert_evals_to!(
+        r#"Str.to_u64 "0o2000000000000000000000""#,
+        RocResult::err(()),
+        RocResult<u64, ()>
+    );
+    assert_evals_to!(
+        r#"Str.to_u64 "0b1_0000000000000000000000000000000000000000000000000000000000000000""#,
+        RocResult::err(()),
+        RocResult<u64, ()>
+    );
+    assert_evals_to!(
+        r#"Str.to_u64 "18_446_744_073_709_551_616""#,
+        RocResult::err(()),
+        RocResult<u64, ()>
+    );
+
+    // digits that are out of range for the base
+    assert_evals_to!(
+        r#"Str.to_u64 "0b102""#,
+        RocResult::err(()),
+        RocResult<u64, ()>
+    );
+    assert_evals_to!(
+        r#"Str.to_u64 "0o8""#,
+        RocResult::err(()),
+        RocResult<u64, ()>
+    );
+    assert_evals_to!(
+        r#"Str.to_u64 "0xG""#,
+        RocResult::err(()),
+        RocResult<u64, ()>
+    );
+
+    // a prefix without digits, and a negative unsigned number
+    assert_evals_to!(
+        r#"Str.to_u64 "0x""#,
+        RocResult::err(()),
+        RocResult<u64, ()>
+    );
+    assert_evals_to!(
+        r#"Str.to_u64 "-0x1""#,
+        RocResult::err(()),
+        RocResult<u64, ()>
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn str_to_i64_with_prefix() {
+    assert_evals_to!(
+        r#"Str.to_i64 "-0x8000_0000_0000_0000""#,
+        RocResult::ok(i64::MIN),
+        RocResult<i64, ()>
+    );
+    assert_evals_to!(
+        r#"Str.to_i64 "0x7FFF_FFFF_FFFF_FFFF""#,
+        RocResult::ok(i64::MAX),
+        RocResult<i64, ()>
+    );
+    assert_evals_to!(
+        r#"Str.to_i64 "-0o17""#,
+        RocResult::ok(-0o17),
+        RocResult<i64, ()>
+    );
+    assert_evals_to!(
+        r#"Str.to_i64 "-0b1""#,
+        RocResult::ok(-1),
+        RocResult<i64, ()>
+    );
+    assert_evals_to!(
+        r#"Str.to_i64 "-0x8000_0000_0000_0001""#,
+        RocResult::err(()),
+        RocResult<i64, ()>
+    );
+    assert_evals_to!(
+        r#"Str.to_i64 "0x8000_0000_0000_0000""#,
+        RocResult::err(()),
+        RocResult<i64, ()>
+    );
+}
+
 #[test]
 #[cfg(feature = "gen-llvm")]
 fn str_to_i32() {
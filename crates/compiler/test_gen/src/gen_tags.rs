@@ -2327,3 +2327,247 @@ fn recursive_tag_id_in_allocation_eq() {
         bool
     );
 }
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn tagged_pointer_construction() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Expr : [Add Expr Expr, Num I32]
+
+            eval : Expr -> I32
+            eval = \expr ->
+                when expr is
+                    Add a b -> eval a + eval b
+                    Num n -> n
+
+            main = eval (Add (Num 1) (Add (Num -2) (Num 40)))
+            "#
+        ),
+        39,
+        i32
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn tagged_pointer_pattern_match() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Expr : [Add Expr Expr, Neg Expr, Num U8]
+
+            describe : Expr -> Str
+            describe = \expr ->
+                when expr is
+                    Num 0 -> "zero"
+                    Num n -> Num.to_str n
+                    Neg (Num n) -> "-" |> Str.concat (Num.to_str n)
+                    Neg _ -> "-(...)"
+                    Add (Num a) (Num b) -> Num.to_str (a + b)
+                    Add _ _ -> "... + ..."
+
+            main =
+                [Num 0, Num 255, Neg (Num 3), Neg (Neg (Num 3)), Add (Num 1) (Num 2), Add (Neg (Num 1)) (Num 2)]
+                |> List.map describe
+                |> Str.join_with ", "
+            "#
+        ),
+        RocStr::from("zero, 255, -3, -(...), 3, ... + ..."),
+        RocStr
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn tagged_pointer_list_of_mixed_variants() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Expr : [Mul Expr Expr, Num I16]
+
+            eval : Expr -> I64
+            eval = \expr ->
+                when expr is
+                    Mul a b -> eval a * eval b
+                    Num n -> Num.to_i64 n
+
+            shared = Mul (Num 2) (Num 3)
+
+            main =
+                [Num 1, shared, Num -7, Mul shared (Num 10), Num 32767]
+                |> List.map eval
+                |> List.sum
+            "#
+        ),
+        1 + 6 - 7 + 60 + 32767,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn tagged_pointer_immediate_tags_are_refcounted() {
+    // the immediate leaf is shared, stored in a record, moved out of it, and rebuilt, so every
+    // refcount pass sees it next to allocated tags
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            Expr : [Add Expr Expr, Num I32]
+
+            eval : Expr -> I32
+            eval = \expr ->
+                when expr is
+                    Add a b -> eval a + eval b
+                    Num n -> n
+
+            double : Expr -> Expr
+            double = \expr ->
+                when expr is
+                    Add a b -> Add (double a) (double b)
+                    Num n -> Num (2 * n)
+
+            describe : { left : Expr, right : Expr } -> I32
+            describe = \sides -> eval (double sides.left) + eval (double sides.right)
+
+            main =
+                leaf = Num 5
+                tree = Add leaf (Add leaf (Num 1))
+
+                describe { left: leaf, right: tree } + eval leaf
+            "#
+        ),
+        10 + 22 + 5,
+        i32
+    );
+}
+
+#[cfg(feature = "gen-llvm")]
+fn assert_returned_without_memcpy(payload: &str, int_type: &str) {
+    // a non-recursive tag union is always returned by pointer, so `pick` copies its result
+    let src = indoc::formatdoc!(
+        r#"
+        app "test" provides [main] to "./platform"
+
+        pick : Bool -> [Left {payload}, Right {payload}]
+        pick = \b -> if b then Left 1 else Right 2
+
+        main =
+            when pick Bool.true is
+                Left x -> x
+                Right y -> y
+        "#
+    );
+
+    for target in [roc_target::Target::LinuxX64, roc_target::Target::Sbf] {
+        let ir = crate::helpers::llvm::llvm_ir_for_target(&src, target);
+
+        let pick = ir
+            .lines()
+            .skip_while(|line| !(line.starts_with("define") && line.contains("pick")))
+            .take_while(|line| *line != "}")
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        assert!(!pick.is_empty(), "{target:?}: pick is not defined in\n{ir}");
+        assert!(
+            pick.contains(&format!("load {int_type}")),
+            "{target:?}: expected a single {int_type} copy in\n{pick}"
+        );
+        assert!(
+            !pick.contains("memcpy"),
+            "{target:?}: unexpected memcpy in\n{pick}"
+        );
+    }
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn two_byte_union_is_copied_without_memcpy() {
+    assert_returned_without_memcpy("U8", "i16");
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn four_byte_union_is_copied_without_memcpy() {
+    assert_returned_without_memcpy("U16", "i32");
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn eight_byte_union_is_copied_without_memcpy() {
+    assert_returned_without_memcpy("U32", "i64");
+}
+
+#[cfg(feature = "gen-llvm")]
+const POOLED_TREE: &str = indoc::indoc!(
+    r#"
+    app "test" provides [main] to "./platform"
+
+    Tree : [Leaf, Node Tree I64 Tree]
+
+    insert : Tree, I64 -> Tree
+    insert = \tree, value ->
+        when tree is
+            Leaf -> Node Leaf value Leaf
+            Node left x right ->
+                if value < x then
+                    Node (insert left value) x right
+                else
+                    Node left x (insert right value)
+
+    sum : Tree -> I64
+    sum = \tree ->
+        when tree is
+            Leaf -> 0
+            Node left x right -> sum left + x + sum right
+
+    # inserts 1 to 100 in a shuffled order
+    build : Tree, I64 -> Tree
+    build = \tree, n ->
+        if n == 0 then
+            tree
+        else
+            build (insert tree ((n * 37) % 101)) (n - 1)
+
+    main = sum (build Leaf 100)
+    "#
+);
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn pool_alloc_tree() {
+    crate::helpers::llvm::assert_evals_to_pooled!(POOLED_TREE, 5050, i64);
+}
+
+#[test]
+#[cfg(feature = "gen-llvm")]
+fn pool_alloc_takes_tags_from_their_size_class() {
+    let ir = crate::helpers::llvm::llvm_ir_with_options(
+        POOLED_TREE,
+        roc_target::Target::default(),
+        crate::helpers::llvm::BackendOptions {
+            pool_alloc: true,
+            ..Default::default()
+        },
+    );
+
+    // a node is a refcount and three 8-byte fields
+    assert!(
+        ir.contains("@roc__pool_size_classes = internal constant [8 x i32] [i32 32, i32 0"),
+        "{ir}"
+    );
+    assert!(
+        ir.contains("@roc_builtins.utils.allocate_pooled_with_refcount(i64 24, i32 8, i32 0)"),
+        "{ir}"
+    );
+}
@@ -57,11 +57,17 @@ pub fn helper(
 
     let load_config = LoadConfig {
         target: roc_target::Target::LinuxX64,
-        render: roc_reporting::report::RenderTarget::ColorTerminal,
+        render: roc_reporting::report::RenderTarget::ColorTerminal(
+            roc_reporting::report::ColorChoice::Always,
+        ),
         palette: roc_reporting::report::DEFAULT_PALETTE,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
         function_kind: FunctionKind::LambdaSet,
+        expect_filter: None,
+        finalizers: Vec::new(),
+        types_cache_dir: None,
+        fold_constants: true,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -221,6 +227,9 @@ pub fn helper(
             builtins_host_tempfile.path().to_str().unwrap(),
         ],
         LinkType::Dylib,
+        false,
+        true,
+        None,
     )
     .expect("failed to link dynamic library");
 
@@ -5,7 +5,7 @@ use std::sync::OnceLock;
 use inkwell::module::Module;
 use libloading::Library;
 use roc_build::link::llvm_module_to_dylib;
-use roc_collections::all::MutSet;
+use roc_collections::all::{MutMap, MutSet};
 use roc_command_utils::zig;
 use roc_gen_llvm::llvm::externs::add_default_roc_externs;
 use roc_gen_llvm::{llvm::build::LlvmBackendMode, run_roc::RocCallResult};
@@ -15,7 +15,7 @@ use roc_load::{
 use roc_mono::ir::{CrashTag, OptLevel, SingleEntryPoint};
 use roc_packaging::cache::RocCacheDir;
 use roc_region::all::LineInfo;
-use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
+use roc_reporting::report::{ColorChoice, RenderTarget, DEFAULT_PALETTE};
 use roc_target::Target;
 
 #[cfg(feature = "gen-llvm-wasm")]
@@ -27,6 +27,11 @@ use roc_gen_wasm::wasm32_result::Wasm32Result;
 #[cfg(feature = "gen-llvm-wasm")]
 const TEST_WRAPPER_NAME: &str = "test_wrapper";
 
+/// The host function that runs before a value of [HelperConfig::finalized_type] is freed. It
+/// counts how often it is called in the global [FINALIZED_COUNT].
+const TEST_FINALIZER: &str = "roc_test_finalizer";
+const FINALIZED_COUNT: &str = "roc_test_finalized_count";
+
 #[allow(dead_code)]
 pub const OPT_LEVEL: OptLevel = if cfg!(debug_assertions) {
     OptLevel::Normal
@@ -72,10 +77,18 @@ fn create_llvm_module<'a>(
     let load_config = LoadConfig {
         target,
         function_kind,
-        render: RenderTarget::ColorTerminal,
+        render: RenderTarget::ColorTerminal(ColorChoice::Always),
         palette: DEFAULT_PALETTE,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
+        expect_filter: None,
+        finalizers: config
+            .finalized_type
+            .map(|type_name| (type_name.to_string(), TEST_FINALIZER.to_string()))
+            .into_iter()
+            .collect(),
+        types_cache_dir: None,
+        fold_constants: true,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -216,12 +229,33 @@ fn create_llvm_module<'a>(
         }
     }
 
+    let pool_size_classes = config.options.pool_alloc.then(|| {
+        &*arena.alloc(roc_mono::pool::SizeClasses::from_procedures(
+            &layout_interner,
+            procedures.values(),
+        ))
+    });
+
     // Compile and add all the Procs before adding main
     let env = roc_gen_llvm::llvm::build::Env {
         arena,
         builder: &builder,
         dibuilder: &dibuilder,
         compile_unit: &compile_unit,
+        debug_info: None,
+        panic_strategy: roc_gen_llvm::llvm::build::PanicStrategy::Host,
+        check_alignment: false,
+        inline_threshold: None,
+        optimize_stack_alloc: config.options.optimize_stack_alloc,
+        overflow_trap: false,
+        pgo: None,
+        lto: false,
+        sbf_stack_limit: None,
+        stack_guard: config.stack_guard,
+        data_sections: MutMap::default(),
+        cfi: config.options.cfi,
+        pool_size_classes,
+        valgrind: false,
         context,
         interns,
         module,
@@ -235,6 +269,10 @@ fn create_llvm_module<'a>(
     // platform to provide them.
     add_default_roc_externs(&env);
 
+    if config.finalized_type.is_some() {
+        add_test_finalizer(&env);
+    }
+
     let entry_point = match loaded.entry_point {
         EntryPoint::Executable {
             exposed_to_host,
@@ -276,6 +314,8 @@ fn create_llvm_module<'a>(
         ),
     };
 
+    roc_gen_llvm::llvm::dedup::merge_identical_functions(&env);
+
     env.dibuilder.finalize();
 
     let ll_file_path = std::env::temp_dir().join("test.ll");
@@ -301,12 +341,63 @@ fn create_llvm_module<'a>(
     (main_fn_name, delayed_errors, env.module)
 }
 
+/// Define [TEST_FINALIZER], which increments [FINALIZED_COUNT]
+fn add_test_finalizer(env: &roc_gen_llvm::llvm::build::Env<'_, '_, '_>) {
+    use inkwell::module::Linkage;
+    use inkwell::AddressSpace;
+
+    let context = env.context;
+    let count_type = context.i64_type();
+
+    let count = env.module.add_global(count_type, None, FINALIZED_COUNT);
+    count.set_linkage(Linkage::External);
+    count.set_initializer(&count_type.const_zero());
+
+    let ptr_type = context.ptr_type(AddressSpace::default());
+    let fn_type = context.void_type().fn_type(&[ptr_type.into()], false);
+    let finalizer = env
+        .module
+        .add_function(TEST_FINALIZER, fn_type, Some(Linkage::External));
+
+    let builder = context.create_builder();
+    builder.position_at_end(context.append_basic_block(finalizer, "entry"));
+
+    let count_ptr = count.as_pointer_value();
+    let old_count = builder
+        .build_load(count_type, count_ptr, "old_count")
+        .unwrap()
+        .into_int_value();
+    let new_count = builder
+        .build_int_add(old_count, count_type.const_int(1, false), "new_count")
+        .unwrap();
+    builder.build_store(count_ptr, new_count).unwrap();
+    builder.build_return(None).unwrap();
+}
+
+/// The code generation flags of a test. A test sets the ones it checks, and takes the
+/// [Default] for the rest.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BackendOptions {
+    /// Build lists that never leave their procedure on the stack
+    pub optimize_stack_alloc: bool,
+    /// Check the callee of every call through a function pointer
+    pub cfi: bool,
+    /// Allocate the tags of recursive unions from pools
+    pub pool_alloc: bool,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct HelperConfig {
     pub mode: LlvmBackendMode,
     pub ignore_problems: bool,
     pub emit_debug_info: bool,
     pub opt_level: OptLevel,
+    pub options: BackendOptions,
+    /// An opaque type of the test whose values are given to [TEST_FINALIZER] before they are
+    /// freed
+    pub finalized_type: Option<&'static str>,
+    /// Check for stack overflow at the start of every procedure
+    pub stack_guard: bool,
 }
 
 #[allow(dead_code)]
@@ -336,6 +427,98 @@ pub fn helper<'a>(
     (main_fn_name, delayed_errors, lib)
 }
 
+/// Compile a program for `target` without running it, and return the resulting LLVM IR.
+#[allow(dead_code)]
+pub fn llvm_ir_for_target(src: &str, target: Target) -> String {
+    llvm_ir_with_options(src, target, BackendOptions::default())
+}
+
+/// Like [llvm_ir_for_target], with the given code generation flags. With `cfi`, closures are
+/// erased, so they are called through function pointers.
+#[allow(dead_code)]
+pub fn llvm_ir_with_options(src: &str, target: Target, options: BackendOptions) -> String {
+    let arena = bumpalo::Bump::new();
+    let context = inkwell::context::Context::create();
+
+    let config = HelperConfig {
+        mode: LlvmBackendMode::GenTest,
+        ignore_problems: false,
+        emit_debug_info: false,
+        opt_level: OptLevel::Development,
+        options,
+        finalized_type: None,
+        stack_guard: false,
+    };
+
+    let function_kind = if options.cfi {
+        FunctionKind::Erased
+    } else {
+        FunctionKind::LambdaSet
+    };
+
+    let (_main_fn_name, _delayed_errors, module) =
+        create_llvm_module(&arena, src, config, &context, target, function_kind);
+
+    module.print_to_string().to_string()
+}
+
+/// Compile a program for `target` without running it, and estimate the compute units of its
+/// main function.
+#[allow(dead_code)]
+pub fn compute_estimate_for_target(
+    src: &str,
+    target: Target,
+) -> roc_gen_llvm::llvm::compute_budget::ComputeEstimate {
+    with_main_function(src, target, |module, main_fn| {
+        roc_gen_llvm::llvm::compute_budget::estimate_compute_units(module, &[main_fn])
+    })
+}
+
+/// Compile a program for `target` without running it, and find the deepest call path from its
+/// main function.
+#[allow(dead_code)]
+pub fn deepest_call_path_for_target(
+    src: &str,
+    target: Target,
+) -> roc_gen_llvm::llvm::stack_depth::CallPath {
+    with_main_function(src, target, |module, main_fn| {
+        roc_gen_llvm::llvm::stack_depth::deepest_call_path(module, &[main_fn])
+    })
+}
+
+#[allow(dead_code)]
+fn with_main_function<T>(
+    src: &str,
+    target: Target,
+    f: impl for<'ctx> FnOnce(&Module<'ctx>, inkwell::values::FunctionValue<'ctx>) -> T,
+) -> T {
+    let arena = bumpalo::Bump::new();
+    let context = inkwell::context::Context::create();
+
+    let config = HelperConfig {
+        mode: LlvmBackendMode::GenTest,
+        ignore_problems: false,
+        emit_debug_info: false,
+        opt_level: OptLevel::Development,
+        options: BackendOptions::default(),
+        finalized_type: None,
+        stack_guard: false,
+    };
+
+    let (main_fn_name, _delayed_errors, module) = create_llvm_module(
+        &arena,
+        src,
+        config,
+        &context,
+        target,
+        FunctionKind::LambdaSet,
+    );
+
+    let main_fn = module.get_function(main_fn_name).unwrap();
+
+    f(module, main_fn)
+}
+
 #[allow(dead_code)]
 fn write_final_wasm() -> bool {
     #[allow(unused_imports)]
@@ -467,6 +650,9 @@ where
         emit_debug_info: false,
         ignore_problems,
         opt_level: OPT_LEVEL,
+        options: BackendOptions::default(),
+        finalized_type: None,
+        stack_guard: false,
     };
 
     let wasm_bytes = compile_to_wasm_bytes(&arena, config, src, &context, function_kind);
@@ -523,6 +709,8 @@ pub fn try_run_lib_function<T>(
     }
 }
 
+/// Run the test, and give back how often the finalizer of `finalized_type` was called, if there
+/// is one
 #[allow(dead_code)]
 // only used in tests
 #[allow(dead_code)]
@@ -531,8 +719,11 @@ pub(crate) fn llvm_evals_to<T, U, F>(
     expected: U,
     transform: F,
     ignore_problems: bool,
+    options: BackendOptions,
+    finalized_type: Option<&'static str>,
     function_kind: FunctionKind,
-) where
+) -> Option<u64>
+where
     U: PartialEq + std::fmt::Debug,
     F: FnOnce(T) -> U,
 {
@@ -547,6 +738,9 @@ pub(crate) fn llvm_evals_to<T, U, F>(
         emit_debug_info: false,
         ignore_problems,
         opt_level: crate::helpers::llvm::OPT_LEVEL,
+        options,
+        finalized_type,
+        stack_guard: false,
     };
 
     let (main_fn_name, errors, lib) =
@@ -572,6 +766,65 @@ pub(crate) fn llvm_evals_to<T, U, F>(
             CrashTag::User => panic!(r#"User crash with message: "{msg}""#),
         },
     }
+
+    finalized_type.map(|_| unsafe {
+        let count: libloading::Symbol<*const u64> = lib
+            .get(FINALIZED_COUNT.as_bytes())
+            .expect("the test finalizer is defined");
+
+        **count
+    })
+}
+
+/// Run the test with stack overflow checks, on a new thread with a stack of `stack_size` bytes.
+/// The thread sets the stack limit halfway down its stack, so the checks catch an overflow long
+/// before the thread runs out of stack.
+#[allow(dead_code)]
+pub(crate) fn llvm_evals_on_small_stack<T: Send>(
+    src: &str,
+    stack_size: usize,
+) -> Result<T, (String, CrashTag)> {
+    use roc_gen_llvm::llvm::stack_guard::STACK_LIMIT;
+
+    let arena = bumpalo::Bump::new();
+    let context = inkwell::context::Context::create();
+
+    let config = HelperConfig {
+        mode: LlvmBackendMode::GenTest,
+        emit_debug_info: false,
+        ignore_problems: false,
+        opt_level: OPT_LEVEL,
+        options: BackendOptions::default(),
+        finalized_type: None,
+        stack_guard: true,
+    };
+
+    let (main_fn_name, errors, lib) =
+        helper(&arena, config, src, &context, FunctionKind::LambdaSet);
+    assert!(errors.is_empty(), "Encountered errors:\n{errors}");
+
+    std::thread::scope(|scope| {
+        std::thread::Builder::new()
+            .stack_size(stack_size)
+            .spawn_scoped(scope, || {
+                // the stack grows down from about here
+                let marker = 0u8;
+                let stack_top = std::ptr::addr_of!(marker) as usize;
+
+                unsafe {
+                    let limit: libloading::Symbol<*mut usize> = lib
+                        .get(STACK_LIMIT.as_bytes())
+                        .expect("the stack limit is defined");
+
+                    **limit = stack_top - stack_size / 2;
+                }
+
+                try_run_lib_function::<T>(main_fn_name, &lib)
+            })
+            .expect("failed to spawn the test thread")
+            .join()
+            .unwrap()
+    })
 }
 
 #[allow(unused_macros)]
@@ -582,6 +835,8 @@ macro_rules! assert_llvm_evals_to {
             $expected,
             $transform,
             $ignore_problems,
+            $crate::helpers::llvm::BackendOptions::default(),
+            None,
             roc_load::FunctionKind::LambdaSet,
         );
     };
@@ -647,11 +902,54 @@ macro_rules! assert_evals_to_erased {
             $expected,
             $crate::helpers::llvm::identity,
             false,
+            $crate::helpers::llvm::BackendOptions::default(),
+            None,
             roc_load::FunctionKind::Erased,
         );
     }};
 }
 
+#[allow(unused_macros)]
+macro_rules! assert_evals_to_pooled {
+    ($src:expr, $expected:expr, $ty:ty) => {{
+        crate::helpers::llvm::llvm_evals_to::<$ty, _, _>(
+            $src,
+            $expected,
+            $crate::helpers::llvm::identity,
+            false,
+            $crate::helpers::llvm::BackendOptions {
+                pool_alloc: true,
+                ..Default::default()
+            },
+            None,
+            roc_load::FunctionKind::LambdaSet,
+        );
+    }};
+}
+
+/// Like [assert_evals_to], and check that the finalizer of the opaque type `$finalized_type` of
+/// the test was called `$finalized_count` times
+#[allow(unused_macros)]
+macro_rules! assert_evals_to_finalized {
+    ($src:expr, $expected:expr, $ty:ty, $finalized_type:expr, $finalized_count:expr) => {{
+        let finalized_count = crate::helpers::llvm::llvm_evals_to::<$ty, _, _>(
+            $src,
+            $expected,
+            $crate::helpers::llvm::identity,
+            false,
+            $crate::helpers::llvm::BackendOptions::default(),
+            Some($finalized_type),
+            roc_load::FunctionKind::LambdaSet,
+        );
+
+        assert_eq!(
+            finalized_count,
+            Some($finalized_count),
+            "the finalizer was not called as often as expected"
+        );
+    }};
+}
+
 #[allow(dead_code)]
 pub fn identity<T>(value: T) -> T {
     value
@@ -662,6 +960,10 @@ pub(crate) use assert_evals_to;
 #[allow(unused_imports)]
 pub(crate) use assert_evals_to_erased;
 #[allow(unused_imports)]
+pub(crate) use assert_evals_to_finalized;
+#[allow(unused_imports)]
+pub(crate) use assert_evals_to_pooled;
+#[allow(unused_imports)]
 pub(crate) use assert_llvm_evals_to;
 #[cfg(feature = "gen-llvm-wasm")]
 pub(crate) use assert_wasm_evals_to;
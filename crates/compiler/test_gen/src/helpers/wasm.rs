@@ -52,12 +52,46 @@ pub fn compile_to_wasm_bytes<'a, T: Wasm32Result>(
     arena: &'a bumpalo::Bump,
     src: &str,
     test_wrapper_type_info: PhantomData<T>,
+) -> Vec<u8> {
+    compile_to_wasm_bytes_help(arena, src, false, false, test_wrapper_type_info)
+}
+
+#[allow(dead_code)]
+pub fn compile_to_wasm_bytes_with_bulk_memory<'a, T: Wasm32Result>(
+    arena: &'a bumpalo::Bump,
+    src: &str,
+    test_wrapper_type_info: PhantomData<T>,
+) -> Vec<u8> {
+    compile_to_wasm_bytes_help(arena, src, true, false, test_wrapper_type_info)
+}
+
+#[allow(dead_code)]
+pub fn compile_to_wasm_bytes_with_exceptions<'a, T: Wasm32Result>(
+    arena: &'a bumpalo::Bump,
+    src: &str,
+    test_wrapper_type_info: PhantomData<T>,
+) -> Vec<u8> {
+    compile_to_wasm_bytes_help(arena, src, false, true, test_wrapper_type_info)
+}
+
+fn compile_to_wasm_bytes_help<'a, T: Wasm32Result>(
+    arena: &'a bumpalo::Bump,
+    src: &str,
+    bulk_memory: bool,
+    exceptions: bool,
+    test_wrapper_type_info: PhantomData<T>,
 ) -> Vec<u8> {
     let platform_bytes = include_bytes!(host_bytes_path!());
     println!("Loading test host {}", host_bytes_path!());
 
-    let compiled_bytes =
-        compile_roc_to_wasm_bytes(arena, platform_bytes, src, test_wrapper_type_info);
+    let compiled_bytes = compile_roc_to_wasm_bytes(
+        arena,
+        platform_bytes,
+        src,
+        bulk_memory,
+        exceptions,
+        test_wrapper_type_info,
+    );
 
     if write_final_wasm() {
         let build_dir_hash = crate::helpers::src_hash(src);
@@ -71,6 +105,8 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
     arena: &'a bumpalo::Bump,
     host_bytes: &[u8],
     src: &str,
+    bulk_memory: bool,
+    exceptions: bool,
     _test_wrapper_type_info: PhantomData<T>,
 ) -> Vec<u8> {
     let filename = PathBuf::from("Test.roc");
@@ -89,11 +125,17 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
 
     let load_config = LoadConfig {
         target: roc_target::Target::Wasm32,
-        render: roc_reporting::report::RenderTarget::ColorTerminal,
+        render: roc_reporting::report::RenderTarget::ColorTerminal(
+            roc_reporting::report::ColorChoice::Always,
+        ),
         palette: DEFAULT_PALETTE_HTML,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
         function_kind: FunctionKind::LambdaSet,
+        expect_filter: None,
+        finalizers: Vec::new(),
+        types_cache_dir: None,
+        fold_constants: true,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -130,6 +172,8 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
         module_id,
         exposed_to_host,
         stack_bytes: roc_gen_wasm::Env::DEFAULT_STACK_BYTES,
+        bulk_memory,
+        exceptions,
     };
 
     let host_module = roc_gen_wasm::parse_host(env.arena, host_bytes).unwrap_or_else(|e| {
@@ -159,6 +159,8 @@ impl<'a> BackendInputs<'a> {
             module_id,
             exposed_to_host,
             stack_bytes: Env::DEFAULT_STACK_BYTES,
+            bulk_memory: false,
+            exceptions: false,
         };
 
         // Identifier stuff for the backend
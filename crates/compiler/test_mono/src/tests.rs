@@ -22,7 +22,11 @@ use roc_module::symbol::Interns;
 use roc_module::symbol::Symbol;
 use roc_mono::ir::Proc;
 use roc_mono::ir::ProcLayout;
+use roc_mono::layout::InLayout;
+use roc_mono::layout::LayoutInterner;
+use roc_mono::layout::LayoutRepr;
 use roc_mono::layout::STLayoutInterner;
+use roc_mono::layout::UnionLayout;
 use roc_test_utils::TAG_LEN_ENCODER_FMT;
 use test_mono_macros::*;
 
@@ -124,7 +128,14 @@ fn promote_expr_to_module(src: &str) -> String {
     buffer
 }
 
-fn compiles_to_ir(test_name: &str, src: &str, mode: &str, allow_type_errors: bool, no_check: bool) {
+fn compiles_to_ir(
+    test_name: &str,
+    src: &str,
+    mode: &str,
+    allow_type_errors: bool,
+    no_check: bool,
+    no_fold: bool,
+) {
     use roc_packaging::cache::RocCacheDir;
     use std::path::PathBuf;
 
@@ -158,6 +169,10 @@ fn compiles_to_ir(test_name: &str, src: &str, mode: &str, allow_type_errors: boo
         render: roc_reporting::report::RenderTarget::Generic,
         palette: roc_reporting::report::DEFAULT_PALETTE,
         exec_mode,
+        expect_filter: None,
+        finalizers: Vec::new(),
+        types_cache_dir: None,
+        fold_constants: !no_fold,
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -299,6 +314,56 @@ fn verify_procedures<'a>(
     }
 }
 
+/// Monomorphize `src`, and pass the layout of the value that `main` evaluates to to `check`.
+fn main_layout(src: &str, check: impl for<'a> FnOnce(&STLayoutInterner<'a>, InLayout<'a>)) {
+    use roc_packaging::cache::RocCacheDir;
+    use std::path::PathBuf;
+
+    let arena = &Bump::new();
+
+    let load_config = LoadConfig {
+        target: TARGET,
+        function_kind: FunctionKind::LambdaSet,
+        threading: Threading::Single,
+        render: roc_reporting::report::RenderTarget::Generic,
+        palette: roc_reporting::report::DEFAULT_PALETTE,
+        exec_mode: ExecutionMode::Executable,
+        expect_filter: None,
+        finalizers: Vec::new(),
+        types_cache_dir: None,
+        fold_constants: true,
+    };
+    let loaded = roc_load::load_and_monomorphize_from_str(
+        arena,
+        PathBuf::from("Test.roc"),
+        &promote_expr_to_module(src),
+        PathBuf::from("fake/test/path"),
+        None,
+        RocCacheDir::Disallowed,
+        load_config,
+    );
+
+    let loaded = match loaded {
+        Ok(x) => x,
+        Err(e) => panic!("{e:?}"),
+    };
+
+    let main_fn_symbol = *loaded
+        .exposed_to_host
+        .top_level_values
+        .keys()
+        .next()
+        .unwrap();
+
+    let (_, proc_layout) = loaded
+        .procedures
+        .keys()
+        .find(|(symbol, _)| *symbol == main_fn_symbol)
+        .unwrap();
+
+    check(&loaded.layout_interner, proc_layout.result);
+}
+
 #[mono_test]
 fn ir_int_literal() {
     r"
@@ -306,7 +371,7 @@ fn ir_int_literal() {
     "
 }
 
-#[mono_test]
+#[mono_test(no_fold = "true")]
 fn ir_int_add() {
     r"
     x = [1,2]
@@ -314,6 +379,14 @@ fn ir_int_add() {
     "
 }
 
+#[mono_test]
+fn ir_int_add_folded() {
+    r"
+    x = [1,2]
+    5 + 4 + 3 + List.len x
+    "
+}
+
 #[mono_test]
 fn ir_assignment() {
     r"
@@ -350,13 +423,20 @@ fn ir_when_record() {
     "
 }
 
-#[mono_test]
+#[mono_test(no_fold = "true")]
 fn ir_plus() {
     r"
     1 + 2
     "
 }
 
+#[mono_test]
+fn ir_plus_folded() {
+    r"
+    1 + 2
+    "
+}
+
 #[mono_test]
 fn ir_round() {
     r"
@@ -373,7 +453,7 @@ fn ir_when_idiv() {
     "
 }
 
-#[mono_test]
+#[mono_test(no_fold = "true")]
 fn ir_two_defs() {
     r"
     x = 3
@@ -417,7 +497,7 @@ fn guard_pattern_true() {
     "
 }
 
-#[mono_test]
+#[mono_test(no_fold = "true")]
 fn when_on_record() {
     r"
     when { x: 0x2 } is
@@ -473,7 +553,7 @@ fn list_append() {
     "
 }
 
-#[mono_test]
+#[mono_test(no_fold = "true")]
 fn list_len() {
     r"
     x = [1,2,3]
@@ -483,6 +563,16 @@ fn list_len() {
     "
 }
 
+#[mono_test]
+fn list_len_folded() {
+    r"
+    x = [1,2,3]
+    y = [1.0]
+
+    List.len x + List.len y
+    "
+}
+
 #[mono_test]
 fn when_joinpoint() {
     r"
@@ -979,7 +1069,7 @@ fn optional_when() {
     "
 }
 
-#[mono_test]
+#[mono_test(no_fold = "true")]
 fn optional_field_with_binary_op() {
     r"
         { bar ? 1 + 1 } = {}
@@ -987,7 +1077,7 @@ fn optional_field_with_binary_op() {
     "
 }
 
-#[mono_test]
+#[mono_test(no_fold = "true")]
 fn nested_optional_field_with_binary_op() {
     r#"
         when { x: ([{}], "foo") } is
@@ -1854,7 +1944,7 @@ fn lambda_set_niche_same_layout_different_constructor() {
     )
 }
 
-#[mono_test]
+#[mono_test(no_fold = "true")]
 fn choose_u64_layout() {
     indoc!(
         r"
@@ -1863,7 +1953,7 @@ fn choose_u64_layout() {
     )
 }
 
-#[mono_test]
+#[mono_test(no_fold = "true")]
 fn choose_i128_layout() {
     indoc!(
         r"
@@ -1875,7 +1965,7 @@ fn choose_i128_layout() {
     )
 }
 
-#[mono_test]
+#[mono_test(no_fold = "true")]
 fn choose_u128_layout() {
     indoc!(
         r"
@@ -2135,7 +2225,7 @@ fn issue_3669() {
     )
 }
 
-#[mono_test]
+#[mono_test(no_fold = "true")]
 fn num_width_gt_u8_layout_as_float() {
     indoc!(
         r"
@@ -3269,7 +3359,7 @@ fn dbg_str_followed_by_number() {
     )
 }
 
-#[mono_test]
+#[mono_test(no_fold = "true")]
 fn dbg_expr() {
     indoc!(
         r#"
@@ -3296,7 +3386,7 @@ fn dbg_inside_string() {
     )
 }
 
-#[mono_test]
+#[mono_test(no_fold = "true")]
 fn pizza_dbg() {
     indoc!(
         r#"
@@ -3640,7 +3730,7 @@ fn issue_6174() {
     )
 }
 
-#[mono_test]
+#[mono_test(no_fold = "true")]
 fn issue_6606_1() {
     indoc!(
         r"
@@ -3711,3 +3801,122 @@ fn return_annotated() {
         "#
     )
 }
+
+#[test]
+fn result_str_unit_layout() {
+    // every bit pattern of a Str is a valid Str, so the tag id needs a word of its own
+    main_layout(
+        indoc!(
+            r#"
+            result : Result Str {}
+            result = Ok "hello"
+
+            result
+            "#
+        ),
+        |interner, layout| {
+            assert!(matches!(
+                interner.get_repr(layout),
+                LayoutRepr::Union(UnionLayout::NonRecursive(_))
+            ));
+            assert_eq!(interner.stack_size(layout), 32);
+        },
+    )
+}
+
+#[test]
+fn cons_list_layout() {
+    // `Nil` is the null pointer
+    main_layout(
+        indoc!(
+            r"
+            ConsList a : [Cons a (ConsList a), Nil]
+
+            list : ConsList I64
+            list = Cons 1 (Cons 2 Nil)
+
+            list
+            "
+        ),
+        |interner, layout| {
+            assert!(matches!(
+                interner.get_repr(layout),
+                LayoutRepr::Union(UnionLayout::NullableUnwrapped { .. })
+            ));
+            assert_eq!(interner.stack_size(layout), 8);
+        },
+    )
+}
+
+#[test]
+fn maybe_box_layout() {
+    // `None` is the null pointer, `Some` is the box itself
+    main_layout(
+        indoc!(
+            r"
+            maybe : [Some (Box I64), None]
+            maybe = Some (Box.box 42)
+
+            maybe
+            "
+        ),
+        |interner, layout| {
+            assert!(matches!(
+                interner.get_repr(layout),
+                LayoutRepr::Union(UnionLayout::NullableUnwrapped { .. })
+            ));
+            assert_eq!(interner.stack_size(layout), 8);
+        },
+    )
+}
+
+#[test]
+fn record_fields_sorted_by_alignment() {
+    // the U64 goes first, so the two U8 fields fill its trailing padding instead of each taking a word
+    main_layout(
+        indoc!(
+            r"
+            record : { a : U8, b : U64, c : U8 }
+            record = { a: 1, b: 2, c: 3 }
+
+            record
+            "
+        ),
+        |interner, layout| {
+            let LayoutRepr::Struct(field_layouts) = interner.get_repr(layout) else {
+                panic!("not a struct: {:?}", interner.dbg(layout));
+            };
+
+            let fields: Vec<_> = field_layouts
+                .iter()
+                .map(|field| interner.get_repr(*field))
+                .collect();
+
+            assert_eq!(fields, [LayoutRepr::U64, LayoutRepr::U8, LayoutRepr::U8]);
+            assert_eq!(interner.stack_size(layout), 16);
+        },
+    )
+}
+
+#[test]
+fn small_scalar_union_layout() {
+    // the tag id is a U8 of its own after the payload, not a word
+    main_layout(
+        indoc!(
+            r"
+            small : [A U8, B U8, C U8, D U8]
+            small = C 42
+
+            small
+            "
+        ),
+        |interner, layout| {
+            assert!(matches!(
+                interner.get_repr(layout),
+                LayoutRepr::Union(UnionLayout::NonRecursive(_))
+            ));
+            assert_eq!(interner.stack_size(layout), 2);
+            assert_eq!(interner.alignment_bytes(layout), 1);
+        },
+    )
+}
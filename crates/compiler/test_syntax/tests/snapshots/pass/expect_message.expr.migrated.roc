@@ -0,0 +1,3 @@
+expect 1 == 2, "one"
+
+4
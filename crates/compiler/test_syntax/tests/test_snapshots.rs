@@ -457,6 +457,7 @@ mod test_snapshots {
         pass/equals_with_spaces.expr,
         pass/expect.expr,
         pass/expect_defs.moduledefs,
+        pass/expect_message.expr,
         pass/expect_single_line.expr,
         pass/ext_on_fn_ty.expr,
         pass/extra_newline.expr,
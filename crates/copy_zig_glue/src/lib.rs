@@ -33,6 +33,7 @@ pub fn copy_zig_glue() {
         workspace_dir.join("crates/cli/tests/test-projects/test-platform-simple-zig/glue"),
         workspace_dir.join("crates/cli/tests/test-projects/multiple_exposed/platform/glue"),
         workspace_dir.join("crates/cli/tests/test-projects/tui/platform/glue"),
+        workspace_dir.join("crates/cli/tests/test-projects/wasi-hello/platform/glue"),
         workspace_dir.join("crates/cli/tests/platform-switching/zig-platform/glue"),
     ];
 
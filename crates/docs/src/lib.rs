@@ -695,10 +695,16 @@ pub fn load_module_for_docs(filename: PathBuf) -> LoadedModule {
     let load_config = LoadConfig {
         target: roc_target::Target::LinuxX64, // This is just type-checking for docs, so "target" doesn't matter
         function_kind: roc_solve::FunctionKind::LambdaSet,
-        render: roc_reporting::report::RenderTarget::ColorTerminal,
+        render: roc_reporting::report::RenderTarget::ColorTerminal(
+            roc_reporting::report::ColorChoice::Always,
+        ),
         palette: roc_reporting::report::DEFAULT_PALETTE,
         threading: Threading::AllAvailable,
         exec_mode: ExecutionMode::Check,
+        expect_filter: None,
+        finalizers: Vec::new(),
+        types_cache_dir: None,
+        fold_constants: true,
     };
     match roc_load::load_and_typecheck(
         &arena,
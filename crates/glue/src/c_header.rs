@@ -0,0 +1,601 @@
+//! Generates a C header with the types and entry points a platform exposes to its host.
+//!
+//! `roc glue` needs a glue spec written in Roc for every host language. A C host only needs the
+//! declarations that match what the compiler generates, and those follow from the layouts that
+//! [crate::types::Types] already computed, so `roc gen-header` writes them directly.
+//!
+//! Every type is declared with the size and alignment of its layout, and the header checks both
+//! with `_Static_assert`, so a C compiler that would lay a type out differently rejects the header
+//! instead of reading the wrong bytes. Types whose size depends on the app, like closures, can
+//! not be declared in C, and are incomplete struct types that the host can only point to.
+//!
+//! The header also records the ABI version of the platform, and checks when the host starts that
+//! the application it was linked with was built for the same version.
+
+use crate::load::{load_types, IgnoreErrors};
+use crate::types::{
+    RocNum, RocSingleTagPayload, RocStructFields, RocTagUnion, RocType, TypeId, Types,
+};
+use roc_load::Threading;
+use roc_mono::abi::ABI_VERSION_SYMBOL;
+use roc_target::Target;
+use std::fmt::Write;
+use std::io;
+use std::path::Path;
+use target_lexicon::Triple;
+
+/// Roc's builtin types, and the functions every host has to provide
+const PRELUDE: &str = r#"#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+typedef struct RocStr {
+    uint8_t *bytes;
+    size_t len;
+    size_t capacity;
+} RocStr;
+
+typedef struct RocList {
+    void *elements;
+    size_t len;
+    size_t capacity;
+} RocList;
+
+#ifdef __SIZEOF_INT128__
+typedef __int128 RocI128;
+typedef unsigned __int128 RocU128;
+/* A fixed-point number with 18 decimal places */
+typedef __int128 RocDec;
+#endif
+
+/* Provided by the host */
+extern void *roc_alloc(size_t size, uint32_t alignment);
+extern void *roc_realloc(void *ptr, size_t new_size, size_t old_size, uint32_t alignment);
+extern void roc_dealloc(void *ptr, uint32_t alignment);
+extern void roc_panic(RocStr *msg, uint32_t tag_id);
+extern void roc_dbg(RocStr *loc, RocStr *msg, RocStr *src);
+extern void *roc_memset(void *str, int c, size_t n);
+
+/* Optionally provided by the host, to free allocations of the same alignment with one call */
+extern void roc_dealloc_batch(void **ptrs, size_t count, uint32_t alignment);
+"#;
+
+/// Write the header for the platform at `input_path` to `output_path`
+pub fn generate(input_path: &Path, output_path: &Path) -> io::Result<i32> {
+    let target: Target = Triple::host().into();
+
+    let types = load_types(
+        input_path.to_path_buf(),
+        Threading::AllAvailable,
+        IgnoreErrors { can: false },
+        target,
+    )?;
+
+    let Some(types) = types.iter().find(|types| types.target() == target) else {
+        eprintln!("`roc gen-header` does not support the target {target} yet.");
+
+        return Ok(1);
+    };
+
+    let guard = output_path
+        .file_name()
+        .map(|name| c_ident(&name.to_string_lossy()).to_uppercase())
+        .unwrap_or_else(|| "ROC_APP_H".to_string());
+
+    std::fs::write(output_path, header(types, &guard))?;
+
+    Ok(0)
+}
+
+/// The header for `types`, guarded by the macro `guard`
+pub fn header(types: &Types, guard: &str) -> String {
+    let mut buf = String::new();
+
+    writeln!(buf, "/* Generated by `roc gen-header`. Do not edit. */").unwrap();
+    writeln!(buf, "#ifndef {guard}").unwrap();
+    writeln!(buf, "#define {guard}\n").unwrap();
+    buf.push_str(PRELUDE);
+
+    let mut declared = vec![false; types.ids().len()];
+
+    for id in types.ids() {
+        declare(types, id, &mut declared, &mut buf);
+    }
+
+    for (name, id) in types.entry_points() {
+        declare_entry_point(types, name, *id, &mut buf);
+    }
+
+    declare_abi_check(types.abi_version(), &mut buf);
+
+    writeln!(buf, "\n#endif /* {guard} */").unwrap();
+
+    buf
+}
+
+/// Declare `id` after the types it contains by value
+fn declare(types: &Types, id: TypeId, declared: &mut [bool], buf: &mut String) {
+    let index = types.ids().position(|other| other == id).unwrap();
+
+    if declared[index] {
+        return;
+    }
+
+    declared[index] = true;
+
+    if !is_opaque(types, id) {
+        for dep in contained_types(types, id) {
+            declare(types, dep, declared, buf);
+        }
+    }
+
+    let Some(name) = declared_name(types, id) else {
+        return;
+    };
+
+    buf.push('\n');
+
+    if is_opaque(types, id) {
+        writeln!(
+            buf,
+            "/* The size of this type depends on the app, so it can only be used behind a pointer */"
+        )
+        .unwrap();
+        writeln!(buf, "typedef struct {name} {name};").unwrap();
+
+        return;
+    }
+
+    match types.get_type(id) {
+        RocType::Struct { fields, .. } | RocType::TagUnionPayload { fields, .. } => {
+            let RocStructFields::HasNoClosure { fields } = fields else {
+                unreachable!("structs with closures are opaque")
+            };
+
+            let fields = fields
+                .iter()
+                .map(|(label, field)| (field_name(label), *field));
+
+            declare_struct(types, &name, fields, buf);
+        }
+        RocType::TagUnion(union) => declare_tag_union(types, &name, union, buf),
+        RocType::RocResult(ok, err) => {
+            let payload_size = [*ok, *err]
+                .iter()
+                .filter(|payload| !is_zero_sized(types, **payload))
+                .map(|payload| types.size_rounded_to_alignment(*payload))
+                .max()
+                .unwrap_or(0);
+
+            let tags = [("Err", *err), ("Ok", *ok)];
+
+            declare_discriminants(&name, tags.iter().map(|(tag, _)| *tag), buf);
+            declare_union(types, &name, tags.into_iter(), payload_size, 1, buf);
+        }
+        RocType::RocDict(_, _) | RocType::RocSet(_) => {
+            // glue does not describe the insides of these builtin records
+            writeln!(buf, "typedef struct {name} {{").unwrap();
+            writeln!(
+                buf,
+                "    _Alignas({}) uint8_t bytes[{}];",
+                types.align(id),
+                types.size_rounded_to_alignment(id)
+            )
+            .unwrap();
+            writeln!(buf, "}} {name};").unwrap();
+        }
+        _ => unreachable!("{name} is not a declared type"),
+    }
+
+    writeln!(
+        buf,
+        "_Static_assert(sizeof({name}) == {}, \"the size of {name} does not match Roc\");",
+        types.size_rounded_to_alignment(id)
+    )
+    .unwrap();
+    writeln!(
+        buf,
+        "_Static_assert(_Alignof({name}) == {}, \"the alignment of {name} does not match Roc\");",
+        types.align(id)
+    )
+    .unwrap();
+}
+
+fn declare_struct(
+    types: &Types,
+    name: &str,
+    fields: impl Iterator<Item = (String, TypeId)>,
+    buf: &mut String,
+) {
+    writeln!(buf, "typedef struct {name} {{").unwrap();
+
+    for (field, field_id) in fields {
+        if !is_zero_sized(types, field_id) {
+            writeln!(
+                buf,
+                "    {};",
+                declaration(&type_name(types, field_id), &field)
+            )
+            .unwrap();
+        }
+    }
+
+    writeln!(buf, "}} {name};").unwrap();
+}
+
+fn declare_tag_union(types: &Types, name: &str, union: &RocTagUnion, buf: &mut String) {
+    match union {
+        RocTagUnion::Enumeration { tags, size, .. } => {
+            declare_discriminants(name, tags.iter().map(String::as_str), buf);
+            writeln!(buf, "typedef {} {name};", uint_name(*size)).unwrap();
+        }
+        RocTagUnion::NonRecursive {
+            tags,
+            discriminant_offset,
+            discriminant_size,
+            ..
+        } => {
+            declare_discriminants(name, tags.iter().map(|(tag, _)| tag.as_str()), buf);
+
+            let payloads = tags
+                .iter()
+                .filter_map(|(tag, payload)| Some((tag.as_str(), (*payload)?)));
+
+            declare_union(
+                types,
+                name,
+                payloads,
+                *discriminant_offset,
+                *discriminant_size,
+                buf,
+            );
+        }
+        RocTagUnion::SingleTagStruct {
+            payload: RocSingleTagPayload::HasNoClosure { payload_fields },
+            ..
+        } => {
+            let fields = payload_fields
+                .iter()
+                .enumerate()
+                .map(|(index, field)| (format!("f{index}"), *field));
+
+            declare_struct(types, name, fields, buf);
+        }
+        RocTagUnion::SingleTagStruct {
+            payload: RocSingleTagPayload::HasClosure { .. },
+            ..
+        } => unreachable!("tag unions with closures are opaque"),
+        RocTagUnion::Recursive { tags, .. } | RocTagUnion::NullableWrapped { tags, .. } => {
+            declare_discriminants(name, tags.iter().map(|(tag, _)| tag.as_str()), buf);
+            declare_pointer(name, buf);
+        }
+        RocTagUnion::NullableUnwrapped { .. } | RocTagUnion::NonNullableUnwrapped { .. } => {
+            declare_pointer(name, buf);
+        }
+    }
+}
+
+/// A tag union with a payload is a union of its payloads and its discriminant. The discriminant
+/// is not always after the largest payload: it can be in padding that all payloads have, so it is
+/// put at its offset explicitly.
+fn declare_union<'t>(
+    types: &Types,
+    name: &str,
+    payloads: impl Iterator<Item = (&'t str, TypeId)>,
+    discriminant_offset: u32,
+    discriminant_size: u32,
+    buf: &mut String,
+) {
+    writeln!(buf, "typedef union {name} {{").unwrap();
+
+    for (tag, payload) in payloads {
+        if !is_zero_sized(types, payload) {
+            let payload_type = type_name(types, payload);
+
+            writeln!(buf, "    {};", declaration(&payload_type, &c_ident(tag))).unwrap();
+        }
+    }
+
+    writeln!(buf, "    struct {{").unwrap();
+    if discriminant_offset > 0 {
+        writeln!(buf, "        uint8_t padding[{discriminant_offset}];").unwrap();
+    }
+    writeln!(buf, "        {} tag;", uint_name(discriminant_size)).unwrap();
+    writeln!(buf, "    }} discriminant;").unwrap();
+    writeln!(buf, "}} {name};").unwrap();
+}
+
+fn declare_discriminants<'t>(name: &str, tags: impl Iterator<Item = &'t str>, buf: &mut String) {
+    writeln!(buf, "enum {name}_Discriminant {{").unwrap();
+
+    for (index, tag) in tags.enumerate() {
+        writeln!(buf, "    {name}_{} = {index},", c_ident(tag)).unwrap();
+    }
+
+    writeln!(buf, "}};").unwrap();
+}
+
+/// A recursive tag union is a pointer to its heap-allocated value
+fn declare_pointer(name: &str, buf: &mut String) {
+    writeln!(
+        buf,
+        "/* A pointer to the value on the heap, which may store the discriminant in its unused low bits */"
+    )
+    .unwrap();
+    writeln!(buf, "typedef void *{name};").unwrap();
+}
+
+/// The `roc__<name>_1_exposed_generic` function the host calls. Its first argument points to
+/// where the result is written.
+fn declare_entry_point(types: &Types, name: &str, id: TypeId, buf: &mut String) {
+    let extern_name = format!("roc__{name}_1_exposed_generic");
+
+    buf.push('\n');
+
+    match types.get_type(id) {
+        RocType::Function(roc_fn) => {
+            let returns_closure = matches!(types.get_type(roc_fn.ret), RocType::Function(_));
+
+            let mut params = vec![if returns_closure {
+                "uint8_t *ret".to_string()
+            } else {
+                declaration(&pointer_to(&type_name(types, roc_fn.ret)), "ret")
+            }];
+
+            for (index, arg) in roc_fn.args.iter().enumerate() {
+                if is_zero_sized(types, *arg) {
+                    continue;
+                }
+
+                let arg_type = match is_passed_by_pointer(types, *arg) {
+                    true => pointer_to(&type_name(types, *arg)),
+                    false => type_name(types, *arg),
+                };
+
+                params.push(declaration(&arg_type, &format!("arg{index}")));
+            }
+
+            writeln!(buf, "extern void {extern_name}({});", params.join(", ")).unwrap();
+
+            if returns_closure {
+                writeln!(
+                    buf,
+                    "/* The size of the closure that {extern_name} writes */"
+                )
+                .unwrap();
+                writeln!(buf, "extern int64_t roc__{name}_1_exposed_size(void);").unwrap();
+            }
+        }
+        _ => {
+            let ret = declaration(&pointer_to(&type_name(types, id)), "ret");
+
+            writeln!(buf, "extern void {extern_name}({ret});").unwrap();
+        }
+    }
+}
+
+/// The host checks at startup that the application was built against the same ABI as this
+/// header, see [roc_mono::abi]. Hosts that can not print or abort define `ROC_NO_ABI_CHECK` and
+/// compare `__roc_abi_version` themselves.
+fn declare_abi_check(abi_version: u64, buf: &mut String) {
+    writeln!(
+        buf,
+        r#"
+/* The ABI version of the platform this header was generated for */
+#define ROC_ABI_VERSION UINT64_C(0x{abi_version:016x})
+
+/* The ABI version the application was built for */
+extern const uint64_t {ABI_VERSION_SYMBOL};
+
+#if !defined(ROC_NO_ABI_CHECK) && (defined(__GNUC__) || defined(__clang__))
+#include <stdio.h>
+#include <stdlib.h>
+
+__attribute__((constructor)) static void roc_check_abi_version(void) {{
+    if ({ABI_VERSION_SYMBOL} != ROC_ABI_VERSION) {{
+        fprintf(stderr,
+                "The Roc application was built for a different version of its platform than this "
+                "host: the host expects ABI version %016llx, but the application has ABI version "
+                "%016llx. Rebuild the host from the platform that the application uses.\n",
+                (unsigned long long)ROC_ABI_VERSION, (unsigned long long){ABI_VERSION_SYMBOL});
+        abort();
+    }}
+}}
+#endif"#
+    )
+    .unwrap();
+}
+
+/// The types that `id` contains by value, which have to be declared before it
+fn contained_types(types: &Types, id: TypeId) -> Vec<TypeId> {
+    match types.get_type(id) {
+        RocType::Struct { fields, .. } | RocType::TagUnionPayload { fields, .. } => match fields {
+            RocStructFields::HasNoClosure { fields } => fields.iter().map(|(_, id)| *id).collect(),
+            RocStructFields::HasClosure { fields } => fields.iter().map(|(_, id, _)| *id).collect(),
+        },
+        RocType::TagUnion(RocTagUnion::NonRecursive { tags, .. }) => {
+            tags.iter().filter_map(|(_, payload)| *payload).collect()
+        }
+        RocType::TagUnion(RocTagUnion::SingleTagStruct { payload, .. }) => match payload {
+            RocSingleTagPayload::HasNoClosure { payload_fields } => payload_fields.clone(),
+            RocSingleTagPayload::HasClosure { payload_getters } => {
+                payload_getters.iter().map(|(id, _)| *id).collect()
+            }
+        },
+        RocType::RocResult(ok, err) => vec![*ok, *err],
+        RocType::RecursivePointer(content) => vec![*content],
+        RocType::Function(roc_fn) => {
+            let mut ids = roc_fn.args.clone();
+            ids.push(roc_fn.ret);
+            ids
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// The name of the typedef that declares a type, if it gets one
+fn declared_name(types: &Types, id: TypeId) -> Option<String> {
+    let name = match types.get_type(id) {
+        RocType::Struct { name, .. }
+        | RocType::TagUnionPayload { name, .. }
+        | RocType::TagUnion(RocTagUnion::Enumeration { name, .. })
+        | RocType::TagUnion(RocTagUnion::NonRecursive { name, .. })
+        | RocType::TagUnion(RocTagUnion::Recursive { name, .. })
+        | RocType::TagUnion(RocTagUnion::NonNullableUnwrapped { name, .. })
+        | RocType::TagUnion(RocTagUnion::SingleTagStruct { name, .. })
+        | RocType::TagUnion(RocTagUnion::NullableWrapped { name, .. })
+        | RocType::TagUnion(RocTagUnion::NullableUnwrapped { name, .. }) => name,
+        RocType::Function(roc_fn) => &roc_fn.function_name,
+        RocType::RocResult(ok, err) => {
+            let ok = type_name(types, *ok);
+            let err = type_name(types, *err);
+
+            return Some(c_ident(&format!("RocResult_{ok}_{err}")));
+        }
+        RocType::RocDict(_, _) | RocType::RocSet(_) => {
+            let kind = match types.get_type(id) {
+                RocType::RocDict(_, _) => "RocDict",
+                _ => "RocSet",
+            };
+            let index = types.ids().position(|other| other == id).unwrap();
+
+            return Some(format!("{kind}_{index}"));
+        }
+        _ => return None,
+    };
+
+    Some(c_ident(name))
+}
+
+/// Whether the size of a type is only known once the app is compiled
+fn is_opaque(types: &Types, id: TypeId) -> bool {
+    match types.get_type(id) {
+        RocType::Function(_) => true,
+        RocType::Struct { fields, .. } | RocType::TagUnionPayload { fields, .. } => {
+            matches!(fields, RocStructFields::HasClosure { .. })
+        }
+        RocType::TagUnion(RocTagUnion::SingleTagStruct { payload, .. }) => {
+            matches!(payload, RocSingleTagPayload::HasClosure { .. })
+        }
+        RocType::TagUnion(RocTagUnion::NonRecursive { tags, .. }) => tags
+            .iter()
+            .filter_map(|(_, payload)| *payload)
+            .any(|payload| is_opaque(types, payload)),
+        RocType::RocResult(ok, err) => is_opaque(types, *ok) || is_opaque(types, *err),
+        _ => false,
+    }
+}
+
+fn is_zero_sized(types: &Types, id: TypeId) -> bool {
+    matches!(types.get_type(id), RocType::Unit | RocType::EmptyTagUnion)
+}
+
+/// Whether the exposed functions take an argument of this type by pointer. This follows the C
+/// calling convention that the LLVM backend gives exposed functions.
+fn is_passed_by_pointer(types: &Types, id: TypeId) -> bool {
+    match types.get_type(id) {
+        RocType::RocStr | RocType::RocList(_) | RocType::Unsized => true,
+        RocType::Struct { .. }
+        | RocType::TagUnionPayload { .. }
+        | RocType::RocDict(_, _)
+        | RocType::RocSet(_)
+        | RocType::TagUnion(RocTagUnion::SingleTagStruct { .. }) => {
+            let target = types.target();
+            let size = types.size_rounded_to_alignment(id) as usize;
+
+            size > target.max_by_value_size()
+                || (target == Target::WinX64 && !matches!(size, 1 | 2 | 4 | 8))
+        }
+        _ => false,
+    }
+}
+
+/// The C type of a value of `id`
+fn type_name(types: &Types, id: TypeId) -> String {
+    match types.get_type(id) {
+        RocType::RocStr => "RocStr".to_string(),
+        RocType::Bool => "bool".to_string(),
+        RocType::Num(num) => num_name(*num).to_string(),
+        RocType::RocList(_) | RocType::Unsized => "RocList".to_string(),
+        RocType::RocBox(_) => "void *".to_string(),
+        RocType::RecursivePointer(content) => type_name(types, *content),
+        RocType::Unit | RocType::EmptyTagUnion => "void".to_string(),
+        _ => declared_name(types, id).unwrap(),
+    }
+}
+
+fn pointer_to(c_type: &str) -> String {
+    match c_type.ends_with('*') {
+        true => format!("{c_type}*"),
+        false => format!("{c_type} *"),
+    }
+}
+
+/// `c_type name`, without a space after a `*`
+fn declaration(c_type: &str, name: &str) -> String {
+    match c_type.ends_with('*') {
+        true => format!("{c_type}{name}"),
+        false => format!("{c_type} {name}"),
+    }
+}
+
+fn num_name(num: RocNum) -> &'static str {
+    match num {
+        RocNum::I8 => "int8_t",
+        RocNum::U8 => "uint8_t",
+        RocNum::I16 => "int16_t",
+        RocNum::U16 => "uint16_t",
+        RocNum::I32 => "int32_t",
+        RocNum::U32 => "uint32_t",
+        RocNum::I64 => "int64_t",
+        RocNum::U64 => "uint64_t",
+        RocNum::I128 => "RocI128",
+        RocNum::U128 => "RocU128",
+        RocNum::F32 => "float",
+        RocNum::F64 => "double",
+        RocNum::Dec => "RocDec",
+    }
+}
+
+fn uint_name(size: u32) -> &'static str {
+    match size {
+        1 => "uint8_t",
+        2 => "uint16_t",
+        4 => "uint32_t",
+        8 => "uint64_t",
+        _ => unreachable!("a discriminant of {size} bytes"),
+    }
+}
+
+/// Payload fields are named by their position
+fn field_name(label: &str) -> String {
+    match label.parse::<usize>() {
+        Ok(index) => format!("f{index}"),
+        Err(_) => c_ident(label),
+    }
+}
+
+/// `name`, changed where needed to be a valid C identifier
+fn c_ident(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "auto", "bool", "break", "case", "char", "const", "continue", "default", "do", "double",
+        "else", "enum", "extern", "float", "for", "goto", "if", "inline", "int", "long",
+        "register", "restrict", "return", "short", "signed", "sizeof", "static", "struct",
+        "switch", "typedef", "union", "unsigned", "void", "volatile", "while",
+    ];
+
+    let mut ident: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+
+    if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+
+    if KEYWORDS.contains(&ident.as_str()) {
+        ident.push('_');
+    }
+
+    ident
+}
@@ -2,6 +2,7 @@
 //! This tool is not necessary for writing a platform in another language,
 //! however, it's a great convenience! Currently supports Rust platforms, and
 //! the plan is to support any language via a plugin model.
+pub mod c_header;
 pub mod enums;
 pub mod load;
 pub mod roc_type;
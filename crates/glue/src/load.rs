@@ -16,7 +16,7 @@ use roc_load::{ExecutionMode, FunctionKind, LoadConfig, LoadedModule, LoadingPro
 use roc_mono::ir::{generate_glue_procs, CrashTag, GlueProc, OptLevel};
 use roc_mono::layout::{GlobalLayoutInterner, LayoutCache, LayoutInterner};
 use roc_packaging::cache::{self, RocCacheDir};
-use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
+use roc_reporting::report::{ColorChoice, RenderTarget, DEFAULT_PALETTE};
 use roc_target::{Architecture, Target, TargetFromTripleError::TripleUnsupported};
 use roc_types::subs::{Subs, Variable};
 use std::fs::File;
@@ -59,8 +59,32 @@ pub fn generate(
                 backend,
                 opt_level: OptLevel::Development,
                 emit_debug_info: false,
+                emit_source_debug_info: false,
                 emit_llvm_ir: false,
+                emit_llvm_bc: false,
+                emit_mono: false,
+                emit_asm: false,
                 fuzz: false,
+                max_stack_frame: None,
+                host_panic: false,
+                sanitize_address: false,
+                check_alignment: false,
+                inline_threshold: None,
+                optimize_stack_alloc: false,
+                overflow_trap: false,
+                pgo: None,
+                lto: false,
+                sbf_compute_budget: None,
+                sbf_stack_limit: None,
+                stack_size: None,
+                data_sections: Vec::new(),
+                shared_library: link_type == LinkType::Dylib,
+                pie: true,
+                cfi: false,
+                pool_alloc: false,
+                valgrind: false,
+                bulk_memory: false,
+                wasm_exceptions: false,
             };
 
             let load_config = standard_load_config(
@@ -76,6 +100,7 @@ pub fn generate(
             // that will be loaded by the roc compiler/cli
             let build_host = false;
             let suppress_build_host_warning = true;
+            let use_cache = false;
 
             let res_binary_path = match tempdir_res {
                 Ok(dylib_dir) => build_file(
@@ -88,6 +113,7 @@ pub fn generate(
                     linking_strategy,
                     build_host,
                     suppress_build_host_warning,
+                    use_cache,
                     None,
                     RocCacheDir::Persistent(cache::roc_cache_packages_dir().as_path()),
                     load_config,
@@ -106,6 +132,8 @@ pub fn generate(
                     problems,
                     total_time,
                     expect_metadata: _,
+                    module_paths: _,
+                    platform_main_roc_path: _,
                 }) => {
                     // TODO: Should binary_path be update to deal with extensions?
                     use roc_target::OperatingSystem;
@@ -188,9 +216,11 @@ pub fn generate(
 
                     Ok(0)
                 }
-                Err(BuildFileError::ErrorModule { module, total_time }) => {
-                    handle_error_module(module, total_time)
-                }
+                Err(BuildFileError::ErrorModule { module, total_time }) => handle_error_module(
+                    module,
+                    total_time,
+                    RenderTarget::ColorTerminal(ColorChoice::Always),
+                ),
                 Err(BuildFileError::LoadingProblem(problem)) => handle_loading_problem(problem),
             };
 
@@ -430,6 +460,10 @@ pub fn load_types(
             palette: DEFAULT_PALETTE,
             threading,
             exec_mode: ExecutionMode::Check,
+            expect_filter: None,
+            finalizers: Vec::new(),
+            types_cache_dir: None,
+            fold_constants: true,
         },
     )
     .unwrap_or_else(|problem| match problem {
@@ -474,6 +508,11 @@ pub fn load_types(
             Err(TripleUnsupported) => continue,
         };
 
+        // roc_type::Architecture has no variant for these yet
+        if matches!(architecture, Architecture::Riscv64 | Architecture::Sbf) {
+            continue;
+        }
+
         let layout_interner = GlobalLayoutInterner::with_capacity(128, target);
         let mut layout_cache = LayoutCache::new(layout_interner.fork(), target);
         let mut glue_procs_by_layout = MutMap::default();
@@ -66,6 +66,9 @@ pub struct Types {
     /// type declaration earlier in the file than where it gets referenced by another type.
     deps: VecMap<TypeId, Vec<TypeId>>,
     target: Target,
+
+    /// See [roc_mono::abi]
+    abi_version: u64,
 }
 
 impl Types {
@@ -88,6 +91,7 @@ impl Types {
             types_by_name: FnvHashMap::with_capacity_and_hasher(10, Default::default()),
             entry_points: Vec::new(),
             deps: VecMap::with_capacity(cap),
+            abi_version: 0,
         }
     }
 
@@ -111,6 +115,8 @@ impl Types {
             target,
         );
 
+        types.abi_version = env.abi_version(&entry_points);
+
         for (_symbol, var) in entry_points.clone() {
             env.lambda_set_ids = env.find_lambda_sets(var);
             let id = env.add_toplevel_type(var, &mut types);
@@ -137,6 +143,11 @@ impl Types {
         self.entry_points.as_slice()
     }
 
+    /// The ABI version that an application built against these entry points embeds
+    pub fn abi_version(&self) -> u64 {
+        self.abi_version
+    }
+
     pub fn is_equivalent(&self, a: &RocType, b: &RocType) -> bool {
         self.is_equivalent_help(RocTypeOrPending::Type(a), RocTypeOrPending::Type(b))
     }
@@ -919,6 +930,9 @@ impl From<Architecture> for roc_type::Architecture {
         match arch {
             Architecture::Aarch32 => roc_type::Architecture::Aarch32,
             Architecture::Aarch64 => roc_type::Architecture::Aarch64,
+            Architecture::Riscv64 | Architecture::Sbf => {
+                internal_error!("Glue is not generated for the {:?} architecture", arch)
+            }
             Architecture::Wasm32 => roc_type::Architecture::Wasm32,
             Architecture::X86_32 => roc_type::Architecture::X86x32,
             Architecture::X86_64 => roc_type::Architecture::X86x64,
@@ -1197,6 +1211,43 @@ impl<'a> Env<'a> {
         }
     }
 
+    /// Uses the same layouts as the procedures that mono exposes to the host: a function gets the
+    /// layouts of its arguments and its return value, and any other value gets its own layout.
+    fn abi_version(&mut self, entry_points: &MutMap<Symbol, Variable>) -> u64 {
+        let subs = self.subs;
+        let mut layouts = Vec::with_capacity(entry_points.len());
+
+        for (symbol, var) in entry_points {
+            let (arguments, result) = match subs.get_content_without_compacting(*var) {
+                Content::Structure(FlatType::Func(args, _closure_var, ret_var, _fx_var)) => {
+                    let arguments = subs
+                        .get_subs_slice(*args)
+                        .iter()
+                        .map(|arg| self.layout(*arg))
+                        .collect::<Vec<_>>();
+
+                    (arguments, self.layout(*ret_var))
+                }
+                _ => (Vec::new(), self.layout(*var)),
+            };
+
+            layouts.push((symbol.as_str(self.interns), arguments, result));
+        }
+
+        roc_mono::abi::abi_version(
+            &self.layout_cache.interner,
+            layouts
+                .iter()
+                .map(|(name, arguments, result)| (*name, arguments.as_slice(), *result)),
+        )
+    }
+
+    fn layout(&mut self, var: Variable) -> InLayout<'a> {
+        self.layout_cache
+            .from_var(self.arena, var, self.subs)
+            .expect("Something weird ended up in the content")
+    }
+
     fn resolve_pending_recursive_types(&mut self, types: &mut Types) {
         // TODO if VecMap gets a drain() method, use that instead of doing take() and into_iter
         let pending = core::mem::take(&mut self.pending_recursive_types);
@@ -1484,7 +1535,8 @@ fn add_type_help<'a>(
                             UnionLayout::Recursive(_)
                             | UnionLayout::NonNullableUnwrapped(_)
                             | UnionLayout::NullableWrapped { .. }
-                            | UnionLayout::NullableUnwrapped { .. } => {
+                            | UnionLayout::NullableUnwrapped { .. }
+                            | UnionLayout::TaggedPointer { .. } => {
                                 unreachable!();
                             }
                         }
@@ -2026,11 +2078,35 @@ fn tag_union_type_from_layout<'a>(
                     nullable_id: null_represents_first_tag,
                     other_fields: _, // TODO use this!
                 } => {
-                    let mut tags =
-                        union_tags_to_types(&name, union_tags, subs, env, types, layout, true);
+                    // A non-recursive union like `[Some (Box a), None]` stores the box directly,
+                    // so the heap-allocated payload is the boxed value.
+                    let stores_box = matches!(
+                        subs.get_content_without_compacting(var),
+                        Content::Structure(FlatType::TagUnion(_, _))
+                    );
+
+                    let mut tags = union_tags_to_types(
+                        &name,
+                        union_tags,
+                        subs,
+                        env,
+                        types,
+                        layout,
+                        !stores_box,
+                    );
                     // NullableUnwrapped tag unions should always have exactly 2 tags.
                     debug_assert_eq!(tags.len(), 2);
 
+                    if stores_box {
+                        for (_, payload) in tags.iter_mut() {
+                            if let Some(RocType::RocBox(boxed)) =
+                                payload.map(|id| types.get_type(id))
+                            {
+                                *payload = Some(*boxed);
+                            }
+                        }
+                    }
+
                     let null_tag;
                     let non_null;
 
@@ -2055,6 +2131,15 @@ fn tag_union_type_from_layout<'a>(
                         null_represents_first_tag,
                     }
                 }
+                // A recursive tag union with a variant that only holds a small integer
+                // Optimization: Store that integer in the pointer itself, so it is never allocated
+                // e.g. `Expr : [Num I32, Add Expr Expr]`
+                TaggedPointer { .. } => {
+                    internal_error!(
+                        "Glue is not generated for the tag union {}, which stores an integer in its pointer",
+                        name
+                    )
+                }
             }
         }
         LayoutRepr::Builtin(Builtin::Int(int_width)) => {
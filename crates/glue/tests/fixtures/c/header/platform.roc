@@ -0,0 +1,23 @@
+platform "test-platform"
+    requires {} { main : Str, Color -> Summary }
+    exposes []
+    packages {}
+    imports []
+    provides [main_for_host]
+
+Color : [Red, Green, Blue]
+
+# The U128 gives the union an alignment of 16, so its discriminant is
+# stored in the padding after the Str
+Shape : [Circle F64, Rectangle F64 F64, Label Str, Big U128, Empty]
+
+Summary : {
+    name : Str,
+    color : Color,
+    shape : Shape,
+    points : List I64,
+    count : U32,
+}
+
+main_for_host : Str, Color -> Summary
+main_for_host = \name, color -> main(name, color)
@@ -171,6 +171,39 @@ mod glue_cli_tests {
         "#),
     }
 
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn c_header_compiles() {
+        let platform_dir = fixtures_dir("c/header");
+        let header_dir = tempfile::tempdir().unwrap();
+        let header_path = header_dir.path().join("roc_app.h");
+
+        let gen_header_cmd = ExecCli::new("gen-header", platform_dir.join("platform.roc"))
+            .add_args(["--output", header_path.to_str().unwrap()]);
+
+        gen_header_cmd.run().assert_clean_success();
+
+        let header = std::fs::read_to_string(&header_path).unwrap();
+
+        assert!(
+            header.contains("extern void roc__main_for_host_1_exposed_generic(Summary *ret, RocStr *arg0, Color arg1);"),
+            "Unexpected entry point in the header:\n\n{header}"
+        );
+
+        // the header checks the size and alignment of every type it declares
+        let cc_out = std::process::Command::new("cc")
+            .args(["-fsyntax-only", "-std=c11", "-x", "c"])
+            .arg(&header_path)
+            .output()
+            .unwrap();
+
+        assert!(
+            cc_out.status.success(),
+            "The header did not compile:\n\n{}\n\n{header}",
+            String::from_utf8_lossy(&cc_out.stderr)
+        );
+    }
+
     fn check_for_tests(all_fixtures: &mut roc_collections::VecSet<String>) {
         use roc_collections::VecSet;
 
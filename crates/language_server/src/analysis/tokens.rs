@@ -617,12 +617,16 @@ impl IterTokens for ValueDef<'_> {
             ValueDef::Dbg {
                 preceding_comment,
                 condition,
-            }
-            | ValueDef::Expect {
+            } => (onetoken(Token::Comment, *preceding_comment, arena).into_iter())
+                .chain(condition.iter_tokens(arena))
+                .collect_in(arena),
+            ValueDef::Expect {
                 preceding_comment,
                 condition,
+                message,
             } => (onetoken(Token::Comment, *preceding_comment, arena).into_iter())
                 .chain(condition.iter_tokens(arena))
+                .chain(message.iter_tokens(arena))
                 .collect_in(arena),
             ValueDef::ModuleImport(import) => onetoken(Token::Import, import.name.region, arena),
             ValueDef::IngestedFileImport(import) => {
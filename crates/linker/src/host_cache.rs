@@ -0,0 +1,190 @@
+//! Preprocessed hosts, cached so that the same host is preprocessed only once.
+//!
+//! An entry is keyed by the bytes of the host executable and of the stub library it was linked
+//! against, the target, and the version of the linker. Only the bytes count: a platform that is
+//! downloaded again to the same path with a different host gets a new entry, and a host that is
+//! rebuilt without changes reuses the old one.
+use roc_target::Target;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The directory in the roc cache dir that holds the entries
+const HOST_CACHE_DIR_NAME: &str = "preprocessed-hosts";
+
+const PREPROCESSED_HOST_FILE_NAME: &str = "host";
+const METADATA_FILE_NAME: &str = "metadata";
+
+/// Bump the number whenever the preprocessed host or its metadata change, so that the entries an
+/// older linker made are not used.
+const LINKER_VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), ".1");
+
+/// Like [crate::preprocess_host], but first looks for the host in the cache in `roc_cache_dir`.
+/// Returns whether the preprocessed host and its metadata were taken from the cache.
+#[allow(clippy::too_many_arguments)]
+pub fn preprocess_host_cached(
+    target: Target,
+    host_exe_path: &Path,
+    metadata_path: &Path,
+    preprocessed_path: &Path,
+    shared_lib: &Path,
+    roc_cache_dir: &Path,
+    verbose: bool,
+    time: bool,
+) -> bool {
+    let key = match cache_key(target, host_exe_path, shared_lib) {
+        Ok(key) => key,
+        // the preprocessing will report a host that cannot be read
+        Err(_) => {
+            crate::preprocess_host(
+                target,
+                host_exe_path,
+                metadata_path,
+                preprocessed_path,
+                shared_lib,
+                verbose,
+                time,
+            );
+
+            return false;
+        }
+    };
+
+    let cache_dir = roc_cache_dir.join(HOST_CACHE_DIR_NAME);
+
+    with_cache(&cache_dir, &key, metadata_path, preprocessed_path, || {
+        crate::preprocess_host(
+            target,
+            host_exe_path,
+            metadata_path,
+            preprocessed_path,
+            shared_lib,
+            verbose,
+            time,
+        )
+    })
+}
+
+fn cache_key(target: Target, host_exe_path: &Path, shared_lib: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    let target = target.to_string();
+
+    // every part is prefixed with its length, so that no two keys hash the same bytes
+    for part in [
+        LINKER_VERSION.as_bytes(),
+        target.as_bytes(),
+        &fs::read(host_exe_path)?,
+        &fs::read(shared_lib)?,
+    ] {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part);
+    }
+
+    let hash = hasher.finalize();
+
+    Ok(hash.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// Copy the entry `key` of `cache_dir` to `metadata_path` and `preprocessed_path`, or make them
+/// with `preprocess` and store them in a new entry. Returns whether the entry existed.
+///
+/// A cache that cannot be read or written is the same as an empty one.
+fn with_cache(
+    cache_dir: &Path,
+    key: &str,
+    metadata_path: &Path,
+    preprocessed_path: &Path,
+    preprocess: impl FnOnce(),
+) -> bool {
+    let entry = cache_dir.join(key);
+    let cached_host = entry.join(PREPROCESSED_HOST_FILE_NAME);
+    let cached_metadata = entry.join(METADATA_FILE_NAME);
+
+    if cached_host.is_file()
+        && cached_metadata.is_file()
+        && fs::copy(&cached_host, preprocessed_path).is_ok()
+        && fs::copy(&cached_metadata, metadata_path).is_ok()
+    {
+        return true;
+    }
+
+    preprocess();
+
+    // fill a new directory, then move it into place, so that a build running at the same time
+    // never sees half an entry
+    let store = || -> io::Result<()> {
+        fs::create_dir_all(cache_dir)?;
+
+        let new_entry = tempfile::tempdir_in(cache_dir)?;
+        fs::copy(
+            preprocessed_path,
+            new_entry.path().join(PREPROCESSED_HOST_FILE_NAME),
+        )?;
+        fs::copy(metadata_path, new_entry.path().join(METADATA_FILE_NAME))?;
+
+        // when another build stored the same entry first, this fails, and the new one is dropped
+        fs::rename(new_entry.path(), &entry)
+    };
+
+    let _ = store();
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build(cache_dir: &Path, out_dir: &Path, host: &[u8], preprocessed: &[u8]) -> bool {
+        let host_path = out_dir.join("host");
+        let stub_path = out_dir.join("libapp.so");
+        let preprocessed_path = out_dir.join("host.rh");
+        let metadata_path = out_dir.join("host.rm");
+
+        fs::write(&host_path, host).unwrap();
+        fs::write(&stub_path, b"stub").unwrap();
+
+        let key = cache_key(Target::LinuxX64, &host_path, &stub_path).unwrap();
+
+        with_cache(cache_dir, &key, &metadata_path, &preprocessed_path, || {
+            fs::write(&preprocessed_path, preprocessed).unwrap();
+            fs::write(&metadata_path, b"metadata").unwrap();
+        })
+    }
+
+    #[test]
+    fn reuses_a_host_with_the_same_bytes() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let out_dir = tempfile::tempdir().unwrap();
+        let preprocessed_path = out_dir.path().join("host.rh");
+
+        assert!(!build(cache_dir.path(), out_dir.path(), b"host", b"first"));
+        fs::remove_file(&preprocessed_path).unwrap();
+
+        assert!(
+            build(cache_dir.path(), out_dir.path(), b"host", b"second"),
+            "the host was preprocessed again"
+        );
+        assert_eq!(fs::read(&preprocessed_path).unwrap(), b"first");
+    }
+
+    #[test]
+    fn preprocesses_a_changed_host_at_the_same_path() {
+        let cache_dir = tempfile::tempdir().unwrap();
+        let out_dir = tempfile::tempdir().unwrap();
+        let preprocessed_path = out_dir.path().join("host.rh");
+
+        assert!(!build(cache_dir.path(), out_dir.path(), b"host", b"first"));
+        assert!(!build(
+            cache_dir.path(),
+            out_dir.path(),
+            b"new host",
+            b"second"
+        ));
+        assert_eq!(fs::read(&preprocessed_path).unwrap(), b"second");
+
+        assert!(build(cache_dir.path(), out_dir.path(), b"host", b"third"));
+        assert_eq!(fs::read(&preprocessed_path).unwrap(), b"first");
+    }
+}
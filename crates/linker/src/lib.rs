@@ -8,6 +8,7 @@ use object::Object;
 use roc_error_macros::internal_error;
 use roc_load::ExposedToHost;
 use roc_module::symbol::Interns;
+use roc_mono::abi::ABI_VERSION_SYMBOL;
 use roc_target::{Architecture, OperatingSystem, Target};
 use std::cmp::Ordering;
 use std::mem;
@@ -19,6 +20,9 @@ mod pe;
 mod util;
 
 mod generate_dylib;
+mod host_cache;
+
+pub use host_cache::preprocess_host_cached;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum LinkType {
@@ -42,6 +46,7 @@ pub fn support_level(link_type: LinkType, target: Target) -> SupportLevel {
             Target::WinX64 => SupportLevel::Full,
             // macho support is incomplete
             Target::MacX64 => SupportLevel::None,
+            // opt in with --linker=surgical until it is tested end to end
             Target::MacArm64 => SupportLevel::Wip,
             _ => SupportLevel::None,
         }
@@ -131,6 +136,9 @@ impl ExposedSymbols {
             custom_names.extend([format!("roc__{sym}_{id}_caller")]);
         }
 
+        // hosts that check the ABI version of the app read this global
+        custom_names.push(ABI_VERSION_SYMBOL.to_string());
+
         // on windows (PE) binary search is used on the symbols,
         // so they must be in alphabetical order
         custom_names.sort_unstable();
@@ -157,6 +165,9 @@ impl ExposedSymbols {
             }
         }
 
+        // hosts that check the ABI version of the app read this global
+        custom_names.push(ABI_VERSION_SYMBOL.to_string());
+
         // on windows (PE) binary search is used on the symbols,
         // so they must be in alphabetical order
         custom_names.sort_unstable();
@@ -323,6 +334,17 @@ pub fn preprocess_host(
             );
         }
 
+        (Architecture::Aarch64, OperatingSystem::Mac) => {
+            crate::macho::preprocess_macho_arm64(
+                host_exe_path,
+                metadata_path,
+                preprocessed_path,
+                shared_lib,
+                verbose,
+                time,
+            );
+        }
+
         (_, OperatingSystem::Mac) => {
             crate::macho::preprocess_macho_le(
                 target.architecture(),
@@ -375,6 +397,16 @@ fn surgery(
             crate::elf::surgery_elf(roc_app_bytes, metadata_path, executable_path, verbose, time);
         }
 
+        (Architecture::Aarch64, OperatingSystem::Mac) => {
+            crate::macho::surgery_macho_arm64(
+                roc_app_bytes,
+                metadata_path,
+                executable_path,
+                verbose,
+                time,
+            );
+        }
+
         (_, OperatingSystem::Mac) => {
             crate::macho::surgery_macho(
                 roc_app_bytes,
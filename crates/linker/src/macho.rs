@@ -24,6 +24,11 @@ use crate::{
     load_struct_inplace_mut, load_structs_inplace, open_mmap, open_mmap_mut,
 };
 
+mod arm64;
+mod signature;
+
+pub(crate) use arm64::{preprocess_macho_arm64, surgery_macho_arm64};
+
 const MIN_SECTION_ALIGNMENT: usize = 0x40;
 
 // TODO: Analyze if this offset is always correct.
@@ -711,7 +716,7 @@ fn gen_macho_le(
         let mut offset = start_of_roc_commands;
 
         md.roc_text_segment = offset;
-        set_segment_load_command(&mut out_mmap, offset, b"__ROC_TEXT", E | R, false, 1);
+        set_segment_load_command(&mut out_mmap, offset, b"__ROC_TEXT", E | R, 0, 1);
         offset += mem::size_of::<macho::SegmentCommand64<LE>>();
         set_section_load_command(
             &mut out_mmap,
@@ -723,7 +728,14 @@ fn gen_macho_le(
         offset += mem::size_of::<macho::Section64<LE>>();
 
         md.roc_data_const_segment = offset;
-        set_segment_load_command(&mut out_mmap, offset, b"__ROC_DATA_CONST", W | R, true, 1);
+        set_segment_load_command(
+            &mut out_mmap,
+            offset,
+            b"__ROC_DATA_CONST",
+            W | R,
+            macho::SG_PROTECTED_VERSION_1,
+            1,
+        );
         offset += mem::size_of::<macho::SegmentCommand64<LE>>();
         set_section_load_command(
             &mut out_mmap,
@@ -735,7 +747,7 @@ fn gen_macho_le(
         offset += mem::size_of::<macho::Section64<LE>>();
 
         md.roc_data_segment = offset;
-        set_segment_load_command(&mut out_mmap, offset, b"__ROC_DATA", W | R, false, 2);
+        set_segment_load_command(&mut out_mmap, offset, b"__ROC_DATA", W | R, 0, 2);
         offset += mem::size_of::<macho::SegmentCommand64<LE>>();
         set_section_load_command(
             &mut out_mmap,
@@ -855,7 +867,7 @@ fn set_segment_load_command(
     offset: usize,
     name: &[u8],
     prot: u32,
-    is_relro: bool,
+    flags: u32,
     nsects: u32,
 ) {
     let cmdsize = mem::size_of::<macho::SegmentCommand64<LE>>() as u32
@@ -878,14 +890,7 @@ fn set_segment_load_command(
     info.initprot.set(LE, prot);
 
     info.nsects.set(LE, nsects);
-    info.flags.set(
-        LE,
-        if is_relro {
-            macho::SG_PROTECTED_VERSION_1
-        } else {
-            0
-        },
-    );
+    info.flags.set(LE, flags);
 }
 
 fn set_section_load_command(
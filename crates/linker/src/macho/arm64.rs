@@ -0,0 +1,2351 @@
+//! Surgical linking of arm64 macOS hosts.
+//!
+//! The host is linked against a stub `libapp.dylib`, so it calls app functions through stubs in
+//! `__TEXT,__stubs`, and finds other app symbols in slots of its global offset table that dyld
+//! binds at launch. Preprocessing makes the load of `libapp.dylib` weak, so that the host still
+//! launches without it, and inserts empty `__ROC_*` segments right before `__LINKEDIT`.
+//!
+//! Surgery then puts the app in those segments, points the stubs, calls and slots of the host at
+//! the app, moves `__LINKEDIT` behind the app, and signs the result again, because arm64 macOS
+//! does not run code that is not signed.
+//!
+//! Hosts that need anything this does not handle are rejected with a suggestion to use the legacy
+//! linker, rather than turned into an executable that crashes.
+use bincode::{deserialize_from, serialize_into};
+use memmap2::MmapMut;
+use object::macho;
+use object::{
+    LittleEndian as LE, Object, ObjectSection, ObjectSymbol, RelocationFlags, RelocationTarget,
+    Section, SectionIndex, SectionKind, SymbolIndex, SymbolSection,
+};
+use roc_collections::all::MutMap;
+use roc_error_macros::{internal_error, user_error};
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::CStr,
+    io::{BufReader, BufWriter},
+    mem,
+    path::Path,
+    time::Instant,
+};
+
+use super::signature::{ad_hoc_signature, signature_size, ExecSegment};
+use super::{
+    collect_roc_definitions, headerpad_size, set_section_load_command, set_segment_load_command,
+};
+use crate::util::report_timing;
+use crate::{
+    align_by_constraint, load_struct_inplace, load_struct_inplace_mut, load_structs_inplace,
+    open_mmap, open_mmap_mut,
+};
+
+const PAGE_SIZE: u64 = 0x4000;
+
+/// Makes dyld write protect a segment once it has applied the fixups in it
+const SG_READ_ONLY: u32 = 0x10;
+
+const DYLD_CHAINED_PTR_64: u16 = 2;
+const DYLD_CHAINED_PTR_64_OFFSET: u16 = 6;
+const DYLD_CHAINED_PTR_START_NONE: u16 = 0xFFFF;
+const DYLD_CHAINED_PTR_START_MULTI: u16 = 0x8000;
+const DYLD_CHAINED_IMPORT: u32 = 1;
+const DYLD_CHAINED_IMPORT_ADDEND: u32 = 2;
+const DYLD_CHAINED_IMPORT_ADDEND64: u32 = 3;
+
+/// The 64-bit pointer formats count the distance to the next fixup in a chain in 4-byte units
+const CHAINED_POINTER_STRIDE: u64 = 4;
+
+/// How many instructions we look back from a load for the `adrp` that computed its base.
+/// Compilers keep the two close, and this only guards against a host that we would break.
+const ADRP_LOOKBACK: usize = 16;
+
+/// The segments that surgery fills in, with their protection, flags and sections.
+/// These have to match NUM_ROC_SEGMENTS and NUM_ROC_SECTIONS.
+#[allow(clippy::type_complexity)]
+const ROC_SEGMENTS: [(&[u8], u32, u32, &[(&[u8], u32)]); 3] = [
+    (
+        b"__ROC_TEXT",
+        macho::VM_PROT_READ | macho::VM_PROT_EXECUTE,
+        0,
+        &[(
+            b"__text",
+            macho::S_REGULAR | macho::S_ATTR_PURE_INSTRUCTIONS | macho::S_ATTR_SOME_INSTRUCTIONS,
+        )],
+    ),
+    (
+        b"__ROC_DATA_CONST",
+        macho::VM_PROT_READ | macho::VM_PROT_WRITE,
+        SG_READ_ONLY,
+        &[(b"__const", macho::S_REGULAR)],
+    ),
+    (
+        b"__ROC_DATA",
+        macho::VM_PROT_READ | macho::VM_PROT_WRITE,
+        0,
+        &[(b"__data", macho::S_REGULAR), (b"__bss", macho::S_ZEROFILL)],
+    ),
+];
+
+macro_rules! unsupported {
+    ($($arg:tt)*) => {
+        user_error!(
+            "The surgical linker cannot link this arm64 macOS host, because {}.\n\nUse `--linker=legacy` to link it with the system linker instead.",
+            format_args!($($arg)*)
+        )
+    };
+}
+
+/// A change to the host that makes it use the app instead of `libapp.dylib`
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+enum Patch {
+    /// A `b` or `bl` to the stub of an app function, which can branch to the function directly
+    Branch {
+        file_offset: u64,
+        vaddr: u64,
+        symbol: String,
+    },
+    /// The stub of an app function, which becomes `adrp`, `add` and `br` to that function
+    Stub {
+        file_offset: u64,
+        vaddr: u64,
+        symbol: String,
+    },
+    /// An `adrp` and `ldr` of the slot of an app symbol, which become an `adrp` and `add` of
+    /// the address of that symbol. Only hosts with bind opcodes need this, because dyld
+    /// overwrites those slots with the missing symbols of `libapp.dylib`.
+    GotLoad {
+        file_offset: u64,
+        vaddr: u64,
+        symbol: String,
+    },
+    /// A chained fixup that binds an app symbol, which becomes a rebase to that symbol
+    ChainedBind {
+        file_offset: u64,
+        symbol: String,
+        addend: u64,
+    },
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+enum Fixups {
+    Chained { pointer_format: u16 },
+    Opcodes,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+enum LinkeditKind {
+    Plain,
+    ChainedFixups,
+    RebaseOpcodes,
+}
+
+/// Data in `__LINKEDIT` that a load command points to
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+struct LinkeditPiece {
+    /// Offset in the file of the field that holds the offset of this data.
+    /// For the kinds that surgery changes, the size follows it.
+    offset_field: usize,
+    offset: u32,
+    size: u32,
+    kind: LinkeditKind,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+struct Metadata {
+    /// The symbols that the host imports from the app
+    app_symbols: Vec<String>,
+    patches: Vec<Patch>,
+    roc_symbol_vaddresses: MutMap<String, u64>,
+    /// The stubs of functions that the host imports from other libraries, which the app can call
+    host_stubs: MutMap<String, u64>,
+    /// The slots of symbols that the host imports from other libraries, which the app can load
+    host_got: MutMap<String, u64>,
+    fixups: Fixups,
+    /// The address of the Mach-O header, which is what `DYLD_CHAINED_PTR_64_OFFSET` is relative to
+    image_base: u64,
+    /// The offset and size of `__TEXT` in the file
+    text_segment: (u64, u64),
+    /// Offsets of the load commands of `__ROC_TEXT`, `__ROC_DATA_CONST` and `__ROC_DATA`
+    roc_segments: [usize; 3],
+    /// The index of `__ROC_TEXT` among the segments
+    roc_segment_index: u8,
+    /// Offset of the `__LINKEDIT` load command
+    linkedit_segment: usize,
+    /// Offset of the `LC_CODE_SIGNATURE` load command
+    code_signature: usize,
+    linkedit_pieces: Vec<LinkeditPiece>,
+}
+
+impl Metadata {
+    fn write_to_file(&self, metadata_filename: &Path) {
+        let metadata_file =
+            std::fs::File::create(metadata_filename).unwrap_or_else(|e| internal_error!("{}", e));
+
+        serialize_into(BufWriter::new(metadata_file), self)
+            .unwrap_or_else(|err| internal_error!("Failed to serialize metadata: {err}"));
+    }
+
+    fn read_from_file(metadata_filename: &Path) -> Self {
+        let input = std::fs::File::open(metadata_filename)
+            .unwrap_or_else(|e| internal_error!("Failed to open metadata file: {e}"));
+
+        deserialize_from(BufReader::new(input))
+            .unwrap_or_else(|err| internal_error!("Failed to deserialize metadata: {err}"))
+    }
+}
+
+struct Segment<'a> {
+    command: usize,
+    info: &'a macho::SegmentCommand64<LE>,
+    sections: &'a [macho::Section64<LE>],
+}
+
+impl Segment<'_> {
+    fn name(&self) -> &[u8] {
+        trim_nul(&self.info.segname)
+    }
+}
+
+#[derive(Default)]
+struct LoadCommands<'a> {
+    segments: Vec<Segment<'a>>,
+    symtab: Option<&'a macho::SymtabCommand<LE>>,
+    dysymtab: Option<&'a macho::DysymtabCommand<LE>>,
+    dyld_info: Option<&'a macho::DyldInfoCommand<LE>>,
+    chained_fixups: Option<&'a macho::LinkeditDataCommand<LE>>,
+    data_in_code: Option<&'a macho::LinkeditDataCommand<LE>>,
+    code_signature: Option<usize>,
+    /// The offset of the command that loads `libapp.dylib`, and the ordinal that symbols
+    /// imported from it refer to it by
+    app_dylib: Option<(usize, u32)>,
+    linkedit_pieces: Vec<LinkeditPiece>,
+    /// Offset just after the last load command
+    end: usize,
+}
+
+impl<'a> LoadCommands<'a> {
+    fn parse(exec_data: &'a [u8], shared_lib: &Path) -> Self {
+        use macho::{
+            DyldInfoCommand, DylibCommand, DysymtabCommand, LinkeditDataCommand, Section64,
+            SegmentCommand64, SymtabCommand,
+        };
+
+        let header = load_struct_inplace::<macho::MachHeader64<LE>>(exec_data, 0);
+        let mut commands = Self::default();
+        let mut offset = mem::size_of_val(header);
+        let mut dylib_ordinal = 0;
+
+        for _ in 0..header.ncmds.get(LE) {
+            let info = load_struct_inplace::<macho::LoadCommand<LE>>(exec_data, offset);
+            let cmd = info.cmd.get(LE);
+            let cmdsize = info.cmdsize.get(LE) as usize;
+
+            match cmd {
+                macho::LC_SEGMENT_64 => {
+                    let info = load_struct_inplace::<SegmentCommand64<LE>>(exec_data, offset);
+                    let sections = load_structs_inplace::<Section64<LE>>(
+                        exec_data,
+                        offset + mem::size_of_val(info),
+                        info.nsects.get(LE) as usize,
+                    );
+
+                    commands.segments.push(Segment {
+                        command: offset,
+                        info,
+                        sections,
+                    });
+                }
+                macho::LC_LOAD_DYLIB
+                | macho::LC_LOAD_WEAK_DYLIB
+                | macho::LC_REEXPORT_DYLIB
+                | macho::LC_LOAD_UPWARD_DYLIB
+                | macho::LC_LAZY_LOAD_DYLIB => {
+                    dylib_ordinal += 1;
+
+                    let info = load_struct_inplace::<DylibCommand<LE>>(exec_data, offset);
+                    let name_offset = offset + info.dylib.name.offset.get(LE) as usize;
+                    let path =
+                        CStr::from_bytes_until_nul(&exec_data[name_offset..offset + cmdsize])
+                            .ok()
+                            .and_then(|name| name.to_str().ok())
+                            .map(Path::new);
+
+                    if cmd == macho::LC_LOAD_DYLIB
+                        && path.and_then(Path::file_name) == shared_lib.file_name()
+                    {
+                        commands.app_dylib = Some((offset, dylib_ordinal));
+                    }
+                }
+                macho::LC_SYMTAB => {
+                    let info = load_struct_inplace::<SymtabCommand<LE>>(exec_data, offset);
+                    let symbol_size = mem::size_of::<macho::Nlist64<LE>>() as u32;
+
+                    commands.linkedit_piece(
+                        offset + mem::offset_of!(SymtabCommand<LE>, symoff),
+                        info.symoff.get(LE),
+                        info.nsyms.get(LE) * symbol_size,
+                        LinkeditKind::Plain,
+                    );
+                    commands.linkedit_piece(
+                        offset + mem::offset_of!(SymtabCommand<LE>, stroff),
+                        info.stroff.get(LE),
+                        info.strsize.get(LE),
+                        LinkeditKind::Plain,
+                    );
+                    commands.symtab = Some(info);
+                }
+                macho::LC_DYSYMTAB => {
+                    let info = load_struct_inplace::<DysymtabCommand<LE>>(exec_data, offset);
+
+                    if info.ntoc.get(LE) != 0
+                        || info.nmodtab.get(LE) != 0
+                        || info.nextrefsyms.get(LE) != 0
+                        || info.nextrel.get(LE) != 0
+                        || info.nlocrel.get(LE) != 0
+                    {
+                        unsupported!("it has classic relocations or a table of contents");
+                    }
+
+                    commands.linkedit_piece(
+                        offset + mem::offset_of!(DysymtabCommand<LE>, indirectsymoff),
+                        info.indirectsymoff.get(LE),
+                        info.nindirectsyms.get(LE) * 4,
+                        LinkeditKind::Plain,
+                    );
+                    commands.dysymtab = Some(info);
+                }
+                macho::LC_DYLD_INFO | macho::LC_DYLD_INFO_ONLY => {
+                    let info = load_struct_inplace::<DyldInfoCommand<LE>>(exec_data, offset);
+
+                    for (field, data_offset, size, kind) in [
+                        (
+                            mem::offset_of!(DyldInfoCommand<LE>, rebase_off),
+                            info.rebase_off,
+                            info.rebase_size,
+                            LinkeditKind::RebaseOpcodes,
+                        ),
+                        (
+                            mem::offset_of!(DyldInfoCommand<LE>, bind_off),
+                            info.bind_off,
+                            info.bind_size,
+                            LinkeditKind::Plain,
+                        ),
+                        (
+                            mem::offset_of!(DyldInfoCommand<LE>, weak_bind_off),
+                            info.weak_bind_off,
+                            info.weak_bind_size,
+                            LinkeditKind::Plain,
+                        ),
+                        (
+                            mem::offset_of!(DyldInfoCommand<LE>, lazy_bind_off),
+                            info.lazy_bind_off,
+                            info.lazy_bind_size,
+                            LinkeditKind::Plain,
+                        ),
+                        (
+                            mem::offset_of!(DyldInfoCommand<LE>, export_off),
+                            info.export_off,
+                            info.export_size,
+                            LinkeditKind::Plain,
+                        ),
+                    ] {
+                        commands.linkedit_piece(
+                            offset + field,
+                            data_offset.get(LE),
+                            size.get(LE),
+                            kind,
+                        );
+                    }
+                    commands.dyld_info = Some(info);
+                }
+                macho::LC_CODE_SIGNATURE => {
+                    commands.code_signature = Some(offset);
+                }
+                macho::LC_DYLD_CHAINED_FIXUPS
+                | macho::LC_DYLD_EXPORTS_TRIE
+                | macho::LC_FUNCTION_STARTS
+                | macho::LC_DATA_IN_CODE
+                | macho::LC_SEGMENT_SPLIT_INFO
+                | macho::LC_DYLIB_CODE_SIGN_DRS
+                | macho::LC_LINKER_OPTIMIZATION_HINT => {
+                    let info = load_struct_inplace::<LinkeditDataCommand<LE>>(exec_data, offset);
+                    let kind = if cmd == macho::LC_DYLD_CHAINED_FIXUPS {
+                        LinkeditKind::ChainedFixups
+                    } else {
+                        LinkeditKind::Plain
+                    };
+
+                    commands.linkedit_piece(
+                        offset + mem::offset_of!(LinkeditDataCommand<LE>, dataoff),
+                        info.dataoff.get(LE),
+                        info.datasize.get(LE),
+                        kind,
+                    );
+
+                    match cmd {
+                        macho::LC_DYLD_CHAINED_FIXUPS => commands.chained_fixups = Some(info),
+                        macho::LC_DATA_IN_CODE => commands.data_in_code = Some(info),
+                        _ => {}
+                    }
+                }
+                _ => {}
+            }
+
+            offset += cmdsize;
+        }
+
+        commands.end = offset;
+
+        commands
+    }
+
+    fn linkedit_piece(&mut self, offset_field: usize, offset: u32, size: u32, kind: LinkeditKind) {
+        // zero-sized data can stay wherever it claims to be, as long as that is 0
+        if offset != 0 || size != 0 {
+            self.linkedit_pieces.push(LinkeditPiece {
+                offset_field,
+                offset,
+                size,
+                kind,
+            });
+        }
+    }
+}
+
+struct Symtab<'a> {
+    symbols: &'a [macho::Nlist64<LE>],
+    strings: &'a [u8],
+    indirect_symbols: &'a [u8],
+}
+
+impl<'a> Symtab<'a> {
+    fn new(exec_data: &'a [u8], commands: &LoadCommands<'a>) -> Self {
+        let (Some(symtab), Some(dysymtab)) = (commands.symtab, commands.dysymtab) else {
+            unsupported!("it has no symbol table");
+        };
+
+        let strings_start = symtab.stroff.get(LE) as usize;
+        let indirect_start = dysymtab.indirectsymoff.get(LE) as usize;
+
+        Self {
+            symbols: load_structs_inplace(
+                exec_data,
+                symtab.symoff.get(LE) as usize,
+                symtab.nsyms.get(LE) as usize,
+            ),
+            strings: &exec_data[strings_start..strings_start + symtab.strsize.get(LE) as usize],
+            indirect_symbols: &exec_data
+                [indirect_start..indirect_start + dysymtab.nindirectsyms.get(LE) as usize * 4],
+        }
+    }
+
+    /// The name of a symbol, and the ordinal of the library it is imported from,
+    /// which is 0 for symbols that the host defines
+    fn symbol(&self, index: u32) -> (&'a str, u32) {
+        let nlist = &self.symbols[index as usize];
+        let name = c_str_at(self.strings, nlist.n_strx.get(LE) as usize);
+
+        let ordinal = if nlist.n_type & macho::N_TYPE == macho::N_UNDF {
+            u32::from(nlist.n_desc.get(LE) >> 8)
+        } else {
+            0
+        };
+
+        (name, ordinal)
+    }
+
+    /// The symbol of an entry in a stub or pointer section. Entries for local symbols say so
+    /// instead of pointing at one.
+    fn indirect_symbol(&self, index: u32) -> Option<u32> {
+        let symbol = read_u32(self.indirect_symbols, index as usize * 4);
+
+        (symbol & (macho::INDIRECT_SYMBOL_LOCAL | macho::INDIRECT_SYMBOL_ABS) == 0)
+            .then_some(symbol)
+    }
+}
+
+pub(crate) fn preprocess_macho_arm64(
+    host_exe_path: &Path,
+    metadata_path: &Path,
+    preprocessed_path: &Path,
+    shared_lib: &Path,
+    verbose: bool,
+    time: bool,
+) {
+    let total_start = Instant::now();
+    let exec_parsing_start = total_start;
+    let exec_data = &*open_mmap(host_exe_path);
+    let exec_obj = match object::File::parse(exec_data) {
+        Ok(obj) => obj,
+        Err(err) => {
+            internal_error!("Failed to parse executable file: {}", err);
+        }
+    };
+
+    if exec_obj.architecture() != object::Architecture::Aarch64 {
+        internal_error!("{} is not an arm64 executable", host_exe_path.display());
+    }
+
+    let header = load_struct_inplace::<macho::MachHeader64<LE>>(exec_data, 0);
+    if header.filetype.get(LE) != macho::MH_EXECUTE {
+        unsupported!("it is not an executable");
+    }
+
+    let commands = LoadCommands::parse(exec_data, shared_lib);
+    let symtab = Symtab::new(exec_data, &commands);
+
+    let Some((_, app_ordinal)) = commands.app_dylib else {
+        internal_error!("Host does not link library `{}`!", shared_lib.display());
+    };
+
+    match commands.segments.last() {
+        Some(segment) if segment.name() == b"__LINKEDIT" => {}
+        _ => unsupported!("__LINKEDIT is not its last segment"),
+    }
+
+    let Some(text_segment) = commands.segments.iter().find(|seg| seg.name() == b"__TEXT") else {
+        unsupported!("it has no __TEXT segment");
+    };
+
+    let exec_parsing_duration = exec_parsing_start.elapsed();
+
+    let symbol_processing_start = Instant::now();
+
+    let mut md = Metadata {
+        app_symbols: Vec::new(),
+        patches: Vec::new(),
+        roc_symbol_vaddresses: collect_roc_definitions(&exec_obj),
+        host_stubs: MutMap::default(),
+        host_got: MutMap::default(),
+        fixups: Fixups::Opcodes,
+        image_base: text_segment.info.vmaddr.get(LE),
+        text_segment: (
+            text_segment.info.fileoff.get(LE),
+            text_segment.info.filesize.get(LE),
+        ),
+        roc_segments: [0; 3],
+        roc_segment_index: (commands.segments.len() - 1) as u8,
+        linkedit_segment: 0,
+        code_signature: 0,
+        linkedit_pieces: Vec::new(),
+    };
+
+    if verbose {
+        println!(
+            "Found roc symbol definitions: {:+x?}",
+            md.roc_symbol_vaddresses
+        );
+    }
+
+    // Find the stubs and slots of the symbols the host imports, through the indirect symbol
+    // table that every stub and pointer section has a range of.
+    let mut app_stubs: MutMap<u64, &str> = MutMap::default();
+    let mut app_slots: MutMap<u64, &str> = MutMap::default();
+
+    for section in commands.segments.iter().flat_map(|seg| seg.sections) {
+        let section_type = section.flags.get(LE) & macho::SECTION_TYPE;
+        let entry_size = match section_type {
+            macho::S_SYMBOL_STUBS => u64::from(section.reserved2.get(LE)),
+            macho::S_NON_LAZY_SYMBOL_POINTERS | macho::S_LAZY_SYMBOL_POINTERS => 8,
+            _ => continue,
+        };
+
+        if entry_size == 0 {
+            continue;
+        }
+
+        for i in 0..section.size.get(LE) / entry_size {
+            let Some(symbol) = symtab.indirect_symbol(section.reserved1.get(LE) + i as u32) else {
+                continue;
+            };
+            let (name, ordinal) = symtab.symbol(symbol);
+            let vaddr = section.addr.get(LE) + i * entry_size;
+            let file_offset = u64::from(section.offset.get(LE)) + i * entry_size;
+
+            match (section_type, ordinal == app_ordinal) {
+                (macho::S_SYMBOL_STUBS, true) => {
+                    if entry_size < 12 {
+                        unsupported!("its stubs are too small to jump to the app");
+                    }
+
+                    app_stubs.insert(vaddr, name);
+                    md.patches.push(Patch::Stub {
+                        file_offset,
+                        vaddr,
+                        symbol: name.to_string(),
+                    });
+                }
+                (macho::S_SYMBOL_STUBS, false) => {
+                    md.host_stubs.insert(name.to_string(), vaddr);
+                }
+                (_, true) => {
+                    app_slots.insert(vaddr, name);
+                }
+                (macho::S_NON_LAZY_SYMBOL_POINTERS, false) => {
+                    md.host_got.insert(name.to_string(), vaddr);
+                }
+                // a lazy pointer only holds the address of its symbol after the first call
+                (_, false) => {}
+            }
+
+            if ordinal == app_ordinal && !md.app_symbols.iter().any(|sym| sym == name) {
+                md.app_symbols.push(name.to_string());
+            }
+        }
+    }
+
+    if verbose {
+        println!();
+        println!(
+            "Symbols the host imports from the app: {:?}",
+            md.app_symbols
+        );
+        println!("App stubs: {app_stubs:+x?}");
+        println!("App slots: {app_slots:+x?}");
+    }
+
+    // Find where dyld writes the addresses of app symbols when it launches the host.
+    if let Some(info) = commands.chained_fixups {
+        let blob = linkedit_data(exec_data, info.dataoff.get(LE), info.datasize.get(LE));
+        md.fixups = chained_binds(exec_data, blob, &commands, app_ordinal, &mut md.patches);
+    } else if let Some(info) = commands.dyld_info {
+        let binds = linkedit_data(exec_data, info.bind_off.get(LE), info.bind_size.get(LE));
+
+        for (segment, offset, ordinal, name) in bind_opcode_targets(binds) {
+            let vaddr = commands.segments[segment].info.vmaddr.get(LE) + offset;
+
+            if ordinal == i64::from(app_ordinal) && !app_slots.contains_key(&vaddr) {
+                unsupported!("it stores the address of `{name}` outside its global offset table");
+            }
+        }
+
+        md.fixups = Fixups::Opcodes;
+    } else {
+        unsupported!("it has neither chained fixups nor dyld info");
+    }
+
+    let symbol_processing_duration = symbol_processing_start.elapsed();
+
+    // Find the calls of app stubs, and the loads of app slots, in the code of the host.
+    let text_disassembly_start = Instant::now();
+
+    let data_in_code = match commands.data_in_code {
+        Some(info) => data_in_code_ranges(linkedit_data(
+            exec_data,
+            info.dataoff.get(LE),
+            info.datasize.get(LE),
+        )),
+        None => Vec::new(),
+    };
+
+    for segment in commands.segments.iter() {
+        if segment.info.initprot.get(LE) & macho::VM_PROT_EXECUTE == 0 {
+            continue;
+        }
+
+        for section in segment.sections {
+            let flags = section.flags.get(LE);
+
+            if flags & macho::SECTION_TYPE == macho::S_SYMBOL_STUBS
+                || flags & (macho::S_ATTR_PURE_INSTRUCTIONS | macho::S_ATTR_SOME_INSTRUCTIONS) == 0
+            {
+                continue;
+            }
+
+            scan_code_section(
+                exec_data,
+                section,
+                &data_in_code,
+                &app_stubs,
+                // hosts with chained fixups get a rebase in every app slot instead
+                (md.fixups == Fixups::Opcodes).then_some(&app_slots),
+                &mut md.patches,
+            );
+        }
+    }
+
+    let text_disassembly_duration = text_disassembly_start.elapsed();
+
+    if verbose {
+        println!();
+        println!("Patches: {:+x?}", md.patches);
+    }
+
+    let platform_gen_start = Instant::now();
+
+    let out_mmap = gen_macho_arm64(exec_data, &commands, &mut md, preprocessed_path);
+
+    let platform_gen_duration = platform_gen_start.elapsed();
+
+    if verbose {
+        println!();
+        println!("{md:+x?}");
+    }
+
+    let saving_metadata_start = Instant::now();
+    md.write_to_file(metadata_path);
+    let saving_metadata_duration = saving_metadata_start.elapsed();
+
+    let flushing_data_start = Instant::now();
+    out_mmap
+        .flush()
+        .unwrap_or_else(|e| internal_error!("{}", e));
+    // Also drop files to to ensure data is fully written here.
+    drop(out_mmap);
+    let flushing_data_duration = flushing_data_start.elapsed();
+
+    let total_duration = total_start.elapsed();
+
+    if verbose || time {
+        println!();
+        println!("Timings");
+        report_timing("Executable Parsing", exec_parsing_duration);
+        report_timing("Symbol Processing", symbol_processing_duration);
+        report_timing("Text Disassembly", text_disassembly_duration);
+        report_timing("Generate Modified Platform", platform_gen_duration);
+        report_timing("Saving Metadata", saving_metadata_duration);
+        report_timing("Flushing Data to Disk", flushing_data_duration);
+        report_timing(
+            "Other",
+            total_duration
+                - exec_parsing_duration
+                - symbol_processing_duration
+                - text_disassembly_duration
+                - platform_gen_duration
+                - saving_metadata_duration
+                - flushing_data_duration,
+        );
+        report_timing("Total", total_duration);
+    }
+}
+
+/// Collects the chained fixups that bind app symbols, and returns the pointer format of the host
+fn chained_binds(
+    exec_data: &[u8],
+    blob: &[u8],
+    commands: &LoadCommands,
+    app_ordinal: u32,
+    patches: &mut Vec<Patch>,
+) -> Fixups {
+    let starts_offset = read_u32(blob, 4) as usize;
+    let imports_offset = read_u32(blob, 8) as usize;
+    let symbols_offset = read_u32(blob, 12) as usize;
+    let imports_format = read_u32(blob, 20);
+
+    if read_u32(blob, 24) != 0 {
+        unsupported!("its chained fixups compress the names of imports");
+    }
+
+    // the library ordinal, name and addend of an import
+    let import = |ordinal: u64| {
+        let ordinal = ordinal as usize;
+
+        let (lib_ordinal, name_offset, addend) = match imports_format {
+            DYLD_CHAINED_IMPORT => {
+                let import = read_u32(blob, imports_offset + 4 * ordinal);
+                (import & 0xFF, import >> 9, 0)
+            }
+            DYLD_CHAINED_IMPORT_ADDEND => {
+                let import = read_u32(blob, imports_offset + 8 * ordinal);
+                let addend = read_u32(blob, imports_offset + 8 * ordinal + 4) as i32;
+                (import & 0xFF, import >> 9, addend as i64 as u64)
+            }
+            DYLD_CHAINED_IMPORT_ADDEND64 => {
+                let import = read_u64(blob, imports_offset + 16 * ordinal);
+                let addend = read_u64(blob, imports_offset + 16 * ordinal + 8);
+                ((import & 0xFFFF) as u32, (import >> 32) as u32, addend)
+            }
+            other => unsupported!("its chained fixups use import format {other}"),
+        };
+
+        let name = c_str_at(blob, symbols_offset + name_offset as usize);
+
+        (lib_ordinal, name, addend)
+    };
+
+    let seg_count = read_u32(blob, starts_offset) as usize;
+    if seg_count != commands.segments.len() {
+        internal_error!("The chained fixups of the host have starts for {seg_count} segments, but it has {} segments", commands.segments.len());
+    }
+
+    let mut pointer_format = None;
+
+    for (index, segment) in commands.segments.iter().enumerate() {
+        let seg_info_offset = read_u32(blob, starts_offset + 4 + 4 * index) as usize;
+
+        if seg_info_offset == 0 {
+            continue;
+        }
+
+        let starts = starts_offset + seg_info_offset;
+        let page_size = u64::from(read_u16(blob, starts + 4));
+        let format = read_u16(blob, starts + 6);
+        let page_count = read_u16(blob, starts + 20) as usize;
+
+        if format != DYLD_CHAINED_PTR_64 && format != DYLD_CHAINED_PTR_64_OFFSET {
+            unsupported!("its chained fixups use pointer format {format}");
+        }
+
+        if *pointer_format.get_or_insert(format) != format {
+            unsupported!("its chained fixups mix pointer formats");
+        }
+
+        for page in 0..page_count {
+            let page_start = read_u16(blob, starts + 22 + 2 * page);
+
+            if page_start == DYLD_CHAINED_PTR_START_NONE {
+                continue;
+            }
+
+            if page_start & DYLD_CHAINED_PTR_START_MULTI != 0 {
+                unsupported!("its chained fixups have several chains in a page");
+            }
+
+            let mut file_offset =
+                segment.info.fileoff.get(LE) + page as u64 * page_size + u64::from(page_start);
+
+            loop {
+                let pointer = read_u64(exec_data, file_offset as usize);
+
+                if pointer >> 63 == 1 {
+                    let (lib_ordinal, name, addend) = import(pointer & 0xFF_FFFF);
+
+                    if lib_ordinal == app_ordinal {
+                        patches.push(Patch::ChainedBind {
+                            file_offset,
+                            symbol: name.to_string(),
+                            addend: addend.wrapping_add((pointer >> 24) & 0xFF),
+                        });
+                    }
+                }
+
+                let next = (pointer >> 51) & 0xFFF;
+                if next == 0 {
+                    break;
+                }
+
+                file_offset += next * CHAINED_POINTER_STRIDE;
+            }
+        }
+    }
+
+    Fixups::Chained {
+        pointer_format: pointer_format.unwrap_or(DYLD_CHAINED_PTR_64_OFFSET),
+    }
+}
+
+/// The segment index, offset in that segment, library ordinal and name of every bind
+fn bind_opcode_targets(opcodes: &[u8]) -> Vec<(usize, u64, i64, &str)> {
+    let mut binds = Vec::new();
+
+    let mut segment = 0;
+    let mut offset = 0u64;
+    let mut ordinal = 0;
+    let mut name = "";
+
+    let mut pos = 0;
+    while pos < opcodes.len() {
+        let byte = opcodes[pos];
+        let immediate = byte & macho::BIND_IMMEDIATE_MASK;
+        pos += 1;
+
+        match byte & macho::BIND_OPCODE_MASK {
+            // the lazy bind opcodes put one of these after every symbol
+            macho::BIND_OPCODE_DONE => {}
+            macho::BIND_OPCODE_SET_DYLIB_ORDINAL_IMM => ordinal = i64::from(immediate),
+            macho::BIND_OPCODE_SET_DYLIB_ORDINAL_ULEB => {
+                ordinal = read_uleb128(opcodes, &mut pos) as i64;
+            }
+            macho::BIND_OPCODE_SET_DYLIB_SPECIAL_IMM => {
+                // the special ordinals are small negative numbers
+                ordinal = if immediate == 0 {
+                    0
+                } else {
+                    i64::from((immediate | macho::BIND_OPCODE_MASK) as i8)
+                };
+            }
+            macho::BIND_OPCODE_SET_SYMBOL_TRAILING_FLAGS_IMM => {
+                name = c_str_at(opcodes, pos);
+                pos += name.len() + 1;
+            }
+            macho::BIND_OPCODE_SET_TYPE_IMM => {}
+            macho::BIND_OPCODE_SET_ADDEND_SLEB => {
+                read_uleb128(opcodes, &mut pos);
+            }
+            macho::BIND_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB => {
+                segment = immediate as usize;
+                offset = read_uleb128(opcodes, &mut pos);
+            }
+            macho::BIND_OPCODE_ADD_ADDR_ULEB => {
+                offset = offset.wrapping_add(read_uleb128(opcodes, &mut pos));
+            }
+            macho::BIND_OPCODE_DO_BIND => {
+                binds.push((segment, offset, ordinal, name));
+                offset += 8;
+            }
+            macho::BIND_OPCODE_DO_BIND_ADD_ADDR_ULEB => {
+                binds.push((segment, offset, ordinal, name));
+                offset = offset.wrapping_add(read_uleb128(opcodes, &mut pos) + 8);
+            }
+            macho::BIND_OPCODE_DO_BIND_ADD_ADDR_IMM_SCALED => {
+                binds.push((segment, offset, ordinal, name));
+                offset += u64::from(immediate) * 8 + 8;
+            }
+            macho::BIND_OPCODE_DO_BIND_ULEB_TIMES_SKIPPING_ULEB => {
+                let count = read_uleb128(opcodes, &mut pos);
+                let skip = read_uleb128(opcodes, &mut pos);
+
+                for _ in 0..count {
+                    binds.push((segment, offset, ordinal, name));
+                    offset = offset.wrapping_add(skip + 8);
+                }
+            }
+            _ => unsupported!("its bind opcodes use opcode {byte:#x}"),
+        }
+    }
+
+    binds
+}
+
+/// The file offsets of the data that the linker put among the instructions of the host
+fn data_in_code_ranges(entries: &[u8]) -> Vec<std::ops::Range<u64>> {
+    // each entry is a u32 offset, a u16 length, and a u16 kind
+    entries
+        .chunks_exact(8)
+        .map(|entry| {
+            let start = u64::from(read_u32(entry, 0));
+            start..start + u64::from(read_u16(entry, 4))
+        })
+        .collect()
+}
+
+fn scan_code_section(
+    exec_data: &[u8],
+    section: &macho::Section64<LE>,
+    data_in_code: &[std::ops::Range<u64>],
+    app_stubs: &MutMap<u64, &str>,
+    app_slots: Option<&MutMap<u64, &str>>,
+    patches: &mut Vec<Patch>,
+) {
+    let section_offset = u64::from(section.offset.get(LE));
+    let section_vaddr = section.addr.get(LE);
+    let instructions = section.size.get(LE) / 4;
+
+    // the last `adrp` that wrote each register, and the instruction it is
+    let mut pages: [Option<(u64, u64)>; 32] = [None; 32];
+
+    for i in 0..instructions {
+        let file_offset = section_offset + 4 * i;
+        let vaddr = section_vaddr + 4 * i;
+
+        if data_in_code
+            .iter()
+            .any(|range| range.contains(&file_offset))
+        {
+            pages = [None; 32];
+            continue;
+        }
+
+        let insn = read_u32(exec_data, file_offset as usize);
+
+        if is_branch(insn) {
+            if let Some(symbol) = app_stubs.get(&branch_target(insn, vaddr)) {
+                patches.push(Patch::Branch {
+                    file_offset,
+                    vaddr,
+                    symbol: symbol.to_string(),
+                });
+            }
+        } else if is_adrp(insn) {
+            pages[(insn & 0x1F) as usize] = Some((i, adrp_page(insn, vaddr)));
+        } else if let (Some(app_slots), Some(load)) = (app_slots, decode_ldr_x(insn)) {
+            let Some((adrp, page)) = pages[load.rn as usize] else {
+                continue;
+            };
+
+            if i - adrp > ADRP_LOOKBACK as u64 {
+                continue;
+            }
+
+            if let Some(symbol) = app_slots.get(&(page + load.offset)) {
+                if adrp + 1 != i || load.rt != load.rn {
+                    unsupported!("it loads the address of `{symbol}` in a way that the linker cannot change to use the app");
+                }
+
+                patches.push(Patch::GotLoad {
+                    file_offset: file_offset - 4,
+                    vaddr: vaddr - 4,
+                    symbol: symbol.to_string(),
+                });
+            }
+        }
+    }
+}
+
+fn gen_macho_arm64(
+    exec_data: &[u8],
+    commands: &LoadCommands,
+    md: &mut Metadata,
+    preprocessed_path: &Path,
+) -> MmapMut {
+    use macho::{LinkeditDataCommand, MachHeader64, Section64, SegmentCommand64};
+
+    let start_of_first_section = commands
+        .segments
+        .iter()
+        .flat_map(|seg| seg.sections)
+        .filter(|section| section.offset.get(LE) != 0 && section.size.get(LE) != 0)
+        .map(|section| section.offset.get(LE) as usize)
+        .min()
+        .unwrap_or_else(|| {
+            internal_error!("Could not establish offset of first section following load commands")
+        });
+
+    // The new segments go right before __LINKEDIT, so that they are right before it in memory too.
+    let insert_at = commands.segments[commands.segments.len() - 1].command;
+    let segments_size = headerpad_size();
+    let signature_size = match commands.code_signature {
+        Some(_) => 0,
+        None => mem::size_of::<LinkeditDataCommand<LE>>(),
+    };
+    let required_size = segments_size + signature_size;
+
+    if start_of_first_section - commands.end < required_size {
+        // Inform the user that the host has to be relinked with `-headerpad <size>` flag so that the surgical linker
+        // can add Roc specific load commands and not clobber the first section.
+        let optimal_headerpad_size = align_by_constraint(required_size, PAGE_SIZE as usize);
+        internal_error!("Not enough free space between end of load commands and start of first section in the host.
+            Consider recompiling the host with \"-Wl,-headerpad,0x{optimal_headerpad_size:x}\" linker flag.");
+    }
+
+    let mut out_mmap = open_mmap_mut(preprocessed_path, exec_data.len());
+
+    out_mmap[..insert_at].copy_from_slice(&exec_data[..insert_at]);
+    out_mmap[insert_at + segments_size..commands.end + segments_size]
+        .copy_from_slice(&exec_data[insert_at..commands.end]);
+    out_mmap[commands.end + segments_size..start_of_first_section].fill(0);
+    out_mmap[start_of_first_section..].copy_from_slice(&exec_data[start_of_first_section..]);
+
+    let moved = |offset: usize| {
+        if offset >= insert_at {
+            offset + segments_size
+        } else {
+            offset
+        }
+    };
+
+    let mut offset = insert_at;
+    for (index, (segname, prot, flags, sections)) in ROC_SEGMENTS.into_iter().enumerate() {
+        md.roc_segments[index] = offset;
+        set_segment_load_command(
+            &mut out_mmap,
+            offset,
+            segname,
+            prot,
+            flags,
+            sections.len() as u32,
+        );
+        offset += mem::size_of::<SegmentCommand64<LE>>();
+
+        for (sectname, flags) in sections {
+            set_section_load_command(&mut out_mmap, offset, sectname, segname, *flags);
+            offset += mem::size_of::<Section64<LE>>();
+        }
+    }
+    debug_assert_eq!(offset, insert_at + segments_size);
+
+    md.code_signature = match commands.code_signature {
+        Some(offset) => moved(offset),
+        None => {
+            let offset = commands.end + segments_size;
+            let info = load_struct_inplace_mut::<LinkeditDataCommand<LE>>(&mut out_mmap, offset);
+            info.cmd.set(LE, macho::LC_CODE_SIGNATURE);
+            info.cmdsize.set(LE, signature_size as u32);
+            info.dataoff.set(LE, 0);
+            info.datasize.set(LE, 0);
+
+            offset
+        }
+    };
+
+    // A weak load lets the executable run without libapp.dylib, and keeps the ordinals of the
+    // other libraries the same.
+    if let Some((offset, _)) = commands.app_dylib {
+        load_struct_inplace_mut::<macho::LoadCommand<LE>>(&mut out_mmap, moved(offset))
+            .cmd
+            .set(LE, macho::LC_LOAD_WEAK_DYLIB);
+    }
+
+    let header = load_struct_inplace_mut::<MachHeader64<LE>>(&mut out_mmap, 0);
+    let added_commands = ROC_SEGMENTS.len() as u32 + u32::from(signature_size != 0);
+    header.ncmds.set(LE, header.ncmds.get(LE) + added_commands);
+    header
+        .sizeofcmds
+        .set(LE, header.sizeofcmds.get(LE) + required_size as u32);
+
+    md.linkedit_segment = moved(insert_at);
+    md.linkedit_pieces = commands
+        .linkedit_pieces
+        .iter()
+        .map(|piece| LinkeditPiece {
+            offset_field: moved(piece.offset_field),
+            ..*piece
+        })
+        .collect();
+
+    out_mmap
+}
+
+/// Where a `__ROC_*` segment goes in the output
+#[derive(Debug, Default, Clone, Copy)]
+struct RocSegment {
+    vmaddr: u64,
+    vmsize: u64,
+    fileoff: u64,
+    filesize: u64,
+}
+
+impl RocSegment {
+    fn contains(&self, vaddr: u64) -> bool {
+        (self.vmaddr..self.vmaddr + self.vmsize).contains(&vaddr)
+    }
+}
+
+/// Where the `__ROC_*` sections go in the output
+#[derive(Debug, Default, Clone, Copy)]
+struct RocSection {
+    addr: u64,
+    size: u64,
+    align: u64,
+}
+
+/// The address of an app section in the output, and in the object file of the app
+#[derive(Debug, Clone, Copy)]
+struct PlacedSection {
+    vaddr: u64,
+    address: u64,
+}
+
+enum Resolved {
+    Address(u64),
+    /// The slot of a symbol that the host imports from another library
+    Slot(u64),
+}
+
+struct App<'a> {
+    obj: &'a object::File<'a>,
+    md: &'a Metadata,
+    sections: MutMap<SectionIndex, PlacedSection>,
+    symbols: MutMap<SymbolIndex, u64>,
+}
+
+impl App<'_> {
+    fn resolve(&self, index: SymbolIndex) -> Resolved {
+        if let Some(&vaddr) = self.symbols.get(&index) {
+            return Resolved::Address(vaddr);
+        }
+
+        let symbol = self
+            .obj
+            .symbol_by_index(index)
+            .unwrap_or_else(|e| internal_error!("Failed to find symbol {index:?}: {e}"));
+        let name = symbol.name().unwrap_or_default();
+
+        if symbol.is_definition() {
+            unsupported!("the app defines `{name}` in a section that the linker does not support");
+        }
+
+        if let Some(&vaddr) = self
+            .md
+            .roc_symbol_vaddresses
+            .get(name.trim_start_matches('_'))
+        {
+            Resolved::Address(vaddr)
+        } else if let Some(&vaddr) = self.md.host_stubs.get(name) {
+            Resolved::Address(vaddr)
+        } else if let Some(&vaddr) = self.md.host_got.get(name) {
+            Resolved::Slot(vaddr)
+        } else {
+            unsupported!("the app uses `{name}`, which the host neither defines nor imports");
+        }
+    }
+
+    fn address(&self, index: SymbolIndex) -> u64 {
+        match self.resolve(index) {
+            Resolved::Address(vaddr) => vaddr,
+            Resolved::Slot(_) => {
+                let name = self.symbol_name(index);
+                unsupported!("the app needs the address of `{name}`, which only the global offset table of the host has");
+            }
+        }
+    }
+
+    fn symbol_name(&self, index: SymbolIndex) -> &str {
+        self.obj
+            .symbol_by_index(index)
+            .ok()
+            .and_then(|sym| sym.name().ok())
+            .unwrap_or_default()
+    }
+}
+
+pub(crate) fn surgery_macho_arm64(
+    roc_app_bytes: &[u8],
+    metadata_path: &Path,
+    executable_path: &Path,
+    verbose: bool,
+    time: bool,
+) {
+    let app_obj = match object::File::parse(roc_app_bytes) {
+        Ok(obj) => obj,
+        Err(err) => {
+            internal_error!("Failed to parse application file: {}", err);
+        }
+    };
+
+    let total_start = Instant::now();
+
+    let loading_metadata_start = total_start;
+    let md = Metadata::read_from_file(metadata_path);
+    let loading_metadata_duration = loading_metadata_start.elapsed();
+
+    let load_and_mmap_start = Instant::now();
+    let exec_mmap = open_mmap(executable_path);
+    let exec_data = &*exec_mmap;
+
+    // The kernel remembers the code signature of a file, and kills processes that do not match
+    // it, so writing over the executable would make the new one fail to launch even when it is
+    // signed correctly. The mapping keeps the old contents while we write a new file.
+    std::fs::remove_file(executable_path).unwrap_or_else(|e| internal_error!("{}", e));
+    let load_and_mmap_duration = load_and_mmap_start.elapsed();
+
+    let out_gen_start = Instant::now();
+    let identifier = executable_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("a.out");
+
+    let out_mmap = surgery_macho_arm64_help(
+        exec_data,
+        &md,
+        &app_obj,
+        executable_path,
+        identifier,
+        verbose,
+    );
+
+    let out_gen_duration = out_gen_start.elapsed();
+    let flushing_data_start = Instant::now();
+
+    out_mmap
+        .flush()
+        .unwrap_or_else(|e| internal_error!("{}", e));
+    // Also drop files to to ensure data is fully written here.
+    drop(out_mmap);
+
+    let flushing_data_duration = flushing_data_start.elapsed();
+
+    // Make sure the final executable has permision to execute.
+    #[cfg(target_family = "unix")]
+    {
+        use std::fs;
+        use std::os::unix::fs::PermissionsExt;
+
+        let mut perms = fs::metadata(executable_path)
+            .unwrap_or_else(|e| internal_error!("{}", e))
+            .permissions();
+        perms.set_mode(perms.mode() | 0o111);
+        fs::set_permissions(executable_path, perms).unwrap_or_else(|e| internal_error!("{}", e));
+    }
+
+    let total_duration = total_start.elapsed();
+
+    if verbose || time {
+        println!("\nTimings");
+        report_timing("Loading Metadata", loading_metadata_duration);
+        report_timing("Loading and mmap-ing", load_and_mmap_duration);
+        report_timing("Output Generation", out_gen_duration);
+        report_timing("Flushing Data to Disk", flushing_data_duration);
+
+        let sum = loading_metadata_duration
+            + load_and_mmap_duration
+            + out_gen_duration
+            + flushing_data_duration;
+
+        report_timing("Other", total_duration.saturating_sub(sum));
+        report_timing("Total", total_duration);
+    }
+}
+
+fn surgery_macho_arm64_help(
+    exec_data: &[u8],
+    md: &Metadata,
+    app_obj: &object::File,
+    executable_path: &Path,
+    identifier: &str,
+    verbose: bool,
+) -> MmapMut {
+    let linkedit =
+        load_struct_inplace::<macho::SegmentCommand64<LE>>(exec_data, md.linkedit_segment);
+    let base_fileoff = linkedit.fileoff.get(LE);
+
+    // text, const, data and bss, in the order they go in
+    let mut groups: [Vec<Section>; 4] = Default::default();
+
+    for section in app_obj.sections() {
+        let name = section.name().unwrap_or_default();
+        if name == "__eh_frame" || name == "__compact_unwind" {
+            continue;
+        }
+
+        let group = match section.kind() {
+            SectionKind::Text => 0,
+            SectionKind::ReadOnlyData
+            | SectionKind::ReadOnlyDataWithRel
+            | SectionKind::ReadOnlyString => 1,
+            SectionKind::Data => 2,
+            SectionKind::UninitializedData | SectionKind::Common => 3,
+            SectionKind::Tls | SectionKind::UninitializedTls | SectionKind::TlsVariables => {
+                unsupported!("the app uses thread-local storage");
+            }
+            _ => continue,
+        };
+
+        groups[group].push(section);
+    }
+
+    // First decide on sections locations and then recode every exact symbol locations.
+    let mut segments = [RocSegment::default(); 3];
+    let mut roc_sections = [RocSection::default(); 4];
+    let mut sections: MutMap<SectionIndex, PlacedSection> = MutMap::default();
+
+    let mut vmaddr = linkedit.vmaddr.get(LE);
+    let mut fileoff = base_fileoff;
+
+    for (segment, segment_groups) in segments.iter_mut().zip([0..1, 1..2, 2..4]) {
+        let mut cursor = vmaddr;
+        let mut file_end = vmaddr;
+
+        for group in segment_groups {
+            let mut align = 1;
+            let mut start = None;
+
+            for section in groups[group].iter() {
+                align = align.max(section.align().max(1));
+                cursor =
+                    align_by_constraint(cursor as usize, section.align().max(1) as usize) as u64;
+                start.get_or_insert(cursor);
+
+                sections.insert(
+                    section.index(),
+                    PlacedSection {
+                        vaddr: cursor,
+                        address: section.address(),
+                    },
+                );
+                cursor += section.size();
+            }
+
+            let start = start.unwrap_or(cursor);
+            roc_sections[group] = RocSection {
+                addr: start,
+                size: cursor - start,
+                align,
+            };
+
+            if group != 3 {
+                file_end = cursor;
+            }
+        }
+
+        let filesize = align_by_constraint((file_end - vmaddr) as usize, PAGE_SIZE as usize) as u64;
+        let vmsize = align_by_constraint((cursor - vmaddr) as usize, PAGE_SIZE as usize) as u64;
+
+        *segment = RocSegment {
+            vmaddr,
+            vmsize: vmsize.max(PAGE_SIZE),
+            fileoff,
+            filesize,
+        };
+
+        vmaddr += segment.vmsize;
+        fileoff += filesize;
+    }
+
+    if verbose {
+        println!();
+        println!("Roc segments: {segments:+x?}");
+        println!("Roc sections: {roc_sections:+x?}");
+    }
+
+    let mut symbols: MutMap<SymbolIndex, u64> = MutMap::default();
+    let mut app_vaddrs: MutMap<&str, u64> = MutMap::default();
+
+    for symbol in app_obj.symbols() {
+        if let SymbolSection::Section(index) = symbol.section() {
+            if let Some(placed) = sections.get(&index) {
+                let vaddr = placed.vaddr + symbol.address() - placed.address;
+                symbols.insert(symbol.index(), vaddr);
+
+                if symbol.is_global() {
+                    if let Ok(name) = symbol.name() {
+                        app_vaddrs.insert(name, vaddr);
+                    }
+                }
+            }
+        }
+    }
+
+    let app = App {
+        obj: app_obj,
+        md,
+        sections,
+        symbols,
+    };
+
+    // Copy sections and resolve their relocations.
+    let mut roc_data = vec![0; (fileoff - base_fileoff) as usize];
+    let mut rebases = Vec::new();
+
+    for section in groups[..3].iter().flatten() {
+        let placed = app.sections[&section.index()];
+        let segment = segments
+            .iter()
+            .rev()
+            .find(|segment| segment.vmaddr <= placed.vaddr)
+            .unwrap();
+        let start = segment.fileoff - base_fileoff + placed.vaddr - segment.vmaddr;
+        let data = section
+            .data()
+            .unwrap_or_else(|e| internal_error!("Failed to load data for section: {e}"));
+
+        // Only the bss segment differs between file and memory layout, and it comes last.
+        roc_data[start as usize..start as usize + data.len()].copy_from_slice(data);
+
+        for (offset, relocation) in section.relocations() {
+            let place = (start + offset) as usize;
+            let pc = placed.vaddr + offset;
+
+            relocate(&app, &mut roc_data, place, pc, &relocation, &mut rebases);
+        }
+    }
+
+    for &vaddr in rebases.iter() {
+        if !segments[1].contains(vaddr) && !segments[2].contains(vaddr) {
+            unsupported!("the app has an absolute address in its code");
+        }
+    }
+    rebases.sort_unstable();
+
+    // The new segments need fixups for the absolute addresses in the app, which are the
+    // addresses that the linker would have put in the executable, before dyld slides it.
+    let mut chained_starts = [None, None, None];
+    let mut rebase_opcodes = Vec::new();
+
+    match md.fixups {
+        Fixups::Chained { pointer_format } => {
+            for (index, segment) in segments.iter().enumerate() {
+                let pointers: Vec<u64> = rebases
+                    .iter()
+                    .filter(|&&vaddr| segment.contains(vaddr))
+                    .map(|vaddr| vaddr - segment.vmaddr)
+                    .collect();
+
+                if pointers.is_empty() {
+                    continue;
+                }
+
+                let data_start = (segment.fileoff - base_fileoff) as usize;
+                chain_rebases(
+                    &mut roc_data[data_start..],
+                    &pointers,
+                    pointer_format,
+                    md.image_base,
+                );
+                chained_starts[index] = Some(chained_starts_in_segment(
+                    pointer_format,
+                    segment.vmaddr - md.image_base,
+                    segment.vmsize,
+                    &pointers,
+                ));
+            }
+        }
+        Fixups::Opcodes => {
+            for (index, segment) in segments.iter().enumerate() {
+                let segment_index = md.roc_segment_index + index as u8;
+
+                for vaddr in rebases.iter().filter(|&&vaddr| segment.contains(vaddr)) {
+                    rebase_opcodes.push((segment_index, vaddr - segment.vmaddr));
+                }
+            }
+        }
+    }
+
+    let new_linkedit_fileoff = fileoff as usize;
+    let (linkedit_data, linkedit_updates) = rebuild_linkedit(
+        exec_data,
+        md,
+        new_linkedit_fileoff,
+        &chained_starts,
+        &rebase_opcodes,
+    );
+
+    let code_limit = align_by_constraint(new_linkedit_fileoff + linkedit_data.len(), 16);
+    let signature_len = signature_size(code_limit, identifier);
+
+    let mut out_mmap = open_mmap_mut(executable_path, code_limit + signature_len);
+
+    out_mmap[..base_fileoff as usize].copy_from_slice(&exec_data[..base_fileoff as usize]);
+    out_mmap[base_fileoff as usize..new_linkedit_fileoff].copy_from_slice(&roc_data);
+    out_mmap[new_linkedit_fileoff..new_linkedit_fileoff + linkedit_data.len()]
+        .copy_from_slice(&linkedit_data);
+
+    // Update the load commands.
+    for ((segment, &command), section_groups) in
+        segments
+            .iter()
+            .zip(md.roc_segments.iter())
+            .zip([0..1, 1..2, 2..4])
+    {
+        let info = load_struct_inplace_mut::<macho::SegmentCommand64<LE>>(&mut out_mmap, command);
+        info.vmaddr.set(LE, segment.vmaddr);
+        info.vmsize.set(LE, segment.vmsize);
+        info.fileoff.set(LE, segment.fileoff);
+        info.filesize.set(LE, segment.filesize);
+
+        let mut offset = command + mem::size_of::<macho::SegmentCommand64<LE>>();
+        for group in section_groups {
+            let roc_section = roc_sections[group];
+            let info = load_struct_inplace_mut::<macho::Section64<LE>>(&mut out_mmap, offset);
+
+            info.addr.set(LE, roc_section.addr);
+            info.size.set(LE, roc_section.size);
+            info.align.set(LE, roc_section.align.trailing_zeros());
+
+            if group != 3 {
+                info.offset.set(
+                    LE,
+                    (segment.fileoff + roc_section.addr - segment.vmaddr) as u32,
+                );
+            }
+
+            offset += mem::size_of::<macho::Section64<LE>>();
+        }
+    }
+
+    let linkedit_vmaddr = segments[2].vmaddr + segments[2].vmsize;
+    let linkedit_filesize = (code_limit + signature_len - new_linkedit_fileoff) as u64;
+    let info =
+        load_struct_inplace_mut::<macho::SegmentCommand64<LE>>(&mut out_mmap, md.linkedit_segment);
+    info.vmaddr.set(LE, linkedit_vmaddr);
+    info.vmsize.set(
+        LE,
+        align_by_constraint(linkedit_filesize as usize, PAGE_SIZE as usize) as u64,
+    );
+    info.fileoff.set(LE, new_linkedit_fileoff as u64);
+    info.filesize.set(LE, linkedit_filesize);
+
+    for (offset_field, offset, size) in linkedit_updates {
+        write_u32(&mut out_mmap, offset_field, offset);
+
+        if let Some(size) = size {
+            write_u32(&mut out_mmap, offset_field + 4, size);
+        }
+    }
+
+    let info =
+        load_struct_inplace_mut::<macho::LinkeditDataCommand<LE>>(&mut out_mmap, md.code_signature);
+    info.dataoff.set(LE, code_limit as u32);
+    info.datasize.set(LE, signature_len as u32);
+
+    // Point the host at the app.
+    for patch in md.patches.iter() {
+        apply_patch(&mut out_mmap, md, &app_vaddrs, patch);
+    }
+
+    let signature = ad_hoc_signature(
+        &out_mmap[..code_limit],
+        identifier,
+        ExecSegment {
+            fileoff: md.text_segment.0,
+            filesize: md.text_segment.1,
+        },
+    );
+    out_mmap[code_limit..].copy_from_slice(&signature);
+
+    out_mmap
+}
+
+fn relocate(
+    app: &App,
+    data: &mut [u8],
+    place: usize,
+    pc: u64,
+    relocation: &object::Relocation,
+    rebases: &mut Vec<u64>,
+) {
+    let RelocationFlags::MachO {
+        r_type, r_length, ..
+    } = relocation.flags()
+    else {
+        internal_error!("Expected a Mach-O relocation, but got {relocation:?}");
+    };
+
+    let addend = relocation.addend();
+
+    let symbol = match relocation.target() {
+        RelocationTarget::Symbol(index) => Some(index),
+        RelocationTarget::Section(_) if r_type == macho::ARM64_RELOC_UNSIGNED => None,
+        target => unsupported!("the app has a relocation to {target:?}"),
+    };
+
+    let insn = || read_u32(data, place);
+    let out_of_range = || -> u32 {
+        internal_error!("The target of a relocation at {pc:#x} in the app is out of range")
+    };
+
+    match r_type {
+        macho::ARM64_RELOC_SUBTRACTOR => {
+            // object merges this into the unsigned relocation that follows it
+            unsupported!(
+                "the app has a subtraction that is not followed by an unsigned relocation"
+            );
+        }
+        macho::ARM64_RELOC_UNSIGNED => {
+            let implicit_addend = match r_length {
+                2 => i64::from(read_u32(data, place) as i32),
+                3 => read_u64(data, place) as i64,
+                _ => unsupported!("the app has an unsigned relocation of length {r_length}"),
+            };
+
+            let value = match (symbol, relocation.target()) {
+                (Some(index), _) => {
+                    let addend = if relocation.has_implicit_addend() {
+                        implicit_addend
+                    } else {
+                        addend
+                    };
+
+                    app.address(index).wrapping_add_signed(addend)
+                }
+                (None, RelocationTarget::Section(index)) => {
+                    let Some(placed) = app.sections.get(&index) else {
+                        unsupported!(
+                            "the app points into a section that the linker does not support"
+                        );
+                    };
+
+                    // the implicit addend is the address of the target in the object file
+                    (implicit_addend as u64)
+                        .wrapping_sub(placed.address)
+                        .wrapping_add(placed.vaddr)
+                }
+                _ => unreachable!(),
+            };
+
+            match r_length {
+                3 => {
+                    write_u64(data, place, value);
+                    rebases.push(pc);
+                }
+                _ => unsupported!("the app has a 32-bit absolute address"),
+            }
+        }
+        macho::ARM64_RELOC_BRANCH26 => {
+            let target = app.address(symbol.unwrap()).wrapping_add_signed(addend);
+            let insn = encode_branch(insn(), pc, target).unwrap_or_else(out_of_range);
+            write_u32(data, place, insn);
+        }
+        macho::ARM64_RELOC_PAGE21 => {
+            let target = app.address(symbol.unwrap()).wrapping_add_signed(addend);
+            let insn = encode_adrp(insn(), pc, target).unwrap_or_else(out_of_range);
+            write_u32(data, place, insn);
+        }
+        macho::ARM64_RELOC_PAGEOFF12 => {
+            let target = app.address(symbol.unwrap()).wrapping_add_signed(addend);
+            let insn = encode_pageoff12(insn(), target).unwrap_or_else(|| {
+                internal_error!(
+                    "The app has an unexpected instruction at {pc:#x} for its relocation"
+                )
+            });
+            write_u32(data, place, insn);
+        }
+        macho::ARM64_RELOC_GOT_LOAD_PAGE21 => {
+            let target = match app.resolve(symbol.unwrap()) {
+                Resolved::Address(vaddr) | Resolved::Slot(vaddr) => vaddr,
+            };
+            let insn = encode_adrp(insn(), pc, target).unwrap_or_else(out_of_range);
+            write_u32(data, place, insn);
+        }
+        macho::ARM64_RELOC_GOT_LOAD_PAGEOFF12 => {
+            let insn = match app.resolve(symbol.unwrap()) {
+                // the address is known, so it can be computed instead of loaded
+                Resolved::Address(vaddr) => decode_ldr_x(insn())
+                    .map(|load| add_x_imm(load.rt, load.rn, (vaddr & 0xFFF) as u32)),
+                Resolved::Slot(vaddr) => encode_pageoff12(insn(), vaddr),
+            };
+            let insn = insn.unwrap_or_else(|| {
+                internal_error!(
+                    "The app has an unexpected instruction at {pc:#x} for its relocation"
+                )
+            });
+            write_u32(data, place, insn);
+        }
+        _ => unsupported!(
+            "the app has a relocation of type {}",
+            super::format_reloc_type(r_type)
+        ),
+    }
+}
+
+fn apply_patch(out: &mut [u8], md: &Metadata, app_vaddrs: &MutMap<&str, u64>, patch: &Patch) {
+    let target = |symbol: &str| {
+        app_vaddrs.get(symbol).copied().unwrap_or_else(|| {
+            user_error!("The host uses `{symbol}`, but the app does not define it.")
+        })
+    };
+
+    match patch {
+        Patch::Branch {
+            file_offset,
+            vaddr,
+            symbol,
+        } => {
+            let file_offset = *file_offset as usize;
+
+            // calls that cannot reach the app directly still go through the stub
+            if let Some(insn) = encode_branch(read_u32(out, file_offset), *vaddr, target(symbol)) {
+                write_u32(out, file_offset, insn);
+            }
+        }
+        Patch::Stub {
+            file_offset,
+            vaddr,
+            symbol,
+        } => {
+            let file_offset = *file_offset as usize;
+            let target = target(symbol);
+
+            // adrp x16, target@PAGE ; add x16, x16, target@PAGEOFF ; br x16
+            let adrp = encode_adrp(0x9000_0010, *vaddr, target)
+                .unwrap_or_else(|| internal_error!("The app is too far away from the host"));
+            write_u32(out, file_offset, adrp);
+            write_u32(
+                out,
+                file_offset + 4,
+                add_x_imm(16, 16, (target & 0xFFF) as u32),
+            );
+            write_u32(out, file_offset + 8, 0xD61F_0200);
+        }
+        Patch::GotLoad {
+            file_offset,
+            vaddr,
+            symbol,
+        } => {
+            let file_offset = *file_offset as usize;
+            let target = target(symbol);
+
+            let adrp = encode_adrp(read_u32(out, file_offset), *vaddr, target)
+                .unwrap_or_else(|| internal_error!("The app is too far away from the host"));
+            let load = decode_ldr_x(read_u32(out, file_offset + 4))
+                .unwrap_or_else(|| internal_error!("Expected a load at {:#x}", vaddr + 4));
+
+            write_u32(out, file_offset, adrp);
+            write_u32(
+                out,
+                file_offset + 4,
+                add_x_imm(load.rt, load.rn, (target & 0xFFF) as u32),
+            );
+        }
+        Patch::ChainedBind {
+            file_offset,
+            symbol,
+            addend,
+        } => {
+            let Fixups::Chained { pointer_format } = md.fixups else {
+                internal_error!("A host with bind opcodes has chained fixups to patch");
+            };
+            let file_offset = *file_offset as usize;
+            let next = (read_u64(out, file_offset) >> 51) & 0xFFF;
+            let rebase = chained_rebase(
+                pointer_format,
+                md.image_base,
+                target(symbol).wrapping_add(*addend),
+                next,
+            );
+
+            write_u64(out, file_offset, rebase);
+        }
+    }
+}
+
+/// Encodes a rebase to `vaddr` in the chained fixup format of the host
+fn chained_rebase(pointer_format: u16, image_base: u64, vaddr: u64, next: u64) -> u64 {
+    let target = match pointer_format {
+        DYLD_CHAINED_PTR_64 => vaddr,
+        DYLD_CHAINED_PTR_64_OFFSET => vaddr - image_base,
+        _ => internal_error!("Unexpected chained pointer format {pointer_format}"),
+    };
+
+    debug_assert!(
+        target < 1 << 36,
+        "{target:#x} does not fit in a chained rebase"
+    );
+
+    target | (next << 51)
+}
+
+/// Turns the addresses at `pointers`, which are sorted offsets into `segment_data`, into chains
+/// of rebases, one chain per page
+fn chain_rebases(segment_data: &mut [u8], pointers: &[u64], pointer_format: u16, image_base: u64) {
+    for (i, &offset) in pointers.iter().enumerate() {
+        if offset % CHAINED_POINTER_STRIDE != 0 {
+            unsupported!("the app has an absolute address that is not aligned");
+        }
+
+        let next = match pointers.get(i + 1) {
+            Some(&next) if next / PAGE_SIZE == offset / PAGE_SIZE => {
+                (next - offset) / CHAINED_POINTER_STRIDE
+            }
+            _ => 0,
+        };
+
+        let vaddr = read_u64(segment_data, offset as usize);
+        let rebase = chained_rebase(pointer_format, image_base, vaddr, next);
+        write_u64(segment_data, offset as usize, rebase);
+    }
+}
+
+/// A `dyld_chained_starts_in_segment` for the chains that `chain_rebases` made
+fn chained_starts_in_segment(
+    pointer_format: u16,
+    segment_offset: u64,
+    vmsize: u64,
+    pointers: &[u64],
+) -> Vec<u8> {
+    let page_count = vmsize.div_ceil(PAGE_SIZE) as usize;
+    let mut page_starts = vec![DYLD_CHAINED_PTR_START_NONE; page_count];
+
+    for &offset in pointers.iter().rev() {
+        page_starts[(offset / PAGE_SIZE) as usize] = (offset % PAGE_SIZE) as u16;
+    }
+
+    let size = 22 + 2 * page_count;
+    let mut out = Vec::with_capacity(size);
+
+    out.extend((size as u32).to_le_bytes());
+    out.extend((PAGE_SIZE as u16).to_le_bytes());
+    out.extend(pointer_format.to_le_bytes());
+    out.extend(segment_offset.to_le_bytes());
+    out.extend(0u32.to_le_bytes()); // max_valid_pointer, which only 32-bit formats use
+    out.extend((page_count as u16).to_le_bytes());
+
+    for page_start in page_starts {
+        out.extend(page_start.to_le_bytes());
+    }
+
+    out
+}
+
+/// Adds the starts of the chains in the `__ROC_*` segments to the chained fixups of the host.
+/// Those segments come right before `__LINKEDIT`, which is the last segment and has no fixups.
+fn add_chained_starts(blob: &[u8], roc_starts: &[Option<Vec<u8>>; 3]) -> Vec<u8> {
+    let starts_offset = read_u32(blob, 4) as usize;
+    let imports_offset = read_u32(blob, 8) as usize;
+    let symbols_offset = read_u32(blob, 12) as usize;
+    let seg_count = read_u32(blob, starts_offset) as usize;
+
+    if !(starts_offset < imports_offset && imports_offset <= symbols_offset) {
+        unsupported!("its chained fixups are not in the order that the linker expects");
+    }
+
+    let seg_info_offset = |index: usize| read_u32(blob, starts_offset + 4 + 4 * index) as usize;
+
+    if seg_info_offset(seg_count - 1) != 0 {
+        unsupported!("its last segment has chained fixups");
+    }
+
+    let mut starts: Vec<Option<&[u8]>> = (0..seg_count - 1)
+        .map(|index| match seg_info_offset(index) {
+            0 => None,
+            offset => {
+                let start = starts_offset + offset;
+                Some(&blob[start..start + read_u32(blob, start) as usize])
+            }
+        })
+        .collect();
+    starts.extend(roc_starts.iter().map(|starts| starts.as_deref()));
+    starts.push(None);
+
+    let mut out = blob[..starts_offset].to_vec();
+    out.extend((starts.len() as u32).to_le_bytes());
+    out.resize(starts_offset + 4 + 4 * starts.len(), 0);
+
+    for (index, segment_starts) in starts.into_iter().enumerate() {
+        if let Some(segment_starts) = segment_starts {
+            out.resize(align_by_constraint(out.len(), 8), 0);
+            let seg_info_offset = (out.len() - starts_offset) as u32;
+            write_u32(&mut out, starts_offset + 4 + 4 * index, seg_info_offset);
+            out.extend(segment_starts);
+        }
+    }
+
+    out.resize(align_by_constraint(out.len(), 8), 0);
+    let new_imports_offset = out.len();
+    out.extend(&blob[imports_offset..symbols_offset]);
+    let new_symbols_offset = out.len();
+    out.extend(&blob[symbols_offset..]);
+
+    write_u32(&mut out, 8, new_imports_offset as u32);
+    write_u32(&mut out, 12, new_symbols_offset as u32);
+
+    out
+}
+
+/// Adds rebases of the pointers at the given segment indices and offsets to rebase opcodes
+fn append_rebase_opcodes(opcodes: &[u8], rebases: &[(u8, u64)]) -> Vec<u8> {
+    let mut out = opcodes[..rebase_opcodes_end(opcodes)].to_vec();
+
+    if !rebases.is_empty() {
+        out.push(macho::REBASE_OPCODE_SET_TYPE_IMM | macho::REBASE_TYPE_POINTER);
+    }
+
+    for &(segment, offset) in rebases {
+        if segment > macho::REBASE_IMMEDIATE_MASK {
+            unsupported!("it has too many segments for rebase opcodes");
+        }
+
+        out.push(macho::REBASE_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB | segment);
+        write_uleb128(&mut out, offset);
+        out.push(macho::REBASE_OPCODE_DO_REBASE_IMM_TIMES | 1);
+    }
+
+    out.push(macho::REBASE_OPCODE_DONE);
+    out.resize(align_by_constraint(out.len(), 8), macho::REBASE_OPCODE_DONE);
+
+    out
+}
+
+/// The offset of the `REBASE_OPCODE_DONE` that ends rebase opcodes
+fn rebase_opcodes_end(opcodes: &[u8]) -> usize {
+    let mut pos = 0;
+
+    while pos < opcodes.len() {
+        let opcode = opcodes[pos] & macho::REBASE_OPCODE_MASK;
+
+        if opcode == macho::REBASE_OPCODE_DONE {
+            return pos;
+        }
+
+        pos += 1;
+
+        match opcode {
+            macho::REBASE_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB
+            | macho::REBASE_OPCODE_ADD_ADDR_ULEB
+            | macho::REBASE_OPCODE_DO_REBASE_ULEB_TIMES
+            | macho::REBASE_OPCODE_DO_REBASE_ADD_ADDR_ULEB => {
+                read_uleb128(opcodes, &mut pos);
+            }
+            macho::REBASE_OPCODE_DO_REBASE_ULEB_TIMES_SKIPPING_ULEB => {
+                read_uleb128(opcodes, &mut pos);
+                read_uleb128(opcodes, &mut pos);
+            }
+            _ => {}
+        }
+    }
+
+    pos
+}
+
+/// Lays out the data of `__LINKEDIT` at `new_fileoff`, in the same order as before, with the
+/// fixups of the `__ROC_*` segments added. Returns the data and the new offsets and sizes.
+fn rebuild_linkedit(
+    exec_data: &[u8],
+    md: &Metadata,
+    new_fileoff: usize,
+    chained_starts: &[Option<Vec<u8>>; 3],
+    rebase_opcodes: &[(u8, u64)],
+) -> (Vec<u8>, Vec<(usize, u32, Option<u32>)>) {
+    let mut pieces: Vec<&LinkeditPiece> = md.linkedit_pieces.iter().collect();
+    pieces.sort_by_key(|piece| piece.offset);
+
+    let mut out = Vec::new();
+    let mut updates = Vec::with_capacity(pieces.len());
+    let mut moved: MutMap<u32, (u32, Option<u32>)> = MutMap::default();
+    let mut added_rebases = rebase_opcodes.is_empty();
+
+    for piece in pieces {
+        if let Some(&(offset, size)) = moved.get(&piece.offset) {
+            updates.push((piece.offset_field, offset, size));
+            continue;
+        }
+
+        // ld keeps all of these 8-byte aligned
+        out.resize(align_by_constraint(out.len(), 8), 0);
+
+        let new_offset = (new_fileoff + out.len()) as u32;
+        let data = linkedit_data(exec_data, piece.offset, piece.size);
+
+        let new_size = match piece.kind {
+            LinkeditKind::Plain => {
+                out.extend(data);
+                None
+            }
+            LinkeditKind::ChainedFixups => {
+                let data = add_chained_starts(data, chained_starts);
+                out.extend(&data);
+                Some(data.len() as u32)
+            }
+            LinkeditKind::RebaseOpcodes => {
+                let data = append_rebase_opcodes(data, rebase_opcodes);
+                out.extend(&data);
+                added_rebases = true;
+                Some(data.len() as u32)
+            }
+        };
+
+        moved.insert(piece.offset, (new_offset, new_size));
+        updates.push((piece.offset_field, new_offset, new_size));
+    }
+
+    if !added_rebases {
+        unsupported!("it has no rebase opcodes to add the rebases of the app to");
+    }
+
+    (out, updates)
+}
+
+fn linkedit_data(exec_data: &[u8], offset: u32, size: u32) -> &[u8] {
+    &exec_data[offset as usize..offset as usize + size as usize]
+}
+
+fn trim_nul(name: &[u8]) -> &[u8] {
+    let end = name.iter().position(|&b| b == 0).unwrap_or(name.len());
+    &name[..end]
+}
+
+fn c_str_at(bytes: &[u8], offset: usize) -> &str {
+    CStr::from_bytes_until_nul(&bytes[offset..])
+        .ok()
+        .and_then(|name| name.to_str().ok())
+        .unwrap_or_else(|| internal_error!("Failed to read a symbol name at offset {offset:#x}"))
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(bytes[offset..offset + 2].try_into().unwrap())
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap())
+}
+
+fn write_u32(bytes: &mut [u8], offset: usize, value: u32) {
+    bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn write_u64(bytes: &mut [u8], offset: usize, value: u64) {
+    bytes[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+}
+
+fn read_uleb128(bytes: &[u8], pos: &mut usize) -> u64 {
+    let mut value = 0;
+    let mut shift = 0;
+
+    loop {
+        let byte = bytes[*pos];
+        *pos += 1;
+
+        if shift < 64 {
+            value |= u64::from(byte & 0x7F) << shift;
+        }
+        shift += 7;
+
+        if byte & 0x80 == 0 {
+            return value;
+        }
+    }
+}
+
+fn write_uleb128(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+
+        out.push(byte | 0x80);
+    }
+}
+
+// arm64 instructions
+
+/// `b` and `bl`
+fn is_branch(insn: u32) -> bool {
+    insn & 0x7C00_0000 == 0x1400_0000
+}
+
+fn branch_target(insn: u32, pc: u64) -> u64 {
+    let imm26 = ((insn & 0x03FF_FFFF) as i32) << 6 >> 6;
+
+    pc.wrapping_add_signed(i64::from(imm26) << 2)
+}
+
+fn encode_branch(insn: u32, pc: u64, target: u64) -> Option<u32> {
+    let delta = target.wrapping_sub(pc) as i64;
+
+    if delta % 4 != 0 || !(-(1 << 27)..(1 << 27)).contains(&delta) {
+        return None;
+    }
+
+    Some((insn & 0xFC00_0000) | ((delta >> 2) as u32 & 0x03FF_FFFF))
+}
+
+fn is_adrp(insn: u32) -> bool {
+    insn & 0x9F00_0000 == 0x9000_0000
+}
+
+fn adrp_page(insn: u32, pc: u64) -> u64 {
+    let immlo = (insn >> 29) & 0b11;
+    let immhi = (insn >> 5) & 0x7_FFFF;
+    let imm21 = (((immhi << 2) | immlo) as i32) << 11 >> 11;
+
+    (pc & !0xFFF).wrapping_add_signed(i64::from(imm21) << 12)
+}
+
+fn encode_adrp(insn: u32, pc: u64, target: u64) -> Option<u32> {
+    let delta = (target >> 12) as i64 - (pc >> 12) as i64;
+
+    if !(-(1 << 20)..(1 << 20)).contains(&delta) {
+        return None;
+    }
+
+    let imm = delta as u32;
+
+    Some((insn & 0x9F00_001F) | ((imm & 0b11) << 29) | (((imm >> 2) & 0x7_FFFF) << 5))
+}
+
+/// `ldr Xt, [Xn, #offset]`
+#[derive(Debug, PartialEq, Eq)]
+struct LoadX {
+    rt: u32,
+    rn: u32,
+    offset: u64,
+}
+
+fn decode_ldr_x(insn: u32) -> Option<LoadX> {
+    (insn & 0xFFC0_0000 == 0xF940_0000).then(|| LoadX {
+        rt: insn & 0x1F,
+        rn: (insn >> 5) & 0x1F,
+        offset: u64::from((insn >> 10) & 0xFFF) * 8,
+    })
+}
+
+/// `add Xd, Xn, #imm12`
+fn add_x_imm(rd: u32, rn: u32, imm12: u32) -> u32 {
+    0x9100_0000 | (imm12 << 10) | (rn << 5) | rd
+}
+
+/// Puts the offset of `target` in its page into an `add` or a load or store, which scale it by
+/// the size of what they access
+fn encode_pageoff12(insn: u32, target: u64) -> Option<u32> {
+    let lo12 = (target & 0xFFF) as u32;
+
+    let shift = if insn & 0x1F00_0000 == 0x1100_0000 {
+        // add and sub
+        0
+    } else if insn & 0x3B00_0000 == 0x3900_0000 {
+        // loads and stores with an unsigned offset, where 128-bit vector registers have size 0
+        let size = insn >> 30;
+        if insn & 0x0480_0000 == 0x0480_0000 && size == 0 {
+            4
+        } else {
+            size
+        }
+    } else {
+        return None;
+    };
+
+    if lo12 & ((1 << shift) - 1) != 0 {
+        return None;
+    }
+
+    Some((insn & !(0xFFF << 10)) | ((lo12 >> shift) << 10))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn branch_round_trip() {
+        // bl #-0x10
+        let bl = 0x97FF_FFFC;
+        assert!(is_branch(bl));
+        assert_eq!(branch_target(bl, 0x1_0000_0010), 0x1_0000_0000);
+
+        let encoded = encode_branch(bl, 0x1_0000_0000, 0x1_0800_0000 - 4).unwrap();
+        assert!(is_branch(encoded));
+        assert_eq!(branch_target(encoded, 0x1_0000_0000), 0x1_0800_0000 - 4);
+
+        assert_eq!(encode_branch(bl, 0x1_0000_0000, 0x1_0800_0000), None);
+    }
+
+    #[test]
+    fn adrp_round_trip() {
+        // adrp x8, 0
+        let adrp = 0x9000_0008;
+        assert!(is_adrp(adrp));
+
+        for target in [0x1_0000_4000, 0x1_2345_6789, 0xFFFF_F000] {
+            let encoded = encode_adrp(adrp, 0x1_0000_0abc, target).unwrap();
+            assert_eq!(encoded & 0x1F, 8);
+            assert_eq!(adrp_page(encoded, 0x1_0000_0abc), target & !0xFFF);
+        }
+    }
+
+    #[test]
+    fn pageoff12_scales_by_access_size() {
+        // ldr x8, [x8]
+        let ldr = 0xF940_0108;
+        assert_eq!(
+            decode_ldr_x(encode_pageoff12(ldr, 0x1_0000_0010).unwrap()),
+            Some(LoadX {
+                rt: 8,
+                rn: 8,
+                offset: 0x10
+            })
+        );
+        assert_eq!(encode_pageoff12(ldr, 0x1_0000_0004), None);
+
+        // ldrb w0, [x8]
+        let ldrb = 0x3940_0100;
+        assert_eq!(encode_pageoff12(ldrb, 0x123), Some(ldrb | (0x123 << 10)));
+
+        // ldr q0, [x8]
+        let ldr_q = 0x3DC0_0100;
+        assert_eq!(encode_pageoff12(ldr_q, 0x120), Some(ldr_q | (0x12 << 10)));
+
+        // add x0, x8, #0
+        let add = add_x_imm(0, 8, 0);
+        assert_eq!(encode_pageoff12(add, 0xFFF), Some(add_x_imm(0, 8, 0xFFF)));
+    }
+
+    #[test]
+    fn rebases_chain_within_pages() {
+        let image_base = 0x1_0000_0000;
+        let pointers = [0x8, 0x10, PAGE_SIZE + 0x20];
+
+        let mut data = vec![0; 2 * PAGE_SIZE as usize];
+        for &offset in pointers.iter() {
+            write_u64(&mut data, offset as usize, image_base + 0x4000 + offset);
+        }
+
+        chain_rebases(&mut data, &pointers, DYLD_CHAINED_PTR_64_OFFSET, image_base);
+
+        assert_eq!(read_u64(&data, 0x8), 0x4008 | (2 << 51));
+        assert_eq!(read_u64(&data, 0x10), 0x4010);
+        assert_eq!(
+            read_u64(&data, PAGE_SIZE as usize + 0x20),
+            0x4000 + PAGE_SIZE + 0x20
+        );
+
+        let starts =
+            chained_starts_in_segment(DYLD_CHAINED_PTR_64_OFFSET, 0x8000, 3 * PAGE_SIZE, &pointers);
+        assert_eq!(read_u32(&starts, 0) as usize, starts.len());
+        assert_eq!(read_u16(&starts, 20), 3);
+        assert_eq!(read_u16(&starts, 22), 0x8);
+        assert_eq!(read_u16(&starts, 24), 0x20);
+        assert_eq!(read_u16(&starts, 26), DYLD_CHAINED_PTR_START_NONE);
+    }
+
+    #[test]
+    fn chained_starts_of_roc_segments_go_before_linkedit() {
+        let mut blob = vec![0; 28];
+        write_u32(&mut blob, 4, 28);
+
+        // __TEXT without fixups, __DATA with fixups, and __LINKEDIT
+        blob.extend(3u32.to_le_bytes());
+        blob.extend([0, 0, 0, 0]);
+        blob.extend(16u32.to_le_bytes());
+        blob.extend([0, 0, 0, 0]);
+        let data_starts = chained_starts_in_segment(DYLD_CHAINED_PTR_64, 0x4000, PAGE_SIZE, &[0]);
+        blob.extend(&data_starts);
+
+        let imports_offset = blob.len();
+        blob.extend([1, 2, 0, 0]);
+        let symbols_offset = blob.len();
+        blob.extend(b"\0_roc_alloc\0");
+        write_u32(&mut blob, 8, imports_offset as u32);
+        write_u32(&mut blob, 12, symbols_offset as u32);
+
+        let roc_starts = chained_starts_in_segment(DYLD_CHAINED_PTR_64, 0xC000, PAGE_SIZE, &[8]);
+        let out = add_chained_starts(&blob, &[None, None, Some(roc_starts.clone())]);
+
+        let starts_offset = read_u32(&out, 4) as usize;
+        assert_eq!(read_u32(&out, starts_offset), 6);
+
+        let seg_info = |index: usize| read_u32(&out, starts_offset + 4 + 4 * index) as usize;
+        assert_eq!(seg_info(0), 0);
+        assert_eq!(seg_info(3), 0);
+        assert_eq!(seg_info(5), 0);
+
+        let data = starts_offset + seg_info(1);
+        assert_eq!(&out[data..data + data_starts.len()], data_starts.as_slice());
+        let roc_data = starts_offset + seg_info(4);
+        assert_eq!(
+            &out[roc_data..roc_data + roc_starts.len()],
+            roc_starts.as_slice()
+        );
+
+        let imports = read_u32(&out, 8) as usize;
+        let symbols = read_u32(&out, 12) as usize;
+        assert_eq!(&out[imports..symbols], &[1, 2, 0, 0]);
+        assert_eq!(c_str_at(&out, symbols + 1), "_roc_alloc");
+    }
+
+    #[test]
+    fn rebase_opcodes_go_before_done() {
+        use macho::*;
+
+        let opcodes = [
+            REBASE_OPCODE_SET_TYPE_IMM | REBASE_TYPE_POINTER,
+            REBASE_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB | 2,
+            0x80,
+            0x01,
+            REBASE_OPCODE_DO_REBASE_IMM_TIMES | 1,
+            REBASE_OPCODE_DONE,
+            0,
+            0,
+        ];
+
+        let appended = append_rebase_opcodes(&opcodes, &[(4, 0x90)]);
+
+        assert_eq!(
+            appended,
+            [
+                &opcodes[..5],
+                &[
+                    REBASE_OPCODE_SET_TYPE_IMM | REBASE_TYPE_POINTER,
+                    REBASE_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB | 4,
+                    0x90,
+                    0x01,
+                    REBASE_OPCODE_DO_REBASE_IMM_TIMES | 1,
+                    REBASE_OPCODE_DONE,
+                    0,
+                    0,
+                    0,
+                    0,
+                    0,
+                ],
+            ]
+            .concat()
+        );
+    }
+}
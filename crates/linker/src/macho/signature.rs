@@ -0,0 +1,147 @@
+//! Ad-hoc code signatures for Mach-O executables.
+//!
+//! macOS on arm64 kills any process whose pages do not match the code signature of its
+//! executable, and surgery changes every page after the header. An ad-hoc signature has no
+//! certificate, it is just the hashes of those pages, which is what `ld` writes for a host.
+use sha2::{Digest, Sha256};
+
+// Everything in a code signature is big-endian, unlike the rest of the executable.
+const CSMAGIC_EMBEDDED_SIGNATURE: u32 = 0xfade_0cc0;
+const CSMAGIC_CODEDIRECTORY: u32 = 0xfade_0c02;
+const CSSLOT_CODEDIRECTORY: u32 = 0;
+
+/// The first version of the code directory with the `exec_seg_*` fields
+const CS_SUPPORTSEXECSEG: u32 = 0x2_0400;
+const CS_ADHOC: u32 = 0x2;
+const CS_LINKER_SIGNED: u32 = 0x2_0000;
+const CS_EXECSEG_MAIN_BINARY: u64 = 0x1;
+const CS_HASHTYPE_SHA256: u8 = 2;
+const CS_SHA256_LEN: usize = 32;
+
+const CODE_PAGE_SIZE_LOG2: u8 = 12;
+const CODE_PAGE_SIZE: usize = 1 << CODE_PAGE_SIZE_LOG2;
+
+/// The size of a version `CS_SUPPORTSEXECSEG` code directory, before the identifier
+const CODE_DIRECTORY_SIZE: usize = 88;
+
+/// The header of the super blob, and the index entry of its only blob, the code directory
+const SUPER_BLOB_SIZE: usize = 12 + 8;
+
+/// The part of the executable that contains the code of the main binary, usually `__TEXT`
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ExecSegment {
+    pub fileoff: u64,
+    pub filesize: u64,
+}
+
+/// The size of the signature of the first `code_limit` bytes of an executable
+pub(crate) fn signature_size(code_limit: usize, identifier: &str) -> usize {
+    let code_slots = code_limit.div_ceil(CODE_PAGE_SIZE);
+
+    SUPER_BLOB_SIZE + CODE_DIRECTORY_SIZE + identifier.len() + 1 + code_slots * CS_SHA256_LEN
+}
+
+/// Signs `code`, which is the executable up to the offset that the signature will be written to
+pub(crate) fn ad_hoc_signature(
+    code: &[u8],
+    identifier: &str,
+    exec_segment: ExecSegment,
+) -> Vec<u8> {
+    let code_slots = code.len().div_ceil(CODE_PAGE_SIZE);
+
+    let ident_offset = CODE_DIRECTORY_SIZE;
+    let hash_offset = ident_offset + identifier.len() + 1;
+    let code_directory_size = hash_offset + code_slots * CS_SHA256_LEN;
+    let total_size = SUPER_BLOB_SIZE + code_directory_size;
+
+    let code_limit = u32::try_from(code.len())
+        .expect("an executable that needs a 64-bit code limit is larger than the linker supports");
+
+    let mut out = Vec::with_capacity(total_size);
+
+    out.extend(CSMAGIC_EMBEDDED_SIGNATURE.to_be_bytes());
+    out.extend((total_size as u32).to_be_bytes());
+    out.extend(1u32.to_be_bytes());
+    out.extend(CSSLOT_CODEDIRECTORY.to_be_bytes());
+    out.extend((SUPER_BLOB_SIZE as u32).to_be_bytes());
+
+    out.extend(CSMAGIC_CODEDIRECTORY.to_be_bytes());
+    out.extend((code_directory_size as u32).to_be_bytes());
+    out.extend(CS_SUPPORTSEXECSEG.to_be_bytes());
+    out.extend((CS_ADHOC | CS_LINKER_SIGNED).to_be_bytes());
+    out.extend((hash_offset as u32).to_be_bytes());
+    out.extend((ident_offset as u32).to_be_bytes());
+    // special slots hash the info plist, entitlements and so on, which we do not have
+    out.extend(0u32.to_be_bytes());
+    out.extend((code_slots as u32).to_be_bytes());
+    out.extend(code_limit.to_be_bytes());
+    out.extend([
+        CS_SHA256_LEN as u8,
+        CS_HASHTYPE_SHA256,
+        0, // platform
+        CODE_PAGE_SIZE_LOG2,
+    ]);
+    out.extend(0u32.to_be_bytes()); // spare2
+    out.extend(0u32.to_be_bytes()); // scatter offset
+    out.extend(0u32.to_be_bytes()); // team offset
+    out.extend(0u32.to_be_bytes()); // spare3
+    out.extend(0u64.to_be_bytes()); // 64-bit code limit
+    out.extend(exec_segment.fileoff.to_be_bytes());
+    out.extend(exec_segment.filesize.to_be_bytes());
+    out.extend(CS_EXECSEG_MAIN_BINARY.to_be_bytes());
+    debug_assert_eq!(out.len(), SUPER_BLOB_SIZE + ident_offset);
+
+    out.extend(identifier.as_bytes());
+    out.push(0);
+
+    for page in code.chunks(CODE_PAGE_SIZE) {
+        out.extend(Sha256::digest(page));
+    }
+
+    debug_assert_eq!(out.len(), total_size);
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn be_u32(bytes: &[u8], offset: usize) -> u32 {
+        u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn hashes_every_page() {
+        let code: Vec<u8> = (0..CODE_PAGE_SIZE + 100).map(|i| i as u8).collect();
+        let exec_segment = ExecSegment {
+            fileoff: 0,
+            filesize: 0x4000,
+        };
+
+        let signature = ad_hoc_signature(&code, "app", exec_segment);
+        assert_eq!(signature.len(), signature_size(code.len(), "app"));
+
+        assert_eq!(be_u32(&signature, 0), CSMAGIC_EMBEDDED_SIGNATURE);
+        assert_eq!(be_u32(&signature, 4), signature.len() as u32);
+
+        let directory = &signature[be_u32(&signature, 16) as usize..];
+        assert_eq!(be_u32(directory, 0), CSMAGIC_CODEDIRECTORY);
+        assert_eq!(
+            be_u32(directory, 28),
+            2,
+            "one slot for the full page, one for the rest"
+        );
+        assert_eq!(be_u32(directory, 32), code.len() as u32);
+
+        let ident_offset = be_u32(directory, 20) as usize;
+        assert_eq!(&directory[ident_offset..ident_offset + 4], b"app\0");
+
+        let hash_offset = be_u32(directory, 16) as usize;
+        let last_page = &directory[hash_offset + CS_SHA256_LEN..][..CS_SHA256_LEN];
+        assert_eq!(
+            last_page,
+            Sha256::digest(&code[CODE_PAGE_SIZE..]).as_slice()
+        );
+    }
+}
@@ -1,6 +1,7 @@
 #[cfg(not(target_family = "wasm"))]
 use {
     crate::https::{self, PackageMetadata, Problem},
+    crate::tarball::Compression,
     roc_error_macros::internal_error,
     std::fs,
 };
@@ -69,7 +70,8 @@ fn nixos_error_if_dynamic(url: &str, dest_dir: &Path) {
 /// into that dir. If the cache dir on the filesystem, then look into it to see if we already
 /// have an entry for the given URL. If we do, return its info. If we don't already have it, then:
 ///
-/// - Download and decompress the compressed tarball from the given URL
+/// - Download and decompress the compressed tarball from the given URL, or take it from the
+///   [roc_package_dir] if it has a tarball with the hash in the URL
 /// - Verify its bytes against the hash in the URL
 /// - Extract the tarball's contents into the appropriate cache directory
 ///
@@ -79,6 +81,15 @@ fn nixos_error_if_dynamic(url: &str, dest_dir: &Path) {
 pub fn install_package<'a>(
     roc_cache_dir: RocCacheDir<'_>,
     url: &'a str,
+) -> Result<(PathBuf, Option<&'a str>), Problem> {
+    install_package_help(roc_cache_dir, roc_package_dir().as_deref(), url)
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn install_package_help<'a>(
+    roc_cache_dir: RocCacheDir<'_>,
+    package_dir: Option<&Path>,
+    url: &'a str,
 ) -> Result<(PathBuf, Option<&'a str>), Problem> {
     use std::io::ErrorKind;
 
@@ -107,14 +118,29 @@ pub fn install_package<'a>(
                 Ok((dest_dir, root_module_filename))
             } else {
                 // Download into a tempdir; only move it to dest_dir if hash verification passes.
-                eprintln!(
-                    "Downloading \u{001b}[36m{url}\u{001b}[0m\n    into {}\n",
-                    cache_dir.display()
-                );
                 let tempdir = tempfile::tempdir().map_err(Problem::IoErr)?;
                 let tempdir_path = tempdir.path();
-                let downloaded_hash =
-                    https::download_and_hash(url, tempdir_path, MAX_DOWNLOAD_BYTES)?;
+                let vendored_tarball =
+                    package_dir.and_then(|package_dir| vendored_tarball(package_dir, content_hash));
+                let downloaded_hash = match &vendored_tarball {
+                    Some(tarball) => {
+                        // The tarball was vendored, so there is nothing to download. It is still
+                        // verified, in case the file on disk was changed.
+                        eprintln!(
+                            "Unpacking \u{001b}[36m{url}\u{001b}[0m\n    from {}\n    into {}\n",
+                            tarball.display(),
+                            cache_dir.display()
+                        );
+                        https::unpack_and_hash(tarball, tempdir_path)?
+                    }
+                    None => {
+                        eprintln!(
+                            "Downloading \u{001b}[36m{url}\u{001b}[0m\n    into {}\n",
+                            cache_dir.display()
+                        );
+                        https::download_and_hash(url, tempdir_path, MAX_DOWNLOAD_BYTES)?
+                    }
+                };
 
                 // Download the tarball into memory and verify it.
                 // The tarball name is the hash of its contents.
@@ -178,10 +204,17 @@ pub fn install_package<'a>(
                     // The package's files are now in the cache. We're done!
                     Ok((dest_dir, root_module_filename))
                 } else {
-                    Err(Problem::InvalidContentHash {
-                        expected: content_hash.to_string(),
-                        actual: downloaded_hash,
-                    })
+                    match vendored_tarball {
+                        Some(tarball) => Err(Problem::InvalidVendoredContentHash {
+                            tarball,
+                            expected: content_hash.to_string(),
+                            actual: downloaded_hash,
+                        }),
+                        None => Err(Problem::InvalidContentHash {
+                            expected: content_hash.to_string(),
+                            actual: downloaded_hash,
+                        }),
+                    }
                 }
             }
         }
@@ -196,6 +229,28 @@ pub fn install_package<'a>(
     }
 }
 
+/// The tarball in `package_dir` that is named after the given hash, with any of the extensions
+/// that a package URL can have
+#[cfg(not(target_family = "wasm"))]
+fn vendored_tarball(package_dir: &Path, content_hash: &str) -> Option<PathBuf> {
+    [
+        Compression::Brotli,
+        Compression::Gzip,
+        Compression::Uncompressed,
+    ]
+    .into_iter()
+    .map(|compression| package_dir.join(format!("{content_hash}{}", compression.file_ext())))
+    .find(|path| path.is_file())
+}
+
+/// The dir in the ROC_PACKAGE_DIR environment variable, if it's set. It holds the tarballs of
+/// packages, named like the ones in their URLs (e.g. jDRlAFAA3738vu3-vMpLUoyxtA86Z7CaZneoOKrihbE.tar.br),
+/// so that the packages an app depends on can be installed without network access.
+#[cfg(not(target_family = "wasm"))]
+pub fn roc_package_dir() -> Option<PathBuf> {
+    std::env::var_os("ROC_PACKAGE_DIR").map(PathBuf::from)
+}
+
 #[cfg(windows)]
 // e.g. the "Roc" in %APPDATA%\\Roc
 const ROC_CACHE_DIR_NAME: &str = "Roc";
@@ -267,3 +322,97 @@ pub fn roc_cache_packages_dir() -> PathBuf {
     const PACKAGES_DIR_NAME: &str = "packages";
     roc_cache_dir().join(PACKAGES_DIR_NAME)
 }
+
+#[cfg(all(test, not(target_family = "wasm")))]
+mod tests {
+    use super::*;
+    use crate::tarball;
+
+    /// Build the tarball of a package with one module, and move it into `package_dir`. Returns
+    /// its filename, which starts with its hash.
+    fn vendor_package(package_dir: &Path, greeting: &str) -> String {
+        let src_dir = tempfile::tempdir().unwrap();
+        let main_path = src_dir.path().join("main.roc");
+
+        fs::write(&main_path, "package [Greeting] {}\n").unwrap();
+        fs::write(
+            src_dir.path().join("Greeting.roc"),
+            format!("module [greeting]\n\ngreeting = \"{greeting}\"\n"),
+        )
+        .unwrap();
+
+        let filename = tarball::build(&main_path, Compression::Brotli).unwrap();
+        fs::copy(
+            main_path.with_file_name(&filename),
+            package_dir.join(&filename),
+        )
+        .unwrap();
+
+        filename
+    }
+
+    #[test]
+    fn installs_a_vendored_package_without_downloading() {
+        let package_dir = tempfile::tempdir().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        let filename = vendor_package(package_dir.path(), "hello");
+
+        // nothing can be downloaded from a .invalid domain
+        let url = format!("https://roc.invalid/packages/{filename}");
+
+        let (installed, root_module) = install_package_help(
+            RocCacheDir::Persistent(cache_dir.path()),
+            Some(package_dir.path()),
+            &url,
+        )
+        .unwrap();
+
+        assert_eq!(root_module, None);
+        assert!(installed.starts_with(cache_dir.path()));
+        assert!(installed.join("main.roc").is_file());
+        assert_eq!(
+            fs::read_to_string(installed.join("Greeting.roc")).unwrap(),
+            "module [greeting]\n\ngreeting = \"hello\"\n"
+        );
+    }
+
+    #[test]
+    fn rejects_a_tampered_vendored_package() {
+        let package_dir = tempfile::tempdir().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        let filename = vendor_package(package_dir.path(), "hello");
+        let tampered = vendor_package(package_dir.path(), "goodbye");
+
+        fs::rename(
+            package_dir.path().join(&tampered),
+            package_dir.path().join(&filename),
+        )
+        .unwrap();
+
+        let url = format!("https://roc.invalid/packages/{filename}");
+
+        match install_package_help(
+            RocCacheDir::Persistent(cache_dir.path()),
+            Some(package_dir.path()),
+            &url,
+        ) {
+            Err(Problem::InvalidVendoredContentHash {
+                tarball,
+                expected,
+                actual,
+            }) => {
+                assert_eq!(tarball, package_dir.path().join(&filename));
+                assert_eq!(format!("{expected}.tar.br"), filename);
+                assert_eq!(format!("{actual}.tar.br"), tampered);
+            }
+            other => panic!("the tampered package was not rejected: {other:?}"),
+        }
+
+        // nothing was installed into the cache
+        assert!(!cache_dir
+            .path()
+            .join("roc.invalid/packages")
+            .join(filename.trim_end_matches(".tar.br"))
+            .exists());
+    }
+}
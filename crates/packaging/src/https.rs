@@ -1,6 +1,7 @@
 use std::{
+    fs::File,
     io::{self, Read, Write},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 use crate::tarball::Compression;
@@ -234,6 +235,12 @@ pub enum Problem {
         expected: String,
         actual: String,
     },
+    /// A tarball in the vendor dir did not have the hash in the package's URL
+    InvalidVendoredContentHash {
+        tarball: PathBuf,
+        expected: String,
+        actual: String,
+    },
     IoErr(io::Error),
     FsExtraErr(fs_extra::error::Error),
     HttpErr(reqwest::Error),
@@ -288,6 +295,16 @@ pub fn download_and_hash(
     decompress_into(dest_dir, encoding, resp)
 }
 
+/// Unpack a tarball that is already on disk (e.g. in the vendor dir) into dest_dir, and return
+/// its hash the same way [download_and_hash] does. The compression is inferred from the file
+/// extension.
+pub fn unpack_and_hash(tarball: &Path, dest_dir: &Path) -> Result<String, Problem> {
+    let encoding = Encoding::new("", &tarball.to_string_lossy())?;
+    let file = File::open(tarball).map_err(Problem::IoErr)?;
+
+    decompress_into(dest_dir, encoding, io::BufReader::new(file))
+}
+
 /// The content encodings we support
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum Encoding {
@@ -22,7 +22,7 @@ pub enum Compression {
 }
 
 impl Compression {
-    const fn file_ext(&self) -> &'static str {
+    pub(crate) const fn file_ext(&self) -> &'static str {
         match self {
             Compression::Brotli => ".tar.br",
             Compression::Gzip => ".tar.gz",
@@ -2,7 +2,7 @@ use bumpalo::Bump;
 use inkwell::context::Context;
 use libloading::Library;
 use roc_build::link::llvm_module_to_dylib;
-use roc_collections::all::MutSet;
+use roc_collections::all::{MutMap, MutSet};
 
 use roc_gen_llvm::llvm::build::LlvmBackendMode;
 use roc_gen_llvm::llvm::externs::add_default_roc_externs;
@@ -208,6 +208,20 @@ fn mono_module_to_dylib_llvm<'a>(
         builder: &builder,
         dibuilder: &dibuilder,
         compile_unit: &compile_unit,
+        debug_info: None,
+        panic_strategy: roc_gen_llvm::llvm::build::PanicStrategy::Host,
+        check_alignment: false,
+        inline_threshold: None,
+        optimize_stack_alloc: false,
+        overflow_trap: false,
+        pgo: None,
+        lto: false,
+        sbf_stack_limit: None,
+        stack_guard: false,
+        data_sections: MutMap::default(),
+        cfi: false,
+        pool_size_classes: None,
+        valgrind: false,
         context: &context,
         interns,
         module,
@@ -330,6 +344,9 @@ fn mono_module_to_dylib_asm<'a>(
             builtins_host_tempfile.path().to_str().unwrap(),
         ],
         roc_build::link::LinkType::Dylib,
+        false,
+        true,
+        None,
     )
     .expect("failed to link dynamic library");
 
@@ -6,7 +6,7 @@ use const_format::concatcp;
 use roc_load::MonomorphizedModule;
 use roc_mono::ir::OptLevel;
 use roc_repl_eval::gen::Problems;
-use roc_repl_ui::colors::{CYAN, END_COL};
+use roc_repl_ui::colors::{CYAN, END_COL, GREEN};
 use roc_repl_ui::repl_state::{ReplAction, ReplState};
 use roc_repl_ui::{format_output, is_incomplete, CONT_PROMPT, PROMPT, SHORT_INSTRUCTIONS, TIPS};
 use roc_reporting::report::{
@@ -26,6 +26,23 @@ use target_lexicon::Triple;
 
 use crate::cli_gen::eval_llvm;
 
+/// Starts reading lines without checking whether they parse, for input that the repl would
+/// otherwise evaluate too early, until a line with just [PASTE_END].
+const PASTE_COMMAND: &str = ":paste";
+const PASTE_END: &str = ":end";
+
+const PASTE_MESSAGE: &str = concatcp!(
+    "Reading lines until ",
+    GREEN,
+    PASTE_END,
+    END_COL,
+    " (or ",
+    GREEN,
+    "ctrl-c",
+    END_COL,
+    " to cancel).\n"
+);
+
 pub const WELCOME_MESSAGE: &str = concatcp!(
     "\n  The rockin' ",
     CYAN,
@@ -96,9 +113,23 @@ pub fn main(has_color: bool, has_header: bool) -> i32 {
     loop {
         match editor.readline(&strip_colors_if_necessary(PROMPT)) {
             Ok(line) => {
-                let line = line.trim();
+                let mut line = line.trim().to_string();
 
-                editor.add_history_entry(line);
+                if line == PASTE_COMMAND {
+                    print!("{}", strip_colors_if_necessary(PASTE_MESSAGE));
+
+                    match read_paste(&mut editor, &strip_colors_if_necessary(CONT_PROMPT)) {
+                        Ok(Some(pasted)) => line = pasted,
+                        Ok(None) => continue,
+                        Err(err) => {
+                            eprintln!("REPL error: {err:?}");
+                            return 1;
+                        }
+                    }
+                }
+
+                // A multi-line input is one entry, so that it can be recalled all at once
+                editor.add_history_entry(line.as_str());
 
                 let repl_state = &mut editor
                     .helper_mut()
@@ -108,7 +139,7 @@ pub fn main(has_color: bool, has_header: bool) -> i32 {
                 arena.reset();
 
                 let action = std::panic::catch_unwind(AssertUnwindSafe(|| {
-                    repl_state.step(&arena, line, target, DEFAULT_PALETTE)
+                    repl_state.step(&arena, &line, target, DEFAULT_PALETTE)
                 }))
                 .unwrap_or_else(|e| notify_repl_panic(target, e));
 
@@ -156,6 +187,41 @@ pub fn main(has_color: bool, has_header: bool) -> i32 {
     }
 }
 
+/// Read the lines after a [PASTE_COMMAND] as they are, until [PASTE_END]. Returns `None` if the
+/// paste was cancelled with Ctrl-C.
+fn read_paste(
+    editor: &mut rustyline::Editor<ReplHelper>,
+    prompt: &str,
+) -> rustyline::Result<Option<String>> {
+    use rustyline::error::ReadlineError;
+
+    let set_pasting = |editor: &mut rustyline::Editor<ReplHelper>, pasting| {
+        editor
+            .helper_mut()
+            .expect("Editor helper was not set")
+            .validator
+            .pasting = pasting;
+    };
+
+    set_pasting(editor, true);
+
+    let mut lines = Vec::new();
+    let result = loop {
+        match editor.readline(prompt) {
+            Ok(line) if line.trim() == PASTE_END => break Ok(Some(lines.join("\n"))),
+            Ok(line) => lines.push(line),
+            // End of input finishes the paste, like the repl itself
+            Err(ReadlineError::Eof) => break Ok(Some(lines.join("\n"))),
+            Err(ReadlineError::Interrupted) => break Ok(None),
+            Err(err) => break Err(err),
+        }
+    };
+
+    set_pasting(editor, false);
+
+    result
+}
+
 fn notify_repl_panic(target: Target, e: Box<dyn Any + Send>) -> ReplAction<'static> {
     let message = if let Some(s) = e.downcast_ref::<&str>() {
         s.to_string()
@@ -197,11 +263,14 @@ pub fn evaluate(
 }
 
 #[derive(Default)]
-struct InputValidator {}
+struct InputValidator {
+    /// Every line is taken as it is while pasting
+    pasting: bool,
+}
 
 impl Validator for InputValidator {
     fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
-        if is_incomplete(ctx.input()) {
+        if !self.pasting && is_incomplete(ctx.input()) {
             Ok(ValidationResult::Incomplete)
         } else {
             Ok(ValidationResult::Valid(None))
@@ -528,7 +528,8 @@ fn jit_to_ast_help<'a, A: ReplApp<'a>>(
         LayoutRepr::Union(UnionLayout::Recursive(_))
         | LayoutRepr::Union(UnionLayout::NonNullableUnwrapped(_))
         | LayoutRepr::Union(UnionLayout::NullableUnwrapped { .. })
-        | LayoutRepr::Union(UnionLayout::NullableWrapped { .. }) => {
+        | LayoutRepr::Union(UnionLayout::NullableWrapped { .. })
+        | LayoutRepr::Union(UnionLayout::TaggedPointer { .. }) => {
             let size = env.layout_cache.interner.stack_size(layout);
 
             let opt_union = app.call_function_dynamic_size(
@@ -785,6 +786,42 @@ fn addr_to_ast<'a, M: ReplAppMemory>(
                 when_recursive,
             )
         }
+        (_, LayoutRepr::Union(union_layout @ UnionLayout::TaggedPointer { immediate_id, .. })) => {
+            let (rec_var, tags) = match unroll_recursion_var(env, raw_content) {
+                Content::Structure(FlatType::RecursiveTagUnion(rec_var, tags, _)) => {
+                    (rec_var, tags)
+                }
+                other => unreachable!("Unexpected content for TaggedPointer: {:?}", other),
+            };
+
+            let (vars_of_tag, union_variant) = get_tags_vars_and_variant(env, tags, Some(*rec_var));
+
+            let tags_and_layouts = match union_variant {
+                UnionVariant::Wrapped(WrappedVariant::TaggedPointer {
+                    sorted_tag_layouts, ..
+                }) => sorted_tag_layouts,
+                _ => unreachable!("any other variant would have a different layout"),
+            };
+
+            let word = mem.deref_usize(addr) as u64;
+            let (tag_id, data_addr) = if word & UnionLayout::IMMEDIATE_BIT != 0 {
+                // the integer is in the low bytes of the union value itself
+                (immediate_id as i64, addr)
+            } else {
+                tag_id_from_recursive_ptr(env, mem, union_layout, addr)
+            };
+
+            let (tag_name, arg_layouts) = &tags_and_layouts[tag_id as usize];
+            expr_of_tag(
+                env,
+                mem,
+                data_addr,
+                tag_name.expect_tag_ref(),
+                arg_layouts,
+                &vars_of_tag[tag_name.expect_tag_ref()],
+                when_recursive,
+            )
+        }
         (
             Content::Structure(FlatType::Apply(Symbol::BOX_BOX_TYPE, args)),
             LayoutRepr::Union(UnionLayout::NonNullableUnwrapped([inner_layout])),
@@ -844,6 +881,42 @@ fn addr_to_ast<'a, M: ReplAppMemory>(
                 when_recursive,
             )
         }
+        (
+            Content::Structure(FlatType::TagUnion(tags, _)),
+            LayoutRepr::Union(UnionLayout::NullableUnwrapped { .. }),
+        ) => {
+            // a non-recursive union like `[Some (Box a), None]`, which stores the box directly
+            let (vars_of_tag, union_variant) = get_tags_vars_and_variant(env, tags, None);
+
+            let (nullable_name, other_name, other_arg_layouts) = match union_variant {
+                UnionVariant::Wrapped(WrappedVariant::NullableBox {
+                    nullable_name,
+                    other_name,
+                    other_fields,
+                    ..
+                }) => (
+                    nullable_name.expect_tag(),
+                    other_name.expect_tag(),
+                    other_fields,
+                ),
+                _ => unreachable!("any other variant would have a different layout"),
+            };
+
+            if mem.deref_usize(addr) == 0 {
+                tag_name_to_expr(env, &nullable_name)
+            } else {
+                // the union is laid out just like its only argument, the box
+                expr_of_tag(
+                    env,
+                    mem,
+                    addr,
+                    &other_name,
+                    other_arg_layouts,
+                    &vars_of_tag[&other_name],
+                    WhenRecursive::Unreachable,
+                )
+            }
+        }
         (_, LayoutRepr::Union(UnionLayout::NullableUnwrapped { .. })) => {
             let (rec_var, tags) = match unroll_recursion_var(env, raw_content) {
                 Content::Structure(FlatType::RecursiveTagUnion(rec_var, tags, _)) => {
@@ -71,10 +71,16 @@ pub fn compile_to_mono<'a, 'i, I: Iterator<Item = &'i str>>(
         LoadConfig {
             target,
             function_kind: FunctionKind::LambdaSet,
-            render: roc_reporting::report::RenderTarget::ColorTerminal,
+            render: roc_reporting::report::RenderTarget::ColorTerminal(
+                roc_reporting::report::ColorChoice::Always,
+            ),
             palette,
             threading: Threading::Single,
             exec_mode: ExecutionMode::Executable,
+            expect_filter: None,
+            finalizers: Vec::new(),
+            types_cache_dir: None,
+            fold_constants: true,
         },
     );
 
@@ -20,6 +20,10 @@ pub mod run;
 #[cfg(not(windows))]
 use app::{ExpectMemory, ExpectReplApp};
 
+/// Reads the values of the variables of a failed `expect` back from memory.
+///
+/// Values are rendered from their layout, like in the repl. A custom `Inspect` implementation of an
+/// opaque type is not used here.
 #[cfg(not(windows))]
 #[allow(clippy::too_many_arguments)]
 pub fn get_values<'a>(
@@ -97,7 +101,7 @@ mod test {
     use roc_gen_llvm::{llvm::build::LlvmBackendMode, run_roc::RocCallResult, run_roc_dylib};
     use roc_load::{ExecutionMode, FunctionKind, LoadConfig, LoadMonomorphizedError, Threading};
     use roc_packaging::cache::RocCacheDir;
-    use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
+    use roc_reporting::report::{ColorChoice, RenderTarget, DEFAULT_PALETTE};
     use target_lexicon::Triple;
 
     use crate::run::expect_mono_module_to_dylib;
@@ -120,10 +124,14 @@ mod test {
         let load_config = LoadConfig {
             target,
             function_kind,
-            render: RenderTarget::ColorTerminal,
+            render: RenderTarget::ColorTerminal(ColorChoice::Always),
             palette: DEFAULT_PALETTE,
             threading: Threading::Single,
             exec_mode: ExecutionMode::Test,
+            expect_filter: None,
+            finalizers: Vec::new(),
+            types_cache_dir: None,
+            fold_constants: true,
         };
         let loaded = match roc_load::load_and_monomorphize_from_str(
             arena,
@@ -170,7 +178,7 @@ mod test {
         for (_, expect_funcs) in expects_by_module {
             let (_failed, _passed) = crate::run::run_expects_with_memory(
                 &mut writer,
-                RenderTarget::ColorTerminal,
+                RenderTarget::ColorTerminal(ColorChoice::Always),
                 arena,
                 interns,
                 &global_layout_interner,
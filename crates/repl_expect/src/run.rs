@@ -7,7 +7,7 @@ use bumpalo::collections::Vec as BumpVec;
 use bumpalo::Bump;
 use inkwell::context::Context;
 use roc_build::link::llvm_module_to_dylib;
-use roc_can::expr::ExpectLookup;
+use roc_can::expr::{ExpectLookup, EXPECT_MESSAGE};
 use roc_collections::{MutMap, MutSet, VecMap};
 use roc_error_macros::internal_error;
 use roc_gen_llvm::{
@@ -21,8 +21,12 @@ use roc_mono::{
     ir::OptLevel,
     layout::{GlobalLayoutInterner, STLayoutInterner},
 };
+use roc_parse::ast::{Expr, StrLiteral};
 use roc_region::all::Region;
-use roc_reporting::{error::expect::Renderer, report::RenderTarget};
+use roc_reporting::{
+    error::expect::Renderer,
+    report::{ColorChoice, RenderTarget},
+};
 use roc_target::Target;
 use roc_types::subs::Subs;
 
@@ -257,7 +261,7 @@ pub fn render_expects_in_memory<'a>(
     let renderer = Renderer::new(
         arena,
         interns,
-        RenderTarget::ColorTerminal,
+        RenderTarget::ColorTerminal(ColorChoice::Always),
         module_id,
         filename,
         &source,
@@ -338,12 +342,31 @@ fn render_expect_failure<'a>(
         symbols.len(),
     );
 
+    // the message of an `expect condition, message` is written before the lookups
+    let (message, symbols, variables, expressions) = match symbols.first() {
+        Some(symbol) if symbol.as_str(interns) == EXPECT_MESSAGE => {
+            let message = match &expressions[0] {
+                Expr::Str(StrLiteral::PlainLine(message)) => *message,
+                other => internal_error!("the message of an expect is not a string: {other:?}"),
+            };
+
+            (
+                Some(message),
+                &symbols[1..],
+                &variables[1..],
+                &expressions[1..],
+            )
+        }
+        _ => (None, &symbols[..], &variables[..], &expressions[..]),
+    };
+
     renderer.render_failure(
         writer,
         &mut data.subs,
-        &symbols,
-        &variables,
-        &expressions,
+        message,
+        symbols,
+        variables,
+        expressions,
         expect_region,
         failure_region,
     )?;
@@ -489,6 +512,20 @@ pub fn expect_mono_module_to_dylib<'a>(
         builder: &builder,
         dibuilder: &dibuilder,
         compile_unit: &compile_unit,
+        debug_info: None,
+        panic_strategy: roc_gen_llvm::llvm::build::PanicStrategy::Host,
+        check_alignment: false,
+        inline_threshold: None,
+        optimize_stack_alloc: false,
+        overflow_trap: false,
+        pgo: None,
+        lto: false,
+        sbf_stack_limit: None,
+        stack_guard: false,
+        data_sections: MutMap::default(),
+        cfi: false,
+        pool_size_classes: None,
+        valgrind: false,
         context: &context,
         interns,
         module,
@@ -123,6 +123,35 @@ fn partial_record_definition() {
     }
 }
 
+#[test]
+fn if_over_lines() {
+    let mut input = "if Bool.true then".to_string();
+    incomplete(&mut input);
+
+    input.push_str("    1");
+    incomplete(&mut input);
+
+    input.push_str("else");
+    incomplete(&mut input);
+
+    input.push_str("    2");
+    complete(&input, &mut ReplState::new(), "1 : Num *");
+}
+
+#[test]
+fn list_over_lines() {
+    let mut input = "[1, 2,".to_string();
+    incomplete(&mut input);
+
+    input.push_str("3]");
+    complete(&input, &mut ReplState::new(), "[1, 2, 3] : List (Num *)");
+}
+
+#[test]
+fn syntax_error_before_the_end_is_not_incomplete() {
+    assert!(!is_incomplete("1 + )"));
+}
+
 #[test]
 fn tips() {
     assert!(!is_incomplete(""));
@@ -1301,6 +1301,13 @@ fn dec_in_repl() {
     )
 }
 
+#[test]
+fn dec_arithmetic_in_repl() {
+    expect_success("1.5dec * 1.5dec", "2.25 : Dec");
+    expect_success("10dec / 4", "2.5 : Dec");
+    expect_success("Num.to_str (1dec / 8)", "\"0.125\" : Str");
+}
+
 #[test]
 fn print_i8_issue_2710() {
     expect_success(
@@ -1597,6 +1604,7 @@ fn interpolation_with_nested_interpolation() {
                 Enter an expression to evaluate, or a definition (like x = 1) to use later.
 
                   - ctrl-v + ctrl-j makes a newline
+                  - :paste reads lines as they are until :end
                   - :q quits
                   - :help shows this text again
             "#
@@ -38,6 +38,17 @@ pub const TIPS: &str = concatcp!(
             "  - ",
             END_COL,
             GREEN,
+            ":paste",
+            END_COL,
+            " reads lines as they are until ",
+            GREEN,
+            ":end",
+            END_COL,
+            "\n",
+            CYAN,
+            "  - ",
+            END_COL,
+            GREEN,
             ":q",
             END_COL,
             " quits\n",
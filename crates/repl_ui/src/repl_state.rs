@@ -295,13 +295,14 @@ pub enum ParseOutcome<'a> {
 }
 
 /// Special case some syntax errors to allow for multi-line inputs
-fn parse_outcome_for_error(e: EExpr<'_>) -> ParseOutcome<'_> {
+fn parse_outcome_for_error<'a>(e: EExpr<'a>, line: &str) -> ParseOutcome<'a> {
     match e {
         EExpr::Closure(EClosure::Body(_, _), _)
         | EExpr::When(EWhen::Pattern(EPattern::Start(_), _), _)
         | EExpr::Record(_, _)
         | EExpr::Start(_)
         | EExpr::IndentStart(_) => ParseOutcome::Incomplete,
+        _ if e.is_unexpected_end(line) => ParseOutcome::Incomplete,
         _ => ParseOutcome::SyntaxErr,
     }
 }
@@ -319,7 +320,7 @@ pub fn parse_src<'a>(arena: &'a Bump, line: &'a str) -> ParseOutcome<'a> {
             let src_bytes = line.as_bytes();
 
             match parse_repl_defs_and_optional_expr(arena, State::new(src_bytes)) {
-                Err((_, e)) => parse_outcome_for_error(e),
+                Err((_, e)) => parse_outcome_for_error(e, line),
                 Ok((_, (defs, opt_last_expr), _state)) => {
                     if defs.is_empty() && opt_last_expr.is_none() {
                         ParseOutcome::Empty
@@ -254,6 +254,8 @@ async fn eval_wasm<'a>(
             arena,
             module_id,
             stack_bytes: roc_gen_wasm::Env::DEFAULT_STACK_BYTES,
+            bulk_memory: false,
+            exceptions: false,
             exposed_to_host: exposed_to_host
                 .top_level_values
                 .keys()
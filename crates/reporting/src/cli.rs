@@ -1,12 +1,13 @@
 use std::path::PathBuf;
 
 use roc_collections::MutMap;
-use roc_module::symbol::{Interns, ModuleId};
+use roc_module::symbol::{Interns, ModuleId, Symbol};
 use roc_problem::can::Problem;
-use roc_region::all::LineInfo;
+use roc_region::all::{LineInfo, Region};
 use roc_solve_problem::TypeError;
 
-use crate::report::ANSI_STYLE_CODES;
+use crate::json::Span;
+use crate::report::{Palette, RenderTarget, ANSI_STYLE_CODES};
 
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct Problems {
@@ -28,29 +29,35 @@ impl Problems {
     }
 
     // prints e.g. `1 error and 0 warnings found in 63 ms.`
-    pub fn print_error_warning_count(&self, total_time: std::time::Duration) {
-        const GREEN: &str = ANSI_STYLE_CODES.green;
-        const YELLOW: &str = ANSI_STYLE_CODES.yellow;
-        const RESET: &str = ANSI_STYLE_CODES.reset;
+    pub fn print_error_warning_count(&self, total_time: std::time::Duration, has_color: bool) {
+        let (green, yellow, reset) = if has_color {
+            (
+                ANSI_STYLE_CODES.green,
+                ANSI_STYLE_CODES.yellow,
+                ANSI_STYLE_CODES.reset,
+            )
+        } else {
+            ("", "", "")
+        };
 
         print!(
             "{}{}{} {} and {}{}{} {} found in {} ms",
             match self.errors {
-                0 => GREEN,
-                _ => YELLOW,
+                0 => green,
+                _ => yellow,
             },
             self.errors,
-            RESET,
+            reset,
             match self.errors {
                 1 => "error",
                 _ => "errors",
             },
             match self.warnings {
-                0 => GREEN,
-                _ => YELLOW,
+                0 => green,
+                _ => yellow,
             },
             self.warnings,
-            RESET,
+            reset,
             match self.warnings {
                 1 => "warning",
                 _ => "warnings",
@@ -65,8 +72,9 @@ pub fn report_problems(
     interns: &Interns,
     can_problems: &mut MutMap<ModuleId, Vec<roc_problem::can::Problem>>,
     type_problems: &mut MutMap<ModuleId, Vec<TypeError>>,
+    render: RenderTarget,
 ) -> Problems {
-    use crate::report::{can_problem, type_problem, Report, RocDocAllocator, DEFAULT_PALETTE};
+    use crate::report::{can_problem, type_problem, RocDocAllocator, DEFAULT_PALETTE};
     use roc_problem::Severity::*;
 
     let palette = DEFAULT_PALETTE;
@@ -86,7 +94,12 @@ pub fn report_problems(
     let mut errors = Vec::with_capacity(total_problems);
     let mut fatally_errored = false;
 
-    for (home, (module_path, src)) in sources.iter() {
+    // The modules are loaded on several threads, so the order of `sources` (and the ids of the
+    // modules) can differ from one build to the next. Reporting by path keeps the output the same.
+    let mut modules: Vec<_> = sources.iter().collect();
+    modules.sort_by(|(_, (a, _)), (_, (b, _))| a.cmp(b));
+
+    for (home, (module_path, src)) in modules {
         let mut src_lines: Vec<&str> = Vec::new();
 
         src_lines.extend(src.split('\n'));
@@ -99,11 +112,13 @@ pub fn report_problems(
         let problems = type_problems.remove(home).unwrap_or_default();
 
         for problem in problems {
+            let region = problem.region();
+
             if let Some(report) = type_problem(&alloc, &lines, module_path.clone(), problem) {
                 let severity = report.severity;
                 let mut buf = String::new();
 
-                report.render_color_terminal(&mut buf, &alloc, &palette);
+                render_problem(report, render, region, &lines, &mut buf, &alloc, &palette);
 
                 match severity {
                     Warning => {
@@ -137,11 +152,12 @@ pub fn report_problems(
         ordered.extend(shadowing_errs);
 
         for problem in ordered.into_iter() {
+            let region = problem.region();
             let report = can_problem(&alloc, &lines, module_path.clone(), problem);
             let severity = report.severity;
             let mut buf = String::new();
 
-            report.render_color_terminal(&mut buf, &alloc, &palette);
+            render_problem(report, render, region, &lines, &mut buf, &alloc, &palette);
 
             match severity {
                 Warning => {
@@ -163,8 +179,11 @@ pub fn report_problems(
 
     let problems_reported;
 
-    // Only print warnings if there are no errors
-    if errors.is_empty() {
+    if let RenderTarget::Json = render {
+        // Every problem was already printed, warnings too
+        problems_reported = 0;
+    } else if errors.is_empty() {
+        // Only print warnings if there are no errors
         problems_reported = warnings.len();
 
         for warning in warnings.iter() {
@@ -185,7 +204,7 @@ pub fn report_problems(
     // compiling it, as it lets you clearly see where the compiler
     // errors/warnings end and the program output begins.
     if problems_reported > 0 {
-        println!("{}\u{001B}[0m\n", Report::horizontal_rule(&palette));
+        print_horizontal_rule(render);
     }
 
     Problems {
@@ -194,3 +213,339 @@ pub fn report_problems(
         warnings: warnings.len(),
     }
 }
+
+/// The rule after the reports, which also clears the colors they used
+fn print_horizontal_rule(render: RenderTarget) {
+    use crate::report::{Report, DEFAULT_PALETTE, NO_COLOR_PALETTE};
+
+    if render.has_color() {
+        println!("{}\u{001B}[0m\n", Report::horizontal_rule(&DEFAULT_PALETTE));
+    } else {
+        println!("{}\n", Report::horizontal_rule(&NO_COLOR_PALETTE));
+    }
+}
+
+/// As JSON, a report gets the span of the problem it is about
+fn render_problem<'b>(
+    report: crate::report::Report<'b>,
+    render: RenderTarget,
+    region: Option<Region>,
+    lines: &LineInfo,
+    buf: &mut String,
+    alloc: &'b crate::report::RocDocAllocator<'b>,
+    palette: &'b Palette,
+) {
+    match render {
+        RenderTarget::Json => {
+            report.render_json(buf, region.map(|region| Span::new(region, lines)));
+
+            // Print it right away, so tools can show it while the rest is still being reported
+            print!("{buf}");
+        }
+        _ => report.render(render, buf, alloc, palette),
+    }
+}
+
+/// A procedure whose estimated stack frame is larger than the target allows.
+pub struct OversizedStackFrame {
+    pub symbol: Symbol,
+    pub frame_size: u32,
+    /// The largest value in the frame, as a pretty-printed layout, and its size in bytes
+    pub largest: Option<(String, u32)>,
+}
+
+pub fn report_oversized_stack_frames(
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    interns: &Interns,
+    frames: &[OversizedStackFrame],
+    max_frame_size: u32,
+    render: RenderTarget,
+) -> Problems {
+    use crate::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
+    use roc_problem::Severity;
+    use ven_pretty::DocAllocator;
+
+    let palette = DEFAULT_PALETTE;
+
+    for frame in frames {
+        let home = frame.symbol.module_id();
+        let filename = sources
+            .get(&home)
+            .map(|(path, _)| path.clone())
+            .unwrap_or_default();
+
+        let alloc = RocDocAllocator::new(&[], home, interns);
+
+        let mut lines = vec![alloc.concat([
+            alloc.reflow("The "),
+            alloc.symbol_unqualified(frame.symbol),
+            alloc.reflow(" function needs an estimated "),
+            alloc.string(frame.frame_size.to_string()),
+            alloc.reflow(" bytes of stack, but this target only allows "),
+            alloc.string(max_frame_size.to_string()),
+            alloc.reflow(" bytes per stack frame."),
+        ])];
+
+        if let Some((layout, size)) = &frame.largest {
+            lines.push(alloc.concat([
+                alloc.reflow("The largest value it keeps on the stack takes "),
+                alloc.string(size.to_string()),
+                alloc.reflow(" bytes:"),
+            ]));
+            lines.push(alloc.type_str(layout).indent(4));
+        }
+
+        lines.push(alloc.concat([
+            alloc.tip(),
+            alloc.reflow("Putting large values in a "),
+            alloc.type_str("Box"),
+            alloc.reflow(" moves them to the heap."),
+        ]));
+
+        let report = Report {
+            title: "STACK FRAME TOO LARGE".to_string(),
+            filename,
+            doc: alloc.stack(lines),
+            severity: Severity::Warning,
+        };
+
+        let mut buf = String::new();
+
+        match render {
+            RenderTarget::Json => {
+                report.render_json(&mut buf, None);
+                print!("{buf}");
+            }
+            _ => {
+                report.render(render, &mut buf, &alloc, &palette);
+                println!("\n{buf}\n");
+            }
+        }
+    }
+
+    if !frames.is_empty() && !matches!(render, RenderTarget::Json) {
+        print_horizontal_rule(render);
+    }
+
+    Problems {
+        fatally_errored: false,
+        errors: 0,
+        warnings: frames.len(),
+    }
+}
+
+/// Report that the program calls the `WeakRef` functions in `functions`, which crash on the 32-bit
+/// `target` it is built for.
+pub fn report_unsupported_weak_refs(
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    interns: &Interns,
+    home: ModuleId,
+    functions: &[Symbol],
+    target: &str,
+    render: RenderTarget,
+) -> Problems {
+    use crate::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
+    use roc_problem::Severity;
+    use ven_pretty::DocAllocator;
+
+    let palette = DEFAULT_PALETTE;
+
+    let filename = sources
+        .get(&home)
+        .map(|(path, _)| path.clone())
+        .unwrap_or_default();
+
+    let alloc = RocDocAllocator::new(&[], home, interns);
+
+    let lines = vec![
+        alloc.concat([
+            alloc.reflow("This program is built for "),
+            alloc.string(target.to_string()),
+            alloc.reflow(", but it uses these "),
+            alloc.type_str("WeakRef"),
+            alloc.reflow(" functions:"),
+        ]),
+        alloc
+            .stack(
+                functions
+                    .iter()
+                    .map(|symbol| alloc.symbol_qualified(*symbol)),
+            )
+            .indent(4),
+        alloc.concat([
+            alloc.reflow("Weak references are only supported on 64-bit targets. On this target, "),
+            alloc.reflow("the program crashes when it calls one of them."),
+        ]),
+    ];
+
+    let report = Report {
+        title: "UNSUPPORTED ON THIS TARGET".to_string(),
+        filename,
+        doc: alloc.stack(lines),
+        severity: Severity::RuntimeError,
+    };
+
+    let mut buf = String::new();
+
+    match render {
+        RenderTarget::Json => {
+            report.render_json(&mut buf, None);
+            print!("{buf}");
+        }
+        _ => {
+            report.render(render, &mut buf, &alloc, &palette);
+            println!("\n{buf}\n");
+            print_horizontal_rule(render);
+        }
+    }
+
+    Problems {
+        fatally_errored: false,
+        errors: 1,
+        warnings: 0,
+    }
+}
+
+/// Warn that the frames along the call path `frames` take `depth` bytes of stack together, more
+/// than `limit` allows. `frames` are the names of the functions and the sizes of their frames,
+/// starting with the function the host calls.
+pub fn report_stack_depth(
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    interns: &Interns,
+    home: ModuleId,
+    frames: &[(String, u64)],
+    depth: u64,
+    limit: u32,
+) -> Problems {
+    use crate::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
+    use roc_problem::Severity;
+    use ven_pretty::DocAllocator;
+
+    let palette = DEFAULT_PALETTE;
+
+    let filename = sources
+        .get(&home)
+        .map(|(path, _)| path.clone())
+        .unwrap_or_default();
+
+    let alloc = RocDocAllocator::new(&[], home, interns);
+
+    let lines = vec![
+        alloc.concat([
+            alloc.reflow("The deepest chain of calls in this program needs an estimated "),
+            alloc.string(depth.to_string()),
+            alloc.reflow(" bytes of stack, but this target only allows "),
+            alloc.string(limit.to_string()),
+            alloc.reflow(" bytes."),
+        ]),
+        alloc.reflow("These are the functions on it, and the size of their stack frames:"),
+        alloc
+            .stack(frames.iter().map(|(name, size)| {
+                alloc.concat([
+                    alloc.string(name.clone()),
+                    alloc.reflow(": "),
+                    alloc.string(size.to_string()),
+                ])
+            }))
+            .indent(4),
+        alloc.concat([
+            alloc.tip(),
+            alloc.reflow("Putting large values in a "),
+            alloc.type_str("Box"),
+            alloc.reflow(" moves them to the heap."),
+        ]),
+    ];
+
+    let report = Report {
+        title: "CALL STACK TOO DEEP".to_string(),
+        filename,
+        doc: alloc.stack(lines),
+        severity: Severity::Warning,
+    };
+
+    let mut buf = String::new();
+    report.render_color_terminal(&mut buf, &alloc, &palette);
+
+    println!("\n{buf}\n");
+    println!("{}\u{001B}[0m\n", Report::horizontal_rule(&palette));
+
+    Problems {
+        fatally_errored: false,
+        errors: 0,
+        warnings: 1,
+    }
+}
+
+/// How many of the most expensive functions a compute budget warning lists
+const COMPUTE_BUDGET_TOP_FUNCTIONS: usize = 5;
+
+/// Warn that the compute units estimated for a program are over `budget`. `functions` are the
+/// names and estimated costs of the functions it runs, the most expensive first.
+pub fn report_compute_budget(
+    sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    interns: &Interns,
+    home: ModuleId,
+    estimate: u64,
+    budget: u64,
+    functions: &[(String, u64)],
+) -> Problems {
+    use crate::report::{Report, RocDocAllocator, DEFAULT_PALETTE};
+    use roc_problem::Severity;
+    use ven_pretty::DocAllocator;
+
+    let palette = DEFAULT_PALETTE;
+
+    let filename = sources
+        .get(&home)
+        .map(|(path, _)| path.clone())
+        .unwrap_or_default();
+
+    let alloc = RocDocAllocator::new(&[], home, interns);
+
+    let shown = functions.len().min(COMPUTE_BUDGET_TOP_FUNCTIONS);
+
+    let lines = vec![
+        alloc.concat([
+            alloc.reflow("This program runs through an estimated "),
+            alloc.string(estimate.to_string()),
+            alloc.reflow(" compute units of code, but the budget is "),
+            alloc.string(budget.to_string()),
+            alloc.reflow(" compute units."),
+        ]),
+        alloc.reflow("These functions contribute the most:"),
+        alloc
+            .stack(functions[..shown].iter().map(|(name, cost)| {
+                alloc.concat([
+                    alloc.string(name.clone()),
+                    alloc.reflow(": "),
+                    alloc.string(cost.to_string()),
+                ])
+            }))
+            .indent(4),
+        alloc.concat([
+            alloc.note(""),
+            alloc.reflow(
+                "Every instruction is counted once, so the cost of a loop is that of a single iteration.",
+            ),
+        ]),
+    ];
+
+    let report = Report {
+        title: "COMPUTE BUDGET EXCEEDED".to_string(),
+        filename,
+        doc: alloc.stack(lines),
+        severity: Severity::Warning,
+    };
+
+    let mut buf = String::new();
+    report.render_color_terminal(&mut buf, &alloc, &palette);
+
+    println!("\n{buf}\n");
+    println!("{}\u{001B}[0m\n", Report::horizontal_rule(&palette));
+
+    Problems {
+        fatally_errored: false,
+        errors: 0,
+        warnings: 1,
+    }
+}
@@ -140,11 +140,14 @@ impl<'a> Renderer<'a> {
         self.line_info.convert_region(display_region)
     }
 
+    /// The message of an `expect condition, message` is shown above the rest of the report, so
+    /// failures of the same expectation can be told apart.
     #[allow(clippy::too_many_arguments)]
     pub fn render_failure<W>(
         &self,
         writer: &mut W,
         subs: &mut Subs,
+        message: Option<&str>,
         symbols: &[Symbol],
         variables: &[Variable],
         expressions: &[Expr<'_>],
@@ -158,7 +161,7 @@ impl<'a> Renderer<'a> {
         let severity = Severity::RuntimeError;
 
         let line_col_region = self.to_line_col_region(expect_region, failure_region);
-        let doc = self.render_lookups(
+        let lookups = self.render_lookups(
             subs,
             line_col_region,
             symbols,
@@ -167,6 +170,17 @@ impl<'a> Renderer<'a> {
             severity,
         );
 
+        let doc = match message {
+            Some(message) => {
+                let lines = message
+                    .lines()
+                    .map(|line| self.alloc.string(line.to_string()));
+
+                self.alloc.stack([self.alloc.vcat(lines), lookups])
+            }
+            None => lookups,
+        };
+
         let report = Report {
             title: "EXPECT FAILED".into(),
             doc,
@@ -198,9 +212,14 @@ impl<'a> Renderer<'a> {
         W: std::io::Write,
     {
         let line_col_region = self.to_line_col_region(expect_region, dbg_expr_region);
+        let (cyan, reset) = if self.render_target.has_color() {
+            ("\u{001b}[36m", "\u{001b}[0m")
+        } else {
+            ("", "")
+        };
         write!(
             writer,
-            "\u{001b}[36m[{} {}:{}] \u{001b}[0m",
+            "{cyan}[{} {}:{}] {reset}",
             self.filename.display(),
             line_col_region.start.line + 1,
             line_col_region.start.column + 1
@@ -1558,7 +1558,8 @@ fn to_dbg_or_expect_report<'a>(
         roc_parse::parser::EExpect::Dbg(_) => unreachable!("another branch would be taken"),
         roc_parse::parser::EExpect::Expect(_) => unreachable!("another branch would be taken"),
 
-        roc_parse::parser::EExpect::Condition(e_expr, condition_start) => {
+        roc_parse::parser::EExpect::Condition(e_expr, condition_start)
+        | roc_parse::parser::EExpect::Message(e_expr, condition_start) => {
             // is adding context helpful here?
             to_expr_report(alloc, lines, filename, context, e_expr, *condition_start)
         }
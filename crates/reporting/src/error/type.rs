@@ -2373,6 +2373,44 @@ fn to_pattern_report<'b>(
 ) -> Report<'b> {
     use roc_types::types::PReason;
 
+    // If it looks like a field typo in a record destructure, early return with a special report
+    // for that.
+    if category == PatternCategory::Record {
+        let expected_type = match &expected {
+            PExpected::NoExpectation(expected_type) => expected_type,
+            PExpected::ForReason(_, expected_type, _) => expected_type,
+        };
+
+        if let ErrorType::Record(expected_fields, expected_ext) =
+            expected_type.clone().unwrap_structural_alias()
+        {
+            if let ErrorType::Record(found_fields, _) = found.clone().unwrap_structural_alias() {
+                // a flex extension could have taken the field, so it is not what went wrong
+                let is_closed = !matches!(expected_ext, TypeExt::FlexOpen(_));
+
+                let field = found_fields
+                    .keys()
+                    .find(|field| is_closed && !expected_fields.contains_key(field));
+
+                if let Some(field) = field {
+                    return report_record_field_typo(
+                        alloc,
+                        lines,
+                        filename,
+                        severity,
+                        None,
+                        "",
+                        field,
+                        "",
+                        expr_region,
+                        expected_fields,
+                        expected_ext,
+                    );
+                }
+            }
+        }
+    }
+
     match expected {
         PExpected::NoExpectation(expected_type) => {
             let doc = alloc.stack([
@@ -5530,6 +5568,8 @@ fn pattern_to_doc_help<'b>(
             Decimal(d) => alloc.text(RocDec::from_ne_bytes(d).to_string()),
             Str(s) => alloc.string(s.into()),
         },
+        Range(range, _) if range.start == range.end => alloc.text(range.start.to_string()),
+        Range(range, _) => alloc.text(format!("{}..{}", range.start, range.end)),
         List(arity, patterns) => {
             let inner = match arity {
                 ListArity::Exact(_) => alloc.intersperse(
@@ -5586,7 +5626,7 @@ fn pattern_to_doc_help<'b>(
                             Anything => {
                                 arg_docs.push(alloc.text(label.to_string()));
                             }
-                            Literal(_) | Ctor(_, _, _) | List(..) => {
+                            Literal(_) | Range(..) | Ctor(_, _, _) | List(..) => {
                                 arg_docs.push(
                                     alloc
                                         .text(label.to_string())
@@ -0,0 +1,92 @@
+//! Reports as newline-delimited JSON, for editors and other tools that read diagnostics.
+//!
+//! Every report is one [Diagnostic] object on a line of its own, so a long build can be read
+//! while it is still running. The message is the report as it would be printed without colors,
+//! and its header is left out, because the file and the title are fields of their own.
+
+use roc_problem::Severity;
+use roc_region::all::{LineInfo, Region};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub file: String,
+    /// `"error"`, `"warning"`, or `"fatal"` for errors that stop compilation
+    pub severity: &'static str,
+    /// The title of the report in kebab case, e.g. `type-mismatch`
+    pub code: String,
+    pub title: String,
+    pub message: String,
+    /// The tips and hints of the message, each joined into one line
+    pub hints: Vec<String>,
+    pub span: Option<Span>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub start: Location,
+    pub end: Location,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Location {
+    /// The byte offset into the file
+    pub offset: u32,
+    /// The zero-based line
+    pub line: u32,
+    /// The zero-based column, in bytes
+    pub column: u32,
+}
+
+impl Span {
+    pub fn new(region: Region, lines: &LineInfo) -> Self {
+        let line_columns = lines.convert_region(region);
+
+        Span {
+            start: Location {
+                offset: region.start().offset,
+                line: line_columns.start.line,
+                column: line_columns.start.column,
+            },
+            end: Location {
+                offset: region.end().offset,
+                line: line_columns.end.line,
+                column: line_columns.end.column,
+            },
+        }
+    }
+}
+
+impl Diagnostic {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("a diagnostic can always be serialized")
+    }
+}
+
+pub fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Fatal => "fatal",
+        Severity::RuntimeError => "error",
+        Severity::Warning => "warning",
+    }
+}
+
+/// `TYPE MISMATCH` becomes `type-mismatch`
+pub fn code(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// The paragraphs of a rendered message that start with `Tip:` or `Hint:`
+pub fn hints(message: &str) -> Vec<String> {
+    message
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| paragraph.starts_with("Tip:") || paragraph.starts_with("Hint:"))
+        .map(|paragraph| paragraph.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect()
+}
@@ -3,6 +3,8 @@ use roc_module::ident::{Lowercase, ModuleName, TagName, Uppercase};
 use roc_module::symbol::{Interns, ModuleId, ModuleIds, PQModuleName, PackageQualified, Symbol};
 use roc_problem::Severity;
 use roc_region::all::LineColumnRegion;
+use std::ffi::OsString;
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 use std::{fmt, io};
 use ven_pretty::{text, BoxAllocator, DocAllocator, DocBuilder, Render, RenderAnnotated};
@@ -12,6 +14,8 @@ use byte_unit::Byte;
 #[cfg(not(target_family = "wasm"))]
 use roc_packaging::https::Problem;
 
+use crate::json::{self, Diagnostic, Span};
+
 pub use crate::error::canonicalize::can_problem;
 pub use crate::error::parse::parse_problem;
 pub use crate::error::r#type::type_problem;
@@ -111,11 +115,62 @@ pub fn pretty_header_with_path(title: &str, path: &Path) -> String {
     header
 }
 
+/// Whether to color terminal output with ANSI escape codes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    Always,
+    /// Color output to a terminal. Setting `NO_COLOR` turns colors off, and setting
+    /// `CLICOLOR_FORCE` turns them on even when the output is not a terminal.
+    #[default]
+    Auto,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn use_color(self) -> bool {
+        self.use_color_in(std::env::var_os, io::stdout().is_terminal())
+    }
+
+    /// Like [ColorChoice::use_color], in an environment where `var` looks up the environment
+    /// variables, and `is_terminal` says whether the output is a terminal.
+    pub fn use_color_in(
+        self,
+        var: impl Fn(&'static str) -> Option<OsString>,
+        is_terminal: bool,
+    ) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                // see https://no-color.org and https://bixense.com/clicolors
+                if var("NO_COLOR").map_or(false, |value| !value.is_empty()) {
+                    false
+                } else if var("CLICOLOR_FORCE").map_or(false, |value| {
+                    !value.is_empty() && value.to_str() != Some("0")
+                }) {
+                    true
+                } else {
+                    is_terminal
+                }
+            }
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum RenderTarget {
-    ColorTerminal,
+    ColorTerminal(ColorChoice),
     Generic,
     LanguageServer,
+    /// One line of JSON per report, see [crate::json]
+    Json,
+}
+
+impl RenderTarget {
+    /// Whether reports rendered for this target have ANSI escape codes in them
+    pub fn has_color(self) -> bool {
+        matches!(self, RenderTarget::ColorTerminal(color) if color.use_color())
+    }
 }
 
 /// A textual report.
@@ -130,17 +185,52 @@ impl<'b> Report<'b> {
     pub fn render(
         self,
         target: RenderTarget,
-        buf: &'b mut String,
+        buf: &mut String,
         alloc: &'b RocDocAllocator<'b>,
         palette: &'b Palette,
     ) {
         match target {
             RenderTarget::Generic => self.render_ci(buf, alloc),
-            RenderTarget::ColorTerminal => self.render_color_terminal(buf, alloc, palette),
+            RenderTarget::ColorTerminal(color) => {
+                let palette = if color.use_color() {
+                    palette
+                } else {
+                    &NO_COLOR_PALETTE
+                };
+
+                self.render_color_terminal(buf, alloc, palette)
+            }
             RenderTarget::LanguageServer => self.render_language_server(buf, alloc),
+            RenderTarget::Json => self.render_json(buf, None),
         }
     }
 
+    /// Render as a JSON object on one line, followed by a newline. The span is passed in, because
+    /// a report does not know which region of the file it is about.
+    pub fn render_json(self, buf: &mut String, span: Option<Span>) {
+        let err_msg = "<buffer is not a utf-8 encoded string>";
+
+        let mut message = String::new();
+
+        self.doc
+            .1
+            .render_raw(70, &mut CiWrite::new(&mut message))
+            .expect(err_msg);
+
+        let diagnostic = Diagnostic {
+            file: self.filename.to_string_lossy().into_owned(),
+            severity: json::severity_name(self.severity),
+            code: json::code(&self.title),
+            hints: json::hints(&message),
+            title: self.title,
+            message,
+            span,
+        };
+
+        buf.push_str(&diagnostic.to_json());
+        buf.push('\n');
+    }
+
     /// Render to CI console output, where no colors are available.
     pub fn render_ci(self, buf: &mut String, alloc: &'b RocDocAllocator<'b>) {
         let err_msg = "<buffer is not a utf-8 encoded string>";
@@ -1339,6 +1429,45 @@ pub fn to_https_problem_report<'b>(
                 severity: Severity::Fatal,
             }
         }
+        Problem::InvalidVendoredContentHash {
+            tarball,
+            expected,
+            actual,
+        } => {
+            let doc = alloc.stack([
+                alloc.reflow(r"I found the package for this URL in the ROC_PACKAGE_DIR:"),
+                alloc.string((&url).to_string()).annotate(Annotation::Url).indent(4),
+                alloc.reflow(r"But its tarball was changed since it was vendored:"),
+                alloc
+                    .string(tarball.display().to_string())
+                    .annotate(Annotation::PlainText)
+                    .indent(4),
+                alloc.concat([
+                    alloc.reflow(r"This is the content signature I was "),
+                    alloc.reflow(r"expecting").annotate(Annotation::Emphasized),
+                    alloc.reflow(r":"),
+                ]),
+                alloc.string(expected).annotate(Annotation::PlainText).indent(4),
+                alloc.concat([
+                    alloc.reflow(r"However, this is the content signature I "),
+                    alloc.reflow(r"obtained").annotate(Annotation::Emphasized),
+                    alloc.reflow(r":"),
+                ]),
+                alloc.string(actual).annotate(Annotation::PlainText).indent(4),
+                alloc.reflow(r"To keep you secure, I will not execute this untrusted code."),
+                alloc.concat([
+                    alloc.tip(),
+                    alloc.reflow(r"Replace the tarball with the one from the URL, or remove it so that I download the package."),
+                ]),
+            ]);
+
+            Report {
+                filename,
+                doc,
+                title: "INVALID CONTENT HASH".to_string(),
+                severity: Severity::Fatal,
+            }
+        }
         Problem::NotFound => {
             let doc = alloc.stack([
                 alloc.reflow(r"I tried to download from this URL:"),
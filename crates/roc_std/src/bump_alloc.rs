@@ -0,0 +1,324 @@
+//! A bump allocator that provides `roc_alloc`, `roc_realloc` and `roc_dealloc` for hosts that
+//! run on Solana, or anywhere else where the heap is a fixed region of memory that never needs to
+//! be returned.
+//!
+//! Enable the `bump-alloc` feature, and call [init] with the heap region before calling into Roc:
+//!
+//! ```ignore
+//! // the heap that the Solana runtime gives every program
+//! const HEAP_START: *mut u8 = 0x3_0000_0000 as *mut u8;
+//! const HEAP_LEN: usize = 32 * 1024;
+//!
+//! unsafe { roc_std::bump_alloc::init(HEAP_START, HEAP_LEN) };
+//! ```
+//!
+//! Memory is only handed back when the most recent allocation is freed, or shrunk in place. When
+//! the heap is exhausted (or [init] was never called), `roc_alloc` returns a null pointer.
+
+use core::cell::UnsafeCell;
+#[cfg(not(test))]
+use core::ffi::c_void;
+use core::ptr;
+
+/// Every allocation is aligned to at least this many bytes, because Roc stores an 8-byte
+/// refcount in front of heap-allocated values.
+pub const MIN_ALIGNMENT: usize = 8;
+
+/// A bump allocator over a region of memory.
+#[derive(Debug)]
+pub struct BumpAllocator {
+    heap: *mut u8,
+    len: usize,
+    /// Offset of the first free byte
+    next: usize,
+    /// Offset of the most recent allocation, which can still grow or shrink in place
+    last: Option<usize>,
+}
+
+impl BumpAllocator {
+    /// An allocator without any memory. Every allocation fails.
+    pub const fn empty() -> Self {
+        Self {
+            heap: ptr::null_mut(),
+            len: 0,
+            next: 0,
+            last: None,
+        }
+    }
+
+    /// # Safety
+    ///
+    /// `heap` must be valid for reads and writes of `len` bytes for as long as the allocator, and
+    /// any memory it hands out, is in use.
+    pub const unsafe fn new(heap: *mut u8, len: usize) -> Self {
+        Self {
+            heap,
+            len,
+            next: 0,
+            last: None,
+        }
+    }
+
+    /// The number of bytes that have not been handed out yet
+    pub fn remaining(&self) -> usize {
+        self.len - self.next
+    }
+
+    /// Allocate `size` bytes, aligned to `alignment` (or [MIN_ALIGNMENT], if that is larger).
+    /// Returns a null pointer if the heap does not have enough room left.
+    pub fn alloc(&mut self, size: usize, alignment: u32) -> *mut u8 {
+        let Some(offset) = self.aligned_offset(self.next, alignment) else {
+            return ptr::null_mut();
+        };
+
+        match offset.checked_add(size) {
+            Some(end) if end <= self.len => {
+                self.next = end;
+                self.last = Some(offset);
+
+                // `offset` is within the heap
+                unsafe { self.heap.add(offset) }
+            }
+            _ => ptr::null_mut(),
+        }
+    }
+
+    /// Resize an allocation to `new_size` bytes. The most recent allocation grows or shrinks in
+    /// place if it can; anything else is copied into a new allocation.
+    ///
+    /// Returns a null pointer, and leaves the old allocation alone, if the heap does not have
+    /// enough room left.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by this allocator, and `old_size` must be the size it was
+    /// allocated (or last resized) with.
+    pub unsafe fn realloc(
+        &mut self,
+        ptr: *mut u8,
+        new_size: usize,
+        old_size: usize,
+        alignment: u32,
+    ) -> *mut u8 {
+        let offset = self.offset_of(ptr);
+
+        if self.last == Some(offset) && self.aligned_offset(offset, alignment) == Some(offset) {
+            if let Some(end) = offset.checked_add(new_size) {
+                if end <= self.len {
+                    self.next = end;
+
+                    return ptr;
+                }
+            }
+        } else if new_size <= old_size {
+            // the tail of the allocation is just never reused
+            return ptr;
+        }
+
+        let new_ptr = self.alloc(new_size, alignment);
+
+        if !new_ptr.is_null() {
+            // a new allocation never overlaps an existing one
+            ptr::copy_nonoverlapping(ptr, new_ptr, old_size.min(new_size));
+        }
+
+        new_ptr
+    }
+
+    /// Free an allocation. Only the most recent allocation is actually given back.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by this allocator, and must not be used afterwards.
+    pub unsafe fn dealloc(&mut self, ptr: *mut u8) {
+        let offset = self.offset_of(ptr);
+
+        if self.last == Some(offset) {
+            self.next = offset;
+            self.last = None;
+        }
+    }
+
+    /// The first offset at or after `from` that is suitably aligned for an allocation
+    fn aligned_offset(&self, from: usize, alignment: u32) -> Option<usize> {
+        let alignment = (alignment as usize).max(MIN_ALIGNMENT);
+        debug_assert!(alignment.is_power_of_two());
+
+        let address = (self.heap as usize).checked_add(from)?;
+        let aligned = address.checked_add(alignment - 1)? & !(alignment - 1);
+
+        Some(from + (aligned - address))
+    }
+
+    fn offset_of(&self, ptr: *mut u8) -> usize {
+        ptr as usize - self.heap as usize
+    }
+}
+
+struct GlobalHeap(UnsafeCell<BumpAllocator>);
+
+// Roc programs run on a single thread, and `init` requires that nothing is allocated while it
+// runs.
+unsafe impl Sync for GlobalHeap {}
+
+static HEAP: GlobalHeap = GlobalHeap(UnsafeCell::new(BumpAllocator::empty()));
+
+/// Use `len` bytes starting at `heap` for all future `roc_alloc` calls.
+///
+/// # Safety
+///
+/// Like [BumpAllocator::new]. This must not be called while Roc code is running, and any memory
+/// that was allocated before is no longer valid afterwards.
+pub unsafe fn init(heap: *mut u8, len: usize) {
+    *HEAP.0.get() = BumpAllocator::new(heap, len);
+}
+
+/// # Safety
+///
+/// Must not be called while another call into the global heap is running.
+#[cfg(not(test))]
+unsafe fn global_heap() -> &'static mut BumpAllocator {
+    &mut *HEAP.0.get()
+}
+
+// The unit tests are linked with their own libc-based versions of the symbols below.
+
+/// # Safety
+///
+/// Like [BumpAllocator::alloc], on the heap given to [init].
+#[cfg(not(test))]
+#[no_mangle]
+pub unsafe extern "C" fn roc_alloc(size: usize, alignment: u32) -> *mut c_void {
+    global_heap().alloc(size, alignment).cast()
+}
+
+/// # Safety
+///
+/// Like [BumpAllocator::realloc], on the heap given to [init].
+#[cfg(not(test))]
+#[no_mangle]
+pub unsafe extern "C" fn roc_realloc(
+    ptr: *mut c_void,
+    new_size: usize,
+    old_size: usize,
+    alignment: u32,
+) -> *mut c_void {
+    global_heap()
+        .realloc(ptr.cast(), new_size, old_size, alignment)
+        .cast()
+}
+
+/// # Safety
+///
+/// Like [BumpAllocator::dealloc], on the heap given to [init].
+#[cfg(not(test))]
+#[no_mangle]
+pub unsafe extern "C" fn roc_dealloc(ptr: *mut c_void, _alignment: u32) {
+    global_heap().dealloc(ptr.cast())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[repr(C, align(16))]
+    struct Heap([u8; 256]);
+
+    fn with_heap(f: impl FnOnce(&mut BumpAllocator, *mut u8)) {
+        let mut heap = Heap([0; 256]);
+        let start = heap.0.as_mut_ptr();
+        let mut allocator = unsafe { BumpAllocator::new(start, heap.0.len()) };
+
+        f(&mut allocator, start);
+    }
+
+    #[test]
+    fn allocations_are_at_least_8_byte_aligned() {
+        with_heap(|allocator, start| {
+            let a = allocator.alloc(3, 1);
+            let b = allocator.alloc(5, 2);
+            let c = allocator.alloc(1, 16);
+
+            assert_eq!(a, start);
+            assert_eq!(b as usize, start as usize + 8);
+            assert_eq!(c as usize, start as usize + 16);
+            assert_eq!(allocator.remaining(), 256 - 17);
+        });
+    }
+
+    #[test]
+    fn alloc_fails_when_the_heap_is_full() {
+        with_heap(|allocator, _| {
+            assert!(!allocator.alloc(200, 8).is_null());
+            assert!(allocator.alloc(100, 8).is_null());
+            assert!(!allocator.alloc(56, 8).is_null());
+            assert!(allocator.alloc(1, 8).is_null());
+        });
+
+        let mut allocator = BumpAllocator::empty();
+        assert!(allocator.alloc(1, 8).is_null());
+    }
+
+    #[test]
+    fn realloc_grows_the_last_allocation_in_place() {
+        with_heap(|allocator, _| {
+            let a = allocator.alloc(16, 8);
+            let grown = unsafe { allocator.realloc(a, 64, 16, 8) };
+
+            assert_eq!(grown, a);
+            assert_eq!(allocator.remaining(), 256 - 64);
+
+            let shrunk = unsafe { allocator.realloc(a, 8, 64, 8) };
+
+            assert_eq!(shrunk, a);
+            assert_eq!(allocator.remaining(), 256 - 8);
+        });
+    }
+
+    #[test]
+    fn realloc_copies_an_older_allocation() {
+        with_heap(|allocator, _| {
+            let a = allocator.alloc(4, 8);
+            unsafe { ptr::copy_nonoverlapping([1u8, 2, 3, 4].as_ptr(), a, 4) };
+
+            let b = allocator.alloc(8, 8);
+            let moved = unsafe { allocator.realloc(a, 12, 4, 8) };
+
+            assert_ne!(moved, a);
+            assert!(moved > b);
+            assert_eq!(
+                unsafe { core::slice::from_raw_parts(moved, 4) },
+                [1, 2, 3, 4]
+            );
+        });
+    }
+
+    #[test]
+    fn realloc_fails_without_touching_the_old_allocation() {
+        with_heap(|allocator, _| {
+            let a = allocator.alloc(8, 8);
+            let _b = allocator.alloc(8, 8);
+
+            let moved = unsafe { allocator.realloc(a, 1024, 8, 8) };
+
+            assert!(moved.is_null());
+            assert_eq!(allocator.remaining(), 256 - 16);
+        });
+    }
+
+    #[test]
+    fn dealloc_gives_back_only_the_last_allocation() {
+        with_heap(|allocator, _| {
+            let a = allocator.alloc(8, 8);
+            let b = allocator.alloc(8, 8);
+
+            unsafe { allocator.dealloc(a) };
+            assert_eq!(allocator.remaining(), 256 - 16);
+
+            unsafe { allocator.dealloc(b) };
+            assert_eq!(allocator.remaining(), 256 - 8);
+
+            assert_eq!(allocator.alloc(8, 8), b);
+        });
+    }
+}
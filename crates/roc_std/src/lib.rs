@@ -1,17 +1,24 @@
 //! Provides Rust representations of Roc data structures.
-// #![cfg_attr(not(feature = "std"), no_std)]
+//!
+//! Without the default `std` feature, this crate is `no_std`, and does not need an allocator
+//! either: all memory is managed through `roc_alloc` and friends.
+#![cfg_attr(not(feature = "std"), no_std)]
 #![crate_type = "lib"]
 
-use arrayvec::ArrayString;
+use arrayvec::{ArrayString, ArrayVec};
 use core::cmp::Ordering;
+use core::convert::Infallible;
 use core::ffi::c_void;
 use core::fmt::{self, Debug};
 use core::hash::{Hash, Hasher};
 use core::mem::{ManuallyDrop, MaybeUninit};
 use core::ops::Drop;
 use core::str;
-use std::convert::Infallible;
 
+#[cfg(feature = "bump-alloc")]
+pub mod bump_alloc;
+#[cfg(feature = "random-seed")]
+pub mod random_seed;
 mod roc_box;
 mod roc_list;
 mod roc_str;
@@ -19,7 +26,7 @@ mod storage;
 
 pub use roc_box::RocBox;
 pub use roc_list::{ReadOnlyRocList, RocList, SendSafeRocList};
-pub use roc_str::{InteriorNulError, ReadOnlyRocStr, RocStr, SendSafeRocStr};
+pub use roc_str::{InteriorNulError, ReadOnlyRocStr, RocStr, SendSafeRocStr, Utf16Error};
 pub use storage::Storage;
 
 // A list of C functions that are being imported
@@ -187,6 +194,24 @@ impl<T, E> RocResult<T, E> {
         matches!(self.tag, RocResultTag::RocErr)
     }
 
+    /// Borrow the payload, like `Result::as_ref`.
+    pub fn as_ref(&self) -> Result<&T, &E> {
+        match self.as_result_of_refs() {
+            Ok(payload) => Ok(&**payload),
+            Err(payload) => Err(&**payload),
+        }
+    }
+
+    /// Apply `f` to an `Ok` payload. An `Err` payload is moved over as it is.
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> RocResult<U, E> {
+        Result::from(self).map(f).into()
+    }
+
+    /// Apply `f` to an `Err` payload. An `Ok` payload is moved over as it is.
+    pub fn map_err<F, O: FnOnce(E) -> F>(self, f: O) -> RocResult<T, F> {
+        Result::from(self).map_err(f).into()
+    }
+
     fn into_payload(self) -> RocResultPayload<T, E> {
         let mut value = MaybeUninit::uninit();
 
@@ -321,14 +346,18 @@ impl RocDec {
         (upper_bits, lower_bits)
     }
 
+    /// The most digits [RocDec::from_str] accepts, counting the zeros that a negative exponent
+    /// adds in front.
+    const MAX_PARSED_DIGITS: usize = 256;
+
     #[allow(clippy::should_implement_trait)]
-    pub fn from_str(value: &str) -> Option<Self> {
+    pub fn from_str(value: &str) -> Result<Self, ParseDecError> {
         let (sign, value) = match value.chars().next() {
             Some('+') => (1i128, &value[1..]),
             Some('-') => (-1i128, &value[1..]),
             _ => (1i128, value),
         };
-        let mut digits = vec![];
+        let mut digits = ArrayVec::<u32, { Self::MAX_PARSED_DIGITS }>::new();
         let mut point = None;
         let mut epow = 0;
         for (i, c) in value.char_indices() {
@@ -336,7 +365,7 @@ impl RocDec {
                 '.' => {
                     if point.is_some() {
                         // there should only be one "." in the string
-                        return None;
+                        return Err(ParseDecError::InvalidDigit);
                     } else {
                         point = Some(digits.len());
                     }
@@ -349,64 +378,181 @@ impl RocDec {
                         epow = pow;
                         break;
                     }
-                    Err(_) => return None,
+                    Err(_) => return Err(ParseDecError::InvalidDigit),
                 },
-                _ => digits.push(c.to_digit(10)?),
+                _ => {
+                    let digit = c.to_digit(10).ok_or(ParseDecError::InvalidDigit)?;
+                    digits
+                        .try_push(digit)
+                        .map_err(|_| ParseDecError::Overflow)?;
+                }
             }
         }
         if digits.is_empty() {
             // no digits parsed
-            return None;
+            return Err(ParseDecError::Empty);
         }
 
         let mut point = point.unwrap_or(digits.len());
         // eg for "1.3e2" we want a string like "130", so move point and append 0's as necessary
         while epow > 0 {
             if point == digits.len() {
-                digits.push(0);
+                digits.try_push(0).map_err(|_| ParseDecError::Overflow)?;
             }
             point += 1;
             epow -= 1;
         }
         // eg for "1e-1" we want a string like "0.1", so insert 0's as necessary
         while (point as i32) + epow < 1 {
-            digits.insert(0, 0);
+            digits
+                .try_insert(0, 0)
+                .map_err(|_| ParseDecError::Overflow)?;
             point += 1;
         }
 
         let (before_point, after_point) = digits.split_at(point);
 
-        let mut hi = 0i128;
-        for &d in before_point {
-            hi = hi.checked_mul(10)?;
-            hi = hi.checked_add(d.into())?;
-        }
-        let hi = hi.checked_mul(sign)?;
-
-        let mut lo = 0i128;
-        for &d in after_point
-            .iter()
-            // add infinite trailing 0's, then truncate by Self::DECIMAL_PLACES
-            // so eg ".123" becomes ".12300000000000000000", and ".0000000000000000000123" becomes ".00000000000000000001"
-            .chain(std::iter::repeat(&0))
-            .take(Self::DECIMAL_PLACES)
-        {
-            lo = lo.checked_mul(10)?;
-            lo = lo.checked_add(d.into())?;
-        }
-        let lo = lo.checked_mul(sign)?;
+        // the integer and fractional parts, as far as they fit
+        let parse = || {
+            let mut hi = 0i128;
+            for &d in before_point {
+                hi = hi.checked_mul(10)?;
+                hi = hi.checked_add(d.into())?;
+            }
+            let hi = hi.checked_mul(sign)?;
+
+            let mut lo = 0i128;
+            for &d in after_point
+                .iter()
+                // add infinite trailing 0's, then truncate by Self::DECIMAL_PLACES
+                // so eg ".123" becomes ".12300000000000000000", and ".0000000000000000000123" becomes ".00000000000000000001"
+                .chain(core::iter::repeat(&0))
+                .take(Self::DECIMAL_PLACES)
+            {
+                lo = lo.checked_mul(10)?;
+                lo = lo.checked_add(d.into())?;
+            }
+            let lo = lo.checked_mul(sign)?;
 
-        let num = hi.checked_mul(Self::ONE_POINT_ZERO)?.checked_add(lo)?;
+            hi.checked_mul(Self::ONE_POINT_ZERO)?.checked_add(lo)
+        };
 
-        Some(Self(num.to_ne_bytes()))
+        match parse() {
+            Some(num) => Ok(Self(num.to_ne_bytes())),
+            None => Err(ParseDecError::Overflow),
+        }
     }
 
-    /// This is private because RocDec being an i128 is an implementation detail
     #[inline(always)]
     fn as_i128(&self) -> i128 {
         i128::from_ne_bytes(self.0)
     }
 
+    /// The number times 10^18, which is how Roc stores a `Dec`.
+    pub fn to_i128(self) -> i128 {
+        self.as_i128()
+    }
+
+    /// The `Dec` whose value is `scaled` divided by 10^18, e.g. `from_i128_scaled(1)` is the
+    /// smallest positive `Dec`.
+    pub fn from_i128_scaled(scaled: i128) -> Self {
+        Self(scaled.to_ne_bytes())
+    }
+
+    /// `self + other`, or None if that overflows.
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.as_i128()
+            .checked_add(other.as_i128())
+            .map(Self::from_i128_scaled)
+    }
+
+    /// `self - other`, or None if that overflows.
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.as_i128()
+            .checked_sub(other.as_i128())
+            .map(Self::from_i128_scaled)
+    }
+
+    /// `self * other`, rounded towards zero, or None if that overflows.
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let (a, b) = (self.as_i128(), other.as_i128());
+
+        // like the builtin, `MIN` can only be multiplied by 0 and 1
+        if a == i128::MIN || b == i128::MIN {
+            return match (a, b) {
+                (0, _) | (_, 0) => Some(Self::from_i128_scaled(0)),
+                (_, Self::ONE_POINT_ZERO) => Some(self),
+                (Self::ONE_POINT_ZERO, _) => Some(other),
+                _ => None,
+            };
+        }
+
+        let product = mul_u128(a.unsigned_abs(), b.unsigned_abs());
+        let magnitude = div_u256_by_u128(product, Self::ONE_POINT_ZERO as u128)?;
+
+        Self::with_sign(magnitude, (a < 0) != (b < 0))
+    }
+
+    /// `self / other`, rounded towards zero, or None if `other` is zero or the result overflows.
+    pub fn checked_div(self, other: Self) -> Option<Self> {
+        let (a, b) = (self.as_i128(), other.as_i128());
+
+        if b == 0 {
+            return None;
+        }
+
+        if a == 0 {
+            return Some(Self::from_i128_scaled(0));
+        }
+
+        // like the builtin, `MIN` can only be divided by 1, and only 1 be divided by `MIN`
+        if a == i128::MIN {
+            return (b == Self::ONE_POINT_ZERO).then_some(self);
+        }
+
+        if b == i128::MIN {
+            return (a == Self::ONE_POINT_ZERO).then_some(other);
+        }
+
+        let numerator = mul_u128(a.unsigned_abs(), Self::ONE_POINT_ZERO as u128);
+        let magnitude = div_u256_by_u128(numerator, b.unsigned_abs())?;
+
+        Self::with_sign(magnitude, (a < 0) != (b < 0))
+    }
+
+    /// `self + other`, or [RocDec::MIN] or [RocDec::MAX] if that overflows.
+    pub fn saturating_add(self, other: Self) -> Self {
+        Self::from_i128_scaled(self.as_i128().saturating_add(other.as_i128()))
+    }
+
+    /// `self - other`, or [RocDec::MIN] or [RocDec::MAX] if that overflows.
+    pub fn saturating_sub(self, other: Self) -> Self {
+        Self::from_i128_scaled(self.as_i128().saturating_sub(other.as_i128()))
+    }
+
+    /// `self * other`, rounded towards zero, or [RocDec::MIN] or [RocDec::MAX] if that overflows.
+    pub fn saturating_mul(self, other: Self) -> Self {
+        self.checked_mul(other).unwrap_or_else(|| {
+            if (self.as_i128() < 0) != (other.as_i128() < 0) {
+                Self::MIN
+            } else {
+                Self::MAX
+            }
+        })
+    }
+
+    /// A magnitude of at most `i128::MAX` with the given sign. Like the builtins, this never
+    /// produces `MIN`, even though `-magnitude` would fit.
+    fn with_sign(magnitude: u128, is_negative: bool) -> Option<Self> {
+        let magnitude = i128::try_from(magnitude).ok()?;
+
+        Some(Self::from_i128_scaled(if is_negative {
+            -magnitude
+        } else {
+            magnitude
+        }))
+    }
+
     pub fn from_ne_bytes(bytes: [u8; 16]) -> Self {
         Self(bytes)
     }
@@ -466,6 +612,79 @@ impl RocDec {
     }
 }
 
+/// Why [RocDec::from_str] could not parse a string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseDecError {
+    /// The string has no digits
+    Empty,
+    /// The string has a character that does not belong in a decimal, or a second `.`
+    InvalidDigit,
+    /// The number is too large (or has too many digits) to fit in a `Dec`
+    Overflow,
+}
+
+impl fmt::Display for ParseDecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ParseDecError::Empty => "cannot parse a decimal from an empty string",
+            ParseDecError::InvalidDigit => "invalid digit found in decimal",
+            ParseDecError::Overflow => "decimal is too large to fit in a Dec",
+        })
+    }
+}
+
+impl str::FromStr for RocDec {
+    type Err = ParseDecError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        RocDec::from_str(value)
+    }
+}
+
+/// The full 256-bit product of `a` and `b`, as `(high, low)` halves
+fn mul_u128(a: u128, b: u128) -> (u128, u128) {
+    const LOWER: u128 = u64::MAX as u128;
+
+    let (a_hi, a_lo) = (a >> 64, a & LOWER);
+    let (b_hi, b_lo) = (b >> 64, b & LOWER);
+
+    let lo_lo = a_lo * b_lo;
+    let hi_lo = a_hi * b_lo;
+    let lo_hi = a_lo * b_hi;
+    let hi_hi = a_hi * b_hi;
+
+    // the middle column can carry into the high half, but not overflow it
+    let middle = (lo_lo >> 64) + (hi_lo & LOWER) + (lo_hi & LOWER);
+
+    let low = (middle << 64) | (lo_lo & LOWER);
+    let high = hi_hi + (hi_lo >> 64) + (lo_hi >> 64) + (middle >> 64);
+
+    (high, low)
+}
+
+/// `numerator / denominator` rounded down, or None if the quotient does not fit in a u128
+fn div_u256_by_u128((high, low): (u128, u128), denominator: u128) -> Option<u128> {
+    if high >= denominator {
+        return None;
+    }
+
+    // long division, one bit at a time; the remainder always stays below the denominator
+    let mut remainder = high;
+    let mut quotient = 0;
+
+    for bit in (0..128).rev() {
+        let carry = remainder >> 127;
+        remainder = (remainder << 1) | ((low >> bit) & 1);
+
+        if carry == 1 || remainder >= denominator {
+            remainder = remainder.wrapping_sub(denominator);
+            quotient |= 1 << bit;
+        }
+    }
+
+    Some(quotient)
+}
+
 impl From<i32> for RocDec {
     fn from(value: i32) -> Self {
         RocDec::from_ne_bytes((RocDec::ONE_POINT_ZERO * value as i128).to_ne_bytes())
@@ -0,0 +1,61 @@
+//! Provides `roc_random_seed`, the seed that the builtins hash the keys of every `Dict` with, for
+//! hosts that can get a random number when they start.
+//!
+//! Enable the `random-seed` feature, and call [init] once, before calling into Roc:
+//!
+//! ```ignore
+//! let mut seed = [0; 8];
+//! getrandom::getrandom(&mut seed).unwrap();
+//!
+//! unsafe { roc_std::random_seed::init(u64::from_ne_bytes(seed)) };
+//! ```
+//!
+//! Until [init] is called, the seed is [DEFAULT_SEED]. A host that defines no `roc_random_seed`
+//! at all gets a seed derived from the address the program is loaded at, or [DEFAULT_SEED] on
+//! SBF, where that address is always the same.
+//!
+//! The seed only decides which bucket a key lands in. Whether two dictionaries are equal does not
+//! depend on it, and neither does the order of `Dict.walk` and `Dict.to_list`, which is the order
+//! the keys were inserted in.
+
+use core::cell::UnsafeCell;
+
+/// The seed the builtins use on SBF when the host does not define `roc_random_seed`
+pub const DEFAULT_SEED: u64 = 0xa0761d6478bd642f;
+
+struct GlobalSeed(UnsafeCell<u64>);
+
+// Roc programs run on a single thread, and `init` requires that no Roc code runs while it does.
+unsafe impl Sync for GlobalSeed {}
+
+static SEED: GlobalSeed = GlobalSeed(UnsafeCell::new(DEFAULT_SEED));
+
+/// Hash the keys of all dictionaries with `seed` from now on.
+///
+/// # Safety
+///
+/// This must be called before any `Dict` is built. A dictionary does not store its seed, so one
+/// that was built before would no longer find its keys.
+pub unsafe fn init(seed: u64) {
+    *SEED.0.get() = seed;
+}
+
+/// The seed given to [init], or [DEFAULT_SEED]
+#[no_mangle]
+pub extern "C" fn roc_random_seed() -> u64 {
+    unsafe { *SEED.0.get() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn init_replaces_the_default_seed() {
+        assert_eq!(roc_random_seed(), DEFAULT_SEED);
+
+        unsafe { init(42) };
+
+        assert_eq!(roc_random_seed(), 42);
+    }
+}
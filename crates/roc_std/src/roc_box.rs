@@ -4,14 +4,13 @@ use crate::{roc_alloc, roc_dealloc, storage::Storage, RocRefcounted};
 use core::{
     cell::Cell,
     cmp::{self, Ordering},
+    ffi::c_void,
     fmt::Debug,
     mem,
     ops::Deref,
     ptr::{self, NonNull},
 };
 
-use std::os::raw::c_void;
-
 #[repr(C)]
 pub struct RocBox<T>
 where
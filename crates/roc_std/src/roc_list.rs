@@ -2,17 +2,16 @@
 
 use core::{
     cell::Cell,
-    cmp::{self, Ordering},
+    cmp::{self, max, Ordering},
     ffi::c_void,
     fmt::Debug,
     hash::Hash,
     intrinsics::copy_nonoverlapping,
     iter::FromIterator,
     mem::{self, ManuallyDrop},
-    ops::{Deref, DerefMut},
+    ops::{Deref, DerefMut, Range},
     ptr::{self, NonNull},
 };
-use std::{cmp::max, ops::Range};
 
 use crate::{
     roc_alloc, roc_dealloc, roc_realloc, storage::Storage, RocRefcounted, ROC_REFCOUNT_CONSTANT,
@@ -153,7 +152,7 @@ where
     }
 
     pub fn as_mut_ptr(&mut self) -> *mut T {
-        self.as_mut_slice().as_mut_ptr()
+        self.deref_mut().as_mut_ptr()
     }
 
     pub fn as_ptr(&self) -> *const T {
@@ -196,17 +195,33 @@ where
         self
     }
 
-    /// Note that there is no way to convert directly to a Vec.
-    ///
-    /// This is because RocList values are not allocated using the system allocator, so
-    /// handing off any heap-allocated bytes to a Vec would not work because its Drop
-    /// implementation would try to free those bytes using the wrong allocator.
+    /// The elements of the list, if it is safe to modify them in place: nothing else refers to
+    /// this list's allocation, it is not a seamless slice of another list, and it is not
+    /// readonly (like a list constant in the app).
     ///
-    /// Instead, if you want a Rust Vec, you need to do a fresh allocation and copy the
-    /// bytes over - in other words, calling this `as_slice` method and then calling `to_vec`
-    /// on that.
-    pub fn as_mut_slice(&mut self) -> &mut [T] {
-        &mut *self
+    /// Use [RocList::make_unique] to get mutable access to a list that may be shared.
+    pub fn as_mut_slice(&mut self) -> Option<&mut [T]> {
+        if self.is_uniquely_owned() {
+            Some(self.deref_mut())
+        } else {
+            None
+        }
+    }
+
+    /// Give this list an allocation of its own if it shares one (or is readonly), and return
+    /// its elements, which are then safe to modify. Like Roc, this copies a shared list.
+    pub fn make_unique(&mut self) -> &mut [T] {
+        if !self.is_uniquely_owned() {
+            self.reallocate(self.len());
+        }
+
+        self.deref_mut()
+    }
+
+    /// Iterate over the elements mutably, after making the list unique like
+    /// [RocList::make_unique] does.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.make_unique().iter_mut()
     }
 
     #[inline(always)]
@@ -258,7 +273,7 @@ where
     #[allow(unused)]
     pub(crate) fn ptr_to_refcount(&self) -> *mut usize {
         if self.is_seamless_slice() {
-            ((self.capacity_or_ref_ptr << 1) - std::mem::size_of::<usize>()) as *mut _
+            ((self.capacity_or_ref_ptr << 1) - core::mem::size_of::<usize>()) as *mut _
         } else {
             unsafe { self.ptr_to_first_elem().cast::<usize>().sub(1) as *mut _ }
         }
@@ -274,9 +289,11 @@ where
     }
 
     pub fn push(&mut self, value: T) {
-        if self.capacity() <= self.len() {
-            // reserve space for (at least!) one more element
-            self.reserve(1);
+        let required = self.len() + 1;
+
+        if self.capacity() < required || !self.is_uniquely_owned() {
+            // grow like the builtins do, so pushing n elements reallocates O(log n) times
+            self.reallocate(Self::grown_capacity(self.capacity(), required));
         }
 
         let elements = self.elements.unwrap().as_ptr();
@@ -324,59 +341,7 @@ where
             return;
         }
 
-        let new_len = self.len() + slice.len();
-        let non_null_elements = if let Some((elements, storage)) = self.elements_and_storage() {
-            // Decrement the list's refence count.
-            let mut copy = storage.get();
-            let is_unique = copy.decrease();
-
-            if is_unique {
-                // If we have enough capacity, we can add to the existing elements in-place.
-                if self.capacity() >= new_len {
-                    elements
-                } else {
-                    // There wasn't enough capacity, so we need a new allocation.
-                    // Since this is a unique RocList, we can use realloc here.
-                    let new_ptr = unsafe {
-                        roc_realloc(
-                            self.ptr_to_allocation(),
-                            Self::alloc_bytes(new_len),
-                            Self::alloc_bytes(self.capacity()),
-                            Self::alloc_alignment() as u32,
-                        )
-                    };
-
-                    self.capacity_or_ref_ptr = new_len;
-
-                    Self::elems_from_allocation(NonNull::new(new_ptr).unwrap_or_else(|| {
-                        todo!("Reallocation failed");
-                    }))
-                }
-            } else {
-                if !copy.is_readonly() {
-                    // Write the decremented reference count back.
-                    storage.set(copy);
-                }
-
-                // Allocate new memory.
-                self.capacity_or_ref_ptr = slice.len();
-                let new_elements = Self::elems_with_capacity(slice.len());
-
-                // Copy the old elements to the new allocation.
-                unsafe {
-                    copy_nonoverlapping(elements.as_ptr(), new_elements.as_ptr(), self.len());
-                }
-                // Clear the seamless slice bit since we now have clear ownership.
-                self.length = self.len();
-
-                new_elements
-            }
-        } else {
-            self.capacity_or_ref_ptr = slice.len();
-            Self::elems_with_capacity(slice.len())
-        };
-
-        self.elements = Some(non_null_elements);
+        self.reserve(slice.len());
 
         let elements = self.elements.unwrap().as_ptr();
 
@@ -415,7 +380,7 @@ where
             None
         } else {
             // increment the refcount
-            std::mem::forget(self.clone());
+            core::mem::forget(self.clone());
 
             let element_ptr = self.as_slice()[range.start..]
                 .as_ptr()
@@ -434,92 +399,116 @@ where
         }
     }
 
-    /// Increase a RocList's capacity by at least the requested number of elements (possibly more).
+    /// Make sure there is room for at least `num_elems` more elements, so that many `push`es
+    /// will not reallocate.
     ///
-    /// May return a new RocList, if the provided one was not unique.
+    /// A list that shares its allocation (with a clone, or as a seamless slice) gets a new
+    /// allocation of its own, which is what Roc does before modifying a shared list too.
     pub fn reserve(&mut self, num_elems: usize) {
         let new_len = num_elems + self.len();
-        let new_elems;
-        let old_elements_ptr;
 
-        match self.elements_and_storage() {
-            Some((elements, storage)) => {
-                if storage.get().is_unique() && !self.is_seamless_slice() {
-                    unsafe {
-                        let old_alloc = self.ptr_to_allocation();
-
-                        // Try to reallocate in-place.
-                        let new_alloc = roc_realloc(
-                            old_alloc,
-                            Self::alloc_bytes(new_len),
-                            Self::alloc_bytes(self.capacity()),
-                            Self::alloc_alignment() as u32,
-                        );
-
-                        if new_alloc == old_alloc {
-                            // We successfully reallocated in-place; we're done!
-                            return;
-                        } else {
-                            // We got back a different allocation; copy the existing elements
-                            // into it. We don't need to increment their refcounts because
-                            // The existing allocation that references to them is now gone and
-                            // no longer referencing them.
-                            new_elems = Self::elems_from_allocation(
-                                NonNull::new(new_alloc).unwrap_or_else(|| {
-                                    todo!("Reallocation failed");
-                                }),
-                            );
-                        }
+        if self.capacity() >= new_len && self.is_uniquely_owned() {
+            return;
+        }
 
-                        // Note that realloc automatically deallocates the old allocation,
-                        // so we don't need to call roc_dealloc here.
-                    }
-                } else {
-                    // Make a new allocation
-                    new_elems = Self::elems_with_capacity(new_len);
-                    old_elements_ptr = elements.as_ptr();
+        // like `List.reserve`, trust the caller with the exact number of elements
+        self.reallocate(new_len);
+    }
 
-                    unsafe {
-                        // Copy the old elements to the new allocation.
-                        copy_nonoverlapping(old_elements_ptr, new_elems.as_ptr(), self.len());
-                    }
+    /// Whether this list may write into its allocation: nobody else can see it, and it is
+    /// not a slice of a bigger list.
+    fn is_uniquely_owned(&self) -> bool {
+        match self.storage() {
+            Some(storage) => storage.is_unique() && !self.is_seamless_slice(),
+            None => true,
+        }
+    }
 
-                    // Decrease the current allocation's reference count.
-                    let mut new_storage = storage.get();
-
-                    if !new_storage.is_readonly() {
-                        let needs_dealloc = new_storage.decrease();
-
-                        if needs_dealloc {
-                            // Unlike in Drop, do *not* decrement the refcounts of all the elements!
-                            // The new allocation is referencing them, so instead of incrementing them all
-                            // all just to decrement them again here, we neither increment nor decrement them.
-                            unsafe {
-                                roc_dealloc(
-                                    self.ptr_to_allocation(),
-                                    Self::alloc_alignment() as u32,
-                                );
-                            }
-                        } else {
-                            // Write the storage back.
-                            storage.set(new_storage);
-                        }
+    /// The capacity to grow to when `requested_len` elements do not fit; mirrors
+    /// `calculateCapacity` in the builtins.
+    fn grown_capacity(old_capacity: usize, requested_len: usize) -> usize {
+        let element_width = mem::size_of::<T>();
+
+        if requested_len <= old_capacity {
+            return old_capacity;
+        }
+
+        if requested_len != old_capacity + 1 || element_width == 0 {
+            // The user is explicitly requesting n elements.
+            return requested_len;
+        }
+
+        let new_capacity = if old_capacity == 0 {
+            64 / element_width
+        } else if old_capacity < 4096 / element_width || old_capacity > 4096 * 32 / element_width {
+            old_capacity * 2
+        } else {
+            (old_capacity * 3).div_ceil(2)
+        };
+
+        new_capacity.max(requested_len)
+    }
+
+    /// Move the elements into an allocation with room for `new_capacity` elements, which must be
+    /// at least the current length. The allocation is reused only if this list owns it.
+    fn reallocate(&mut self, new_capacity: usize) {
+        let Some(elements) = self.elements else {
+            // This is an empty list, so this is the same as `with_capacity`.
+            self.update_to(Self::with_capacity(new_capacity));
+
+            return;
+        };
+
+        if self.is_uniquely_owned() {
+            unsafe {
+                // Note that realloc automatically deallocates the old allocation (or grows it in
+                // place), so we don't need to call roc_dealloc here. The elements move along
+                // with it, so their refcounts stay the same.
+                let new_alloc = roc_realloc(
+                    self.ptr_to_allocation(),
+                    Self::alloc_bytes(new_capacity),
+                    Self::alloc_bytes(self.capacity()),
+                    Self::alloc_alignment() as u32,
+                );
+
+                let new_elems =
+                    Self::elems_from_allocation(NonNull::new(new_alloc).unwrap_or_else(|| {
+                        todo!("Reallocation failed");
+                    }));
+
+                self.update_to(Self {
+                    elements: Some(new_elems),
+                    length: self.len(),
+                    capacity_or_ref_ptr: new_capacity,
+                });
+            }
+        } else {
+            // Copy the elements into a new allocation. They are now referenced from both
+            // allocations, until the old one is dropped below.
+            let new_elems = Self::elems_with_capacity(new_capacity);
+
+            unsafe {
+                copy_nonoverlapping(elements.as_ptr(), new_elems.as_ptr(), self.len());
+
+                if T::is_refcounted() {
+                    for index in 0..self.len() {
+                        (*new_elems.as_ptr().add(index)).inc();
                     }
                 }
             }
-            None => {
-                // This is an empty list, so `reserve` is the same as `with_capacity`.
-                self.update_to(Self::with_capacity(new_len));
 
-                return;
-            }
+            // The length loses its seamless slice bit, since we now have clear ownership.
+            // Dropping the old list decreases the refcount of the old allocation, which frees it
+            // (and the elements that only it references) if this list was its last user.
+            drop(mem::replace(
+                self,
+                Self {
+                    elements: Some(new_elems),
+                    length: self.len(),
+                    capacity_or_ref_ptr: new_capacity,
+                },
+            ));
         }
-
-        self.update_to(Self {
-            elements: Some(new_elems),
-            length: self.len(),
-            capacity_or_ref_ptr: new_len,
-        });
     }
 
     /// Replace self with a new version, without letting `drop` run in between.
@@ -680,10 +669,10 @@ where
 
         let ptr = self.ptr_to_refcount();
         unsafe {
-            let value = std::ptr::read(ptr);
+            let value = core::ptr::read(ptr);
             // Only safe to write to the pointer if it is not constant (0)
             if value != ROC_REFCOUNT_CONSTANT {
-                std::ptr::write(ptr, (value as isize + 1) as usize);
+                core::ptr::write(ptr, (value as isize + 1) as usize);
             }
         }
     }
@@ -812,7 +801,7 @@ where
             // If the size_hint didn't give us a max, we may need to grow. 1.5x seems to be good, based on:
             // https://archive.ph/Z2R8w and https://github.com/facebook/folly/blob/1f2706/folly/docs/FBVector.md
             if list.length == list.capacity() {
-                list.reserve(list.capacity() / 2);
+                list.reserve((list.capacity() / 2).max(1));
                 elements = list.elements.unwrap().as_ptr();
             }
 
@@ -1110,6 +1099,6 @@ mod tests {
         drop(new_x);
 
         // free the underlying memory
-        unsafe { crate::roc_dealloc(ptr, std::mem::align_of::<usize>() as u32) }
+        unsafe { crate::roc_dealloc(ptr, core::mem::align_of::<usize>() as u32) }
     }
 }
@@ -10,16 +10,16 @@ use serde::{
 use core::{
     cmp,
     convert::TryFrom,
+    ffi::CStr,
     fmt,
     hash::{self, Hash},
     mem::{self, size_of, ManuallyDrop},
-    ops::{Deref, DerefMut},
-    ptr,
+    ops::{Deref, DerefMut, Range},
+    ptr::{self, NonNull},
 };
 
 #[cfg(feature = "std")]
-use std::ffi::{CStr, CString};
-use std::{ops::Range, ptr::NonNull};
+use std::ffi::CString;
 
 use crate::{roc_realloc, RocList, RocRefcounted, ROC_REFCOUNT_CONSTANT};
 
@@ -74,7 +74,7 @@ impl RocStr {
             Self(RocStrInner { small_string })
         } else {
             let heap_allocated = RocList::from_slice(slice);
-            let big_string = unsafe { std::mem::transmute(heap_allocated) };
+            let big_string = unsafe { core::mem::transmute(heap_allocated) };
             Self(RocStrInner {
                 heap_allocated: ManuallyDrop::new(big_string),
             })
@@ -91,7 +91,7 @@ impl RocStr {
     pub unsafe fn from_raw_parts(bytes: *mut u8, len: usize, cap: usize) -> Self {
         if len <= SmallString::CAPACITY {
             unsafe {
-                let slice = std::slice::from_raw_parts(bytes, len);
+                let slice = core::slice::from_raw_parts(bytes, len);
                 let small_string = SmallString::try_from_utf8_bytes(slice).unwrap_unchecked();
                 Self(RocStrInner { small_string })
             }
@@ -106,6 +106,110 @@ impl RocStr {
         }
     }
 
+    /// Like [`String::from_utf8_lossy`](https://doc.rust-lang.org/std/string/struct.String.html#method.from_utf8_lossy):
+    /// every invalid sequence of bytes becomes U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// Short results are stored as a small string, without allocating.
+    pub fn from_utf8_lossy(bytes: &[u8]) -> Self {
+        if let Ok(string) = core::str::from_utf8(bytes) {
+            return Self::from(string);
+        }
+
+        const REPLACEMENT: &str = "\u{FFFD}";
+
+        let mut len = 0;
+        for_each_utf8_chunk(bytes, |valid, invalid| {
+            len += valid.len() + if invalid { REPLACEMENT.len() } else { 0 };
+        });
+
+        unsafe {
+            Self::from_fn(len, |dest| {
+                let mut offset = 0;
+                let mut push = |chunk: &str| {
+                    dest[offset..][..chunk.len()].copy_from_slice(chunk.as_bytes());
+                    offset += chunk.len();
+                };
+
+                for_each_utf8_chunk(bytes, |valid, invalid| {
+                    push(valid);
+
+                    if invalid {
+                        push(REPLACEMENT);
+                    }
+                });
+            })
+        }
+    }
+
+    /// Decode UTF-16, failing on the first unpaired surrogate.
+    ///
+    /// Short results are stored as a small string, without allocating.
+    pub fn from_utf16(units: &[u16]) -> Result<Self, Utf16Error> {
+        let mut len = 0;
+        let mut pos = 0;
+
+        for decoded in core::char::decode_utf16(units.iter().copied()) {
+            match decoded {
+                Ok(c) => {
+                    len += c.len_utf8();
+                    pos += c.len_utf16();
+                }
+                Err(error) => {
+                    return Err(Utf16Error {
+                        pos,
+                        unpaired_surrogate: error.unpaired_surrogate(),
+                    })
+                }
+            }
+        }
+
+        Ok(unsafe {
+            Self::from_fn(len, |dest| {
+                let mut offset = 0;
+
+                // all of the units were checked above
+                for c in core::char::decode_utf16(units.iter().copied()).flatten() {
+                    offset += c.encode_utf8(&mut dest[offset..]).len();
+                }
+            })
+        })
+    }
+
+    /// Encode the string as UTF-16.
+    #[cfg(feature = "std")]
+    pub fn to_utf16(&self) -> Vec<u16> {
+        self.encode_utf16().collect()
+    }
+
+    /// Create a string of `len` bytes, which are written by `fill`. This avoids building the
+    /// string somewhere else first, and copying it over.
+    ///
+    /// # Safety
+    ///
+    /// `fill` must overwrite the `len` bytes it is given with valid UTF-8.
+    unsafe fn from_fn(len: usize, fill: impl FnOnce(&mut [u8])) -> Self {
+        if len <= SmallString::CAPACITY {
+            let mut bytes = [0; SmallString::CAPACITY];
+            fill(&mut bytes[..len]);
+
+            unsafe { Self::from_slice_unchecked(&bytes[..len]) }
+        } else {
+            let mut big_string = BigString::with_capacity(len);
+
+            unsafe {
+                let elements = big_string.ptr_to_first_elem();
+                ptr::write_bytes(elements, 0, len);
+                fill(core::slice::from_raw_parts_mut(elements, len));
+            }
+
+            big_string.length = len;
+
+            Self(RocStrInner {
+                heap_allocated: ManuallyDrop::new(big_string),
+            })
+        }
+    }
+
     fn is_small_str(&self) -> bool {
         unsafe { self.0.small_string.is_small_str() }
     }
@@ -214,7 +318,7 @@ impl RocStr {
                 let mut big_string = BigString::with_capacity(target_cap);
 
                 unsafe {
-                    std::ptr::copy_nonoverlapping(
+                    core::ptr::copy_nonoverlapping(
                         self.as_bytes().as_ptr(),
                         big_string.ptr_to_first_elem(),
                         self.len(),
@@ -265,7 +369,7 @@ impl RocStr {
             Some(RocStr(RocStrInner { small_string }))
         } else {
             // increment the refcount
-            std::mem::forget(self.clone());
+            core::mem::forget(self.clone());
 
             let big = unsafe { &self.0.heap_allocated };
             let ptr = unsafe { (self.as_bytes().as_ptr() as *mut u8).add(range.start) };
@@ -391,7 +495,7 @@ impl RocStr {
                             let elem_ptr = big_string.ptr_to_first_elem();
 
                             // memcpy the bytes into the stack allocation
-                            std::ptr::copy_nonoverlapping(elem_ptr, alloc_ptr, len);
+                            core::ptr::copy_nonoverlapping(elem_ptr, alloc_ptr, len);
 
                             terminate(alloc_ptr, len)
                         })
@@ -456,7 +560,7 @@ impl RocStr {
         self.with_terminator(terminator, |dest_ptr: *mut u16, str_slice: &str| {
             // Translate UTF-8 source bytes into UTF-16 and write them into the destination.
             for (index, wchar) in str_slice.encode_utf16().enumerate() {
-                unsafe { std::ptr::write_unaligned(dest_ptr.add(index), wchar) };
+                unsafe { core::ptr::write_unaligned(dest_ptr.add(index), wchar) };
             }
 
             func(dest_ptr, str_slice.len())
@@ -541,7 +645,7 @@ impl RocStr {
         use core::mem::align_of;
 
         let terminate = |alloc_ptr: *mut E, str_slice: &str| unsafe {
-            std::ptr::write_unaligned(alloc_ptr.add(str_slice.len()), terminator);
+            core::ptr::write_unaligned(alloc_ptr.add(str_slice.len()), terminator);
 
             func(alloc_ptr, str_slice)
         };
@@ -621,7 +725,7 @@ impl RocStr {
     }
 }
 
-pub struct SplitWhitespace<'a>(std::iter::Peekable<std::str::CharIndices<'a>>, &'a RocStr);
+pub struct SplitWhitespace<'a>(core::iter::Peekable<core::str::CharIndices<'a>>, &'a RocStr);
 
 impl Iterator for SplitWhitespace<'_> {
     type Item = RocStr;
@@ -665,7 +769,6 @@ impl Deref for RocStr {
 }
 
 /// This can fail because a CStr may contain invalid UTF-8 characters
-#[cfg(feature = "std")]
 impl TryFrom<&CStr> for RocStr {
     type Error = core::str::Utf8Error;
 
@@ -693,6 +796,38 @@ pub struct InteriorNulError {
     pub roc_str: RocStr,
 }
 
+/// Like https://doc.rust-lang.org/std/string/struct.FromUtf16Error.html, but
+/// also says where the unpaired surrogate is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Utf16Error {
+    /// The index of the unpaired surrogate, in UTF-16 code units
+    pub pos: usize,
+    pub unpaired_surrogate: u16,
+}
+
+/// Call `f` with every run of valid UTF-8 in `bytes`, and whether an invalid sequence follows it.
+/// An incomplete sequence at the end counts as one invalid sequence, like in
+/// `String::from_utf8_lossy`.
+fn for_each_utf8_chunk(mut bytes: &[u8], mut f: impl FnMut(&str, bool)) {
+    loop {
+        match core::str::from_utf8(bytes) {
+            Ok(valid) => {
+                f(valid, false);
+                return;
+            }
+            Err(error) => {
+                let (valid, rest) = bytes.split_at(error.valid_up_to());
+                f(unsafe { core::str::from_utf8_unchecked(valid) }, true);
+
+                match error.error_len() {
+                    Some(invalid_len) => bytes = &rest[invalid_len..],
+                    None => return,
+                }
+            }
+        }
+    }
+}
+
 impl Default for RocStr {
     fn default() -> Self {
         Self::empty()
@@ -910,11 +1045,11 @@ impl BigString {
     }
 
     fn as_bytes(&self) -> &[u8] {
-        unsafe { std::slice::from_raw_parts(self.ptr_to_first_elem(), self.len()) }
+        unsafe { core::slice::from_raw_parts(self.ptr_to_first_elem(), self.len()) }
     }
 
     fn as_str(&self) -> &str {
-        unsafe { std::str::from_utf8_unchecked(self.as_bytes()) }
+        unsafe { core::str::from_utf8_unchecked(self.as_bytes()) }
     }
 
     fn is_unique(&self) -> bool {
@@ -923,7 +1058,7 @@ impl BigString {
         }
 
         let ptr = self.ptr_to_refcount();
-        let rc = unsafe { std::ptr::read(ptr) as isize };
+        let rc = unsafe { core::ptr::read(ptr) as isize };
 
         rc == 1
     }
@@ -934,7 +1069,7 @@ impl BigString {
         }
 
         let ptr = self.ptr_to_refcount();
-        let rc = unsafe { std::ptr::read(ptr) as isize };
+        let rc = unsafe { core::ptr::read(ptr) as isize };
 
         rc == 0
     }
@@ -944,18 +1079,18 @@ impl BigString {
 
         let ptr = self.ptr_to_refcount();
         // Only safe to write to the pointer if it is not constant (0)
-        if unsafe { std::ptr::read(ptr) } != ROC_REFCOUNT_CONSTANT {
-            unsafe { std::ptr::write(ptr, ROC_REFCOUNT_CONSTANT) }
+        if unsafe { core::ptr::read(ptr) } != ROC_REFCOUNT_CONSTANT {
+            unsafe { core::ptr::write(ptr, ROC_REFCOUNT_CONSTANT) }
         }
     }
 
     fn inc(&mut self) {
         let ptr = self.ptr_to_refcount();
         unsafe {
-            let value = std::ptr::read(ptr);
+            let value = core::ptr::read(ptr);
             // Only safe to write to the pointer if it is not constant (0)
             if value != ROC_REFCOUNT_CONSTANT {
-                std::ptr::write(ptr, (value as isize + 1) as usize);
+                core::ptr::write(ptr, (value as isize + 1) as usize);
             }
         }
     }
@@ -968,7 +1103,7 @@ impl BigString {
 
         let ptr = self.ptr_to_refcount();
         unsafe {
-            let value = std::ptr::read(ptr) as isize;
+            let value = core::ptr::read(ptr) as isize;
             match value {
                 0 => {
                     // static lifetime, do nothing
@@ -978,7 +1113,7 @@ impl BigString {
                     crate::roc_dealloc(self.ptr_to_allocation().cast(), 1);
                 }
                 _ => {
-                    std::ptr::write(ptr, (value - 1) as usize);
+                    core::ptr::write(ptr, (value - 1) as usize);
                 }
             }
         }
@@ -1000,7 +1135,7 @@ impl BigString {
     ///
     /// May return a new BigString, if the provided one was not unique.
     fn reserve(&mut self, n: usize) {
-        let align = std::mem::size_of::<usize>();
+        let align = core::mem::size_of::<usize>();
         let desired_cap = self.len() + n;
         let desired_alloc = align + desired_cap;
 
@@ -1026,15 +1161,19 @@ impl BigString {
                 capacity_or_alloc_ptr: desired_cap,
             };
 
-            std::mem::swap(&mut this, self);
-            std::mem::forget(this);
+            core::mem::swap(&mut this, self);
+            core::mem::forget(this);
         } else {
             let ptr = unsafe { crate::roc_alloc(desired_alloc, align as _) } as *mut u8;
             let elements = unsafe { NonNull::new_unchecked(ptr.cast::<u8>().add(align)) };
 
             unsafe {
                 // Copy the old elements to the new allocation.
-                std::ptr::copy_nonoverlapping(self.ptr_to_first_elem(), ptr.add(align), self.len());
+                core::ptr::copy_nonoverlapping(
+                    self.ptr_to_first_elem(),
+                    ptr.add(align),
+                    self.len(),
+                );
             }
 
             let mut this = Self {
@@ -1043,8 +1182,8 @@ impl BigString {
                 capacity_or_alloc_ptr: desired_cap,
             };
 
-            std::mem::swap(&mut this, self);
-            std::mem::drop(this);
+            core::mem::swap(&mut this, self);
+            core::mem::drop(this);
         }
     }
 }
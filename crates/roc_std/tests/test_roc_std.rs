@@ -10,11 +10,13 @@ use core::ffi::c_void;
 
 const ROC_SMALL_STR_CAPACITY: usize = core::mem::size_of::<roc_std::RocStr>() - 1;
 
+#[cfg(not(feature = "bump-alloc"))]
 #[no_mangle]
 pub unsafe extern "C" fn roc_alloc(size: usize, _alignment: u32) -> *mut c_void {
     libc::malloc(size)
 }
 
+#[cfg(not(feature = "bump-alloc"))]
 #[no_mangle]
 pub unsafe extern "C" fn roc_realloc(
     c_ptr: *mut c_void,
@@ -25,6 +27,7 @@ pub unsafe extern "C" fn roc_realloc(
     libc::realloc(c_ptr, new_size)
 }
 
+#[cfg(not(feature = "bump-alloc"))]
 #[no_mangle]
 pub unsafe extern "C" fn roc_dealloc(c_ptr: *mut c_void, _alignment: u32) {
     libc::free(c_ptr)
@@ -54,7 +57,9 @@ pub unsafe extern "C" fn roc_memset(dst: *mut c_void, c: i32, n: usize) -> *mut
 
 #[cfg(test)]
 mod test_roc_std {
-    use roc_std::{RocBox, RocDec, RocList, RocResult, RocStr, SendSafeRocStr};
+    use roc_std::{
+        ParseDecError, RocBox, RocDec, RocList, RocResult, RocStr, SendSafeRocStr, Utf16Error,
+    };
 
     fn roc_str_byte_representation(string: &RocStr) -> [u8; RocStr::SIZE] {
         unsafe { core::mem::transmute_copy(string) }
@@ -133,6 +138,86 @@ mod test_roc_std {
         assert_eq!(roc_str.capacity() >= 5000, true);
     }
 
+    fn is_small_str(string: &RocStr) -> bool {
+        roc_str_byte_representation(string)[RocStr::SIZE - 1] & RocStr::MASK != 0
+    }
+
+    #[test]
+    fn from_utf8_lossy_valid() {
+        let small = RocStr::from_utf8_lossy("héllo".as_bytes());
+        assert_eq!(small.as_str(), "héllo");
+        assert!(is_small_str(&small));
+
+        let big = RocStr::from_utf8_lossy("this one does not fit in a small string".as_bytes());
+        assert_eq!(big.as_str(), "this one does not fit in a small string");
+        assert!(!is_small_str(&big));
+    }
+
+    #[test]
+    fn from_utf8_lossy_small_str() {
+        let bytes = b"ab\xFFcd\xE2\x82";
+        let string = RocStr::from_utf8_lossy(bytes);
+
+        assert_eq!(string.as_str(), String::from_utf8_lossy(bytes));
+        assert_eq!(string.as_str(), "ab\u{FFFD}cd\u{FFFD}");
+        assert!(is_small_str(&string));
+    }
+
+    #[test]
+    fn from_utf8_lossy_big_str() {
+        let mut bytes = Vec::new();
+        for _ in 0..10 {
+            bytes.extend_from_slice(b"valid \xC0\x80 and \xF0\x9F\x98 again ");
+        }
+
+        let string = RocStr::from_utf8_lossy(&bytes);
+
+        assert_eq!(string.as_str(), String::from_utf8_lossy(&bytes));
+        assert!(!is_small_str(&string));
+    }
+
+    #[test]
+    fn from_utf16_roundtrip() {
+        let small = "h€llo 😀";
+        let units: Vec<u16> = small.encode_utf16().collect();
+        let string = RocStr::from_utf16(&units).unwrap();
+
+        assert_eq!(string.as_str(), small);
+        assert!(is_small_str(&string));
+        assert_eq!(string.to_utf16(), units);
+
+        let big = "a string with 😀 that is too long to be small";
+        let units: Vec<u16> = big.encode_utf16().collect();
+        let string = RocStr::from_utf16(&units).unwrap();
+
+        assert_eq!(string.as_str(), big);
+        assert!(!is_small_str(&string));
+        assert_eq!(string.to_utf16(), units);
+    }
+
+    #[test]
+    fn from_utf16_unpaired_surrogate() {
+        // the emoji is one surrogate pair, so the lone surrogate is the fourth unit
+        let mut units: Vec<u16> = "a😀b".encode_utf16().collect();
+        units.insert(3, 0xD800);
+
+        assert_eq!(
+            RocStr::from_utf16(&units),
+            Err(Utf16Error {
+                pos: 3,
+                unpaired_surrogate: 0xD800,
+            })
+        );
+
+        assert_eq!(
+            RocStr::from_utf16(&[0xDC00]),
+            Err(Utf16Error {
+                pos: 0,
+                unpaired_surrogate: 0xDC00,
+            })
+        );
+    }
+
     #[test]
     #[cfg(feature = "serde")]
     fn str_short_serde_roundtrip() {
@@ -174,6 +259,156 @@ mod test_roc_std {
         assert_eq!(roc_list.capacity(), 5000);
     }
 
+    #[test]
+    fn reserve_keeps_enough_capacity() {
+        let mut roc_list = RocList::<u64>::with_capacity(10);
+        let elements = roc_list.as_ptr();
+
+        roc_list.reserve(10);
+
+        assert_eq!(roc_list.capacity(), 10);
+        assert_eq!(roc_list.as_ptr(), elements);
+    }
+
+    #[quickcheck_macros::quickcheck]
+    fn push_reallocates_logarithmically(len: u16) -> bool {
+        let mut roc_list = RocList::<u64>::empty();
+        let mut reallocations = 0;
+
+        for elem in 0..len as u64 {
+            let capacity = roc_list.capacity();
+            roc_list.push(elem);
+
+            if roc_list.capacity() != capacity {
+                reallocations += 1;
+            }
+        }
+
+        let log2_len = u16::BITS - len.leading_zeros();
+
+        roc_list.iter().copied().eq(0..len as u64) && reallocations <= 2 * log2_len
+    }
+
+    #[test]
+    fn push_to_clone_copies_on_write() {
+        let long_str = RocStr::from("a string that is much too long to be small");
+
+        let mut roc_list = RocList::with_capacity(4);
+        roc_list.push(long_str.clone());
+
+        let clone = roc_list.clone();
+        roc_list.push(RocStr::from("another string"));
+
+        assert_eq!(clone.as_slice(), core::slice::from_ref(&long_str));
+        assert_eq!(roc_list.as_slice(), &[long_str, "another string".into()]);
+        assert_ne!(clone.as_ptr(), roc_list.as_ptr());
+        assert!(clone.is_unique());
+        assert!(roc_list.is_unique());
+
+        drop(clone);
+
+        assert_eq!(
+            roc_list[0].as_str(),
+            "a string that is much too long to be small"
+        );
+    }
+
+    #[test]
+    fn reserve_on_shared_list_copies_on_write() {
+        let mut roc_list = RocList::from_slice(&[1u64, 2, 3]);
+        let clone = roc_list.clone();
+
+        roc_list.reserve(100);
+        roc_list.push(4);
+
+        assert_eq!(clone.as_slice(), &[1, 2, 3]);
+        assert_eq!(roc_list.as_slice(), &[1, 2, 3, 4]);
+        assert!(roc_list.capacity() >= 103);
+        assert_eq!(clone.capacity(), 3);
+    }
+
+    #[test]
+    fn push_to_seamless_slice_copies_on_write() {
+        let roc_list = RocList::from_slice(&[1u64, 2, 3, 4]);
+        let mut slice = roc_list.slice_range(1..3);
+
+        slice.push(5);
+
+        assert_eq!(roc_list.as_slice(), &[1, 2, 3, 4]);
+        assert_eq!(slice.as_slice(), &[2, 3, 5]);
+        assert!(!slice.is_seamless_slice());
+    }
+
+    #[test]
+    fn as_mut_slice_on_unique_list() {
+        let mut roc_list = RocList::from_slice(&[1u8, 2, 3]);
+
+        roc_list.as_mut_slice().unwrap()[0] = 9;
+
+        assert_eq!(roc_list.as_slice(), &[9, 2, 3]);
+        assert_eq!(RocList::<u8>::empty().as_mut_slice(), Some(&mut [][..]));
+    }
+
+    #[test]
+    fn make_unique_copies_shared_list() {
+        let mut roc_list = RocList::from_slice(&[1u8, 2, 3]);
+        let clone = roc_list.clone();
+
+        assert_eq!(roc_list.as_mut_slice(), None);
+
+        roc_list.make_unique()[0] = 9;
+
+        assert_eq!(roc_list.as_slice(), &[9, 2, 3]);
+        assert_eq!(clone.as_slice(), &[1, 2, 3]);
+        assert!(roc_list.is_unique());
+        assert!(clone.is_unique());
+    }
+
+    #[test]
+    fn make_unique_copies_seamless_slice() {
+        let roc_list = RocList::from_slice(&[1u8, 2, 3, 4]);
+        let mut slice = roc_list.slice_range(1..3);
+
+        assert_eq!(slice.as_mut_slice(), None);
+
+        slice.make_unique()[0] = 9;
+
+        assert_eq!(slice.as_slice(), &[9, 3]);
+        assert_eq!(roc_list.as_slice(), &[1, 2, 3, 4]);
+        assert!(!slice.is_seamless_slice());
+    }
+
+    #[test]
+    fn make_unique_copies_readonly_list() {
+        let mut readonly = RocList::from_slice(&[1u8, 2, 3]);
+        // readonly values are never freed, so this one is leaked
+        unsafe { readonly.set_readonly() };
+
+        let mut roc_list = readonly.clone();
+
+        assert_eq!(roc_list.as_mut_slice(), None);
+
+        roc_list.make_unique()[0] = 9;
+
+        assert_eq!(roc_list.as_slice(), &[9, 2, 3]);
+        assert_eq!(readonly.as_slice(), &[1, 2, 3]);
+        assert!(!roc_list.is_readonly());
+    }
+
+    #[test]
+    fn iter_mut_on_shared_list() {
+        let strings = [RocStr::from("a string that is much too long to be small")];
+        let mut roc_list = RocList::from_slice(&strings);
+        let clone = roc_list.clone();
+
+        for string in roc_list.iter_mut() {
+            *string = RocStr::from("another string");
+        }
+
+        assert_eq!(roc_list.as_slice(), &["another string".into()]);
+        assert_eq!(clone.as_slice(), &strings);
+    }
+
     #[test]
     #[cfg(feature = "serde")]
     fn short_list_roundtrip() {
@@ -267,6 +502,108 @@ mod test_roc_std {
         assert!(roc_result.is_err());
     }
 
+    fn long_str(contents: &str) -> RocStr {
+        let string = RocStr::from(format!("{contents}, which is too long to be small").as_str());
+        assert!(string.is_unique());
+
+        string
+    }
+
+    #[test]
+    fn roc_result_into_result_moves_refcounted_payloads() {
+        let ok = long_str("ok");
+        let roc_result: RocResult<RocStr, RocList<u8>> = RocResult::ok(ok.clone());
+        assert!(!ok.is_unique());
+
+        let result: Result<RocStr, RocList<u8>> = roc_result.into();
+        let payload = result.unwrap();
+        assert_eq!(payload, ok);
+        drop(payload);
+        assert!(ok.is_unique());
+
+        let err = RocList::from_slice(&[1u8, 2, 3]);
+        let roc_result: RocResult<RocStr, RocList<u8>> = RocResult::err(err.clone());
+        assert!(!err.is_unique());
+
+        let result: Result<RocStr, RocList<u8>> = roc_result.into();
+        drop(result);
+        assert!(err.is_unique());
+    }
+
+    #[test]
+    fn result_into_roc_result_moves_refcounted_payloads() {
+        let ok = long_str("ok");
+        let roc_result: RocResult<RocStr, RocStr> = Ok(ok.clone()).into();
+        assert_eq!(roc_result.as_ref(), Ok(&ok));
+        drop(roc_result);
+        assert!(ok.is_unique());
+
+        let err = long_str("err");
+        let roc_result: RocResult<RocStr, RocStr> = Err(err.clone()).into();
+        assert_eq!(roc_result.as_ref(), Err(&err));
+        drop(roc_result);
+        assert!(err.is_unique());
+    }
+
+    #[test]
+    fn roc_result_map() {
+        let ok = long_str("ok");
+        let err = long_str("err");
+
+        let roc_result: RocResult<RocStr, RocStr> = RocResult::ok(ok.clone());
+        let mapped = roc_result.map(|string| string.len());
+        assert_eq!(mapped, RocResult::ok(ok.len()));
+        assert!(ok.is_unique());
+
+        let roc_result: RocResult<RocStr, RocStr> = RocResult::err(err.clone());
+        let mapped = roc_result.map(|string| string.len());
+        assert_eq!(mapped.as_ref(), Err(&err));
+        drop(mapped);
+        assert!(err.is_unique());
+
+        let roc_result: RocResult<RocStr, RocStr> = RocResult::err(err.clone());
+        let mapped = roc_result.map_err(|string| RocList::from_slice(string.as_bytes()));
+        assert_eq!(mapped.as_ref().unwrap_err().as_slice(), err.as_bytes());
+        drop(mapped);
+        assert!(err.is_unique());
+
+        let roc_result: RocResult<RocStr, RocStr> = RocResult::ok(ok.clone());
+        let mapped = roc_result.map_err(|string| string.len());
+        assert_eq!(mapped.as_ref(), Ok(&ok));
+        drop(mapped);
+        assert!(ok.is_unique());
+    }
+
+    #[test]
+    fn roc_result_layout() {
+        use core::mem::{align_of, size_of};
+
+        // like a two-tag union in mono: the payload, then the tag id after the largest payload
+        assert_eq!(
+            size_of::<RocResult<RocStr, RocList<u8>>>(),
+            4 * size_of::<usize>()
+        );
+        assert_eq!(size_of::<RocResult<u8, u16>>(), 4);
+        assert_eq!(align_of::<RocResult<u8, u16>>(), 2);
+
+        // a zero-sized payload takes no space
+        assert_eq!(size_of::<RocResult<(), RocStr>>(), 4 * size_of::<usize>());
+        assert_eq!(size_of::<RocResult<(), ()>>(), 1);
+
+        let tag_of = |bytes: &[u8]| bytes[bytes.len() - 1];
+        let ok: RocResult<u8, u16> = RocResult::ok(7);
+        let err: RocResult<u8, u16> = RocResult::err(7);
+        // `Err` sorts before `Ok`, so it has tag id 0
+        assert_eq!(
+            tag_of(&unsafe { core::mem::transmute::<RocResult<u8, u16>, [u8; 4]>(ok) }),
+            1
+        );
+        assert_eq!(
+            tag_of(&unsafe { core::mem::transmute::<RocResult<u8, u16>, [u8; 4]>(err) }),
+            0
+        );
+    }
+
     #[test]
     fn create_roc_box() {
         let contents = 42i32;
@@ -336,6 +673,93 @@ mod test_roc_std {
         assert_eq!(RocDec::from(0), RocDec::default());
     }
 
+    fn dec(string: &str) -> RocDec {
+        RocDec::from_str(string).unwrap()
+    }
+
+    #[test]
+    fn roc_dec_from_str_errors() {
+        assert_eq!(RocDec::from_str(""), Err(ParseDecError::Empty));
+        assert_eq!(RocDec::from_str("-"), Err(ParseDecError::Empty));
+        assert_eq!(RocDec::from_str("1.2.3"), Err(ParseDecError::InvalidDigit));
+        assert_eq!(RocDec::from_str("12a"), Err(ParseDecError::InvalidDigit));
+        assert_eq!(RocDec::from_str("1ex"), Err(ParseDecError::InvalidDigit));
+        assert_eq!(
+            RocDec::from_str("170141183460469231732"),
+            Err(ParseDecError::Overflow)
+        );
+        assert_eq!(RocDec::from_str("1e300"), Err(ParseDecError::Overflow));
+
+        assert_eq!("2.5".parse::<RocDec>(), Ok(dec("2.5")));
+    }
+
+    #[test]
+    fn roc_dec_scaled_i128() {
+        assert_eq!(dec("1.5").to_i128(), 1_500_000_000_000_000_000);
+        assert_eq!(RocDec::from_i128_scaled(1), dec("0.000000000000000001"));
+        assert_eq!(RocDec::from_i128_scaled(i128::MIN), RocDec::MIN);
+    }
+
+    #[test]
+    fn roc_dec_checked_add_sub() {
+        assert_eq!(dec("1.5").checked_add(dec("2.25")), Some(dec("3.75")));
+        assert_eq!(RocDec::MAX.checked_add(dec("0.000000000000000001")), None);
+        assert_eq!(RocDec::MIN.checked_sub(dec("0.000000000000000001")), None);
+        assert_eq!(dec("1").checked_sub(dec("2.5")), Some(dec("-1.5")));
+    }
+
+    #[test]
+    fn roc_dec_checked_mul() {
+        assert_eq!(dec("1.5").checked_mul(dec("-2.5")), Some(dec("-3.75")));
+        assert_eq!(
+            dec("0.000000001").checked_mul(dec("0.0000000009")),
+            Some(dec("0"))
+        );
+        // rounds towards zero
+        assert_eq!(
+            dec("-0.000000001").checked_mul(dec("0.0000000019")),
+            Some(dec("-0.000000000000000001"))
+        );
+        assert_eq!(
+            dec("85070591730234615865").checked_mul(dec("2")),
+            Some(dec("170141183460469231730"))
+        );
+        assert_eq!(dec("85070591730234615866").checked_mul(dec("2")), None);
+
+        assert_eq!(RocDec::MIN.checked_mul(dec("1")), Some(RocDec::MIN));
+        assert_eq!(RocDec::MIN.checked_mul(dec("0")), Some(dec("0")));
+        assert_eq!(RocDec::MIN.checked_mul(dec("0.5")), None);
+    }
+
+    #[test]
+    fn roc_dec_checked_div() {
+        assert_eq!(
+            dec("10").checked_div(dec("3")),
+            Some(dec("3.333333333333333333"))
+        );
+        assert_eq!(
+            dec("-2").checked_div(dec("3")),
+            Some(dec("-0.666666666666666666"))
+        );
+        assert_eq!(dec("1").checked_div(dec("0")), None);
+        assert_eq!(RocDec::MAX.checked_div(dec("0.5")), None);
+        assert_eq!(RocDec::MIN.checked_div(dec("1")), Some(RocDec::MIN));
+    }
+
+    #[test]
+    fn roc_dec_saturating() {
+        let tiny = dec("0.000000000000000001");
+
+        assert_eq!(RocDec::MAX.saturating_add(tiny), RocDec::MAX);
+        assert_eq!(RocDec::MIN.saturating_sub(tiny), RocDec::MIN);
+        assert_eq!(dec("1").saturating_sub(tiny), dec("0.999999999999999999"));
+
+        assert_eq!(RocDec::MAX.saturating_mul(dec("2")), RocDec::MAX);
+        assert_eq!(RocDec::MAX.saturating_mul(dec("-2")), RocDec::MIN);
+        assert_eq!(RocDec::MIN.saturating_mul(dec("-2")), RocDec::MAX);
+        assert_eq!(dec("3").saturating_mul(dec("0.5")), dec("1.5"));
+    }
+
     #[test]
     fn safe_send_no_copy() {
         let x = RocStr::from("This is a long string but still unique. Yay!!!");
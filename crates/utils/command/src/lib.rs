@@ -96,6 +96,56 @@ pub fn zig() -> Command {
     }
 }
 
+/// Where `solana-install` (or `agave-install`) puts the LLVM tools of the Solana platform tools
+const SBF_PLATFORM_TOOLS_DIRS: [&str; 2] = [
+    ".local/share/solana/install/active_release/bin/platform-tools-sdk/sbf/dependencies/platform-tools/llvm/bin",
+    ".local/share/solana/install/active_release/bin/sdk/sbf/dependencies/platform-tools/llvm/bin",
+];
+
+/// The LLVM tool `name` of the Solana platform tools, which understand the SBF target. Uses the
+/// directory in `ROC_SBF_TOOLS` if it is set, then the installed platform tools, then `PATH`.
+fn sbf_platform_tool(name: &str) -> String {
+    if let Ok(dir) = std::env::var("ROC_SBF_TOOLS") {
+        return PathBuf::from(dir).join(name).display().to_string();
+    }
+
+    if let Ok(home) = std::env::var("HOME") {
+        for dir in SBF_PLATFORM_TOOLS_DIRS {
+            let path = PathBuf::from(&home).join(dir).join(name);
+
+            if path.exists() {
+                return path.display().to_string();
+            }
+        }
+    }
+
+    name.into()
+}
+
+/// Gives a friendly error if the Solana platform tools are not installed.
+/// The sbf target is linked with their `ld.lld`, since it knows the SBF relocations.
+pub fn sbf_lld() -> Command {
+    let command_str = sbf_platform_tool("ld.lld");
+
+    if check_command_available(&command_str) {
+        Command::new(command_str)
+    } else {
+        panic!("I could not find the ld.lld of the Solana platform tools.\nPlease install them, see instructions at https://solana.com/docs/intro/installation, or set ROC_SBF_TOOLS to the directory that contains their ld.lld.",)
+    }
+}
+
+/// Gives a friendly error if the Solana platform tools are not installed.
+/// Like [clang], but with the clang of the platform tools, which can compile a host for sbf.
+pub fn sbf_clang() -> Command {
+    let command_str = sbf_platform_tool("clang");
+
+    if check_command_available(&command_str) {
+        Command::new(command_str)
+    } else {
+        panic!("I could not find the clang of the Solana platform tools.\nPlease install them, see instructions at https://solana.com/docs/intro/installation, or set ROC_SBF_TOOLS to the directory that contains their clang.",)
+    }
+}
+
 fn check_command_available(command_name: &str) -> bool {
     if cfg!(target_family = "unix") {
         let unparsed_path = match std::env::var("PATH") {
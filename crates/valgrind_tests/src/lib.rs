@@ -61,16 +61,25 @@ fn build_host() {
 }
 
 fn valgrind_test(source: &str) {
+    valgrind_test_help(source, false)
+}
+
+/// Allocates the tags of recursive unions from pools, and tells Valgrind about them
+fn valgrind_test_pooled(source: &str) {
+    valgrind_test_help(source, true)
+}
+
+fn valgrind_test_help(source: &str, pool_alloc_with_valgrind: bool) {
     copy_zig_glue::initialize_zig_test_platforms();
 
     #[cfg(target_os = "linux")]
     {
-        valgrind_test_linux(source)
+        valgrind_test_linux(source, pool_alloc_with_valgrind)
     }
 
     #[cfg(not(target_os = "linux"))]
     {
-        let _ = source;
+        let _ = (source, pool_alloc_with_valgrind);
     }
 }
 
@@ -97,7 +106,7 @@ fn get_platform_main_roc_path() -> std::path::PathBuf {
 }
 
 #[cfg(target_os = "linux")]
-fn valgrind_test_linux(source: &str) {
+fn valgrind_test_linux(source: &str, pool_alloc_with_valgrind: bool) {
     use roc_build::program::BuiltFile;
 
     // the host is identical for all tests so we only want to build it once
@@ -150,6 +159,7 @@ fn valgrind_test_linux(source: &str) {
         &app_module_path,
         &app_module_source,
         build_host_requested,
+        pool_alloc_with_valgrind,
     );
 
     match res_binary_path {
@@ -158,6 +168,8 @@ fn valgrind_test_linux(source: &str) {
             problems,
             total_time: _,
             expect_metadata: _,
+            module_paths: _,
+            platform_main_roc_path: _,
         }) => {
             if problems.exit_code() != 0 {
                 panic!("there are problems")
@@ -580,3 +592,66 @@ fn joinpoint_that_owns() {
         "#
     ));
 }
+
+#[test]
+fn lists_and_strings_dropped_together() {
+    valgrind_test(indoc!(
+        r#"
+        (
+            words = List.repeat 7u64 100
+            shared = List.repeat 8u64 100
+            kept = [shared, shared]
+            name = Str.concat "a string that is stored " "on the heap"
+            title = Str.repeat "-" 40
+
+            total = List.len words + List.len shared + List.len kept + Str.count_utf8_bytes name + Str.count_utf8_bytes title
+
+            Num.to_str total
+        )
+        "#
+    ));
+}
+
+#[test]
+fn pooled_tree_is_not_lost() {
+    valgrind_test_pooled(indoc!(
+        r#"
+        app "test"
+            packages { pf: "replace_me_platform_path" }
+            imports []
+            provides [main] to pf
+
+        Tree : [Leaf, Node Tree I64 Tree]
+
+        insert : Tree, I64 -> Tree
+        insert = \tree, value ->
+            when tree is
+                Leaf -> Node Leaf value Leaf
+                Node left x right ->
+                    if value < x then
+                        Node (insert left value) x right
+                    else
+                        Node left x (insert right value)
+
+        sum : Tree -> I64
+        sum = \tree ->
+            when tree is
+                Leaf -> 0
+                Node left x right -> sum left + x + sum right
+
+        build : Tree, I64 -> Tree
+        build = \tree, n ->
+            if n == 0 then
+                tree
+            else
+                build (insert tree ((n * 37) % 101)) (n - 1)
+
+        # the first tree is freed before the second one takes its slots
+        main =
+            first = sum (build Leaf 100)
+            second = sum (build Leaf 50)
+
+            Num.to_str (first + second)
+        "#
+    ));
+}
@@ -464,10 +464,10 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
                 let skipped_op = OpCode::from(module.code.bytes[self.program_counter]);
                 OpCode::skip_bytes(&module.code.bytes, &mut self.program_counter).unwrap();
                 match skipped_op {
-                    BLOCK | LOOP | IF => {
+                    BLOCK | LOOP | IF | TRY => {
                         depth += 1;
                     }
-                    END => {
+                    END | DELEGATE => {
                         depth -= 1;
                         if depth == target_block_depth {
                             break;
@@ -661,10 +661,10 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
                             OpCode::skip_bytes(&module.code.bytes, &mut self.program_counter)
                                 .unwrap();
                             match skipped_op {
-                                BLOCK | LOOP | IF => {
+                                BLOCK | LOOP | IF | TRY => {
                                     depth += 1;
                                 }
-                                END => {
+                                END | DELEGATE => {
                                     if depth == target_depth {
                                         // `if` without `else`
                                         self.blocks.pop();
@@ -695,6 +695,9 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
                 // We don't want to execute the ELSE block, so we skip it, just like `br 0` would.
                 self.do_break(0, module);
             }
+            TRY | CATCH | THROW | RETHROW | DELEGATE | CATCHALL => {
+                return Err(Error::UnsupportedOp(op_code));
+            }
             END => {
                 if self.blocks.len() == (self.current_frame.body_block_index + 1) {
                     // implicit RETURN at end of function
@@ -10,6 +10,7 @@ pub mod wasi;
 pub use instance::Instance;
 pub use wasi::{WasiDispatcher, WasiFile};
 
+use roc_wasm_module::opcodes::OpCode;
 pub use roc_wasm_module::Value;
 use roc_wasm_module::ValueType;
 
@@ -68,6 +69,7 @@ pub(crate) enum Error {
     StackEmpty,
     MemoryAccessOutOfBounds(u32, u32),
     UnreachableOp,
+    UnsupportedOp(OpCode),
 }
 
 impl Error {
@@ -92,6 +94,11 @@ impl Error {
             Error::UnreachableOp => {
                 format!("WebAssembly `unreachable` instruction at file offset {file_offset:#x}.\n")
             }
+            Error::UnsupportedOp(op_code) => {
+                format!(
+                    "ERROR: I found a {op_code:?} instruction at file offset {file_offset:#x}, but I don't support exception handling yet.\n"
+                )
+            }
         }
     }
 }
@@ -786,6 +786,27 @@ fn test_call_indirect_help(table_index: u32, elem_index: u32) -> Value {
     inst.call_export(start_fn_name, []).unwrap().unwrap()
 }
 
+#[test]
+#[should_panic(expected = "I don't support exception handling yet")]
+fn test_throw_unsupported() {
+    let arena = Bump::new();
+    let mut module = WasmModule::new(&arena);
+
+    let signature0 = Signature {
+        param_types: bumpalo::vec![in &arena],
+        ret_type: None,
+    };
+    create_exported_function_no_locals(&mut module, "test", signature0, |buf| {
+        buf.push(OpCode::THROW as u8);
+        buf.encode_u32(0); // tag index
+        buf.push(OpCode::END as u8);
+    });
+
+    let mut inst =
+        Instance::for_module(&arena, &module, DefaultImportDispatcher::default(), false).unwrap();
+    inst.call_export("test", []).unwrap();
+}
+
 // #[test]
 // fn test_drop() {}
 
@@ -19,7 +19,7 @@ use self::parse::{Parse, ParseError};
 use self::sections::{
     CodeSection, DataSection, ElementSection, ExportSection, FunctionSection, GlobalSection,
     ImportDesc, ImportSection, MemorySection, NameSection, OpaqueSection, Section, SectionId,
-    TableSection, TypeSection,
+    TableSection, TagSection, TypeSection,
 };
 pub use self::serialize::{SerialBuffer, Serialize};
 
@@ -35,6 +35,7 @@ pub struct WasmModule<'a> {
     pub function: FunctionSection<'a>,
     pub table: TableSection,
     pub memory: MemorySection<'a>,
+    pub tag: TagSection<'a>,
     pub global: GlobalSection<'a>,
     pub export: ExportSection<'a>,
     pub start: OpaqueSection<'a>,
@@ -57,6 +58,7 @@ impl<'a> WasmModule<'a> {
             function: FunctionSection::new(arena),
             table: TableSection::new(),
             memory: MemorySection::new(arena, 0),
+            tag: TagSection::new(arena),
             global: GlobalSection::new(arena),
             export: ExportSection::new(arena),
             start: OpaqueSection::new(),
@@ -89,6 +91,7 @@ impl<'a> WasmModule<'a> {
             self.table.serialize(buffer);
         }
         self.memory.serialize(buffer);
+        self.tag.serialize(buffer);
         self.global.serialize(buffer);
         self.export.serialize(buffer);
         self.start.serialize(buffer);
@@ -106,6 +109,7 @@ impl<'a> WasmModule<'a> {
             + self.function.size()
             + self.table.size()
             + self.memory.size()
+            + self.tag.size()
             + self.global.size()
             + self.export.size()
             + self.start.size()
@@ -137,6 +141,7 @@ impl<'a> WasmModule<'a> {
         let function = FunctionSection::parse(arena, bytes, &mut cursor)?;
         let table = TableSection::parse((), bytes, &mut cursor)?;
         let memory = MemorySection::parse(arena, bytes, &mut cursor)?;
+        let tag = TagSection::parse(arena, bytes, &mut cursor)?;
         let global = GlobalSection::parse(arena, bytes, &mut cursor)?;
         let export = ExportSection::parse(arena, bytes, &mut cursor)?;
         let start = OpaqueSection::parse((arena, SectionId::Start), bytes, &mut cursor)?;
@@ -220,6 +225,7 @@ impl<'a> WasmModule<'a> {
             function,
             table,
             memory,
+            tag,
             global,
             export,
             start,
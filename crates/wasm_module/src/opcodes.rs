@@ -12,6 +12,10 @@ pub enum OpCode {
     LOOP = 0x03,
     IF = 0x04,
     ELSE = 0x05,
+    TRY = 0x06,
+    CATCH = 0x07,
+    THROW = 0x08,
+    RETHROW = 0x09,
     END = 0x0b,
     BR = 0x0c,
     BRIF = 0x0d,
@@ -19,6 +23,8 @@ pub enum OpCode {
     RETURN = 0x0f,
     CALL = 0x10,
     CALLINDIRECT = 0x11,
+    DELEGATE = 0x18,
+    CATCHALL = 0x19,
     DROP = 0x1a,
     SELECT = 0x1b,
     GETLOCAL = 0x20,
@@ -204,6 +210,10 @@ pub const LOOKUP_TABLE: [Option<OpCode>; 256] = {
     result[0x03] = Some(LOOP);
     result[0x04] = Some(IF);
     result[0x05] = Some(ELSE);
+    result[0x06] = Some(TRY);
+    result[0x07] = Some(CATCH);
+    result[0x08] = Some(THROW);
+    result[0x09] = Some(RETHROW);
     result[0x0b] = Some(END);
     result[0x0c] = Some(BR);
     result[0x0d] = Some(BRIF);
@@ -211,6 +221,8 @@ pub const LOOKUP_TABLE: [Option<OpCode>; 256] = {
     result[0x0f] = Some(RETURN);
     result[0x10] = Some(CALL);
     result[0x11] = Some(CALLINDIRECT);
+    result[0x18] = Some(DELEGATE);
+    result[0x19] = Some(CATCHALL);
     result[0x1a] = Some(DROP);
     result[0x1b] = Some(SELECT);
     result[0x20] = Some(GETLOCAL);
@@ -444,8 +456,10 @@ fn immediates_for(op: OpCode) -> Result<OpImmediates, String> {
     let imm = match op {
         UNREACHABLE => NoImmediate,
         NOP => NoImmediate,
-        BLOCK | LOOP | IF => Byte1,
+        BLOCK | LOOP | IF | TRY => Byte1,
         ELSE => NoImmediate,
+        CATCH | THROW | RETHROW | DELEGATE => Leb32x1,
+        CATCHALL => NoImmediate,
         END => NoImmediate,
         BR | BRIF => Leb32x1,
         BRTABLE => BrTable,
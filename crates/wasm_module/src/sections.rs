@@ -37,6 +37,9 @@ pub enum SectionId {
     /// DataCount section is unused. Only needed for single-pass validation of
     /// memory.init and data.drop, which we don't use
     DataCount = 12,
+    /// Tag section, from the exception handling proposal. It goes between the Memory and Global
+    /// sections, despite its ID.
+    Tag = 13,
 }
 
 impl Debug for SectionId {
@@ -55,6 +58,7 @@ impl Debug for SectionId {
             Self::Code => write!(f, "Code"),
             Self::Data => write!(f, "Data"),
             Self::DataCount => write!(f, "DataCount"),
+            Self::Tag => write!(f, "Tag"),
             #[allow(unreachable_patterns)]
             unknown => write!(f, "<unknown section ID 0x{:2x}>", *unknown as u8),
         }
@@ -858,6 +862,41 @@ impl<'a> MemorySection<'a> {
 
 section_impl!(MemorySection, SectionId::Memory);
 
+/*******************************************************************
+ *
+ * Tag section
+ *
+ *******************************************************************/
+
+#[derive(Debug)]
+pub struct TagSection<'a> {
+    pub count: u32,
+    pub bytes: Vec<'a, u8>,
+}
+
+impl<'a> TagSection<'a> {
+    /// The only kind of tag there is so far
+    const ATTRIBUTE_EXCEPTION: u8 = 0;
+
+    pub fn new(arena: &'a Bump) -> Self {
+        TagSection {
+            count: 0,
+            bytes: Vec::new_in(arena),
+        }
+    }
+
+    /// Add an exception tag whose values have the params of a function signature,
+    /// and return its index
+    pub fn append(&mut self, signature_index: u32) -> u32 {
+        self.bytes.push(Self::ATTRIBUTE_EXCEPTION);
+        self.bytes.encode_u32(signature_index);
+        self.count += 1;
+        self.count - 1
+    }
+}
+
+section_impl!(TagSection, SectionId::Tag);
+
 /*******************************************************************
  *
  * Global section
@@ -1095,6 +1134,7 @@ pub enum ExportType {
     Table = 1,
     Mem = 2,
     Global = 3,
+    Tag = 4,
 }
 
 impl From<u8> for ExportType {
@@ -1104,6 +1144,7 @@ impl From<u8> for ExportType {
             1 => Self::Table,
             2 => Self::Mem,
             3 => Self::Global,
+            4 => Self::Tag,
             _ => internal_error!("invalid ExportType {:2x?}", x),
         }
     }